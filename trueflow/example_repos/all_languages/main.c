@@ -0,0 +1,20 @@
+#include <stdio.h>
+
+#define GREETING "hello"
+
+struct counter {
+    int value;
+};
+
+typedef struct counter counter_t;
+
+static void bump(counter_t *c) {
+    c->value += 1;
+}
+
+int main(void) {
+    counter_t c = {0};
+    bump(&c);
+    printf("%s %d\n", GREETING, c.value);
+    return 0;
+}
@@ -0,0 +1,24 @@
+#include <iostream>
+#include <string>
+
+namespace demo {
+
+class Greeter {
+public:
+    explicit Greeter(std::string name) : name_(std::move(name)) {}
+    void greet() const;
+
+private:
+    std::string name_;
+};
+
+void Greeter::greet() const {
+    std::cout << "hello " << name_ << "\n";
+}
+
+}  // namespace demo
+
+int main() {
+    demo::Greeter("world").greet();
+    return 0;
+}
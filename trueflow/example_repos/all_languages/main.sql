@@ -0,0 +1,12 @@
+-- Accounts and their balances.
+CREATE TABLE accounts (
+    id INTEGER PRIMARY KEY,
+    owner TEXT NOT NULL,
+    balance INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE INDEX accounts_owner ON accounts (owner);
+
+ALTER TABLE accounts ADD COLUMN note TEXT DEFAULT 'n/a; pending';
+
+SELECT owner, balance FROM accounts WHERE balance > 0;
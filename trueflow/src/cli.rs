@@ -1,6 +1,11 @@
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
+use std::path::PathBuf;
 
-use crate::logging::LoggingMode;
+use trueflow_core::color::ColorChoice;
+use trueflow_core::logging::LoggingMode;
+use trueflow_core::output::{CheckFormat, FeedbackFormat, OutputFormat, StructuredFormat};
+use trueflow_core::schemas::SchemaName;
+use trueflow_core::store::Identity;
 
 #[derive(Parser)]
 #[command(name = "trueflow")]
@@ -20,6 +25,86 @@ pub struct Cli {
         hide = true
     )]
     pub logging_mode: LoggingMode,
+
+    /// Also write JSON logs to this file (rotated by size, see [logging])
+    #[arg(long, env = "TRUEFLOW_LOG_FILE", global = true)]
+    pub log_file: Option<PathBuf>,
+
+    /// Don't pipe long text output through $PAGER
+    #[arg(long, global = true)]
+    pub no_pager: bool,
+
+    /// Colorize text output (honors NO_COLOR in auto mode)
+    #[arg(long, value_enum, default_value_t = ColorChoice::Auto, global = true)]
+    pub color: ColorChoice,
+
+    /// Branch or ref to diff against instead of main (default: `[vcs] base_branch`)
+    #[arg(long, value_name = "REF", env = "TRUEFLOW_BASE", global = true)]
+    pub base: Option<String>,
+
+    /// Scan files inside submodules instead of reviewing each as its commit
+    #[arg(long, global = true)]
+    pub recurse_submodules: bool,
+
+    /// Files to parse in parallel (default: `[scan] jobs`, else one per CPU)
+    #[arg(
+        long,
+        short = 'j',
+        value_name = "N",
+        env = "TRUEFLOW_JOBS",
+        global = true
+    )]
+    pub jobs: Option<usize>,
+
+    /// Print results (and errors) as json, yaml, or toml; overrides --json and --format
+    #[arg(long, value_enum, value_name = "FORMAT", global = true)]
+    pub output: Option<StructuredFormat>,
+
+    /// Override a config key for this run, e.g. `-c policy.require_human=true` (repeatable)
+    #[arg(long = "config", short = 'c', value_name = "KEY=VALUE", global = true)]
+    pub config_overrides: Vec<String>,
+
+    #[command(flatten)]
+    pub agent: AgentArgs,
+}
+
+/// Record as an automated reviewer instead of the git user.
+#[derive(Args, Debug, Clone, Default)]
+pub struct AgentArgs {
+    /// Act as the named agent (e.g. "code-simplifier")
+    #[arg(long = "agent", env = "TRUEFLOW_AGENT", global = true)]
+    pub name: Option<String>,
+
+    /// Model behind the agent
+    #[arg(long = "agent-model", env = "TRUEFLOW_AGENT_MODEL", global = true)]
+    pub model: Option<String>,
+
+    /// Agent version
+    #[arg(long = "agent-version", env = "TRUEFLOW_AGENT_VERSION", global = true)]
+    pub version: Option<String>,
+}
+
+impl AgentArgs {
+    /// The agent identity selected by `--agent`, if any.
+    pub fn identity(&self) -> Option<Identity> {
+        let name = self
+            .name
+            .as_deref()
+            .map(str::trim)
+            .filter(|name| !name.is_empty())?;
+        let non_empty = |value: &Option<String>| {
+            value
+                .as_deref()
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(str::to_string)
+        };
+        Some(Identity::Agent {
+            name: name.to_string(),
+            model: non_empty(&self.model),
+            version: non_empty(&self.version),
+        })
+    }
 }
 
 #[derive(Subcommand)]
@@ -29,16 +114,29 @@ pub enum Commands {
         /// Output format (default is text, use --json for machine parsing)
         #[arg(long)]
         json: bool,
+
+        /// List added, removed, and modified blocks with their fingerprints instead of hunks
+        #[arg(long)]
+        blocks: bool,
     },
     /// Mark a hunk with a verdict
     Mark {
         /// Content-based fingerprint of the hunk
-        #[arg(long)]
-        fingerprint: String,
+        #[arg(long, required_unless_present_any = ["path", "kind", "tag", "stdin", "reply_to"])]
+        fingerprint: Option<String>,
 
-        /// Verdict: approved, rejected, question, comment
-        #[arg(long, default_value = "approved")]
-        verdict: String,
+        /// Reply to the record with this id (or unique prefix), on that record's block
+        #[arg(long = "reply-to", value_name = "ID", conflicts_with_all = ["stdin", "kind", "tag"])]
+        reply_to: Option<String>,
+
+        /// Read entries ({fingerprint, verdict, check, note, ...}) as a JSON array or JSON lines
+        /// from stdin and append them in one write; missing fields fall back to the flags
+        #[arg(long, conflicts_with_all = ["fingerprint", "kind", "tag"])]
+        stdin: bool,
+
+        /// Verdict: approved, rejected, question, comment [default: approved, or comment with --reply-to]
+        #[arg(long)]
+        verdict: Option<String>,
 
         /// Check type: review, security, style, etc.
         #[arg(long, default_value = "review")]
@@ -48,22 +146,110 @@ pub enum Commands {
         #[arg(long)]
         note: Option<String>,
 
-        /// Path hint for debugging/UI
+        /// Path hint for debugging/UI. Without --fingerprint, mark every block under this file or directory
         #[arg(long)]
         path: Option<String>,
 
+        /// Without --fingerprint, only mark blocks of this kind (e.g. "import"); repeatable
+        #[arg(long, conflicts_with = "fingerprint")]
+        kind: Vec<String>,
+
+        /// Without --fingerprint, only mark blocks with this tag (e.g. "test", "generated"); repeatable
+        #[arg(long, conflicts_with = "fingerprint")]
+        tag: Vec<String>,
+
         /// Line number hint
         #[arg(long)]
         line: Option<u32>,
 
+        /// Confidence in the verdict, from 0 to 1
+        #[arg(long)]
+        confidence: Option<f64>,
+
+        /// Tool that produced the verdict
+        #[arg(long, env = "TRUEFLOW_TOOL")]
+        tool: Option<String>,
+
+        /// Session id of the run that produced the verdict
+        #[arg(long, env = "TRUEFLOW_SESSION")]
+        session: Option<String>,
+
+        /// Reference to the prompt behind the verdict (path, URL, or id)
+        #[arg(long = "prompt-ref")]
+        prompt_ref: Option<String>,
+
+        /// External reference (issue URL or key, e.g. JIRA-123); repeatable
+        #[arg(long = "ref")]
+        refs: Vec<String>,
+
+        /// Sign the record with git's user.signingkey (GPG, or SSH when gpg.format=ssh)
+        #[arg(long)]
+        sign: bool,
+
         /// Suppress output for UI usage
         #[arg(long)]
         quiet: bool,
     },
-    /// Sync reviews with remote (fetch & push trueflow-db branch)
-    Sync,
+    /// Sync reviews with remote (fetch, merge & push trueflow-db branch)
+    Sync {
+        /// Remote to fetch from and push to
+        #[arg(long, default_value = "origin")]
+        remote: String,
+
+        /// Report what would be pulled and pushed without writing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Merge three versions of reviews.jsonl; git runs this as a merge driver
+    MergeDriver {
+        /// Common ancestor (git's %O)
+        #[arg(required_unless_present = "install")]
+        ancestor: Option<PathBuf>,
+
+        /// Our version (git's %A); the merged ledger is written here
+        #[arg(required_unless_present = "install")]
+        ours: Option<PathBuf>,
+
+        /// Their version (git's %B)
+        #[arg(required_unless_present = "install")]
+        theirs: Option<PathBuf>,
+
+        /// Register the driver in git config and .gitattributes instead
+        #[arg(long, conflicts_with_all = ["ancestor", "ours", "theirs"])]
+        install: bool,
+    },
+    /// Post review notes on unreviewed changes to a pull or merge request
+    Publish {
+        /// Pull request (or GitLab merge request) number
+        #[arg(long)]
+        pr: u64,
+
+        /// Print the comments instead of posting them
+        #[arg(long)]
+        dry_run: bool,
+    },
     /// CI gate check
-    Check,
+    Check {
+        /// Only count this reviewer's verdicts (email, or agent:<name>)
+        #[arg(long, value_name = "EMAIL")]
+        reviewer: Option<String>,
+
+        /// Distinct approvers each change needs; overrides `[policy] required_approvals`
+        #[arg(long, value_name = "N", conflicts_with = "reviewer")]
+        required_approvals: Option<usize>,
+
+        /// Gate these changes instead of main..HEAD: rev:`<sha>`, rev:`<start>..<end>` (trees work too) or staged
+        #[arg(long, value_name = "TARGET")]
+        target: Option<String>,
+
+        /// Report format: text, json, yaml, toml, junit, or github (Actions annotations)
+        #[arg(long, value_enum, default_value_t = CheckFormat::Text)]
+        format: CheckFormat,
+
+        /// Alias for --format json
+        #[arg(long, conflicts_with = "format")]
+        json: bool,
+    },
     /// Scan the directory and build the Merkle tree (Audit mode)
     Scan {
         /// Output JSON
@@ -73,18 +259,27 @@ pub enum Commands {
         /// Output the full Merkle tree
         #[arg(long)]
         tree: bool,
+
+        /// Count recent commits in each block's risk score (blames every
+        /// file)
+        #[arg(long)]
+        churn: bool,
     },
     /// Interactive review of unreviewed blocks
     Review {
-        /// Output format (default is text, use --json for machine parsing)
-        #[arg(long)]
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+
+        /// Alias for --format json
+        #[arg(long, conflicts_with = "format")]
         json: bool,
 
         /// Review everything (Audit mode), ignoring git status
         #[arg(long)]
         all: bool,
 
-        /// Review targets (file:`<path>`, rev:`<sha>`, rev:`<start>..<end>`)
+        /// Review targets (staged, base, file:`<path>`, dir:`<path>`, glob:`<pattern>`, rev:`<sha>`, rev:`<start>..<end>`)
         #[arg(long, value_name = "TARGET")]
         target: Vec<String>,
 
@@ -95,17 +290,263 @@ pub enum Commands {
         /// Exclude block types (e.g. "gap", "comment", "whitespace")
         #[arg(long)]
         exclude: Vec<String>,
+
+        /// Only show blocks assigned to you (git user.email)
+        #[arg(long)]
+        mine: bool,
+
+        /// One line per file (block count and kinds) plus totals
+        #[arg(long)]
+        summary: bool,
+
+        /// Show at most N blocks
+        #[arg(long, value_name = "N")]
+        limit: Option<usize>,
+
+        /// Skip the first N blocks of the queue
+        #[arg(long, value_name = "N", default_value_t = 0)]
+        offset: usize,
+
+        /// Show blocks from at most N files
+        #[arg(long, value_name = "N")]
+        max_files: Option<usize>,
+
+        /// Only blocks that became unreviewed since the last TUI session ended
+        #[arg(long)]
+        since_last: bool,
+
+        /// Print only the number of unreviewed blocks; exits 1 when it is not zero
+        #[arg(long, conflicts_with_all = ["summary", "json", "format"])]
+        count: bool,
+
+        /// Show the queue of this reviewer (email, or agent:<name>): blocks they have not approved
+        #[arg(long, value_name = "EMAIL")]
+        reviewer: Option<String>,
+
+        /// Review lane for this check (e.g. security): blocks of paths with the matching [tags] entry, judged by verdicts recorded under it
+        #[arg(long, value_name = "CHECK")]
+        check: Option<String>,
+    },
+    /// Approve unreviewed blocks that match the [auto_approve] rules, under a machine identity
+    AutoApprove {
+        /// Consider everything unreviewed, ignoring git status
+        #[arg(long)]
+        all: bool,
+
+        /// Review targets (staged, base, file:`<path>`, dir:`<path>`, glob:`<pattern>`, rev:`<sha>`, rev:`<start>..<end>`)
+        #[arg(long, value_name = "TARGET")]
+        target: Vec<String>,
+
+        /// List the blocks and the rule each matches without recording anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Ask the [prereview] annotator about unreviewed blocks and record its hints as comments
+    Prereview {
+        /// Consider everything unreviewed, ignoring git status
+        #[arg(long)]
+        all: bool,
+
+        /// Review targets (staged, base, file:`<path>`, dir:`<path>`, glob:`<pattern>`, rev:`<sha>`, rev:`<start>..<end>`)
+        #[arg(long, value_name = "TARGET")]
+        target: Vec<String>,
+
+        /// Annotate blocks again even if they already have an annotation
+        #[arg(long)]
+        force: bool,
+
+        /// List the blocks that would be sent without running the annotator
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Assign a block or path to a reviewer
+    Assign {
+        /// Block fingerprint, or a file/directory path
+        #[arg(
+            required_unless_present_any = ["path", "fingerprint"],
+            conflicts_with_all = ["path", "fingerprint"]
+        )]
+        target: Option<String>,
+
+        /// File or directory; assigns every block below it, including ones added later
+        #[arg(long, conflicts_with = "fingerprint")]
+        path: Option<String>,
+
+        /// Block fingerprint
+        #[arg(long)]
+        fingerprint: Option<String>,
+
+        /// Reviewer email
+        #[arg(long)]
+        to: String,
+
+        /// Optional note
+        #[arg(long)]
+        note: Option<String>,
+    },
+    /// Request review of unreviewed files from CODEOWNERS or explicit reviewers
+    Request {
+        /// Request review of everything unreviewed, ignoring git status
+        #[arg(long)]
+        all: bool,
+
+        /// Review targets (staged, base, file:`<path>`, dir:`<path>`, glob:`<pattern>`, rev:`<sha>`, rev:`<start>..<end>`)
+        #[arg(long, value_name = "TARGET")]
+        target: Vec<String>,
+
+        /// Reviewer email (repeatable). Defaults to CODEOWNERS owners.
+        #[arg(long = "reviewer", value_name = "EMAIL")]
+        reviewers: Vec<String>,
+
+        /// Optional note
+        #[arg(long)]
+        note: Option<String>,
+    },
+    /// List review requests
+    Requests {
+        /// Output JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Only requests where you are a reviewer
+        #[arg(long)]
+        mine: bool,
+
+        /// Include requests with nothing left to review
+        #[arg(long)]
+        all: bool,
+    },
+    /// Summarize review activity per reviewer
+    Activity {
+        /// Start of the window: age (`7d`, `12h`, `2w`), date (`2024-05-01`), or unix time
+        #[arg(long)]
+        since: Option<String>,
+
+        /// End of the window (same formats as --since)
+        #[arg(long)]
+        until: Option<String>,
+
+        /// Output JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Review coverage per directory and language
+    Stats {
+        /// Output JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Directory levels to group by
+        #[arg(long, value_name = "N", default_value_t = 1)]
+        depth: usize,
+
+        /// Also write a coverage badge (SVG, or Shields endpoint JSON for `.json` paths)
+        #[arg(long, value_name = "PATH")]
+        badge: Option<PathBuf>,
+    },
+    /// Record current review debt in .trueflow/metrics.jsonl for `report burndown`
+    Snapshot {
+        /// Directory levels to group by
+        #[arg(long, value_name = "N", default_value_t = 1)]
+        depth: usize,
+
+        /// Output JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Report on review progress
+    Report {
+        #[command(subcommand)]
+        action: ReportAction,
+    },
+    /// Query the review ledger
+    History {
+        /// Fingerprint, or a prefix of one
+        #[arg(long)]
+        fingerprint: Option<String>,
+
+        /// File path, or a directory to match everything below it
+        #[arg(long)]
+        path: Option<String>,
+
+        /// Only records since: age (`7d`, `12h`, `2w`), date (`2024-05-01`), or unix time
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Only records before (same formats as --since)
+        #[arg(long)]
+        until: Option<String>,
+
+        /// Verdict: approved, rejected, question, comment
+        #[arg(long)]
+        verdict: Option<String>,
+
+        /// Reviewer type: email or oidc (people), agent, or machine (rules such as auto-approve)
+        #[arg(long, value_name = "TYPE", value_parser = ["email", "oidc", "agent", "machine"])]
+        identity_type: Option<String>,
+
+        /// Output JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// List open questions
+    Questions {
+        /// File path, or a directory to match everything below it
+        #[arg(long)]
+        path: Option<String>,
+
+        /// Only questions asked by this identity (email, or agent:<name>)
+        #[arg(long)]
+        author: Option<String>,
+
+        /// Output JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Answer a question, closing it
+    Answer {
+        /// Question record id, or a unique prefix of one
+        #[arg(long)]
+        id: String,
+
+        /// The reply
+        #[arg(long)]
+        note: String,
+    },
+    /// Mark a verdict's feedback as dealt with, hiding it from `feedback`; closes questions unanswered
+    Resolve {
+        /// Verdict record id, or a unique prefix of one
+        #[arg(long)]
+        id: String,
+
+        /// Optional note, e.g. "fixed in abc123"
+        #[arg(long)]
+        note: Option<String>,
+    },
+    /// Show the discussion on a block: notes, replies, answers and resolutions
+    Thread {
+        /// Fingerprint, or a unique prefix of one
+        #[arg(long)]
+        fingerprint: String,
+
+        /// Output JSON
+        #[arg(long)]
+        json: bool,
     },
     /// Export feedback for LLM/Agent consumption
     Feedback {
-        /// Output format (xml or json)
-        #[arg(long, default_value = "xml")]
-        format: String,
+        /// Output format: xml, json, yaml, toml, or markdown (a prompt for coding agents)
+        #[arg(long, value_enum, default_value_t = FeedbackFormat::Xml)]
+        format: FeedbackFormat,
 
         /// Include approved blocks (for few-shot examples)
         #[arg(long)]
         include_approved: bool,
 
+        /// Include verdicts marked resolved (or answered)
+        #[arg(long)]
+        include_resolved: bool,
+
         /// Only include block types
         #[arg(long)]
         only: Vec<String>,
@@ -113,6 +554,22 @@ pub enum Commands {
         /// Exclude block types
         #[arg(long)]
         exclude: Vec<String>,
+
+        /// Only reviews by this identity (email, or agent:<name>)
+        #[arg(long)]
+        author: Option<String>,
+
+        /// Only verdicts under this check (e.g. security)
+        #[arg(long)]
+        check: Option<String>,
+
+        /// Only reviews since: age (`7d`), date (`2024-05-01`), unix time, or a revision
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Only blocks whose latest verdict is this (repeatable): approved, rejected, question, comment
+        #[arg(long)]
+        verdict: Vec<String>,
     },
     /// Inspect a block (and optionally split it)
     Inspect {
@@ -124,7 +581,8 @@ pub enum Commands {
         #[arg(long)]
         split: bool,
     },
-    /// Verify record attestations
+    /// Check records against the schema, their signatures, and the tree; exits 1
+    /// on malformed, duplicated or badly signed records
     Verify {
         /// Verify all records
         #[arg(long)]
@@ -133,7 +591,191 @@ pub enum Commands {
         /// Verify a specific record id
         #[arg(long)]
         id: Option<String>,
+
+        /// Also fail (exit 2) when verdicts review code no longer in the tree
+        #[arg(long)]
+        strict: bool,
+    },
+    /// Write review records to a bundle for machines without a shared remote
+    Export {
+        /// Only records made at or after this time (7d, 2024-05-01, unix
+        /// seconds) or revision's commit time
+        #[arg(long, value_name = "TIME|REV")]
+        since: Option<String>,
+
+        /// Write the bundle here instead of stdout
+        #[arg(short = 'o', long = "out", value_name = "FILE")]
+        out: Option<PathBuf>,
+    },
+    /// Add the records from an exported bundle, skipping ids already present
+    Import {
+        /// Bundle written by `export`
+        bundle: PathBuf,
+    },
+    /// Print the versioned JSON schema of records or of a command's JSON output
+    Schema {
+        /// Schema to print; lists them when omitted
+        #[arg(value_enum)]
+        schema: Option<SchemaName>,
+    },
+    /// Print a shell completion script, e.g. `trueflow completions zsh > _trueflow`
+    Completions {
+        #[arg(value_enum)]
+        shell: clap_complete::Shell,
+    },
+    /// Print the man page of trueflow or a subcommand, or write every page with --out
+    Man {
+        /// Subcommand to document, e.g. `store migrate`
+        command: Vec<String>,
+
+        /// Directory to write a page per command to
+        #[arg(long, value_name = "DIR")]
+        out: Option<PathBuf>,
+    },
+    /// Set up .trueflow/, a starter trueflow.toml and .gitignore entry in this repository
+    Init {
+        /// Also install the pre-commit and pre-push hooks
+        #[arg(long)]
+        hooks: bool,
+
+        /// Also write a GitHub Actions workflow running `check`
+        #[arg(long)]
+        ci: bool,
+
+        /// Replace an existing trueflow.toml, workflow or hooks
+        #[arg(long)]
+        force: bool,
+    },
+    /// Install or remove git hooks that run `check` before commits and pushes
+    Hooks {
+        #[command(subcommand)]
+        action: HooksAction,
+    },
+    /// Sign in with [identity.oidc] and cache the identity records are attributed to
+    Login {
+        /// OIDC issuer URL; overrides [identity.oidc] issuer
+        #[arg(long, value_name = "URL")]
+        issuer: Option<String>,
+
+        /// Client id registered with the issuer; overrides [identity.oidc] client_id
+        #[arg(long, value_name = "ID")]
+        client_id: Option<String>,
+    },
+    /// Show and change config across its layers
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+    /// Maintain the review store
+    Store {
+        #[command(subcommand)]
+        action: StoreAction,
+    },
+    /// Run a language server that shows unreviewed blocks as diagnostics
+    Lsp,
+    /// Serve a JSON API for editor plugins and web UIs on 127.0.0.1
+    Serve {
+        /// Port to listen on (0 picks a free one)
+        #[arg(long, default_value_t = 7890)]
+        port: u16,
     },
     /// Launch the TUI
-    Tui,
+    Tui {
+        /// Skip the scope picker and review these targets (staged, base, file:`<path>`, dir:`<path>`, glob:`<pattern>`, rev:`<sha>`)
+        #[arg(long, value_name = "TARGET")]
+        target: Vec<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum HooksAction {
+    /// Write pre-commit (staged changes) and pre-push (pushed commits) hooks
+    Install {
+        /// Replace existing hooks that trueflow didn't write
+        #[arg(long)]
+        force: bool,
+    },
+    /// Remove the hooks `install` wrote
+    Uninstall,
+}
+
+#[derive(Subcommand)]
+pub enum ConfigAction {
+    /// Print a key's effective value (e.g. `policy.required_approvals`)
+    Get { key: String },
+    /// Set a key in the repo's trueflow.toml, keeping its comments
+    Set {
+        key: String,
+        /// A TOML value (`true`, `2`, `["gap"]`); anything else is a string
+        value: String,
+        /// Write to the per-user config instead (~/.config/trueflow/config.toml)
+        #[arg(long)]
+        user: bool,
+    },
+    /// Print every key that is set, with its value and the layer it comes from
+    List {
+        /// Include built-in defaults
+        #[arg(long)]
+        all: bool,
+    },
+    /// Show the config layers and check the merged config for problems
+    Doctor,
+}
+
+#[derive(Subcommand)]
+pub enum ReportAction {
+    /// Unreviewed blocks over time, from the snapshots `trueflow snapshot` recorded
+    Burndown {
+        /// Start of the window: age (`30d`, `2w`), date (`2024-05-01`), or unix time
+        #[arg(long, default_value = "30d")]
+        since: String,
+
+        /// End of the window (same formats as --since)
+        #[arg(long)]
+        until: Option<String>,
+
+        /// Output JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// A self-contained HTML report of coverage, unreviewed blocks, notes and reviewers
+    Html {
+        /// Directory to write index.html to
+        #[arg(short = 'o', long, value_name = "DIR", default_value = "review-report")]
+        out: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum StoreAction {
+    /// Upgrade older records to the current schema version, adding block
+    /// context where the block is still in the tree
+    Migrate {
+        /// Report what would change without rewriting the store
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn agent_args_select_agent_identity() {
+        let args = AgentArgs {
+            name: Some("code-simplifier".to_string()),
+            model: Some("gpt-x".to_string()),
+            version: Some(" ".to_string()),
+        };
+        assert_eq!(
+            args.identity(),
+            Some(Identity::Agent {
+                name: "code-simplifier".to_string(),
+                model: Some("gpt-x".to_string()),
+                version: None,
+            })
+        );
+        assert_eq!(AgentArgs::default().identity(), None);
+    }
 }
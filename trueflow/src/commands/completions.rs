@@ -0,0 +1,12 @@
+use crate::cli::Cli;
+use anyhow::Result;
+use clap::CommandFactory;
+use clap_complete::Shell;
+
+/// Prints the completion script for `shell`.
+pub fn run(shell: Shell) -> Result<()> {
+    let mut command = Cli::command();
+    let name = command.get_name().to_string();
+    clap_complete::generate(shell, &mut command, name, &mut std::io::stdout());
+    Ok(())
+}
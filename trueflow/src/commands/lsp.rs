@@ -0,0 +1,402 @@
+use anyhow::{Result, anyhow};
+use log::{info, warn};
+use lsp_server::{Connection, Message, Notification, Request, Response};
+use lsp_types::notification::{
+    DidCloseTextDocument, DidOpenTextDocument, DidSaveTextDocument,
+    Notification as LspNotification, PublishDiagnostics,
+};
+use lsp_types::request::{CodeActionRequest, ExecuteCommand, Request as LspRequest};
+use lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams,
+    CodeActionProviderCapability, Command, Diagnostic, DiagnosticSeverity,
+    DidCloseTextDocumentParams, DidOpenTextDocumentParams, DidSaveTextDocumentParams,
+    ExecuteCommandOptions, ExecuteCommandParams, InitializeParams, NumberOrString, Position,
+    PublishDiagnosticsParams, Range, ServerCapabilities, TextDocumentSyncCapability,
+    TextDocumentSyncKind, TextDocumentSyncOptions, TextDocumentSyncSaveOptions, Url,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use trueflow_core::commands::mark::{self, MarkParams};
+use trueflow_core::commands::review::{
+    ReviewOptions, ReviewTarget, collect_review_summary_in, default_reviewer_in, normalize_path_str,
+};
+use trueflow_core::config::load_in as load_config_in;
+use trueflow_core::context::TrueflowContext;
+use trueflow_core::policy::counted_records;
+use trueflow_core::store::{Record, Verdict, open_store_in, workspace_root_in};
+
+/// The command code actions run to record a verdict.
+const MARK_COMMAND: &str = "trueflow.mark";
+
+/// A block of an open file that still needs review.
+#[derive(Debug, Clone, PartialEq)]
+struct OpenBlock {
+    fingerprint: String,
+    start_line: usize,
+    end_line: usize,
+    verdict: Option<Verdict>,
+    note: Option<String>,
+}
+
+/// Arguments of [`MARK_COMMAND`].
+#[derive(Debug, Serialize, Deserialize)]
+struct MarkArgs {
+    uri: Url,
+    fingerprint: String,
+    verdict: Verdict,
+    line: u32,
+}
+
+/// Runs a language server on stdin/stdout that reports unreviewed blocks of
+/// open files as diagnostics, with code actions to approve or reject them.
+pub fn run(context: &TrueflowContext) -> Result<()> {
+    info!("lsp start");
+    let (connection, io_threads) = Connection::stdio();
+    let capabilities = serde_json::to_value(ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Options(
+            TextDocumentSyncOptions {
+                open_close: Some(true),
+                change: Some(TextDocumentSyncKind::NONE),
+                save: Some(TextDocumentSyncSaveOptions::Supported(true)),
+                ..TextDocumentSyncOptions::default()
+            },
+        )),
+        code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+        execute_command_provider: Some(ExecuteCommandOptions {
+            commands: vec![MARK_COMMAND.to_string()],
+            ..ExecuteCommandOptions::default()
+        }),
+        ..ServerCapabilities::default()
+    })?;
+    let params: InitializeParams = serde_json::from_value(connection.initialize(capabilities)?)?;
+    let root = match workspace_root(&params) {
+        Some(root) => root,
+        None => std::env::current_dir()?,
+    };
+
+    let mut server = Server {
+        context,
+        root,
+        connection: &connection,
+        open: HashMap::new(),
+    };
+    for message in &connection.receiver {
+        match message {
+            Message::Request(request) => {
+                if connection.handle_shutdown(&request)? {
+                    break;
+                }
+                server.handle_request(request)?;
+            }
+            Message::Notification(notification) => server.handle_notification(notification)?,
+            Message::Response(_) => {}
+        }
+    }
+    drop(server);
+    drop(connection);
+    io_threads.join()?;
+    info!("lsp stop");
+    Ok(())
+}
+
+#[allow(deprecated)] // `root_uri` is still what most clients send.
+fn workspace_root(params: &InitializeParams) -> Option<PathBuf> {
+    params
+        .workspace_folders
+        .as_ref()
+        .and_then(|folders| folders.first())
+        .map(|folder| &folder.uri)
+        .or(params.root_uri.as_ref())
+        .and_then(|uri| uri.to_file_path().ok())
+}
+
+struct Server<'a> {
+    context: &'a TrueflowContext,
+    /// The workspace folder the client opened; paths are relative to it.
+    root: PathBuf,
+    connection: &'a Connection,
+    /// Unreviewed blocks of each open document, as last published.
+    open: HashMap<Url, Vec<OpenBlock>>,
+}
+
+impl Server<'_> {
+    fn handle_request(&mut self, request: Request) -> Result<()> {
+        let response = match request.method.as_str() {
+            CodeActionRequest::METHOD => {
+                let params: CodeActionParams = serde_json::from_value(request.params)?;
+                let actions = self.code_actions(&params)?;
+                Response::new_ok(request.id, actions)
+            }
+            ExecuteCommand::METHOD => {
+                let params: ExecuteCommandParams = serde_json::from_value(request.params)?;
+                match self.execute(params) {
+                    Ok(()) => Response::new_ok(request.id, Value::Null),
+                    Err(err) => Response::new_err(
+                        request.id,
+                        lsp_server::ErrorCode::RequestFailed as i32,
+                        format!("{err:#}"),
+                    ),
+                }
+            }
+            _ => Response::new_err(
+                request.id,
+                lsp_server::ErrorCode::MethodNotFound as i32,
+                format!("Unsupported request: {}", request.method),
+            ),
+        };
+        self.connection.sender.send(response.into())?;
+        Ok(())
+    }
+
+    fn handle_notification(&mut self, notification: Notification) -> Result<()> {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params: DidOpenTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+                self.publish(params.text_document.uri)
+            }
+            DidSaveTextDocument::METHOD => {
+                let params: DidSaveTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+                self.publish(params.text_document.uri)
+            }
+            DidCloseTextDocument::METHOD => {
+                let params: DidCloseTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+                self.open.remove(&params.text_document.uri);
+                self.send_diagnostics(params.text_document.uri, Vec::new())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Re-reviews the document on disk and publishes its diagnostics.
+    fn publish(&mut self, uri: Url) -> Result<()> {
+        let blocks = match relative_path(&uri, &self.root) {
+            Some(path) => review_file(self.context, &self.root, &path).unwrap_or_else(|err| {
+                warn!("Failed to review {}: {:#}", path, err);
+                Vec::new()
+            }),
+            None => Vec::new(),
+        };
+        let diagnostics = blocks.iter().map(diagnostic).collect();
+        self.open.insert(uri.clone(), blocks);
+        self.send_diagnostics(uri, diagnostics)
+    }
+
+    fn send_diagnostics(&self, uri: Url, diagnostics: Vec<Diagnostic>) -> Result<()> {
+        let params = PublishDiagnosticsParams {
+            uri,
+            diagnostics,
+            version: None,
+        };
+        self.connection
+            .sender
+            .send(Notification::new(PublishDiagnostics::METHOD.to_string(), params).into())?;
+        Ok(())
+    }
+
+    /// Approve and reject actions for the innermost block at the cursor.
+    fn code_actions(&self, params: &CodeActionParams) -> Result<Vec<CodeActionOrCommand>> {
+        let uri = &params.text_document.uri;
+        let line = params.range.start.line as usize;
+        let Some(block) = self
+            .open
+            .get(uri)
+            .and_then(|blocks| enclosing_block(blocks, line))
+        else {
+            return Ok(Vec::new());
+        };
+        let mut actions = Vec::new();
+        for (verdict, title) in [
+            (Verdict::Approved, "Approve block (trueflow)"),
+            (Verdict::Rejected, "Reject block (trueflow)"),
+        ] {
+            let args = MarkArgs {
+                uri: uri.clone(),
+                fingerprint: block.fingerprint.clone(),
+                verdict,
+                line: block.start_line as u32,
+            };
+            actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                title: title.to_string(),
+                kind: Some(CodeActionKind::QUICKFIX),
+                command: Some(Command {
+                    title: title.to_string(),
+                    command: MARK_COMMAND.to_string(),
+                    arguments: Some(vec![serde_json::to_value(args)?]),
+                }),
+                ..CodeAction::default()
+            }));
+        }
+        Ok(actions)
+    }
+
+    fn execute(&mut self, params: ExecuteCommandParams) -> Result<()> {
+        if params.command != MARK_COMMAND {
+            return Err(anyhow!("Unknown command: {}", params.command));
+        }
+        let args: MarkArgs = serde_json::from_value(
+            params
+                .arguments
+                .into_iter()
+                .next()
+                .ok_or_else(|| anyhow!("{} needs an argument", MARK_COMMAND))?,
+        )?;
+        mark::record_all_in(
+            self.context,
+            &self.root,
+            &load_config_in(&self.root)?,
+            vec![MarkParams {
+                fingerprint: args.fingerprint,
+                verdict: args.verdict,
+                check: "review".to_string(),
+                note: None,
+                path: relative_path(&args.uri, &self.root),
+                line: Some(args.line),
+                provenance: None,
+                confidence: None,
+                refs: Vec::new(),
+                in_reply_to: None,
+                sign: false,
+            }],
+        )?;
+        self.publish(args.uri)
+    }
+}
+
+/// The document's path relative to `root`, if it is a file in it.
+fn relative_path(uri: &Url, root: &Path) -> Option<String> {
+    let path = uri.to_file_path().ok()?;
+    let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+    let path = path.canonicalize().unwrap_or(path);
+    let relative = path.strip_prefix(&root).ok()?;
+    Some(normalize_path_str(&relative.to_string_lossy()))
+}
+
+/// Unreviewed blocks of `path`, with the current verdict and its note.
+fn review_file(context: &TrueflowContext, root: &Path, path: &str) -> Result<Vec<OpenBlock>> {
+    let config = load_config_in(root)?;
+    let filters = config.review.resolve_filters(&[], &[]);
+    let options = ReviewOptions {
+        all: false,
+        targets: vec![ReviewTarget::File(path.to_string())],
+        only: Vec::new(),
+        exclude: Vec::new(),
+        assignee: None,
+        reviewer: default_reviewer_in(context, root, &config)?,
+        check: None,
+    };
+    let summary = collect_review_summary_in(root, &config, &options, &filters)?;
+    let store = open_store_in(&workspace_root_in(root)?, &config.store)?;
+    let history = counted_records(store.read_history()?, &config.policy);
+    let notes = latest_notes(&history);
+    Ok(summary
+        .files
+        .iter()
+        .filter(|file| Path::new(&file.path) == Path::new(path))
+        .flat_map(|file| &file.blocks)
+        .map(|block| OpenBlock {
+            fingerprint: block.hash.clone(),
+            start_line: block.start_line,
+            end_line: block.end_line,
+            verdict: summary.review_state.get(&block.hash).cloned(),
+            note: notes.get(block.hash.as_str()).cloned(),
+        })
+        .collect())
+}
+
+/// The note of each fingerprint's latest review verdict, when it has one.
+fn latest_notes(records: &[Record]) -> HashMap<&str, String> {
+    let mut sorted: Vec<&Record> = records
+        .iter()
+        .filter(|record| record.kind.is_verdict() && record.check == "review")
+        .collect();
+    sorted.sort_by_key(|record| record.timestamp);
+    let mut notes = HashMap::new();
+    for record in sorted {
+        match record.note.as_deref().map(str::trim) {
+            Some(note) if !note.is_empty() => {
+                notes.insert(record.fingerprint.as_str(), note.to_string());
+            }
+            _ => {
+                notes.remove(record.fingerprint.as_str());
+            }
+        }
+    }
+    notes
+}
+
+fn diagnostic(block: &OpenBlock) -> Diagnostic {
+    let (severity, label) = match block.verdict {
+        Some(Verdict::Rejected) => (DiagnosticSeverity::WARNING, "Rejected"),
+        Some(Verdict::Question) => (DiagnosticSeverity::INFORMATION, "Question"),
+        _ => (DiagnosticSeverity::INFORMATION, "Unreviewed block"),
+    };
+    let message = match (&block.verdict, &block.note) {
+        (Some(Verdict::Rejected | Verdict::Question), Some(note)) => format!("{label}: {note}"),
+        _ => label.to_string(),
+    };
+    Diagnostic {
+        range: Range {
+            start: Position::new(block.start_line as u32, 0),
+            end: Position::new(block.end_line.max(block.start_line + 1) as u32, 0),
+        },
+        severity: Some(severity),
+        code: Some(NumberOrString::String(
+            block
+                .fingerprint
+                .get(..12)
+                .unwrap_or(&block.fingerprint)
+                .to_string(),
+        )),
+        source: Some("trueflow".to_string()),
+        message,
+        ..Diagnostic::default()
+    }
+}
+
+/// The smallest block spanning `line`.
+fn enclosing_block(blocks: &[OpenBlock], line: usize) -> Option<&OpenBlock> {
+    blocks
+        .iter()
+        .filter(|block| block.start_line <= line && line < block.end_line.max(block.start_line + 1))
+        .min_by_key(|block| block.end_line - block.start_line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(start_line: usize, end_line: usize, verdict: Option<Verdict>) -> OpenBlock {
+        OpenBlock {
+            fingerprint: format!("{start_line:0>16}"),
+            start_line,
+            end_line,
+            verdict,
+            note: Some("Leaks the handle".to_string()),
+        }
+    }
+
+    #[test]
+    fn diagnostics_carry_the_verdict_and_note() {
+        let unreviewed = diagnostic(&block(0, 3, None));
+        assert_eq!(unreviewed.message, "Unreviewed block");
+        assert_eq!(unreviewed.range.end, Position::new(3, 0));
+
+        let rejected = diagnostic(&block(4, 6, Some(Verdict::Rejected)));
+        assert_eq!(rejected.message, "Rejected: Leaks the handle");
+        assert_eq!(rejected.severity, Some(DiagnosticSeverity::WARNING));
+        assert_eq!(rejected.source.as_deref(), Some("trueflow"));
+    }
+
+    #[test]
+    fn code_actions_pick_the_innermost_block() {
+        let blocks = vec![block(0, 10, None), block(2, 4, None)];
+        assert_eq!(enclosing_block(&blocks, 3).map(|b| b.start_line), Some(2));
+        assert_eq!(enclosing_block(&blocks, 5).map(|b| b.start_line), Some(0));
+        assert!(enclosing_block(&blocks, 10).is_none());
+    }
+}
@@ -0,0 +1,35 @@
+use crate::cli::Cli;
+use anyhow::{Context, Result, bail};
+use clap::CommandFactory;
+use clap_mangen::Man;
+use std::fs;
+use std::path::Path;
+
+/// Prints the man page of `trueflow`, or of the subcommand `path` names
+/// (e.g. `["store", "migrate"]`). With `out`, writes a page for every
+/// command to that directory instead.
+pub fn run(path: &[String], out: Option<&Path>) -> Result<()> {
+    let mut command = Cli::command().disable_help_subcommand(true);
+    // Gives subcommands their `trueflow-<name>` page titles.
+    command.build();
+
+    if let Some(dir) = out {
+        if !path.is_empty() {
+            bail!("--out writes every page; drop the subcommand");
+        }
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        clap_mangen::generate_to(command, dir)
+            .with_context(|| format!("Failed to write man pages to {}", dir.display()))?;
+        println!("Wrote man pages to {}", dir.display());
+        return Ok(());
+    }
+
+    for name in path {
+        let Some(sub) = command.find_subcommand(name).cloned() else {
+            bail!("No subcommand `{name}` of `{}`", command.get_name());
+        };
+        command = sub;
+    }
+    Man::new(command).render(&mut std::io::stdout())?;
+    Ok(())
+}
@@ -1,10 +1,10 @@
-pub mod check;
-pub mod diff;
-pub mod feedback;
-pub mod inspect;
-pub mod mark;
-pub mod review;
-pub mod scan;
-pub mod sync;
+//! The commands that need a terminal, a server or clap, on top of the
+//! engine's own.
+
+pub use trueflow_core::commands::*;
+
+pub mod completions;
+pub mod lsp;
+pub mod man;
+pub mod serve;
 pub mod tui;
-pub mod verify;
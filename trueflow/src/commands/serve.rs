@@ -0,0 +1,488 @@
+use anyhow::{Result, anyhow};
+use log::{info, warn};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::{Component, Path};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tiny_http::{Header, Method, Request, Response, Server};
+use trueflow_core::commands::inspect::find_block;
+use trueflow_core::commands::mark::{self, MarkParams};
+use trueflow_core::commands::review::{
+    ReviewOptions, collect_review_summary, parse_review_targets,
+};
+use trueflow_core::commands::stats::collect_stats;
+use trueflow_core::config::load as load_config;
+use trueflow_core::context::TrueflowContext;
+use trueflow_core::http_store::server_token;
+use trueflow_core::identity::CurrentIdentity;
+use trueflow_core::record_schema::{record_schema, validate};
+use trueflow_core::scanner;
+use trueflow_core::signing::{Verification, Verifier};
+use trueflow_core::store::{CURRENT_VERSION, Record, RecordQuery, Verdict, open_store};
+
+/// Largest request body read; far more than a big `mark --stdin` batch.
+const MAX_BODY_BYTES: usize = 4 * 1024 * 1024;
+
+/// An API reply before it is written out.
+struct Reply {
+    status: u16,
+    body: Value,
+}
+
+impl Reply {
+    fn ok(body: Value) -> Self {
+        Self { status: 200, body }
+    }
+
+    fn error(status: u16, message: impl ToString) -> Self {
+        Self {
+            status,
+            body: json!({ "error": message.to_string() }),
+        }
+    }
+}
+
+/// Serves the review API on localhost until interrupted. GET responses are
+/// cached until something in the working tree or the ledger changes. Every
+/// request must carry the store token as a bearer token; when none is set, a
+/// fresh one is generated and printed after the address.
+pub fn run(context: &TrueflowContext, port: u16) -> Result<()> {
+    let (token, generated) = match server_token(&load_config()?.store) {
+        Some(token) => (token, false),
+        None => (uuid::Uuid::new_v4().simple().to_string(), true),
+    };
+    let server = Server::http(("127.0.0.1", port))
+        .map_err(|err| anyhow!("Failed to listen on 127.0.0.1:{}: {}", port, err))?;
+    let addr = server
+        .server_addr()
+        .to_ip()
+        .ok_or_else(|| anyhow!("Server is not listening on a TCP address"))?;
+    info!("serve start (addr={})", addr);
+    println!("Listening on http://{addr}");
+    if generated {
+        println!("Token: {token}");
+    }
+    std::io::stdout().flush()?;
+
+    let dirty = Arc::new(AtomicBool::new(false));
+    let watcher = watch_workdir(dirty.clone());
+    if let Err(err) = &watcher {
+        warn!(
+            "File watching unavailable, responses are not cached: {}",
+            err
+        );
+    }
+    let mut cache: HashMap<String, String> = HashMap::new();
+
+    for mut request in server.incoming_requests() {
+        if dirty.swap(false, Ordering::SeqCst) || watcher.is_err() {
+            cache.clear();
+        }
+        let url = request.url().to_string();
+        let cacheable = *request.method() == Method::Get;
+        let (status, body) = match cache.get(&url).filter(|_| cacheable) {
+            _ if !from_localhost(&request) => {
+                let reply = Reply::error(403, "Host and Origin must be localhost");
+                (reply.status, serde_json::to_string_pretty(&reply.body)?)
+            }
+            _ if !authorized(&request, &token) => {
+                let reply = Reply::error(401, "Missing or wrong bearer token");
+                (reply.status, serde_json::to_string_pretty(&reply.body)?)
+            }
+            Some(body) => (200, body.clone()),
+            None => {
+                let reply = handle(context, &mut request);
+                let body = serde_json::to_string_pretty(&reply.body)?;
+                if reply.status == 200 {
+                    if cacheable {
+                        cache.insert(url.clone(), body.clone());
+                    } else {
+                        // Don't wait for the watcher to see our own write.
+                        cache.clear();
+                    }
+                }
+                (reply.status, body)
+            }
+        };
+        info!("serve {} {} -> {}", request.method().as_str(), url, status);
+        let response = Response::from_string(body)
+            .with_status_code(status)
+            .with_header(json_header());
+        if let Err(err) = request.respond(response) {
+            warn!("Failed to answer {}: {}", url, err);
+        }
+    }
+    Ok(())
+}
+
+fn header<'a>(request: &'a Request, name: &'static str) -> Option<&'a str> {
+    request
+        .headers()
+        .iter()
+        .find(|header| header.field.equiv(name))
+        .map(|header| header.value.as_str())
+}
+
+/// Whether `Host`, and `Origin` when sent, name this machine. A web page
+/// can make the browser send requests here, but not with a local `Origin`,
+/// and a DNS name rebound to 127.0.0.1 still shows up in `Host`.
+fn from_localhost(request: &Request) -> bool {
+    header(request, "Host").is_some_and(|host| is_local(&format!("http://{host}")))
+        && header(request, "Origin").is_none_or(is_local)
+}
+
+fn is_local(url: &str) -> bool {
+    url::Url::parse(url)
+        .is_ok_and(|url| matches!(url.host_str(), Some("localhost" | "127.0.0.1" | "[::1]")))
+}
+
+fn authorized(request: &Request, token: &str) -> bool {
+    header(request, "Authorization")
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|given| constant_time_eq(given.as_bytes(), token.as_bytes()))
+}
+
+/// Compares every byte, so the response time doesn't tell how much of a
+/// guessed token was right.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+fn json_header() -> Header {
+    Header::from_bytes("Content-Type", "application/json").expect("static header is valid")
+}
+
+fn handle(context: &TrueflowContext, request: &mut Request) -> Reply {
+    let url = request.url().to_string();
+    let (path, query) = url.split_once('?').unwrap_or((&url, ""));
+    let params: Vec<(String, String)> = url::form_urlencoded::parse(query.as_bytes())
+        .into_owned()
+        .collect();
+    let result = match (request.method(), path.trim_end_matches('/')) {
+        (Method::Get, "/review") => get_review(context, &params),
+        (Method::Get, "/stats") => get_stats(context, &params),
+        (Method::Get, path) if path.starts_with("/blocks/") => get_block(&path["/blocks/".len()..]),
+        (Method::Get, "/records") => get_records(),
+        (Method::Post, "/mark") => with_body(request, |body| post_mark(context, body)),
+        (Method::Post, "/records") => with_body(request, |body| append_records(context, body)),
+        (_, "/review" | "/stats" | "/mark" | "/records") => {
+            Ok(Reply::error(405, "Method not allowed"))
+        }
+        _ => Ok(Reply::error(404, format!("No route for {}", path))),
+    };
+    result.unwrap_or_else(|err| Reply::error(500, format!("{err:#}")))
+}
+
+fn with_body(request: &mut Request, handle: impl FnOnce(&str) -> Result<Reply>) -> Result<Reply> {
+    let too_large = || Reply::error(413, format!("Body over {MAX_BODY_BYTES} bytes"));
+    if request
+        .body_length()
+        .is_some_and(|length| length > MAX_BODY_BYTES)
+    {
+        return Ok(too_large());
+    }
+    match read_capped(request.as_reader(), MAX_BODY_BYTES) {
+        Ok(Some(body)) => handle(&body),
+        Ok(None) => Ok(too_large()),
+        Err(err) => Ok(Reply::error(400, err)),
+    }
+}
+
+/// All of `reader` as text, or `None` when it holds more than `limit` bytes.
+fn read_capped(reader: &mut dyn Read, limit: usize) -> std::io::Result<Option<String>> {
+    let mut body = String::new();
+    let read = reader.take(limit as u64 + 1).read_to_string(&mut body)?;
+    Ok((read <= limit).then_some(body))
+}
+
+fn values<'a>(params: &'a [(String, String)], key: &str) -> Vec<&'a str> {
+    params
+        .iter()
+        .filter(|(name, _)| name == key)
+        .map(|(_, value)| value.as_str())
+        .collect()
+}
+
+fn flag(params: &[(String, String)], key: &str) -> bool {
+    values(params, key)
+        .last()
+        .is_some_and(|value| matches!(*value, "" | "1" | "true"))
+}
+
+/// `GET /review?all&target=...&only=...&exclude=...&reviewer=...`: what
+/// `review --json` prints.
+fn get_review(_context: &TrueflowContext, params: &[(String, String)]) -> Result<Reply> {
+    let owned = |key| -> Vec<String> {
+        values(params, key)
+            .into_iter()
+            .map(str::to_string)
+            .collect()
+    };
+    let targets = match parse_review_targets(&owned("target")) {
+        Ok(targets) => targets,
+        Err(err) => return Ok(Reply::error(400, err)),
+    };
+    let options = ReviewOptions {
+        all: flag(params, "all"),
+        targets,
+        only: owned("only"),
+        exclude: owned("exclude"),
+        assignee: None,
+        reviewer: values(params, "reviewer")
+            .last()
+            .map(|value| value.to_string()),
+        check: values(params, "check")
+            .last()
+            .filter(|check| **check != "review")
+            .map(|value| value.to_string()),
+    };
+    let filters = load_config()?
+        .review
+        .resolve_filters(&options.only, &options.exclude);
+    let summary = collect_review_summary(&options, &filters)?;
+    Ok(Reply::ok(serde_json::to_value(&summary.files)?))
+}
+
+/// `GET /stats?depth=N`: what `stats --json` prints.
+fn get_stats(context: &TrueflowContext, params: &[(String, String)]) -> Result<Reply> {
+    let depth = match values(params, "depth").last() {
+        Some(depth) => match depth.parse() {
+            Ok(depth) => depth,
+            Err(_) => return Ok(Reply::error(400, format!("Invalid depth: {depth}"))),
+        },
+        None => 1,
+    };
+    Ok(Reply::ok(serde_json::to_value(collect_stats(
+        context, depth,
+    )?)?))
+}
+
+/// `GET /blocks/<fingerprint>`: the block, its file, and its verdicts.
+fn get_block(fingerprint: &str) -> Result<Reply> {
+    if fingerprint.is_empty() {
+        return Ok(Reply::error(400, "Missing fingerprint"));
+    }
+    let files = scanner::scan_directory(".")?;
+    let found = match find_block(&files, fingerprint) {
+        Ok(Some(found)) => found,
+        Ok(None) => return Ok(Reply::error(404, "Block not found")),
+        Err(err) => return Ok(Reply::error(400, err)),
+    };
+    let history = open_store()?.query(&RecordQuery {
+        fingerprint: Some(found.block.hash.clone()),
+        ..RecordQuery::default()
+    })?;
+    Ok(Reply::ok(json!({
+        "path": found.path,
+        "language": found.language,
+        "block": found.block,
+        "history": history,
+    })))
+}
+
+/// `POST /mark`: entries as for `mark --stdin`, all appended in one write.
+fn post_mark(context: &TrueflowContext, body: &str) -> Result<Reply> {
+    let template = MarkParams {
+        fingerprint: String::new(),
+        verdict: Verdict::Approved,
+        check: "review".to_string(),
+        note: None,
+        path: None,
+        line: None,
+        provenance: None,
+        confidence: None,
+        refs: Vec::new(),
+        in_reply_to: None,
+        sign: false,
+    };
+    let batch = match mark::parse_entries(body, &template) {
+        Ok(batch) if batch.is_empty() => return Ok(Reply::error(400, "No entries to mark")),
+        Ok(batch) => batch,
+        Err(err) => return Ok(Reply::error(400, format!("{err:#}"))),
+    };
+    let ids = mark::record_all(context, batch)?;
+    Ok(Reply::ok(json!({ "ids": ids })))
+}
+
+/// `GET /records`: the whole ledger, oldest first, for `[store] backend = "http"`.
+fn get_records() -> Result<Reply> {
+    Ok(Reply::ok(serde_json::to_value(
+        open_store()?.read_history()?,
+    )?))
+}
+
+/// `POST /records` appends a JSON array of records in one write. Each must
+/// match the record schema, and be by the identity this server records as
+/// unless a trusted signer attested it: the token says who may write, not
+/// whom the records may name.
+fn append_records(context: &TrueflowContext, body: &str) -> Result<Reply> {
+    let entries: Vec<Value> = match serde_json::from_str(body) {
+        Ok(entries) => entries,
+        Err(err) => return Ok(Reply::error(400, format!("Invalid records: {err}"))),
+    };
+    let schema = record_schema();
+    let mut records = Vec::with_capacity(entries.len());
+    for (index, entry) in entries.into_iter().enumerate() {
+        let mut problems = validate(&schema, &entry);
+        let record = serde_json::from_value::<Record>(entry);
+        match &record {
+            Ok(record) if record.version > CURRENT_VERSION => problems.push(format!(
+                "/version: {} is newer than this trueflow reads ({CURRENT_VERSION})",
+                record.version
+            )),
+            Ok(record) if record.confidence.is_some_and(|c| !(0.0..=1.0).contains(&c)) => {
+                problems.push("/confidence: must be between 0 and 1".to_string())
+            }
+            Ok(_) => {}
+            Err(err) if problems.is_empty() => problems.push(err.to_string()),
+            Err(_) => {}
+        }
+        match record {
+            Ok(record) if problems.is_empty() => records.push(record),
+            _ => {
+                return Ok(Reply::error(
+                    400,
+                    format!("Invalid record {index}: {}", problems.join("; ")),
+                ));
+            }
+        }
+    }
+
+    let caller = CurrentIdentity::resolve(context)?.identity;
+    let foreign: Vec<(usize, &Record)> = records
+        .iter()
+        .enumerate()
+        .filter(|(_, record)| record.identity != caller)
+        .collect();
+    if !foreign.is_empty() {
+        let verifier = Verifier::for_workspace(&load_config()?)?;
+        for (index, record) in foreign {
+            if !attested(&verifier, record)? {
+                return Ok(Reply::error(
+                    403,
+                    format!(
+                        "Record {index} is by {}, not {}, and no trusted signer attested it",
+                        record.identity.label(),
+                        caller.label()
+                    ),
+                ));
+            }
+        }
+    }
+
+    let count = records.len();
+    open_store()?.append_all(records)?;
+    Ok(Reply::ok(json!({ "records": count })))
+}
+
+/// Whether `record` carries attestations and each verifies against a
+/// pinned signer.
+fn attested(verifier: &Verifier, record: &Record) -> Result<bool> {
+    let Some(attestations) = record.attestations.as_ref().filter(|list| !list.is_empty()) else {
+        return Ok(false);
+    };
+    let payload = record.signing_payload()?;
+    for attestation in attestations {
+        if verifier.verify(record, &payload, attestation)? != Verification::Valid {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Sets `dirty` whenever a file that can change review state does.
+fn watch_workdir(dirty: Arc<AtomicBool>) -> notify::Result<RecommendedWatcher> {
+    let mut watcher =
+        notify::recommended_watcher(move |event: notify::Result<notify::Event>| match event {
+            Ok(event) if event.paths.iter().any(|path| invalidates(path)) => {
+                dirty.store(true, Ordering::SeqCst);
+            }
+            Ok(_) => {}
+            Err(_) => dirty.store(true, Ordering::SeqCst),
+        })?;
+    watcher.watch(Path::new("."), RecursiveMode::Recursive)?;
+    Ok(watcher)
+}
+
+/// Whether a change to `path` can change review state. Inside `.git` only
+/// the index, HEAD, and refs matter; inside `.trueflow` only the ledger and
+/// CODEOWNERS, so scan caches and logs don't clear the cache.
+fn invalidates(path: &Path) -> bool {
+    let components: Vec<String> = path
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(name) => Some(name.to_string_lossy().into_owned()),
+            _ => None,
+        })
+        .collect();
+    let name = components.last().map(String::as_str).unwrap_or("");
+    if let Some(index) = components.iter().position(|part| part == ".git") {
+        return matches!(name, "index" | "HEAD")
+            || components.get(index + 1).is_some_and(|part| part == "refs");
+    }
+    if components.iter().any(|part| part == ".trueflow") {
+        return matches!(
+            name,
+            "reviews.jsonl" | "reviews.db" | "reviews.db-wal" | "CODEOWNERS"
+        );
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_state_changes_invalidate() {
+        assert!(invalidates(Path::new("./src/lib.rs")));
+        assert!(invalidates(Path::new("/repo/.git/index")));
+        assert!(invalidates(Path::new("/repo/.git/refs/heads/main")));
+        assert!(invalidates(Path::new("/repo/.trueflow/reviews.jsonl")));
+        assert!(!invalidates(Path::new("/repo/.git/objects/ab/cdef")));
+        assert!(!invalidates(Path::new("/repo/.trueflow/cache/scan.json")));
+        assert!(!invalidates(Path::new(
+            "/repo/.trueflow/logs/trueflow.jsonl"
+        )));
+    }
+
+    #[test]
+    fn only_local_urls_are_local() {
+        assert!(is_local("http://localhost:7890"));
+        assert!(is_local("http://127.0.0.1"));
+        assert!(is_local("http://[::1]:80"));
+        assert!(!is_local("http://localhost.evil.example"));
+        assert!(!is_local("http://localhost@evil.example"));
+        assert!(!is_local("null"));
+    }
+
+    #[test]
+    fn bodies_over_the_limit_are_refused() {
+        assert_eq!(
+            read_capped(&mut "abc".as_bytes(), 3).unwrap().as_deref(),
+            Some("abc")
+        );
+        assert_eq!(read_capped(&mut "abcd".as_bytes(), 3).unwrap(), None);
+    }
+
+    #[test]
+    fn tokens_compare_whole() {
+        assert!(constant_time_eq(b"s3cret", b"s3cret"));
+        assert!(!constant_time_eq(b"s3cret", b"s3creT"));
+        assert!(!constant_time_eq(b"s3c", b"s3cret"));
+    }
+
+    #[test]
+    fn flags_accept_bare_and_true() {
+        let params = vec![
+            ("all".to_string(), String::new()),
+            ("mine".to_string(), "0".to_string()),
+        ];
+        assert!(flag(&params, "all"));
+        assert!(!flag(&params, "mine"));
+        assert!(!flag(&params, "missing"));
+    }
+}
@@ -1,43 +1,85 @@
-use crate::analysis::Language;
-use crate::block::BlockKind;
-use crate::commands::mark;
-use crate::commands::review::{ReviewOptions, ReviewTarget, collect_review_summary};
-use crate::config::{BlockFilters, load as load_config};
-use crate::context::TrueflowContext;
-use crate::store::Verdict;
-use crate::tree::{Tree, TreeNodeId, TreeNodeKind};
-use crate::vcs;
-use anyhow::Result;
+use crate::keymap::{Action, KeyBinding, Keymap, key_label};
+use anyhow::{Result, anyhow, bail};
 use crossterm::{
-    event::{self, Event, KeyCode, KeyEventKind},
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind, MouseButton,
+        MouseEvent, MouseEventKind,
+    },
     execute,
     terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
 };
+use log::warn;
 use ratatui::{
     Frame, Terminal,
-    layout::{Alignment, Constraint, Layout, Rect},
+    layout::{Alignment, Constraint, Layout, Position, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
     widgets::{Block as UiBlock, Gauge, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, Wrap},
 };
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::io::{self, Stdout};
+use std::ops::Range;
 use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::str::FromStr;
+use trueflow_core::analysis::Language;
+use trueflow_core::assignment::{Assignments, latest_assignments};
+use trueflow_core::auto_approve::approve_enabled_rules;
+use trueflow_core::blame::{BlockBlame, FileBlame};
+use trueflow_core::block::{Block, BlockKind, PreviousApproval};
+use trueflow_core::claims::{ActiveClaims, active_claims, claim_record, format_remaining};
+use trueflow_core::commands::mark;
+use trueflow_core::commands::review::{
+    ReviewOptions, ReviewTarget, annotate_blocks, collect_review_summary, collect_unreviewed,
+    default_reviewer, parse_review_targets, repo_relative_path, workdir_prefix_from_root,
+};
+use trueflow_core::config::{
+    BlockFilters, ReviewOrderMode, TeamConfig, ThemeMode, TrueflowConfig, TuiConfig,
+    load as load_config,
+};
+use trueflow_core::context::TrueflowContext;
+use trueflow_core::finder::fuzzy_score;
+use trueflow_core::highlight::{HighlightKind, highlight_lines};
+use trueflow_core::identity::CurrentIdentity;
+use trueflow_core::owner_review::OWNER_REVIEW_TAG;
+use trueflow_core::prereview::{PrereviewHint, hint_row, is_prereview, latest_hints};
+use trueflow_core::risk::{BlockRisk, RiskScorer};
+use trueflow_core::scanner;
+use trueflow_core::session::{SessionSnapshot, load_json, remove_json, save_json};
+use trueflow_core::side_by_side::{self, DiffRow};
+use trueflow_core::store::{
+    Identity, VcsSystem, Verdict, approved_hashes_from_verdicts, open_store, without_revoked,
+};
+use trueflow_core::sub_splitter;
+use trueflow_core::thread::{self, ThreadNode};
+use trueflow_core::tree::{Tree, TreeNodeId, TreeNodeKind};
+use trueflow_core::vcs::{self, CommitInfo};
+use trueflow_core::vcs_provider;
+use unicode_width::UnicodeWidthStr;
 
 // --- Core Structs ---
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// Where the last TUI review stood when it was quit.
+const RESUME_FILE: &str = "session.json";
+/// What `e` opens files in when neither `$VISUAL` nor `$EDITOR` is set.
+const DEFAULT_EDITOR: &str = "vi";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 enum ReviewScope {
     All,
     MainDiff,
+    Mine { assignee: Identity },
     Commit { id: String, summary: String },
+    Targets(Vec<ReviewTarget>),
 }
 
 impl ReviewScope {
     fn label(&self) -> String {
         match self {
             ReviewScope::All => "entire review".to_string(),
-            ReviewScope::MainDiff => "diff vs main".to_string(),
+            ReviewScope::MainDiff => format!("diff vs {}", vcs::base_label()),
+            ReviewScope::Mine { assignee } => format!("assigned to {assignee}"),
             ReviewScope::Commit { id, summary } => {
                 let short_id = short_commit_id(id);
                 let summary = truncate_text(summary, 32);
@@ -47,6 +89,11 @@ impl ReviewScope {
                     format!("commit {short_id} {summary}")
                 }
             }
+            ReviewScope::Targets(targets) => targets
+                .iter()
+                .map(ReviewTarget::label)
+                .collect::<Vec<_>>()
+                .join(", "),
         }
     }
 
@@ -57,18 +104,45 @@ impl ReviewScope {
                 targets: vec![ReviewTarget::All],
                 only: Vec::new(),
                 exclude: Vec::new(),
+                assignee: None,
+                reviewer: None,
+                check: None,
             },
             ReviewScope::MainDiff => ReviewOptions {
                 all: false,
                 targets: vec![ReviewTarget::MainDiff],
                 only: Vec::new(),
                 exclude: Vec::new(),
+                assignee: None,
+                reviewer: None,
+                check: None,
+            },
+            ReviewScope::Mine { assignee } => ReviewOptions {
+                all: true,
+                targets: vec![ReviewTarget::All],
+                only: Vec::new(),
+                exclude: Vec::new(),
+                assignee: Some(assignee.clone()),
+                reviewer: None,
+                check: None,
             },
             ReviewScope::Commit { id, .. } => ReviewOptions {
                 all: false,
                 targets: vec![ReviewTarget::Revision(id.clone())],
                 only: Vec::new(),
                 exclude: Vec::new(),
+                assignee: None,
+                reviewer: None,
+                check: None,
+            },
+            ReviewScope::Targets(targets) => ReviewOptions {
+                all: false,
+                targets: targets.clone(),
+                only: Vec::new(),
+                exclude: Vec::new(),
+                assignee: None,
+                reviewer: None,
+                check: None,
             },
         }
     }
@@ -78,6 +152,52 @@ impl ReviewScope {
 struct ScopeOption {
     label: String,
     scope: ReviewScope,
+    /// Set on the "Resume previous session" option.
+    resume: Option<SavedSession>,
+}
+
+/// A tree node remembered across runs by its path and content hash.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct NodeKey {
+    path: String,
+    hash: String,
+}
+
+impl NodeKey {
+    fn of(tree: &Tree, id: TreeNodeId) -> Self {
+        let node = tree.node(id);
+        Self {
+            path: node.path.clone(),
+            hash: node.hash.clone(),
+        }
+    }
+
+    /// The node with this path and hash, else whatever is at the path now.
+    fn find(&self, tree: &Tree) -> Option<TreeNodeId> {
+        if self.path.is_empty() {
+            return Some(tree.root());
+        }
+        tree.node_by_path_and_hash(&self.path, &self.hash)
+            .or_else(|| tree.find_by_path(&self.path))
+    }
+}
+
+/// A comment typed but not submitted yet.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct CommentDraft {
+    node: NodeKey,
+    text: String,
+}
+
+/// What `.trueflow/session.json` keeps so a quit review can be resumed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct SavedSession {
+    scope: ReviewScope,
+    cursor: Option<NodeKey>,
+    draft: Option<CommentDraft>,
+    /// Blocks put off with `defer`, in the order they were deferred.
+    #[serde(default)]
+    deferred: Vec<NodeKey>,
 }
 
 #[derive(Debug, Clone)]
@@ -108,16 +228,19 @@ impl ScopeSelector {
         self.selected = self.selected.saturating_sub(1);
     }
 
-    fn selected_scope(&self) -> Option<ReviewScope> {
-        self.options
-            .get(self.selected)
-            .map(|option| option.scope.clone())
+    fn selection(&self) -> Option<ScopeSelection> {
+        let option = self.options.get(self.selected)?;
+        Some(match &option.resume {
+            Some(saved) => ScopeSelection::Resume(saved.clone()),
+            None => ScopeSelection::Selected(option.scope.clone()),
+        })
     }
 }
 
 enum ScopeSelection {
     Quit,
     Selected(ReviewScope),
+    Resume(SavedSession),
 }
 
 struct ReviewNavigator {
@@ -232,7 +355,7 @@ impl ReviewNavigator {
     }
 }
 
-fn review_band(block: &crate::block::Block) -> ReviewBand {
+fn review_band(block: &trueflow_core::block::Block) -> ReviewBand {
     match block.kind.default_review_priority() {
         0 => ReviewBand::Data,
         20 => ReviewBand::Const,
@@ -248,7 +371,7 @@ fn review_band_rank(band: ReviewBand) -> u8 {
     }
 }
 
-fn review_group(path: &str, node: &crate::tree::TreeNode) -> ReviewGroup {
+fn review_group(path: &str, node: &trueflow_core::tree::TreeNode) -> ReviewGroup {
     if is_test_block(path, node) {
         ReviewGroup::Test
     } else if is_library_path(path) {
@@ -273,7 +396,7 @@ fn is_library_path(path: &str) -> bool {
             && !path.starts_with("src/bin/"))
 }
 
-fn is_test_block(path: &str, node: &crate::tree::TreeNode) -> bool {
+fn is_test_block(path: &str, node: &trueflow_core::tree::TreeNode) -> bool {
     if is_test_path(path) {
         return true;
     }
@@ -306,7 +429,13 @@ fn is_test_path(path: &str) -> bool {
 }
 
 impl ReviewOrder {
-    fn from_summary(summary: &crate::commands::review::ReviewSummary) -> Self {
+    /// Blocks in review order; riskiest first when there are `risk`
+    /// scores. Files `claimed` by someone else go last.
+    fn from_summary(
+        summary: &trueflow_core::commands::review::ReviewSummary,
+        risk: &HashMap<String, BlockRisk>,
+        claimed: impl Fn(&str) -> bool,
+    ) -> Self {
         let mut ordered = Vec::new();
         let mut items: Vec<_> = summary
             .unreviewed_block_nodes
@@ -349,6 +478,17 @@ impl ReviewOrder {
                     b_cursor.start_line,
                 ))
         });
+        if !risk.is_empty() {
+            items.sort_by_key(|(_, node)| {
+                std::cmp::Reverse(
+                    node.block
+                        .as_ref()
+                        .and_then(|block| risk.get(&block.hash))
+                        .map_or(0, |risk| risk.score),
+                )
+            });
+        }
+        items.sort_by_cached_key(|(cursor, _)| claimed(&cursor.file_path));
 
         for (cursor, _) in items {
             ordered.push(cursor);
@@ -361,42 +501,107 @@ impl ReviewOrder {
         self.ordered.first().map(|cursor| cursor.node_id)
     }
 
+    /// Moves `node_id` to the end of the order.
+    fn defer(&mut self, node_id: TreeNodeId) {
+        if let Some(index) = self.position(node_id) {
+            let cursor = self.ordered.remove(index);
+            self.ordered.push(cursor);
+        }
+    }
+
+    fn position(&self, node_id: TreeNodeId) -> Option<usize> {
+        self.ordered
+            .iter()
+            .position(|cursor| cursor.node_id == node_id)
+    }
+
+    /// The blocks from `a` to `b` (either way round) still in `remaining`.
+    fn between(
+        &self,
+        a: TreeNodeId,
+        b: TreeNodeId,
+        remaining: &HashSet<TreeNodeId>,
+    ) -> Vec<TreeNodeId> {
+        let (Some(a), Some(b)) = (self.position(a), self.position(b)) else {
+            return Vec::new();
+        };
+        self.ordered[a.min(b)..=a.max(b)]
+            .iter()
+            .map(|cursor| cursor.node_id)
+            .filter(|id| remaining.contains(id))
+            .collect()
+    }
+
+    /// The block in `remaining` right after (or before) `current`.
+    fn step(
+        &self,
+        current: TreeNodeId,
+        forward: bool,
+        remaining: &HashSet<TreeNodeId>,
+    ) -> Option<TreeNodeId> {
+        let index = self.position(current)?;
+        let found = if forward {
+            self.ordered[index + 1..]
+                .iter()
+                .find(|cursor| remaining.contains(&cursor.node_id))
+        } else {
+            self.ordered[..index]
+                .iter()
+                .rev()
+                .find(|cursor| remaining.contains(&cursor.node_id))
+        };
+        found.map(|cursor| cursor.node_id)
+    }
+
     fn next_after_blocks(
         &self,
         current: TreeNodeId,
         remaining: &HashSet<TreeNodeId>,
+        claimed: impl Fn(&str) -> bool,
     ) -> Option<TreeNodeId> {
         let index = self
             .ordered
             .iter()
             .position(|cursor| cursor.node_id == current)?;
-        self.ordered
+        let candidates = self
+            .ordered
             .iter()
             .skip(index + 1)
-            .find(|cursor| remaining.contains(&cursor.node_id))
-            .map(|cursor| cursor.node_id)
+            .filter(|cursor| remaining.contains(&cursor.node_id));
+        unclaimed_first(candidates, claimed)
     }
 
     fn next_after_subtree(
         &self,
         subtree_blocks: &HashSet<TreeNodeId>,
         remaining: &HashSet<TreeNodeId>,
+        claimed: impl Fn(&str) -> bool,
     ) -> Option<TreeNodeId> {
         let start_index = self
             .ordered
             .iter()
             .position(|cursor| subtree_blocks.contains(&cursor.node_id))?;
 
-        self.ordered
-            .iter()
-            .skip(start_index + 1)
-            .find(|cursor| {
-                remaining.contains(&cursor.node_id) && !subtree_blocks.contains(&cursor.node_id)
-            })
-            .map(|cursor| cursor.node_id)
+        let candidates = self.ordered.iter().skip(start_index + 1).filter(|cursor| {
+            remaining.contains(&cursor.node_id) && !subtree_blocks.contains(&cursor.node_id)
+        });
+        unclaimed_first(candidates, claimed)
     }
 }
 
+/// The first candidate in a file nobody else has claimed since the order
+/// was built, else the first candidate.
+fn unclaimed_first<'a>(
+    candidates: impl Iterator<Item = &'a ReviewCursor> + Clone,
+    claimed: impl Fn(&str) -> bool,
+) -> Option<TreeNodeId> {
+    candidates
+        .clone()
+        .find(|cursor| !claimed(&cursor.file_path))
+        .or_else(|| candidates.clone().next())
+        .map(|cursor| cursor.node_id)
+}
+
 // --- Application Logic ---
 
 #[derive(Clone, PartialEq)]
@@ -411,6 +616,12 @@ enum PendingAction {
         verdict: Verdict,
         note: Option<String>,
     },
+    /// The blocks of a selection (`v`), in review order; never empty.
+    Range {
+        node_ids: Vec<TreeNodeId>,
+        verdict: Verdict,
+        note: Option<String>,
+    },
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -473,14 +684,30 @@ impl PendingAction {
                 verdict: verdict.clone(),
                 note: Some(note),
             },
+            PendingAction::Range {
+                node_ids, verdict, ..
+            } => PendingAction::Range {
+                node_ids: node_ids.clone(),
+                verdict: verdict.clone(),
+                note: Some(note),
+            },
         }
     }
 
-    fn verdict_label(&self) -> &'static str {
+    fn node_id(&self) -> TreeNodeId {
         match self {
-            PendingAction::Single { verdict, .. } | PendingAction::Batch { verdict, .. } => {
-                verdict.as_str()
+            PendingAction::Single { node_id, .. } | PendingAction::Batch { node_id, .. } => {
+                *node_id
             }
+            PendingAction::Range { node_ids, .. } => node_ids[0],
+        }
+    }
+
+    fn verdict_label(&self) -> &'static str {
+        match self {
+            PendingAction::Single { verdict, .. }
+            | PendingAction::Batch { verdict, .. }
+            | PendingAction::Range { verdict, .. } => verdict.as_str(),
         }
     }
 }
@@ -496,6 +723,10 @@ enum InputMode {
         action: PendingAction,
         count: usize,
     },
+    /// Typing a `/` query; the text is in `input_buffer`.
+    Search,
+    /// The `?` keybinding overlay; any key closes it.
+    Help,
 }
 
 struct AppState {
@@ -515,36 +746,275 @@ struct AppState {
     scroll_offset: u16,
     content_height: u16,
     viewport_height: u16,
+    claims: ClaimBoard,
+    /// Approved blocks still waiting for a CODEOWNERS owner.
+    owner_review: HashSet<String>,
+    /// The approved version of blocks edited since, by block hash.
+    previous_approvals: HashMap<String, PreviousApproval>,
+    /// Discussion rows (depth, text) on blocks in scope, by block hash.
+    threads: HashMap<String, Vec<(usize, String)>>,
+    /// Discussion rows left on earlier versions of blocks in scope, by the
+    /// hash of the block now in their place.
+    earlier: HashMap<String, Vec<(usize, String)>>,
+    /// The latest `trueflow prereview` annotation per block hash.
+    prereview: HashMap<String, PrereviewHint>,
+    /// Risk scores by block hash, with `[review] order = "risk"`.
+    risk: HashMap<String, BlockRisk>,
+    /// The commits a `rev:` scope covers, oldest first.
+    scope_commits: Vec<CommitInfo>,
+    /// Which of `scope_commits` wrote each block, by block hash.
+    block_commits: HashMap<String, Vec<CommitInfo>>,
+    /// Fingerprints approved when the session started; splitting a block
+    /// leaves their sub-blocks out.
+    approved: HashSet<String>,
+    /// Blocks split into sub-blocks (`s`); their children are the sub-blocks.
+    split: HashSet<TreeNodeId>,
+    scope: ReviewScope,
+    /// The last comment left with Esc; `c` on its node picks it up again.
+    draft: Option<CommentDraft>,
+    undo_stack: Vec<UndoEntry>,
+    /// Where the selection (`v`) started; it runs to the cursor in review order.
+    selection_anchor: Option<TreeNodeId>,
+    /// Blocks put off with `defer`, oldest first; they sit at the end of
+    /// `review_order`.
+    deferred: Vec<TreeNodeId>,
+    /// Show blocks side by side with their version on main (`d`).
+    diff_view: bool,
+    /// Aligned old/new lines per file; `None` when there is nothing to compare.
+    diff_cache: HashMap<String, Option<Vec<DiffRow>>>,
+    /// Who wrote each file's lines, blamed when one of its blocks is first
+    /// shown; `None` when the file can't be blamed.
+    blame_cache: HashMap<String, Option<FileBlame>>,
+    /// File paths and block signatures `/` searches, with their nodes.
+    search_index: Vec<(TreeNodeId, String)>,
+    /// The last search; `n`/`N` cycle through its matches.
+    search: Option<SearchResults>,
+    keymap: Keymap,
+    palette: UiPalette,
+    /// Clickable screen areas from the last render.
+    click_targets: Vec<(Rect, ClickTarget)>,
+    /// Content lines that list a node, by line index, for `click_targets`.
+    content_targets: Vec<(usize, TreeNodeId)>,
+    /// Whether blocks show only the first line of each sub-block.
+    folded: bool,
+    /// Lines each block hides when folded, by block hash, relative to the
+    /// block's first line.
+    fold_cache: HashMap<String, Vec<Range<usize>>>,
 }
 
-pub fn run(context: &TrueflowContext) -> Result<()> {
-    let mut terminal = setup_terminal()?;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ClickTarget {
+    Node(TreeNodeId),
+    Action(Action),
+}
+
+struct SearchResults {
+    query: String,
+    matches: Vec<TreeNodeId>,
+    current: usize,
+}
+
+/// A verdict given in this session, kept so `u` can take it back.
+struct UndoEntry {
+    /// The records written, with the node each is on.
+    records: Vec<(String, TreeNodeId)>,
+    /// Where the cursor goes back to.
+    node_id: TreeNodeId,
+    /// Blocks the verdict hid from the tree.
+    hidden: Vec<TreeNodeId>,
+    /// Of those, the ones counted in `remaining_blocks`.
+    reviewable: Vec<TreeNodeId>,
+}
+
+/// Claims the file under the cursor and tracks what others have claimed.
+struct ClaimBoard {
+    me: Identity,
+    /// How long our claims last; `None` under `[team] claims = false`.
+    ttl_secs: Option<i64>,
+    active: ActiveClaims,
+    assignments: Assignments,
+    workdir_prefix: Option<String>,
+}
+
+impl ClaimBoard {
+    fn load(me: Identity, team: &TeamConfig) -> Self {
+        let mut board = Self {
+            me,
+            ttl_secs: team
+                .claims
+                .then_some(i64::from(team.claim_ttl_minutes) * 60),
+            active: ActiveClaims::default(),
+            assignments: Assignments::default(),
+            workdir_prefix: workdir_prefix_from_root(),
+        };
+        if let Err(err) = board.reload() {
+            warn!("Failed to load claims: {}", err);
+        }
+        board
+    }
+
+    fn reload(&mut self) -> Result<()> {
+        let history = open_store()?.read_history()?;
+        if self.ttl_secs.is_some() {
+            self.active = active_claims(&history, unix_now());
+        }
+        self.assignments = latest_assignments(&history);
+        Ok(())
+    }
+
+    fn repo_path(&self, path: &str) -> String {
+        repo_relative_path(path, self.workdir_prefix.as_deref())
+    }
+
+    fn held_by_other(&self, path: &str) -> Option<String> {
+        let claim = self.active.held_by_other(&self.repo_path(path), &self.me)?;
+        Some(format!(
+            "Claimed by {} ({} left)",
+            claim.holder,
+            format_remaining(claim.expires_at, unix_now())
+        ))
+    }
+
+    fn is_held_by_other(&self, path: &str) -> bool {
+        self.active
+            .held_by_other(&self.repo_path(path), &self.me)
+            .is_some()
+    }
+
+    /// Who `assign` or `request` handed the node to, if anyone.
+    fn assigned(&self, path: &str, fingerprint: &str) -> Option<String> {
+        let assignees = self
+            .assignments
+            .assignees_for(&self.repo_path(path), fingerprint);
+        if assignees.is_empty() {
+            return None;
+        }
+        let names: Vec<String> = assignees
+            .iter()
+            .map(|assignee| {
+                if *assignee == self.me {
+                    format!("{assignee} (you)")
+                } else {
+                    assignee.to_string()
+                }
+            })
+            .collect();
+        Some(format!("Assigned to {}", names.join(", ")))
+    }
+
+    /// Writes a claim unless ours still has at least half its TTL left.
+    fn claim(&mut self, path: &str) -> Result<()> {
+        let Some(ttl_secs) = self.ttl_secs else {
+            return Ok(());
+        };
+        let repo_path = self.repo_path(path);
+        let now = unix_now();
+        if self
+            .active
+            .held_by_me_until(&repo_path, &self.me, now + ttl_secs / 2)
+        {
+            return Ok(());
+        }
+        let repo_ref = mark::repo_ref_from_snapshot(&vcs::snapshot_from_workdir());
+        let record = claim_record(&self.me, repo_ref, &repo_path, ttl_secs, now);
+        // Claims are ephemeral, so they are not signed.
+        open_store()?.append(record)?;
+        self.reload()
+    }
+}
+
+fn unix_now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn claim_current_file(state: &mut AppState) {
+    let node = state.navigator.tree.node(state.navigator.current_id());
+    if !matches!(node.kind, TreeNodeKind::File | TreeNodeKind::Block) || node.path.is_empty() {
+        return;
+    }
+    let path = node.path.clone();
+    if let Err(err) = state.claims.claim(&path) {
+        warn!("Failed to claim {}: {}", path, err);
+    }
+}
+
+pub fn run(context: &TrueflowContext, targets: &[String]) -> Result<()> {
+    let targets = parse_review_targets(targets)?;
     let config = load_config()?;
+    let keymap = Keymap::from_config(&config.tui.keys)?;
+    let palette = UiPalette::from_config(&config.tui)?;
+    let mut terminal = setup_terminal()?;
     let run_result = (|| {
-        let scope_options = load_scope_options()?;
-        let selection = run_scope_selector(&mut terminal, ScopeSelector::new(scope_options))?;
-
-        match selection {
-            ScopeSelection::Quit => Ok(()),
-            ScopeSelection::Selected(scope) => {
-                let filters = config.review.resolve_filters(&[], &[]);
-                let summary = load_review_state(context, &scope, &filters)?;
-                let state =
-                    build_review_state(context, summary, config.tui.confirm_batch, scope.label())?;
-                run_app(context, &mut terminal, state)
-            }
+        let selection = if targets.is_empty() {
+            let scope_options = load_scope_options(context)?;
+            run_scope_selector(&mut terminal, ScopeSelector::new(scope_options), &palette)?
+        } else {
+            ScopeSelection::Selected(ReviewScope::Targets(targets))
+        };
+
+        let (scope, resume) = match selection {
+            ScopeSelection::Quit => return Ok(()),
+            ScopeSelection::Selected(scope) => (scope, None),
+            ScopeSelection::Resume(saved) => (saved.scope.clone(), Some(saved)),
+        };
+        let filters = config.review.resolve_filters(&[], &[]);
+        terminal.draw(|f| render_loading(f, &scope.label(), &palette))?;
+        let mut summary = load_review_state(context, &scope, &filters)?;
+        let mut label = scope.label();
+        let auto_approved = approve_enabled_rules(&summary.files, &config)?;
+        if auto_approved.total() > 0 {
+            label = format!("{} (auto-approved {})", label, auto_approved.total());
+            summary = load_review_state(context, &scope, &filters)?;
+        }
+        let mut state =
+            build_review_state(context, summary, &config, keymap, palette, scope, label)?;
+        if let Some(saved) = &resume {
+            restore_session(&mut state, saved);
         }
+        run_app(context, &mut terminal, state)?;
+        if let Err(err) = record_session_end(context, &filters) {
+            warn!("Failed to record review session: {}", err);
+        }
+        Ok(())
     })();
     restore_terminal(&mut terminal)?;
     run_result
 }
 
+/// Remembers what is still pending so `review --since-last` can show only
+/// what arrives afterwards.
+fn record_session_end(context: &TrueflowContext, filters: &BlockFilters) -> Result<()> {
+    let mut options = ReviewScope::All.to_review_options();
+    options.reviewer = default_reviewer(context, &load_config()?.policy)?;
+    let files = collect_unreviewed(&options, filters)?;
+    SessionSnapshot::from_files(&files, unix_now()).save()
+}
+
 fn build_review_state(
     context: &TrueflowContext,
-    summary: crate::commands::review::ReviewSummary,
-    confirm_batch: bool,
+    mut summary: trueflow_core::commands::review::ReviewSummary,
+    config: &TrueflowConfig,
+    keymap: Keymap,
+    palette: UiPalette,
+    scope: ReviewScope,
     scope_label: String,
 ) -> Result<AppState> {
+    let scorer = match config.review.order {
+        ReviewOrderMode::Risk => Some(RiskScorer::new(&config.risk)?),
+        ReviewOrderMode::File => None,
+    };
+    if !summary.commits.is_empty() || scorer.is_some() {
+        annotate_blocks(&mut summary.files, &summary.commits, scorer.as_ref());
+    }
+    let risk: HashMap<String, BlockRisk> = summary
+        .files
+        .iter()
+        .flat_map(|file| &file.blocks)
+        .filter_map(|block| Some((block.hash.clone(), block.risk?)))
+        .collect();
     let reviewable_nodes: HashSet<TreeNodeId> = summary
         .unreviewed_block_nodes
         .iter()
@@ -556,8 +1026,39 @@ fn build_review_state(
     let root_children = summary.tree.node(summary.tree.root()).children.clone();
     let root_cursor = root_children.first().copied();
 
-    let review_order = ReviewOrder::from_summary(&summary);
+    let claims = ClaimBoard::load(CurrentIdentity::resolve(context)?.identity, &config.team);
+    let review_order =
+        ReviewOrder::from_summary(&summary, &risk, |path| claims.is_held_by_other(path));
+    let owner_review = summary
+        .files
+        .iter()
+        .flat_map(|file| &file.blocks)
+        .filter(|block| block.tags.iter().any(|tag| tag == OWNER_REVIEW_TAG))
+        .map(|block| block.hash.clone())
+        .collect();
+    let approved = approved_hashes_from_verdicts(&summary.review_state);
+    let previous_approvals = summary
+        .files
+        .iter()
+        .flat_map(|file| &file.blocks)
+        .filter_map(|block| Some((block.hash.clone(), block.previous.clone()?)))
+        .collect();
+    let mut history = without_revoked(open_store()?.read_history()?);
+    let prereview = latest_hints(&history);
+    // Annotations get a row of their own rather than a thread.
+    history.retain(|record| !is_prereview(record));
+    let discussions = thread::by_block(&history);
+    let threads = thread_rows(&summary, &discussions);
+    let earlier = earlier_rows(&summary, &discussions);
+    let block_commits = summary
+        .files
+        .iter()
+        .flat_map(|file| &file.blocks)
+        .filter(|block| !block.commits.is_empty())
+        .map(|block| (block.hash.clone(), block.commits.clone()))
+        .collect();
     let navigator = ReviewNavigator::new(summary.tree, summary.unreviewed_block_nodes)?;
+    let search_index = build_search_index(&navigator);
 
     Ok(AppState {
         navigator,
@@ -568,7 +1069,7 @@ fn build_review_state(
         scope_label,
         input_mode: InputMode::Normal,
         input_buffer: String::new(),
-        confirm_batch,
+        confirm_batch: config.tui.confirm_batch,
         repo_name: detect_repo_name(context),
         last_frame: std::time::Instant::now(),
         file_cache: HashMap::new(),
@@ -576,22 +1077,245 @@ fn build_review_state(
         scroll_offset: 0,
         content_height: 0,
         viewport_height: 0,
+        claims,
+        owner_review,
+        previous_approvals,
+        threads,
+        earlier,
+        prereview,
+        risk,
+        scope_commits: summary.commits,
+        block_commits,
+        approved,
+        split: HashSet::new(),
+        scope,
+        draft: None,
+        undo_stack: Vec::new(),
+        selection_anchor: None,
+        deferred: Vec::new(),
+        diff_view: false,
+        diff_cache: HashMap::new(),
+        blame_cache: HashMap::new(),
+        search_index,
+        search: None,
+        keymap,
+        palette,
+        click_targets: Vec::new(),
+        content_targets: Vec::new(),
+        folded: false,
+        fold_cache: HashMap::new(),
     })
 }
 
-fn load_scope_options() -> Result<Vec<ScopeOption>> {
-    let mut options = vec![
-        ScopeOption {
-            label: "All files".to_string(),
-            scope: ReviewScope::All,
-        },
-        ScopeOption {
-            label: "Diff vs main".to_string(),
-            scope: ReviewScope::MainDiff,
+/// The discussion on each block of `summary`, by block hash.
+fn thread_rows(
+    summary: &trueflow_core::commands::review::ReviewSummary,
+    discussions: &HashMap<String, Vec<ThreadNode>>,
+) -> HashMap<String, Vec<(usize, String)>> {
+    summary
+        .files
+        .iter()
+        .flat_map(|file| &file.blocks)
+        .filter_map(|block| {
+            let threads = discussions.get(&block.hash)?;
+            Some((block.hash.clone(), discussion_rows(threads)))
+        })
+        .collect()
+}
+
+/// What was said about earlier versions of each block of `summary`, by
+/// block hash: threads on fingerprints no longer in the file whose line
+/// falls inside the block. Threads opened by an approval are left out; the
+/// "Changed since your approval" row covers those.
+fn earlier_rows(
+    summary: &trueflow_core::commands::review::ReviewSummary,
+    discussions: &HashMap<String, Vec<ThreadNode>>,
+) -> HashMap<String, Vec<(usize, String)>> {
+    let mut by_path: HashMap<&str, Vec<&ThreadNode>> = HashMap::new();
+    for thread in discussions.values().flatten() {
+        let record = &thread.record;
+        if record.kind.is_verdict() && record.verdict == Verdict::Approved {
+            continue;
+        }
+        if let (Some(path), Some(_)) = (&record.path_hint, record.line_hint) {
+            by_path.entry(path).or_default().push(thread);
+        }
+    }
+    if by_path.is_empty() {
+        return HashMap::new();
+    }
+
+    let workdir_prefix = workdir_prefix_from_root();
+    let mut rows = HashMap::new();
+    for file in &summary.files {
+        let repo_path = repo_relative_path(&file.path, workdir_prefix.as_deref());
+        let mut paths = vec![file.path.as_str()];
+        if repo_path != file.path {
+            paths.push(&repo_path);
+        }
+        let mut earlier: Vec<&ThreadNode> = paths
+            .iter()
+            .filter_map(|path| by_path.get(path))
+            .flatten()
+            .copied()
+            .filter(|thread| {
+                summary
+                    .tree
+                    .node_by_path_and_hash(&file.path, &thread.record.fingerprint)
+                    .is_none()
+            })
+            .collect();
+        earlier.sort_by_key(|thread| thread.record.timestamp);
+        for block in &file.blocks {
+            // Hints are 0-based from the TUI and 1-based from `mark --line`.
+            let lines = block.start_line..=block.end_line;
+            let here: Vec<&ThreadNode> = earlier
+                .iter()
+                .copied()
+                .filter(|thread| {
+                    thread
+                        .record
+                        .line_hint
+                        .is_some_and(|line| lines.contains(&(line as usize)))
+                })
+                .collect();
+            if !here.is_empty() {
+                rows.insert(block.hash.clone(), discussion_rows(here));
+            }
+        }
+    }
+    rows
+}
+
+/// One row (depth, text) per record in `threads`: who, what verdict, and
+/// the first line of the note.
+fn discussion_rows<'a>(threads: impl IntoIterator<Item = &'a ThreadNode>) -> Vec<(usize, String)> {
+    threads
+        .into_iter()
+        .flat_map(|thread| thread.flatten())
+        .map(|(depth, record)| {
+            let note = record.note.as_deref().and_then(|note| note.lines().next());
+            let text = match note {
+                Some(note) => format!("{} {}: {}", record.identity, thread::label(record), note),
+                None => format!("{} {}", record.identity, thread::label(record)),
+            };
+            (depth, text)
+        })
+        .collect()
+}
+
+/// Puts the cursor back where the saved session left it and reopens its
+/// comment draft. Nodes reviewed since then are skipped.
+fn restore_session(state: &mut AppState, saved: &SavedSession) {
+    let visible = |state: &AppState, key: &NodeKey| {
+        key.find(&state.navigator.tree)
+            .filter(|id| state.navigator.visible_nodes.contains(id))
+    };
+    for key in &saved.deferred {
+        if let Some(id) = visible(state, key)
+            && state.reviewable_nodes.contains(&id)
+        {
+            state.review_order.defer(id);
+            state.deferred.push(id);
+        }
+    }
+    if let Some(id) = saved.cursor.as_ref().and_then(|key| visible(state, key)) {
+        state.navigator.set_current(id);
+    }
+    let Some(draft) = &saved.draft else {
+        return;
+    };
+    let Some(id) = visible(state, &draft.node) else {
+        return;
+    };
+    state.draft = Some(draft.clone());
+    if id == state.navigator.current_id() {
+        state.input_mode = InputMode::Editing {
+            action: PendingAction::from_node(&state.navigator.tree, id, Verdict::Comment),
+        };
+        state.input_buffer = draft.text.clone();
+    }
+}
+
+/// Saves the scope, cursor, draft and deferred blocks for "Resume previous session", or
+/// forgets them once nothing is left to review.
+fn save_session(state: &AppState) -> Result<()> {
+    if state.remaining_blocks == 0 {
+        return remove_json(RESUME_FILE);
+    }
+    save_json(RESUME_FILE, &session(state))
+}
+
+/// Where the review stands, by node keys that outlast the tree.
+fn session(state: &AppState) -> SavedSession {
+    let tree = &state.navigator.tree;
+    SavedSession {
+        scope: state.scope.clone(),
+        cursor: Some(NodeKey::of(tree, state.navigator.current_id())),
+        draft: state.draft.clone(),
+        deferred: pending_deferred(state)
+            .map(|id| NodeKey::of(tree, id))
+            .collect(),
+    }
+}
+
+fn load_saved_session() -> Option<SavedSession> {
+    load_json(RESUME_FILE).unwrap_or_else(|err| {
+        warn!("Ignoring saved TUI session: {}", err);
+        None
+    })
+}
+
+fn load_scope_options(context: &TrueflowContext) -> Result<Vec<ScopeOption>> {
+    let mut options = Vec::new();
+    if let Some(saved) = load_saved_session() {
+        options.push(ScopeOption {
+            label: format!("Resume previous session ({})", saved.scope.label()),
+            scope: saved.scope.clone(),
+            resume: Some(saved),
+        });
+    }
+    let provider = vcs_provider::detect();
+    options.push(ScopeOption {
+        label: "All files".to_string(),
+        scope: ReviewScope::All,
+        resume: None,
+    });
+    // Base diffs and the index are git concepts; outside git only files remain.
+    if provider
+        .as_ref()
+        .is_some_and(|provider| provider.system() == VcsSystem::Git)
+    {
+        options.extend([
+            ScopeOption {
+                label: format!("Diff vs {}", vcs::base_label()),
+                scope: ReviewScope::MainDiff,
+                resume: None,
+            },
+            ScopeOption {
+                label: "Staged changes".to_string(),
+                scope: ReviewScope::Targets(vec![ReviewTarget::Staged]),
+                resume: None,
+            },
+        ]);
+    }
+    options.push(ScopeOption {
+        label: "Assigned to me".to_string(),
+        scope: ReviewScope::Mine {
+            assignee: CurrentIdentity::resolve(context)?.identity,
         },
-    ];
+        resume: None,
+    });
+
+    for dir in scanner::top_level_dirs(Path::new(".")) {
+        options.push(ScopeOption {
+            label: format!("Directory {dir}/"),
+            scope: ReviewScope::Targets(vec![ReviewTarget::Directory(dir)]),
+            resume: None,
+        });
+    }
 
-    if let Ok(commits) = vcs::recent_commits(8) {
+    if let Some(Ok(commits)) = provider.map(|provider| provider.recent_commits(8)) {
         for commit in commits {
             options.push(commit_scope_option(commit));
         }
@@ -614,6 +1338,7 @@ fn commit_scope_option(commit: vcs::CommitInfo) -> ScopeOption {
             id: commit.id,
             summary: commit.summary,
         },
+        resume: None,
     }
 }
 
@@ -644,7 +1369,7 @@ fn truncate_text(text: &str, max_chars: usize) -> String {
 fn setup_terminal() -> Result<Terminal<ratatui::backend::CrosstermBackend<Stdout>>> {
     let mut stdout = io::stdout();
     enable_raw_mode()?;
-    execute!(stdout, EnterAlternateScreen)?;
+    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
     let backend = ratatui::backend::CrosstermBackend::new(stdout);
     Ok(Terminal::new(backend)?)
 }
@@ -653,21 +1378,114 @@ fn restore_terminal(
     terminal: &mut Terminal<ratatui::backend::CrosstermBackend<Stdout>>,
 ) -> Result<()> {
     disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    execute!(
+        terminal.backend_mut(),
+        DisableMouseCapture,
+        LeaveAlternateScreen
+    )?;
     terminal.show_cursor()?;
     Ok(())
 }
 
+/// Whether the terminal has a dark background, from `COLORFGBG` or else
+/// by asking the terminal for its background color (OSC 11).
+fn terminal_background_is_dark() -> Option<bool> {
+    if let Ok(value) = std::env::var("COLORFGBG")
+        && let Some(dark) = colorfgbg_is_dark(&value)
+    {
+        return Some(dark);
+    }
+    query_background().and_then(|reply| osc11_is_dark(&reply))
+}
+
+/// `COLORFGBG` is `fg;bg` (sometimes `fg;default;bg`) in ANSI color numbers.
+fn colorfgbg_is_dark(value: &str) -> Option<bool> {
+    let background: u8 = value.rsplit(';').next()?.trim().parse().ok()?;
+    Some(matches!(background, 0..=6 | 8))
+}
+
+/// Parses a `rgb:RRRR/GGGG/BBBB` reply; components are 1 to 4 hex digits.
+fn osc11_is_dark(reply: &str) -> Option<bool> {
+    let start = reply.find("rgb:")? + 4;
+    let channels: Vec<f64> = reply[start..]
+        .split(['/', '\x07', '\x1b'])
+        .take(3)
+        .map(|hex| {
+            let value = u32::from_str_radix(hex, 16).ok()?;
+            let max = 16u32.checked_pow(hex.len() as u32)?.checked_sub(1)?;
+            (max > 0).then(|| value as f64 / max as f64)
+        })
+        .collect::<Option<_>>()?;
+    let [r, g, b] = channels[..] else {
+        return None;
+    };
+    Some(0.2126 * r + 0.7152 * g + 0.0722 * b < 0.5)
+}
+
+/// How long to wait for the terminal to answer; most never do if they
+/// don't support the query.
+#[cfg(unix)]
+const BACKGROUND_QUERY_TIMEOUT_MS: i32 = 100;
+
+#[cfg(unix)]
+fn query_background() -> Option<String> {
+    use std::io::{Read, Write};
+    use std::os::fd::AsRawFd;
+
+    let mut tty = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/tty")
+        .ok()?;
+    enable_raw_mode().ok()?;
+    let reply = (|| {
+        tty.write_all(b"\x1b]11;?\x1b\\").ok()?;
+        tty.flush().ok()?;
+        let deadline = std::time::Instant::now()
+            + std::time::Duration::from_millis(BACKGROUND_QUERY_TIMEOUT_MS as u64);
+        let mut reply = Vec::new();
+        let mut buf = [0u8; 64];
+        // Read until BEL or ST ends the reply.
+        while !reply.ends_with(b"\x07") && !reply.ends_with(b"\x1b\\") {
+            let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+            let mut fd = libc::pollfd {
+                fd: tty.as_raw_fd(),
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            // SAFETY: `fd` is a single valid pollfd for the duration of the call.
+            let ready = unsafe { libc::poll(&mut fd, 1, remaining.as_millis() as i32) };
+            if ready <= 0 {
+                return None;
+            }
+            let read = tty.read(&mut buf).ok()?;
+            if read == 0 {
+                return None;
+            }
+            reply.extend_from_slice(&buf[..read]);
+        }
+        Some(String::from_utf8_lossy(&reply).into_owned())
+    })();
+    let _ = disable_raw_mode();
+    reply
+}
+
+#[cfg(not(unix))]
+fn query_background() -> Option<String> {
+    None
+}
+
 fn run_scope_selector(
     terminal: &mut Terminal<ratatui::backend::CrosstermBackend<Stdout>>,
     mut selector: ScopeSelector,
+    palette: &UiPalette,
 ) -> Result<ScopeSelection> {
     let mut needs_render = true;
     let mut last_frame = std::time::Instant::now();
 
     loop {
         if needs_render || last_frame.elapsed().as_millis() >= 250 {
-            terminal.draw(|f| render_scope_selector(f, &selector))?;
+            terminal.draw(|f| render_scope_selector(f, &selector, palette))?;
             last_frame = std::time::Instant::now();
             needs_render = false;
         }
@@ -687,8 +1505,8 @@ fn run_scope_selector(
                     needs_render = true;
                 }
                 KeyCode::Enter => {
-                    if let Some(scope) = selector.selected_scope() {
-                        return Ok(ScopeSelection::Selected(scope));
+                    if let Some(selection) = selector.selection() {
+                        return Ok(selection);
                     }
                 }
                 _ => {}
@@ -711,120 +1529,499 @@ fn run_app(
             needs_render = false;
         }
 
-        if event::poll(std::time::Duration::from_millis(16))?
-            && let Event::Key(key) = event::read()?
-            && key.kind == KeyEventKind::Press
-        {
-            match &state.input_mode {
-                InputMode::Normal => match key.code {
-                    KeyCode::Char('q') => return Ok(()),
-                    KeyCode::Char('k') | KeyCode::Down => {
-                        handle_descend(&mut state);
-                        needs_render = true;
-                    }
-                    KeyCode::Char('i') | KeyCode::Up => {
-                        handle_ascend(&mut state);
-                        needs_render = true;
-                    }
-                    KeyCode::Char('l') | KeyCode::Right => {
-                        handle_next(&mut state);
-                        needs_render = true;
-                    }
-                    KeyCode::Char('j') | KeyCode::Left => {
-                        handle_prev(&mut state);
+        if !event::poll(std::time::Duration::from_millis(16))? {
+            continue;
+        }
+        match event::read()? {
+            Event::Mouse(mouse) if state.input_mode == InputMode::Normal => {
+                match handle_mouse(terminal, context, &mut state, mouse)? {
+                    KeyOutcome::Skipped => {}
+                    KeyOutcome::Handled => {
                         needs_render = true;
+                        claim_current_file(&mut state);
                     }
-                    KeyCode::Char('n') => {
-                        handle_next(&mut state);
-                        needs_render = true;
+                    KeyOutcome::Quit => return Ok(()),
+                }
+            }
+            Event::Key(key) if key.kind == KeyEventKind::Press => {
+                match &state.input_mode {
+                    InputMode::Normal => {
+                        let actions: Vec<Action> =
+                            state.keymap.actions(KeyBinding::from(key)).collect();
+                        for action in actions {
+                            match handle_key_action(terminal, context, &mut state, action)? {
+                                KeyOutcome::Skipped => continue,
+                                KeyOutcome::Handled => {
+                                    needs_render = true;
+                                    break;
+                                }
+                                KeyOutcome::Quit => return Ok(()),
+                            }
+                        }
                     }
-                    KeyCode::Char('b') => {
-                        handle_prev(&mut state);
+                    InputMode::Help => {
+                        state.input_mode = InputMode::Normal;
                         needs_render = true;
                     }
-                    KeyCode::Char('a') => {
-                        handle_action(terminal, context, &mut state, Verdict::Approved)?;
-                        needs_render = true;
-                    }
-                    KeyCode::Char('x') => {
-                        handle_action(terminal, context, &mut state, Verdict::Rejected)?;
-                        needs_render = true;
-                    }
-                    KeyCode::Char('c') => {
-                        handle_comment_action(&mut state)?;
-                        needs_render = true;
-                    }
-                    KeyCode::Char(' ') if state.navigator.current_id() != state.navigator.tree.root() => {
-                        handle_scroll_page_down(&mut state);
-                        needs_render = true;
-                    }
-                    KeyCode::PageUp => {
-                        handle_scroll_page_up(&mut state);
-                        needs_render = true;
-                    }
-                    KeyCode::PageDown => {
-                        handle_scroll_page_down(&mut state);
-                        needs_render = true;
-                    }
-                    KeyCode::Home => {
-                        state.scroll_offset = 0;
-                        needs_render = true;
-                    }
-                    KeyCode::End => {
-                        state.scroll_offset = state.content_height.saturating_sub(state.viewport_height);
-                        needs_render = true;
-                    }
-                    KeyCode::Char('g') => {
-                        state.navigator.jump_root();
-                        needs_render = true;
-                    }
-                    KeyCode::Enter | KeyCode::Char(' ')
-                        if state.navigator.current_id() == state.navigator.tree.root() =>
-                    {
-                        if let Some(first) = state.review_order.first_block() {
-                            state.navigator.set_current(first);
+                    InputMode::Editing { .. } => match key.code {
+                        KeyCode::Enter => {
+                            handle_editing_submit(terminal, context, &mut state)?;
+                            needs_render = true;
                         }
-                        needs_render = true;
-                    }
-                    _ => {}
-                },
-                InputMode::Editing { .. } => match key.code {
-                    KeyCode::Enter => {
-                        handle_editing_submit(terminal, context, &mut state)?;
-                        needs_render = true;
-                    }
-                    KeyCode::Esc => {
-                        handle_editing_cancel(&mut state);
-                        needs_render = true;
-                    }
-                    KeyCode::Backspace => {
-                        state.input_buffer.pop();
-                        needs_render = true;
-                    }
-                    KeyCode::Char(c) => {
-                        state.input_buffer.push(c);
-                        needs_render = true;
-                    }
-                    _ => {}
-                },
-                InputMode::ConfirmBatch { .. } => match key.code {
-                    KeyCode::Enter => {
-                        handle_confirm_batch(terminal, context, &mut state)?;
-                        needs_render = true;
-                    }
-                    KeyCode::Esc => {
-                        handle_confirm_cancel(&mut state);
-                        needs_render = true;
+                        KeyCode::Esc => {
+                            handle_editing_cancel(&mut state);
+                            needs_render = true;
+                        }
+                        KeyCode::Backspace => {
+                            state.input_buffer.pop();
+                            needs_render = true;
+                        }
+                        KeyCode::Char(c) => {
+                            state.input_buffer.push(c);
+                            needs_render = true;
+                        }
+                        _ => {}
+                    },
+                    InputMode::Search => match key.code {
+                        KeyCode::Enter => {
+                            handle_search_submit(&mut state);
+                            needs_render = true;
+                        }
+                        KeyCode::Esc => {
+                            state.input_mode = InputMode::Normal;
+                            state.input_buffer.clear();
+                            needs_render = true;
+                        }
+                        KeyCode::Backspace => {
+                            state.input_buffer.pop();
+                            needs_render = true;
+                        }
+                        KeyCode::Char(c) => {
+                            state.input_buffer.push(c);
+                            needs_render = true;
+                        }
+                        _ => {}
+                    },
+                    InputMode::ConfirmBatch { .. } => match key.code {
+                        KeyCode::Enter => {
+                            handle_confirm_batch(terminal, context, &mut state)?;
+                            needs_render = true;
+                        }
+                        KeyCode::Esc => {
+                            handle_confirm_cancel(&mut state);
+                            needs_render = true;
+                        }
+                        _ => {}
+                    },
+                }
+                claim_current_file(&mut state);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Lines the scroll wheel moves the code pane.
+const MOUSE_SCROLL_LINES: u16 = 3;
+
+/// The wheel scrolls the code pane; a left click follows whatever
+/// `click_targets` has under the pointer.
+fn handle_mouse(
+    terminal: &mut Terminal<ratatui::backend::CrosstermBackend<Stdout>>,
+    context: &TrueflowContext,
+    state: &mut AppState,
+    mouse: MouseEvent,
+) -> Result<KeyOutcome> {
+    match mouse.kind {
+        MouseEventKind::ScrollDown => {
+            let max = state.content_height.saturating_sub(state.viewport_height);
+            state.scroll_offset = (state.scroll_offset + MOUSE_SCROLL_LINES).min(max);
+        }
+        MouseEventKind::ScrollUp => {
+            state.scroll_offset = state.scroll_offset.saturating_sub(MOUSE_SCROLL_LINES);
+        }
+        MouseEventKind::Down(MouseButton::Left) => {
+            let pointer = Position::new(mouse.column, mouse.row);
+            let target = state
+                .click_targets
+                .iter()
+                .find(|(area, _)| area.contains(pointer))
+                .map(|(_, target)| *target);
+            match target {
+                Some(ClickTarget::Node(id)) => {
+                    if state.navigator.current_id() == state.navigator.tree.root() {
+                        state.root_cursor = Some(id);
                     }
-                    _ => {}
-                },
+                    state.navigator.set_current(id);
+                    state.scroll_offset = 0;
+                }
+                Some(ClickTarget::Action(action)) => {
+                    return handle_key_action(terminal, context, state, action);
+                }
+                None => return Ok(KeyOutcome::Skipped),
+            }
+        }
+        _ => return Ok(KeyOutcome::Skipped),
+    }
+    Ok(KeyOutcome::Handled)
+}
+
+enum KeyOutcome {
+    Handled,
+    /// The action doesn't apply here; try the next one bound to the key.
+    Skipped,
+    Quit,
+}
+
+fn handle_key_action(
+    terminal: &mut Terminal<ratatui::backend::CrosstermBackend<Stdout>>,
+    context: &TrueflowContext,
+    state: &mut AppState,
+    action: Action,
+) -> Result<KeyOutcome> {
+    let at_root = state.navigator.current_id() == state.navigator.tree.root();
+    match action {
+        Action::Quit => {
+            if let Err(err) = save_session(state) {
+                warn!("Failed to save TUI session: {}", err);
+            }
+            return Ok(KeyOutcome::Quit);
+        }
+        Action::Descend => {
+            state.selection_anchor = None;
+            handle_descend(state);
+        }
+        Action::Ascend => {
+            state.selection_anchor = None;
+            handle_ascend(state);
+        }
+        Action::Next if state.selection_anchor.is_some() => extend_selection(state, true),
+        Action::Prev if state.selection_anchor.is_some() => extend_selection(state, false),
+        Action::Next => handle_next(state),
+        Action::Prev => handle_prev(state),
+        Action::Select if state.selection_anchor.is_some() => state.selection_anchor = None,
+        Action::Select => {
+            let current = state.navigator.current_id();
+            if state.review_order.position(current).is_none()
+                || !state.reviewable_nodes.contains(&current)
+            {
+                return Ok(KeyOutcome::Skipped);
+            }
+            state.selection_anchor = Some(current);
+        }
+        Action::ClearSearch if state.selection_anchor.is_some() => state.selection_anchor = None,
+        Action::Defer => {
+            if !handle_defer(state) {
+                return Ok(KeyOutcome::Skipped);
+            }
+        }
+        Action::Edit => {
+            if !handle_edit(terminal, context, state)? {
+                return Ok(KeyOutcome::Skipped);
+            }
+        }
+        Action::SearchNext | Action::SearchPrev | Action::ClearSearch if state.search.is_none() => {
+            return Ok(KeyOutcome::Skipped);
+        }
+        Action::SearchNext => cycle_search(state, 1),
+        Action::SearchPrev => cycle_search(state, -1),
+        Action::ClearSearch => state.search = None,
+        Action::Search => {
+            state.input_mode = InputMode::Search;
+            state.input_buffer.clear();
+        }
+        Action::Approve => handle_action(terminal, context, state, Verdict::Approved)?,
+        Action::Reject => handle_action(terminal, context, state, Verdict::Rejected)?,
+        Action::Comment => handle_note_action(state, Verdict::Comment),
+        Action::Question => handle_note_action(state, Verdict::Question),
+        Action::Undo => handle_undo(terminal, context, state)?,
+        Action::Diff => {
+            state.diff_view = !state.diff_view;
+            state.scroll_offset = 0;
+        }
+        Action::Start if !at_root => return Ok(KeyOutcome::Skipped),
+        Action::Start => {
+            if let Some(first) = state.review_order.first_block() {
+                state.navigator.set_current(first);
             }
         }
+        Action::PageDown => handle_scroll_page_down(state),
+        Action::PageUp => handle_scroll_page_up(state),
+        Action::HalfPageDown => handle_scroll_half_page(state, true),
+        Action::HalfPageUp => handle_scroll_half_page(state, false),
+        Action::Fold => {
+            state.folded = !state.folded;
+            state.scroll_offset = 0;
+        }
+        Action::Split => handle_split(state)?,
+        Action::Top => state.scroll_offset = 0,
+        Action::Bottom => {
+            state.scroll_offset = state.content_height.saturating_sub(state.viewport_height);
+        }
+        Action::Root => {
+            state.selection_anchor = None;
+            state.navigator.jump_root();
+        }
+        Action::Help => state.input_mode = InputMode::Help,
     }
+    Ok(KeyOutcome::Handled)
 }
 
 // ... helper functions for actions ...
 
+/// Splits the current block into its sub-blocks and moves to the first, so
+/// each can be approved or rejected on its own. A block with children
+/// already is just entered.
+fn handle_split(state: &mut AppState) -> Result<()> {
+    let current = state.navigator.current_id();
+    let node = state.navigator.tree.node(current);
+    if !matches!(node.kind, TreeNodeKind::Block) {
+        return Ok(());
+    }
+    if node.children.is_empty() {
+        let (Some(block), Some(language)) = (&node.block, &node.language) else {
+            return Ok(());
+        };
+        let filters = load_config()?.review.resolve_filters(&[], &[]);
+        let sub_blocks: Vec<Block> = sub_splitter::split(block, language.clone())?
+            .into_iter()
+            .filter(|sub| filters.allows_subblock(&sub.kind) && !state.approved.contains(&sub.hash))
+            .collect();
+        let whole =
+            |sub: &Block| sub.start_line == block.start_line && sub.end_line == block.end_line;
+        if sub_blocks.is_empty() || (sub_blocks.len() == 1 && whole(&sub_blocks[0])) {
+            return Ok(());
+        }
+        let ids = state.navigator.tree.add_sub_blocks(current, sub_blocks);
+        state.navigator.visible_nodes.extend(ids);
+        state.split.insert(current);
+    }
+    state.navigator.descend();
+    state.scroll_offset = 0;
+    Ok(())
+}
+
+/// Sends the current block to the end of the review order and moves on, so
+/// hard blocks can wait without a verdict. False when the cursor is not on
+/// an unreviewed block.
+fn handle_defer(state: &mut AppState) -> bool {
+    let current = state.navigator.current_id();
+    if state.review_order.position(current).is_none() || !state.reviewable_nodes.contains(&current)
+    {
+        return false;
+    }
+    state.selection_anchor = None;
+    let next = compute_next_review_target(state, current);
+    state.review_order.defer(current);
+    state.deferred.retain(|id| *id != current);
+    state.deferred.push(current);
+    if let Some(next) = next {
+        state.navigator.set_current(next);
+        state.scroll_offset = 0;
+    }
+    true
+}
+
+/// Deferred blocks still waiting for a verdict.
+fn pending_deferred(state: &AppState) -> impl Iterator<Item = TreeNodeId> + '_ {
+    state
+        .deferred
+        .iter()
+        .copied()
+        .filter(|id| state.reviewable_nodes.contains(id))
+}
+
+/// Opens the file under the cursor in the editor at the block's first line,
+/// then reloads the review so the edit shows. False when the cursor is not
+/// on a file or block.
+fn handle_edit(
+    terminal: &mut Terminal<ratatui::backend::CrosstermBackend<Stdout>>,
+    context: &TrueflowContext,
+    state: &mut AppState,
+) -> Result<bool> {
+    let node = state.navigator.tree.node(state.navigator.current_id());
+    if !matches!(node.kind, TreeNodeKind::File | TreeNodeKind::Block) || node.path.is_empty() {
+        return Ok(false);
+    }
+    let line = node.block.as_ref().map_or(1, |block| block.start_line + 1);
+    let path = node.path.clone();
+    // A failed or aborted edit still leaves the review running.
+    if let Err(err) = with_terminal_suspend(terminal, || Ok(open_in_editor(&path, line)))? {
+        warn!("{}", err);
+    }
+    reload_review(context, state)?;
+    Ok(true)
+}
+
+/// Runs `$VISUAL`, else `$EDITOR`, else `vi` on `path` at `line`.
+fn open_in_editor(path: &str, line: usize) -> Result<()> {
+    let command = ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|command| !command.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_EDITOR.to_string());
+    let mut parts = command.split_whitespace();
+    let program = parts.next().unwrap_or(DEFAULT_EDITOR);
+    let status = Command::new(program)
+        .args(parts)
+        .arg(format!("+{line}"))
+        .arg(path)
+        .status()
+        .map_err(|err| anyhow!("Failed to start editor '{}': {}", command, err))?;
+    if !status.success() {
+        bail!("Editor '{}' exited with {}", command, status);
+    }
+    Ok(())
+}
+
+/// Rebuilds the review from the working tree, keeping the cursor, draft,
+/// deferred blocks, undo history and view toggles. The scan cache means
+/// only files changed since are read again.
+fn reload_review(context: &TrueflowContext, state: &mut AppState) -> Result<()> {
+    let config = load_config()?;
+    let filters = config.review.resolve_filters(&[], &[]);
+    let summary = load_review_state(context, &state.scope, &filters)?;
+    let mut fresh = build_review_state(
+        context,
+        summary,
+        &config,
+        state.keymap.clone(),
+        state.palette.clone(),
+        state.scope.clone(),
+        state.scope_label.clone(),
+    )?;
+
+    let old_tree = &state.navigator.tree;
+    let old_cursor = state.navigator.current_id();
+    restore_session(
+        &mut fresh,
+        &SavedSession {
+            draft: None,
+            ..session(state)
+        },
+    );
+    // An edited block has a new hash; stay on whatever block now holds
+    // its first line.
+    let old_node = old_tree.node(old_cursor);
+    let landed = fresh.navigator.tree.node(fresh.navigator.current_id());
+    if let Some(block) = &old_node.block
+        && !matches!(landed.kind, TreeNodeKind::Block)
+        && let Some(id) = block_at_line(&fresh.navigator, &old_node.path, block.start_line)
+    {
+        fresh.navigator.set_current(id);
+    }
+
+    let remap = |id: TreeNodeId| NodeKey::of(old_tree, id).find(&fresh.navigator.tree);
+    fresh.undo_stack = std::mem::take(&mut state.undo_stack)
+        .into_iter()
+        .filter_map(|entry| {
+            let ids = |ids: &[TreeNodeId]| -> Option<Vec<TreeNodeId>> {
+                ids.iter().map(|&id| remap(id)).collect()
+            };
+            Some(UndoEntry {
+                records: entry
+                    .records
+                    .iter()
+                    .map(|(record, id)| Some((record.clone(), remap(*id)?)))
+                    .collect::<Option<_>>()?,
+                node_id: remap(entry.node_id)?,
+                hidden: ids(&entry.hidden)?,
+                reviewable: ids(&entry.reviewable)?,
+            })
+        })
+        .collect();
+    fresh.draft = state.draft.take();
+    fresh.diff_view = state.diff_view;
+    fresh.folded = state.folded;
+    *state = fresh;
+    Ok(())
+}
+
+/// The visible block in the file at `path` that spans `line`, innermost
+/// first.
+fn block_at_line(navigator: &ReviewNavigator, path: &str, line: usize) -> Option<TreeNodeId> {
+    let mut found = None;
+    let mut stack = vec![navigator.tree.find_by_path(path)?];
+    while let Some(id) = stack.pop() {
+        for &child in &navigator.tree.node(id).children {
+            let Some(block) = &navigator.tree.node(child).block else {
+                continue;
+            };
+            if navigator.visible_nodes.contains(&child)
+                && (block.start_line..block.end_line.max(block.start_line + 1)).contains(&line)
+            {
+                found = Some(child);
+                stack.push(child);
+            }
+        }
+    }
+    found
+}
+
+/// Moves the cursor to the next (or previous) unreviewed block in review
+/// order, taking the selection along.
+fn extend_selection(state: &mut AppState, forward: bool) {
+    let current = state.navigator.current_id();
+    if let Some(next) = state
+        .review_order
+        .step(current, forward, &state.reviewable_nodes)
+    {
+        state.navigator.set_current(next);
+        state.scroll_offset = 0;
+    }
+}
+
+/// The unreviewed blocks between the selection's anchor and the cursor.
+fn selected_blocks(state: &AppState) -> Vec<TreeNodeId> {
+    state
+        .selection_anchor
+        .map(|anchor| {
+            state.review_order.between(
+                anchor,
+                state.navigator.current_id(),
+                &state.reviewable_nodes,
+            )
+        })
+        .unwrap_or_default()
+}
+
+/// What a verdict key applies to: the selection, else the current node.
+fn pending_action(state: &AppState, verdict: Verdict) -> PendingAction {
+    let node_ids = selected_blocks(state);
+    if node_ids.is_empty() {
+        PendingAction::from_node(&state.navigator.tree, state.navigator.current_id(), verdict)
+    } else {
+        PendingAction::Range {
+            node_ids,
+            verdict,
+            note: None,
+        }
+    }
+}
+
+/// Asks before a verdict on more than one block, when `confirm_batch` is on.
+fn confirm_or_execute(
+    terminal: &mut Terminal<ratatui::backend::CrosstermBackend<Stdout>>,
+    context: &TrueflowContext,
+    state: &mut AppState,
+    action: PendingAction,
+) -> Result<()> {
+    let count = match &action {
+        PendingAction::Single { .. } => None,
+        PendingAction::Batch { node_id, .. } => {
+            Some(count_descendant_blocks(&state.navigator, *node_id))
+        }
+        PendingAction::Range { node_ids, .. } => Some(node_ids.len()),
+    };
+    match count {
+        Some(count) if state.confirm_batch => {
+            state.input_mode = InputMode::ConfirmBatch { action, count };
+            Ok(())
+        }
+        _ => execute_action(terminal, context, state, action),
+    }
+}
+
 fn handle_ascend(state: &mut AppState) {
     if state.navigator.current_id() == state.navigator.tree.root() {
         return;
@@ -878,6 +2075,81 @@ fn handle_next(state: &mut AppState) {
     }
 }
 
+/// Labels for every file and block that started out unreviewed.
+fn build_search_index(navigator: &ReviewNavigator) -> Vec<(TreeNodeId, String)> {
+    let mut index: Vec<(TreeNodeId, String)> = navigator
+        .visible_nodes
+        .iter()
+        .filter_map(|&id| {
+            let node = navigator.tree.node(id);
+            match (&node.kind, &node.block) {
+                (TreeNodeKind::File, _) => Some((id, node.path.clone())),
+                (TreeNodeKind::Block, Some(block)) => {
+                    Some((id, format!("{} {}", node.path, block_signature(block))))
+                }
+                _ => None,
+            }
+        })
+        .collect();
+    index.sort_by(|a, b| a.1.cmp(&b.1));
+    index
+}
+
+/// Nodes still in the tree whose label matches `query`, best first.
+fn search_matches<'a>(state: &'a AppState, query: &str) -> Vec<(TreeNodeId, &'a str)> {
+    let mut scored: Vec<(i64, TreeNodeId, &str)> = state
+        .search_index
+        .iter()
+        .filter(|(id, _)| state.navigator.visible_nodes.contains(id))
+        .filter_map(|(id, label)| Some((fuzzy_score(query, label)?, *id, label.as_str())))
+        .collect();
+    scored.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.2.cmp(b.2)));
+    scored
+        .into_iter()
+        .map(|(_, id, label)| (id, label))
+        .collect()
+}
+
+fn handle_search_submit(state: &mut AppState) {
+    let query = state.input_buffer.trim().to_string();
+    state.input_mode = InputMode::Normal;
+    state.input_buffer.clear();
+    if query.is_empty() {
+        state.search = None;
+        return;
+    }
+    let matches: Vec<TreeNodeId> = search_matches(state, &query)
+        .into_iter()
+        .map(|(id, _)| id)
+        .collect();
+    if let Some(&first) = matches.first() {
+        state.navigator.set_current(first);
+        state.scroll_offset = 0;
+    }
+    state.search = Some(SearchResults {
+        query,
+        matches,
+        current: 0,
+    });
+}
+
+/// Jumps `offset` matches along, wrapping, skipping nodes reviewed since.
+fn cycle_search(state: &mut AppState, offset: isize) {
+    let Some(search) = &mut state.search else {
+        return;
+    };
+    search
+        .matches
+        .retain(|id| state.navigator.visible_nodes.contains(id));
+    if search.matches.is_empty() {
+        return;
+    }
+    let len = search.matches.len() as isize;
+    search.current = (search.current as isize + offset).rem_euclid(len) as usize;
+    state.navigator.set_current(search.matches[search.current]);
+    state.scroll_offset = 0;
+}
+
 fn handle_scroll_page_up(state: &mut AppState) {
     let scroll_amount = state.viewport_height.saturating_sub(1);
     state.scroll_offset = state.scroll_offset.saturating_sub(scroll_amount);
@@ -889,6 +2161,16 @@ fn handle_scroll_page_down(state: &mut AppState) {
     state.scroll_offset = (state.scroll_offset + scroll_amount).min(max_scroll);
 }
 
+fn handle_scroll_half_page(state: &mut AppState, down: bool) {
+    let scroll_amount = (state.viewport_height / 2).max(1);
+    let max_scroll = state.content_height.saturating_sub(state.viewport_height);
+    state.scroll_offset = if down {
+        (state.scroll_offset + scroll_amount).min(max_scroll)
+    } else {
+        state.scroll_offset.saturating_sub(scroll_amount)
+    };
+}
+
 fn move_root_cursor(state: &mut AppState, offset: isize) {
     let root = state.navigator.tree.root();
     let root_children: Vec<TreeNodeId> = state
@@ -920,27 +2202,20 @@ fn handle_action(
     state: &mut AppState,
     verdict: Verdict,
 ) -> Result<()> {
-    let action =
-        PendingAction::from_node(&state.navigator.tree, state.navigator.current_id(), verdict);
-
-    if matches!(action, PendingAction::Batch { .. }) && state.confirm_batch {
-        let count = count_descendant_blocks(&state.navigator, state.navigator.current_id());
-        state.input_mode = InputMode::ConfirmBatch { action, count };
-    } else {
-        execute_action(terminal, context, state, action)?;
-    }
-    Ok(())
+    let action = pending_action(state, verdict);
+    confirm_or_execute(terminal, context, state, action)
 }
 
-fn handle_comment_action(state: &mut AppState) -> Result<()> {
-    let action = PendingAction::from_node(
-        &state.navigator.tree,
-        state.navigator.current_id(),
-        Verdict::Comment,
-    );
+/// Opens the note box for a comment or a question on the current node.
+fn handle_note_action(state: &mut AppState, verdict: Verdict) {
+    let current = state.navigator.current_id();
+    let action = pending_action(state, verdict);
     state.input_mode = InputMode::Editing { action };
-    state.input_buffer.clear();
-    Ok(())
+    let key = NodeKey::of(&state.navigator.tree, current);
+    state.input_buffer = match &state.draft {
+        Some(draft) if draft.node == key => draft.text.clone(),
+        _ => String::new(),
+    };
 }
 
 fn handle_editing_submit(
@@ -962,24 +2237,24 @@ fn handle_editing_submit(
 
     state.input_mode = InputMode::Normal;
     state.input_buffer.clear();
-
-    if matches!(action, PendingAction::Batch { .. }) && state.confirm_batch {
-        let count = count_descendant_blocks(
-            &state.navigator,
-            match &action {
-                PendingAction::Single { node_id, .. } | PendingAction::Batch { node_id, .. } => {
-                    *node_id
-                }
-            },
-        );
-        state.input_mode = InputMode::ConfirmBatch { action, count };
-    } else {
-        execute_action(terminal, context, state, action)?;
+    let key = NodeKey::of(&state.navigator.tree, action.node_id());
+    if state.draft.as_ref().is_some_and(|draft| draft.node == key) {
+        state.draft = None;
     }
-    Ok(())
+
+    confirm_or_execute(terminal, context, state, action)
 }
 
+/// Leaves the comment box, keeping any text as the draft for that node.
 fn handle_editing_cancel(state: &mut AppState) {
+    if let InputMode::Editing { action } = &state.input_mode
+        && !state.input_buffer.trim().is_empty()
+    {
+        state.draft = Some(CommentDraft {
+            node: NodeKey::of(&state.navigator.tree, action.node_id()),
+            text: state.input_buffer.clone(),
+        });
+    }
     state.input_mode = InputMode::Normal;
     state.input_buffer.clear();
 }
@@ -1007,7 +2282,7 @@ fn execute_action(
     state: &mut AppState,
     action: PendingAction,
 ) -> Result<()> {
-    let (node_id, verdict, note) = match action {
+    let (node_ids, verdict, note) = match action {
         PendingAction::Single {
             node_id,
             verdict,
@@ -1017,58 +2292,130 @@ fn execute_action(
             node_id,
             verdict,
             note,
-        } => (node_id, verdict, note),
+        } => (vec![node_id], verdict, note),
+        PendingAction::Range {
+            node_ids,
+            verdict,
+            note,
+        } => (node_ids, verdict, note),
     };
+    state.selection_anchor = None;
 
-    let next_id = compute_next_review_target(state, node_id);
+    // A range is in review order, so the review continues after its last block.
+    let next_id = compute_next_review_target(state, node_ids[node_ids.len() - 1]);
 
-    with_terminal_suspend(terminal, || {
-        let node = state.navigator.tree.node(node_id);
-        let fingerprint = match node.kind {
-            TreeNodeKind::Root => "root".to_string(), // Or repo hash?
-            TreeNodeKind::Directory => node.hash.clone(),
-            TreeNodeKind::File => node.hash.clone(),
-            TreeNodeKind::Block => node.hash.clone(),
-        };
+    let record_ids = with_terminal_suspend(terminal, || {
+        let batch = node_ids
+            .iter()
+            .map(|&node_id| {
+                let node = state.navigator.tree.node(node_id);
+                mark::MarkParams {
+                    fingerprint: action_fingerprint(node),
+                    verdict: verdict.clone(),
+                    check: "review".to_string(),
+                    note: note.clone(),
+                    path: action_path_hint(node),
+                    line: node.block.as_ref().map(|block| block.start_line as u32),
+                    provenance: None,
+                    confidence: None,
+                    refs: Vec::new(),
+                    in_reply_to: None,
+                    sign: false,
+                }
+            })
+            .collect();
+        mark::record_all(context, batch)
+    })?;
 
-        // For root/dir, path might be empty or a dir path.
-        // For file/block, it's the file path.
-        let path_hint = if node.path.is_empty() {
-            None
-        } else {
-            Some(node.path.clone())
-        };
+    let (hidden, reviewable) = apply_action_locally(state, &node_ids, &verdict, next_id);
+    state.undo_stack.push(UndoEntry {
+        records: record_ids
+            .into_iter()
+            .zip(node_ids.iter().copied())
+            .collect(),
+        node_id: node_ids[0],
+        hidden,
+        reviewable,
+    });
+    Ok(())
+}
+
+fn action_fingerprint(node: &trueflow_core::tree::TreeNode) -> String {
+    match node.kind {
+        TreeNodeKind::Root => "root".to_string(), // Or repo hash?
+        TreeNodeKind::Directory => node.hash.clone(),
+        TreeNodeKind::File => node.hash.clone(),
+        TreeNodeKind::Block => node.hash.clone(),
+    }
+}
 
-        let line_hint = node.block.as_ref().map(|block| block.start_line as u32);
+/// For root/dir, path might be empty or a dir path.
+/// For file/block, it's the file path.
+fn action_path_hint(node: &trueflow_core::tree::TreeNode) -> Option<String> {
+    if node.path.is_empty() {
+        None
+    } else {
+        Some(node.path.clone())
+    }
+}
 
-        mark::run(
-            context,
-            mark::MarkParams {
-                fingerprint,
-                verdict: verdict.clone(),
-                check: "review".to_string(),
-                note,
-                path: path_hint,
-                line: line_hint,
-            },
-        )
+/// Revokes the last verdict of this session and puts the blocks it hid
+/// back into the tree and the review queue.
+fn handle_undo(
+    terminal: &mut Terminal<ratatui::backend::CrosstermBackend<Stdout>>,
+    context: &TrueflowContext,
+    state: &mut AppState,
+) -> Result<()> {
+    let Some(entry) = state.undo_stack.pop() else {
+        return Ok(());
+    };
+    with_terminal_suspend(terminal, || {
+        for (record_id, node_id) in &entry.records {
+            let node = state.navigator.tree.node(*node_id);
+            mark::revoke(
+                context,
+                record_id,
+                &action_fingerprint(node),
+                action_path_hint(node),
+            )?;
+        }
+        Ok(())
     })?;
 
-    apply_action_locally(state, node_id, &verdict, next_id);
+    for &block_id in &entry.hidden {
+        state.navigator.visible_nodes.insert(block_id);
+        for ancestor in state.navigator.tree.ancestors(block_id) {
+            state.navigator.visible_nodes.insert(ancestor);
+        }
+    }
+    state
+        .reviewable_nodes
+        .extend(entry.reviewable.iter().copied());
+    state.remaining_blocks += entry.reviewable.len();
+    state.navigator.set_current(entry.node_id);
+    state.scroll_offset = 0;
     Ok(())
 }
 
-fn with_terminal_suspend<F>(
+fn with_terminal_suspend<F, T>(
     terminal: &mut Terminal<ratatui::backend::CrosstermBackend<Stdout>>,
     action: F,
-) -> Result<()>
+) -> Result<T>
 where
-    F: FnOnce() -> Result<()>,
+    F: FnOnce() -> Result<T>,
 {
     disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    execute!(
+        terminal.backend_mut(),
+        DisableMouseCapture,
+        LeaveAlternateScreen
+    )?;
     let result = action();
-    execute!(terminal.backend_mut(), EnterAlternateScreen)?;
+    execute!(
+        terminal.backend_mut(),
+        EnterAlternateScreen,
+        EnableMouseCapture
+    )?;
     enable_raw_mode()?;
     terminal.clear()?;
     result
@@ -1078,29 +2425,28 @@ fn load_review_state(
     context: &TrueflowContext,
     scope: &ReviewScope,
     filters: &BlockFilters,
-) -> Result<crate::commands::review::ReviewSummary> {
-    let options = scope.to_review_options();
-    collect_review_summary(context, &options, filters)
+) -> Result<trueflow_core::commands::review::ReviewSummary> {
+    let mut options = scope.to_review_options();
+    options.reviewer = default_reviewer(context, &load_config()?.policy)?;
+    collect_review_summary(&options, filters)
 }
 
+/// Hides the blocks a verdict settles and moves on. Returns the blocks it
+/// hid, and those of them that were still counted as remaining.
 fn apply_action_locally(
     state: &mut AppState,
-    node_id: TreeNodeId,
+    node_ids: &[TreeNodeId],
     verdict: &Verdict,
     next_id: Option<TreeNodeId>,
-) {
-    let block_ids = collect_block_ids_for_action(state, node_id);
+) -> (Vec<TreeNodeId>, Vec<TreeNodeId>) {
+    let mut hidden = Vec::new();
+    let mut reviewable = Vec::new();
 
     if matches!(verdict, Verdict::Approved | Verdict::Rejected) {
-        let mut removed_reviewable = 0;
-        for block_id in block_ids {
-            if state.navigator.visible_nodes.remove(&block_id) {
-                if state.reviewable_nodes.remove(&block_id) {
-                    removed_reviewable += 1;
-                }
-            }
+        for &node_id in node_ids {
+            hide_settled(state, node_id, &mut hidden, &mut reviewable);
         }
-        state.remaining_blocks = state.remaining_blocks.saturating_sub(removed_reviewable);
+        state.remaining_blocks = state.remaining_blocks.saturating_sub(reviewable.len());
     }
 
     prune_invisible_ancestors(state);
@@ -1112,29 +2458,66 @@ fn apply_action_locally(
         state.navigator.jump_root();
         state.scroll_offset = 0;
     }
+    (hidden, reviewable)
 }
 
-fn collect_block_ids_for_action(state: &AppState, node_id: TreeNodeId) -> Vec<TreeNodeId> {
-    let node = state.navigator.tree.node(node_id);
-    match node.kind {
-        TreeNodeKind::Block => {
-            if node
-                .block
-                .as_ref()
-                .is_some_and(|block| matches!(block.kind, BlockKind::Impl | BlockKind::Interface))
-            {
-                state.navigator.block_ids_in_subtree(node_id)
-            } else {
-                vec![node_id]
+/// Hides the blocks a verdict on `node_id` settles, noting them in `hidden`
+/// and those still counted as remaining in `reviewable`.
+fn hide_settled(
+    state: &mut AppState,
+    node_id: TreeNodeId,
+    hidden: &mut Vec<TreeNodeId>,
+    reviewable: &mut Vec<TreeNodeId>,
+) {
+    for block_id in collect_block_ids_for_action(state, node_id) {
+        if state.navigator.visible_nodes.remove(&block_id) {
+            hidden.push(block_id);
+            if state.reviewable_nodes.remove(&block_id) {
+                reviewable.push(block_id);
+            }
+        }
+    }
+    // A split block is settled once each of its sub-blocks is.
+    let mut child = node_id;
+    while let Some(parent) = state.navigator.tree.parent(child)
+        && state.split.contains(&parent)
+        && !state
+            .navigator
+            .tree
+            .node(parent)
+            .children
+            .iter()
+            .any(|id| state.navigator.visible_nodes.contains(id))
+    {
+        if state.navigator.visible_nodes.remove(&parent) {
+            hidden.push(parent);
+            if state.reviewable_nodes.remove(&parent) {
+                reviewable.push(parent);
             }
         }
+        child = parent;
+    }
+}
+
+fn collect_block_ids_for_action(state: &AppState, node_id: TreeNodeId) -> Vec<TreeNodeId> {
+    let node = state.navigator.tree.node(node_id);
+    match node.kind {
+        // A block's children are an impl's items or the parts it was split into.
+        TreeNodeKind::Block if node.children.is_empty() => vec![node_id],
         _ => state.navigator.block_ids_in_subtree(node_id),
     }
 }
 
 fn compute_next_review_target(state: &AppState, node_id: TreeNodeId) -> Option<TreeNodeId> {
+    if let Some(parent) = state.navigator.tree.parent(node_id)
+        && state.split.contains(&parent)
+    {
+        return next_sub_block(state, parent, node_id)
+            .or_else(|| compute_next_review_target(state, parent));
+    }
     let node = state.navigator.tree.node(node_id);
     let remaining = &state.reviewable_nodes;
+    let claimed = |path: &str| state.claims.is_held_by_other(path);
     match node.kind {
         TreeNodeKind::Block => {
             if node
@@ -1149,9 +2532,11 @@ fn compute_next_review_target(state: &AppState, node_id: TreeNodeId) -> Option<T
                     .collect();
                 state
                     .review_order
-                    .next_after_subtree(&subtree_blocks, remaining)
+                    .next_after_subtree(&subtree_blocks, remaining, claimed)
             } else {
-                state.review_order.next_after_blocks(node_id, remaining)
+                state
+                    .review_order
+                    .next_after_blocks(node_id, remaining, claimed)
             }
         }
         _ => {
@@ -1162,11 +2547,23 @@ fn compute_next_review_target(state: &AppState, node_id: TreeNodeId) -> Option<T
                 .collect();
             state
                 .review_order
-                .next_after_subtree(&subtree_blocks, remaining)
+                .next_after_subtree(&subtree_blocks, remaining, claimed)
         }
     }
 }
 
+/// The next sub-block of `parent` still waiting after `node_id`, wrapping
+/// around to any skipped earlier.
+fn next_sub_block(state: &AppState, parent: TreeNodeId, node_id: TreeNodeId) -> Option<TreeNodeId> {
+    let siblings = &state.navigator.tree.node(parent).children;
+    let index = siblings.iter().position(|&id| id == node_id)?;
+    siblings[index + 1..]
+        .iter()
+        .chain(&siblings[..index])
+        .copied()
+        .find(|id| state.navigator.visible_nodes.contains(id))
+}
+
 fn prune_invisible_ancestors(state: &mut AppState) {
     let mut visible_nodes = HashSet::new();
     for node_id in state
@@ -1217,8 +2614,7 @@ fn count_descendant_blocks(navigator: &ReviewNavigator, id: TreeNodeId) -> usize
 
 // --- UI Rendering ---
 
-fn render_scope_selector(frame: &mut Frame, selector: &ScopeSelector) {
-    let palette = UiPalette::default();
+fn render_scope_selector(frame: &mut Frame, selector: &ScopeSelector, palette: &UiPalette) {
     let area = frame.area();
 
     frame.render_widget(
@@ -1273,8 +2669,26 @@ fn render_scope_selector(frame: &mut Frame, selector: &ScopeSelector) {
     );
 }
 
+/// Shown while the selected scope is scanned; the scanner's progress bar draws
+/// on stderr below it.
+fn render_loading(frame: &mut Frame, scope_label: &str, palette: &UiPalette) {
+    let area = frame.area();
+    frame.render_widget(
+        UiBlock::default().style(Style::default().bg(palette.bg)),
+        area,
+    );
+    frame.render_widget(
+        Paragraph::new(Line::from(Span::styled(
+            format!("Loading {scope_label}..."),
+            Style::default().fg(palette.dim).bg(palette.bg),
+        )))
+        .alignment(Alignment::Center),
+        centered_rect(area, 70, 20),
+    );
+}
+
 fn ui(frame: &mut Frame, state: &mut AppState) {
-    let palette = UiPalette::default();
+    let palette = state.palette.clone();
     let area = frame.area();
 
     // 1. Background
@@ -1303,24 +2717,43 @@ fn ui(frame: &mut Frame, state: &mut AppState) {
     ) {
         render_input_overlay(frame, state, area, &palette);
     }
+    if state.input_mode == InputMode::Search {
+        render_search_overlay(frame, state, area, &palette);
+    }
+    if state.input_mode == InputMode::Help {
+        render_help_overlay(frame, state, area, &palette);
+    }
 }
 
 fn render_active_node(frame: &mut Frame, state: &mut AppState, area: Rect, palette: &UiPalette) {
-    let node = state.navigator.tree.node(state.navigator.current_id());
+    let current = state.navigator.current_id();
+    if matches!(state.navigator.tree.node(current).kind, TreeNodeKind::Block) {
+        let path = state.navigator.tree.node(current).path.clone();
+        load_blame(state, &path);
+    }
+    let node = state.navigator.tree.node(current);
 
     let header_lines = build_header_lines(node, state, palette);
 
     let focus_layout = compute_focus_layout(area, header_lines.len() as u16);
-    let actions_lines = build_action_lines(focus_layout.actions.width, palette);
+    let action_rows = build_action_rows(focus_layout.actions.width, &state.keymap);
+    let actions_lines = action_lines(&action_rows, palette);
     let node_snapshot = node.clone();
+    state.click_targets.clear();
+    state.content_targets.clear();
     let (content_lines, total_lines) =
-        build_content_lines(state, &node_snapshot, palette, focus_layout.code.height);
+        if state.diff_view && matches!(node_snapshot.kind, TreeNodeKind::Block) {
+            build_diff_lines(state, &node_snapshot, palette, focus_layout.code.width)
+        } else {
+            build_content_lines(state, &node_snapshot, palette, focus_layout.code.height)
+        };
 
     state.content_height = total_lines as u16;
     state.viewport_height = focus_layout.code.height;
     state.scroll_offset = state
         .scroll_offset
         .min(state.content_height.saturating_sub(state.viewport_height));
+    register_click_targets(state, &focus_layout, &action_rows);
 
     let meta_block = UiBlock::default()
         .borders(ratatui::widgets::Borders::ALL)
@@ -1365,8 +2798,18 @@ fn render_active_node(frame: &mut Frame, state: &mut AppState, area: Rect, palet
     frame.render_widget(actions_paragraph, focus_layout.actions);
 }
 
+/// Discussion rows shown above a block before pointing at `trueflow thread`.
+const MAX_THREAD_ROWS: usize = 6;
+/// Risk scores from here up are shown in bold.
+const HIGH_RISK: u8 = 60;
+/// Commits listed above a block, and on the root screen.
+const MAX_COMMIT_ROWS: usize = 3;
+const MAX_SCOPE_COMMITS: usize = 10;
+/// Message body lines shown under each commit on the root screen.
+const MAX_COMMIT_BODY_LINES: usize = 6;
+
 fn build_header_lines(
-    node: &crate::tree::TreeNode,
+    node: &trueflow_core::tree::TreeNode,
     state: &AppState,
     palette: &UiPalette,
 ) -> Vec<Line<'static>> {
@@ -1394,6 +2837,17 @@ fn build_header_lines(
 
     lines.push(format_header_row(&header_text, palette, true));
 
+    if state.selection_anchor.is_some() {
+        lines.push(format_header_row(
+            &format!(
+                "Selected {} block(s): a verdict applies to all, esc drops the selection",
+                selected_blocks(state).len()
+            ),
+            palette,
+            true,
+        ));
+    }
+
     if matches!(node.kind, TreeNodeKind::Block)
         && let Some(breadcrumb) = build_block_breadcrumb(node, state)
     {
@@ -1407,6 +2861,110 @@ fn build_header_lines(
         lines.push(format_header_row(&node.path, palette, false));
     }
 
+    if !matches!(node.kind, TreeNodeKind::Root)
+        && !node.path.is_empty()
+        && let Some(claimed) = state.claims.held_by_other(&node.path)
+    {
+        lines.push(format_header_row(&claimed, palette, true));
+    }
+
+    if !matches!(node.kind, TreeNodeKind::Root)
+        && !node.path.is_empty()
+        && let Some(assigned) = state.claims.assigned(&node.path, &node.hash)
+    {
+        lines.push(format_header_row(&assigned, palette, false));
+    }
+
+    if state.deferred.contains(&node.id) {
+        lines.push(format_header_row("Deferred earlier", palette, false));
+    }
+
+    if matches!(node.kind, TreeNodeKind::Block) && state.owner_review.contains(&node.hash) {
+        lines.push(format_header_row("Needs owner review", palette, true));
+    }
+
+    if matches!(node.kind, TreeNodeKind::Block)
+        && let Some(previous) = state.previous_approvals.get(&node.hash)
+    {
+        lines.push(format_header_row(
+            &format!(
+                "Changed since your approval at {} (d to compare)",
+                previous.short_revision()
+            ),
+            palette,
+            true,
+        ));
+    }
+
+    if let Some(block) = &node.block
+        && let Some(Some(blame)) = state.blame_cache.get(&node.path)
+    {
+        lines.push(format_header_row(
+            &blame_row(&blame.block(block)),
+            palette,
+            false,
+        ));
+    }
+
+    if matches!(node.kind, TreeNodeKind::Block)
+        && let Some(risk) = state.risk.get(&node.hash)
+    {
+        lines.push(format_header_row(
+            &format!(
+                "Risk {} (complexity {}, churn {}, path {})",
+                risk.score, risk.complexity, risk.churn, risk.path
+            ),
+            palette,
+            risk.score >= HIGH_RISK,
+        ));
+    }
+
+    if matches!(node.kind, TreeNodeKind::Block)
+        && let Some(hint) = state.prereview.get(&node.hash)
+    {
+        lines.push(format_header_row(&hint_row(hint), palette, false));
+    }
+
+    if matches!(node.kind, TreeNodeKind::Block)
+        && let Some(commits) = state.block_commits.get(&node.hash)
+    {
+        for commit in commits.iter().take(MAX_COMMIT_ROWS) {
+            lines.push(format_header_row(
+                &format!("Commit {}: {}", commit.short_id(), commit.summary),
+                palette,
+                false,
+            ));
+        }
+        if commits.len() > MAX_COMMIT_ROWS {
+            lines.push(format_header_row(
+                &format!("{} more commits", commits.len() - MAX_COMMIT_ROWS),
+                palette,
+                false,
+            ));
+        }
+    }
+
+    if matches!(node.kind, TreeNodeKind::Block)
+        && let Some(rows) = state.threads.get(&node.hash)
+    {
+        let more = format!(
+            "(trueflow thread --fingerprint {})",
+            &node.hash[..node.hash.len().min(12)]
+        );
+        push_thread_rows(&mut lines, rows, 0, &more, palette);
+    }
+
+    if matches!(node.kind, TreeNodeKind::Block)
+        && let Some(rows) = state.earlier.get(&node.hash)
+    {
+        lines.push(format_header_row(
+            "On earlier versions of this block:",
+            palette,
+            true,
+        ));
+        push_thread_rows(&mut lines, rows, 1, "on earlier versions", palette);
+    }
+
     if !matches!(node.kind, TreeNodeKind::Root) && !node.hash.is_empty() {
         lines.push(format_header_row(
             &format!("Hash: {}", &node.hash[..node.hash.len().min(12)]),
@@ -1422,7 +2980,42 @@ fn build_header_lines(
     lines
 }
 
-fn build_block_breadcrumb(node: &crate::tree::TreeNode, state: &AppState) -> Option<String> {
+/// Up to `MAX_THREAD_ROWS` discussion rows, replies indented under what
+/// they reply to, then how many more there are.
+fn push_thread_rows(
+    lines: &mut Vec<Line<'static>>,
+    rows: &[(usize, String)],
+    indent: usize,
+    more: &str,
+    palette: &UiPalette,
+) {
+    for (depth, text) in rows.iter().take(MAX_THREAD_ROWS) {
+        let marker = if *depth == 0 { "" } else { "└ " };
+        let indent = "  ".repeat(indent + depth);
+        lines.push(format_header_row(
+            &format!("{indent}{marker}{text}"),
+            palette,
+            false,
+        ));
+    }
+    if rows.len() > MAX_THREAD_ROWS {
+        lines.push(format_header_row(
+            &format!(
+                "{}… {} more {}",
+                "  ".repeat(indent),
+                rows.len() - MAX_THREAD_ROWS,
+                more
+            ),
+            palette,
+            false,
+        ));
+    }
+}
+
+fn build_block_breadcrumb(
+    node: &trueflow_core::tree::TreeNode,
+    state: &AppState,
+) -> Option<String> {
     if !matches!(node.kind, TreeNodeKind::Block) {
         return None;
     }
@@ -1478,7 +3071,7 @@ fn build_block_breadcrumb(node: &crate::tree::TreeNode, state: &AppState) -> Opt
     }
 }
 
-fn block_signature(block: &crate::block::Block) -> String {
+fn block_signature(block: &trueflow_core::block::Block) -> String {
     let Some(line) = block
         .content
         .lines()
@@ -1491,10 +3084,9 @@ fn block_signature(block: &crate::block::Block) -> String {
     if matches!(
         block.kind,
         BlockKind::Function | BlockKind::Method | BlockKind::FunctionSignature
-    ) {
-        if let Some(idx) = find_argument_list_start(&text) {
-            text.truncate(idx);
-        }
+    ) && let Some(idx) = find_argument_list_start(&text)
+    {
+        text.truncate(idx);
     }
 
     truncate_text(text.trim(), 72)
@@ -1530,31 +3122,132 @@ fn format_header_row(text: &str, palette: &UiPalette, bold: bool) -> Line<'stati
     Line::from(Span::styled(text.to_string(), style))
 }
 
-fn build_action_lines(width: u16, palette: &UiPalette) -> Vec<Line<'static>> {
-    let top_left = "[a]pprove [c]omment [x]reject";
-    let top_right = "[g]root [q]uit";
-    let top_spacing = top_line_spacing(width, top_left, top_right);
+/// `[a]pprove` when the action's first key starts its name, else `[x]reject`.
+fn key_hint(keymap: &Keymap, action: Action, name: &str) -> String {
+    match keymap.keys(action).first() {
+        Some(KeyBinding {
+            code: KeyCode::Char(ch),
+            ctrl: false,
+        }) if name.starts_with(ch.to_ascii_lowercase()) => {
+            format!("[{ch}]{}", &name[ch.len_utf8()..])
+        }
+        Some(key) => format!("[{}]{name}", key_label(key)),
+        None => String::new(),
+    }
+}
 
-    let top_line = Line::from(vec![
-        Span::styled(top_left.to_string(), Style::default().fg(palette.dim)),
-        Span::styled(top_spacing, Style::default().bg(palette.bg)),
-        Span::styled(top_right.to_string(), Style::default().fg(palette.dim)),
+/// The hint rows under the code pane, as text pieces tagged with the
+/// action a click on them triggers.
+fn build_action_rows(width: u16, keymap: &Keymap) -> Vec<Vec<(String, Option<Action>)>> {
+    let hints = |actions: &[(Action, &str)]| {
+        let mut row: Vec<(String, Option<Action>)> = Vec::new();
+        for (action, name) in actions {
+            let hint = key_hint(keymap, *action, name);
+            if hint.is_empty() {
+                continue;
+            }
+            if !row.is_empty() {
+                row.push((" ".to_string(), None));
+            }
+            row.push((hint, Some(*action)));
+        }
+        row
+    };
+    let mut top = hints(&[
+        (Action::Approve, "approve"),
+        (Action::Comment, "comment"),
+        (Action::Question, "question"),
+        (Action::Reject, "reject"),
+        (Action::Undo, "undo"),
+    ]);
+    let top_right = hints(&[
+        (Action::Select, "select"),
+        (Action::Diff, "diff"),
+        (Action::Root, "root"),
+        (Action::Help, "help"),
+        (Action::Quit, "quit"),
     ]);
+    let top_spacing = top_line_spacing(width, &row_text(&top), &row_text(&top_right));
+    top.push((top_spacing, None));
+    top.extend(top_right);
+
+    let hint = |action, name| (key_hint(keymap, action, name), Some(action));
+    let prev = key_hint(keymap, Action::Prev, "prev");
+    let prev_padding = " ".repeat(19usize.saturating_sub(prev.width()));
+    vec![
+        top,
+        vec![hint(Action::Ascend, "ascend")],
+        vec![
+            (prev, Some(Action::Prev)),
+            (prev_padding, None),
+            hint(Action::Next, "next"),
+        ],
+        vec![("  ".to_string(), None), hint(Action::Descend, "descend")],
+    ]
+}
+
+fn row_text(row: &[(String, Option<Action>)]) -> String {
+    row.iter().map(|(text, _)| text.as_str()).collect()
+}
 
+fn action_lines(rows: &[Vec<(String, Option<Action>)>], palette: &UiPalette) -> Vec<Line<'static>> {
+    let top_style = Style::default().fg(palette.dim);
     let pyramid_style = Style::default()
         .fg(palette.dim)
         .add_modifier(Modifier::BOLD);
+    rows.iter()
+        .enumerate()
+        .map(|(index, row)| {
+            let style = if index == 0 { top_style } else { pyramid_style };
+            Line::from(
+                row.iter()
+                    .map(|(text, _)| Span::styled(text.clone(), style))
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .collect()
+}
 
-    let pyramid_lines = vec![
-        Line::from(Span::styled("[i]ascend", pyramid_style)),
-        Line::from(Span::styled("[j]prev            [l]next", pyramid_style)),
-        Line::from(Span::styled("  [k]descend", pyramid_style)),
-    ];
+/// Records where listed nodes and action hints landed on screen.
+fn register_click_targets(
+    state: &mut AppState,
+    layout: &FocusLayout,
+    action_rows: &[Vec<(String, Option<Action>)>],
+) {
+    let code = layout.code;
+    let first = state.scroll_offset as usize;
+    for &(line, id) in &state.content_targets {
+        let Some(row) = line.checked_sub(first) else {
+            continue;
+        };
+        if row >= code.height as usize {
+            continue;
+        }
+        let area = Rect::new(code.x, code.y + row as u16, code.width, 1);
+        state.click_targets.push((area, ClickTarget::Node(id)));
+    }
 
-    let mut lines = Vec::with_capacity(1 + pyramid_lines.len());
-    lines.push(top_line);
-    lines.extend(pyramid_lines);
-    lines
+    let actions = action_targets(layout.actions, action_rows);
+    state.click_targets.extend(actions);
+}
+
+/// Where each hint of `rows` sits once centered in `area`, as the
+/// paragraph that draws them centers them.
+fn action_targets(area: Rect, rows: &[Vec<(String, Option<Action>)>]) -> Vec<(Rect, ClickTarget)> {
+    let mut targets = Vec::new();
+    for (index, row) in rows.iter().enumerate().take(area.height as usize) {
+        let row_width = row.iter().map(|(text, _)| text.width()).sum::<usize>() as u16;
+        let mut x = area.x + area.width.saturating_sub(row_width) / 2;
+        for (text, action) in row {
+            let width = text.width() as u16;
+            if let Some(action) = action {
+                let hint = Rect::new(x, area.y + index as u16, width, 1).intersection(area);
+                targets.push((hint, ClickTarget::Action(*action)));
+            }
+            x = x.saturating_add(width);
+        }
+    }
+    targets
 }
 
 fn top_line_spacing(width: u16, left: &str, right: &str) -> String {
@@ -1572,11 +3265,34 @@ fn render_footer(frame: &mut Frame, state: &AppState, area: Rect, palette: &UiPa
         1.0
     };
 
-    let label = format!(
+    let mut label = format!(
         " {}/{} reviewed ",
         state.total_blocks - state.remaining_blocks,
         state.total_blocks
     );
+    let deferred = pending_deferred(state).count();
+    if deferred > 0 {
+        label.push_str(&format!(
+            "· {} to go, {deferred} deferred ",
+            state.remaining_blocks.saturating_sub(deferred)
+        ));
+    }
+    if let Some(search) = &state.search {
+        let position = if search.matches.is_empty() {
+            "no matches".to_string()
+        } else {
+            format!("{}/{}", search.current + 1, search.matches.len())
+        };
+        label.push_str(&format!("· /{} {position} ", search.query));
+    }
+    if state.content_height > state.viewport_height {
+        let first = state.scroll_offset + 1;
+        let last = (state.scroll_offset + state.viewport_height).min(state.content_height);
+        label.push_str(&format!("· lines {first}-{last}/{} ", state.content_height));
+    }
+    if state.folded {
+        label.push_str("· folded ");
+    }
 
     let gauge = Gauge::default()
         .block(UiBlock::default().borders(ratatui::widgets::Borders::NONE))
@@ -1589,7 +3305,7 @@ fn render_footer(frame: &mut Frame, state: &AppState, area: Rect, palette: &UiPa
 
 fn build_content_lines(
     state: &mut AppState,
-    node: &crate::tree::TreeNode,
+    node: &trueflow_core::tree::TreeNode,
     palette: &UiPalette,
     code_height: u16,
 ) -> (Vec<Line<'static>>, usize) {
@@ -1601,7 +3317,10 @@ fn build_content_lines(
     }
 }
 
-fn load_file_lines(state: &mut AppState, node: &crate::tree::TreeNode) -> Option<Vec<String>> {
+fn load_file_lines(
+    state: &mut AppState,
+    node: &trueflow_core::tree::TreeNode,
+) -> Option<Vec<String>> {
     if node.path.is_empty() {
         return None;
     }
@@ -1622,7 +3341,7 @@ fn load_file_lines(state: &mut AppState, node: &crate::tree::TreeNode) -> Option
 
 fn build_block_lines(
     state: &mut AppState,
-    node: &crate::tree::TreeNode,
+    node: &trueflow_core::tree::TreeNode,
     palette: &UiPalette,
     code_height: u16,
 ) -> (Vec<Line<'static>>, usize) {
@@ -1638,7 +3357,12 @@ fn build_block_lines(
 
     let language = node.language.clone();
     let block_lines: Vec<String> = block.content.lines().map(|line| line.to_string()).collect();
-    let extra_space = code_height.saturating_sub(block_lines.len() as u16) as isize;
+    let mut code_lines = format_code_lines(&block_lines, palette, language.as_ref());
+    if state.folded {
+        let hidden = fold_ranges(state, block, language.as_ref());
+        code_lines = fold_code_lines(code_lines, &hidden, palette);
+    }
+    let extra_space = code_height.saturating_sub(code_lines.len() as u16) as isize;
 
     // TODO: if paginating, we shouldn't truncate context based on viewport height alone.
     // However, existing context logic tries to center the block vertically.
@@ -1657,86 +3381,306 @@ fn build_block_lines(
     let mut top_context = total_context / 2 + (total_context % 2);
     let mut bottom_context = total_context / 2;
 
-    if extra_space < 2 {
+    // Binary blocks describe the file; there are no surrounding lines to show.
+    if extra_space < 2 || block.kind == BlockKind::Binary {
         // Block is large or fits perfectly. Minimal context.
         // Actually, if block is large, extra_space < 0.
         // We should just render the block + maybe minimal context if we want?
         // Existing logic returns just block lines if extra_space < 2.
         // This is fine for now; large blocks will just be the block itself.
-        let lines: Vec<Line> = block_lines
-            .iter()
-            .map(|line| format_code_line(line, palette, language.as_ref()))
-            .collect();
-        return (lines.clone(), lines.len());
+        let len = code_lines.len();
+        return (code_lines, len);
+    }
+
+    let file_lines = match load_file_lines(state, node) {
+        Some(lines) => lines,
+        None => {
+            let len = code_lines.len();
+            return (code_lines, len);
+        }
+    };
+
+    let start_line = block.start_line.min(file_lines.len());
+    let end_line = block.end_line.min(file_lines.len());
+
+    let available_top = start_line;
+    let available_bottom = file_lines.len().saturating_sub(end_line);
+
+    if top_context > available_top {
+        let overflow = top_context - available_top;
+        top_context = available_top;
+        bottom_context = (bottom_context + overflow).min(available_bottom);
+    }
+
+    if bottom_context > available_bottom {
+        let overflow = bottom_context - available_bottom;
+        bottom_context = available_bottom;
+        top_context = (top_context + overflow).min(available_top);
+    }
+
+    if top_context + bottom_context < total_context {
+        let missing = total_context - (top_context + bottom_context);
+        let add_top = missing.min(available_top.saturating_sub(top_context));
+        top_context += add_top;
+        let add_bottom = missing
+            .saturating_sub(add_top)
+            .min(available_bottom.saturating_sub(bottom_context));
+        bottom_context += add_bottom;
+    }
+
+    let mut lines = Vec::new();
+    if top_context > 0 {
+        let start = start_line.saturating_sub(top_context);
+        let end = start_line;
+        for line in &file_lines[start..end] {
+            lines.push(format_context_line(line, palette));
+        }
+    }
+
+    lines.extend(code_lines);
+
+    if bottom_context > 0 {
+        let start = end_line;
+        let end = (end_line + bottom_context).min(file_lines.len());
+        for line in &file_lines[start..end] {
+            lines.push(format_context_line(line, palette));
+        }
+    }
+
+    let len = lines.len();
+    (lines, len)
+}
+
+/// The lines of `block` that folding hides: everything but the first line
+/// of each multi-line sub-block.
+fn fold_ranges(
+    state: &mut AppState,
+    block: &trueflow_core::block::Block,
+    language: Option<&Language>,
+) -> Vec<Range<usize>> {
+    if let Some(ranges) = state.fold_cache.get(&block.hash) {
+        return ranges.clone();
+    }
+    let sub_blocks = language
+        .and_then(|language| sub_splitter::split(block, language.clone()).ok())
+        .unwrap_or_default();
+    let ranges = hidden_ranges(block, &sub_blocks);
+    state.fold_cache.insert(block.hash.clone(), ranges.clone());
+    ranges
+}
+
+fn hidden_ranges(
+    block: &trueflow_core::block::Block,
+    sub_blocks: &[trueflow_core::block::Block],
+) -> Vec<Range<usize>> {
+    let block_len = block.end_line.saturating_sub(block.start_line);
+    sub_blocks
+        .iter()
+        .filter(|sub| sub.kind != BlockKind::Gap)
+        .map(|sub| {
+            let start = sub.start_line.saturating_sub(block.start_line);
+            let end = sub.end_line.saturating_sub(block.start_line).min(block_len);
+            start + 1..end
+        })
+        // A sub-block that is the whole block would fold everything away.
+        .filter(|range| !range.is_empty() && range.end - range.start + 1 < block_len)
+        .collect()
+}
+
+/// Replaces each hidden range of `lines` with a marker saying how much it
+/// hides.
+fn fold_code_lines(
+    lines: Vec<Line<'static>>,
+    hidden: &[Range<usize>],
+    palette: &UiPalette,
+) -> Vec<Line<'static>> {
+    let mut folded = Vec::with_capacity(lines.len());
+    for (index, line) in lines.into_iter().enumerate() {
+        match hidden.iter().find(|range| range.contains(&index)) {
+            Some(range) if range.start == index => {
+                let count = range.len();
+                let noun = if count == 1 { "line" } else { "lines" };
+                folded.push(Line::from(Span::styled(
+                    format!("    ⋯ {count} {noun} folded"),
+                    Style::default().fg(palette.dim).bg(palette.code_bg),
+                )));
+            }
+            Some(_) => {}
+            None => folded.push(line),
+        }
+    }
+    folded
+}
+
+/// The block beside the version it was approved at or, failing that, its
+/// version on main: old on the left, new on the right.
+fn build_diff_lines(
+    state: &mut AppState,
+    node: &trueflow_core::tree::TreeNode,
+    palette: &UiPalette,
+    width: u16,
+) -> (Vec<Line<'static>>, usize) {
+    let message = |text: &str| {
+        (
+            vec![Line::from(Span::styled(
+                text.to_string(),
+                Style::default().fg(palette.dim).bg(palette.code_bg),
+            ))],
+            1,
+        )
+    };
+    let Some(block) = &node.block else {
+        return message("(No content)");
+    };
+    if let Some(previous) = state.previous_approvals.get(&block.hash) {
+        let rows = side_by_side::align(&previous.previous_content, &block.content);
+        return diff_row_lines(&rows, "(Unchanged since approval)", node, palette, width);
+    }
+    let Some(rows) = load_diff_rows(state, &node.path) else {
+        return message("(No version on main to compare with)");
+    };
+    let rows = side_by_side::rows_for_lines(rows, block.start_line..block.end_line);
+    diff_row_lines(rows, "(Unchanged from main)", node, palette, width)
+}
+
+/// Renders aligned rows in two columns, or just the new side under
+/// `unchanged` when nothing differs.
+fn diff_row_lines(
+    rows: &[DiffRow],
+    unchanged: &'static str,
+    node: &trueflow_core::tree::TreeNode,
+    palette: &UiPalette,
+    width: u16,
+) -> (Vec<Line<'static>>, usize) {
+    if rows.is_empty() {
+        return (
+            vec![Line::from(Span::styled(
+                "(No content)",
+                Style::default().fg(palette.dim).bg(palette.code_bg),
+            ))],
+            1,
+        );
+    }
+    if !rows.iter().any(|row| row.changed) {
+        let mut lines = vec![Line::from(Span::styled(
+            unchanged,
+            Style::default().fg(palette.dim).bg(palette.code_bg),
+        ))];
+        let new_lines: Vec<&str> = rows.iter().filter_map(|row| row.new.as_deref()).collect();
+        lines.extend(format_code_lines(
+            &new_lines,
+            palette,
+            node.language.as_ref(),
+        ));
+        let len = lines.len();
+        return (lines, len);
     }
 
-    let file_lines = match load_file_lines(state, node) {
-        Some(lines) => lines,
-        None => {
-            let lines: Vec<Line> = block_lines
-                .iter()
-                .map(|line| format_code_line(line, palette, language.as_ref()))
-                .collect();
-            return (lines.clone(), lines.len());
-        }
+    // One column per side, less the separator and the scrollbar.
+    let column = (width.saturating_sub(3) / 2) as usize;
+    let side = |text: Option<&str>, changed: bool, color: Color| {
+        let text = text.map(|text| text.replace('\t', "    "));
+        let fitted = match &text {
+            Some(text) => format!("{:<column$}", truncate_to_width(text, column)),
+            None => " ".repeat(column),
+        };
+        let style = match (changed, &text) {
+            (true, Some(_)) => Style::default().fg(color).bg(palette.code_bg),
+            (true, None) => Style::default().bg(palette.meta_bg),
+            (false, _) => Style::default().fg(palette.code_fg).bg(palette.code_bg),
+        };
+        Span::styled(fitted, style)
     };
+    let lines: Vec<Line<'static>> = rows
+        .iter()
+        .map(|row| {
+            Line::from(vec![
+                side(row.old.as_deref(), row.changed, palette.del),
+                Span::styled(" │ ", Style::default().fg(palette.dim).bg(palette.code_bg)),
+                side(row.new.as_deref(), row.changed, palette.add),
+            ])
+        })
+        .collect();
+    let len = lines.len();
+    (lines, len)
+}
 
-    let start_line = block.start_line.min(file_lines.len());
-    let end_line = block.end_line.min(file_lines.len());
-
-    let available_top = start_line;
-    let available_bottom = file_lines.len().saturating_sub(end_line);
-
-    if top_context > available_top {
-        let overflow = top_context - available_top;
-        top_context = available_top;
-        bottom_context = (bottom_context + overflow).min(available_bottom);
-    }
-
-    if bottom_context > available_bottom {
-        let overflow = bottom_context - available_bottom;
-        bottom_context = available_bottom;
-        top_context = (top_context + overflow).min(available_top);
+/// Aligned rows of `path` against the base branch, computed once per file.
+fn load_diff_rows<'a>(state: &'a mut AppState, path: &str) -> Option<&'a [DiffRow]> {
+    if !state.diff_cache.contains_key(path) {
+        let rows = (|| {
+            let new = std::fs::read_to_string(path).ok()?;
+            let repo = vcs::repo_from_workdir().ok()?;
+            let repo_path = repo_relative_path(path, workdir_prefix_from_root().as_deref());
+            let old = vcs::base_file_contents(&repo, &repo_path)
+                .inspect_err(|err| {
+                    warn!("Failed to read {} on the base branch: {}", repo_path, err)
+                })
+                .ok()?
+                .unwrap_or_default();
+            Some(side_by_side::align(&old, &new))
+        })();
+        state.diff_cache.insert(path.to_string(), rows);
     }
+    state.diff_cache.get(path)?.as_deref()
+}
 
-    if top_context + bottom_context < total_context {
-        let missing = total_context - (top_context + bottom_context);
-        let add_top = missing.min(available_top.saturating_sub(top_context));
-        top_context += add_top;
-        let add_bottom = missing
-            .saturating_sub(add_top)
-            .min(available_bottom.saturating_sub(bottom_context));
-        bottom_context += add_bottom;
+fn load_blame<'a>(state: &'a mut AppState, path: &str) -> Option<&'a FileBlame> {
+    if !state.blame_cache.contains_key(path) {
+        let blame = (|| {
+            let working = std::fs::read_to_string(path).ok()?;
+            let repo = vcs::repo_from_workdir().ok()?;
+            let repo_path = repo_relative_path(path, workdir_prefix_from_root().as_deref());
+            FileBlame::for_path(&repo, &repo_path, &working)
+                .inspect_err(|err| warn!("Failed to blame {}: {}", repo_path, err))
+                .ok()
+        })();
+        state.blame_cache.insert(path.to_string(), blame);
     }
+    state.blame_cache.get(path)?.as_ref()
+}
 
-    let mut lines = Vec::new();
-    if top_context > 0 {
-        let start = start_line.saturating_sub(top_context);
-        let end = start_line;
-        for line in &file_lines[start..end] {
-            lines.push(format_context_line(line, palette, language.as_ref()));
-        }
+/// "By alice (40 lines), 2 uncommitted lines; last changed 2024-03-01 in
+/// a1b2c3d", or "Not committed yet" for new code.
+fn blame_row(blame: &BlockBlame) -> String {
+    let Some(commit) = &blame.last_commit else {
+        return "Not committed yet".to_string();
+    };
+    let mut parts: Vec<String> = blame
+        .authors
+        .iter()
+        .take(2)
+        .map(|author| format!("{} ({} lines)", author.name, author.lines))
+        .collect();
+    if blame.authors.len() > 2 {
+        parts.push(format!("{} more", blame.authors.len() - 2));
     }
-
-    for line in &block_lines {
-        lines.push(format_code_line(line, palette, language.as_ref()));
+    if blame.uncommitted_lines > 0 {
+        parts.push(format!("{} uncommitted lines", blame.uncommitted_lines));
     }
+    let date = chrono::DateTime::from_timestamp(commit.time, 0)
+        .map(|time| time.format("%Y-%m-%d").to_string())
+        .unwrap_or_else(|| commit.time.to_string());
+    format!(
+        "By {}; last changed {} in {}",
+        parts.join(", "),
+        date,
+        commit.short_id()
+    )
+}
 
-    if bottom_context > 0 {
-        let start = end_line;
-        let end = (end_line + bottom_context).min(file_lines.len());
-        for line in &file_lines[start..end] {
-            lines.push(format_context_line(line, palette, language.as_ref()));
-        }
+fn truncate_to_width(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
     }
-
-    let len = lines.len();
-    (lines, len)
+    let mut out: String = text.chars().take(width.saturating_sub(1)).collect();
+    out.push('…');
+    out
 }
 
 fn build_file_lines(
     state: &mut AppState,
-    node: &crate::tree::TreeNode,
+    node: &trueflow_core::tree::TreeNode,
     palette: &UiPalette,
     _code_height: u16,
 ) -> (Vec<Line<'static>>, usize) {
@@ -1752,18 +3696,15 @@ fn build_file_lines(
     };
 
     // With scrolling enabled, we return all lines and let the viewport clip them.
-    let lines = file_lines
-        .iter()
-        .map(|line| format_code_line(line, palette, language.as_ref()))
-        .collect::<Vec<_>>();
+    let lines = format_code_lines(&file_lines, palette, language.as_ref());
 
     let len = lines.len();
     (lines, len)
 }
 
 fn build_directory_lines(
-    state: &AppState,
-    node: &crate::tree::TreeNode,
+    state: &mut AppState,
+    node: &trueflow_core::tree::TreeNode,
     palette: &UiPalette,
     _code_height: u16,
 ) -> (Vec<Line<'static>>, usize) {
@@ -1779,9 +3720,9 @@ fn build_directory_lines(
             TreeNodeKind::Block => format!("{}:{}", child.name, child.hash),
             TreeNodeKind::Root => child.name.clone(),
         };
-        entries.push(label);
+        entries.push((label, *child_id));
     }
-    entries.sort();
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
 
     if entries.is_empty() {
         return (
@@ -1794,15 +3735,50 @@ fn build_directory_lines(
     }
 
     // Scrollable directory view
+    state.content_targets.extend(
+        entries
+            .iter()
+            .enumerate()
+            .map(|(index, (_, id))| (index, *id)),
+    );
     let entries_list = entries
         .iter()
-        .map(|entry| format_directory_line(entry, palette))
+        .map(|(entry, _)| format_directory_line(entry, palette))
         .collect::<Vec<_>>();
 
     let len = entries_list.len();
     (entries_list, len)
 }
 
+/// The commits in scope for the root screen: a heading, then each commit's
+/// summary with the start of its message body below it, newest first. The
+/// flag marks dim rows.
+fn scope_commit_rows(commits: &[CommitInfo]) -> Vec<(String, bool)> {
+    let mut rows = vec![(format!("Commits: {}", commits.len()), false)];
+    for commit in commits.iter().rev().take(MAX_SCOPE_COMMITS) {
+        rows.push((format!("  {} {}", commit.short_id(), commit.summary), false));
+        let body: Vec<&str> = commit
+            .message
+            .lines()
+            .skip(1)
+            .skip_while(|line| line.trim().is_empty())
+            .collect();
+        for line in body.iter().take(MAX_COMMIT_BODY_LINES) {
+            rows.push((format!("      {line}"), true));
+        }
+        if body.len() > MAX_COMMIT_BODY_LINES {
+            rows.push(("      …".to_string(), true));
+        }
+    }
+    if commits.len() > MAX_SCOPE_COMMITS {
+        rows.push((
+            format!("  {} more", commits.len() - MAX_SCOPE_COMMITS),
+            true,
+        ));
+    }
+    rows
+}
+
 fn build_root_lines(
     state: &mut AppState,
     palette: &UiPalette,
@@ -1826,7 +3802,13 @@ fn build_root_lines(
     let mut lines = Vec::new();
     lines.push(Line::from(vec![
         Span::styled(
-            format!("Unreviewed blocks: {}", state.remaining_blocks),
+            match pending_deferred(state).count() {
+                0 => format!("Unreviewed blocks: {}", state.remaining_blocks),
+                deferred => format!(
+                    "Unreviewed blocks: {} ({deferred} deferred)",
+                    state.remaining_blocks
+                ),
+            },
             Style::default().fg(palette.fg).bg(palette.code_bg),
         ),
         Span::styled(
@@ -1838,6 +3820,18 @@ fn build_root_lines(
         format!("Files/dirs: {}", root_children.len()),
         Style::default().fg(palette.dim).bg(palette.code_bg),
     )));
+    if !state.scope_commits.is_empty() {
+        lines.push(Line::from(""));
+        for (text, dim) in scope_commit_rows(&state.scope_commits) {
+            lines.push(Line::from(Span::styled(
+                text,
+                Style::default()
+                    .fg(if dim { palette.dim } else { palette.fg })
+                    .bg(palette.code_bg),
+            )));
+        }
+        lines.push(Line::from(""));
+    }
 
     let mut kind_counts = count_block_kinds(state);
     kind_counts.sort_by(|a, b| {
@@ -1876,6 +3870,13 @@ fn build_root_lines(
         )));
     }
 
+    let listing_start = lines.len();
+    state.content_targets.extend(
+        root_children
+            .iter()
+            .enumerate()
+            .map(|(index, id)| (listing_start + index, *id)),
+    );
     let mut listing = root_children
         .iter()
         .map(|id| {
@@ -1917,7 +3918,8 @@ fn parent_kind(kind: &BlockKind) -> &'static str {
         BlockKind::Function
         | BlockKind::Method
         | BlockKind::FunctionSignature
-        | BlockKind::CodeParagraph => "Code Logic",
+        | BlockKind::CodeParagraph
+        | BlockKind::Statement => "Code Logic",
         BlockKind::Struct
         | BlockKind::Enum
         | BlockKind::Class
@@ -1925,6 +3927,8 @@ fn parent_kind(kind: &BlockKind) -> &'static str {
         | BlockKind::Macro
         | BlockKind::Const
         | BlockKind::Static
+        | BlockKind::Constants
+        | BlockKind::Entry
         | BlockKind::Type => "Definitions",
         BlockKind::Module
         | BlockKind::Modules
@@ -1974,27 +3978,16 @@ fn format_directory_line(entry: &str, palette: &UiPalette) -> Line<'static> {
     ])
 }
 
-fn format_context_line(
-    line: &str,
-    palette: &UiPalette,
-    language: Option<&Language>,
-) -> Line<'static> {
+/// A line around the block, dimmed and indented past the gutter.
+fn format_context_line(line: &str, palette: &UiPalette) -> Line<'static> {
     let gutter_left = 4;
     let gutter_right = 2;
     let gutter_spacing = " ".repeat(gutter_left + gutter_right + 1);
-    let tokens = highlight_line(line, language);
-    let mut spans = Vec::with_capacity(tokens.len() + 1);
-    spans.push(Span::styled(
-        gutter_spacing,
-        Style::default().fg(palette.context).bg(palette.code_bg),
-    ));
-    for token in tokens {
-        let style = style_for_token(&token.kind, palette)
-            .fg(palette.context)
-            .bg(palette.code_bg);
-        spans.push(Span::styled(token.text, style));
-    }
-    Line::from(spans)
+    let style = Style::default().fg(palette.context).bg(palette.code_bg);
+    Line::from(vec![
+        Span::styled(gutter_spacing, style),
+        Span::styled(line.to_string(), style),
+    ])
 }
 
 fn render_input_overlay(frame: &mut Frame, state: &AppState, area: Rect, palette: &UiPalette) {
@@ -2002,8 +3995,12 @@ fn render_input_overlay(frame: &mut Frame, state: &AppState, area: Rect, palette
     frame.render_widget(ratatui::widgets::Clear, popup_area);
 
     let (title, hints, content) = match &state.input_mode {
-        InputMode::Editing { .. } => (
-            " Comment ",
+        InputMode::Editing { action } => (
+            if action.verdict_label() == Verdict::Question.as_str() {
+                " Question "
+            } else {
+                " Comment "
+            },
             "Enter to submit • Esc to cancel",
             state.input_buffer.clone(),
         ),
@@ -2011,12 +4008,16 @@ fn render_input_overlay(frame: &mut Frame, state: &AppState, area: Rect, palette
             " Batch Action ",
             "Enter to confirm • Esc to cancel",
             format!(
-                "This will apply '{}' to {} unreviewed descendant block(s).",
+                "This will apply '{}' to {} {} block(s).",
                 action.verdict_label(),
-                count
+                count,
+                match action {
+                    PendingAction::Range { .. } => "selected",
+                    _ => "unreviewed descendant",
+                }
             ),
         ),
-        InputMode::Normal => return,
+        InputMode::Normal | InputMode::Search | InputMode::Help => return,
     };
 
     let block = UiBlock::default()
@@ -2038,6 +4039,104 @@ fn render_input_overlay(frame: &mut Frame, state: &AppState, area: Rect, palette
     );
 }
 
+/// Every action with its keys and its `[tui.keys]` name.
+fn render_help_overlay(frame: &mut Frame, state: &AppState, area: Rect, palette: &UiPalette) {
+    let popup_area = centered_rect(area, 70, 80);
+    frame.render_widget(ratatui::widgets::Clear, popup_area);
+
+    let labels: Vec<(Action, String)> = Action::ALL
+        .into_iter()
+        .map(|action| (action, state.keymap.label(action)))
+        .collect();
+    let key_width = labels
+        .iter()
+        .map(|(_, label)| label.chars().count())
+        .max()
+        .unwrap_or(0);
+    let mut lines: Vec<Line> = labels
+        .into_iter()
+        .map(|(action, label)| {
+            let padding = " ".repeat(key_width - label.chars().count() + 2);
+            Line::from(vec![
+                Span::styled(
+                    label,
+                    Style::default().fg(palette.fg).add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(padding),
+                Span::raw(action.description()),
+                Span::styled(
+                    format!("  ({})", action.name()),
+                    Style::default().fg(palette.dim),
+                ),
+            ])
+        })
+        .collect();
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "Remap keys under [tui.keys] in trueflow.toml • any key to close",
+        Style::default().fg(palette.dim),
+    )));
+
+    let block = UiBlock::default()
+        .title(" Keys ")
+        .borders(ratatui::widgets::Borders::ALL)
+        .style(Style::default().bg(palette.bg).fg(palette.fg));
+    frame.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false }),
+        popup_area,
+    );
+}
+
+/// The `/` prompt with the best matches for what is typed so far.
+fn render_search_overlay(frame: &mut Frame, state: &AppState, area: Rect, palette: &UiPalette) {
+    const PREVIEW: usize = 8;
+    let popup_area = centered_rect(area, 70, 40);
+    frame.render_widget(ratatui::widgets::Clear, popup_area);
+
+    let mut lines = vec![
+        Line::from(format!("/{}", state.input_buffer)),
+        Line::from(""),
+    ];
+    let matches = search_matches(state, state.input_buffer.trim());
+    for (idx, (_, label)) in matches.iter().take(PREVIEW).enumerate() {
+        let style = if idx == 0 {
+            Style::default().fg(palette.fg).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(palette.dim)
+        };
+        lines.push(Line::from(Span::styled(label.to_string(), style)));
+    }
+    if matches.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "No matches",
+            Style::default().fg(palette.dim),
+        )));
+    } else if matches.len() > PREVIEW {
+        lines.push(Line::from(Span::styled(
+            format!("... {} more", matches.len() - PREVIEW),
+            Style::default().fg(palette.dim),
+        )));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "Enter to jump • n/N for next/previous match • Esc to cancel",
+        Style::default().fg(palette.dim),
+    )));
+
+    let block = UiBlock::default()
+        .title(" Search ")
+        .borders(ratatui::widgets::Borders::ALL)
+        .style(Style::default().bg(palette.bg).fg(palette.fg));
+    frame.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false }),
+        popup_area,
+    );
+}
+
 fn centered_rect(r: Rect, percent_x: u16, percent_y: u16) -> Rect {
     let popup_layout = Layout::vertical([
         Constraint::Percentage((100 - percent_y) / 2),
@@ -2054,6 +4153,264 @@ fn centered_rect(r: Rect, percent_x: u16, percent_y: u16) -> Rect {
     .split(popup_layout[1])[1]
 }
 
+#[cfg(test)]
+mod session_tests {
+    use super::*;
+    use trueflow_core::block::{Block, FileState};
+
+    fn tree() -> Tree {
+        trueflow_core::tree::build_tree_from_files(&[FileState {
+            path: "src/a.rs".to_string(),
+            language: Language::Rust,
+            file_hash: "file".to_string(),
+            generated: false,
+            blocks: vec![Block::new(
+                "fn a() {}".to_string(),
+                BlockKind::Function,
+                0,
+                1,
+            )],
+        }])
+    }
+
+    #[test]
+    fn node_keys_fall_back_to_the_path() {
+        let tree = tree();
+        let file = tree.find_by_path("src/a.rs").unwrap();
+        let block = tree.node(file).children[0];
+        assert_eq!(NodeKey::of(&tree, block).find(&tree), Some(block));
+
+        let edited = NodeKey {
+            path: "src/a.rs".to_string(),
+            hash: "gone".to_string(),
+        };
+        assert_eq!(edited.find(&tree), Some(file));
+        assert_eq!(
+            NodeKey::of(&tree, tree.root()).find(&tree),
+            Some(tree.root())
+        );
+    }
+
+    #[test]
+    fn saved_sessions_round_trip() {
+        let saved = SavedSession {
+            scope: ReviewScope::Targets(vec![ReviewTarget::Directory("src".to_string())]),
+            cursor: Some(NodeKey {
+                path: "src/a.rs".to_string(),
+                hash: "abc".to_string(),
+            }),
+            draft: Some(CommentDraft {
+                node: NodeKey {
+                    path: "src/a.rs".to_string(),
+                    hash: "abc".to_string(),
+                },
+                text: "half a thought".to_string(),
+            }),
+            deferred: vec![NodeKey {
+                path: "src/b.rs".to_string(),
+                hash: "def".to_string(),
+            }],
+        };
+        let json = serde_json::to_string(&saved).unwrap();
+        assert_eq!(serde_json::from_str::<SavedSession>(&json).unwrap(), saved);
+    }
+
+    #[test]
+    fn sessions_saved_before_defer_still_load() {
+        let saved = SavedSession {
+            scope: ReviewScope::All,
+            cursor: None,
+            draft: None,
+            deferred: Vec::new(),
+        };
+        let mut json = serde_json::to_value(&saved).unwrap();
+        json.as_object_mut().unwrap().remove("deferred");
+        assert_eq!(serde_json::from_value::<SavedSession>(json).unwrap(), saved);
+    }
+
+    #[test]
+    fn edited_blocks_are_found_again_by_line() {
+        let tree = trueflow_core::tree::build_tree_from_files(&[FileState {
+            path: "src/a.rs".to_string(),
+            language: Language::Rust,
+            file_hash: "file".to_string(),
+            generated: false,
+            blocks: vec![
+                Block::new("fn a() {}".to_string(), BlockKind::Function, 0, 3),
+                Block::new("fn b() {}".to_string(), BlockKind::Function, 4, 6),
+            ],
+        }]);
+        let file = tree.find_by_path("src/a.rs").unwrap();
+        let (a, b) = (tree.node(file).children[0], tree.node(file).children[1]);
+        let navigator = ReviewNavigator::new(tree, HashSet::from([a, b])).unwrap();
+        assert_eq!(block_at_line(&navigator, "src/a.rs", 1), Some(a));
+        assert_eq!(block_at_line(&navigator, "src/a.rs", 4), Some(b));
+        assert_eq!(block_at_line(&navigator, "src/a.rs", 9), None);
+        assert_eq!(block_at_line(&navigator, "src/b.rs", 1), None);
+    }
+
+    fn rejection(fingerprint: &str, line: u32) -> trueflow_core::store::Record {
+        use trueflow_core::store::{BlockState, Record, RepoRef};
+        Record {
+            id: format!("{fingerprint}-{line}"),
+            path_hint: Some("src/a.rs".to_string()),
+            line_hint: Some(line),
+            note: Some("Handle the error".to_string()),
+            ..Record::verdict(
+                fingerprint.to_string(),
+                "review".to_string(),
+                Verdict::Rejected,
+                Identity::Email {
+                    email: "alice@example.com".to_string(),
+                },
+                RepoRef::Vcs {
+                    system: VcsSystem::Git,
+                    revision: "deadbeef".to_string(),
+                },
+                BlockState::Committed,
+                1,
+            )
+        }
+    }
+
+    #[test]
+    fn scope_commits_list_newest_first_with_their_bodies() {
+        let commit = |id: &str, message: &str| CommitInfo {
+            id: id.to_string(),
+            summary: message.lines().next().unwrap_or_default().to_string(),
+            message: message.to_string(),
+        };
+        let rows = scope_commit_rows(&[
+            commit("aaaaaaaaaa", "Add a"),
+            commit("bbbbbbbbbb", "Add b\n\nCallers need it."),
+        ]);
+        assert_eq!(
+            rows,
+            [
+                ("Commits: 2".to_string(), false),
+                ("  bbbbbbb Add b".to_string(), false),
+                ("      Callers need it.".to_string(), true),
+                ("  aaaaaaa Add a".to_string(), false),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejections_of_earlier_versions_show_on_the_block_in_their_place() {
+        let block = Block::new("fn a() {}".to_string(), BlockKind::Function, 0, 1);
+        let summary = trueflow_core::commands::review::ReviewSummary {
+            files: vec![trueflow_core::commands::review::UnreviewedFile {
+                path: "src/a.rs".to_string(),
+                language: Language::Rust,
+                blocks: vec![block.clone()],
+            }],
+            total_blocks: 1,
+            block_counts: Vec::new(),
+            review_state: HashMap::new(),
+            commits: Vec::new(),
+            tree: tree(),
+            unreviewed_block_nodes: HashSet::new(),
+        };
+        let history = vec![
+            rejection("old", 0),
+            rejection("elsewhere", 40),
+            rejection(&block.hash, 0),
+        ];
+        let discussions = thread::by_block(&history);
+
+        let earlier = earlier_rows(&summary, &discussions);
+        assert_eq!(
+            earlier[&block.hash],
+            [(
+                0,
+                "alice@example.com rejected: Handle the error".to_string()
+            )]
+        );
+        assert_eq!(thread_rows(&summary, &discussions)[&block.hash].len(), 1);
+    }
+
+    #[test]
+    fn risk_scores_put_the_riskiest_blocks_first() {
+        let file = |path: &str, content: &str| FileState {
+            path: path.to_string(),
+            language: Language::Rust,
+            file_hash: path.to_string(),
+            generated: false,
+            blocks: vec![Block::new(content.to_string(), BlockKind::Function, 0, 1)],
+        };
+        let files = [file("src/a.rs", "fn a() {}"), file("src/b.rs", "fn b() {}")];
+        let tree = trueflow_core::tree::build_tree_from_files(&files);
+        let blocks: Vec<TreeNodeId> = ["src/a.rs", "src/b.rs"]
+            .iter()
+            .map(|path| tree.node(tree.find_by_path(path).unwrap()).children[0])
+            .collect();
+        let summary = trueflow_core::commands::review::ReviewSummary {
+            files: Vec::new(),
+            total_blocks: 2,
+            block_counts: Vec::new(),
+            review_state: HashMap::new(),
+            commits: Vec::new(),
+            tree,
+            unreviewed_block_nodes: blocks.iter().copied().collect(),
+        };
+        let order = |risk: &HashMap<String, BlockRisk>| {
+            ReviewOrder::from_summary(&summary, risk, |_| false)
+                .ordered
+                .iter()
+                .map(|cursor| cursor.node_id)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(order(&HashMap::new()), blocks);
+        let risky = BlockRisk {
+            score: 80,
+            complexity: 40,
+            churn: 10,
+            path: 30,
+        };
+        let risk = HashMap::from([(files[1].blocks[0].hash.clone(), risky)]);
+        assert_eq!(order(&risk), [blocks[1], blocks[0]]);
+    }
+
+    #[test]
+    fn deferred_blocks_go_to_the_end_of_the_order() {
+        let tree = tree();
+        let file = tree.find_by_path("src/a.rs").unwrap();
+        let (a, b, c) = (tree.root(), file, tree.node(file).children[0]);
+        let cursor = |node_id| ReviewCursor {
+            file_path: "src/a.rs".to_string(),
+            band: ReviewBand::Code,
+            kind_rank: 0,
+            start_line: 0,
+            node_id,
+        };
+        let mut order = ReviewOrder {
+            ordered: vec![cursor(a), cursor(b), cursor(c)],
+        };
+        let remaining = HashSet::from([a, b, c]);
+
+        order.defer(a);
+        assert_eq!(order.first_block(), Some(b));
+        assert_eq!(order.between(a, b, &remaining), vec![b, c, a]);
+        assert_eq!(order.step(c, true, &remaining), Some(a));
+        assert_eq!(order.step(b, false, &remaining), None);
+    }
+
+    #[test]
+    fn search_index_labels_files_and_blocks() {
+        let tree = tree();
+        let file = tree.find_by_path("src/a.rs").unwrap();
+        let block = tree.node(file).children[0];
+        let navigator = ReviewNavigator::new(tree, HashSet::from([block])).unwrap();
+        let index = build_search_index(&navigator);
+        let labels: Vec<&str> = index.iter().map(|(_, label)| label.as_str()).collect();
+        assert_eq!(labels.len(), 2);
+        assert_eq!(labels[0], "src/a.rs");
+        assert!(labels[1].starts_with("src/a.rs "));
+        assert!(index.contains(&(block, labels[1].to_string())));
+    }
+}
+
 struct FocusLayout {
     meta: Rect,
     code: Rect,
@@ -2150,25 +4507,29 @@ mod focus_layout_tests {
     }
 }
 
+#[derive(Clone)]
 struct UiPalette {
     bg: Color,
     fg: Color,
     code_fg: Color,
     dim: Color,
     add: Color,
-    #[allow(dead_code)]
     del: Color,
     keyword: Color,
     string: Color,
     number: Color,
+    comment: Color,
+    function: Color,
+    type_name: Color,
+    constant: Color,
     code_bg: Color,
     meta_bg: Color,
     meta_border: Color,
     context: Color,
 }
 
-impl Default for UiPalette {
-    fn default() -> Self {
+impl UiPalette {
+    fn light() -> Self {
         Self {
             bg: Color::Rgb(248, 248, 245),
             fg: Color::Rgb(60, 56, 54),
@@ -2179,91 +4540,216 @@ impl Default for UiPalette {
             keyword: Color::Rgb(69, 133, 136),
             string: Color::Rgb(215, 153, 33),
             number: Color::Rgb(177, 98, 134),
+            comment: Color::Rgb(146, 131, 116),
+            function: Color::Rgb(121, 116, 14),
+            type_name: Color::Rgb(181, 118, 20),
+            constant: Color::Rgb(143, 63, 113),
             code_bg: Color::Rgb(240, 240, 238),
             meta_bg: Color::Rgb(244, 244, 242),
             meta_border: Color::Rgb(204, 204, 200),
             context: Color::Rgb(200, 200, 196),
         }
     }
+
+    fn dark() -> Self {
+        Self {
+            bg: Color::Rgb(29, 32, 33),
+            fg: Color::Rgb(235, 219, 178),
+            code_fg: Color::Rgb(235, 219, 178),
+            dim: Color::Rgb(146, 131, 116),
+            add: Color::Rgb(184, 187, 38),
+            del: Color::Rgb(251, 73, 52),
+            keyword: Color::Rgb(131, 165, 152),
+            string: Color::Rgb(250, 189, 47),
+            number: Color::Rgb(211, 134, 155),
+            comment: Color::Rgb(146, 131, 116),
+            function: Color::Rgb(184, 187, 38),
+            type_name: Color::Rgb(142, 192, 124),
+            constant: Color::Rgb(211, 134, 155),
+            code_bg: Color::Rgb(40, 40, 40),
+            meta_bg: Color::Rgb(50, 48, 47),
+            meta_border: Color::Rgb(80, 73, 69),
+            context: Color::Rgb(102, 92, 84),
+        }
+    }
+
+    /// The `[tui] theme` palette with `[tui.colors]` applied. Colors are
+    /// names (`red`, `darkgray`) or `#rrggbb`.
+    fn from_config(config: &TuiConfig) -> Result<Self> {
+        let dark = match config.theme {
+            ThemeMode::Light => false,
+            ThemeMode::Dark => true,
+            ThemeMode::Auto => terminal_background_is_dark().unwrap_or(false),
+        };
+        let mut palette = if dark { Self::dark() } else { Self::light() };
+        for (name, value) in &config.colors {
+            let color = Color::from_str(value)
+                .map_err(|_| anyhow!("Invalid color {value:?} for [tui.colors] {name}"))?;
+            let slot = match name.as_str() {
+                "bg" => &mut palette.bg,
+                "fg" => &mut palette.fg,
+                "code_fg" => &mut palette.code_fg,
+                "dim" => &mut palette.dim,
+                "add" => &mut palette.add,
+                "del" => &mut palette.del,
+                "keyword" => &mut palette.keyword,
+                "string" => &mut palette.string,
+                "number" => &mut palette.number,
+                "comment" => &mut palette.comment,
+                "function" => &mut palette.function,
+                "type" => &mut palette.type_name,
+                "constant" => &mut palette.constant,
+                "code_bg" => &mut palette.code_bg,
+                "meta_bg" => &mut palette.meta_bg,
+                "meta_border" => &mut palette.meta_border,
+                "context" => &mut palette.context,
+                _ => bail!("Unknown [tui.colors] color {name:?}"),
+            };
+            *slot = color;
+        }
+        Ok(palette)
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum TokenKind {
-    Base,
-    Keyword,
-    #[allow(dead_code)]
-    String,
-    Number,
-    #[allow(dead_code)]
-    Comment,
+fn style_for_token(kind: Option<HighlightKind>, palette: &UiPalette) -> Style {
+    match kind {
+        None => Style::default().fg(palette.code_fg),
+        Some(HighlightKind::Keyword) => Style::default()
+            .fg(palette.keyword)
+            .add_modifier(Modifier::BOLD),
+        Some(HighlightKind::String) => Style::default().fg(palette.string),
+        Some(HighlightKind::Number) => Style::default().fg(palette.number),
+        Some(HighlightKind::Comment) => Style::default()
+            .fg(palette.comment)
+            .add_modifier(Modifier::ITALIC),
+        Some(HighlightKind::Function) => Style::default().fg(palette.function),
+        Some(HighlightKind::Type) => Style::default().fg(palette.type_name),
+        Some(HighlightKind::Constant) => Style::default().fg(palette.constant),
+    }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-struct HighlightToken {
-    text: String,
-    kind: TokenKind,
+/// Highlights `lines` as one piece of `language` source.
+fn format_code_lines<S: AsRef<str>>(
+    lines: &[S],
+    palette: &UiPalette,
+    language: Option<&Language>,
+) -> Vec<Line<'static>> {
+    highlight_lines(lines, language)
+        .iter()
+        .map(|tokens| {
+            Line::from(
+                tokens
+                    .iter()
+                    .map(|token| {
+                        Span::styled(
+                            token.text.clone(),
+                            style_for_token(token.kind, palette).bg(palette.code_bg),
+                        )
+                    })
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .collect()
 }
 
-fn highlight_line(line: &str, _language: Option<&Language>) -> Vec<HighlightToken> {
-    // Very basic highlighting for now
-    let mut tokens = Vec::new();
-    let mut current_word = String::new();
+#[cfg(test)]
+mod palette_tests {
+    use super::*;
 
-    for c in line.chars() {
-        if c.is_alphanumeric() || c == '_' {
-            current_word.push(c);
-        } else {
-            if !current_word.is_empty() {
-                tokens.push(classify_token(&current_word));
-                current_word.clear();
-            }
-            tokens.push(HighlightToken {
-                text: c.to_string(),
-                kind: TokenKind::Base,
-            });
-        }
+    #[test]
+    fn reads_terminal_background_replies() {
+        assert_eq!(
+            osc11_is_dark("\x1b]11;rgb:1d1d/2020/2121\x1b\\"),
+            Some(true)
+        );
+        assert_eq!(osc11_is_dark("\x1b]11;rgb:ff/ff/f5\x07"), Some(false));
+        assert_eq!(osc11_is_dark("garbage"), None);
+        assert_eq!(colorfgbg_is_dark("15;0"), Some(true));
+        assert_eq!(colorfgbg_is_dark("0;default;15"), Some(false));
     }
-    if !current_word.is_empty() {
-        tokens.push(classify_token(&current_word));
+
+    #[test]
+    fn colors_override_the_chosen_theme() {
+        let config = TuiConfig {
+            theme: ThemeMode::Dark,
+            colors: HashMap::from([("keyword".to_string(), "#010203".to_string())]),
+            ..Default::default()
+        };
+        let palette = UiPalette::from_config(&config).unwrap();
+        assert_eq!(palette.keyword, Color::Rgb(1, 2, 3));
+        assert_eq!(palette.bg, UiPalette::dark().bg);
+
+        let config = TuiConfig {
+            theme: ThemeMode::Light,
+            colors: HashMap::from([("keywords".to_string(), "red".to_string())]),
+            ..Default::default()
+        };
+        assert!(UiPalette::from_config(&config).is_err());
     }
-    tokens
 }
 
-fn classify_token(word: &str) -> HighlightToken {
-    let kind = match word {
-        "fn" | "struct" | "enum" | "impl" | "use" | "mod" | "pub" | "let" | "mut" | "if"
-        | "else" | "match" | "for" | "while" | "return" | "break" | "continue" | "const"
-        | "static" | "trait" | "type" => TokenKind::Keyword,
-        "true" | "false" => TokenKind::Number,
-        _ if word.chars().all(char::is_numeric) => TokenKind::Number,
-        _ => TokenKind::Base,
-    };
-    HighlightToken {
-        text: word.to_string(),
-        kind,
+#[cfg(test)]
+mod mouse_tests {
+    use super::*;
+
+    fn action_at(targets: &[(Rect, ClickTarget)], x: u16, y: u16) -> Option<ClickTarget> {
+        targets
+            .iter()
+            .find(|(area, _)| area.contains(Position::new(x, y)))
+            .map(|(_, target)| *target)
     }
-}
 
-fn style_for_token(kind: &TokenKind, palette: &UiPalette) -> Style {
-    match kind {
-        TokenKind::Base => Style::default().fg(palette.code_fg),
-        TokenKind::Keyword => Style::default()
-            .fg(palette.keyword)
-            .add_modifier(Modifier::BOLD),
-        TokenKind::String => Style::default().fg(palette.string),
-        TokenKind::Number => Style::default().fg(palette.number),
-        TokenKind::Comment => Style::default().fg(palette.dim),
+    #[test]
+    fn hints_are_clickable_where_they_are_drawn() {
+        let area = Rect::new(0, 10, 80, 4);
+        let rows = build_action_rows(area.width, &Keymap::default());
+        let targets = action_targets(area, &rows);
+        let top = row_text(&rows[0]);
+        let reject = top.find("[x]reject").unwrap() as u16;
+        assert_eq!(
+            action_at(&targets, reject + 2, 10),
+            Some(ClickTarget::Action(Action::Reject))
+        );
+        assert_eq!(
+            action_at(&targets, 0, 10),
+            Some(ClickTarget::Action(Action::Approve))
+        );
+        assert_eq!(action_at(&targets, 40, 10), None);
+
+        let ascend = row_text(&rows[1]);
+        let x = (80 - ascend.width() as u16) / 2;
+        assert_eq!(
+            action_at(&targets, x, 11),
+            Some(ClickTarget::Action(Action::Ascend))
+        );
     }
 }
 
-fn format_code_line(line: &str, palette: &UiPalette, language: Option<&Language>) -> Line<'static> {
-    let tokens = highlight_line(line, language);
-    let mut spans = Vec::with_capacity(tokens.len());
-    for token in tokens {
-        spans.push(Span::styled(
-            token.text,
-            style_for_token(&token.kind, palette).bg(palette.code_bg),
-        ));
+#[cfg(test)]
+mod fold_tests {
+    use super::*;
+    use trueflow_core::block::Block;
+
+    #[test]
+    fn folding_keeps_the_first_line_of_each_sub_block() {
+        let content = "fn a() {\n    let x = 1;\n    let y = 2;\n\n    x + y\n}\n";
+        let mut block = Block::new(content.to_string(), BlockKind::Function, 10, 16);
+        block.start_line = 10;
+        let sub_blocks = sub_splitter::split(&block, Language::Rust).unwrap();
+        let hidden = hidden_ranges(&block, &sub_blocks);
+        assert!(!hidden.is_empty());
+        assert!(hidden.iter().all(|range| range.start > 0 && range.end <= 6));
+
+        let palette = UiPalette::light();
+        let lines: Vec<Line<'static>> = content
+            .lines()
+            .map(|line| Line::from(line.to_string()))
+            .collect();
+        let folded = fold_code_lines(lines, &[Range { start: 1, end: 3 }], &palette);
+        let text: Vec<String> = folded.iter().map(|line| line.to_string()).collect();
+        assert_eq!(text[0], "fn a() {");
+        assert_eq!(text[1], "    ⋯ 2 lines folded");
+        assert_eq!(text[2], "");
+        assert_eq!(text.len(), 5);
     }
-    Line::from(spans)
 }
@@ -0,0 +1,280 @@
+use anyhow::{Result, bail};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::HashMap;
+pub use trueflow_core::key_action::Action;
+
+/// A key, optionally held with Ctrl. Shift is part of the character.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyBinding {
+    pub code: KeyCode,
+    pub ctrl: bool,
+}
+
+impl KeyBinding {
+    pub fn plain(code: KeyCode) -> Self {
+        Self { code, ctrl: false }
+    }
+}
+
+impl From<KeyEvent> for KeyBinding {
+    fn from(event: KeyEvent) -> Self {
+        Self {
+            code: event.code,
+            ctrl: event.modifiers.contains(KeyModifiers::CONTROL),
+        }
+    }
+}
+
+/// Which keys trigger which actions: the defaults, with `[tui.keys]` applied.
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: HashMap<Action, Vec<KeyBinding>>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self {
+            bindings: Action::ALL
+                .iter()
+                .map(|action| {
+                    let keys = action
+                        .default_keys()
+                        .iter()
+                        .map(|name| parse_key(name).expect("default keys parse"))
+                        .collect();
+                    (*action, keys)
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Keymap {
+    /// Replaces the keys of each configured action. A key the config binds
+    /// is taken away from the actions it was bound to by default, so
+    /// `approve = ["l"]` doesn't also move to the next sibling.
+    pub fn from_config(overrides: &HashMap<Action, Vec<String>>) -> Result<Self> {
+        let mut keymap = Self::default();
+        let mut configured: HashMap<Action, Vec<KeyBinding>> = HashMap::new();
+        for (action, names) in overrides {
+            let mut keys = Vec::with_capacity(names.len());
+            for name in names {
+                let Some(key) = parse_key(name) else {
+                    bail!(
+                        "Unknown key {name:?} for [tui.keys] {}; use a character or one of: {} \
+                         (prefix ctrl- to hold Ctrl)",
+                        action.name(),
+                        NAMED_KEYS.join(", ")
+                    );
+                };
+                keys.push(key);
+            }
+            configured.insert(*action, keys);
+        }
+        for (action, keys) in keymap.bindings.iter_mut() {
+            if let Some(custom) = configured.get(action) {
+                *keys = custom.clone();
+            } else {
+                keys.retain(|key| !configured.values().any(|custom| custom.contains(key)));
+            }
+        }
+        Ok(keymap)
+    }
+
+    pub fn keys(&self, action: Action) -> &[KeyBinding] {
+        self.bindings.get(&action).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// The actions bound to `key`, in the order they should be tried.
+    pub fn actions(&self, key: KeyBinding) -> impl Iterator<Item = Action> + '_ {
+        Action::ALL
+            .into_iter()
+            .filter(move |action| self.keys(*action).contains(&key))
+    }
+
+    /// The keys of `action` for display, e.g. `l/→/n`.
+    pub fn label(&self, action: Action) -> String {
+        let keys: Vec<String> = self.keys(action).iter().map(key_label).collect();
+        if keys.is_empty() {
+            "unbound".to_string()
+        } else {
+            keys.join("/")
+        }
+    }
+}
+
+const NAMED_KEYS: [&str; 13] = [
+    "up",
+    "down",
+    "left",
+    "right",
+    "enter",
+    "esc",
+    "space",
+    "tab",
+    "backspace",
+    "pageup",
+    "pagedown",
+    "home",
+    "end",
+];
+
+/// A key as written in `[tui.keys]`: a single character (case matters) or
+/// one of [`NAMED_KEYS`], optionally prefixed with `ctrl-`.
+fn parse_key(name: &str) -> Option<KeyBinding> {
+    let lower = name.to_ascii_lowercase();
+    if let Some(rest) = lower
+        .strip_prefix("ctrl-")
+        .or_else(|| lower.strip_prefix("ctrl+"))
+    {
+        let code = parse_key_code(&name[name.len() - rest.len()..])?;
+        return Some(KeyBinding { code, ctrl: true });
+    }
+    parse_key_code(name).map(KeyBinding::plain)
+}
+
+fn parse_key_code(name: &str) -> Option<KeyCode> {
+    let mut chars = name.chars();
+    if let (Some(ch), None) = (chars.next(), chars.next()) {
+        return Some(KeyCode::Char(ch));
+    }
+    Some(match name.to_ascii_lowercase().as_str() {
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "enter" => KeyCode::Enter,
+        "esc" => KeyCode::Esc,
+        "space" => KeyCode::Char(' '),
+        "tab" => KeyCode::Tab,
+        "backspace" => KeyCode::Backspace,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        _ => return None,
+    })
+}
+
+/// How a key is shown in hints and the help overlay.
+pub fn key_label(key: &KeyBinding) -> String {
+    if key.ctrl {
+        return format!("ctrl-{}", key_code_label(&key.code));
+    }
+    key_code_label(&key.code)
+}
+
+fn key_code_label(key: &KeyCode) -> String {
+    match key {
+        KeyCode::Char(' ') => "space".to_string(),
+        KeyCode::Char(ch) => ch.to_string(),
+        KeyCode::Up => "↑".to_string(),
+        KeyCode::Down => "↓".to_string(),
+        KeyCode::Left => "←".to_string(),
+        KeyCode::Right => "→".to_string(),
+        KeyCode::Enter => "enter".to_string(),
+        KeyCode::Esc => "esc".to_string(),
+        KeyCode::Tab => "tab".to_string(),
+        KeyCode::Backspace => "backspace".to_string(),
+        KeyCode::PageUp => "pgup".to_string(),
+        KeyCode::PageDown => "pgdn".to_string(),
+        KeyCode::Home => "home".to_string(),
+        KeyCode::End => "end".to_string(),
+        other => format!("{other:?}").to_lowercase(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn configured_keys_replace_defaults_and_steal_from_others() {
+        let overrides = HashMap::from([
+            (Action::Approve, vec!["l".to_string()]),
+            (Action::Next, vec!["right".to_string()]),
+        ]);
+        let keymap = Keymap::from_config(&overrides).unwrap();
+        assert_eq!(
+            keymap.keys(Action::Approve),
+            &[KeyBinding::plain(KeyCode::Char('l'))]
+        );
+        assert_eq!(
+            keymap.keys(Action::Next),
+            &[KeyBinding::plain(KeyCode::Right)]
+        );
+        assert_eq!(
+            keymap
+                .actions(KeyBinding::plain(KeyCode::Char('l')))
+                .collect::<Vec<_>>(),
+            vec![Action::Approve]
+        );
+        assert!(
+            keymap
+                .actions(KeyBinding::plain(KeyCode::Char('a')))
+                .next()
+                .is_none()
+        );
+        assert_eq!(keymap.label(Action::Ascend), "i/↑");
+    }
+
+    #[test]
+    fn shared_default_keys_try_actions_in_order() {
+        let keymap = Keymap::default();
+        assert_eq!(
+            keymap
+                .actions(KeyBinding::plain(KeyCode::Char(' ')))
+                .collect::<Vec<_>>(),
+            vec![Action::Start, Action::PageDown]
+        );
+        assert_eq!(
+            keymap
+                .actions(KeyBinding::plain(KeyCode::Char('n')))
+                .collect::<Vec<_>>(),
+            vec![Action::SearchNext, Action::Next]
+        );
+    }
+
+    #[test]
+    fn reads_bindings_from_tui_keys() {
+        let config: trueflow_core::config::TuiConfig =
+            toml::from_str("[keys]\napprove = [\"y\"]\nsearch_next = [\"tab\"]\n").unwrap();
+        let keymap = Keymap::from_config(&config.keys).unwrap();
+        assert_eq!(
+            keymap.keys(Action::Approve),
+            &[KeyBinding::plain(KeyCode::Char('y'))]
+        );
+        assert_eq!(
+            keymap.keys(Action::SearchNext),
+            &[KeyBinding::plain(KeyCode::Tab)]
+        );
+    }
+
+    #[test]
+    fn ctrl_bindings_need_ctrl() {
+        let overrides = HashMap::from([(Action::Quit, vec!["Ctrl-q".to_string()])]);
+        let keymap = Keymap::from_config(&overrides).unwrap();
+        let ctrl_q = KeyBinding {
+            code: KeyCode::Char('q'),
+            ctrl: true,
+        };
+        assert_eq!(
+            keymap.actions(ctrl_q).collect::<Vec<_>>(),
+            vec![Action::Quit]
+        );
+        assert!(
+            keymap
+                .actions(KeyBinding::plain(KeyCode::Char('q')))
+                .next()
+                .is_none()
+        );
+        assert_eq!(keymap.label(Action::HalfPageDown), "ctrl-d");
+    }
+
+    #[test]
+    fn unknown_key_names_are_rejected() {
+        let overrides = HashMap::from([(Action::Quit, vec!["escape-hatch".to_string()])]);
+        let err = Keymap::from_config(&overrides).unwrap_err().to_string();
+        assert!(err.contains("escape-hatch"), "{err}");
+    }
+}
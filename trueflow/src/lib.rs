@@ -1,20 +0,0 @@
-pub mod analysis;
-pub mod block;
-pub mod block_splitter;
-pub mod cli;
-pub mod commands;
-pub mod complexity;
-pub mod config;
-pub mod context;
-pub mod diff_logic;
-pub mod finder;
-pub mod hashing;
-pub mod logging;
-pub mod optimizer;
-pub mod policy;
-pub mod scanner;
-pub mod store;
-pub mod sub_splitter;
-pub mod text_split;
-pub mod tree;
-pub mod vcs;
@@ -1,96 +1,371 @@
+mod cli;
+mod commands;
+mod keymap;
+
 use anyhow::Result;
 use clap::Parser;
 use log::info;
+use std::io::Write;
 
-mod analysis;
-mod block;
-mod block_splitter;
-mod cli;
-mod commands;
-mod complexity;
-mod config;
-mod context;
-mod diff_logic;
-mod hashing;
-mod logging;
-mod optimizer;
-mod policy;
-mod scanner;
-mod store;
-pub mod sub_splitter;
-mod text_split;
-mod tree;
-mod vcs;
+use cli::{Cli, Commands, ConfigAction, HooksAction, ReportAction, StoreAction};
+use trueflow_core::context::TrueflowContext;
+use trueflow_core::output::FeedbackFormat;
+use trueflow_core::{config, config_layers, logging, output, scanner, store, vcs};
 
-use crate::cli::{Cli, Commands};
-use crate::context::TrueflowContext;
+/// `--log-file` wins; otherwise `[logging] structured = true` logs to the store.
+fn structured_log(cli: &Cli) -> Option<logging::StructuredLog> {
+    // Config errors surface later from the command itself.
+    let settings = config::load()
+        .map(|config| config.logging)
+        .unwrap_or_default();
+    let path = match &cli.log_file {
+        Some(path) => path.clone(),
+        None if settings.structured => logging::StructuredLog::default_path().ok()?,
+        None => return None,
+    };
+    Some(logging::StructuredLog {
+        path,
+        max_bytes: settings.max_bytes,
+        max_files: settings.max_files,
+    })
+}
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
-    logging::init_logging(cli.logging_mode, cli.debug)?;
-    let context = TrueflowContext::new(cli);
+    config_layers::configure_overrides(cli.config_overrides.clone());
+    logging::init_logging(cli.logging_mode, cli.debug, structured_log(&cli))?;
+    scanner::configure_jobs(cli.jobs);
+    scanner::configure_submodules(cli.recurse_submodules);
+    vcs::configure_base(cli.base.clone());
+    let context = TrueflowContext {
+        color: cli.color,
+        no_pager: cli.no_pager,
+        output: cli.output,
+        agent: cli.agent.identity(),
+    };
     info!("trueflow starting");
-    info!("logging mode: {:?}", context.invocation.logging_mode);
+    info!("logging mode: {:?}", cli.logging_mode);
     info!("args: {:?}", std::env::args().collect::<Vec<_>>());
     info!("command parsed");
     if let Ok(dir) = context.trueflow_dir() {
         info!("trueflow dir: {}", dir.display());
     }
 
-    match &context.invocation.command {
-        Commands::Diff { json } => commands::diff::run(&context, *json),
+    let structured = cli.output;
+    let result = match &cli.command {
+        Commands::Diff { json, blocks } => {
+            commands::diff::run(&context, context.structured_output(*json), *blocks)
+        }
         Commands::Mark {
             fingerprint,
+            reply_to,
+            stdin,
             verdict,
             check,
             note,
             path,
+            kind,
+            tag,
             line,
-            quiet: _,
-        } => commands::mark::run(
-            &context,
-            commands::mark::MarkParams {
-                fingerprint: fingerprint.clone(),
-                verdict: verdict.parse()?,
+            confidence,
+            tool,
+            session,
+            prompt_ref,
+            refs,
+            sign,
+            quiet,
+        } => {
+            let params = commands::mark::MarkParams {
+                fingerprint: fingerprint.clone().unwrap_or_default(),
+                verdict: match verdict {
+                    Some(verdict) => verdict.parse()?,
+                    None if reply_to.is_some() => store::Verdict::Comment,
+                    None => store::Verdict::Approved,
+                },
                 check: check.clone(),
                 note: note.clone(),
                 path: path.clone(),
                 line: *line,
-            },
+                provenance: Some(store::Provenance {
+                    tool: tool.clone(),
+                    session: session.clone(),
+                    prompt: prompt_ref.clone(),
+                }),
+                confidence: *confidence,
+                refs: refs.clone(),
+                in_reply_to: reply_to.clone(),
+                sign: *sign,
+            };
+            if fingerprint.is_some() || reply_to.is_some() {
+                return commands::mark::run(&context, params);
+            }
+            if *stdin {
+                return commands::mark::run_stdin(&context, params, *quiet);
+            }
+            let selection = commands::mark::BlockSelection {
+                path: path.clone(),
+                kinds: kind
+                    .iter()
+                    .map(|kind| kind.parse())
+                    .collect::<Result<_>>()?,
+                tags: tag.clone(),
+            };
+            commands::mark::run_batch(&context, &selection, params, *quiet)
+        }
+        Commands::Sync { remote, dry_run } => commands::sync::run(&context, remote, *dry_run),
+        Commands::MergeDriver {
+            ancestor,
+            ours,
+            theirs,
+            install,
+        } => match (ancestor, ours, theirs) {
+            (Some(ancestor), Some(ours), Some(theirs)) if !install => {
+                commands::merge_driver::run(ancestor, ours, theirs)
+            }
+            _ => commands::merge_driver::install().map(|_| ()),
+        },
+        Commands::Publish { pr, dry_run } => commands::publish::run(&context, *pr, *dry_run),
+        Commands::Check {
+            reviewer,
+            required_approvals,
+            target,
+            format,
+            json,
+        } => commands::check::run(
+            &context,
+            reviewer.as_deref(),
+            *required_approvals,
+            target.as_deref(),
+            context.check_format(*format, *json),
         ),
-        Commands::Sync => commands::sync::run(&context),
-        Commands::Check => commands::check::run(&context),
-        Commands::Scan { json, tree } => commands::scan::run(&context, *json, *tree),
+        Commands::Scan { json, tree, churn } => {
+            commands::scan::run(&context, context.structured_output(*json), *tree, *churn)
+        }
         Commands::Review {
+            format,
             json,
             all,
             target,
             only,
             exclude,
+            mine,
+            summary,
+            limit,
+            offset,
+            max_files,
+            since_last,
+            count,
+            reviewer,
+            check,
         } => commands::review::run(
             &context,
-            *json,
-            *all,
-            target.clone(),
-            only.clone(),
-            exclude.clone(),
+            commands::review::ReviewParams {
+                format: context.output_format(*format, *json),
+                all: *all,
+                target: target.clone(),
+                only: only.clone(),
+                exclude: exclude.clone(),
+                mine: *mine,
+                summary: *summary,
+                page: commands::review::Page {
+                    offset: *offset,
+                    limit: *limit,
+                    max_files: *max_files,
+                },
+                since_last: *since_last,
+                count: *count,
+                reviewer: reviewer.clone(),
+                check: check.clone(),
+            },
+        ),
+        Commands::AutoApprove {
+            all,
+            target,
+            dry_run,
+        } => commands::auto_approve::run(
+            &context,
+            commands::auto_approve::AutoApproveParams {
+                all: *all,
+                target: target.clone(),
+                dry_run: *dry_run,
+            },
+        ),
+        Commands::Prereview {
+            all,
+            target,
+            force,
+            dry_run,
+        } => commands::prereview::run(
+            &context,
+            commands::prereview::PrereviewParams {
+                all: *all,
+                target: target.clone(),
+                force: *force,
+                dry_run: *dry_run,
+            },
         ),
+        Commands::Assign {
+            target,
+            path,
+            fingerprint,
+            to,
+            note,
+        } => {
+            let target = commands::assign::AssignTarget::from_args(
+                target.as_deref(),
+                path.as_deref(),
+                fingerprint.as_deref(),
+            )?;
+            commands::assign::run(&context, target, to, note.clone())
+        }
+        Commands::Request {
+            all,
+            target,
+            reviewers,
+            note,
+        } => commands::request::run(
+            &context,
+            commands::request::RequestParams {
+                all: *all,
+                target: target.clone(),
+                reviewers: reviewers.clone(),
+                note: note.clone(),
+            },
+        ),
+        Commands::Activity { since, until, json } => commands::activity::run(
+            &context,
+            since.as_deref(),
+            until.as_deref(),
+            context.structured_output(*json),
+        ),
+        Commands::Stats { json, depth, badge } => commands::stats::run(
+            &context,
+            context.structured_output(*json),
+            *depth,
+            badge.as_deref(),
+        ),
+        Commands::Snapshot { depth, json } => {
+            commands::snapshot::run(&context, *depth, context.structured_output(*json))
+        }
+        Commands::Report { action } => match action {
+            ReportAction::Burndown { since, until, json } => commands::report::burndown(
+                Some(since),
+                until.as_deref(),
+                context.structured_output(*json),
+            ),
+            ReportAction::Html { out } => commands::report::html(out),
+        },
+        Commands::History {
+            fingerprint,
+            path,
+            since,
+            until,
+            verdict,
+            identity_type,
+            json,
+        } => commands::history::run(
+            &context,
+            commands::history::HistoryParams {
+                fingerprint: fingerprint.clone(),
+                path: path.clone(),
+                since: since.clone(),
+                until: until.clone(),
+                verdict: verdict.clone(),
+                identity_type: identity_type.clone(),
+                output: context.structured_output(*json),
+            },
+        ),
+        Commands::Questions { path, author, json } => commands::question::list(
+            &context,
+            commands::question::QuestionsParams {
+                path: path.clone(),
+                author: author.clone(),
+                output: context.structured_output(*json),
+            },
+        ),
+        Commands::Answer { id, note } => {
+            commands::question::answer(&context, id, Some(note.clone()))
+        }
+        Commands::Resolve { id, note } => commands::resolve::run(&context, id, note.clone()),
+        Commands::Thread { fingerprint, json } => {
+            commands::thread::run(&context, fingerprint, context.structured_output(*json))
+        }
+        Commands::Requests { json, mine, all } => {
+            commands::request::list(&context, context.structured_output(*json), *mine, *all)
+        }
         Commands::Feedback {
             format,
             include_approved,
+            include_resolved,
             only,
             exclude,
+            author,
+            check,
+            since,
+            verdict,
         } => commands::feedback::run(
             &context,
-            format,
-            *include_approved,
-            only.clone(),
-            exclude.clone(),
+            context
+                .structured_output(false)
+                .map_or(*format, FeedbackFormat::from),
+            commands::feedback::FeedbackFilter {
+                include_approved: *include_approved,
+                include_resolved: *include_resolved,
+                only: only.clone(),
+                exclude: exclude.clone(),
+                author: author.clone(),
+                check: check.clone(),
+                since: since.clone(),
+                verdicts: verdict.clone(),
+            },
         ),
         Commands::Inspect { fingerprint, split } => {
             commands::inspect::run(&context, fingerprint, *split)
         }
-        Commands::Verify { all, id } => commands::verify::run(*all, id.clone()),
-        Commands::Tui => commands::tui::run(&context),
+        Commands::Verify { all, id, strict } => commands::verify::run(*all, id.clone(), *strict),
+        Commands::Export { since, out } => {
+            commands::bundle::export(&context, since.as_deref(), out.as_deref())
+        }
+        Commands::Import { bundle } => commands::bundle::import(&context, bundle),
+        Commands::Schema { schema } => commands::schema::run(*schema),
+        Commands::Completions { shell } => commands::completions::run(*shell),
+        Commands::Man { command, out } => commands::man::run(command, out.as_deref()),
+        Commands::Init { hooks, ci, force } => commands::init::run(commands::init::InitParams {
+            hooks: *hooks,
+            ci: *ci,
+            force: *force,
+        }),
+        Commands::Hooks { action } => match action {
+            HooksAction::Install { force } => commands::hooks::install(*force),
+            HooksAction::Uninstall => commands::hooks::uninstall(),
+        },
+        Commands::Login { issuer, client_id } => {
+            commands::login::run(issuer.clone(), client_id.clone())
+        }
+        Commands::Config { action } => match action {
+            ConfigAction::Get { key } => commands::config::get(key, structured),
+            ConfigAction::Set { key, value, user } => commands::config::set(key, value, *user),
+            ConfigAction::List { all } => commands::config::list(*all, structured),
+            ConfigAction::Doctor => commands::config::doctor(),
+        },
+        Commands::Store { action } => match action {
+            StoreAction::Migrate { dry_run } => commands::store::migrate(*dry_run),
+        },
+        Commands::Lsp => commands::lsp::run(&context),
+        Commands::Serve { port } => commands::serve::run(&context, *port),
+        Commands::Tui { target } => commands::tui::run(&context, target),
+    };
+    if let Err(err) = &result
+        && let Some(exit) = err.downcast_ref::<commands::Exit>()
+    {
+        std::io::stdout().flush()?;
+        std::process::exit(exit.code);
+    }
+    match (result, structured) {
+        (Err(err), Some(format)) => {
+            output::print_error(&err, format)?;
+            std::process::exit(1);
+        }
+        (result, _) => result,
     }
 }
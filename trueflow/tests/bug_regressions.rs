@@ -218,6 +218,7 @@ fn test_exclude_gap_case_insensitive_for_subblocks() -> Result<()> {
     Ok(())
 }
 
+#[cfg(unix)]
 #[test]
 fn test_scan_skips_unreadable_entries() -> Result<()> {
     use std::os::unix::fs::PermissionsExt;
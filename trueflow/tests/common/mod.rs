@@ -5,8 +5,8 @@ use serde_json::Value;
 use std::fs;
 use std::io::{BufWriter, Write};
 use std::path::{Path, PathBuf};
-use std::process::Command;
-use trueflow::store::Record;
+use std::process::{Command, Stdio};
+use trueflow_core::store::Record;
 use uuid::Uuid;
 
 pub struct TestRepo {
@@ -21,6 +21,13 @@ impl TestRepo {
         Ok(Self { path })
     }
 
+    /// A scratch directory outside any version control.
+    pub fn without_vcs(name: &str) -> Result<Self> {
+        let path = temp_dir("trueflow_tests", name);
+        fs::create_dir_all(&path)?;
+        Ok(Self { path })
+    }
+
     pub fn fixture(name: &str) -> Result<Self> {
         let src = Path::new(env!("CARGO_MANIFEST_DIR"))
             .join("example_repos")
@@ -37,16 +44,7 @@ impl TestRepo {
 
         fs::create_dir_all(&path)?;
 
-        // Copy contents
-        let status = Command::new("cp")
-            .arg("-R")
-            .arg(format!("{}/.", src.display()))
-            .arg(&path)
-            .status()?;
-
-        if !status.success() {
-            anyhow::bail!("Failed to copy fixture");
-        }
+        copy_dir_all(&src, &path).context("Failed to copy fixture")?;
 
         init_git(&path)?;
         Ok(Self { path })
@@ -97,6 +95,35 @@ impl TestRepo {
     pub fn run_raw(&self, args: &[&str]) -> Result<std::process::Output> {
         Ok(build_cmd(&self.path, args).output()?)
     }
+
+    /// Runs trueflow with extra environment variables.
+    pub fn run_with_env(&self, args: &[&str], env: &[(&str, &str)]) -> Result<String> {
+        let output = build_cmd(&self.path, args)
+            .envs(env.iter().copied())
+            .output()?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "trueflow failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+        Ok(String::from_utf8(output.stdout)?)
+    }
+
+    /// Runs trueflow with `input` piped to its stdin.
+    pub fn run_with_stdin(&self, args: &[&str], input: &str) -> Result<std::process::Output> {
+        let mut child = build_cmd(&self.path, args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        child
+            .stdin
+            .take()
+            .context("stdin")?
+            .write_all(input.as_bytes())?;
+        Ok(child.wait_with_output()?)
+    }
 }
 
 // Helpers
@@ -108,6 +135,20 @@ fn temp_dir(base: &str, name: &str) -> PathBuf {
         .join(Uuid::new_v4().to_string())
 }
 
+fn copy_dir_all(src: &Path, dst: &Path) -> Result<()> {
+    fs::create_dir_all(dst)?;
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        let target = dst.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir_all(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
+
 fn init_git(path: &Path) -> Result<()> {
     run_git(path, &["init", "-q"])?;
     run_git(path, &["config", "user.email", "test@example.com"])?;
@@ -117,7 +158,10 @@ fn init_git(path: &Path) -> Result<()> {
 
 fn build_cmd(dir: &Path, args: &[&str]) -> Command {
     let mut cmd = Command::new(env!("CARGO_BIN_EXE_trueflow"));
-    cmd.args(args).current_dir(dir);
+    // Keep the developer's own ~/.config/trueflow out of the tests.
+    cmd.args(args)
+        .current_dir(dir)
+        .env("TRUEFLOW_CONFIG_DIR", dir.join(".no-user-config"));
     cmd
 }
 
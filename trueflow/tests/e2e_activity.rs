@@ -0,0 +1,40 @@
+use anyhow::{Context, Result};
+
+mod common;
+use common::{TestRepo, first_block_hash, json_array};
+
+#[test]
+fn test_activity_reports_per_reviewer_counts() -> Result<()> {
+    let repo = TestRepo::new("activity_report")?;
+    repo.write("src/lib.rs", "pub fn core() {}\n")?;
+    repo.commit_all("Add lib")?;
+
+    let output = repo.run(&["review", "--all", "--json"])?;
+    let hash = first_block_hash(&output)?;
+    repo.run(&[
+        "mark",
+        "--fingerprint",
+        &hash,
+        "--verdict",
+        "question",
+        "--note",
+        "why?",
+    ])?;
+
+    let output = repo.run(&["activity", "--since", "1d", "--json"])?;
+    let rows = json_array(&output)?;
+    let row = rows.first().context("Expected an activity row")?;
+    assert_eq!(row["identity"], "test@example.com");
+    assert_eq!(row["blocks_reviewed"], 1);
+    assert_eq!(row["questions"], 1);
+    assert_eq!(row["outstanding_questions"], 1);
+    assert_eq!(row["average_note_length"], 4.0);
+
+    let output = repo.run(&["activity", "--until", "2000-01-01"])?;
+    assert!(output.contains("No review activity"), "{output}");
+
+    let output = repo.run(&["activity"])?;
+    assert!(output.contains("test@example.com"), "{output}");
+
+    Ok(())
+}
@@ -0,0 +1,133 @@
+use anyhow::Result;
+
+mod common;
+use common::{TestRepo, first_block_hash, json_array, read_review_records};
+use trueflow_core::store::Identity;
+
+#[test]
+fn test_mark_as_agent_records_agent_identity() -> Result<()> {
+    let repo = TestRepo::new("agent_identity_mark")?;
+    repo.write("src/lib.rs", "pub fn core() {}\n")?;
+    repo.commit_all("Add lib")?;
+
+    let output = repo.run(&["review", "--all", "--json"])?;
+    let hash = first_block_hash(&output)?;
+    repo.run(&[
+        "--agent",
+        "code-simplifier",
+        "--agent-model",
+        "gpt-x",
+        "mark",
+        "--fingerprint",
+        &hash,
+        "--verdict",
+        "comment",
+        "--note",
+        "inline this",
+    ])?;
+
+    let records = read_review_records(&repo.path.join(".trueflow/reviews.jsonl"))?;
+    assert_eq!(
+        records[0].identity,
+        Identity::Agent {
+            name: "code-simplifier".to_string(),
+            model: Some("gpt-x".to_string()),
+            version: None,
+        }
+    );
+
+    let output = repo.run(&["feedback", "--format", "xml"])?;
+    assert!(
+        output.contains(r#"author="agent:code-simplifier (gpt-x)" agent="true""#),
+        "{output}"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_require_human_ignores_agent_approvals() -> Result<()> {
+    let repo = TestRepo::new("agent_identity_policy")?;
+    repo.write("src/lib.rs", "pub fn core() {}\n")?;
+    repo.commit_all("Add lib")?;
+
+    let output = repo.run(&["review", "--all", "--json"])?;
+    let hash = first_block_hash(&output)?;
+    let marked = repo.run_raw(&["mark", "--fingerprint", &hash, "--verdict", "approved"])?;
+    assert!(marked.status.success());
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_trueflow"))
+        .args(["mark", "--fingerprint", &hash, "--verdict", "rejected"])
+        .env("TRUEFLOW_AGENT", "linter-bot")
+        .current_dir(&repo.path)
+        .output()?;
+    assert!(output.status.success());
+
+    // Agent verdicts count by default, so the later rejection wins.
+    let output = repo.run(&["review", "--all", "--json"])?;
+    assert_eq!(json_array(&output)?.len(), 1);
+
+    repo.write("trueflow.toml", "[policy]\nrequire_human = true\n")?;
+    let output = repo.run(&["review", "--all", "--json", "--exclude", "gap"])?;
+    let files = json_array(&output)?;
+    assert!(
+        files.iter().all(|file| file["path"] != "src/lib.rs"),
+        "Human approval stands when agent verdicts are advisory: {output}"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_mark_records_provenance_and_confidence() -> Result<()> {
+    let repo = TestRepo::new("agent_identity_provenance")?;
+    repo.write("src/lib.rs", "pub fn core() {}\n")?;
+    repo.commit_all("Add lib")?;
+
+    let output = repo.run(&["review", "--all", "--json"])?;
+    let hash = first_block_hash(&output)?;
+    repo.run(&[
+        "--agent",
+        "reviewer-bot",
+        "mark",
+        "--fingerprint",
+        &hash,
+        "--verdict",
+        "approved",
+        "--confidence",
+        "0.4",
+        "--tool",
+        "ci-review",
+        "--session",
+        "run-42",
+        "--prompt-ref",
+        "prompts/review.md",
+    ])?;
+
+    let records = read_review_records(&repo.path.join(".trueflow/reviews.jsonl"))?;
+    let record = &records[0];
+    assert_eq!(record.confidence, Some(0.4));
+    let provenance = record.provenance.as_ref().expect("provenance");
+    assert_eq!(provenance.tool.as_deref(), Some("ci-review"));
+    assert_eq!(provenance.session.as_deref(), Some("run-42"));
+    assert_eq!(provenance.prompt.as_deref(), Some("prompts/review.md"));
+
+    let output = repo.run(&["review", "--all", "--json"])?;
+    assert!(json_array(&output)?.is_empty(), "Agent approval counts");
+
+    repo.write("trueflow.toml", "[policy]\nmin_agent_confidence = 0.8\n")?;
+    let output = repo.run(&["review", "--all", "--json"])?;
+    assert_eq!(
+        first_block_hash(&output)?,
+        hash,
+        "Low-confidence agent approval needs a human"
+    );
+
+    let stderr = repo.run_err(&["mark", "--fingerprint", &hash, "--confidence", "1.5"])?;
+    assert!(
+        stderr.contains("Confidence must be between 0 and 1"),
+        "{stderr}"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,86 @@
+use anyhow::{Context, Result};
+
+mod common;
+use common::{TestRepo, json_array, run_git_output};
+
+fn review_blocks(repo: &TestRepo) -> Result<Vec<serde_json::Value>> {
+    let files = json_array(&repo.run(&["review", "--all", "--json"])?)?;
+    Ok(files
+        .iter()
+        .flat_map(|file| file["blocks"].as_array().cloned().unwrap_or_default())
+        .collect())
+}
+
+#[test]
+fn test_edited_block_shows_the_approved_version() -> Result<()> {
+    let repo = TestRepo::new("anchored_review")?;
+    let approved = "pub fn total(items: &[u32]) -> u32 {\n    items.iter().sum()\n}\n";
+    repo.write("src/lib.rs", approved)?;
+    repo.commit_all("Initial")?;
+    let head = run_git_output(&repo.path, &["rev-parse", "HEAD"])?;
+    let head = head.trim();
+
+    let blocks = review_blocks(&repo)?;
+    let hash = blocks[0]["hash"].as_str().context("hash")?.to_string();
+    assert!(blocks[0].get("previous_content").is_none());
+    repo.run(&[
+        "mark",
+        "--fingerprint",
+        &hash,
+        "--path",
+        "src/lib.rs",
+        "--quiet",
+    ])?;
+    assert!(review_blocks(&repo)?.is_empty());
+
+    repo.write(
+        "src/lib.rs",
+        "pub fn total(items: &[u32]) -> u32 {\n    items.iter().copied().max().unwrap_or(0)\n}\n",
+    )?;
+    let blocks = review_blocks(&repo)?;
+    assert_eq!(blocks.len(), 1);
+    let block = &blocks[0];
+    assert_eq!(block["previous_content"], approved.trim_end());
+    assert_eq!(block["previous_fingerprint"], hash.as_str());
+    assert_eq!(block["previous_revision"], head);
+
+    let text = repo.run(&["review", "--all"])?;
+    assert!(
+        text.contains(&format!("Changed since approval at {}", &head[..7])),
+        "{text}"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_new_block_has_no_previous_version() -> Result<()> {
+    let repo = TestRepo::new("anchored_review_new")?;
+    repo.write("src/lib.rs", "pub fn one() -> u8 {\n    1\n}\n")?;
+    repo.commit_all("Initial")?;
+    let hash = review_blocks(&repo)?[0]["hash"]
+        .as_str()
+        .context("hash")?
+        .to_string();
+    repo.run(&[
+        "mark",
+        "--fingerprint",
+        &hash,
+        "--path",
+        "src/lib.rs",
+        "--quiet",
+    ])?;
+
+    repo.write(
+        "src/lib.rs",
+        "pub fn one() -> u8 {\n    1\n}\n\npub fn two() -> u8 {\n    2\n}\n",
+    )?;
+    let blocks = review_blocks(&repo)?;
+    assert!(!blocks.is_empty());
+    assert!(
+        blocks
+            .iter()
+            .all(|block| block.get("previous_content").is_none()),
+        "{blocks:?}"
+    );
+    Ok(())
+}
@@ -0,0 +1,133 @@
+use anyhow::Result;
+
+mod common;
+use common::{TestRepo, json_array, read_review_records};
+use trueflow_core::store::RecordKind;
+
+fn block_hashes(output: &str) -> Result<Vec<String>> {
+    let files = json_array(output)?;
+    Ok(files
+        .iter()
+        .flat_map(|file| file["blocks"].as_array().cloned().unwrap_or_default())
+        .filter_map(|block| block["hash"].as_str().map(str::to_string))
+        .collect())
+}
+
+fn function_hash(output: &str, name: &str) -> Result<String> {
+    let files = json_array(output)?;
+    files
+        .iter()
+        .flat_map(|file| file["blocks"].as_array().cloned().unwrap_or_default())
+        .find(|block| {
+            block["kind"] == "function"
+                && block["content"]
+                    .as_str()
+                    .is_some_and(|content| content.contains(name))
+        })
+        .and_then(|block| block["hash"].as_str().map(str::to_string))
+        .ok_or_else(|| anyhow::anyhow!("function {name} not found"))
+}
+
+#[test]
+fn test_review_mine_shows_only_blocks_assigned_to_me() -> Result<()> {
+    let repo = TestRepo::new("assign_mine")?;
+    repo.write("src/lib.rs", "pub fn mine() {}\n\npub fn theirs() {}\n")?;
+    repo.commit_all("Add lib")?;
+
+    let output = repo.run(&["review", "--all", "--json"])?;
+    let mine = function_hash(&output, "mine")?;
+    let theirs = function_hash(&output, "theirs")?;
+
+    let output = repo.run(&["review", "--mine", "--json"])?;
+    assert!(
+        json_array(&output)?.is_empty(),
+        "Nothing is assigned yet: {output}"
+    );
+
+    repo.run(&["assign", &mine, "--to", "test@example.com"])?;
+    repo.run(&["assign", &theirs, "--to", "bob@example.com"])?;
+
+    let output = repo.run(&["review", "--mine", "--json"])?;
+    assert_eq!(block_hashes(&output)?, vec![mine.clone()]);
+
+    // Assignments never count as verdicts.
+    let output = repo.run(&["review", "--all", "--json"])?;
+    let hashes = block_hashes(&output)?;
+    assert!(hashes.contains(&mine) && hashes.contains(&theirs));
+
+    repo.run(&["mark", "--fingerprint", &mine, "--verdict", "approved"])?;
+    let output = repo.run(&["review", "--mine", "--json"])?;
+    assert!(json_array(&output)?.is_empty(), "Approved block is done");
+
+    Ok(())
+}
+
+#[test]
+fn test_assign_path_covers_blocks_below_it() -> Result<()> {
+    let repo = TestRepo::new("assign_path")?;
+    repo.write("src/net/http.rs", "pub fn get() {}\n")?;
+    repo.write("src/core.rs", "pub fn core() {}\n")?;
+    repo.commit_all("Add files")?;
+
+    repo.run(&["assign", "src/net", "--to", "test@example.com"])?;
+
+    let output = repo.run(&["review", "--mine", "--json"])?;
+    let files = json_array(&output)?;
+    let paths: Vec<&str> = files
+        .iter()
+        .filter_map(|file| file["path"].as_str())
+        .collect();
+    assert_eq!(paths, vec!["src/net/http.rs"]);
+
+    let records = read_review_records(&repo.path.join(".trueflow/reviews.jsonl"))?;
+    assert_eq!(records.len(), 1);
+    let record = &records[0];
+    assert_eq!(record.kind, RecordKind::Assignment);
+    let assignment = record.assignment.as_ref().expect("assignment payload");
+    assert_eq!(assignment.path.as_deref(), Some("src/net"));
+    assert_eq!(assignment.assignee.label(), "test@example.com");
+
+    Ok(())
+}
+
+#[test]
+fn test_later_assignment_reassigns() -> Result<()> {
+    let repo = TestRepo::new("assign_reassign")?;
+    repo.write("src/lib.rs", "pub fn core() {}\n")?;
+    repo.commit_all("Add lib")?;
+
+    repo.run(&["assign", "src", "--to", "test@example.com"])?;
+    let output = repo.run(&["review", "--mine", "--json"])?;
+    assert!(!json_array(&output)?.is_empty());
+
+    repo.run(&["assign", "src", "--to", "bob@example.com"])?;
+    let output = repo.run(&["review", "--mine", "--json"])?;
+    assert!(json_array(&output)?.is_empty(), "Reassigned to bob");
+
+    Ok(())
+}
+
+#[test]
+fn test_assign_flags_name_the_target_explicitly() -> Result<()> {
+    let repo = TestRepo::new("assign_flags")?;
+    repo.write("src/net/http.rs", "pub fn get() {}\n")?;
+    repo.write("src/core.rs", "pub fn core() {}\n")?;
+    repo.commit_all("Add files")?;
+    let output = repo.run(&["review", "--all", "--json"])?;
+    let core = function_hash(&output, "core")?;
+
+    repo.run(&["assign", "--path", "src/net/", "--to", "test@example.com"])?;
+    repo.run(&["assign", "--fingerprint", &core, "--to", "test@example.com"])?;
+    let records = read_review_records(&repo.path.join(".trueflow/reviews.jsonl"))?;
+    let assignment = records[0].assignment.as_ref().expect("assignment payload");
+    assert_eq!(assignment.path.as_deref(), Some("src/net"));
+    assert_eq!(records[1].fingerprint, core);
+    assert_eq!(records[1].assignment.as_ref().unwrap().path, None);
+
+    let output = repo.run(&["review", "--mine", "--json"])?;
+    assert_eq!(json_array(&output)?.len(), 2);
+
+    let err = repo.run_err(&["assign", "--path", "src/nte", "--to", "bob@example.com"])?;
+    assert!(err.contains("No such file or directory: src/nte"), "{err}");
+    Ok(())
+}
@@ -0,0 +1,162 @@
+use anyhow::Result;
+
+mod common;
+use common::{TestRepo, json_array, read_review_records};
+use trueflow_core::store::Identity;
+
+const SOURCE: &str =
+    "use std::fmt;\n\n// Formats things.\npub fn core() -> fmt::Result {\n    Ok(())\n}\n";
+
+fn block_kinds(output: &str) -> Result<Vec<String>> {
+    let files = json_array(output)?;
+    Ok(files
+        .iter()
+        .flat_map(|file| file["blocks"].as_array().cloned().unwrap_or_default())
+        .filter_map(|block| block["kind"].as_str().map(str::to_string))
+        .collect())
+}
+
+#[test]
+fn test_auto_approve_is_off_by_default() -> Result<()> {
+    let repo = TestRepo::new("auto_approve_default")?;
+    repo.write("src/lib.rs", SOURCE)?;
+    repo.commit_all("Add lib")?;
+
+    let output = repo.run(&["review", "--all", "--json"])?;
+    assert!(block_kinds(&output)?.iter().any(|kind| kind == "import"));
+    assert!(!repo.path.join(".trueflow/reviews.jsonl").exists());
+
+    Ok(())
+}
+
+#[test]
+fn test_auto_approve_records_trivial_blocks() -> Result<()> {
+    let repo = TestRepo::new("auto_approve_enabled")?;
+    repo.write("src/lib.rs", SOURCE)?;
+    repo.write(
+        "trueflow.toml",
+        "[auto_approve]\nenabled = true\nkinds = [\"gap\", \"import\"]\n",
+    )?;
+    repo.commit_all("Add lib")?;
+
+    let output = repo.run_raw(&["review", "--all", "--json"])?;
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr)?;
+    assert!(stderr.contains("Auto-approved"), "{stderr}");
+
+    let kinds = block_kinds(&String::from_utf8(output.stdout)?)?;
+    assert!(!kinds.iter().any(|kind| kind == "import" || kind == "gap"));
+    assert!(kinds.iter().any(|kind| kind == "function"), "{kinds:?}");
+
+    let records = read_review_records(&repo.path.join(".trueflow/reviews.jsonl"))?;
+    assert!(!records.is_empty());
+    for record in &records {
+        assert!(matches!(
+            &record.identity,
+            Identity::Machine { name, .. } if name == "trueflow-auto-approve"
+        ));
+        assert_eq!(
+            record.tags.as_deref(),
+            Some(&["auto-approved".to_string()][..])
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_auto_approve_command_applies_path_and_duplicate_rules() -> Result<()> {
+    let repo = TestRepo::new("auto_approve_command")?;
+    repo.write(
+        "src/math.rs",
+        "pub fn add(a: u32, b: u32) -> u32 {\n    let sum = a + b;\n    sum\n}\n",
+    )?;
+    repo.write(
+        "trueflow.toml",
+        "[auto_approve]\nkinds = []\npaths = [\"generated/**\"]\nduplicates = true\n",
+    )?;
+    repo.commit_all("Initial")?;
+    let files = json_array(&repo.run(&["review", "--all", "--json", "--only", "function"])?)?;
+    let hash = files[0]["blocks"][0]["hash"].as_str().unwrap_or_default();
+    repo.run(&[
+        "mark",
+        "--fingerprint",
+        hash,
+        "--path",
+        "src/math.rs",
+        "--quiet",
+    ])?;
+
+    repo.write(
+        "src/util.rs",
+        "pub fn add(a: u32, b: u32) -> u32 {\n    // Same as math::add.\n    let total = a + b;\n    total\n}\n",
+    )?;
+    repo.write("generated/api.rs", "pub fn api() -> u8 {\n    2\n}\n")?;
+    repo.write("src/lib.rs", "pub fn other() -> u8 {\n    3\n}\n")?;
+    repo.commit_all("Edits")?;
+
+    let dry_run = repo.run(&["auto-approve", "--all", "--dry-run"])?;
+    assert!(
+        dry_run.contains("generated/api.rs:1 function: path matches [auto_approve] paths"),
+        "{dry_run}"
+    );
+    assert!(
+        dry_run.contains("src/util.rs:1 function: same as approved"),
+        "{dry_run}"
+    );
+    assert!(
+        dry_run.contains("Would auto-approve 2 block(s)."),
+        "{dry_run}"
+    );
+    let function_paths = |repo: &TestRepo| -> Result<Vec<String>> {
+        let files = json_array(&repo.run(&["review", "--all", "--json", "--only", "function"])?)?;
+        Ok(files
+            .iter()
+            .filter_map(|file| file["path"].as_str().map(str::to_string))
+            .collect())
+    };
+    assert!(function_paths(&repo)?.contains(&"src/util.rs".to_string()));
+
+    let output = repo.run(&["auto-approve", "--all"])?;
+    assert!(
+        output.contains("Auto-approved 2 block(s): duplicate 1, path 1"),
+        "{output}"
+    );
+    assert_eq!(function_paths(&repo)?, vec!["src/lib.rs".to_string()]);
+
+    let history = repo.run(&["history", "--identity-type", "machine", "--json"])?;
+    let records = json_array(&history)?;
+    assert_eq!(records.len(), 2);
+    assert!(
+        records
+            .iter()
+            .all(|record| record["identity"]["type"] == "machine")
+    );
+    Ok(())
+}
+
+#[test]
+fn test_auto_approve_command_approves_lockfiles() -> Result<()> {
+    let repo = TestRepo::new("auto_approve_lockfile")?;
+    repo.write(
+        "Cargo.lock",
+        "version = 3\n\n[[package]]\nname = \"demo\"\n",
+    )?;
+    repo.write("src/lib.rs", "pub fn one() -> u8 {\n    1\n}\n")?;
+    repo.write(
+        "trueflow.toml",
+        "[auto_approve]\nkinds = []\ngenerated = true\n",
+    )?;
+    repo.commit_all("Initial")?;
+
+    let output = repo.run(&["auto-approve", "--all"])?;
+    assert!(output.contains("generated"), "{output}");
+    let records = read_review_records(&repo.path.join(".trueflow/reviews.jsonl"))?;
+    assert!(!records.is_empty());
+    assert!(
+        records
+            .iter()
+            .all(|record| record.path_hint.as_deref() == Some("Cargo.lock"))
+    );
+    Ok(())
+}
@@ -0,0 +1,37 @@
+use anyhow::Result;
+
+mod common;
+use common::{TestRepo, json_array};
+
+#[test]
+fn test_review_json_blames_committed_and_new_lines() -> Result<()> {
+    let repo = TestRepo::new("blame_review")?;
+    repo.write("src/a.rs", "fn a() {\n    1\n}\n\nfn b() {\n    2\n}\n")?;
+    repo.commit_all("Add a and b")?;
+    repo.write(
+        "src/a.rs",
+        "fn a() {\n    1\n}\n\nfn b() {\n    3\n}\n\nfn c() {}\n",
+    )?;
+
+    let files = json_array(&repo.run(&["review", "--all", "--json"])?)?;
+    let blocks = files[0]["blocks"].as_array().expect("blocks");
+    let blame_at = |line: u64| {
+        blocks
+            .iter()
+            .find(|block| block["start_line"] == line)
+            .map(|block| block["blame"].clone())
+            .expect("block")
+    };
+
+    let edited = blame_at(4);
+    assert_eq!(edited["authors"][0]["name"], "Test User");
+    assert_eq!(edited["authors"][0]["lines"], 2);
+    assert_eq!(edited["uncommitted_lines"], 1);
+    assert_eq!(edited["last_commit"]["summary"], "Add a and b");
+
+    let new = blame_at(8);
+    assert_eq!(new["authors"].as_array().map(Vec::len), Some(0));
+    assert_eq!(new["uncommitted_lines"], 1);
+    assert!(new.get("last_commit").is_none(), "{new}");
+    Ok(())
+}
@@ -0,0 +1,81 @@
+use anyhow::Result;
+
+mod common;
+use common::*;
+
+fn record(id: &str, timestamp: i64) -> serde_json::Value {
+    build_review_record(
+        "deadbeef",
+        ReviewRecordOverrides {
+            id: Some(id),
+            email: Some("test@example.com"),
+            timestamp: Some(timestamp),
+            ..Default::default()
+        },
+    )
+}
+
+#[test]
+fn test_export_and_import_move_records_between_repos() -> Result<()> {
+    let source = TestRepo::new("bundle_source")?;
+    let attested = build_review_record(
+        "deadbeef",
+        ReviewRecordOverrides {
+            id: Some("attested"),
+            email: Some("test@example.com"),
+            timestamp: Some(2000),
+            attestations: Some(serde_json::json!([{
+                "kind": "SSH",
+                "canonicalization": "JCS_V1",
+                "signature": "sig",
+                "public_key": "key"
+            }])),
+            ..Default::default()
+        },
+    );
+    write_reviews_jsonl(
+        &source.path.join(".trueflow"),
+        &[record("old", 1000), attested, record("shared", 3000)],
+    )?;
+
+    let bundle = source.path.join("bundle.json");
+    let output = source.run(&["export", "--since", "1500", "-o", &bundle.to_string_lossy()])?;
+    assert!(output.contains("Exported 2 record(s)"), "{output}");
+
+    let target = TestRepo::new("bundle_target")?;
+    write_reviews_jsonl(&target.path.join(".trueflow"), &[record("shared", 3000)])?;
+    let output = target.run(&["import", &bundle.to_string_lossy()])?;
+    assert!(
+        output.contains("Imported 1 record(s); 1 already present."),
+        "{output}"
+    );
+    let output = target.run(&["import", &bundle.to_string_lossy()])?;
+    assert!(output.contains("Imported 0 record(s)"), "{output}");
+
+    let records = read_review_records(&target.path.join(".trueflow/reviews.jsonl"))?;
+    let ids: Vec<&str> = records.iter().map(|record| record.id.as_str()).collect();
+    assert_eq!(ids, ["shared", "attested"]);
+    let attestation = &records[1].attestations.as_ref().expect("attestations")[0];
+    assert_eq!(attestation.signature, "sig");
+
+    Ok(())
+}
+
+#[test]
+fn test_export_since_revision_uses_its_commit_time() -> Result<()> {
+    let repo = TestRepo::new("bundle_since_rev")?;
+    repo.write("src/lib.rs", "pub fn core() {}\n")?;
+    repo.commit_all("Initial")?;
+    write_reviews_jsonl(&repo.path.join(".trueflow"), &[record("old", 1000)])?;
+
+    let bundle = json(&repo.run(&["export", "--since", "HEAD"])?)?;
+    assert_eq!(bundle["version"], 1);
+    assert!(bundle["records"].as_array().is_some_and(Vec::is_empty));
+    let bundle = json(&repo.run(&["export"])?)?;
+    assert_eq!(bundle["records"][0]["id"], "old");
+
+    let err = repo.run_err(&["export", "--since", "no-such-ref"])?;
+    assert!(err.contains("neither a time nor a revision"), "{err}");
+
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use anyhow::Result;
+
+mod common;
+use common::{TestRepo, first_block_hash, json};
+
+#[test]
+fn test_snapshots_feed_the_burndown_report() -> Result<()> {
+    let repo = TestRepo::new("burndown")?;
+    repo.write("src/lib.rs", "pub fn core() {}\n")?;
+    repo.write("docs/notes.py", "def helper():\n    return 1\n")?;
+    repo.commit_all("Add files")?;
+
+    let output = repo.run(&["report", "burndown"])?;
+    assert!(output.contains("No snapshots in this window"), "{output}");
+
+    let first = json(&repo.run(&["snapshot", "--json"])?)?;
+    assert_eq!(first["unreviewed"], first["total_blocks"]);
+    assert_eq!(first["directories"]["src"], 1);
+
+    let output = repo.run(&["review", "--target", "file:src/lib.rs", "--json"])?;
+    let hash = first_block_hash(&output)?;
+    repo.run(&["mark", "--fingerprint", &hash, "--verdict", "approved"])?;
+    let output = repo.run(&["snapshot"])?;
+    assert!(output.contains("metrics.jsonl"), "{output}");
+
+    let metrics = std::fs::read_to_string(repo.path.join(".trueflow/metrics.jsonl"))?;
+    assert_eq!(metrics.lines().count(), 2);
+
+    let report = json(&repo.run(&["report", "burndown", "--since", "30d", "--json"])?)?;
+    assert_eq!(report["points"].as_array().map(Vec::len), Some(2));
+    assert_eq!(report["change"], -1);
+    let src = report["directories"]
+        .as_array()
+        .and_then(|rows| rows.iter().find(|row| row["name"] == "src"))
+        .cloned()
+        .expect("src row");
+    assert_eq!(
+        (src["start"].as_i64(), src["end"].as_i64()),
+        (Some(1), Some(0))
+    );
+
+    let output = repo.run(&["report", "burndown"])?;
+    assert!(output.contains('#'), "{output}");
+    assert!(output.contains("over 2 snapshot(s)"), "{output}");
+
+    let output = repo.run(&["report", "burndown", "--until", "2000-01-01"])?;
+    assert!(output.contains("No snapshots in this window"), "{output}");
+    Ok(())
+}
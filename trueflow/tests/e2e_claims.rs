@@ -0,0 +1,52 @@
+use anyhow::{Context, Result};
+use serde_json::json;
+
+mod common;
+use common::{
+    ReviewRecordOverrides, TestRepo, build_review_record, first_block_hash, json_array,
+    write_reviews_jsonl,
+};
+
+#[test]
+fn test_claim_records_do_not_change_review_state() -> Result<()> {
+    let repo = TestRepo::new("claims_ledger")?;
+    repo.write("src/lib.rs", "pub fn core() {}\n")?;
+    repo.commit_all("Add lib")?;
+
+    let output = repo.run(&["review", "--all", "--json"])?;
+    let hash = first_block_hash(&output)?;
+
+    let rejected = build_review_record(
+        &hash,
+        ReviewRecordOverrides {
+            verdict: Some("rejected"),
+            timestamp: Some(10),
+            ..Default::default()
+        },
+    );
+    let mut claim = build_review_record(
+        &hash,
+        ReviewRecordOverrides {
+            check: Some("claim"),
+            verdict: Some("comment"),
+            email: Some("bob@example.com"),
+            timestamp: Some(20),
+            ..Default::default()
+        },
+    );
+    claim["kind"] = json!("claim");
+    claim["claim"] = json!({ "path": "src/lib.rs", "expires_at": i64::MAX });
+    write_reviews_jsonl(&repo.path.join(".trueflow"), &[rejected, claim])?;
+
+    let output = repo.run(&["feedback", "--format", "json"])?;
+    let feedback = json_array(&output)?;
+    let entry = feedback.first().context("Expected feedback entry")?;
+    assert_eq!(entry["latest_verdict"], "rejected");
+    let reviews = entry["reviews"].as_array().context("reviews")?;
+    assert_eq!(reviews.len(), 1, "Claims are not reviews");
+
+    let output = repo.run(&["review", "--all", "--json"])?;
+    assert_eq!(first_block_hash(&output)?, hash);
+
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use anyhow::Result;
+
+mod common;
+use common::{TestRepo, json_array};
+
+#[test]
+fn test_review_json_lists_the_commits_behind_each_block() -> Result<()> {
+    let repo = TestRepo::new("commit_context")?;
+    repo.write("src/a.rs", "fn a() {\n    1\n}\n")?;
+    repo.commit_all("Add a")?;
+    repo.write("src/a.rs", "fn a() {\n    1\n}\n\nfn b() {\n    2\n}\n")?;
+    repo.commit_all("Add b\n\nCallers need a second number.")?;
+    repo.write("src/a.rs", "fn a() {\n    3\n}\n\nfn b() {\n    2\n}\n")?;
+    repo.commit_all("Bump a")?;
+
+    let files = json_array(&repo.run(&["review", "--target", "rev:HEAD~2..HEAD", "--json"])?)?;
+    let blocks = files[0]["blocks"].as_array().expect("blocks");
+    let summaries_at = |line: u64| {
+        blocks
+            .iter()
+            .find(|block| block["start_line"] == line)
+            .and_then(|block| block["commits"].as_array())
+            .map(|commits| {
+                commits
+                    .iter()
+                    .map(|commit| commit["summary"].as_str().unwrap_or_default())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default()
+    };
+    assert_eq!(summaries_at(0), ["Bump a"]);
+    assert_eq!(summaries_at(4), ["Add b"]);
+
+    let files = json_array(&repo.run(&["review", "--target", "rev:HEAD~1", "--json"])?)?;
+    let commits = files[0]["blocks"]
+        .as_array()
+        .expect("blocks")
+        .iter()
+        .find(|block| block["start_line"] == 4)
+        .map(|block| block["commits"].clone())
+        .expect("block");
+    assert_eq!(
+        commits[0]["message"],
+        "Add b\n\nCallers need a second number."
+    );
+    Ok(())
+}
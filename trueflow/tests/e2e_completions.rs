@@ -0,0 +1,37 @@
+use anyhow::Result;
+
+mod common;
+use common::*;
+
+#[test]
+fn test_completions_cover_subcommands() -> Result<()> {
+    let repo = TestRepo::new("completions")?;
+    for shell in ["bash", "zsh", "fish", "powershell"] {
+        let script = repo.run(&["completions", shell])?;
+        assert!(script.contains("review"), "{shell}: {script}");
+        assert!(script.contains("auto-approve"), "{shell}: {script}");
+    }
+    assert!(repo.run_err(&["completions", "tcsh"]).is_ok());
+    Ok(())
+}
+
+#[test]
+fn test_man_renders_pages_for_subcommands() -> Result<()> {
+    let repo = TestRepo::new("man_pages")?;
+    let page = repo.run(&["man"])?;
+    assert!(page.contains(".TH trueflow 1"), "{page}");
+
+    let page = repo.run(&["man", "store", "migrate"])?;
+    assert!(page.contains(".TH trueflow-store-migrate 1"), "{page}");
+    assert!(
+        repo.run_err(&["man", "nope"])?
+            .contains("No subcommand `nope`")
+    );
+
+    let out = repo.path.join("man");
+    repo.run(&["man", "--out", out.to_str().unwrap()])?;
+    assert!(out.join("trueflow.1").is_file());
+    assert!(out.join("trueflow-review.1").is_file());
+    assert!(out.join("trueflow-hooks-install.1").is_file());
+    Ok(())
+}
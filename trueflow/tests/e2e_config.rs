@@ -0,0 +1,164 @@
+use anyhow::Result;
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+
+mod common;
+use common::*;
+
+fn trueflow(
+    repo: &TestRepo,
+    user_dir: &Path,
+    envs: &[(&str, &str)],
+    args: &[&str],
+) -> Result<Output> {
+    Ok(Command::new(env!("CARGO_BIN_EXE_trueflow"))
+        .args(args)
+        .current_dir(&repo.path)
+        .env("TRUEFLOW_CONFIG_DIR", user_dir)
+        .envs(envs.iter().copied())
+        .output()?)
+}
+
+fn stdout(output: Output) -> Result<String> {
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+fn layered_repo(name: &str) -> Result<(TestRepo, std::path::PathBuf)> {
+    let repo = TestRepo::new(name)?;
+    let user_dir = repo.path.join(".user");
+    fs::create_dir_all(&user_dir)?;
+    fs::write(
+        user_dir.join("config.toml"),
+        "[policy]\nrequire_human = true\nrequired_approvals = 3\n\n[tui]\ntheme = \"dark\"\n",
+    )?;
+    repo.write(
+        "trueflow.toml",
+        "# Team policy\n[policy]\nrequired_approvals = 2\n",
+    )?;
+    Ok((repo, user_dir))
+}
+
+#[test]
+fn test_layers_override_in_order() -> Result<()> {
+    let (repo, user) = layered_repo("config_layers")?;
+
+    let get = |envs: &[(&str, &str)], args: &[&str]| -> Result<String> {
+        stdout(trueflow(&repo, &user, envs, args)?).map(|out| out.trim().to_string())
+    };
+    assert_eq!(get(&[], &["config", "get", "tui.theme"])?, "dark");
+    assert_eq!(
+        get(&[], &["config", "get", "policy.required_approvals"])?,
+        "2"
+    );
+    assert_eq!(
+        get(
+            &[("TRUEFLOW__POLICY__REQUIRED_APPROVALS", "4")],
+            &["config", "get", "policy.required_approvals"]
+        )?,
+        "4"
+    );
+    assert_eq!(
+        get(
+            &[("TRUEFLOW__POLICY__REQUIRED_APPROVALS", "4")],
+            &[
+                "-c",
+                "policy.required_approvals=5",
+                "config",
+                "get",
+                "policy.required_approvals"
+            ]
+        )?,
+        "5"
+    );
+    assert_eq!(get(&[], &["config", "get", "scan.jobs"])?, "0");
+
+    let list = get(&[], &["config", "list"])?;
+    let user_path = user.join("config.toml");
+    assert!(
+        list.contains(&format!(
+            "policy.require_human = true  # user {}",
+            user_path.display()
+        )),
+        "{list}"
+    );
+    assert!(
+        list.contains("policy.required_approvals = 2  # repo "),
+        "{list}"
+    );
+    assert!(!list.contains("scan.jobs"), "{list}");
+    assert!(get(&[], &["config", "list", "--all"])?.contains("scan.jobs = 0  # default"));
+    assert!(get(&[], &["config", "list", "--all"])?.contains("team.claims = true  # default"));
+    Ok(())
+}
+
+#[test]
+fn test_set_keeps_comments_and_rejects_unknown_keys() -> Result<()> {
+    let (repo, user) = layered_repo("config_set")?;
+
+    stdout(trueflow(
+        &repo,
+        &user,
+        &[],
+        &["config", "set", "policy.per_reviewer", "true"],
+    )?)?;
+    assert_eq!(
+        fs::read_to_string(repo.path.join("trueflow.toml"))?,
+        "# Team policy\n[policy]\nrequired_approvals = 2\nper_reviewer = true\n"
+    );
+
+    stdout(trueflow(
+        &repo,
+        &user,
+        &[],
+        &["config", "set", "--user", "tui.theme", "light"],
+    )?)?;
+    assert!(fs::read_to_string(user.join("config.toml"))?.contains("theme = \"light\""));
+
+    let output = trueflow(
+        &repo,
+        &user,
+        &[],
+        &["config", "set", "policy.per_reviwer", "true"],
+    )?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains("Unknown config key policy.per_reviwer"));
+    Ok(())
+}
+
+#[test]
+fn test_doctor_reports_layers_and_problems() -> Result<()> {
+    let (repo, user) = layered_repo("config_doctor")?;
+
+    let report = stdout(trueflow(&repo, &user, &[], &["config", "doctor"])?)?;
+    assert!(
+        report.contains(&format!("user      {}", user.join("config.toml").display())),
+        "{report}"
+    );
+    assert!(report.contains("No problems found."), "{report}");
+
+    let output = trueflow(
+        &repo,
+        &user,
+        &[("TRUEFLOW__POLICY__REQUIRE_HUMANS", "true")],
+        &["config", "doctor"],
+    )?;
+    assert!(!output.status.success());
+    let report = String::from_utf8(output.stdout)?;
+    assert!(
+        report.contains("env       TRUEFLOW__POLICY__REQUIRE_HUMANS"),
+        "{report}"
+    );
+    assert!(
+        report.contains(
+            "Problem: Unknown key policy.require_humans (env TRUEFLOW__POLICY__REQUIRE_HUMANS)"
+        ),
+        "{report}"
+    );
+    Ok(())
+}
@@ -27,14 +27,8 @@ fn test_vet_diff_initial_state() -> Result<()> {
     repo.write("src/main.rs", "fn main() { println!(\"Hello\"); }")?;
     repo.commit_all("Initial commit")?;
 
-    // 2. Run `vet diff --json`
-    // Since we just added code and haven't reviewed it, vet should show it as unreviewed.
-    // Wait... if it's the initial commit, does it show up in diff?
-    // "vet diff" usually compares HEAD vs main.
-    // If we are ON main, diff main..HEAD is empty.
-    // Ah, design says: "Get diff HEAD vs main".
-    // If I just committed to main, HEAD == main.
-    // So usually we vet a feature branch.
+    // 2. `trueflow diff` compares HEAD against main, so on main itself
+    // there is nothing to review; changes are reviewed on a feature branch.
 
     // Let's create a feature branch.
     checkout_branch(&repo, "feature/add-greeting")?;
@@ -43,7 +37,7 @@ fn test_vet_diff_initial_state() -> Result<()> {
     repo.commit_all("Update greeting")?;
 
     // Now main has "Hello", feature has "Hello World".
-    // vet diff should show the hunk.
+    // trueflow diff should show the hunk.
 
     let changes = get_diff_json(&repo)?;
 
@@ -243,7 +237,7 @@ fn test_diff_handles_renamed_file() -> Result<()> {
 }
 
 #[test]
-fn test_diff_skips_binary_changes() -> Result<()> {
+fn test_diff_reviews_binary_changes() -> Result<()> {
     let repo = TestRepo::new("diff_binary")?;
     let binary_path = repo.path.join("binary.bin");
     fs::write(&binary_path, [0, 255, 0, 1])?;
@@ -255,7 +249,40 @@ fn test_diff_skips_binary_changes() -> Result<()> {
     repo.commit_all("Update binary")?;
 
     let changes = get_diff_json(&repo)?;
-    assert!(changes.is_empty());
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0]["file"], "binary.bin");
+    let diff = changes[0]["diff_content"].as_str().expect("diff_content");
+    assert!(diff.contains("+binary: application/octet-stream"), "{diff}");
+    assert!(diff.contains("+size: 4 bytes"), "{diff}");
+    assert!(!repo.run_raw(&["check"])?.status.success());
+
+    let fp = changes[0]["fingerprint"].as_str().expect("fingerprint");
+    repo.run(&[
+        "mark",
+        "--fingerprint",
+        fp,
+        "--verdict",
+        "approved",
+        "--quiet",
+    ])?;
+    assert!(repo.run_raw(&["check"])?.status.success());
+
+    // `review` shows the file as one metadata block.
+    let files = json_array(&repo.run(&["review", "--all", "--json"])?)?;
+    let block = &files[0]["blocks"][0];
+    assert_eq!(block["kind"], "binary");
+    let hash = block["hash"].as_str().expect("hash");
+    repo.run(&[
+        "mark",
+        "--fingerprint",
+        hash,
+        "--verdict",
+        "approved",
+        "--path",
+        "binary.bin",
+        "--quiet",
+    ])?;
+    assert!(json_array(&repo.run(&["review", "--all", "--json"])?)?.is_empty());
 
     Ok(())
 }
@@ -273,3 +300,168 @@ fn test_diff_errors_without_main_branch() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_base_ref_replaces_main() -> Result<()> {
+    let repo = TestRepo::new("diff_base_ref")?;
+    repo.write("src/lib.rs", "pub fn core() {}\n")?;
+    repo.commit_all("Initial")?;
+    repo.git(&["branch", "-m", "trunk"])?;
+
+    checkout_branch(&repo, "feature-a")?;
+    repo.write("src/a.rs", "pub fn a() {}\n")?;
+    repo.commit_all("Add a")?;
+    checkout_branch(&repo, "feature-b")?;
+    repo.write("src/b.rs", "pub fn b() {}\n")?;
+    repo.commit_all("Add b")?;
+
+    let changed_files = |args: &[&str]| -> Result<Vec<String>> {
+        let mut files: Vec<String> = json_array(&repo.run(args)?)?
+            .iter()
+            .filter_map(|value| {
+                value["file"]
+                    .as_str()
+                    .or(value["path"].as_str())
+                    .map(str::to_string)
+            })
+            .collect();
+        files.sort();
+        files.dedup();
+        Ok(files)
+    };
+
+    assert_eq!(
+        changed_files(&["--base", "trunk", "diff", "--json"])?,
+        ["src/a.rs", "src/b.rs"]
+    );
+    // A stacked branch reviews only what it adds on top of its parent.
+    assert_eq!(
+        changed_files(&["diff", "--json", "--base", "feature-a"])?,
+        ["src/b.rs"]
+    );
+    assert_eq!(
+        changed_files(&[
+            "review",
+            "--target",
+            "base",
+            "--base",
+            "feature-a",
+            "--json"
+        ])?,
+        ["src/b.rs"]
+    );
+
+    repo.write("trueflow.toml", "[vcs]\nbase_branch = \"feature-a\"\n")?;
+    assert_eq!(changed_files(&["diff", "--json"])?, ["src/b.rs"]);
+    let err = repo.run_err(&["check"])?;
+    assert!(err.contains("src/b.rs"), "{err}");
+    assert!(!err.contains("src/a.rs"), "{err}");
+
+    let err = repo.run_err(&["diff", "--base", "nope"])?;
+    assert!(err.contains("nope"), "{err}");
+
+    Ok(())
+}
+
+#[test]
+fn test_diff_blocks_share_review_fingerprints() -> Result<()> {
+    let repo = TestRepo::new("diff_blocks")?;
+    repo.write(
+        "src/lib.rs",
+        "pub fn one() {}\n\npub fn two() {}\n\npub struct Gone;\n",
+    )?;
+    repo.commit_all("Initial")?;
+    checkout_branch(&repo, "feature")?;
+    repo.write(
+        "src/lib.rs",
+        "pub fn one() {\n    edited();\n}\n\npub fn two() {}\n\npub fn three() {}\n",
+    )?;
+    repo.commit_all("Edit")?;
+
+    let changes = json_array(&repo.run(&["diff", "--blocks", "--json"])?)?;
+    let summary: Vec<(&str, &str, u64)> = changes
+        .iter()
+        .map(|change| {
+            (
+                change["change"].as_str().unwrap_or_default(),
+                change["kind"].as_str().unwrap_or_default(),
+                change["line"].as_u64().unwrap_or_default(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            ("modified", "function", 1),
+            ("added", "function", 7),
+            ("removed", "struct", 5),
+        ]
+    );
+    assert!(changes[0]["previous_fingerprint"].is_string());
+    assert!(changes[1].get("previous_fingerprint").is_none());
+    assert_eq!(changes[1]["status"], "unreviewed");
+
+    let reviewed: Vec<String> = json_array(&repo.run(&["review", "--all", "--json"])?)?
+        .iter()
+        .flat_map(|file| file["blocks"].as_array().cloned().unwrap_or_default())
+        .filter_map(|block| block["hash"].as_str().map(str::to_string))
+        .collect();
+    for change in &changes[..2] {
+        let fingerprint = change["fingerprint"].as_str().context("fingerprint")?;
+        assert!(reviewed.iter().any(|hash| hash == fingerprint), "{change}");
+    }
+
+    repo.run(&[
+        "mark",
+        "--fingerprint",
+        changes[1]["fingerprint"].as_str().context("fingerprint")?,
+    ])?;
+    let changes = json_array(&repo.run(&["diff", "--blocks", "--json"])?)?;
+    assert_eq!(changes[1]["status"], "approved");
+
+    Ok(())
+}
+
+/// Hunk fingerprints key existing ledgers, so the diff machinery must not
+/// change them.
+#[test]
+fn test_diff_hunk_fingerprints_are_stable() -> Result<()> {
+    let repo = TestRepo::new("diff_stable_fingerprint")?;
+    repo.write("src/lib.rs", "pub fn one() {}\n\npub fn two() {}\n")?;
+    repo.commit_all("Initial")?;
+    checkout_branch(&repo, "feature")?;
+    repo.write(
+        "src/lib.rs",
+        "pub fn one() {}\n\npub fn two() {\n    2\n}\n",
+    )?;
+    repo.commit_all("Edit")?;
+
+    let changes = get_diff_json(&repo)?;
+    assert_eq!(changes.len(), 1);
+    assert_eq!(
+        changes[0]["fingerprint"],
+        "feb37d96baf453960fbd6334a609bcc67d2f2b7226cd5784ae771e78bee35993"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_color_flag_controls_ansi_output() -> Result<()> {
+    let repo = TestRepo::new("color_flag")?;
+    repo.write("src/lib.rs", LIB_ADD)?;
+    repo.commit_all("Initial")?;
+
+    let output = repo.run(&["review", "--all", "--color", "always"])?;
+    assert!(output.contains("\x1b["), "{output}");
+
+    let output = repo.run(&["review", "--all", "--color", "never"])?;
+    assert!(!output.contains("\x1b["), "{output}");
+
+    // Auto mode never colors a pipe.
+    let output = repo.run(&["review", "--all"])?;
+    assert!(output.contains("[Unreviewed]"));
+    assert!(!output.contains("\x1b["), "{output}");
+
+    Ok(())
+}
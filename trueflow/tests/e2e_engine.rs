@@ -0,0 +1,74 @@
+use anyhow::Result;
+
+mod common;
+use common::*;
+use trueflow_core::{Engine, Identity, ReviewOptions, Verdict};
+
+#[test]
+fn test_engine_reviews_and_marks_in_process() -> Result<()> {
+    let repo = TestRepo::new("engine")?;
+    repo.write("src/lib.rs", "pub fn one() -> u8 {\n    1\n}\n")?;
+    repo.write("src/two.rs", "pub fn two() -> u8 {\n    2\n}\n")?;
+    repo.commit_all("Initial")?;
+    // Keep the developer's own ~/.config/trueflow out of the test.
+    unsafe { std::env::set_var("TRUEFLOW_CONFIG_DIR", repo.path.join(".no-user-config")) };
+
+    let cwd = std::env::current_dir()?;
+    let engine = Engine::open(&repo.path)?;
+    assert_eq!(std::env::current_dir()?, cwd);
+    let scanned = engine.scan()?;
+    assert!(scanned.iter().any(|file| file.path.ends_with("lib.rs")));
+
+    let options = ReviewOptions {
+        all: true,
+        ..ReviewOptions::default()
+    };
+    let summary = engine.review(&options)?;
+    assert_eq!(summary.files.len(), 2);
+    let file = summary
+        .files
+        .iter()
+        .find(|file| file.path == "src/lib.rs")
+        .expect("src/lib.rs is unreviewed");
+
+    for block in &file.blocks {
+        let record = engine.verdict(&block.hash, Verdict::Approved, Some(&file.path))?;
+        engine.mark(record)?;
+    }
+    let history = engine.history()?;
+    assert_eq!(history.len(), file.blocks.len());
+    assert_eq!(
+        history[0].identity,
+        Identity::Email {
+            email: "test@example.com".to_string()
+        }
+    );
+
+    let after = engine.review(&options)?;
+    assert!(after.files.iter().all(|file| file.path != "src/lib.rs"));
+    assert_eq!(after.files.len(), 1);
+
+    // The CLI reads what the engine wrote.
+    let output = repo.run(&["review", "--all", "--json"])?;
+    assert!(!output.contains("src/lib.rs"), "{output}");
+
+    let mut bad = engine.verdict(&file.blocks[0].hash, Verdict::Approved, None)?;
+    bad.confidence = Some(2.0);
+    assert!(engine.mark(bad).is_err());
+
+    // A second workspace in the same process keeps its own settings.
+    let strict = TestRepo::new("engine-strict")?;
+    strict.write("trueflow.toml", "[hashing]\nnormalization = \"strict\"\n")?;
+    strict.write("src/lib.rs", "pub fn one() -> u8 {  \n    1\n}\n")?;
+    repo.write("src/lib.rs", "pub fn one() -> u8 {  \n    1\n}\n")?;
+    strict.commit_all("Initial")?;
+    let strict_engine = Engine::open(&strict.path)?;
+    let fingerprints = |engine: &Engine| -> Result<Vec<String>> {
+        let files = engine.scan()?;
+        let file = files.iter().find(|file| file.path == "src/lib.rs").unwrap();
+        Ok(file.blocks.iter().map(|block| block.hash.clone()).collect())
+    };
+    assert_ne!(fingerprints(&strict_engine)?, fingerprints(&engine)?);
+    assert_eq!(strict_engine.root(), strict.path.canonicalize()?);
+    Ok(())
+}
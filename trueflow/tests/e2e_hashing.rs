@@ -0,0 +1,74 @@
+use anyhow::{Context, Result};
+
+mod common;
+use common::{TestRepo, json_array};
+
+fn unreviewed_hashes(repo: &TestRepo) -> Result<Vec<String>> {
+    let files = json_array(&repo.run(&["review", "--json", "--target", "file:src/lib.rs"])?)?;
+    files
+        .iter()
+        .flat_map(|file| file["blocks"].as_array().cloned().unwrap_or_default())
+        .map(|block| {
+            block["hash"]
+                .as_str()
+                .map(str::to_string)
+                .context("block hash")
+        })
+        .collect()
+}
+
+fn approve_all(repo: &TestRepo) -> Result<()> {
+    for hash in unreviewed_hashes(repo)? {
+        repo.run(&[
+            "mark",
+            "--fingerprint",
+            &hash,
+            "--verdict",
+            "approved",
+            "--quiet",
+        ])?;
+    }
+    assert!(unreviewed_hashes(repo)?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_semantic_hashing_ignores_comments_and_formatting() -> Result<()> {
+    let repo = TestRepo::new("hashing_semantic")?;
+    repo.write("trueflow.toml", "[hashing]\nnormalization = \"semantic\"\n")?;
+    repo.write(
+        "src/lib.rs",
+        "pub fn add(a: i32, b: i32) -> i32 {\n    // Sum.\n    a + b\n}\n",
+    )?;
+    repo.commit_all("Initial")?;
+    approve_all(&repo)?;
+
+    repo.write(
+        "src/lib.rs",
+        "pub fn add(a: i32, b: i32) -> i32 {\n    a + b // the sum\n}\n",
+    )?;
+    assert!(unreviewed_hashes(&repo)?.is_empty());
+
+    repo.write(
+        "src/lib.rs",
+        "pub fn add(a: i32, b: i32) -> i32 {\n    a - b\n}\n",
+    )?;
+    assert_eq!(unreviewed_hashes(&repo)?.len(), 1);
+    Ok(())
+}
+
+#[test]
+fn test_strict_hashing_sees_trailing_whitespace() -> Result<()> {
+    let repo = TestRepo::new("hashing_strict")?;
+    repo.write("src/lib.rs", "pub fn one() -> i32 {\n    1\n}\n")?;
+    repo.commit_all("Initial")?;
+    approve_all(&repo)?;
+
+    // The default mode ignores trailing whitespace.
+    repo.write("src/lib.rs", "pub fn one() -> i32 {   \n    1\n}\n")?;
+    assert!(unreviewed_hashes(&repo)?.is_empty());
+
+    repo.write("trueflow.toml", "[hashing]\nnormalization = \"strict\"\n")?;
+    assert_eq!(unreviewed_hashes(&repo)?.len(), 1);
+    Ok(())
+}
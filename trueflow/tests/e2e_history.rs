@@ -0,0 +1,76 @@
+use anyhow::Result;
+
+mod common;
+use common::{
+    ReviewRecordOverrides, TestRepo, build_review_record, json_array, write_reviews_jsonl,
+};
+
+fn record(fingerprint: &str, verdict: &str, timestamp: i64, path: &str) -> serde_json::Value {
+    let mut record = build_review_record(
+        fingerprint,
+        ReviewRecordOverrides {
+            verdict: Some(verdict),
+            timestamp: Some(timestamp),
+            ..ReviewRecordOverrides::default()
+        },
+    );
+    record["path_hint"] = path.into();
+    record
+}
+
+fn fingerprints(output: &str) -> Result<Vec<String>> {
+    Ok(json_array(output)?
+        .iter()
+        .filter_map(|record| record["fingerprint"].as_str().map(str::to_string))
+        .collect())
+}
+
+#[test]
+fn test_history_filters_ledger() -> Result<()> {
+    let repo = TestRepo::new("history_filters")?;
+    // 2024-01-01T00:00:00Z
+    let jan = 1_704_067_200;
+    write_reviews_jsonl(
+        &repo.path.join(".trueflow"),
+        &[
+            record("aaaa1111", "rejected", jan - 86_400, "src/lib.rs"),
+            record("aaaa1111", "approved", jan + 60, "src/lib.rs"),
+            record("bbbb2222", "rejected", jan + 120, "src/net/mod.rs"),
+            record("cccc3333", "question", jan + 180, "README.md"),
+        ],
+    )?;
+
+    let all = repo.run(&["history", "--json"])?;
+    assert_eq!(
+        fingerprints(&all)?,
+        ["cccc3333", "bbbb2222", "aaaa1111", "aaaa1111"]
+    );
+
+    let by_fp = repo.run(&["history", "--fingerprint", "aaaa", "--json"])?;
+    assert_eq!(json_array(&by_fp)?.len(), 2);
+
+    let by_dir = repo.run(&["history", "--path", "src/net", "--json"])?;
+    assert_eq!(fingerprints(&by_dir)?, ["bbbb2222"]);
+
+    let output = repo.run(&[
+        "history",
+        "--since",
+        "2024-01-01",
+        "--verdict",
+        "rejected",
+        "--json",
+    ])?;
+    assert_eq!(fingerprints(&output)?, ["bbbb2222"]);
+
+    let text = repo.run(&["history", "--path", "src/lib.rs"])?;
+    assert!(
+        text.contains("2024-01-01 00:01 UTC  approved  a@example.com"),
+        "{text}"
+    );
+    assert!(text.contains("  aaaa1111  src/lib.rs"), "{text}");
+
+    let empty = repo.run(&["history", "--verdict", "comment"])?;
+    assert!(empty.contains("No matching review records."));
+
+    Ok(())
+}
@@ -0,0 +1,170 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+
+mod common;
+use common::{TestRepo, json, run_git, run_git_output};
+
+/// Runs git with the hooks calling this build of trueflow.
+fn git_with_hooks(dir: &Path, args: &[&str]) -> Result<Output> {
+    Ok(Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .env("TRUEFLOW", env!("CARGO_BIN_EXE_trueflow"))
+        .output()?)
+}
+
+fn violation_fingerprints(repo: &TestRepo, target: &str) -> Result<Vec<String>> {
+    let output = repo.run_raw(&["check", "--target", target, "--json"])?;
+    let report = json(&String::from_utf8(output.stdout)?)?;
+    Ok(report["violations"]
+        .as_array()
+        .context("violations")?
+        .iter()
+        .filter_map(|violation| violation["fingerprint"].as_str().map(str::to_string))
+        .collect())
+}
+
+#[test]
+fn test_pre_commit_hook_gates_staged_changes() -> Result<()> {
+    let repo = TestRepo::new("hooks_pre_commit")?;
+    repo.write("src/lib.rs", "pub fn core() {}\n")?;
+    repo.commit_all("Initial")?;
+
+    let output = repo.run(&["hooks", "install"])?;
+    assert!(output.contains("pre-commit"), "{output}");
+    assert!(output.contains("pre-push"), "{output}");
+    let hook = repo.path.join(".git/hooks/pre-commit");
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        assert_ne!(fs::metadata(&hook)?.permissions().mode() & 0o111, 0);
+    }
+
+    // Only the staged file is gated; the unstaged edit is not.
+    repo.write("src/lib.rs", "pub fn core() {}\n\npub fn staged() {}\n")?;
+    repo.add("src/lib.rs")?;
+    repo.write("src/other.rs", "pub fn unstaged() {}\n")?;
+    let output = git_with_hooks(&repo.path, &["commit", "-q", "-m", "Add staged"])?;
+    assert!(!output.status.success(), "{output:?}");
+    assert!(String::from_utf8(output.stderr)?.contains("unreviewed change"));
+
+    let fingerprints = violation_fingerprints(&repo, "staged")?;
+    assert_eq!(fingerprints.len(), 1, "{fingerprints:?}");
+    let staged = run_git_output(&repo.path, &["write-tree"])?;
+    let target = format!("rev:HEAD..{}", staged.trim());
+    assert_eq!(violation_fingerprints(&repo, &target)?, fingerprints);
+    repo.run(&[
+        "mark",
+        "--fingerprint",
+        &fingerprints[0],
+        "--verdict",
+        "approved",
+    ])?;
+
+    let output = git_with_hooks(&repo.path, &["commit", "-q", "-m", "Add staged"])?;
+    assert!(output.status.success(), "{output:?}");
+
+    Ok(())
+}
+
+#[test]
+fn test_check_staged_gates_the_first_commit() -> Result<()> {
+    let repo = TestRepo::new("hooks_first_commit")?;
+    repo.run(&["hooks", "install"])?;
+    repo.write("src/lib.rs", "pub fn core() {}\n")?;
+    repo.add("src/lib.rs")?;
+
+    let fingerprints = violation_fingerprints(&repo, "staged")?;
+    assert_eq!(fingerprints.len(), 1, "{fingerprints:?}");
+    let output = git_with_hooks(&repo.path, &["commit", "-q", "-m", "Initial"])?;
+    assert!(!output.status.success(), "{output:?}");
+
+    repo.run(&["mark", "--fingerprint", &fingerprints[0]])?;
+    let output = git_with_hooks(&repo.path, &["commit", "-q", "-m", "Initial"])?;
+    assert!(output.status.success(), "{output:?}");
+    Ok(())
+}
+
+#[test]
+fn test_pre_push_hook_gates_pushed_commits() -> Result<()> {
+    let repo = TestRepo::new("hooks_pre_push")?;
+    let remote = repo.path.with_extension("remote.git");
+    let _ = fs::remove_dir_all(&remote);
+    run_git(
+        &repo.path,
+        &["init", "-q", "--bare", &remote.to_string_lossy()],
+    )?;
+    run_git(
+        &repo.path,
+        &["remote", "add", "origin", &remote.to_string_lossy()],
+    )?;
+    repo.write("src/lib.rs", "pub fn core() {}\n")?;
+    repo.commit_all("Initial")?;
+    run_git(
+        &repo.path,
+        &["push", "-q", "origin", "HEAD:refs/heads/main"],
+    )?;
+    run_git(&repo.path, &["fetch", "-q", "origin"])?;
+
+    repo.run(&["hooks", "install"])?;
+    repo.write("src/lib.rs", "pub fn core() {}\n\npub fn pushed() {}\n")?;
+    git_with_hooks(
+        &repo.path,
+        &["commit", "-q", "--no-verify", "-am", "Add pushed"],
+    )?;
+
+    let output = git_with_hooks(
+        &repo.path,
+        &["push", "-q", "origin", "HEAD:refs/heads/main"],
+    )?;
+    assert!(!output.status.success(), "{output:?}");
+    assert!(String::from_utf8(output.stderr)?.contains("unreviewed change"));
+
+    let fingerprints = violation_fingerprints(&repo, "rev:origin/main..HEAD")?;
+    assert_eq!(fingerprints.len(), 1, "{fingerprints:?}");
+    repo.run(&[
+        "mark",
+        "--fingerprint",
+        &fingerprints[0],
+        "--verdict",
+        "approved",
+    ])?;
+
+    // Pushing a new branch only gates commits the remote doesn't have.
+    let output = git_with_hooks(
+        &repo.path,
+        &["push", "-q", "origin", "HEAD:refs/heads/topic"],
+    )?;
+    assert!(output.status.success(), "{output:?}");
+    let output = git_with_hooks(
+        &repo.path,
+        &["push", "-q", "origin", "HEAD:refs/heads/main"],
+    )?;
+    assert!(output.status.success(), "{output:?}");
+
+    let _ = fs::remove_dir_all(&remote);
+    Ok(())
+}
+
+#[test]
+fn test_hooks_leave_foreign_hooks_alone() -> Result<()> {
+    let repo = TestRepo::new("hooks_foreign")?;
+    let hooks = repo.path.join(".git/hooks");
+    fs::create_dir_all(&hooks)?;
+    fs::write(hooks.join("pre-push"), "#!/bin/sh\nexit 0\n")?;
+
+    let err = repo.run_err(&["hooks", "install"])?;
+    assert!(err.contains("--force"), "{err}");
+
+    repo.run(&["hooks", "install", "--force"])?;
+    repo.run(&["hooks", "install"])?;
+    fs::write(hooks.join("pre-push"), "#!/bin/sh\nexit 0\n")?;
+
+    repo.run(&["hooks", "uninstall"])?;
+    assert!(!hooks.join("pre-commit").exists());
+    assert!(hooks.join("pre-push").exists());
+
+    Ok(())
+}
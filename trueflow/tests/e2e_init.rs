@@ -0,0 +1,52 @@
+use anyhow::Result;
+use std::fs;
+
+mod common;
+use common::*;
+
+const GITIGNORE: &str = "target/\n.trueflow/cache\n.trueflow/logs/\n.trueflow/last_session.json\n.trueflow/session.json\n.trueflow/s3-cache/\n.trueflow/reviews.db\n.trueflow/metrics.jsonl\n";
+
+#[test]
+fn test_init_scaffolds_config_and_gitignore() -> Result<()> {
+    let repo = TestRepo::new("init_scaffold")?;
+    repo.write(".gitignore", "target/")?;
+
+    let stdout = repo.run(&["init"])?;
+    assert!(stdout.contains("Initialized trueflow"), "{stdout}");
+    assert!(repo.path.join(".trueflow").is_dir());
+    let config = fs::read_to_string(repo.path.join("trueflow.toml"))?;
+    assert!(config.contains("[review]"), "{config}");
+    assert!(config.contains("# base_branch"), "{config}");
+    assert_eq!(fs::read_to_string(repo.path.join(".gitignore"))?, GITIGNORE);
+
+    // The starter config is valid and sets no unknown keys.
+    let doctor = repo.run(&["config", "doctor"])?;
+    assert!(!doctor.contains("unknown"), "{doctor}");
+
+    // Running it again keeps what is there.
+    fs::write(repo.path.join("trueflow.toml"), "[tui]\ntheme = \"dark\"\n")?;
+    let stdout = repo.run(&["init"])?;
+    assert!(stdout.contains("Kept existing"), "{stdout}");
+    assert_eq!(
+        fs::read_to_string(repo.path.join("trueflow.toml"))?,
+        "[tui]\ntheme = \"dark\"\n"
+    );
+    assert_eq!(fs::read_to_string(repo.path.join(".gitignore"))?, GITIGNORE);
+    Ok(())
+}
+
+#[test]
+fn test_init_installs_hooks_and_ci_workflow() -> Result<()> {
+    let repo = TestRepo::new("init_hooks_ci")?;
+
+    repo.run(&["init", "--hooks", "--ci"])?;
+    let hook = fs::read_to_string(repo.path.join(".git/hooks/pre-commit"))?;
+    assert!(hook.contains("trueflow hooks install"), "{hook}");
+    let workflow = fs::read_to_string(repo.path.join(".github/workflows/trueflow.yml"))?;
+    assert!(workflow.contains("check --format github"), "{workflow}");
+    assert!(
+        workflow.contains("cargo install --locked --git https://github.com/trueflow-dev/trueflow"),
+        "{workflow}"
+    );
+    Ok(())
+}
@@ -33,6 +33,10 @@ fn test_all_languages_detection() -> Result<()> {
         ("main.ts", "TypeScript"),
         ("main.py", "Python"),
         ("main.sh", "Shell"),
+        ("main.c", "C"),
+        ("main.cpp", "Cpp"),
+        ("main.sql", "Sql"),
+        ("main.yaml", "Yaml"),
         ("main.md", "Markdown"),
         ("main.toml", "Toml"),
         ("main.nix", "Nix"),
@@ -0,0 +1,87 @@
+use anyhow::{Context, Result};
+
+mod common;
+use common::{TestRepo, json_array};
+
+fn review_blocks(repo: &TestRepo) -> Result<Vec<serde_json::Value>> {
+    let files = json_array(&repo.run(&["review", "--all", "--json"])?)?;
+    Ok(files
+        .iter()
+        .flat_map(|file| file["blocks"].as_array().cloned().unwrap_or_default())
+        .collect())
+}
+
+fn has_tag(block: &serde_json::Value, tag: &str) -> bool {
+    block["tags"]
+        .as_array()
+        .is_some_and(|tags| tags.iter().any(|value| value == tag))
+}
+
+#[test]
+fn test_trivial_edit_of_approved_block_needs_reconfirmation() -> Result<()> {
+    let repo = TestRepo::new("lineage_reconfirm")?;
+    repo.write(
+        "src/lib.rs",
+        "pub fn total(items: &[u32]) -> u32 {\n    let s = items.iter().sum();\n    s\n}\n",
+    )?;
+    repo.commit_all("Initial")?;
+
+    for block in review_blocks(&repo)? {
+        let hash = block["hash"].as_str().context("hash")?;
+        repo.run(&[
+            "mark",
+            "--fingerprint",
+            hash,
+            "--verdict",
+            "approved",
+            "--quiet",
+        ])?;
+    }
+    assert!(review_blocks(&repo)?.is_empty());
+
+    // Comment and local rename only: flagged for re-confirmation, without
+    // `review` writing to the ledger.
+    let ledger = repo.path.join(".trueflow/reviews.jsonl");
+    let before = std::fs::read_to_string(&ledger)?;
+    repo.write(
+        "src/lib.rs",
+        "pub fn total(items: &[u32]) -> u32 {\n    // Add them up.\n    let acc = items.iter().sum();\n    acc\n}\n",
+    )?;
+    let blocks = review_blocks(&repo)?;
+    assert_eq!(blocks.len(), 1);
+    assert!(has_tag(&blocks[0], "reconfirm"), "{:?}", blocks[0]);
+    assert_eq!(std::fs::read_to_string(&ledger)?, before);
+
+    // A verdict on the edited block records the link...
+    let hash = blocks[0]["hash"].as_str().context("hash")?;
+    repo.run(&[
+        "mark",
+        "--fingerprint",
+        hash,
+        "--path",
+        "src/lib.rs",
+        "--verdict",
+        "comment",
+        "--note",
+        "Only a rename",
+        "--quiet",
+    ])?;
+    let reviews = std::fs::read_to_string(&ledger)?;
+    assert!(reviews.contains("\"kind\":\"lineage\""), "{reviews}");
+
+    // ...so it survives once the edit is committed.
+    repo.commit_all("Rename")?;
+    let blocks = review_blocks(&repo)?;
+    assert!(has_tag(&blocks[0], "reconfirm"));
+    let text = repo.run(&["review", "--all"])?;
+    assert!(text.contains("[Reconfirm]"), "{text}");
+
+    // A real change is plain unreviewed.
+    repo.write(
+        "src/lib.rs",
+        "pub fn total(items: &[u32]) -> u32 {\n    let acc = items.iter().max().copied().unwrap_or(0);\n    acc\n}\n",
+    )?;
+    let blocks = review_blocks(&repo)?;
+    assert!(!has_tag(&blocks[0], "reconfirm"));
+    Ok(())
+}
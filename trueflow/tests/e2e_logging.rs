@@ -0,0 +1,55 @@
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::fs;
+
+mod common;
+use common::TestRepo;
+
+fn json_lines(content: &str) -> Result<Vec<Value>> {
+    content
+        .lines()
+        .map(|line| serde_json::from_str(line).context("log line is not JSON"))
+        .collect()
+}
+
+#[test]
+fn test_log_file_flag_writes_json_lines() -> Result<()> {
+    let repo = TestRepo::new("structured_log_flag")?;
+    repo.write("src/lib.rs", "pub fn core() {}\n")?;
+    repo.commit_all("Add lib")?;
+
+    let log_path = repo.path.join("trace.jsonl");
+    let log_arg = log_path.to_string_lossy().to_string();
+    repo.run(&["--log-file", &log_arg, "scan", "--json"])?;
+
+    let entries = json_lines(&fs::read_to_string(&log_path)?)?;
+    assert!(
+        entries
+            .iter()
+            .any(|entry| entry["message"] == "trueflow starting" && entry["level"] == "INFO")
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_structured_config_logs_to_store_and_rotates() -> Result<()> {
+    let repo = TestRepo::new("structured_log_config")?;
+    repo.write("src/lib.rs", "pub fn core() {}\n")?;
+    repo.write(
+        "trueflow.toml",
+        "[logging]\nstructured = true\nmax_bytes = 200\nmax_files = 1\n",
+    )?;
+    repo.commit_all("Add lib")?;
+    fs::create_dir_all(repo.path.join(".trueflow"))?;
+
+    repo.run(&["scan", "--json"])?;
+    repo.run(&["scan", "--json"])?;
+
+    let logs = repo.path.join(".trueflow/logs");
+    json_lines(&fs::read_to_string(logs.join("trueflow.jsonl"))?)?;
+    json_lines(&fs::read_to_string(logs.join("trueflow.jsonl.1"))?)?;
+    assert!(!logs.join("trueflow.jsonl.2").exists());
+
+    Ok(())
+}
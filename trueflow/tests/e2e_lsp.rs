@@ -0,0 +1,161 @@
+use anyhow::{Context, Result, bail};
+use serde_json::{Value, json};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::process::{ChildStdin, ChildStdout, Command, Stdio};
+
+mod common;
+use common::{TestRepo, read_review_records};
+
+fn send(stdin: &mut ChildStdin, message: Value) -> Result<()> {
+    let body = message.to_string();
+    write!(stdin, "Content-Length: {}\r\n\r\n{body}", body.len())?;
+    stdin.flush()?;
+    Ok(())
+}
+
+fn receive(stdout: &mut BufReader<ChildStdout>) -> Result<Value> {
+    let mut length = None;
+    loop {
+        let mut line = String::new();
+        if stdout.read_line(&mut line)? == 0 {
+            bail!("server closed stdout");
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some(value) = line.strip_prefix("Content-Length: ") {
+            length = Some(value.parse::<usize>()?);
+        }
+    }
+    let mut body = vec![0; length.context("missing Content-Length")?];
+    stdout.read_exact(&mut body)?;
+    Ok(serde_json::from_slice(&body)?)
+}
+
+/// Reads messages until one matches, skipping the rest.
+fn receive_until(
+    stdout: &mut BufReader<ChildStdout>,
+    matches: impl Fn(&Value) -> bool,
+) -> Result<Value> {
+    loop {
+        let message = receive(stdout)?;
+        if matches(&message) {
+            return Ok(message);
+        }
+    }
+}
+
+fn is_diagnostics(message: &Value) -> bool {
+    message["method"] == "textDocument/publishDiagnostics"
+}
+
+#[test]
+fn test_lsp_publishes_diagnostics_and_marks_blocks() -> Result<()> {
+    let repo = TestRepo::new("lsp")?;
+    repo.write("src/lib.rs", "pub fn core() {}\n\npub fn other() {}\n")?;
+    repo.commit_all("Initial")?;
+    let root = repo.path.canonicalize()?;
+    let uri = format!("file://{}/src/lib.rs", root.display());
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_trueflow"))
+        .arg("lsp")
+        .current_dir(&repo.path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+    let mut stdin = child.stdin.take().context("stdin")?;
+    let mut stdout = BufReader::new(child.stdout.take().context("stdout")?);
+
+    send(
+        &mut stdin,
+        json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {
+            "capabilities": {},
+            "rootUri": format!("file://{}", root.display()),
+        }}),
+    )?;
+    let initialized = receive(&mut stdout)?;
+    assert!(
+        initialized["result"]["capabilities"]["codeActionProvider"]
+            .as_bool()
+            .unwrap_or(false),
+        "{initialized}"
+    );
+    send(
+        &mut stdin,
+        json!({"jsonrpc": "2.0", "method": "initialized", "params": {}}),
+    )?;
+    send(
+        &mut stdin,
+        json!({"jsonrpc": "2.0", "method": "textDocument/didOpen", "params": {
+            "textDocument": {"uri": uri, "languageId": "rust", "version": 1, "text": ""},
+        }}),
+    )?;
+    let published = receive_until(&mut stdout, is_diagnostics)?;
+    let diagnostics = published["params"]["diagnostics"]
+        .as_array()
+        .context("diagnostics")?;
+    assert!(!diagnostics.is_empty(), "{published}");
+    assert!(
+        diagnostics
+            .iter()
+            .all(|diagnostic| diagnostic["source"] == "trueflow"
+                && diagnostic["message"] == "Unreviewed block"),
+        "{published}"
+    );
+    let before = diagnostics.len();
+
+    send(
+        &mut stdin,
+        json!({"jsonrpc": "2.0", "id": 2, "method": "textDocument/codeAction", "params": {
+            "textDocument": {"uri": uri},
+            "range": {"start": {"line": 0, "character": 4}, "end": {"line": 0, "character": 4}},
+            "context": {"diagnostics": []},
+        }}),
+    )?;
+    let actions = receive_until(&mut stdout, |message| message["id"] == 2)?;
+    let actions = actions["result"].as_array().context("actions")?;
+    let titles: Vec<&str> = actions
+        .iter()
+        .filter_map(|action| action["title"].as_str())
+        .collect();
+    assert_eq!(
+        titles,
+        ["Approve block (trueflow)", "Reject block (trueflow)"]
+    );
+
+    send(
+        &mut stdin,
+        json!({"jsonrpc": "2.0", "id": 3, "method": "workspace/executeCommand",
+            "params": actions[1]["command"]}),
+    )?;
+    let republished = receive_until(&mut stdout, is_diagnostics)?;
+    let diagnostics = republished["params"]["diagnostics"]
+        .as_array()
+        .context("diagnostics")?;
+    assert_eq!(diagnostics.len(), before, "{republished}");
+    assert!(
+        diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic["message"] == "Rejected"),
+        "{republished}"
+    );
+    let executed = receive_until(&mut stdout, |message| message["id"] == 3)?;
+    assert!(executed.get("error").is_none(), "{executed}");
+
+    send(
+        &mut stdin,
+        json!({"jsonrpc": "2.0", "id": 4, "method": "shutdown"}),
+    )?;
+    receive_until(&mut stdout, |message| message["id"] == 4)?;
+    send(&mut stdin, json!({"jsonrpc": "2.0", "method": "exit"}))?;
+    assert!(child.wait()?.success());
+
+    let records = read_review_records(&repo.path.join(".trueflow/reviews.jsonl"))?;
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].verdict.as_str(), "rejected");
+    assert_eq!(records[0].path_hint.as_deref(), Some("src/lib.rs"));
+
+    Ok(())
+}
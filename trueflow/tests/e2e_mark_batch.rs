@@ -0,0 +1,131 @@
+use anyhow::Result;
+
+mod common;
+use common::{TestRepo, json_array, read_review_records};
+
+fn unreviewed_paths(repo: &TestRepo) -> Result<Vec<String>> {
+    Ok(json_array(&repo.run(&["review", "--all", "--json"])?)?
+        .iter()
+        .filter_map(|file| file["path"].as_str().map(str::to_string))
+        .collect())
+}
+
+#[test]
+fn test_mark_directory_records_its_subtree_hash() -> Result<()> {
+    let repo = TestRepo::new("mark_batch_path")?;
+    repo.write("src/lib.rs", "pub fn core() {}\n")?;
+    repo.write("src/generated/api.rs", "pub fn a() {}\n\npub fn b() {}\n")?;
+    repo.write("src/generated/types.rs", "pub struct T;\n")?;
+    repo.commit_all("Initial")?;
+
+    let output = repo.run(&["mark", "--path", "src/generated/", "--verdict", "approved"])?;
+    assert!(
+        output.contains("Recorded 'approved' for src/generated."),
+        "{output}"
+    );
+    let records = read_review_records(&repo.path.join(".trueflow/reviews.jsonl"))?;
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].path_hint.as_deref(), Some("src/generated"));
+
+    assert_eq!(unreviewed_paths(&repo)?, ["src/lib.rs"]);
+
+    let err = repo.run_err(&["mark", "--path", "missing/"])?;
+    assert!(err.contains("No files under missing/"), "{err}");
+
+    Ok(())
+}
+
+#[test]
+fn test_mark_by_kind_and_tag_records_each_block() -> Result<()> {
+    let repo = TestRepo::new("mark_batch_kind")?;
+    repo.write(
+        "src/lib.rs",
+        "use std::fmt;\n\npub fn core() {}\n\n#[cfg(test)]\nmod tests {\n    #[test]\n    fn works() {}\n}\n",
+    )?;
+    repo.write("src/other.rs", "use std::io;\n\npub fn other() {}\n")?;
+    repo.commit_all("Initial")?;
+
+    let output = repo.run(&["mark", "--kind", "import", "--quiet"])?;
+    assert!(output.trim().is_empty(), "{output}");
+    let records = read_review_records(&repo.path.join(".trueflow/reviews.jsonl"))?;
+    assert_eq!(records.len(), 2);
+    assert!(records.iter().all(|record| record.line_hint.is_some()));
+
+    let output = repo.run(&["mark", "--tag", "test", "--path", "src/lib.rs"])?;
+    assert!(output.contains("for 1 block(s)"), "{output}");
+
+    let remaining = json_array(&repo.run(&["review", "--all", "--json"])?)?;
+    let kinds: Vec<&str> = remaining
+        .iter()
+        .flat_map(|file| file["blocks"].as_array().into_iter().flatten())
+        .filter_map(|block| block["kind"].as_str())
+        .filter(|kind| !common::is_gap(kind))
+        .collect();
+    assert!(!kinds.contains(&"import"), "{kinds:?}");
+    assert!(!kinds.contains(&"module"), "{kinds:?}");
+    assert!(kinds.contains(&"function"), "{kinds:?}");
+
+    let err = repo.run_err(&["mark", "--fingerprint", "abc", "--kind", "import"])?;
+    assert!(err.contains("cannot be used with"), "{err}");
+
+    Ok(())
+}
+
+#[test]
+fn test_mark_stdin_appends_all_entries() -> Result<()> {
+    let repo = TestRepo::new("mark_batch_stdin")?;
+    repo.write("src/lib.rs", "pub fn core() {}\n")?;
+    repo.commit_all("Initial")?;
+
+    let lines = concat!(
+        r#"{"fingerprint": "aaaa", "verdict": "rejected", "note": "Leaks"}"#,
+        "\n\n",
+        r#"{"fingerprint": "bbbb", "check": "security"}"#,
+        "\n",
+    );
+    let output = repo.run_with_stdin(&["mark", "--stdin", "--note", "Batch"], lines)?;
+    assert!(output.status.success(), "{output:?}");
+    assert!(String::from_utf8(output.stdout)?.contains("Recorded 2 verdict(s)."));
+
+    let array = r#"[{"fingerprint": "cccc", "verdict": "question"}]"#;
+    let output = repo.run_with_stdin(&["mark", "--stdin", "--quiet"], array)?;
+    assert!(output.status.success(), "{output:?}");
+
+    let records = read_review_records(&repo.path.join(".trueflow/reviews.jsonl"))?;
+    let summary: Vec<(&str, &str, &str, Option<&str>)> = records
+        .iter()
+        .map(|record| {
+            (
+                record.fingerprint.as_str(),
+                record.verdict.as_str(),
+                record.check.as_str(),
+                record.note.as_deref(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            ("aaaa", "rejected", "review", Some("Leaks")),
+            ("bbbb", "approved", "security", Some("Batch")),
+            ("cccc", "question", "review", None),
+        ]
+    );
+
+    // One bad entry means nothing is written.
+    let bad = concat!(
+        r#"{"fingerprint": "dddd"}"#,
+        "\n",
+        r#"{"fingerprint": "eeee", "verdict": "maybe"}"#,
+        "\n",
+    );
+    let output = repo.run_with_stdin(&["mark", "--stdin"], bad)?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains("Unknown verdict: maybe"));
+    assert_eq!(
+        read_review_records(&repo.path.join(".trueflow/reviews.jsonl"))?.len(),
+        3
+    );
+
+    Ok(())
+}
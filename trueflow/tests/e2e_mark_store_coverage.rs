@@ -4,7 +4,7 @@ use std::process::Command;
 
 mod common;
 use common::*;
-use trueflow::store::BlockState;
+use trueflow_core::store::BlockState;
 
 #[test]
 fn test_mark_uncommitted_state() -> Result<()> {
@@ -67,6 +67,47 @@ fn test_mark_unknown_state_no_path() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_mark_records_external_refs() -> Result<()> {
+    let repo = TestRepo::new("external_refs")?;
+    repo.write("src/main.rs", "fn main() {}\n")?;
+    repo.commit_all("Initial commit")?;
+
+    let output = repo.run(&["review", "--all", "--json"])?;
+    let hash = first_block_hash(&output)?;
+    repo.run(&[
+        "mark",
+        "--fingerprint",
+        &hash,
+        "--verdict",
+        "rejected",
+        "--ref",
+        "https://github.com/org/repo/issues/7",
+        "--ref",
+        " JIRA-123 ",
+        "--ref",
+        "JIRA-123",
+    ])?;
+
+    let db_path = repo.path.join(".trueflow").join("reviews.jsonl");
+    let records = read_review_records(&db_path)?;
+    assert_eq!(
+        records[0].refs,
+        Some(vec![
+            "https://github.com/org/repo/issues/7".to_string(),
+            "JIRA-123".to_string(),
+        ])
+    );
+
+    let output = repo.run(&["feedback", "--format", "xml"])?;
+    assert!(output.contains("<ref>JIRA-123</ref>"), "{output}");
+    let output = repo.run(&["feedback", "--format", "json"])?;
+    let feedback = json_array(&output)?;
+    assert_eq!(feedback[0]["reviews"][0]["refs"][1], "JIRA-123");
+
+    Ok(())
+}
+
 #[test]
 fn test_store_subdirectory_discovery() -> Result<()> {
     let repo = TestRepo::new("subdir_discovery")?;
@@ -112,13 +153,14 @@ fn test_mark_signing_failure() -> Result<()> {
 
     let hash = "bad1234567890abcdef1234567890abcdef12";
 
-    // Attempt mark, expect failure
+    // Attempt a signed mark, expect failure
     let output = repo.run_err(&[
         "mark",
         "--fingerprint",
         hash,
         "--verdict",
         "approved",
+        "--sign",
         "--quiet",
     ])?;
 
@@ -0,0 +1,148 @@
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+mod common;
+use common::*;
+
+fn record(id: &str, verdict: &str, timestamp: i64) -> Value {
+    build_review_record(
+        "abc123",
+        ReviewRecordOverrides {
+            id: Some(id),
+            verdict: Some(verdict),
+            timestamp: Some(timestamp),
+            ..Default::default()
+        },
+    )
+}
+
+/// Runs git with the merge driver calling this build of trueflow.
+fn git_merge(dir: &Path, branch: &str) -> Result<()> {
+    let output = Command::new("git")
+        .args(["merge", "--no-edit", branch])
+        .current_dir(dir)
+        .env("TRUEFLOW", env!("CARGO_BIN_EXE_trueflow"))
+        .output()?;
+    anyhow::ensure!(
+        output.status.success(),
+        "git merge failed: {}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    Ok(())
+}
+
+/// Runs a merge that must fail, returning git's output.
+fn git_merge_err(dir: &Path, branch: &str) -> Result<String> {
+    let output = Command::new("git")
+        .args(["merge", "--no-edit", branch])
+        .current_dir(dir)
+        .env("TRUEFLOW", env!("CARGO_BIN_EXE_trueflow"))
+        .output()?;
+    anyhow::ensure!(!output.status.success(), "git merge succeeded");
+    Ok(format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    ))
+}
+
+/// Commits `theirs` on a side branch and `ours` on the current one.
+fn diverge(repo: &TestRepo, ours: &[Value], theirs: &[Value]) -> Result<()> {
+    let ledger_dir = repo.path.join(".trueflow");
+    repo.git(&["checkout", "-q", "-b", "other"])?;
+    write_reviews_jsonl(&ledger_dir, theirs)?;
+    repo.commit_all("Their reviews")?;
+    repo.git(&["checkout", "-q", "-"])?;
+    write_reviews_jsonl(&ledger_dir, ours)?;
+    repo.commit_all("Our reviews")
+}
+
+fn installed_repo(name: &str, base: &[Value]) -> Result<TestRepo> {
+    let repo = TestRepo::new(name)?;
+    repo.write("lib.rs", "pub fn core() {}\n")?;
+    write_reviews_jsonl(&repo.path.join(".trueflow"), base)?;
+    let output = repo.run(&["merge-driver", "--install"])?;
+    assert!(output.contains(".trueflow/reviews.jsonl"), "{output}");
+    repo.commit_all("Initial")?;
+    Ok(repo)
+}
+
+#[test]
+fn test_install_registers_the_driver_once() -> Result<()> {
+    let repo = installed_repo("merge_driver_install", &[])?;
+
+    let attributes = fs::read_to_string(repo.path.join(".gitattributes"))?;
+    assert_eq!(attributes, ".trueflow/reviews.jsonl merge=trueflow\n");
+    let driver = run_git_output(&repo.path, &["config", "merge.trueflow.driver"])?;
+    assert!(driver.contains("merge-driver %O %A %B"), "{driver}");
+
+    let again = repo.run(&["merge-driver", "--install"])?;
+    assert!(again.trim().is_empty(), "{again}");
+    assert_eq!(
+        fs::read_to_string(repo.path.join(".gitattributes"))?,
+        attributes
+    );
+    Ok(())
+}
+
+#[test]
+fn test_concurrent_appends_merge_cleanly() -> Result<()> {
+    let base = record("r1", "approved", 10);
+    let repo = installed_repo("merge_driver_appends", std::slice::from_ref(&base))?;
+    diverge(
+        &repo,
+        &[base.clone(), record("r3", "approved", 30)],
+        &[base, record("r2", "rejected", 20)],
+    )?;
+
+    git_merge(&repo.path, "other")?;
+
+    let records = read_review_records(&repo.path.join(".trueflow/reviews.jsonl"))?;
+    let ids: Vec<&str> = records.iter().map(|record| record.id.as_str()).collect();
+    assert_eq!(ids, ["r1", "r2", "r3"]);
+    Ok(())
+}
+
+#[test]
+fn test_conflicting_edits_keep_the_newer_record() -> Result<()> {
+    let repo = installed_repo("merge_driver_conflict", &[record("r1", "approved", 10)])?;
+    diverge(
+        &repo,
+        &[record("r1", "comment", 30)],
+        &[record("r1", "rejected", 20)],
+    )?;
+
+    git_merge(&repo.path, "other")?;
+
+    let content = fs::read_to_string(repo.path.join(".trueflow/reviews.jsonl"))?;
+    let lines: Vec<&str> = content.lines().collect();
+    assert_eq!(lines.len(), 1, "{content}");
+    let merged: Value = serde_json::from_str(lines[0])?;
+    assert_eq!(merged["verdict"].as_str().context("verdict")?, "comment");
+    Ok(())
+}
+
+#[test]
+fn test_unreadable_lines_leave_the_conflict() -> Result<()> {
+    let repo = installed_repo("merge_driver_unreadable", &[record("r1", "approved", 10)])?;
+    diverge(
+        &repo,
+        &[record("r1", "approved", 10), record("r3", "approved", 30)],
+        &[record("r1", "approved", 10), record("r2", "rejected", 20)],
+    )?;
+    let ledger = repo.path.join(".trueflow/reviews.jsonl");
+    let ours = fs::read_to_string(&ledger)?;
+    fs::write(&ledger, format!("{ours}<<<<<<< HEAD\n"))?;
+    repo.commit_all("Leftover marker")?;
+
+    let output = git_merge_err(&repo.path, "other")?;
+    assert!(output.contains("CONFLICT"), "{output}");
+    let content = fs::read_to_string(&ledger)?;
+    assert!(content.contains("<<<<<<< HEAD"), "{content}");
+    assert!(!content.contains("\"r2\""), "{content}");
+    Ok(())
+}
@@ -0,0 +1,43 @@
+use anyhow::Result;
+
+mod common;
+use common::{TestRepo, first_block_hash, json_array, read_review_records};
+
+fn unreviewed_paths(output: &str) -> Result<Vec<String>> {
+    Ok(json_array(output)?
+        .iter()
+        .filter_map(|file| file["path"].as_str().map(str::to_string))
+        .collect())
+}
+
+#[test]
+fn test_review_and_mark_without_a_repository() -> Result<()> {
+    let repo = TestRepo::without_vcs("no_vcs")?;
+    repo.write("src/lib.rs", "pub fn core() {}\n")?;
+    repo.write("src/util.rs", "pub fn util() {}\n")?;
+
+    let scan = json_array(&repo.run(&["scan", "--json"])?)?;
+    assert_eq!(scan.len(), 2);
+
+    // Dirty and revision targets have nothing to ask, so they are skipped.
+    let output = repo.run_raw(&["review", "--json"])?;
+    assert!(output.status.success(), "{output:?}");
+    assert!(unreviewed_paths(&String::from_utf8(output.stdout)?)?.is_empty());
+    assert!(String::from_utf8(output.stderr)?.contains("Not inside a repository"));
+    let output = repo.run_raw(&["review", "--target", "rev:HEAD", "--json"])?;
+    assert!(output.status.success(), "{output:?}");
+
+    let all = repo.run(&["review", "--all", "--json"])?;
+    assert_eq!(unreviewed_paths(&all)?, ["src/lib.rs", "src/util.rs"]);
+    let hash = first_block_hash(&all)?;
+    repo.run(&["mark", "--fingerprint", &hash, "--path", "src/lib.rs"])?;
+    let records = read_review_records(&repo.path.join(".trueflow/reviews.jsonl"))?;
+    assert_eq!(records.len(), 1);
+
+    // Subdirectories share the store found above them.
+    let output = repo.run_in(&["review", "--all", "--json"], &repo.path.join("src"))?;
+    assert_eq!(unreviewed_paths(&output)?, ["util.rs"]);
+    assert!(!repo.path.join("src/.trueflow").exists());
+
+    Ok(())
+}
@@ -0,0 +1,161 @@
+use anyhow::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+mod common;
+use common::*;
+use trueflow_core::store::Identity;
+
+const CONFIG: &str = "[identity]\nprovider = \"oidc\"\n\n[identity.oidc]\nissuer = \"https://sso.example.com\"\nclient_id = \"trueflow\"\n";
+
+/// ID token for alice@example.com (subject `user-42`), expiring in 2100.
+const ID_TOKEN: &str = "eyJhbGciOiJSUzI1NiJ9.eyJpc3MiOiJodHRwczovL3Nzby5leGFtcGxlLmNvbSIsInN1YiI6InVzZXItNDIiLCJhdWQiOiJ0cnVlZmxvdyIsImV4cCI6NDEwMjQ0NDgwMCwiZW1haWwiOiJhbGljZUBleGFtcGxlLmNvbSIsImVtYWlsX3ZlcmlmaWVkIjp0cnVlfQ.sig";
+
+/// Stand-in for curl playing the issuer: discovery, a device code, then one
+/// `authorization_pending` before the token.
+#[cfg(unix)]
+fn fake_issuer(dir: &Path) -> Result<PathBuf> {
+    use std::os::unix::fs::PermissionsExt;
+    let script = format!(
+        r#"#!/bin/sh
+config=$(cat)
+case "$config" in
+  *openid-configuration*)
+    echo '{{"issuer":"https://sso.example.com","token_endpoint":"https://sso.example.com/token","device_authorization_endpoint":"https://sso.example.com/device"}}' ;;
+  *'url = "https://sso.example.com/device"'*)
+    echo '{{"device_code":"dev-1","user_code":"ABCD-EFGH","verification_uri":"https://sso.example.com/activate","expires_in":600,"interval":0}}' ;;
+  *)
+    if [ -f "{pending}" ]; then
+      echo '{{"id_token":"{ID_TOKEN}"}}'
+    else
+      touch "{pending}"
+      echo '{{"error":"authorization_pending"}}'
+    fi ;;
+esac
+"#,
+        pending = dir.join("pending").display(),
+    );
+    let path = dir.join("curl");
+    fs::write(&path, script)?;
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+    Ok(path)
+}
+
+#[cfg(unix)]
+fn trueflow(repo: &TestRepo, home: &Path, args: &[&str]) -> Result<Output> {
+    Ok(Command::new(env!("CARGO_BIN_EXE_trueflow"))
+        .args(args)
+        .current_dir(&repo.path)
+        .env("TRUEFLOW_CONFIG_DIR", home.join("config"))
+        .env("TRUEFLOW_CURL", home.join("curl"))
+        .output()?)
+}
+
+#[cfg(unix)]
+fn setup(name: &str) -> Result<(TestRepo, PathBuf)> {
+    let repo = TestRepo::new(name)?;
+    repo.write("src/lib.rs", "pub fn one() -> u8 {\n    1\n}\n")?;
+    repo.write("trueflow.toml", CONFIG)?;
+    repo.commit_all("Initial")?;
+    let home = std::env::temp_dir()
+        .join("trueflow_tests")
+        .join(format!("oidc_{}", uuid::Uuid::new_v4()));
+    fs::create_dir_all(&home)?;
+    fake_issuer(&home)?;
+    Ok((repo, home))
+}
+
+#[cfg(unix)]
+fn mark_first_block(repo: &TestRepo, home: &Path) -> Result<Output> {
+    let hash = first_block_hash(&repo.run(&["review", "--all", "--json"])?)?;
+    trueflow(
+        repo,
+        home,
+        &[
+            "mark",
+            "--fingerprint",
+            &hash,
+            "--path",
+            "src/lib.rs",
+            "--quiet",
+        ],
+    )
+}
+
+#[cfg(unix)]
+#[test]
+fn test_marking_needs_a_login_with_oidc_provider() -> Result<()> {
+    let (repo, home) = setup("oidc_not_logged_in")?;
+    let output = mark_first_block(&repo, &home)?;
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr)?;
+    assert!(stderr.contains("trueflow login"), "{stderr}");
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn test_login_caches_the_oidc_identity_for_marks() -> Result<()> {
+    let (repo, home) = setup("oidc_login")?;
+
+    let output = trueflow(&repo, &home, &["login"])?;
+    let stdout = String::from_utf8(output.stdout)?;
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(
+        stdout.contains("Open https://sso.example.com/activate and enter the code ABCD-EFGH."),
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains("Logged in as alice@example.com (https://sso.example.com)."),
+        "{stdout}"
+    );
+    let cache = fs::read_to_string(home.join("config/oidc.json"))?;
+    assert!(!cache.contains(ID_TOKEN), "{cache}");
+
+    let output = mark_first_block(&repo, &home)?;
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let records = read_review_records(&repo.path.join(".trueflow/reviews.jsonl"))?;
+    assert_eq!(
+        records[0].identity,
+        Identity::Oidc {
+            issuer: "https://sso.example.com".to_string(),
+            subject: "user-42".to_string(),
+            email: Some("alice@example.com".to_string()),
+        }
+    );
+    Ok(())
+}
+
+#[test]
+fn test_machine_provider_records_a_machine_identity() -> Result<()> {
+    let repo = TestRepo::new("machine_provider")?;
+    repo.write("src/lib.rs", "pub fn one() -> u8 {\n    1\n}\n")?;
+    repo.write(
+        "trueflow.toml",
+        "[identity]\nprovider = \"machine\"\nmachine = \"nightly-ci\"\n",
+    )?;
+    repo.commit_all("Initial")?;
+    let hash = first_block_hash(&repo.run(&["review", "--all", "--json"])?)?;
+    repo.run(&[
+        "mark",
+        "--fingerprint",
+        &hash,
+        "--path",
+        "src/lib.rs",
+        "--quiet",
+    ])?;
+
+    let history = json_array(&repo.run(&["history", "--identity-type", "machine", "--json"])?)?;
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0]["identity"]["name"], "nightly-ci");
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use anyhow::Result;
+
+mod common;
+use common::{TestRepo, json_array};
+
+const SOURCE: &str = "pub const A: u8 = 1;\npub const B: u8 = 2;\nstatic C: u8 = 3;\n\npub fn core() -> u8 {\n    A + B + C\n}\n";
+
+fn block_kinds(output: &str) -> Result<Vec<String>> {
+    let files = json_array(output)?;
+    Ok(files
+        .iter()
+        .flat_map(|file| file["blocks"].as_array().cloned().unwrap_or_default())
+        .filter_map(|block| block["kind"].as_str().map(str::to_string))
+        .collect())
+}
+
+#[test]
+fn test_optimizer_config_merges_constants() -> Result<()> {
+    let repo = TestRepo::new("optimizer_constants")?;
+    repo.write("src/lib.rs", SOURCE)?;
+    repo.commit_all("Add lib")?;
+
+    let kinds = block_kinds(&repo.run(&["review", "--all", "--json"])?)?;
+    let constants = kinds
+        .iter()
+        .filter(|kind| *kind == "const" || *kind == "static")
+        .count();
+    assert_eq!(constants, 3, "{kinds:?}");
+    assert!(!kinds.iter().any(|kind| kind == "Constants"), "{kinds:?}");
+
+    repo.write("trueflow.toml", "[optimizer]\nmerge_constants = true\n")?;
+    let kinds = block_kinds(&repo.run(&["review", "--all", "--json"])?)?;
+    assert_eq!(
+        kinds.iter().filter(|kind| *kind == "Constants").count(),
+        1,
+        "{kinds:?}"
+    );
+    assert!(!kinds.iter().any(|kind| kind == "const" || kind == "static"));
+
+    Ok(())
+}
@@ -0,0 +1,59 @@
+use anyhow::Result;
+
+mod common;
+use common::*;
+
+fn setup(name: &str) -> Result<TestRepo> {
+    let repo = TestRepo::new(name)?;
+    repo.write("src/lib.rs", "pub fn one() -> u8 {\n    1\n}\n")?;
+    repo.commit_all("Initial")?;
+    Ok(repo)
+}
+
+#[test]
+fn test_output_flag_overrides_text_and_json() -> Result<()> {
+    let repo = setup("output_formats")?;
+
+    let yaml: serde_yaml_ng::Value =
+        serde_yaml_ng::from_str(&repo.run(&["review", "--all", "--output", "yaml"])?)?;
+    assert_eq!(yaml[0]["path"].as_str(), Some("src/lib.rs"));
+
+    let toml: toml::Table = toml::from_str(&repo.run(&["--output", "toml", "scan"])?)?;
+    let files = toml["items"].as_array().expect("scan results under items");
+    assert_eq!(files[0]["path"].as_str(), Some("src/lib.rs"));
+
+    // --output wins over --json.
+    let history = repo.run(&["history", "--json", "--output", "toml"])?;
+    assert_eq!(history.trim(), "items = []");
+
+    let entry = json(&repo.run(&["config", "get", "tui.theme", "--output", "json"])?)?;
+    assert_eq!(entry["value"], "auto");
+    assert_eq!(entry["source"], "default");
+    Ok(())
+}
+
+#[test]
+fn test_failing_check_prints_one_report() -> Result<()> {
+    let repo = setup("output_check")?;
+    repo.write("src/lib.rs", "pub fn one() -> u8 {\n    2\n}\n")?;
+    repo.commit_all("Change")?;
+
+    let output = repo.run_raw(&["check", "--target", "rev:HEAD~1..HEAD", "--output", "json"])?;
+    assert_eq!(output.status.code(), Some(1));
+    let report = json(&String::from_utf8(output.stdout)?)?;
+    assert_eq!(report["passed"], false);
+    assert!(!report["violations"].as_array().unwrap().is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_errors_are_structured_under_output() -> Result<()> {
+    let repo = setup("output_errors")?;
+
+    let output = repo.run_raw(&["inspect", "--fingerprint", "ffff", "--output", "json"])?;
+    assert_eq!(output.status.code(), Some(1));
+    let report = json(&String::from_utf8(output.stdout)?)?;
+    assert_eq!(report["error"]["message"], "Block not found");
+    assert_eq!(report["error"]["causes"], serde_json::json!([]));
+    Ok(())
+}
@@ -0,0 +1,89 @@
+use anyhow::{Context, Result};
+
+mod common;
+use common::*;
+
+const CONFIG: &str = "[policy]\nrequire_owner_review = true\n\n[owners.handles]\n\"org/net\" = [\"alice@example.com\"]\n";
+
+fn approve(repo: &TestRepo, fingerprint: &str) -> Result<()> {
+    repo.run(&["mark", "--fingerprint", fingerprint, "--quiet"])?;
+    Ok(())
+}
+
+/// `src/net/` belongs to @org/net; the feature branch touches it and `src/lib.rs`.
+fn owned_repo(name: &str) -> Result<TestRepo> {
+    let repo = TestRepo::new(name)?;
+    repo.write(".github/CODEOWNERS", "/src/net/ @org/net\n")?;
+    repo.write("trueflow.toml", CONFIG)?;
+    repo.write("src/lib.rs", "pub fn core() {}\n")?;
+    repo.write("src/net/http.rs", "pub fn get() {}\n")?;
+    repo.commit_all("Initial")?;
+
+    repo.git(&["checkout", "-b", "feature/owners"])?;
+    repo.write("src/lib.rs", "pub fn core() {}\npub fn helper() {}\n")?;
+    repo.write("src/net/http.rs", "pub fn get() {}\npub fn post() {}\n")?;
+    repo.commit_all("Touch both")?;
+    Ok(repo)
+}
+
+#[test]
+fn test_check_requires_an_owner_approval() -> Result<()> {
+    // GIVEN: a non-owner approved every change
+    let repo = owned_repo("owners_check")?;
+    for change in json_array(&repo.run(&["diff", "--json"])?)? {
+        approve(
+            &repo,
+            change["fingerprint"].as_str().context("fingerprint")?,
+        )?;
+    }
+
+    // THEN: the owned change still needs its owner
+    let output = repo.run_raw(&["check", "--json"])?;
+    assert!(!output.status.success());
+    let report = json(&String::from_utf8(output.stdout)?)?;
+    let violations = report["violations"].as_array().context("violations")?;
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0]["file"], "src/net/http.rs");
+    assert_eq!(violations[0]["reason"], "missing_owner_review");
+    assert_eq!(violations[0]["owners"][0], "alice@example.com");
+
+    // WHEN: the owner approves it too
+    let fingerprint = violations[0]["fingerprint"]
+        .as_str()
+        .context("fingerprint")?
+        .to_string();
+    repo.git(&["config", "user.email", "alice@example.com"])?;
+    approve(&repo, &fingerprint)?;
+
+    // THEN: the gate passes
+    assert!(repo.run_raw(&["check"])?.status.success());
+    Ok(())
+}
+
+#[test]
+fn test_review_keeps_blocks_approved_by_non_owners() -> Result<()> {
+    // GIVEN: a non-owner approved the owned file's blocks
+    let repo = owned_repo("owners_review")?;
+    let target = ["review", "--target", "file:src/net/http.rs", "--json"];
+    for block in first_file_blocks(&repo.run(&target)?)? {
+        approve(&repo, block["hash"].as_str().context("hash")?)?;
+    }
+
+    // THEN: they stay in the queue, tagged for the owner
+    let blocks = first_file_blocks(&repo.run(&target)?)?;
+    assert!(!blocks.is_empty());
+    for block in &blocks {
+        let tags = block["tags"].as_array().context("tags")?;
+        assert!(tags.iter().any(|tag| tag == "needs-owner-review"));
+    }
+
+    // WHEN: the owner approves them
+    repo.git(&["config", "user.email", "alice@example.com"])?;
+    for block in &blocks {
+        approve(&repo, block["hash"].as_str().context("hash")?)?;
+    }
+
+    // THEN: the file is done
+    assert!(json_array(&repo.run(&target)?)?.is_empty());
+    Ok(())
+}
@@ -0,0 +1,256 @@
+use anyhow::{Context, Result};
+use serde_json::Value;
+
+mod common;
+use common::*;
+
+fn mark(repo: &TestRepo, fingerprint: &str, verdict: &str, check: &str) -> Result<()> {
+    repo.run(&[
+        "mark",
+        "--fingerprint",
+        fingerprint,
+        "--verdict",
+        verdict,
+        "--check",
+        check,
+        "--quiet",
+    ])?;
+    Ok(())
+}
+
+fn fingerprint_for(repo: &TestRepo, file: &str) -> Result<String> {
+    let changes = json_array(&repo.run(&["diff", "--json"])?)?;
+    changes
+        .iter()
+        .find(|change| change["file"] == file)
+        .and_then(|change| change["fingerprint"].as_str())
+        .map(str::to_string)
+        .context("fingerprint")
+}
+
+fn check_report(repo: &TestRepo) -> Result<(bool, Value)> {
+    let output = repo.run_raw(&["check", "--json"])?;
+    Ok((
+        output.status.success(),
+        json(&String::from_utf8(output.stdout)?)?,
+    ))
+}
+
+/// A repo whose feature branch touches `src/lib.rs` and `src/crypto/aes.rs`.
+fn feature_repo(name: &str, config: &str) -> Result<TestRepo> {
+    let repo = TestRepo::new(name)?;
+    repo.write("src/lib.rs", "pub fn core() {}\n")?;
+    repo.write("src/crypto/aes.rs", "pub fn encrypt() {}\n")?;
+    repo.write("trueflow.toml", config)?;
+    repo.commit_all("Initial")?;
+
+    repo.git(&["checkout", "-b", "feature/policy"])?;
+    repo.write("src/lib.rs", "pub fn core() {}\npub fn helper() {}\n")?;
+    repo.write(
+        "src/crypto/aes.rs",
+        "pub fn encrypt() {}\npub fn decrypt() {}\n",
+    )?;
+    repo.commit_all("Touch both")?;
+    Ok(repo)
+}
+
+#[test]
+fn test_path_rule_requires_security_approvals() -> Result<()> {
+    // GIVEN: crypto code needs two security approvals
+    let repo = feature_repo(
+        "policy_security_rule",
+        "[[policy.rules]]\npaths = [\"src/crypto/**\"]\ncheck = \"security\"\napprovals = 2\n",
+    )?;
+    let lib = fingerprint_for(&repo, "src/lib.rs")?;
+    let crypto = fingerprint_for(&repo, "src/crypto/aes.rs")?;
+
+    // WHEN: both changes get an ordinary review approval
+    mark(&repo, &lib, "approved", "review")?;
+    mark(&repo, &crypto, "approved", "review")?;
+
+    // THEN: only the crypto change is reported, with what it is missing
+    let (passed, report) = check_report(&repo)?;
+    assert!(!passed);
+    assert_eq!(report["passed"], false);
+    let violations = report["violations"].as_array().context("violations")?;
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0]["file"], "src/crypto/aes.rs");
+    assert_eq!(violations[0]["reason"], "missing_approvals");
+    assert_eq!(violations[0]["check"], "security");
+    assert_eq!(violations[0]["required"], 2);
+    assert_eq!(violations[0]["approvals"], 0);
+
+    // WHEN: two distinct reviewers approve it under the security check
+    mark(&repo, &crypto, "approved", "security")?;
+    repo.git(&["config", "user.email", "security@example.com"])?;
+    mark(&repo, &crypto, "approved", "security")?;
+
+    // THEN: the gate passes
+    let (passed, report) = check_report(&repo)?;
+    assert!(passed, "{report}");
+    assert_eq!(report["violations"].as_array().map(Vec::len), Some(0));
+    Ok(())
+}
+
+fn lane_blocks(repo: &TestRepo, check: &str) -> Result<Vec<(String, Value)>> {
+    let files = json_array(&repo.run(&["review", "--all", "--check", check, "--json"])?)?;
+    Ok(files
+        .iter()
+        .flat_map(|file| {
+            let path = file["path"].as_str().unwrap_or_default().to_string();
+            file["blocks"]
+                .as_array()
+                .cloned()
+                .unwrap_or_default()
+                .into_iter()
+                .map(move |block| (path.clone(), block))
+        })
+        .collect())
+}
+
+#[test]
+fn test_tagged_paths_get_a_lane_and_a_gate() -> Result<()> {
+    // GIVEN: crypto code is tagged security, and the tag needs a security approval
+    let repo = feature_repo(
+        "policy_security_tags",
+        "[tags]\nsecurity = [\"src/crypto/**\"]\n\n[[policy.rules]]\ntags = [\"security\"]\ncheck = \"security\"\n",
+    )?;
+
+    // THEN: its blocks carry the tag and make up the security lane
+    let files = json_array(&repo.run(&["review", "--all", "--json"])?)?;
+    for file in &files {
+        let tagged = file["blocks"]
+            .as_array()
+            .context("blocks")?
+            .iter()
+            .all(|block| {
+                block["tags"]
+                    .as_array()
+                    .is_some_and(|tags| tags.contains(&"security".into()))
+            });
+        assert_eq!(tagged, file["path"] == "src/crypto/aes.rs", "{file}");
+    }
+    let lane = lane_blocks(&repo, "security")?;
+    assert!(!lane.is_empty());
+    assert!(lane.iter().all(|(path, _)| path == "src/crypto/aes.rs"));
+
+    // WHEN: every change gets an ordinary review approval
+    let lib = fingerprint_for(&repo, "src/lib.rs")?;
+    let crypto = fingerprint_for(&repo, "src/crypto/aes.rs")?;
+    mark(&repo, &lib, "approved", "review")?;
+    mark(&repo, &crypto, "approved", "review")?;
+
+    // THEN: the gate still wants the security check on the tagged change
+    let (passed, report) = check_report(&repo)?;
+    assert!(!passed);
+    let violations = report["violations"].as_array().context("violations")?;
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0]["file"], "src/crypto/aes.rs");
+    assert_eq!(violations[0]["check"], "security");
+
+    // WHEN: the lane's blocks are approved under the security check
+    for (_, block) in lane {
+        mark(
+            &repo,
+            block["hash"].as_str().context("hash")?,
+            "approved",
+            "security",
+        )?;
+    }
+
+    // THEN: the lane is empty
+    assert!(lane_blocks(&repo, "security")?.is_empty());
+
+    // WHEN: the change itself is approved under the security check
+    mark(&repo, &crypto, "approved", "security")?;
+
+    // THEN: the gate passes
+    let (passed, report) = check_report(&repo)?;
+    assert!(passed, "{report}");
+    Ok(())
+}
+
+#[test]
+fn test_comment_verdicts_can_be_non_blocking() -> Result<()> {
+    // GIVEN: changes that only carry comments
+    let repo = feature_repo("policy_non_blocking", "")?;
+    for file in ["src/lib.rs", "src/crypto/aes.rs"] {
+        let fingerprint = fingerprint_for(&repo, file)?;
+        mark(&repo, &fingerprint, "comment", "review")?;
+    }
+
+    // THEN: comments block by default
+    let (passed, report) = check_report(&repo)?;
+    assert!(!passed);
+    assert_eq!(report["violations"][0]["reason"], "unreviewed");
+    assert_eq!(report["violations"][0]["status"], "comment");
+
+    // WHEN: comment verdicts are declared non-blocking
+    repo.write(
+        "trueflow.toml",
+        "[policy]\nnon_blocking_verdicts = [\"comment\"]\n",
+    )?;
+
+    // THEN: the gate passes
+    assert!(check_report(&repo)?.0);
+    Ok(())
+}
+
+#[test]
+fn test_rejected_changes_fail_the_gate() -> Result<()> {
+    // GIVEN: one rejected change, comments otherwise non-blocking
+    let repo = feature_repo(
+        "policy_rejected",
+        "[policy]\nnon_blocking_verdicts = [\"comment\"]\n",
+    )?;
+    let lib = fingerprint_for(&repo, "src/lib.rs")?;
+    let crypto = fingerprint_for(&repo, "src/crypto/aes.rs")?;
+    mark(&repo, &lib, "approved", "review")?;
+    mark(&repo, &crypto, "rejected", "review")?;
+
+    // THEN: the rejection is reported as such
+    let (passed, report) = check_report(&repo)?;
+    assert!(!passed);
+    let violations = report["violations"].as_array().context("violations")?;
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0]["reason"], "rejected");
+    assert_eq!(violations[0]["check"], "review");
+
+    // AND: rejections cannot be declared non-blocking
+    repo.write(
+        "trueflow.toml",
+        "[policy]\nnon_blocking_verdicts = [\"rejected\"]\n",
+    )?;
+    let stderr = repo.run_err(&["check"])?;
+    assert!(stderr.contains("non_blocking_verdicts cannot include rejected"));
+    Ok(())
+}
+
+#[test]
+fn test_check_reports_for_ci() -> Result<()> {
+    // GIVEN: one approved change and one rejected one
+    let repo = feature_repo("policy_ci_formats", "")?;
+    let lib = fingerprint_for(&repo, "src/lib.rs")?;
+    let crypto = fingerprint_for(&repo, "src/crypto/aes.rs")?;
+    mark(&repo, &lib, "approved", "review")?;
+    mark(&repo, &crypto, "rejected", "review")?;
+
+    // THEN: JUnit has one failing testcase, for the rejection
+    let output = repo.run_raw(&["check", "--format", "junit"])?;
+    assert!(!output.status.success());
+    let xml = String::from_utf8(output.stdout)?;
+    assert!(xml.starts_with("<?xml"));
+    assert_eq!(xml.matches("<testcase ").count(), 1);
+    assert!(xml.contains("classname=\"src/crypto/aes.rs\""));
+    assert!(xml.contains("<failure type=\"rejected\""));
+
+    // AND: GitHub annotations point at the rejected line
+    let output = repo.run_raw(&["check", "--format", "github"])?;
+    assert!(!output.status.success());
+    let annotations = String::from_utf8(output.stdout)?;
+    let lines: Vec<&str> = annotations.lines().collect();
+    assert_eq!(lines.len(), 1);
+    assert!(lines[0].starts_with("::error file=src/crypto/aes.rs,line="));
+    assert!(lines[0].contains(&crypto));
+    Ok(())
+}
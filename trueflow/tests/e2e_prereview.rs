@@ -0,0 +1,67 @@
+use anyhow::Result;
+
+mod common;
+use common::{TestRepo, json_array, read_review_records};
+use trueflow_core::store::{Identity, Verdict};
+
+const ANNOTATOR: &str = r#"[prereview]
+command = 'if grep -q "pub fn core"; then echo "{\"summary\": \"Adds core\", \"risk\": 20, \"suggested_verdict\": \"approved\"}"; else echo "{}"; fi'
+"#;
+
+#[test]
+fn test_prereview_records_hints_without_approving() -> Result<()> {
+    let repo = TestRepo::new("prereview_hints")?;
+    repo.write("trueflow.toml", ANNOTATOR)?;
+    repo.write("src/lib.rs", "pub fn core() {}\n")?;
+    repo.commit_all("Add lib")?;
+
+    let output = repo.run(&["prereview", "--target", "file:src/lib.rs"])?;
+    assert!(output.contains("Pre-reviewed 1 block(s)."), "{output}");
+
+    let records = read_review_records(&repo.path.join(".trueflow/reviews.jsonl"))?;
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].check, "prereview");
+    assert_eq!(records[0].verdict, Verdict::Comment);
+    assert_eq!(records[0].note.as_deref(), Some("Adds core"));
+    assert_eq!(
+        records[0].identity,
+        Identity::Machine {
+            name: "trueflow-prereview".to_string(),
+            version: None,
+        }
+    );
+
+    // A suggested approval is only a hint: the block is still unreviewed.
+    let output = repo.run(&["review", "--target", "file:src/lib.rs", "--json"])?;
+    let files = json_array(&output)?;
+    let hint = &files[0]["blocks"][0]["prereview"];
+    assert_eq!(hint["summary"], "Adds core");
+    assert_eq!(hint["risk"], 20);
+    assert_eq!(hint["suggested_verdict"], "approved");
+    assert_eq!(hint["by"], "machine:trueflow-prereview");
+
+    let output = repo.run(&["prereview", "--target", "file:src/lib.rs"])?;
+    assert!(output.contains("No blocks to pre-review."), "{output}");
+    let output = repo.run(&["prereview", "--target", "file:src/lib.rs", "--force"])?;
+    assert!(output.contains("Pre-reviewed 1 block(s)."), "{output}");
+    Ok(())
+}
+
+#[test]
+fn test_prereview_needs_an_annotator_and_reports_failures() -> Result<()> {
+    let repo = TestRepo::new("prereview_failures")?;
+    repo.write("src/lib.rs", "pub fn core() {}\n")?;
+    repo.commit_all("Add lib")?;
+
+    let err = repo.run_err(&["prereview", "--all"])?;
+    assert!(err.contains("[prereview] command or url"), "{err}");
+
+    repo.write(
+        "trueflow.toml",
+        "[prereview]\ncommand = \"echo not json\"\n",
+    )?;
+    let err = repo.run_err(&["prereview", "--target", "file:src/lib.rs"])?;
+    assert!(err.contains("Pre-review failed for 1 block(s)"), "{err}");
+    assert!(!repo.path.join(".trueflow/reviews.jsonl").exists());
+    Ok(())
+}
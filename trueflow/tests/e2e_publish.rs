@@ -0,0 +1,118 @@
+// The fakes these tests run are shell scripts.
+#![cfg(unix)]
+
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+mod common;
+use common::*;
+
+/// Stand-in for curl: appends the request config it reads from stdin to
+/// `$CURL_LOG`.
+const FAKE_CURL: &str = "#!/bin/sh\ncat >> \"$CURL_LOG\"\necho '{}'\n";
+
+fn fake_curl() -> Result<(PathBuf, PathBuf)> {
+    use std::os::unix::fs::PermissionsExt;
+    let dir = std::env::temp_dir()
+        .join("trueflow_tests")
+        .join(format!("curl_{}", uuid::Uuid::new_v4()));
+    fs::create_dir_all(&dir)?;
+    let path = dir.join("curl");
+    fs::write(&path, FAKE_CURL)?;
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+    Ok((path, dir.join("requests.log")))
+}
+
+fn publish(repo: &TestRepo, curl: &Path, log: &Path, args: &[&str]) -> Result<Output> {
+    Ok(Command::new(env!("CARGO_BIN_EXE_trueflow"))
+        .arg("publish")
+        .args(args)
+        .current_dir(&repo.path)
+        .env("TRUEFLOW_CURL", curl)
+        .env("CURL_LOG", log)
+        .env("GITLAB_TOKEN", "gl-secret")
+        .env("GITEA_TOKEN", "gt-secret")
+        .output()?)
+}
+
+/// A feature branch whose `src/lib.rs` change was rejected with a note.
+fn rejected_change(name: &str, config: &str) -> Result<TestRepo> {
+    let repo = TestRepo::new(name)?;
+    repo.write("src/lib.rs", "pub fn core() {}\n")?;
+    repo.write("trueflow.toml", config)?;
+    repo.commit_all("Initial")?;
+    repo.git(&["checkout", "-b", "feature/publish"])?;
+    repo.write("src/lib.rs", "pub fn core() {}\npub fn helper() {}\n")?;
+    repo.commit_all("Add helper")?;
+
+    let changes = json_array(&repo.run(&["diff", "--json"])?)?;
+    let fingerprint = changes[0]["fingerprint"].as_str().context("fingerprint")?;
+    repo.run(&[
+        "mark",
+        "--fingerprint",
+        fingerprint,
+        "--verdict",
+        "rejected",
+        "--note",
+        "Needs a doc comment",
+        "--quiet",
+    ])?;
+    Ok(repo)
+}
+
+#[test]
+fn test_publish_to_gitlab_merge_request() -> Result<()> {
+    // GIVEN: GitLab selected in trueflow.toml
+    let repo = rejected_change(
+        "publish_gitlab",
+        "[publish]\nprovider = \"gitlab\"\nrepo = \"org/app\"\n",
+    )?;
+    let (curl, log) = fake_curl()?;
+
+    // WHEN: the notes are published to merge request 7
+    let output = publish(&repo, &curl, &log, &["--pr", "7"])?;
+    assert!(output.status.success(), "{:?}", output);
+    assert!(String::from_utf8(output.stdout)?.contains("Published 1 comment(s) to gitlab #7."));
+
+    // THEN: one diff discussion is started on the changed file
+    let request = fs::read_to_string(&log)?;
+    assert!(request.contains(
+        "url = \"https://gitlab.com/api/v4/projects/org%2Fapp/merge_requests/7/discussions\""
+    ));
+    assert!(request.contains("header = \"PRIVATE-TOKEN: gl-secret\""));
+    assert!(request.contains("\\\"new_path\\\":\\\"src/lib.rs\\\""));
+    assert!(request.contains("Needs a doc comment"));
+    Ok(())
+}
+
+#[test]
+fn test_publish_to_gitea_pull_request() -> Result<()> {
+    // GIVEN: a self-hosted Gitea
+    let repo = rejected_change(
+        "publish_gitea",
+        "[publish]\nprovider = \"gitea\"\nurl = \"https://gitea.example.com/api/v1/\"\nrepo = \"org/app\"\n",
+    )?;
+    let (curl, log) = fake_curl()?;
+
+    // WHEN: a dry run lists the comments
+    let output = publish(&repo, &curl, &log, &["--pr", "3", "--dry-run"])?;
+    assert!(output.status.success(), "{:?}", output);
+    let stdout = String::from_utf8(output.stdout)?;
+    assert!(stdout.contains("Would publish 1 comment(s) to gitea #3:"));
+    assert!(stdout.contains("src/lib.rs:"));
+    assert!(!log.exists());
+
+    // THEN: publishing posts a single review
+    let output = publish(&repo, &curl, &log, &["--pr", "3"])?;
+    assert!(output.status.success(), "{:?}", output);
+    let request = fs::read_to_string(&log)?;
+    assert!(
+        request
+            .contains("url = \"https://gitea.example.com/api/v1/repos/org/app/pulls/3/reviews\"")
+    );
+    assert!(request.contains("header = \"Authorization: token gt-secret\""));
+    assert!(request.contains("\\\"event\\\":\\\"COMMENT\\\""));
+    Ok(())
+}
@@ -0,0 +1,86 @@
+use anyhow::{Context, Result};
+
+mod common;
+use common::{TestRepo, first_block_hash, json_array};
+
+fn ask(repo: &TestRepo, fingerprint: &str, path: &str, note: &str) -> Result<()> {
+    repo.run(&[
+        "mark",
+        "--fingerprint",
+        fingerprint,
+        "--verdict",
+        "question",
+        "--note",
+        note,
+        "--path",
+        path,
+        "--quiet",
+    ])?;
+    Ok(())
+}
+
+fn open_ids(repo: &TestRepo, args: &[&str]) -> Result<Vec<String>> {
+    let mut command = vec!["questions", "--json"];
+    command.extend_from_slice(args);
+    Ok(json_array(&repo.run(&command)?)?
+        .iter()
+        .filter_map(|record| record["id"].as_str().map(str::to_string))
+        .collect())
+}
+
+#[test]
+fn test_answered_questions_leave_the_inbox() -> Result<()> {
+    let repo = TestRepo::new("questions_inbox")?;
+    repo.write("src/lib.rs", "pub fn core() {}\n")?;
+    repo.write("docs/notes.md", "# Notes\n\nSome text.\n")?;
+    repo.commit_all("Initial")?;
+
+    let hash = first_block_hash(&repo.run(&["review", "--all", "--json"])?)?;
+    ask(&repo, &hash, "src/lib.rs", "Why is this public?")?;
+    repo.git(&["config", "user.email", "second@example.com"])?;
+    ask(&repo, "feedface", "docs/notes.md", "Is this still true?")?;
+
+    let all = open_ids(&repo, &[])?;
+    assert_eq!(all.len(), 2);
+    assert_eq!(open_ids(&repo, &["--path", "src"])?, [all[0].clone()]);
+    assert_eq!(
+        open_ids(&repo, &["--author", "second@example.com"])?,
+        [all[1].clone()]
+    );
+
+    let text = repo.run(&["questions"])?;
+    assert!(text.contains("Why is this public?"), "{text}");
+    assert!(text.contains("src/lib.rs"), "{text}");
+
+    let review = repo.run(&["review", "--all", "--reviewer", "test@example.com"])?;
+    assert!(review.contains("[Question]"), "{review}");
+
+    repo.run(&["answer", "--id", &all[0][..8], "--note", "It is the API."])?;
+    assert_eq!(open_ids(&repo, &[])?, [all[1].clone()]);
+    let review = repo.run(&["review", "--all", "--reviewer", "test@example.com"])?;
+    assert!(!review.contains("[Question]"), "{review}");
+    assert!(review.contains("[Unreviewed]"), "{review}");
+
+    repo.run(&["resolve", "--id", &all[1]])?;
+    assert!(open_ids(&repo, &[])?.is_empty());
+    assert!(repo.run(&["questions"])?.contains("No open questions."));
+
+    let err = repo.run_err(&["answer", "--id", "nope", "--note", "?"])?;
+    assert!(err.contains("No question with id nope"), "{err}");
+
+    let records = common::read_review_records(&repo.path.join(".trueflow/reviews.jsonl"))?;
+    let answer = records
+        .iter()
+        .find(|record| record.note.as_deref() == Some("It is the API."))
+        .context("answer record")?;
+    assert_eq!(answer.fingerprint, hash);
+    assert_eq!(
+        answer
+            .answer
+            .as_ref()
+            .map(|answer| answer.question.as_str()),
+        Some(all[0].as_str())
+    );
+
+    Ok(())
+}
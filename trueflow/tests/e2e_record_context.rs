@@ -0,0 +1,199 @@
+use anyhow::{Context, Result};
+use serde_json::Value;
+
+mod common;
+use common::*;
+use trueflow_core::store::CURRENT_VERSION;
+
+fn function_block(repo: &TestRepo) -> Result<(String, String)> {
+    let files = json_array(&repo.run(&["scan", "--json"])?)?;
+    let file = files.first().context("Expected file in output")?;
+    let block = file["blocks"]
+        .as_array()
+        .context("blocks")?
+        .iter()
+        .find(|block| block["kind"] == "function")
+        .context("function block")?;
+    Ok((
+        block["hash"].as_str().context("hash")?.to_string(),
+        file["file_hash"].as_str().context("file_hash")?.to_string(),
+    ))
+}
+
+fn ledger(repo: &TestRepo) -> std::path::PathBuf {
+    repo.path.join(".trueflow/reviews.jsonl")
+}
+
+#[test]
+fn test_mark_records_block_context() -> Result<()> {
+    let repo = TestRepo::new("record_context_mark")?;
+    repo.write("lib.rs", "pub fn core() -> u8 {\n    1\n}\n")?;
+    repo.commit_all("Initial")?;
+    let (hash, file_hash) = function_block(&repo)?;
+    let head = run_git_output(&repo.path, &["rev-parse", "HEAD"])?;
+
+    repo.run(&[
+        "mark",
+        "--fingerprint",
+        &hash,
+        "--path",
+        "lib.rs",
+        "--quiet",
+    ])?;
+
+    let records = read_review_records(&ledger(&repo))?;
+    let record = records.first().context("record")?;
+    assert_eq!(record.version, CURRENT_VERSION);
+    let context = record.context.as_ref().context("context")?;
+    assert_eq!(context.block_kind.as_deref(), Some("function"));
+    assert_eq!(context.language.as_deref(), Some("Rust"));
+    assert_eq!(context.symbol.as_deref(), Some("core"));
+    assert_eq!(context.file_hash.as_deref(), Some(file_hash.as_str()));
+    assert_eq!(context.base_revision.as_deref(), Some(head.trim()));
+    Ok(())
+}
+
+#[test]
+fn test_mark_without_path_finds_the_block() -> Result<()> {
+    let repo = TestRepo::new("record_context_no_path")?;
+    repo.write("lib.rs", "pub fn core() -> u8 {\n    1\n}\n")?;
+    repo.commit_all("Initial")?;
+    let (hash, file_hash) = function_block(&repo)?;
+
+    repo.run(&["mark", "--fingerprint", &hash, "--quiet"])?;
+    let output = repo.run_raw(&[
+        "--logging-mode",
+        "stderr",
+        "mark",
+        "--fingerprint",
+        "deadbeef",
+        "--quiet",
+    ])?;
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr)?;
+    assert!(
+        stderr.contains("Block deadbeef isn't in the workspace"),
+        "{stderr}"
+    );
+
+    let records = read_review_records(&ledger(&repo))?;
+    let context = records[0].context.as_ref().context("context")?;
+    assert_eq!(context.block_kind.as_deref(), Some("function"));
+    assert_eq!(context.language.as_deref(), Some("Rust"));
+    assert_eq!(context.symbol.as_deref(), Some("core"));
+    assert_eq!(context.file_hash.as_deref(), Some(file_hash.as_str()));
+    assert!(records[0].path_hint.is_none());
+    let missing = records[1].context.as_ref().context("context")?;
+    assert!(missing.block_kind.is_none());
+    Ok(())
+}
+
+#[test]
+fn test_store_migrate_upgrades_unsigned_records() -> Result<()> {
+    let repo = TestRepo::new("record_context_migrate")?;
+    repo.write("lib.rs", "pub fn core() -> u8 {\n    1\n}\n")?;
+    repo.commit_all("Initial")?;
+    let (hash, _) = function_block(&repo)?;
+
+    let mut live = build_review_record(
+        &hash,
+        ReviewRecordOverrides {
+            id: Some("live"),
+            ..Default::default()
+        },
+    );
+    live["path_hint"] = "lib.rs".into();
+    let mut legacy = build_review_record(
+        "gone",
+        ReviewRecordOverrides {
+            id: Some("legacy"),
+            ..Default::default()
+        },
+    );
+    legacy.as_object_mut().context("object")?.remove("version");
+    let signed = build_review_record(
+        &hash,
+        ReviewRecordOverrides {
+            id: Some("signed"),
+            attestations: Some(serde_json::json!([{
+                "kind": "SSH",
+                "canonicalization": "JCS_V1",
+                "signature": "sig",
+                "public_key": "key"
+            }])),
+            ..Default::default()
+        },
+    );
+    write_reviews_jsonl(&repo.path.join(".trueflow"), &[live, legacy, signed])?;
+    let before = std::fs::read_to_string(ledger(&repo))?;
+
+    let dry_run = repo.run(&["store", "migrate", "--dry-run"])?;
+    assert!(dry_run.contains("Would migrate 2 record(s)"), "{dry_run}");
+    assert_eq!(std::fs::read_to_string(ledger(&repo))?, before);
+
+    let output = repo.run(&["store", "migrate"])?;
+    assert!(output.contains("Migrated 2 record(s)"), "{output}");
+    assert!(output.contains("1 signed record(s)"), "{output}");
+
+    let lines: Vec<Value> = std::fs::read_to_string(ledger(&repo))?
+        .lines()
+        .map(serde_json::from_str)
+        .collect::<Result<_, _>>()?;
+    let by_id = |id: &str| lines.iter().find(|line| line["id"] == id).cloned();
+    let live = by_id("live").context("live")?;
+    assert_eq!(live["version"], CURRENT_VERSION);
+    assert_eq!(live["context"]["symbol"], "core");
+    assert!(live["context"].get("file_hash").is_none());
+    let legacy = by_id("legacy").context("legacy")?;
+    assert_eq!(legacy["version"], CURRENT_VERSION);
+    assert!(legacy.get("context").is_none());
+    assert_eq!(by_id("signed").context("signed")?["version"], 1);
+
+    let again = repo.run(&["store", "migrate"])?;
+    assert!(again.contains("Migrated 0 record(s)"), "{again}");
+    Ok(())
+}
+
+#[test]
+fn test_store_migrate_leaves_unknown_content_alone() -> Result<()> {
+    let repo = TestRepo::new("record_context_migrate_lossless")?;
+    let mut legacy = build_review_record(
+        "gone",
+        ReviewRecordOverrides {
+            id: Some("legacy"),
+            ..Default::default()
+        },
+    );
+    legacy.as_object_mut().context("object")?.remove("version");
+    let mut current = build_review_record(
+        "kept",
+        ReviewRecordOverrides {
+            id: Some("current"),
+            ..Default::default()
+        },
+    );
+    current["version"] = CURRENT_VERSION.into();
+    current["future_field"] = "kept".into();
+    let mut newer = current.clone();
+    newer["id"] = "newer".into();
+    newer["version"] = (CURRENT_VERSION + 1).into();
+
+    std::fs::create_dir_all(repo.path.join(".trueflow"))?;
+
+    // Unreadable lines and newer records stop the migration untouched.
+    for extra in ["<<<<<<< not a record".to_string(), newer.to_string()] {
+        let content = format!("{legacy}\n{current}\n{extra}\n");
+        std::fs::write(ledger(&repo), &content)?;
+        repo.run_err(&["store", "migrate"])?;
+        assert_eq!(std::fs::read_to_string(ledger(&repo))?, content);
+    }
+
+    // Otherwise records that need no migration are written back as they were.
+    let current_line = current.to_string();
+    std::fs::write(ledger(&repo), format!("{legacy}\n{current_line}\n"))?;
+    let output = repo.run(&["store", "migrate"])?;
+    assert!(output.contains("Migrated 1 record(s)"), "{output}");
+    let content = std::fs::read_to_string(ledger(&repo))?;
+    assert_eq!(content.lines().nth(1), Some(current_line.as_str()));
+    Ok(())
+}
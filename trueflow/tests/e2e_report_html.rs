@@ -0,0 +1,54 @@
+use anyhow::Result;
+
+mod common;
+use common::{TestRepo, first_block_hash};
+
+#[test]
+fn test_html_report_shows_coverage_notes_and_reviewers() -> Result<()> {
+    let repo = TestRepo::new("report_html")?;
+    repo.write(
+        "src/lib.rs",
+        "pub fn core() -> &'static str {\n    \"a<b\"\n}\n",
+    )?;
+    repo.write("src/done.rs", "pub fn done() {}\n")?;
+    repo.commit_all("Add files")?;
+
+    let output = repo.run(&["review", "--target", "file:src/lib.rs", "--json"])?;
+    let hash = first_block_hash(&output)?;
+    repo.run(&[
+        "mark",
+        "--fingerprint",
+        &hash,
+        "--verdict",
+        "rejected",
+        "--note",
+        "Don't <return> a literal",
+    ])?;
+    let output = repo.run(&["review", "--target", "file:src/done.rs", "--json"])?;
+    let hash = first_block_hash(&output)?;
+    repo.run(&["mark", "--fingerprint", &hash, "--verdict", "approved"])?;
+
+    let output = repo.run(&["report", "html", "-o", "audit/"])?;
+    assert!(output.contains("audit/index.html"), "{output}");
+    let html = std::fs::read_to_string(repo.path.join("audit/index.html"))?;
+
+    assert!(html.starts_with("<!DOCTYPE html>"), "{html}");
+    assert!(
+        !html.contains("<script") && !html.contains("<link"),
+        "{html}"
+    );
+    assert!(html.contains("<td>src/done.rs</td>"), "{html}");
+    assert!(html.contains("<td>test@example.com</td>"), "{html}");
+    assert!(
+        html.contains("Don&apos;t &lt;return&gt; a literal"),
+        "{html}"
+    );
+    assert!(html.contains("status rejected"), "{html}");
+    assert!(
+        html.contains("<span class=\"hl-string\">&quot;a&lt;b&quot;</span>"),
+        "{html}"
+    );
+    // Approved blocks only show up in the coverage table.
+    assert!(!html.contains("<h3>src/done.rs</h3>"), "{html}");
+    Ok(())
+}
@@ -0,0 +1,112 @@
+use anyhow::Result;
+
+mod common;
+use common::{TestRepo, first_block_hash, json, json_array};
+
+#[test]
+fn test_request_assigns_codeowners_and_lists_open_requests() -> Result<()> {
+    let repo = TestRepo::new("request_codeowners")?;
+    repo.write(
+        ".github/CODEOWNERS",
+        "* lead@example.com\n/src/net/ test@example.com @org/net\n",
+    )?;
+    repo.write(
+        "trueflow.toml",
+        "[owners.handles]\n\"org/net\" = [\"net@example.com\", \"test@example.com\"]\n",
+    )?;
+    repo.write("src/net/http.rs", "pub fn get() {}\n")?;
+    repo.write("src/core.rs", "pub fn core() {}\n")?;
+    repo.commit_all("Add files")?;
+
+    let output = repo.run(&["request", "--all", "--note", "please look"])?;
+    assert!(output.contains("Requested review of"), "{output}");
+
+    let output = repo.run(&["review", "--mine", "--json"])?;
+    let files = json_array(&output)?;
+    let paths: Vec<&str> = files
+        .iter()
+        .filter_map(|file| file["path"].as_str())
+        .collect();
+    assert_eq!(paths, vec!["src/net/http.rs"]);
+
+    let output = repo.run(&["requests", "--json"])?;
+    let requests = json(&output)?;
+    let requests = requests.as_array().expect("requests array");
+    assert_eq!(requests.len(), 1);
+    let request = &requests[0];
+    assert_eq!(request["requested_by"], "test@example.com");
+    assert_eq!(request["note"], "please look");
+    let mut reviewers: Vec<&str> = request["reviewers"]
+        .as_array()
+        .expect("reviewers")
+        .iter()
+        .filter_map(|value| value.as_str())
+        .collect();
+    reviewers.sort();
+    assert_eq!(
+        reviewers,
+        vec!["lead@example.com", "net@example.com", "test@example.com"]
+    );
+    assert!(request["remaining_blocks"].as_u64().unwrap_or(0) > 0);
+
+    Ok(())
+}
+
+#[test]
+fn test_request_with_explicit_reviewers_closes_when_reviewed() -> Result<()> {
+    let repo = TestRepo::new("request_explicit")?;
+    repo.write("src/lib.rs", "pub fn core() {}\n")?;
+    repo.commit_all("Add lib")?;
+
+    repo.run(&[
+        "request",
+        "--target",
+        "file:src/lib.rs",
+        "--reviewer",
+        "alice@example.com",
+        "--reviewer",
+        "test@example.com",
+    ])?;
+
+    let output = repo.run(&["requests", "--mine"])?;
+    assert!(output.contains("[open]"), "{output}");
+    assert!(output.contains("alice@example.com, test@example.com"));
+
+    let output = repo.run(&["review", "--all", "--json"])?;
+    let hash = first_block_hash(&output)?;
+    repo.run(&["mark", "--fingerprint", &hash, "--verdict", "approved"])?;
+
+    let output = repo.run(&["requests"])?;
+    assert!(output.contains("No open review requests."), "{output}");
+    let output = repo.run(&["requests", "--all"])?;
+    assert!(output.contains("[done]"), "{output}");
+
+    Ok(())
+}
+
+#[test]
+fn test_request_without_reviewers_fails() -> Result<()> {
+    let repo = TestRepo::new("request_no_reviewers")?;
+    repo.write("src/lib.rs", "pub fn core() {}\n")?;
+    repo.commit_all("Add lib")?;
+
+    let stderr = repo.run_err(&["request", "--all"])?;
+    assert!(stderr.contains("No reviewers found"), "{stderr}");
+
+    Ok(())
+}
+
+#[test]
+fn test_request_fails_on_unmapped_codeowners_handles() -> Result<()> {
+    let repo = TestRepo::new("request_unmapped_handle")?;
+    repo.write(".github/CODEOWNERS", "* lead@example.com @org/net @bob\n")?;
+    repo.write("src/lib.rs", "pub fn core() {}\n")?;
+    repo.commit_all("Add lib")?;
+
+    let stderr = repo.run_err(&["request", "--all"])?;
+    assert!(stderr.contains("@bob, @org/net"), "{stderr}");
+    let output = repo.run(&["requests", "--all", "--json"])?;
+    assert_eq!(json(&output)?, serde_json::json!([]));
+
+    Ok(())
+}
@@ -0,0 +1,75 @@
+use anyhow::Result;
+
+mod common;
+use common::{TestRepo, first_block_hash, json_array};
+
+fn reject(repo: &TestRepo, path: &str, note: &str) -> Result<()> {
+    let output = repo.run(&["review", "--target", &format!("file:{path}"), "--json"])?;
+    let hash = first_block_hash(&output)?;
+    repo.run(&[
+        "mark",
+        "--fingerprint",
+        &hash,
+        "--verdict",
+        "rejected",
+        "--note",
+        note,
+        "--quiet",
+    ])?;
+    Ok(())
+}
+
+/// Review ids per file in `feedback --format json`.
+fn feedback(repo: &TestRepo, args: &[&str]) -> Result<Vec<(String, Vec<String>)>> {
+    let mut command = vec!["feedback", "--format", "json"];
+    command.extend_from_slice(args);
+    Ok(json_array(&repo.run(&command)?)?
+        .iter()
+        .map(|entry| {
+            let ids = entry["reviews"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|review| review["id"].as_str().map(str::to_string))
+                .collect();
+            (entry["file"].as_str().unwrap_or_default().to_string(), ids)
+        })
+        .collect())
+}
+
+#[test]
+fn test_resolved_feedback_is_hidden() -> Result<()> {
+    let repo = TestRepo::new("resolve_feedback")?;
+    repo.write("src/a.rs", "fn a() { }\n")?;
+    repo.write("src/b.rs", "fn b() { }\n")?;
+    repo.commit_all("Initial")?;
+    reject(&repo, "src/a.rs", "Use a constant")?;
+    reject(&repo, "src/b.rs", "Missing docs")?;
+
+    let entries = feedback(&repo, &[])?;
+    assert_eq!(entries.len(), 2);
+    let id = entries[0].1[0].clone();
+    let xml = repo.run(&["feedback"])?;
+    assert!(xml.contains(&format!("id=\"{id}\"")), "{xml}");
+
+    repo.run(&["resolve", "--id", &id[..8], "--note", "fixed in abc123"])?;
+    let entries = feedback(&repo, &[])?;
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].0, "src/b.rs");
+    assert_eq!(feedback(&repo, &["--include-resolved"])?.len(), 2);
+
+    let records = common::read_review_records(&repo.path.join(".trueflow/reviews.jsonl"))?;
+    let resolution = records.last().expect("resolution record");
+    assert_eq!(
+        resolution.resolution.as_ref().map(|r| r.record.as_str()),
+        Some(id.as_str())
+    );
+    assert_eq!(resolution.note.as_deref(), Some("fixed in abc123"));
+
+    let again = repo.run_raw(&["resolve", "--id", &id])?;
+    let stderr = String::from_utf8_lossy(&again.stderr);
+    assert!(stderr.contains("already resolved"), "{stderr}");
+    let err = repo.run_err(&["resolve", "--id", "nope"])?;
+    assert!(err.contains("No verdict with id nope"), "{err}");
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use anyhow::{Context, Result};
+
+mod common;
+use common::{TestRepo, first_block_hash, json_array};
+
+fn approve(repo: &TestRepo, fingerprint: &str) -> Result<()> {
+    repo.run(&[
+        "mark",
+        "--fingerprint",
+        fingerprint,
+        "--verdict",
+        "approved",
+        "--quiet",
+    ])?;
+    Ok(())
+}
+
+#[test]
+fn test_check_requires_distinct_approvers() -> Result<()> {
+    let repo = TestRepo::new("reviewers_quorum")?;
+    repo.write("src/lib.rs", "pub fn core() {}\n")?;
+    repo.write("trueflow.toml", "[policy]\nrequired_approvals = 2\n")?;
+    repo.commit_all("Initial")?;
+
+    repo.git(&["checkout", "-b", "feature/quorum"])?;
+    repo.write("src/lib.rs", "pub fn core() {}\npub fn helper() {}\n")?;
+    repo.commit_all("Add helper")?;
+
+    let changes = json_array(&repo.run(&["diff", "--json"])?)?;
+    let fingerprint = changes[0]["fingerprint"]
+        .as_str()
+        .context("fingerprint")?
+        .to_string();
+
+    // One approval is not enough, even when the same reviewer repeats it.
+    approve(&repo, &fingerprint)?;
+    approve(&repo, &fingerprint)?;
+    assert!(!repo.run_raw(&["check"])?.status.success());
+    assert!(
+        repo.run_raw(&["check", "--required-approvals", "1"])?
+            .status
+            .success()
+    );
+
+    repo.git(&["config", "user.email", "second@example.com"])?;
+    approve(&repo, &fingerprint)?;
+    assert!(repo.run_raw(&["check"])?.status.success());
+
+    // Per-reviewer state: each identity only sees its own verdicts.
+    assert!(
+        repo.run_raw(&["check", "--reviewer", "test@example.com"])?
+            .status
+            .success()
+    );
+    assert!(
+        !repo
+            .run_raw(&["check", "--reviewer", "third@example.com"])?
+            .status
+            .success()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_review_reviewer_shows_own_queue() -> Result<()> {
+    let repo = TestRepo::new("reviewers_queue")?;
+    repo.write("src/lib.rs", "pub fn core() {}\n")?;
+    repo.commit_all("Initial")?;
+
+    let hash = first_block_hash(&repo.run(&["review", "--all", "--json"])?)?;
+    approve(&repo, &hash)?;
+    assert!(json_array(&repo.run(&["review", "--all", "--json"])?)?.is_empty());
+
+    let output = repo.run(&[
+        "review",
+        "--all",
+        "--json",
+        "--reviewer",
+        "second@example.com",
+    ])?;
+    assert_eq!(first_block_hash(&output)?, hash);
+
+    // With per_reviewer, the default queue is the current identity's own.
+    repo.write("trueflow.toml", "[policy]\nper_reviewer = true\n")?;
+    repo.git(&["config", "user.email", "second@example.com"])?;
+    let output = repo.run(&["review", "--all", "--json"])?;
+    assert_eq!(first_block_hash(&output)?, hash);
+
+    Ok(())
+}
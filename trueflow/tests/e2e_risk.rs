@@ -0,0 +1,75 @@
+use anyhow::Result;
+use serde_json::Value;
+
+mod common;
+use common::{TestRepo, json_array, run_git_output};
+
+const CHECK_V1: &str = "pub fn check(x: i32) -> bool {\n    if x > 0 {\n        for i in 0..x {\n            if i == 3 {\n                return true;\n            }\n        }\n    }\n    false\n}\n";
+const CHECK_V2: &str = "pub fn check(x: i32) -> bool {\n    if x > 1 {\n        for i in 0..x {\n            if i == 3 {\n                return true;\n            }\n        }\n    }\n    false\n}\n";
+
+fn risk_of(files: &[Value], path: &str) -> Value {
+    files
+        .iter()
+        .find(|file| file["path"] == path)
+        .and_then(|file| file["blocks"].as_array())
+        .and_then(|blocks| blocks.iter().find(|block| block["kind"] == "function"))
+        .map(|block| block["risk"].clone())
+        .expect("function block")
+}
+
+#[test]
+fn test_scan_and_review_score_risk() -> Result<()> {
+    let repo = TestRepo::new("risk_scores")?;
+    repo.write("trueflow.toml", "[risk.paths]\n\"src/auth/**\" = 30\n")?;
+    repo.write("src/auth/login.rs", CHECK_V1)?;
+    repo.write("src/lib.rs", "pub fn core() {}\n")?;
+    repo.commit_all("Add check")?;
+    repo.write("src/auth/login.rs", CHECK_V2)?;
+    repo.commit_all("Tighten check")?;
+
+    let files = json_array(&repo.run(&["scan", "--json", "--churn"])?)?;
+    let risky = risk_of(&files, "src/auth/login.rs");
+    let complexity = risky["complexity"].as_u64().expect("complexity");
+    assert!(complexity > 0, "{risky}");
+    assert_eq!(risky["churn"], 20, "{risky}");
+    assert_eq!(risky["path"], 30, "{risky}");
+    assert_eq!(risky["score"], complexity + 50, "{risky}");
+
+    let plain = risk_of(&files, "src/lib.rs");
+    assert_eq!(plain["path"], 0, "{plain}");
+    assert_eq!(plain["churn"], 10, "{plain}");
+
+    let files = json_array(&repo.run(&["review", "--all", "--json"])?)?;
+    assert_eq!(risk_of(&files, "src/auth/login.rs"), risky);
+    Ok(())
+}
+
+#[test]
+fn test_scan_blames_only_for_churn() -> Result<()> {
+    let repo = TestRepo::new("risk_no_blame")?;
+    repo.write("src/lib.rs", CHECK_V1)?;
+    repo.commit_all("Add check")?;
+    // Blaming anything now fails, and says so.
+    let blob = run_git_output(&repo.path, &["rev-parse", "HEAD:src/lib.rs"])?;
+    let blob = blob.trim();
+    std::fs::remove_file(
+        repo.path
+            .join(".git/objects")
+            .join(&blob[..2])
+            .join(&blob[2..]),
+    )?;
+
+    let output = repo.run_raw(&["--logging-mode", "stderr", "scan", "--json"])?;
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr)?;
+    assert!(!stderr.contains("Skipping churn"), "{stderr}");
+    let files = json_array(&String::from_utf8(output.stdout)?)?;
+    let risk = risk_of(&files, "src/lib.rs");
+    assert_eq!(risk["churn"], 0, "{risk}");
+    assert!(risk["complexity"].as_u64() > Some(0), "{risk}");
+
+    let output = repo.run_raw(&["--logging-mode", "stderr", "scan", "--json", "--churn"])?;
+    let stderr = String::from_utf8(output.stderr)?;
+    assert!(stderr.contains("Skipping churn for src/lib.rs"), "{stderr}");
+    Ok(())
+}
@@ -0,0 +1,253 @@
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::process::{Command, Output};
+use std::sync::{Arc, Mutex};
+use tiny_http::{Method, Response, Server};
+
+mod common;
+use common::{TestRepo, json};
+
+/// Just enough of S3 for the store: ListObjectsV2 two keys a page, and
+/// object PUT and GET under `/bucket/`.
+#[derive(Default)]
+struct Bucket {
+    objects: BTreeMap<String, String>,
+    object_gets: usize,
+    object_puts: usize,
+    authorizations: Vec<String>,
+}
+
+fn start_bucket() -> Result<(String, Arc<Mutex<Bucket>>)> {
+    let server = Server::http("127.0.0.1:0").map_err(|err| anyhow::anyhow!("{err}"))?;
+    let addr = server
+        .server_addr()
+        .to_ip()
+        .expect("tcp address")
+        .to_string();
+    let bucket = Arc::new(Mutex::new(Bucket::default()));
+    let shared = bucket.clone();
+    std::thread::spawn(move || {
+        for mut request in server.incoming_requests() {
+            let mut state = shared.lock().expect("bucket lock");
+            let auth = request
+                .headers()
+                .iter()
+                .filter(|header| {
+                    header.field.equiv("Authorization")
+                        || header.field.equiv("x-amz-security-token")
+                })
+                .map(|header| header.value.to_string())
+                .collect::<Vec<_>>()
+                .join(" ");
+            state.authorizations.push(auth);
+            let url = request.url().to_string();
+            let response = if let Some(query) = url.strip_prefix("/bucket?") {
+                let params: BTreeMap<String, String> =
+                    url::form_urlencoded::parse(query.as_bytes())
+                        .into_owned()
+                        .collect();
+                let prefix = params.get("prefix").cloned().unwrap_or_default();
+                let after = params
+                    .get("continuation-token")
+                    .cloned()
+                    .unwrap_or_default();
+                let keys: Vec<&String> = state
+                    .objects
+                    .keys()
+                    .filter(|key| key.starts_with(&prefix) && **key > after)
+                    .collect();
+                let mut xml = String::from("<ListBucketResult>");
+                for key in keys.iter().take(2) {
+                    xml.push_str(&format!(
+                        "<Contents><Key>{key}</Key><ETag>&quot;{}&quot;</ETag></Contents>",
+                        etag(&state.objects[*key])
+                    ));
+                }
+                if keys.len() > 2 {
+                    xml.push_str(&format!(
+                        "<NextContinuationToken>{}</NextContinuationToken>",
+                        keys[1]
+                    ));
+                }
+                xml.push_str("</ListBucketResult>");
+                Response::from_string(xml)
+            } else if let Some(key) = url.strip_prefix("/bucket/") {
+                let key = key.to_string();
+                match request.method() {
+                    Method::Put => {
+                        let mut body = String::new();
+                        let _ = request.as_reader().read_to_string(&mut body);
+                        state.objects.insert(key, body);
+                        state.object_puts += 1;
+                        Response::from_string("")
+                    }
+                    _ => {
+                        state.object_gets += 1;
+                        match state.objects.get(&key) {
+                            Some(body) => Response::from_string(body.clone()),
+                            None => Response::from_string("NoSuchKey").with_status_code(404),
+                        }
+                    }
+                }
+            } else {
+                Response::from_string("").with_status_code(400)
+            };
+            drop(state);
+            let _ = request.respond(response);
+        }
+    });
+    Ok((format!("http://{addr}/bucket"), bucket))
+}
+
+fn etag(body: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    body.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn repo_on_bucket(name: &str, bucket_url: &str) -> Result<TestRepo> {
+    let repo = TestRepo::new(name)?;
+    repo.write(
+        "trueflow.toml",
+        &format!("[store]\nbackend = \"s3\"\nurl = \"{bucket_url}\"\nprefix = \"team/\"\n"),
+    )?;
+    Ok(repo)
+}
+
+fn run(repo: &TestRepo, args: &[&str], stdin: Option<&str>) -> Result<Output> {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_trueflow"));
+    command
+        .args(args)
+        .current_dir(&repo.path)
+        .env("AWS_ACCESS_KEY_ID", "AKIDEXAMPLE")
+        .env("AWS_SECRET_ACCESS_KEY", "secret")
+        .env("AWS_SESSION_TOKEN", "session-token");
+    let output = match stdin {
+        Some(input) => {
+            use std::io::Write;
+            let mut child = command
+                .stdin(std::process::Stdio::piped())
+                .stdout(std::process::Stdio::piped())
+                .stderr(std::process::Stdio::piped())
+                .spawn()?;
+            child
+                .stdin
+                .take()
+                .expect("stdin")
+                .write_all(input.as_bytes())?;
+            child.wait_with_output()?
+        }
+        None => command.output()?,
+    };
+    anyhow::ensure!(
+        output.status.success(),
+        "trueflow failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    Ok(output)
+}
+
+#[test]
+fn test_s3_store_shares_per_record_objects() -> Result<()> {
+    let (bucket_url, bucket) = start_bucket()?;
+    let alice = repo_on_bucket("s3_store_alice", &bucket_url)?;
+    let bob = repo_on_bucket("s3_store_bob", &bucket_url)?;
+
+    run(&alice, &["mark", "--fingerprint", "aaaa"], None)?;
+    let batch =
+        "{\"fingerprint\": \"bbbb\"}\n{\"fingerprint\": \"cccc\", \"verdict\": \"rejected\"}\n";
+    run(&bob, &["mark", "--stdin", "--quiet"], Some(batch))?;
+    run(&bob, &["mark", "--fingerprint", "dddd"], None)?;
+
+    {
+        let state = bucket.lock().expect("bucket lock");
+        assert_eq!(state.objects.len(), 4);
+        assert!(state.objects.keys().all(|key| key.starts_with("team/")));
+        let auth = state.authorizations.last().cloned().unwrap_or_default();
+        assert!(auth.contains("AWS4-HMAC-SHA256"), "{auth}");
+        assert!(auth.contains("session-token"), "{auth}");
+    }
+
+    // Listing pages through all four objects.
+    let bundle = json(&String::from_utf8(run(&alice, &["export"], None)?.stdout)?)?;
+    let mut fingerprints: Vec<&str> = bundle["records"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|record| record["fingerprint"].as_str())
+        .collect();
+    fingerprints.sort();
+    assert_eq!(fingerprints, ["aaaa", "bbbb", "cccc", "dddd"]);
+
+    // A second read is served from the local cache.
+    let gets = bucket.lock().expect("bucket lock").object_gets;
+    run(&alice, &["export"], None)?;
+    assert_eq!(bucket.lock().expect("bucket lock").object_gets, gets);
+    let cached = std::fs::read_dir(alice.path.join(".trueflow/s3-cache"))?.count();
+    assert_eq!(cached, 4);
+
+    Ok(())
+}
+
+#[test]
+fn test_s3_store_migrate_only_puts_changed_records() -> Result<()> {
+    let (bucket_url, bucket) = start_bucket()?;
+    let alice = repo_on_bucket("s3_store_migrate_alice", &bucket_url)?;
+    let bob = repo_on_bucket("s3_store_migrate_bob", &bucket_url)?;
+
+    run(&alice, &["mark", "--fingerprint", "aaaa"], None)?;
+    run(&bob, &["mark", "--fingerprint", "bbbb"], None)?;
+    let old_key = {
+        let mut state = bucket.lock().expect("bucket lock");
+        let (key, body) = state
+            .objects
+            .iter()
+            .find(|(_, body)| body.contains("aaaa"))
+            .map(|(key, body)| (key.clone(), body.clone()))
+            .expect("alice's object");
+        state
+            .objects
+            .insert(key.clone(), body.replace("\"version\":2", "\"version\":1"));
+        state.object_puts = 0;
+        key
+    };
+
+    run(&alice, &["store", "migrate"], None)?;
+
+    let state = bucket.lock().expect("bucket lock");
+    assert_eq!(state.objects.len(), 2);
+    assert_eq!(state.object_puts, 1);
+    assert!(state.objects[&old_key].contains("\"version\":2"));
+    Ok(())
+}
+
+#[test]
+fn test_s3_store_skips_objects_that_are_not_records() -> Result<()> {
+    let (bucket_url, bucket) = start_bucket()?;
+    let alice = repo_on_bucket("s3_store_bad_object", &bucket_url)?;
+    run(&alice, &["mark", "--fingerprint", "aaaa"], None)?;
+    run(&alice, &["mark", "--fingerprint", "bbbb"], None)?;
+    bucket
+        .lock()
+        .expect("bucket lock")
+        .objects
+        .insert("team/000000000000-bad.json".to_string(), "{not json".into());
+
+    let output = run(&alice, &["--logging-mode", "stderr", "export"], None)?;
+
+    let bundle = json(&String::from_utf8(output.stdout)?)?;
+    let mut fingerprints: Vec<&str> = bundle["records"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|record| record["fingerprint"].as_str())
+        .collect();
+    fingerprints.sort();
+    assert_eq!(fingerprints, ["aaaa", "bbbb"]);
+    let stderr = String::from_utf8(output.stderr)?;
+    assert!(stderr.contains("team/000000000000-bad.json"), "{stderr}");
+
+    Ok(())
+}
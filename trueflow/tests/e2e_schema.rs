@@ -0,0 +1,59 @@
+use anyhow::Result;
+use serde_json::Value;
+
+mod common;
+use common::*;
+use trueflow_core::record_schema::validate;
+
+fn assert_matches_schema(repo: &TestRepo, schema: &str, output: &str) -> Result<()> {
+    let schema = json(&repo.run(&["schema", schema])?)?;
+    let value: Value = serde_json::from_str(output)?;
+    let problems = validate(&schema, &value);
+    assert!(problems.is_empty(), "{problems:#?}\n{output}");
+    Ok(())
+}
+
+#[test]
+fn test_command_output_matches_published_schemas() -> Result<()> {
+    let repo = TestRepo::new("schema_outputs")?;
+    repo.write("src/lib.rs", "pub fn one() -> u8 {\n    1\n}\n")?;
+    repo.commit_all("Initial")?;
+    let hash = first_block_hash(&repo.run(&["review", "--all", "--json"])?)?;
+    repo.run(&[
+        "mark",
+        "--fingerprint",
+        &hash,
+        "--verdict",
+        "rejected",
+        "--note",
+        "Return a constant instead",
+        "--path",
+        "src/lib.rs",
+        "--quiet",
+    ])?;
+    let feedback = repo.run(&["feedback", "--format", "json"])?;
+    assert!(feedback.contains("Return a constant instead"), "{feedback}");
+    assert_matches_schema(&repo, "feedback", &feedback)?;
+
+    repo.write("src/lib.rs", "pub fn one() -> u8 {\n    2\n}\n")?;
+    assert_matches_schema(&repo, "review", &repo.run(&["review", "--all", "--json"])?)?;
+    assert_matches_schema(&repo, "scan", &repo.run(&["scan", "--json"])?)?;
+    assert_matches_schema(&repo, "diff", &repo.run(&["diff", "--json"])?)?;
+    assert_matches_schema(
+        &repo,
+        "diff-blocks",
+        &repo.run(&["diff", "--blocks", "--json"])?,
+    )?;
+    Ok(())
+}
+
+#[test]
+fn test_schemas_are_versioned() -> Result<()> {
+    let repo = TestRepo::new("schema_list")?;
+    let list = repo.run(&["schema"])?;
+    assert!(list.contains("record (v2)"), "{list}");
+    assert!(list.contains("review (v1)"), "{list}");
+    let schema = json(&repo.run(&["schema", "record"])?)?;
+    assert_eq!(schema["$id"], "urn:trueflow:schema:record:v2");
+    Ok(())
+}
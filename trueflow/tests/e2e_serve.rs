@@ -0,0 +1,291 @@
+use anyhow::{Context, Result, bail};
+use serde_json::Value;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::process::{Child, Command, Stdio};
+
+mod common;
+use common::{ReviewRecordOverrides, TestRepo, build_review_record, first_block_hash};
+
+/// A `trueflow serve` process, killed on drop.
+struct Server {
+    child: Child,
+    addr: String,
+    token: String,
+}
+
+impl Server {
+    /// Starts without a configured token, so the server generates one.
+    fn start(repo: &TestRepo) -> Result<Self> {
+        let mut child = spawn_serve(repo, &[])?;
+        let mut stdout = BufReader::new(child.stdout.take().context("stdout")?);
+        let addr = banner(&mut stdout, "Listening on http://")?;
+        let token = banner(&mut stdout, "Token: ")?;
+        Ok(Self { child, addr, token })
+    }
+
+    /// Starts with `token` in the environment variable `token_env`.
+    fn start_with_token(repo: &TestRepo, token_env: &str, token: &str) -> Result<Self> {
+        let mut child = spawn_serve(repo, &[(token_env, token)])?;
+        let mut stdout = BufReader::new(child.stdout.take().context("stdout")?);
+        let addr = banner(&mut stdout, "Listening on http://")?;
+        Ok(Self {
+            child,
+            addr,
+            token: token.to_string(),
+        })
+    }
+
+    /// A request from localhost carrying the server's token.
+    fn request(&self, method: &str, path: &str, body: &str) -> Result<(u16, Value)> {
+        let headers = format!(
+            "Host: localhost\r\nAuthorization: Bearer {}\r\n",
+            self.token
+        );
+        self.request_with(method, path, &headers, body)
+    }
+
+    /// `headers` are the `Name: value\r\n` lines to send, `Host` included.
+    fn request_with(
+        &self,
+        method: &str,
+        path: &str,
+        headers: &str,
+        body: &str,
+    ) -> Result<(u16, Value)> {
+        let mut stream = TcpStream::connect(&self.addr)?;
+        write!(
+            stream,
+            "{method} {path} HTTP/1.1\r\nConnection: close\r\n{headers}Content-Length: {}\r\n\r\n{body}",
+            body.len()
+        )?;
+        let mut response = String::new();
+        stream.read_to_string(&mut response)?;
+        let (head, body) = response
+            .split_once("\r\n\r\n")
+            .context("malformed response")?;
+        let status = head.split_whitespace().nth(1).context("status")?.parse()?;
+        if !head.contains("application/json") {
+            bail!("not JSON: {head}");
+        }
+        Ok((status, serde_json::from_str(body)?))
+    }
+}
+
+fn spawn_serve(repo: &TestRepo, env: &[(&str, &str)]) -> Result<Child> {
+    Ok(Command::new(env!("CARGO_BIN_EXE_trueflow"))
+        .args(["serve", "--port", "0"])
+        .envs(env.iter().copied())
+        .env_remove("TRUEFLOW_STORE_TOKEN")
+        .current_dir(&repo.path)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?)
+}
+
+/// The rest of the next stdout line, which must start with `prefix`.
+fn banner(stdout: &mut impl BufRead, prefix: &str) -> Result<String> {
+    let mut line = String::new();
+    stdout.read_line(&mut line)?;
+    Ok(line
+        .trim()
+        .strip_prefix(prefix)
+        .with_context(|| format!("unexpected banner: {line}"))?
+        .to_string())
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[test]
+fn test_serve_reviews_and_marks_over_http() -> Result<()> {
+    let repo = TestRepo::new("serve_api")?;
+    repo.write("src/lib.rs", "pub fn core() {}\n")?;
+    repo.commit_all("Initial")?;
+    let hash = first_block_hash(&repo.run(&["review", "--all", "--json"])?)?;
+
+    let server = Server::start(&repo)?;
+
+    let (status, files) = server.request("GET", "/review?all", "")?;
+    assert_eq!(status, 200);
+    assert_eq!(files[0]["path"], "src/lib.rs");
+
+    let (status, block) = server.request("GET", &format!("/blocks/{}", &hash[..12]), "")?;
+    assert_eq!(status, 200, "{block}");
+    assert_eq!(block["path"], "src/lib.rs");
+    assert_eq!(block["block"]["hash"], hash.as_str());
+    assert!(block["history"].as_array().context("history")?.is_empty());
+
+    let entry = format!(r#"{{"fingerprint": "{hash}", "note": "Looks fine"}}"#);
+    let (status, marked) = server.request("POST", "/mark", &entry)?;
+    assert_eq!(status, 200, "{marked}");
+    assert_eq!(marked["ids"].as_array().context("ids")?.len(), 1);
+
+    // The write invalidates the cached queue.
+    let (_, files) = server.request("GET", "/review?all", "")?;
+    assert!(files.as_array().context("files")?.is_empty(), "{files}");
+    let (_, block) = server.request("GET", &format!("/blocks/{hash}"), "")?;
+    assert_eq!(block["history"][0]["note"], "Looks fine");
+
+    let (status, stats) = server.request("GET", "/stats", "")?;
+    assert_eq!(status, 200);
+    assert_eq!(stats["total"]["approved"], 1);
+
+    let (status, error) = server.request("POST", "/mark", r#"{"verdict": "approved"}"#)?;
+    assert_eq!(status, 400);
+    assert!(error["error"].as_str().is_some());
+    assert_eq!(server.request("GET", "/blocks/ffffffff", "")?.0, 404);
+    assert_eq!(server.request("GET", "/nope", "")?.0, 404);
+    assert_eq!(server.request("DELETE", "/review", "")?.0, 405);
+
+    Ok(())
+}
+
+#[test]
+fn test_http_store_backend_shares_the_served_ledger() -> Result<()> {
+    let server_repo = TestRepo::new("serve_store_server")?;
+    let server = Server::start(&server_repo)?;
+
+    let client = TestRepo::new("serve_store_client")?;
+    client.write("src/lib.rs", "pub fn core() {}\n")?;
+    client.write(
+        "trueflow.toml",
+        &format!(
+            "[store]\nbackend = \"http\"\nurl = \"http://{}/\"\n",
+            server.addr
+        ),
+    )?;
+    client.commit_all("Initial")?;
+    let token = [("TRUEFLOW_STORE_TOKEN", server.token.as_str())];
+    let hash = first_block_hash(&client.run_with_env(&["review", "--all", "--json"], &token)?)?;
+    client.run_with_env(
+        &["mark", "--fingerprint", &hash, "--path", "src/lib.rs"],
+        &token,
+    )?;
+
+    let (status, records) = server.request("GET", "/records", "")?;
+    assert_eq!(status, 200);
+    assert_eq!(records[0]["fingerprint"], hash.as_str());
+    assert!(server_repo.path.join(".trueflow/reviews.jsonl").exists());
+    assert!(!client.path.join(".trueflow/reviews.jsonl").exists());
+
+    // Reads go through the server too.
+    let remaining =
+        client.run_with_env(&["review", "--json", "--target", "file:src/lib.rs"], &token)?;
+    assert_eq!(remaining.trim(), "[]");
+
+    let (status, _) = server.request("POST", "/records", "{}")?;
+    assert_eq!(status, 400);
+    assert_eq!(server.request("DELETE", "/records", "")?.0, 405);
+    // The ledger is append-only over HTTP.
+    assert_eq!(server.request("PUT", "/records", "[]")?.0, 405);
+
+    Ok(())
+}
+
+#[test]
+fn test_serve_requires_the_store_token_when_set() -> Result<()> {
+    let repo = TestRepo::new("serve_token")?;
+    repo.write(
+        "trueflow.toml",
+        "[store]\ntoken_env = \"SERVE_TEST_TOKEN\"\n",
+    )?;
+    let server = Server::start_with_token(&repo, "SERVE_TEST_TOKEN", "s3cret")?;
+
+    let local = "Host: localhost\r\n";
+    let (status, error) = server.request_with("GET", "/records", local, "")?;
+    assert_eq!(status, 401, "{error}");
+    let wrong = "Host: localhost\r\nAuthorization: Bearer guess\r\n";
+    assert_eq!(server.request_with("GET", "/records", wrong, "")?.0, 401);
+    let (status, records) = server.request("GET", "/records", "")?;
+    assert_eq!(status, 200, "{records}");
+    assert_eq!(server.request("POST", "/records", "[]")?.0, 200);
+
+    Ok(())
+}
+
+#[test]
+fn test_serve_generates_a_token_and_refuses_other_origins() -> Result<()> {
+    let repo = TestRepo::new("serve_origin")?;
+    let server = Server::start(&repo)?;
+    assert_eq!(server.token.len(), 32);
+
+    // Without a configured token, writes still need the generated one.
+    let entry = r#"{"fingerprint": "deadbeef"}"#;
+    let local = "Host: localhost\r\n";
+    assert_eq!(server.request_with("POST", "/mark", local, entry)?.0, 401);
+
+    // A page elsewhere can't use the token-less browser route, nor a
+    // rebound DNS name, even with the token.
+    let auth = format!("Authorization: Bearer {}\r\n", server.token);
+    let rebound = format!("Host: reviews.evil.example:7890\r\n{auth}");
+    let (status, error) = server.request_with("POST", "/mark", &rebound, entry)?;
+    assert_eq!(status, 403, "{error}");
+    let cross_site = format!("Host: 127.0.0.1\r\nOrigin: https://evil.example\r\n{auth}");
+    assert_eq!(
+        server.request_with("POST", "/mark", &cross_site, entry)?.0,
+        403
+    );
+    let same_site = format!("Host: 127.0.0.1\r\nOrigin: http://localhost:7890\r\n{auth}");
+    assert_eq!(
+        server.request_with("POST", "/mark", &same_site, entry)?.0,
+        200
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_serve_appends_only_valid_records_by_its_own_identity() -> Result<()> {
+    let repo = TestRepo::new("serve_append_checks")?;
+    let server = Server::start(&repo)?;
+    let record = |id, email| {
+        build_review_record(
+            "deadbeef",
+            ReviewRecordOverrides {
+                id: Some(id),
+                email: Some(email),
+                ..Default::default()
+            },
+        )
+    };
+
+    // Records naming someone other than the server's identity need a
+    // trusted signature.
+    let forged = serde_json::json!([record("forged", "boss@example.com")]);
+    let (status, error) = server.request("POST", "/records", &forged.to_string())?;
+    assert_eq!(status, 403, "{error}");
+    assert!(
+        error["error"]
+            .as_str()
+            .is_some_and(|e| e.contains("boss@example.com"))
+    );
+
+    let mut unknown = record("unknown", "test@example.com");
+    unknown["surprise"] = true.into();
+    let mut bad_email = record("bad-email", "test@example.com");
+    bad_email["identity"]["email"] = "nobody".into();
+    for invalid in [unknown, bad_email] {
+        let body = serde_json::json!([invalid]).to_string();
+        let (status, error) = server.request("POST", "/records", &body)?;
+        assert_eq!(status, 400, "{error}");
+    }
+
+    let own = serde_json::json!([record("own", "test@example.com")]);
+    let (status, reply) = server.request("POST", "/records", &own.to_string())?;
+    assert_eq!(status, 200, "{reply}");
+    let (_, records) = server.request("GET", "/records", "")?;
+    let ids: Vec<&str> = records
+        .as_array()
+        .context("records")?
+        .iter()
+        .filter_map(|record| record["id"].as_str())
+        .collect();
+    assert_eq!(ids, ["own"]);
+
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use anyhow::Result;
+
+mod common;
+use common::{TestRepo, first_block_hash, json};
+
+#[test]
+fn test_stats_reports_coverage() -> Result<()> {
+    let repo = TestRepo::new("stats_coverage")?;
+    repo.write("src/lib.rs", "pub fn core() {}\n")?;
+    repo.write("docs/notes.py", "def helper():\n    return 1\n")?;
+    repo.commit_all("Add files")?;
+
+    let output = repo.run(&["review", "--target", "file:src/lib.rs", "--json"])?;
+    let hash = first_block_hash(&output)?;
+    repo.run(&["mark", "--fingerprint", &hash, "--verdict", "approved"])?;
+    let output = repo.run(&["review", "--target", "file:docs/notes.py", "--json"])?;
+    let hash = first_block_hash(&output)?;
+    repo.run(&["mark", "--fingerprint", &hash, "--verdict", "question"])?;
+
+    let output = repo.run(&["stats", "--json", "--badge", "badge.svg"])?;
+    let stats = json(&output)?;
+    let directories = stats["directories"].as_array().cloned().unwrap_or_default();
+    let src = directories
+        .iter()
+        .find(|row| row["name"] == "src")
+        .expect("src row");
+    assert_eq!(src["blocks"], src["approved"]);
+    assert_eq!(src["reviewed_percent"], 100.0);
+    let docs = directories
+        .iter()
+        .find(|row| row["name"] == "docs")
+        .expect("docs row");
+    assert_eq!(docs["questions"], 1);
+    assert!(docs["oldest_unreviewed_age_seconds"].is_i64(), "{docs}");
+    assert!(stats["total"]["reviewed_percent"].as_f64() < Some(100.0));
+    let languages = stats["languages"].as_array().cloned().unwrap_or_default();
+    assert!(languages.iter().any(|row| row["name"] == "Python"));
+
+    let badge = std::fs::read_to_string(repo.path.join("badge.svg"))?;
+    assert!(badge.starts_with("<svg"), "{badge}");
+    assert!(badge.contains("reviewed"), "{badge}");
+
+    let output = repo.run(&["stats"])?;
+    assert!(output.contains("Directory"), "{output}");
+    assert!(output.contains("Oldest unreviewed block"), "{output}");
+    Ok(())
+}
@@ -1,9 +1,9 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use std::fs;
 
 mod common;
 use common::*;
-use trueflow::store::{BlockState, RepoRef, VcsSystem};
+use trueflow_core::store::{BlockState, RepoRef, VcsSystem};
 
 #[test]
 fn test_review_skips_invalid_db_lines() -> Result<()> {
@@ -47,3 +47,49 @@ fn test_review_skips_invalid_db_lines() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_sqlite_backend_imports_jsonl_ledger() -> Result<()> {
+    // GIVEN: an existing JSONL ledger
+    let repo = TestRepo::new("sqlite_import")?;
+    let trueflow_dir = repo.path.join(".trueflow");
+    write_reviews_jsonl(
+        &trueflow_dir,
+        &[
+            build_review_record("aaaa1111", ReviewRecordOverrides::default()),
+            build_review_record(
+                "bbbb2222",
+                ReviewRecordOverrides {
+                    verdict: Some("rejected"),
+                    ..ReviewRecordOverrides::default()
+                },
+            ),
+        ],
+    )?;
+
+    // WHEN: the SQLite backend is switched on
+    repo.write("trueflow.toml", "[store]\nbackend = \"sqlite\"\n")?;
+    let history = json_array(&repo.run(&["history", "--json"])?)?;
+
+    // THEN: the records were imported
+    assert_eq!(history.len(), 2);
+    assert!(trueflow_dir.join("reviews.db").exists());
+
+    // AND: new verdicts go to the database, not the JSONL file
+    repo.run(&["mark", "--fingerprint", "aaaa3333", "--quiet"])?;
+    let by_prefix = json_array(&repo.run(&["history", "--fingerprint", "aaaa", "--json"])?)?;
+    let fingerprints: Vec<&str> = by_prefix
+        .iter()
+        .map(|record| record["fingerprint"].as_str().context("fingerprint"))
+        .collect::<Result<_>>()?;
+    assert_eq!(fingerprints, ["aaaa3333", "aaaa1111"]);
+    assert_eq!(
+        read_review_records(&trueflow_dir.join("reviews.jsonl"))?.len(),
+        2
+    );
+
+    // AND: a second run does not import the JSONL ledger again
+    let history = json_array(&repo.run(&["history", "--json"])?)?;
+    assert_eq!(history.len(), 3);
+    Ok(())
+}
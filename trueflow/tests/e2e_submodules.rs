@@ -0,0 +1,127 @@
+use anyhow::Result;
+use serde_json::Value;
+
+mod common;
+use common::{TestRepo, json_array, run_git, run_git_output};
+
+fn blocks_by_path(output: &str) -> Result<Vec<(String, Value)>> {
+    Ok(json_array(output)?
+        .into_iter()
+        .map(|file| (file["path"].as_str().unwrap_or("").to_string(), file))
+        .collect())
+}
+
+fn paths(output: &str) -> Result<Vec<String>> {
+    Ok(blocks_by_path(output)?
+        .into_iter()
+        .map(|(path, _)| path)
+        .collect())
+}
+
+fn paths_of(repo: &TestRepo, args: &[&str]) -> Result<Vec<String>> {
+    paths(&repo.run(args)?)
+}
+
+/// A repository with `vendor/lib` as a submodule; returns it and the
+/// submodule's source repository.
+fn repo_with_submodule(name: &str) -> Result<(TestRepo, TestRepo)> {
+    let library = TestRepo::new(&format!("{name}_lib"))?;
+    library.write("lib.rs", "pub fn vendored() {}\n")?;
+    library.commit_all("Library")?;
+
+    let repo = TestRepo::new(name)?;
+    repo.write("main.rs", "fn main() {}\n")?;
+    let source = library.path.to_string_lossy().into_owned();
+    repo.git(&[
+        "-c",
+        "protocol.file.allow=always",
+        "submodule",
+        "add",
+        "-q",
+        &source,
+        "vendor/lib",
+    ])?;
+    repo.commit_all("Initial")?;
+    Ok((repo, library))
+}
+
+#[test]
+fn test_submodules_are_reviewed_as_pointer_blocks() -> Result<()> {
+    let (repo, _library) = repo_with_submodule("submodule_pointer")?;
+    let submodule = repo.path.join("vendor/lib");
+    let commit = run_git_output(&submodule, &["rev-parse", "HEAD"])?;
+
+    let files = blocks_by_path(&repo.run(&["review", "--all", "--json"])?)?;
+    let paths: Vec<&str> = files.iter().map(|(path, _)| path.as_str()).collect();
+    assert_eq!(paths, ["main.rs", "vendor/lib"]);
+    let pointer = &files[1].1["blocks"][0];
+    assert_eq!(pointer["kind"], "submodule");
+    assert_eq!(pointer["hash"], commit.trim());
+
+    let recursed = paths_of(
+        &repo,
+        &["--recurse-submodules", "review", "--all", "--json"],
+    )?;
+    assert!(
+        recursed.contains(&"vendor/lib/lib.rs".to_string()),
+        "{recursed:?}"
+    );
+    assert!(
+        !recursed.contains(&"vendor/lib".to_string()),
+        "{recursed:?}"
+    );
+
+    repo.run(&[
+        "mark",
+        "--fingerprint",
+        commit.trim(),
+        "--path",
+        "vendor/lib",
+    ])?;
+    assert!(!paths_of(&repo, &["review", "--all", "--json"])?.contains(&"vendor/lib".to_string()));
+
+    // Moving the pointer makes the submodule dirty and unreviewed again.
+    std::fs::write(submodule.join("lib.rs"), "pub fn vendored() -> u8 { 1 }\n")?;
+    run_git(
+        &submodule,
+        &[
+            "-c",
+            "user.name=Test User",
+            "-c",
+            "user.email=test@example.com",
+            "commit",
+            "-qam",
+            "Bump",
+        ],
+    )?;
+    assert_eq!(paths_of(&repo, &["review", "--json"])?, ["vendor/lib"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_linked_worktree_keeps_its_own_store() -> Result<()> {
+    let repo = TestRepo::new("linked_worktree")?;
+    repo.write("src/lib.rs", "pub fn core() {}\n")?;
+    repo.commit_all("Initial")?;
+    let linked = repo.path.with_extension("linked");
+    repo.git(&["worktree", "add", "-q", &linked.to_string_lossy()])?;
+
+    let src = linked.join("src");
+    let output = repo.run_in(&["review", "--all", "--json"], &src)?;
+    assert_eq!(paths(&output)?, ["lib.rs"]);
+    let hash = json_array(&output)?[0]["blocks"][0]["hash"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string();
+    repo.run_in(
+        &["mark", "--fingerprint", &hash, "--path", "src/lib.rs"],
+        &src,
+    )?;
+
+    assert!(linked.join(".trueflow/reviews.jsonl").exists());
+    assert!(!src.join(".trueflow").exists());
+    assert!(!repo.path.join(".trueflow/reviews.jsonl").exists());
+
+    Ok(())
+}
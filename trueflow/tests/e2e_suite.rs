@@ -234,6 +234,124 @@ fn test_feedback_json_includes_non_review_check() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_feedback_markdown_groups_by_file() -> Result<()> {
+    let repo = TestRepo::fixture("empty")?;
+
+    repo.write("src/lib.rs", "fn core() { }")?;
+    repo.write("src/util.py", "def helper():\n    return 1\n")?;
+    repo.commit_all("Add files")?;
+
+    for (path, note) in [
+        ("src/lib.rs", "Needs optimization"),
+        ("src/util.py", "Name this better"),
+    ] {
+        let output = repo.run(&["review", "--target", &format!("file:{path}"), "--json"])?;
+        let hash = first_block_hash(&output)?;
+        repo.run(&[
+            "mark",
+            "--fingerprint",
+            &hash,
+            "--verdict",
+            "rejected",
+            "--note",
+            note,
+            "--quiet",
+        ])?;
+    }
+
+    let output = repo.run(&["feedback", "--format", "markdown"])?;
+    assert!(output.starts_with("# Review feedback"), "{output}");
+    assert!(output.contains("2 block(s) in 2 file(s)"), "{output}");
+    assert!(output.contains("## `src/lib.rs`"), "{output}");
+    assert!(output.contains("```rust\nfn core() { }\n```"), "{output}");
+    assert!(output.contains("```python\ndef helper():"), "{output}");
+    assert!(
+        output.contains("- **rejected** by test@example.com (`"),
+        "{output}"
+    );
+    assert!(output.contains("`): Needs optimization"), "{output}");
+    assert!(output.contains("## What to do"), "{output}");
+    assert!(
+        output.find("src/lib.rs") < output.find("src/util.py"),
+        "{output}"
+    );
+    assert_eq!(repo.run(&["feedback", "--format", "md"])?, output);
+
+    Ok(())
+}
+
+#[test]
+fn test_feedback_filters_by_author_check_since_and_verdict() -> Result<()> {
+    let repo = TestRepo::fixture("empty")?;
+
+    repo.write("src/a.rs", "fn a() { }")?;
+    repo.write("src/b.rs", "fn b() { }")?;
+    repo.write("src/c.rs", "fn c() { }")?;
+    repo.commit_all("Add files")?;
+    let hash = |path: &str| -> Result<String> {
+        first_block_hash(&repo.run(&["review", "--target", &format!("file:{path}"), "--json"])?)
+    };
+    let (a, b, c) = (hash("src/a.rs")?, hash("src/b.rs")?, hash("src/c.rs")?);
+    let mark = |hash: &str, verdict: &str, extra: &[&str]| -> Result<String> {
+        let mut args = vec![
+            "mark",
+            "--fingerprint",
+            hash,
+            "--verdict",
+            verdict,
+            "--quiet",
+        ];
+        args.extend_from_slice(extra);
+        repo.run(&args)
+    };
+    let old = build_review_record(
+        &a,
+        ReviewRecordOverrides {
+            verdict: Some("rejected"),
+            email: Some("old@example.com"),
+            timestamp: Some(1000),
+            ..Default::default()
+        },
+    );
+    write_reviews_jsonl(&repo.path.join(".trueflow"), &[old])?;
+    mark(&b, "question", &["--agent", "linter"])?;
+    mark(
+        &c,
+        "rejected",
+        &["--check", "security", "--note", "injection"],
+    )?;
+
+    let files = |args: &[&str]| -> Result<Vec<String>> {
+        let mut full = vec!["feedback", "--format", "json"];
+        full.extend_from_slice(args);
+        Ok(json_array(&repo.run(&full)?)?
+            .iter()
+            .map(|entry| entry["file"].as_str().unwrap_or_default().to_string())
+            .collect())
+    };
+    assert_eq!(files(&[])?, ["src/a.rs", "src/b.rs", "src/c.rs"]);
+    assert_eq!(files(&["--check", "security"])?, ["src/c.rs"]);
+    assert_eq!(files(&["--author", "agent:linter"])?, ["src/b.rs"]);
+    assert_eq!(files(&["--author", "old@example.com"])?, ["src/a.rs"]);
+    assert_eq!(files(&["--author", "old@example.com"])?, ["src/a.rs"]);
+    assert_eq!(files(&["--verdict", "question"])?, ["src/b.rs"]);
+    assert_eq!(
+        files(&["--verdict", "question", "--verdict", "rejected"])?.len(),
+        3
+    );
+    assert_eq!(files(&["--since", "1d"])?, ["src/b.rs", "src/c.rs"]);
+    assert_eq!(files(&["--since", "HEAD"])?, ["src/b.rs", "src/c.rs"]);
+
+    // A later approval by someone else makes the rejection moot.
+    mark(&a, "approved", &[])?;
+    assert_eq!(files(&["--author", "test@example.com"])?, ["src/c.rs"]);
+
+    let err = repo.run_err(&["feedback", "--since", "no-such-rev"])?;
+    assert!(err.contains("neither a time nor a revision"), "{err}");
+    Ok(())
+}
+
 #[test]
 fn test_half_reviewed_blocks() -> Result<()> {
     let repo = TestRepo::fixture("empty")?;
@@ -461,3 +579,275 @@ fn test_review_orders_imports_after_functions_in_lib() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_review_json_includes_block_metrics() -> Result<()> {
+    let repo = TestRepo::fixture("block_metrics")?;
+    repo.write("src/lib.rs", "pub fn alpha() {\n    beta();\n}\n")?;
+
+    let output = repo.run(&["review", "--all", "--json"])?;
+    let blocks = first_file_blocks(&output)?;
+    let block = blocks
+        .iter()
+        .find(|block| block["kind"] == "function")
+        .context("expected function block")?;
+    let content = block["content"].as_str().context("content")?;
+
+    assert_eq!(block["line_count"].as_u64(), Some(3));
+    assert_eq!(block["byte_count"].as_u64(), Some(content.len() as u64));
+    assert_eq!(
+        block["token_count"].as_u64(),
+        Some(content.len().div_ceil(4) as u64)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_review_excludes_generated_files_by_default() -> Result<()> {
+    let repo = TestRepo::fixture("generated_files")?;
+    repo.write("src/lib.rs", "pub fn handwritten() {}\n")?;
+    repo.write("src/proto.rs", "// @generated by protoc\npub struct Msg;\n")?;
+    repo.write("Cargo.lock", "[[package]]\nname = \"demo\"\n")?;
+    repo.write(".gitattributes", "vendor/** linguist-generated\n")?;
+    repo.write("vendor/dep.rs", "pub fn vendored() {}\n")?;
+
+    let scan = json_array(&repo.run(&["scan", "--json"])?)?;
+    let proto = scan
+        .iter()
+        .find(|file| file["path"] == "src/proto.rs")
+        .context("expected proto file in scan")?;
+    assert_eq!(proto["generated"], true);
+    assert!(
+        proto["blocks"][0]["tags"]
+            .as_array()
+            .is_some_and(|tags| tags.iter().any(|tag| tag == "generated"))
+    );
+
+    let files = json_array(&repo.run(&["review", "--all", "--json"])?)?;
+    let paths: Vec<&str> = files
+        .iter()
+        .filter_map(|file| file["path"].as_str())
+        .collect();
+    assert_eq!(paths, vec!["src/lib.rs"]);
+
+    repo.write("trueflow.toml", "[review]\ninclude_generated = true\n")?;
+    let files = json_array(&repo.run(&["review", "--all", "--json"])?)?;
+    let paths: Vec<&str> = files
+        .iter()
+        .filter_map(|file| file["path"].as_str())
+        .collect();
+    assert!(paths.contains(&"src/proto.rs"));
+    assert!(paths.contains(&"Cargo.lock"));
+    assert!(paths.contains(&"vendor/dep.rs"));
+
+    Ok(())
+}
+
+#[test]
+fn test_review_summary_counts_blocks_per_file() -> Result<()> {
+    let repo = TestRepo::new("review_summary")?;
+    repo.write(
+        "src/lib.rs",
+        "struct Alpha;\n\nfn beta() {}\n\nfn gamma() {}\n",
+    )?;
+    repo.write("README.md", "# Title\n")?;
+    repo.commit_all("Add files")?;
+
+    let output = repo.run(&["review", "--all", "--summary", "--exclude", "gap", "--json"])?;
+    let summary = json(&output)?;
+    let files = summary["files"].as_array().context("files")?;
+    let lib = files
+        .iter()
+        .find(|file| file["path"] == "src/lib.rs")
+        .context("lib summary")?;
+    assert_eq!(lib["blocks"], 3);
+    assert_eq!(lib["kinds"]["function"], 2);
+    assert_eq!(lib["kinds"]["struct"], 1);
+    assert_eq!(summary["total_files"], files.len());
+
+    let output = repo.run(&["review", "--all", "--summary", "--exclude", "gap"])?;
+    assert!(output.contains("function 2, struct 1"), "{output}");
+    assert!(output.contains("Total: "), "{output}");
+    assert!(!output.contains("Hash:"), "{output}");
+
+    Ok(())
+}
+
+#[test]
+fn test_review_format_flag() -> Result<()> {
+    let repo = TestRepo::new("review_format")?;
+    repo.write("src/lib.rs", "fn beta() {}\n")?;
+    repo.commit_all("Add lib")?;
+
+    let from_json = json_array(&repo.run(&["review", "--all", "--json"])?)?;
+    let from_format = json_array(&repo.run(&["review", "--all", "--format", "json"])?)?;
+    assert_eq!(from_json, from_format);
+
+    let yaml = repo.run(&["review", "--all", "--format", "yaml"])?;
+    let parsed: serde_json::Value = serde_yaml_ng::from_str(&yaml)?;
+    assert_eq!(parsed, serde_json::Value::Array(from_json));
+
+    let md = repo.run(&["review", "--all", "--format", "md"])?;
+    assert!(md.starts_with("## Unreviewed blocks"), "{md}");
+    assert!(md.contains("### `src/lib.rs`"), "{md}");
+    assert!(md.contains("| function |"), "{md}");
+
+    let md = repo.run(&["review", "--all", "--summary", "--format", "md"])?;
+    assert!(md.contains("| `src/lib.rs` |"), "{md}");
+
+    repo.run_err(&["review", "--all", "--json", "--format", "yaml"])?;
+
+    Ok(())
+}
+
+#[test]
+fn test_review_json_pages_through_queue() -> Result<()> {
+    let repo = TestRepo::new("review_pages")?;
+    repo.write("src/a.rs", "fn a1() {}\n\nfn a2() {}\n")?;
+    repo.write("src/b.rs", "fn b1() {}\n\nfn b2() {}\n")?;
+    repo.commit_all("Add files")?;
+
+    let hashes = |output: &str| -> Result<Vec<String>> {
+        Ok(json_array(output)?
+            .iter()
+            .flat_map(|file| file["blocks"].as_array().cloned().unwrap_or_default())
+            .filter_map(|block| block["hash"].as_str().map(str::to_string))
+            .collect())
+    };
+    let base = ["review", "--all", "--exclude", "gap", "--json"];
+    let everything = hashes(&repo.run(&base)?)?;
+    assert_eq!(everything.len(), 4);
+
+    let mut paged = Vec::new();
+    for offset in ["0", "3"] {
+        let mut args = base.to_vec();
+        args.extend(["--limit", "3", "--offset", offset]);
+        paged.extend(hashes(&repo.run(&args)?)?);
+    }
+    assert_eq!(paged, everything);
+
+    let mut args = base.to_vec();
+    args.extend(["--max-files", "1"]);
+    let files = json_array(&repo.run(&args)?)?;
+    assert_eq!(files.len(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_review_since_last_session_shows_only_new_blocks() -> Result<()> {
+    let repo = TestRepo::new("review_since_last")?;
+    repo.write("src/lib.rs", "fn old() {}\n")?;
+    repo.commit_all("Add lib")?;
+
+    let output = repo.run(&["review", "--all", "--exclude", "gap", "--json"])?;
+    let pending: Vec<String> = first_file_blocks(&output)?
+        .iter()
+        .filter_map(|block| block["hash"].as_str().map(str::to_string))
+        .collect();
+    // What the TUI writes when a session ends.
+    std::fs::create_dir_all(repo.path.join(".trueflow"))?;
+    std::fs::write(
+        repo.path.join(".trueflow/last_session.json"),
+        serde_json::json!({ "ended_at": 0, "unreviewed": pending }).to_string(),
+    )?;
+
+    repo.write("src/lib.rs", "fn old() {}\n\nfn new() {}\n")?;
+    repo.commit_all("Add new")?;
+
+    let args = [
+        "review",
+        "--all",
+        "--exclude",
+        "gap",
+        "--since-last",
+        "--json",
+    ];
+    let blocks = first_file_blocks(&repo.run(&args)?)?;
+    assert_eq!(blocks.len(), 1);
+    assert!(
+        blocks[0]["content"]
+            .as_str()
+            .is_some_and(|content| content.contains("fn new"))
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_review_count_prints_number_and_sets_exit_code() -> Result<()> {
+    let repo = TestRepo::new("review_count")?;
+    repo.write("src/lib.rs", "fn a() {}\n\nfn b() {}\n")?;
+    repo.commit_all("Add lib")?;
+
+    let output = repo.run_raw(&["review", "--all", "--exclude", "gap", "--count"])?;
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(String::from_utf8(output.stdout)?, "2\n");
+
+    let output = repo.run_raw(&["review", "--all", "--only", "struct", "--count"])?;
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout)?, "0\n");
+
+    Ok(())
+}
+
+#[test]
+fn test_scan_tree_text_rendering() -> Result<()> {
+    let repo = TestRepo::new("scan_tree_text")?;
+    repo.write("src/lib.rs", "fn a() {}\n")?;
+    repo.commit_all("Add lib")?;
+
+    let output = repo.run(&["scan", "--tree"])?;
+    assert!(output.contains("└── src/"), "{output}");
+    assert!(output.contains("lib.rs"), "{output}");
+    assert!(output.contains("(0/1 reviewed)"), "{output}");
+
+    let tree = json(&repo.run(&["scan", "--tree", "--json"])?)?;
+    let file_hash = find_tree_hash(&tree, "src/lib.rs")?;
+    repo.run(&["mark", "--fingerprint", &file_hash, "--verdict", "approved"])?;
+
+    let output = repo.run(&["scan", "--tree"])?;
+    assert!(output.starts_with(". "), "{output}");
+    assert!(output.contains("(1/1 reviewed)"), "{output}");
+    assert!(output.contains("✓ function"), "{output}");
+
+    Ok(())
+}
+
+#[test]
+fn test_review_dir_and_glob_targets() -> Result<()> {
+    let repo = TestRepo::new("review_dir_glob_targets")?;
+    repo.write("src/lib.rs", "pub fn core() {}\n")?;
+    repo.write("src/nested/util.rs", "pub fn util() {}\n")?;
+    repo.write("src/notes.md", "# Notes\n")?;
+    repo.write("tests/it.rs", "fn it() {}\n")?;
+    repo.write("srcfoo/other.rs", "fn other() {}\n")?;
+    repo.commit_all("Add files")?;
+
+    let paths = |target: &str| -> Result<Vec<String>> {
+        let output = repo.run(&["review", "--json", "--target", target])?;
+        let mut paths: Vec<String> = json_array(&output)?
+            .iter()
+            .filter_map(|file| file["path"].as_str().map(str::to_string))
+            .collect();
+        paths.sort();
+        Ok(paths)
+    };
+
+    assert_eq!(
+        paths("dir:src")?,
+        ["src/lib.rs", "src/nested/util.rs", "src/notes.md"]
+    );
+    assert_eq!(paths("dir:./src/nested/")?, ["src/nested/util.rs"]);
+    assert_eq!(
+        paths("glob:src/**/*.rs")?,
+        ["src/lib.rs", "src/nested/util.rs"]
+    );
+    assert_eq!(paths("glob:src/*.rs")?, ["src/lib.rs"]);
+
+    let err = repo.run_err(&["review", "--target", "glob:src/[.rs"])?;
+    assert!(err.contains("Invalid glob target"), "{err}");
+
+    Ok(())
+}
@@ -51,7 +51,7 @@ fn test_vet_sync() -> Result<()> {
     let remote = remote_dir.to_str().context("remote repo path")?;
     run_git(&local.path, &["remote", "add", "origin", remote])?;
 
-    // 3. Create some vet data locally
+    // 3. Create some review data locally
     local.run(&[
         "mark",
         "--fingerprint",
@@ -69,6 +69,12 @@ fn test_vet_sync() -> Result<()> {
     let stdout = run_git_output(&remote_dir, &["branch"])?;
     assert!(stdout.contains("trueflow-db"));
 
+    // Sync also registers the merge driver for the ledger
+    let attributes = fs::read_to_string(local.path.join(".gitattributes"))?;
+    assert!(attributes.contains(".trueflow/reviews.jsonl merge=trueflow"));
+    let driver = run_git_output(&local.path, &["config", "merge.trueflow.driver"])?;
+    assert!(driver.contains("merge-driver"));
+
     // 5. Clone another repo (simulating colleague)
     let colleague = TestRepo::new("colleague_repo")?;
     run_git(&colleague.path, &["remote", "add", "origin", remote])?;
@@ -132,3 +138,144 @@ fn test_sync_dedupes_and_sorts_records() -> Result<()> {
 
     Ok(())
 }
+
+fn bare_remote(name: &str) -> Result<std::path::PathBuf> {
+    let remote_dir = std::env::temp_dir()
+        .join("trueflow_tests")
+        .join(format!("{name}_{}.git", Uuid::new_v4()));
+    fs::create_dir_all(&remote_dir)?;
+    run_git(&remote_dir, &["init", "--bare"])?;
+    Ok(remote_dir)
+}
+
+#[test]
+fn test_sync_dry_run_writes_nothing() -> Result<()> {
+    // GIVEN: a local review not yet on the remote
+    let remote_dir = bare_remote("remote_repo_dry_run")?;
+    let local = TestRepo::new("local_repo_dry_run")?;
+    let remote = remote_dir.to_str().context("remote repo path")?;
+    run_git(&local.path, &["remote", "add", "origin", remote])?;
+    write_reviews_jsonl(
+        &local.path.join(".trueflow"),
+        &[record("one", "fp-one", 1000)],
+    )?;
+
+    // WHEN: we sync with --dry-run
+    let stdout = local.run(&["sync", "--dry-run"])?;
+
+    // THEN: the plan is reported but nothing is pushed
+    assert!(stdout.contains("Would pull 0 and push 1 record(s)"));
+    let branches = run_git_output(&remote_dir, &["branch"])?;
+    assert!(!branches.contains("trueflow-db"));
+    Ok(())
+}
+
+#[test]
+fn test_sync_merges_edits_from_a_named_remote() -> Result<()> {
+    // GIVEN: two clones sharing a remote that is not called origin
+    let remote_dir = bare_remote("remote_repo_named")?;
+    let remote = remote_dir.to_str().context("remote repo path")?;
+    let local = TestRepo::new("local_repo_named")?;
+    run_git(&local.path, &["remote", "add", "upstream", remote])?;
+    let colleague = TestRepo::new("colleague_repo_named")?;
+    run_git(&colleague.path, &["remote", "add", "upstream", remote])?;
+
+    write_reviews_jsonl(&local.path.join(".trueflow"), &[record("a", "fp-a", 1000)])?;
+    local.run(&["sync", "--remote", "upstream"])?;
+    colleague.run(&["sync", "--remote", "upstream"])?;
+
+    // WHEN: each side adds a record and both sync
+    write_reviews_jsonl(
+        &local.path.join(".trueflow"),
+        &[record("a", "fp-a", 1000), record("b", "fp-b", 2000)],
+    )?;
+    write_reviews_jsonl(
+        &colleague.path.join(".trueflow"),
+        &[record("a", "fp-a", 1000), record("c", "fp-c", 3000)],
+    )?;
+    colleague.run(&["sync", "--remote", "upstream"])?;
+    let stdout = local.run(&["sync", "--remote", "upstream"])?;
+
+    // THEN: the remote holds the union and the local ledger pulled the new record
+    assert!(stdout.contains("Pulled 1 and pushed 1 record(s)"));
+    let ids: Vec<String> = read_remote_reviews(&remote_dir)?
+        .iter()
+        .filter_map(|record| record["id"].as_str().map(str::to_string))
+        .collect();
+    assert_eq!(ids, vec!["a", "b", "c"]);
+    let local_ledger = fs::read_to_string(local.path.join(".trueflow/reviews.jsonl"))?;
+    assert_eq!(local_ledger.lines().count(), 3);
+    Ok(())
+}
+
+#[test]
+fn test_sync_appends_pulled_records_to_other_backends() -> Result<()> {
+    // GIVEN: a remote ledger and a colleague on the SQLite backend with a
+    // record of their own
+    let remote_dir = bare_remote("remote_repo_sqlite")?;
+    let remote = remote_dir.to_str().context("remote repo path")?;
+    let local = TestRepo::new("local_repo_sqlite")?;
+    run_git(&local.path, &["remote", "add", "origin", remote])?;
+    write_reviews_jsonl(
+        &local.path.join(".trueflow"),
+        &[record("remote", "fp-remote", 1000)],
+    )?;
+    local.run(&["sync"])?;
+
+    let colleague = TestRepo::new("colleague_repo_sqlite")?;
+    run_git(&colleague.path, &["remote", "add", "origin", remote])?;
+    colleague.write("trueflow.toml", "[store]\nbackend = \"sqlite\"\n")?;
+    colleague.run(&[
+        "mark",
+        "--fingerprint",
+        "fp-own",
+        "--verdict",
+        "approved",
+        "--quiet",
+    ])?;
+
+    // WHEN: the colleague syncs
+    colleague.run(&["sync"])?;
+
+    // THEN: their store gained the remote record and kept their own
+    let history = json_array(&colleague.run(&["history", "--json"])?)?;
+    let fingerprints: Vec<&str> = history
+        .iter()
+        .filter_map(|record| record["fingerprint"].as_str())
+        .collect();
+    assert!(fingerprints.contains(&"fp-remote"), "{fingerprints:?}");
+    assert!(fingerprints.contains(&"fp-own"), "{fingerprints:?}");
+    assert_eq!(read_remote_reviews(&remote_dir)?.len(), 2);
+    Ok(())
+}
+
+#[test]
+fn test_sync_keeps_unreadable_and_unknown_ledger_content() -> Result<()> {
+    // GIVEN: a local ledger with a record from a newer trueflow and a line
+    // this version cannot read
+    let remote_dir = bare_remote("remote_repo_lossless")?;
+    let remote = remote_dir.to_str().context("remote repo path")?;
+    let local = TestRepo::new("local_repo_lossless")?;
+    run_git(&local.path, &["remote", "add", "origin", remote])?;
+    let mut newer = record("newer", "fp-newer", 1000);
+    newer["future_field"] = serde_json::json!({"kept": true});
+    let ledger = local.path.join(".trueflow/reviews.jsonl");
+    let content = format!("{newer}\n<<<<<<< not a record\n");
+    fs::create_dir_all(local.path.join(".trueflow"))?;
+    fs::write(&ledger, &content)?;
+
+    // WHEN: we sync
+    let stderr = local.run_err(&["sync"])?;
+
+    // THEN: it fails without touching the ledger
+    assert!(stderr.contains("unreadable line"), "{stderr}");
+    assert_eq!(fs::read_to_string(&ledger)?, content);
+
+    // AND: once the line is fixed, the unknown field survives both ways
+    fs::write(&ledger, format!("{newer}\n"))?;
+    local.run(&["sync"])?;
+    assert!(fs::read_to_string(&ledger)?.contains("future_field"));
+    let remote_records = read_remote_reviews(&remote_dir)?;
+    assert_eq!(remote_records[0]["future_field"]["kept"], true);
+    Ok(())
+}
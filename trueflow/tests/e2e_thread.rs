@@ -0,0 +1,84 @@
+use anyhow::Result;
+
+mod common;
+use common::{TestRepo, first_block_hash, json_array};
+
+#[test]
+fn test_replies_thread_under_their_parent() -> Result<()> {
+    let repo = TestRepo::new("thread_replies")?;
+    repo.write("src/a.rs", "fn a() { }\n")?;
+    repo.commit_all("Initial")?;
+    let output = repo.run(&["review", "--target", "file:src/a.rs", "--json"])?;
+    let hash = first_block_hash(&output)?;
+    repo.run(&[
+        "mark",
+        "--fingerprint",
+        &hash,
+        "--path",
+        "src/a.rs",
+        "--verdict",
+        "rejected",
+        "--note",
+        "Use a constant",
+        "--quiet",
+    ])?;
+    let records = common::read_review_records(&repo.path.join(".trueflow/reviews.jsonl"))?;
+    let root = records[0].id.clone();
+
+    repo.run(&[
+        "mark",
+        "--reply-to",
+        &root[..8],
+        "--note",
+        "Which one?",
+        "--quiet",
+    ])?;
+    let records = common::read_review_records(&repo.path.join(".trueflow/reviews.jsonl"))?;
+    let reply = records.last().expect("reply record");
+    assert_eq!(reply.in_reply_to.as_deref(), Some(root.as_str()));
+    assert_eq!(reply.fingerprint, hash);
+    assert_eq!(reply.path_hint.as_deref(), Some("src/a.rs"));
+    assert_eq!(reply.verdict.as_str(), "comment");
+    let reply_id = reply.id.clone();
+
+    let threads = json_array(&repo.run(&["thread", "--fingerprint", &hash[..10], "--json"])?)?;
+    assert_eq!(threads.len(), 1);
+    assert_eq!(threads[0]["id"], root.as_str());
+    assert_eq!(threads[0]["replies"][0]["id"], reply_id.as_str());
+    assert_eq!(threads[0]["replies"][0]["note"], "Which one?");
+
+    let text = repo.run(&["thread", "--fingerprint", &hash])?;
+    assert!(text.contains("Use a constant"), "{text}");
+    assert!(text.contains("└ "), "{text}");
+    Ok(())
+}
+
+#[test]
+fn test_reply_on_another_block_is_rejected() -> Result<()> {
+    let repo = TestRepo::new("thread_mismatch")?;
+    repo.write("src/a.rs", "fn a() { }\n")?;
+    repo.commit_all("Initial")?;
+    let output = repo.run(&["review", "--target", "file:src/a.rs", "--json"])?;
+    let hash = first_block_hash(&output)?;
+    repo.run(&[
+        "mark",
+        "--fingerprint",
+        &hash,
+        "--verdict",
+        "comment",
+        "--quiet",
+    ])?;
+    let records = common::read_review_records(&repo.path.join(".trueflow/reviews.jsonl"))?;
+
+    let err = repo.run_err(&[
+        "mark",
+        "--reply-to",
+        &records[0].id,
+        "--fingerprint",
+        "deadbeef",
+    ])?;
+    assert!(err.contains("not deadbeef"), "{err}");
+    let err = repo.run_err(&["mark", "--reply-to", "nope"])?;
+    assert!(err.contains("No record with id nope"), "{err}");
+    Ok(())
+}
@@ -0,0 +1,89 @@
+// The fakes these tests run are shell scripts.
+#![cfg(unix)]
+
+use anyhow::Result;
+use std::os::unix::fs::PermissionsExt;
+use std::process::Command;
+use trueflow_core::store::{RepoRef, VcsSystem};
+
+mod common;
+use common::{TestRepo, first_block_hash, json_array, read_review_records};
+
+const NODE: &str = "abcdef1234567890abcdef1234567890abcdef12";
+
+/// A stand-in `hg` that reports `src/a.rs` as modified and `src/b.rs` as
+/// the only file touched by any revision.
+fn fake_hg(repo: &TestRepo) -> Result<String> {
+    let script = format!(
+        "#!/bin/sh\ncase \"$1\" in\n  status) case \"$*\" in *--change*) echo src/b.rs ;; *) echo src/a.rs ;; esac ;;\n  log) printf '{NODE}' ;;\n  *) exit 1 ;;\nesac\n"
+    );
+    let path = repo.path.join("fake-hg");
+    std::fs::write(&path, script)?;
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+    Ok(path.to_string_lossy().into_owned())
+}
+
+fn run_hg(repo: &TestRepo, hg: &str, args: &[&str]) -> Result<String> {
+    let output = Command::new(env!("CARGO_BIN_EXE_trueflow"))
+        .args(args)
+        .current_dir(&repo.path)
+        .env("TRUEFLOW_HG", hg)
+        .output()?;
+    anyhow::ensure!(
+        output.status.success(),
+        "trueflow failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+fn paths(output: &str) -> Result<Vec<String>> {
+    Ok(json_array(output)?
+        .iter()
+        .filter_map(|file| file["path"].as_str().map(str::to_string))
+        .collect())
+}
+
+#[test]
+fn test_mercurial_checkout_scopes_review_through_hg() -> Result<()> {
+    let repo = TestRepo::new("vcs_provider_hg")?;
+    for name in ["a", "b", "c"] {
+        repo.write(
+            &format!("src/{name}.rs"),
+            &format!("pub fn {name}() {{}}\n"),
+        )?;
+    }
+    // The `.hg` directory takes precedence over `.git` in the same root.
+    std::fs::create_dir(repo.path.join(".hg"))?;
+    let hg = fake_hg(&repo)?;
+
+    assert_eq!(
+        paths(&run_hg(&repo, &hg, &["review", "--json"])?)?,
+        ["src/a.rs"]
+    );
+    assert_eq!(
+        paths(&run_hg(
+            &repo,
+            &hg,
+            &["review", "--target", "rev:42", "--json"]
+        )?)?,
+        ["src/b.rs"]
+    );
+
+    let hash = first_block_hash(&run_hg(&repo, &hg, &["review", "--json"])?)?;
+    run_hg(
+        &repo,
+        &hg,
+        &["mark", "--fingerprint", &hash, "--path", "src/a.rs"],
+    )?;
+    let records = read_review_records(&repo.path.join(".trueflow/reviews.jsonl"))?;
+    assert_eq!(
+        records[0].repo_ref,
+        RepoRef::Vcs {
+            system: VcsSystem::Mercurial,
+            revision: NODE.to_string(),
+        }
+    );
+
+    Ok(())
+}
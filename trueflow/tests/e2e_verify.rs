@@ -1,7 +1,46 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use std::fs;
+use std::process::Command;
 mod common;
 use common::*;
 
+/// Generates an SSH key outside the worktree and returns its private half.
+fn ssh_keygen() -> Result<String> {
+    let key_dir = std::env::temp_dir()
+        .join("trueflow_tests")
+        .join(format!("ssh_key_{}", uuid::Uuid::new_v4()));
+    fs::create_dir_all(&key_dir)?;
+    let key = key_dir.join("id_ed25519");
+    let key = key.to_str().context("key path")?;
+    let status = Command::new("ssh-keygen")
+        .args([
+            "-q",
+            "-t",
+            "ed25519",
+            "-N",
+            "",
+            "-C",
+            "test@example.com",
+            "-f",
+            key,
+        ])
+        .status()?;
+    anyhow::ensure!(status.success(), "ssh-keygen failed");
+    Ok(key.to_string())
+}
+
+/// Configures `repo` to sign with a fresh SSH key, trusted for
+/// `test@example.com` through git's allowed signers file.
+fn configure_ssh_signing(repo: &TestRepo) -> Result<()> {
+    let key = ssh_keygen()?;
+    let allowed_signers = format!("{key}.allowed_signers");
+    let public_key = fs::read_to_string(format!("{key}.pub"))?;
+    fs::write(&allowed_signers, format!("test@example.com {public_key}"))?;
+    repo.git(&["config", "gpg.format", "ssh"])?;
+    repo.git(&["config", "gpg.ssh.allowedSignersFile", &allowed_signers])?;
+    repo.git(&["config", "user.signingkey", &key])
+}
+
 #[test]
 fn test_verify_unsigned_records() -> Result<()> {
     let repo = TestRepo::new("verify_unsigned")?;
@@ -70,3 +109,262 @@ fn test_verify_invalid_attestation() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_mark_sign_with_ssh_key_verifies() -> Result<()> {
+    // GIVEN: a repo whose git config names an SSH signing key
+    let repo = TestRepo::new("verify_ssh_signed")?;
+    configure_ssh_signing(&repo)?;
+
+    // WHEN: a verdict is marked with --sign
+    repo.run(&["mark", "--fingerprint", "deadbeef", "--sign", "--quiet"])?;
+
+    // THEN: the record carries an SSH attestation that verifies
+    let records = read_review_records(&repo.path.join(".trueflow/reviews.jsonl"))?;
+    let attestations = records[0].attestations.as_ref().context("attestations")?;
+    assert_eq!(
+        serde_json::to_value(&attestations[0].kind)?,
+        serde_json::json!("SSH")
+    );
+    let stdout = repo.run(&["verify", "--all"])?;
+    assert!(stdout.contains("Attested: 1"));
+
+    // AND: editing the signed record breaks the signature
+    let ledger = repo.path.join(".trueflow/reviews.jsonl");
+    let tampered = fs::read_to_string(&ledger)?.replace("deadbeef", "cafebabe");
+    fs::write(&ledger, tampered)?;
+    let output = repo.run_raw(&["verify", "--all"])?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stdout)?.contains("Invalid: 1"));
+
+    Ok(())
+}
+
+#[test]
+fn test_self_signed_attestation_needs_a_trusted_signer() -> Result<()> {
+    // GIVEN: a repo that trusts one SSH key for test@example.com
+    let repo = TestRepo::new("verify_ssh_forged")?;
+    configure_ssh_signing(&repo)?;
+
+    // WHEN: someone signs a verdict as test@example.com with their own key,
+    // embedding that key in the record
+    let forger = ssh_keygen()?;
+    repo.git(&["config", "user.signingkey", &forger])?;
+    repo.run(&["mark", "--fingerprint", "deadbeef", "--sign", "--quiet"])?;
+
+    // THEN: the intact signature doesn't make it attested
+    let output = repo.run_raw(&["verify", "--all"])?;
+    let stdout = String::from_utf8(output.stdout)?;
+    assert!(!output.status.success(), "{stdout}");
+    assert!(stdout.contains("Attested: 0"), "{stdout}");
+    assert!(stdout.contains("Invalid: 1"), "{stdout}");
+
+    // AND: with no allowed signers at all it is only unverified
+    repo.git(&["config", "--unset", "gpg.ssh.allowedSignersFile"])?;
+    let output = repo.run_raw(&["verify", "--all"])?;
+    let stdout = String::from_utf8(output.stdout)?;
+    assert!(output.status.success(), "{stdout}");
+    assert!(stdout.contains("Attested: 0"), "{stdout}");
+    assert!(stdout.contains("Unverified: 1"), "{stdout}");
+    assert!(String::from_utf8(output.stderr)?.contains("allowedSignersFile"));
+
+    Ok(())
+}
+
+#[test]
+fn test_identity_sign_config_signs_by_default() -> Result<()> {
+    // GIVEN: signing turned on in trueflow.toml
+    let repo = TestRepo::new("verify_sign_config")?;
+    configure_ssh_signing(&repo)?;
+    repo.write("trueflow.toml", "[identity]\nsign = true\n")?;
+
+    // WHEN: verdicts are marked without --sign
+    repo.run(&["mark", "--fingerprint", "deadbeef", "--quiet"])?;
+
+    // THEN: they are signed anyway
+    let stdout = repo.run(&["verify", "--all"])?;
+    assert!(stdout.contains("Attested: 1"));
+    assert!(stdout.contains("Unattested: 0"));
+
+    Ok(())
+}
+
+#[test]
+fn test_mark_without_sign_leaves_records_unsigned() -> Result<()> {
+    // GIVEN: a signing key in git config but signing not requested
+    let repo = TestRepo::new("verify_sign_opt_in")?;
+    configure_ssh_signing(&repo)?;
+
+    // WHEN: a verdict is marked
+    repo.run(&["mark", "--fingerprint", "deadbeef", "--quiet"])?;
+
+    // THEN: it is not signed
+    let stdout = repo.run(&["verify", "--all"])?;
+    assert!(stdout.contains("Unattested: 1"));
+
+    Ok(())
+}
+
+/// Stand-in for cosign: the "bundle" is the payload digest, and verification
+/// only accepts an identity of `ci@example.com` with some issuer.
+#[cfg(unix)]
+const FAKE_COSIGN: &str = r#"#!/bin/sh
+cmd=$1; shift
+while [ $# -gt 0 ]; do
+  case "$1" in
+    --bundle) bundle=$2; shift 2 ;;
+    --output-certificate) cert=$2; shift 2 ;;
+    --certificate-identity) identity=$2; shift 2 ;;
+    --certificate-oidc-issuer) issuer=$2; shift 2 ;;
+    --certificate-*) shift 2 ;;
+    --yes) shift ;;
+    *) file=$1; shift ;;
+  esac
+done
+digest=$(sha256sum "$file" | cut -d' ' -f1)
+case "$cmd" in
+  sign-blob) printf '{"digest":"%s"}' "$digest" > "$bundle"; printf 'CERT' > "$cert" ;;
+  verify-blob) [ "$identity" = "ci@example.com" ] && [ -n "$issuer" ] || exit 1
+               grep -q "$digest" "$bundle" ;;
+  *) exit 2 ;;
+esac
+"#;
+
+#[cfg(unix)]
+fn fake_cosign() -> Result<String> {
+    use std::os::unix::fs::PermissionsExt;
+    let dir = std::env::temp_dir()
+        .join("trueflow_tests")
+        .join(format!("cosign_{}", uuid::Uuid::new_v4()));
+    fs::create_dir_all(&dir)?;
+    let path = dir.join("cosign");
+    fs::write(&path, FAKE_COSIGN)?;
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+    Ok(path.to_str().context("cosign path")?.to_string())
+}
+
+#[cfg(unix)]
+fn run_with_cosign(repo: &TestRepo, cosign: &str, args: &[&str]) -> Result<std::process::Output> {
+    Ok(Command::new(env!("CARGO_BIN_EXE_trueflow"))
+        .args(args)
+        .current_dir(&repo.path)
+        .env("TRUEFLOW_COSIGN", cosign)
+        .output()?)
+}
+
+#[cfg(unix)]
+#[test]
+fn test_sigstore_attestation_verifies_with_pinned_identity() -> Result<()> {
+    // GIVEN: Sigstore selected as the signing format
+    let repo = TestRepo::new("verify_sigstore")?;
+    let cosign = fake_cosign()?;
+    repo.write("trueflow.toml", "[identity]\nformat = \"sigstore\"\n")?;
+
+    // WHEN: a verdict is marked with --sign
+    let output = run_with_cosign(
+        &repo,
+        &cosign,
+        &["mark", "--fingerprint", "deadbeef", "--sign", "--quiet"],
+    )?;
+    assert!(output.status.success(), "{:?}", output);
+
+    // THEN: the record holds a Sigstore bundle that verifies
+    let records = read_review_records(&repo.path.join(".trueflow/reviews.jsonl"))?;
+    let attestation = &records[0].attestations.as_ref().context("attestations")?[0];
+    assert_eq!(
+        serde_json::to_value(&attestation.kind)?,
+        serde_json::json!("SIGSTORE")
+    );
+    assert_eq!(attestation.public_key, "CERT");
+
+    // AND: without a pinned issuer any certificate would pass, so it is
+    // reported as unverified rather than attested
+    let output = run_with_cosign(&repo, &cosign, &["verify", "--all"])?;
+    let stdout = String::from_utf8(output.stdout)?;
+    assert!(output.status.success(), "{stdout}");
+    assert!(stdout.contains("Attested: 0"), "{stdout}");
+    assert!(stdout.contains("Unverified: 1"), "{stdout}");
+    assert!(String::from_utf8(output.stderr)?.contains("certificate_oidc_issuer"));
+
+    // AND: it verifies once the signer is pinned
+    let pinned = |identity: &str| {
+        format!(
+            "[identity]\nformat = \"sigstore\"\n\n[sigstore]\ncertificate_identity = \"{identity}\"\ncertificate_oidc_issuer = \"https://issuer.example.com\"\n"
+        )
+    };
+    repo.write("trueflow.toml", &pinned("ci@example.com"))?;
+    let output = run_with_cosign(&repo, &cosign, &["verify", "--all"])?;
+    assert!(String::from_utf8(output.stdout)?.contains("Attested: 1"));
+
+    // AND: pinning a different certificate identity rejects it
+    repo.write("trueflow.toml", &pinned("someone@example.com"))?;
+    let output = run_with_cosign(&repo, &cosign, &["verify", "--all"])?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stdout)?.contains("Invalid: 1"));
+
+    Ok(())
+}
+
+#[test]
+fn test_verify_reports_schema_errors_and_duplicate_ids() -> Result<()> {
+    let repo = TestRepo::new("verify_integrity")?;
+    let record = build_review_record(
+        "deadbeef",
+        ReviewRecordOverrides {
+            id: Some("twice"),
+            ..Default::default()
+        },
+    );
+    let mut future = record.clone();
+    future["id"] = "future".into();
+    future["version"] = 99.into();
+    let mut bad_email = record.clone();
+    bad_email["id"] = "bad-email".into();
+    bad_email["identity"]["email"] = "nobody".into();
+    write_reviews_jsonl(
+        &repo.path.join(".trueflow"),
+        &[record.clone(), record, future, bad_email],
+    )?;
+    let ledger = repo.path.join(".trueflow/reviews.jsonl");
+    let mut content = fs::read_to_string(&ledger)?;
+    content.push_str("{not json\n");
+    fs::write(&ledger, content)?;
+
+    let output = repo.run_raw(&["verify", "--all"])?;
+
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8(output.stdout)?;
+    assert!(stdout.contains("Records: 5"), "{stdout}");
+    assert!(stdout.contains("Schema errors: 3"), "{stdout}");
+    assert!(stdout.contains("Duplicate ids: 1"), "{stdout}");
+    let stderr = String::from_utf8(output.stderr)?;
+    assert!(
+        stderr.contains("DUPLICATE ID id=twice records=2"),
+        "{stderr}"
+    );
+    assert!(stderr.contains("line=3: /version"), "{stderr}");
+    assert!(stderr.contains("line=4: /identity/email"), "{stderr}");
+    assert!(stderr.contains("line=5: not JSON"), "{stderr}");
+    Ok(())
+}
+
+#[test]
+fn test_verify_strict_fails_on_stale_fingerprints() -> Result<()> {
+    let repo = TestRepo::new("verify_stale")?;
+    repo.write("lib.rs", "pub fn core() {}\n")?;
+    repo.commit_all("Initial")?;
+    let live = first_block_hash(&repo.run(&["scan", "--json"])?)?;
+    repo.run(&["mark", "--fingerprint", &live, "--quiet"])?;
+    repo.run(&["mark", "--fingerprint", "deadbeef", "--quiet"])?;
+
+    let relaxed = repo.run_raw(&["verify", "--all"])?;
+    assert!(relaxed.status.success());
+    assert!(String::from_utf8(relaxed.stdout)?.contains("Stale fingerprints: 1"));
+
+    let strict = repo.run_raw(&["verify", "--all", "--strict"])?;
+    assert_eq!(strict.status.code(), Some(2));
+    let stderr = String::from_utf8(strict.stderr)?;
+    assert!(stderr.contains("fingerprint=deadbeef"), "{stderr}");
+    assert!(!stderr.contains(&live), "{stderr}");
+    Ok(())
+}
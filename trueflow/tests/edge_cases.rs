@@ -1,7 +1,7 @@
 use anyhow::Result;
 use std::fs;
-use trueflow::block::FileState;
-use trueflow::sub_splitter;
+use trueflow_core::block::FileState;
+use trueflow_core::{scanner, sub_splitter};
 
 mod common;
 use common::TestRepo;
@@ -23,8 +23,11 @@ fn test_binary_file() -> Result<()> {
         .find(|obj| obj["path"].as_str().unwrap().contains("binary.bin"));
     assert!(file_obj.is_some(), "Binary file should be in output");
     let file_obj = file_obj.unwrap();
-    assert_eq!(file_obj["file_hash"], "binary_skipped");
-    assert!(file_obj["blocks"].as_array().unwrap().is_empty());
+    let blocks = file_obj["blocks"].as_array().unwrap();
+    assert_eq!(blocks.len(), 1);
+    assert_eq!(blocks[0]["kind"], "binary");
+    let content = blocks[0]["content"].as_str().unwrap();
+    assert!(content.starts_with("binary: application/octet-stream\nsize: 4 bytes\nsha256: "));
 
     Ok(())
 }
@@ -134,3 +137,115 @@ fn test_sub_splitter_avoids_empty_blocks() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_scan_cache_catches_same_size_edits() -> Result<()> {
+    let repo = TestRepo::new("scan_cache_same_size")?;
+    repo.write("src/a.rs", "fn alpha() {}\n")?;
+    repo.write("src/b.rs", "fn bravo() {}\n")?;
+
+    let first: serde_json::Value = serde_json::from_str(&repo.run(&["scan", "--json"])?)?;
+
+    // Same size, likely the same mtime second as the cached entry.
+    repo.write("src/a.rs", "fn gamma() {}\n")?;
+    let second: serde_json::Value = serde_json::from_str(&repo.run(&["scan", "--json"])?)?;
+
+    let hash = |scan: &serde_json::Value, path: &str| {
+        scan.as_array()
+            .and_then(|files| files.iter().find(|file| file["path"] == path))
+            .map(|file| file["file_hash"].clone())
+    };
+    assert_ne!(hash(&first, "src/a.rs"), hash(&second, "src/a.rs"));
+    assert_eq!(hash(&first, "src/b.rs"), hash(&second, "src/b.rs"));
+    Ok(())
+}
+
+#[test]
+fn test_parallel_scan_is_deterministic() -> Result<()> {
+    let scan = |name: &str, jobs: &str| -> Result<String> {
+        let repo = TestRepo::new(name)?;
+        for index in 0..24 {
+            repo.write(
+                &format!("src/mod_{index}.rs"),
+                &format!("fn f{index}() {{}}\n\nfn g{index}() {{}}\n"),
+            )?;
+        }
+        repo.run(&["scan", "--json", "--jobs", jobs])
+    };
+    assert_eq!(
+        scan("scan_jobs_serial", "1")?,
+        scan("scan_jobs_parallel", "8")?
+    );
+    Ok(())
+}
+
+#[test]
+fn test_scan_respects_ignore_files() -> Result<()> {
+    let scanned = |repo: &TestRepo| -> Result<Vec<String>> {
+        let output: serde_json::Value = serde_json::from_str(&repo.run(&["scan", "--json"])?)?;
+        let mut paths: Vec<String> = output
+            .as_array()
+            .expect("Array")
+            .iter()
+            .filter_map(|file| file["path"].as_str().map(str::to_string))
+            .collect();
+        paths.sort();
+        Ok(paths)
+    };
+
+    let repo = TestRepo::new("scan_ignore_files")?;
+    repo.write("src/lib.rs", "pub fn core() {}\n")?;
+    repo.write("vendor/dep.rs", "pub fn dep() {}\n")?;
+    repo.write("gen/out.rs", "pub fn out() {}\n")?;
+    repo.write("notes/todo.md", "# Todo\n")?;
+    repo.write(".gitignore", "vendor/\n")?;
+    repo.write(".trueflowignore", "gen/\n")?;
+    fs::write(repo.path.join(".git/info/exclude"), "notes/\n")?;
+    assert_eq!(scanned(&repo)?, ["src/lib.rs"]);
+
+    // [scan] include brings ignored paths back and limits the scan to them.
+    repo.write(
+        "trueflow.toml",
+        "[scan]\ninclude = [\"vendor/**\", \"src/**\"]\nexclude = [\"src/lib.rs\"]\n",
+    )?;
+    assert_eq!(scanned(&repo)?, ["vendor/dep.rs"]);
+    Ok(())
+}
+
+#[test]
+fn test_filtered_scan_only_parses_matching_files() -> Result<()> {
+    let repo = TestRepo::new("scan_filtered")?;
+    repo.write("src/a.rs", "fn alpha() {}\n")?;
+    repo.write("src/b.rs", "fn bravo() {}\n")?;
+    let paths = |files: Vec<FileState>| {
+        let mut paths: Vec<String> = files
+            .into_iter()
+            .map(|file| {
+                file.path
+                    .rsplit_once("src/")
+                    .map(|(_, name)| name.to_string())
+                    .unwrap_or(file.path)
+            })
+            .collect();
+        paths.sort();
+        paths
+    };
+    let only_a = |path: &str| path == "src/a.rs";
+
+    // Cold cache: only the matching file comes back.
+    let files = scanner::scan_directory_filtered(&repo.path, only_a)?;
+    assert_eq!(paths(files), ["a.rs"]);
+
+    // Warm cache: unchanged files outside the filter come back from it.
+    scanner::scan_directory(&repo.path)?;
+    std::thread::sleep(std::time::Duration::from_millis(1100));
+    scanner::scan_directory(&repo.path)?;
+    let files = scanner::scan_directory_filtered(&repo.path, only_a)?;
+    assert_eq!(paths(files), ["a.rs", "b.rs"]);
+
+    // A changed file outside the filter is left out rather than re-parsed.
+    repo.write("src/b.rs", "fn bravo_two() {}\n")?;
+    let files = scanner::scan_directory_filtered(&repo.path, only_a)?;
+    assert_eq!(paths(files), ["a.rs"]);
+    Ok(())
+}
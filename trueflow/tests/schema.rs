@@ -3,13 +3,14 @@ use schemars::generate::SchemaSettings;
 use serde_json::Value;
 use std::fs;
 use std::path::PathBuf;
+use trueflow_core::schemas::SchemaName;
 
 #[test]
 fn review_record_schema_matches_snapshot() -> Result<()> {
     let schema = SchemaSettings::draft2020_12()
         .for_serialize()
         .into_generator()
-        .into_root_schema_for::<trueflow::store::Record>();
+        .into_root_schema_for::<trueflow_core::store::Record>();
     let schema_value = serde_json::to_value(schema)?;
 
     if std::env::var("TRUEFLOW_PRINT_SCHEMA").is_ok() {
@@ -30,3 +31,25 @@ fn review_record_schema_matches_snapshot() -> Result<()> {
 fn schema_snapshot_path() -> PathBuf {
     PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("review_record.schema.json")
 }
+
+/// Snapshots of the command output schemas; set TRUEFLOW_UPDATE_SCHEMAS to
+/// rewrite them after an intended change (and bump OUTPUT_SCHEMA_VERSION if
+/// it breaks readers).
+#[test]
+fn output_schemas_match_snapshots() -> Result<()> {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("schemas");
+    for name in SchemaName::ALL {
+        if name == SchemaName::Record {
+            continue;
+        }
+        let path = dir.join(format!("{}.schema.json", name.name()));
+        let schema = name.schema();
+        if std::env::var("TRUEFLOW_UPDATE_SCHEMAS").is_ok() {
+            fs::create_dir_all(&dir)?;
+            fs::write(&path, serde_json::to_string_pretty(&schema)? + "\n")?;
+        }
+        let expected: Value = serde_json::from_str(&fs::read_to_string(&path)?)?;
+        assert_eq!(schema, expected, "schema mismatch at {path:?}");
+    }
+    Ok(())
+}
@@ -1,11 +1,11 @@
 use anyhow::Result;
 use std::path::PathBuf;
 
-use trueflow::analysis::Language;
-use trueflow::block::BlockKind;
-use trueflow::block_splitter;
-use trueflow::finder::fuzzy_find_block;
-use trueflow::sub_splitter;
+use trueflow_core::analysis::Language;
+use trueflow_core::block::BlockKind;
+use trueflow_core::block_splitter;
+use trueflow_core::finder::fuzzy_find_block;
+use trueflow_core::sub_splitter;
 
 fn assert_subblock_kinds(
     path: PathBuf,
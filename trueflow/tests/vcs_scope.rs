@@ -12,7 +12,7 @@ fn test_recent_commits_in_repo_returns_head_first() -> Result<()> {
     repo.commit_all("Second commit")?;
 
     let git_repo = gix::open(&repo.path)?;
-    let commits = trueflow::vcs::recent_commits_in_repo(&git_repo, 8)?;
+    let commits = trueflow_core::vcs::recent_commits_in_repo(&git_repo, 8)?;
 
     assert!(commits.len() >= 2, "expected at least two commits");
     assert_eq!(commits[0].summary, "Second commit");
@@ -32,7 +32,7 @@ fn test_files_changed_main_to_head_in_repo() -> Result<()> {
     repo.commit_all("Add helper")?;
 
     let git_repo = gix::open(&repo.path)?;
-    let changed = trueflow::vcs::files_changed_main_to_head_in_repo(&git_repo)?;
+    let changed = trueflow_core::vcs::files_changed_main_to_head_in_repo(&git_repo)?;
 
     assert!(
         changed.contains("src/lib.rs"),
@@ -41,3 +41,52 @@ fn test_files_changed_main_to_head_in_repo() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_staged_changes_compare_index_to_head() -> Result<()> {
+    let repo = TestRepo::new("staged_changes")?;
+    repo.write("src/main.rs", "fn main() {}\n")?;
+    repo.write("src/old.rs", "fn old() {}\n")?;
+    repo.commit_all("Base")?;
+    repo.write("src/lib.rs", "pub fn helper() {}\n")?;
+    repo.add("src/lib.rs")?;
+    repo.git(&["rm", "-q", "src/old.rs"])?;
+    repo.write("src/main.rs", "fn main() { unstaged(); }\n")?;
+
+    let git_repo = gix::open(&repo.path)?;
+    let staged = trueflow_core::vcs::staged_changes(&git_repo)?;
+
+    let mut paths: Vec<&str> = staged.keys().map(String::as_str).collect();
+    paths.sort();
+    assert_eq!(paths, ["src/lib.rs", "src/old.rs"]);
+    assert!(staged["src/lib.rs"].is_some());
+    assert!(staged["src/old.rs"].is_none());
+
+    Ok(())
+}
+
+#[test]
+fn test_review_staged_target_uses_staged_content() -> Result<()> {
+    let repo = TestRepo::new("review_staged")?;
+    repo.write("src/main.rs", "fn main() {}\n")?;
+    repo.commit_all("Base")?;
+    repo.write("src/lib.rs", "pub fn staged() {}\n")?;
+    repo.add("src/lib.rs")?;
+    repo.write("src/lib.rs", "pub fn staged() {}\n\npub fn unstaged() {}\n")?;
+    repo.write("src/other.rs", "pub fn untracked() {}\n")?;
+
+    let output = repo.run(&["review", "--target", "staged", "--json"])?;
+    let files = common::json_array(&output)?;
+    assert_eq!(files.len(), 1, "{output}");
+    assert_eq!(files[0]["path"], "src/lib.rs");
+    let contents: Vec<&str> = files[0]["blocks"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|block| block["content"].as_str())
+        .collect();
+    assert!(contents.iter().any(|content| content.contains("staged()")));
+    assert!(!contents.iter().any(|content| content.contains("unstaged")));
+
+    Ok(())
+}
@@ -1,7 +1,8 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq, Hash, JsonSchema)]
 pub enum Language {
     Rust,
     Elisp,
@@ -9,6 +10,10 @@ pub enum Language {
     TypeScript,
     Python,
     Shell,
+    C,
+    Cpp,
+    Sql,
+    Yaml,
     Markdown,
     Toml,
     Nix,
@@ -18,7 +23,7 @@ pub enum Language {
     Unknown,
 }
 
-// TODO: add Language::Go, Language::Java, Language::Cpp once tree-sitter support is wired.
+// TODO: add Language::Go, Language::Java once tree-sitter support is wired.
 
 impl Language {
     pub fn uses_text_fallback(&self) -> bool {
@@ -36,7 +41,11 @@ impl Language {
             "ts" => Some(Language::TypeScript),
             "py" => Some(Language::Python),
             "sh" => Some(Language::Shell),
+            "c" | "h" => Some(Language::C),
+            "cc" | "cpp" | "cxx" | "c++" | "hh" | "hpp" | "hxx" | "h++" => Some(Language::Cpp),
             "md" | "markdown" => Some(Language::Markdown),
+            "sql" => Some(Language::Sql),
+            "yaml" | "yml" => Some(Language::Yaml),
             "toml" => Some(Language::Toml),
             "nix" => Some(Language::Nix),
             "just" => Some(Language::Just),
@@ -59,6 +68,38 @@ pub enum FileType {
     Text,
 }
 
+const BINARY_SNIFF_BYTES: usize = 1024; // 1KB check is usually enough
+
+/// A NUL byte near the start of the content marks it as binary.
+pub fn is_binary(bytes: &[u8]) -> bool {
+    bytes[..bytes.len().min(BINARY_SNIFF_BYTES)].contains(&0)
+}
+
+/// MIME type of binary content, from well-known magic numbers.
+pub fn detect_binary_type(bytes: &[u8]) -> &'static str {
+    const MAGIC: &[(&[u8], &str)] = &[
+        (b"\x89PNG\r\n\x1a\n", "image/png"),
+        (b"\xff\xd8\xff", "image/jpeg"),
+        (b"GIF87a", "image/gif"),
+        (b"GIF89a", "image/gif"),
+        (b"\x00\x00\x01\x00", "image/x-icon"),
+        (b"%PDF-", "application/pdf"),
+        (b"PK\x03\x04", "application/zip"),
+        (b"\x1f\x8b", "application/gzip"),
+        (b"\x00asm", "application/wasm"),
+        (b"\x7fELF", "application/x-elf"),
+        (b"SQLite format 3\x00", "application/vnd.sqlite3"),
+    ];
+    if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        return "image/webp";
+    }
+    MAGIC
+        .iter()
+        .find(|(magic, _)| bytes.starts_with(magic))
+        .map(|(_, mime)| *mime)
+        .unwrap_or("application/octet-stream")
+}
+
 pub fn analyze_file(path: &Path) -> FileType {
     // 1. Check for extension-based Code/Markup
     if let Some(ext) = path.extension().and_then(|s| s.to_str())
@@ -75,12 +116,11 @@ pub fn analyze_file(path: &Path) -> FileType {
 
     if let Ok(mut file) = std::fs::File::open(path) {
         use std::io::Read;
-        let mut buffer = [0; 1024]; // 1KB check is usually enough
-        if let Ok(n) = file.read(&mut buffer) {
-            let slice = &buffer[..n];
-            if slice.contains(&0) {
-                return FileType::Binary;
-            }
+        let mut buffer = [0; BINARY_SNIFF_BYTES];
+        if let Ok(n) = file.read(&mut buffer)
+            && is_binary(&buffer[..n])
+        {
+            return FileType::Binary;
         }
     }
 
@@ -101,6 +141,10 @@ mod tests {
         assert_eq!(Language::from_extension("ts"), Some(Language::TypeScript));
         assert_eq!(Language::from_extension("py"), Some(Language::Python));
         assert_eq!(Language::from_extension("sh"), Some(Language::Shell));
+        assert_eq!(Language::from_extension("h"), Some(Language::C));
+        assert_eq!(Language::from_extension("hpp"), Some(Language::Cpp));
+        assert_eq!(Language::from_extension("sql"), Some(Language::Sql));
+        assert_eq!(Language::from_extension("yml"), Some(Language::Yaml));
         assert_eq!(Language::from_extension("md"), Some(Language::Markdown));
         assert_eq!(
             Language::from_extension("markdown"),
@@ -113,4 +157,21 @@ mod tests {
         assert_eq!(Language::from_extension("txt"), Some(Language::Text));
         assert_eq!(Language::from_extension("unknown_ext"), None);
     }
+
+    #[test]
+    fn test_detect_binary_type() {
+        assert_eq!(
+            detect_binary_type(b"\x89PNG\r\n\x1a\n\x00\x00"),
+            "image/png"
+        );
+        assert_eq!(
+            detect_binary_type(b"RIFF\x00\x00\x00\x00WEBPVP8 "),
+            "image/webp"
+        );
+        assert_eq!(detect_binary_type(b"\x00asm\x01\x00"), "application/wasm");
+        assert_eq!(
+            detect_binary_type(&[0, 255, 0, 1]),
+            "application/octet-stream"
+        );
+    }
 }
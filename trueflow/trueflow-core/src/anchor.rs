@@ -0,0 +1,184 @@
+use crate::block::{Block, PreviousApproval};
+use crate::lineage::LineageIndex;
+use crate::store::{Record, RepoRef, Verdict};
+use crate::vcs_provider::VcsProvider;
+use std::collections::{HashMap, HashSet};
+
+/// Where an approval was recorded: the approved fingerprint, the revision
+/// checked out at the time and the file it was in.
+#[derive(Debug, Clone)]
+struct Anchor {
+    fingerprint: String,
+    revision: String,
+    path: String,
+}
+
+/// Approvals that are still in force, for finding the approved version of a
+/// block that has since been edited.
+#[derive(Debug, Default)]
+pub struct ApprovalAnchors {
+    /// Newest first.
+    by_path: HashMap<String, Vec<Anchor>>,
+    by_fingerprint: HashMap<String, Anchor>,
+    approved: HashSet<String>,
+    /// Blocks of (revision, path), read at most once.
+    blocks: HashMap<(String, String), Vec<Block>>,
+}
+
+impl ApprovalAnchors {
+    /// Approvals in `records` of fingerprints still in `approved`. Approvals
+    /// recorded before the first commit have no revision to read back.
+    pub fn from_records(records: &[Record], approved: &HashSet<String>) -> Self {
+        let mut anchors: Vec<(i64, Anchor)> = records
+            .iter()
+            .filter(|record| record.kind.is_verdict() && record.verdict == Verdict::Approved)
+            .filter(|record| approved.contains(&record.fingerprint))
+            .filter_map(|record| {
+                let RepoRef::Vcs { revision, .. } = &record.repo_ref;
+                if revision == "unknown" {
+                    return None;
+                }
+                Some((
+                    record.timestamp,
+                    Anchor {
+                        fingerprint: record.fingerprint.clone(),
+                        revision: revision.clone(),
+                        path: record.path_hint.clone()?,
+                    },
+                ))
+            })
+            .collect();
+        anchors.sort_by_key(|(timestamp, _)| std::cmp::Reverse(*timestamp));
+
+        let mut index = Self {
+            approved: approved.clone(),
+            ..Self::default()
+        };
+        for (_, anchor) in anchors {
+            index
+                .by_fingerprint
+                .entry(anchor.fingerprint.clone())
+                .or_insert_with(|| anchor.clone());
+            index
+                .by_path
+                .entry(anchor.path.clone())
+                .or_default()
+                .push(anchor);
+        }
+        index
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_fingerprint.is_empty()
+    }
+
+    /// Sets `previous` on `blocks` (unreviewed blocks of the file at
+    /// `paths`) that were edited from an approved block: one a lineage link
+    /// leads back to, or else an approved block of the same file, no longer
+    /// in it, that declared the same item. `repo_path` is the file's path in
+    /// the repository, for reading it back at the approval's revision.
+    pub fn annotate(
+        &mut self,
+        provider: &dyn VcsProvider,
+        paths: &[&str],
+        repo_path: &str,
+        blocks: &mut [Block],
+        current: &HashSet<String>,
+        lineage: &LineageIndex,
+    ) {
+        let candidates: Vec<Anchor> = paths
+            .iter()
+            .filter_map(|path| self.by_path.get(*path))
+            .flatten()
+            .filter(|anchor| !current.contains(&anchor.fingerprint))
+            .cloned()
+            .collect();
+        for block in blocks.iter_mut() {
+            if self.approved.contains(&block.hash) {
+                continue;
+            }
+            let linked = lineage
+                .approved_ancestor(&block.hash, &self.approved)
+                .and_then(|ancestor| self.by_fingerprint.get(ancestor).cloned());
+            if let Some(anchor) = linked
+                && let Some(previous) = self.approved_block(provider, &anchor, repo_path)
+            {
+                block.previous = Some(previous_approval(&anchor, previous));
+                continue;
+            }
+            for anchor in &candidates {
+                if let Some(previous) = self.approved_block(provider, anchor, repo_path)
+                    && same_item(&previous, block)
+                {
+                    block.previous = Some(previous_approval(anchor, previous));
+                    break;
+                }
+            }
+        }
+    }
+
+    /// The block `anchor` approved, read back from its revision.
+    fn approved_block(
+        &mut self,
+        provider: &dyn VcsProvider,
+        anchor: &Anchor,
+        repo_path: &str,
+    ) -> Option<Block> {
+        let blocks = self
+            .blocks
+            .entry((anchor.revision.clone(), repo_path.to_string()))
+            .or_insert_with(|| {
+                provider
+                    .blocks_at_revision(&anchor.revision, repo_path)
+                    .unwrap_or_default()
+            });
+        blocks
+            .iter()
+            .find(|block| block.hash == anchor.fingerprint)
+            .cloned()
+    }
+}
+
+fn previous_approval(anchor: &Anchor, block: Block) -> PreviousApproval {
+    PreviousApproval {
+        previous_fingerprint: anchor.fingerprint.clone(),
+        previous_revision: anchor.revision.clone(),
+        previous_content: block.content,
+    }
+}
+
+/// Whether `current` is plausibly an edit of `previous`: the same kind of
+/// block declaring the same name or, for unnamed blocks, at overlapping lines.
+fn same_item(previous: &Block, current: &Block) -> bool {
+    if previous.kind != current.kind {
+        return false;
+    }
+    match (previous.symbol(), current.symbol()) {
+        (Some(before), Some(after)) => before == after,
+        (None, None) => {
+            previous.start_line < current.end_line && current.start_line < previous.end_line
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::BlockKind;
+
+    #[test]
+    fn edits_match_by_declared_name() {
+        let before = Block::new("fn parse() {}\n".to_string(), BlockKind::Function, 4, 5);
+        let edited = Block::new(
+            "fn parse() {\n    todo!()\n}\n".to_string(),
+            BlockKind::Function,
+            6,
+            9,
+        );
+        let other = Block::new("fn render() {}\n".to_string(), BlockKind::Function, 4, 5);
+
+        assert!(same_item(&before, &edited));
+        assert!(!same_item(&before, &other));
+    }
+}
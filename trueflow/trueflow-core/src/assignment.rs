@@ -0,0 +1,211 @@
+use crate::store::{Assignment, Identity, Record, RecordKind};
+use std::collections::HashMap;
+
+/// Latest reviewer assignments, keyed by fingerprint and by path prefix.
+#[derive(Debug, Default)]
+pub struct Assignments {
+    by_fingerprint: HashMap<String, AssignmentEntry>,
+    by_path: HashMap<String, AssignmentEntry>,
+}
+
+#[derive(Debug, Default)]
+struct AssignmentEntry {
+    request: Option<String>,
+    assignees: Vec<Identity>,
+}
+
+impl AssignmentEntry {
+    fn apply(&mut self, assignment: &Assignment) {
+        let same_request = assignment.request.is_some() && self.request == assignment.request;
+        if !same_request {
+            self.request = assignment.request.clone();
+            self.assignees.clear();
+        }
+        if !self.assignees.contains(&assignment.assignee) {
+            self.assignees.push(assignment.assignee.clone());
+        }
+    }
+}
+
+pub fn latest_assignments(records: &[Record]) -> Assignments {
+    let mut sorted: Vec<&Record> = records
+        .iter()
+        .filter(|record| record.kind == RecordKind::Assignment)
+        .collect();
+    sorted.sort_by_key(|record| record.timestamp);
+
+    let mut assignments = Assignments::default();
+    for record in sorted {
+        let Some(assignment) = &record.assignment else {
+            continue;
+        };
+        let entry = match &assignment.path {
+            Some(path) => assignments.by_path.entry(normalize_path(path)),
+            None => assignments.by_fingerprint.entry(record.fingerprint.clone()),
+        };
+        entry.or_default().apply(assignment);
+    }
+    assignments
+}
+
+impl Assignments {
+    /// A fingerprint assignment wins over path assignments; among paths the
+    /// longest matching prefix wins. `path` is repo-relative.
+    pub fn assignees_for(&self, path: &str, fingerprint: &str) -> &[Identity] {
+        if let Some(entry) = self.by_fingerprint.get(fingerprint) {
+            return &entry.assignees;
+        }
+        let path = normalize_path(path);
+        self.by_path
+            .iter()
+            .filter(|(prefix, _)| path_has_prefix(&path, prefix))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, entry)| entry.assignees.as_slice())
+            .unwrap_or(&[])
+    }
+
+    pub fn is_assigned_to(&self, path: &str, fingerprint: &str, identity: &Identity) -> bool {
+        self.assignees_for(path, fingerprint).contains(identity)
+    }
+}
+
+pub fn normalize_path(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    let path = path.trim_start_matches("./").trim_end_matches('/');
+    if path == "." {
+        String::new()
+    } else {
+        path.to_string()
+    }
+}
+
+pub fn path_has_prefix(path: &str, prefix: &str) -> bool {
+    if prefix.is_empty() {
+        return true;
+    }
+    match path.strip_prefix(prefix) {
+        Some(rest) => rest.is_empty() || rest.starts_with('/'),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::{RepoRef, VcsSystem};
+
+    fn email(value: &str) -> Identity {
+        Identity::Email {
+            email: value.to_string(),
+        }
+    }
+
+    fn assignment_record(
+        fingerprint: &str,
+        path: Option<&str>,
+        assignee: &str,
+        timestamp: i64,
+    ) -> Record {
+        request_record(fingerprint, path, assignee, timestamp, None)
+    }
+
+    fn request_record(
+        fingerprint: &str,
+        path: Option<&str>,
+        assignee: &str,
+        timestamp: i64,
+        request: Option<&str>,
+    ) -> Record {
+        Record {
+            id: format!("assign-{timestamp}"),
+            path_hint: path.map(str::to_string),
+            assignment: Some(Assignment {
+                assignee: email(assignee),
+                path: path.map(str::to_string),
+                request: request.map(str::to_string),
+            }),
+            ..Record::non_verdict(
+                RecordKind::Assignment,
+                fingerprint.to_string(),
+                email("lead@example.com"),
+                RepoRef::Vcs {
+                    system: VcsSystem::Git,
+                    revision: "deadbeef".to_string(),
+                },
+                timestamp,
+            )
+        }
+    }
+
+    #[test]
+    fn latest_assignment_wins() {
+        let records = vec![
+            assignment_record("abc", None, "bob@example.com", 20),
+            assignment_record("abc", None, "alice@example.com", 10),
+        ];
+        let assignments = latest_assignments(&records);
+        assert_eq!(
+            assignments.assignees_for("src/lib.rs", "abc"),
+            [email("bob@example.com")]
+        );
+        assert!(assignments.assignees_for("src/lib.rs", "other").is_empty());
+    }
+
+    #[test]
+    fn path_assignments_match_on_component_boundaries() {
+        let records = vec![
+            assignment_record("", Some("src"), "alice@example.com", 10),
+            assignment_record("", Some("./src/net/"), "bob@example.com", 11),
+        ];
+        let assignments = latest_assignments(&records);
+        assert_eq!(
+            assignments.assignees_for("src/net/http.rs", "x"),
+            [email("bob@example.com")]
+        );
+        assert_eq!(
+            assignments.assignees_for("src/main.rs", "x"),
+            [email("alice@example.com")]
+        );
+        assert!(assignments.assignees_for("srcs/main.rs", "x").is_empty());
+    }
+
+    #[test]
+    fn fingerprint_assignment_beats_path_assignment() {
+        let records = vec![
+            assignment_record("abc", None, "carol@example.com", 10),
+            assignment_record("", Some("src"), "alice@example.com", 20),
+        ];
+        let assignments = latest_assignments(&records);
+        assert_eq!(
+            assignments.assignees_for("src/lib.rs", "abc"),
+            [email("carol@example.com")]
+        );
+    }
+
+    #[test]
+    fn assignments_from_one_request_accumulate() {
+        let records = vec![
+            request_record("", Some("src/lib.rs"), "alice@example.com", 10, Some("r1")),
+            request_record("", Some("src/lib.rs"), "bob@example.com", 10, Some("r1")),
+        ];
+        let assignments = latest_assignments(&records);
+        assert_eq!(
+            assignments.assignees_for("src/lib.rs", "x"),
+            [email("alice@example.com"), email("bob@example.com")]
+        );
+
+        let mut records = records;
+        records.push(assignment_record("", Some("src"), "carol@example.com", 20));
+        records.push(assignment_record(
+            "",
+            Some("src/lib.rs"),
+            "carol@example.com",
+            20,
+        ));
+        let assignments = latest_assignments(&records);
+        assert_eq!(
+            assignments.assignees_for("src/lib.rs", "x"),
+            [email("carol@example.com")]
+        );
+    }
+}
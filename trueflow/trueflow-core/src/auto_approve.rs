@@ -0,0 +1,398 @@
+use crate::analysis::Language;
+use crate::block::{Block, BlockKind, FileState};
+use crate::commands::mark::repo_ref_from_snapshot;
+use crate::commands::review::UnreviewedFile;
+use crate::config::{AutoApproveConfig, TrueflowConfig};
+use crate::generated::GENERATED_TAG;
+use crate::hashing::hash_str;
+use crate::lineage::{normalize, normalized_hash};
+use crate::path_tags::path_globs;
+use crate::policy::{counted_records, review_state};
+use crate::scanner;
+use crate::store::{
+    BlockState, Identity, Provenance, Record, Verdict, approved_hashes_from_verdicts, open_store,
+};
+use crate::vcs;
+use anyhow::{Context, Result};
+use globset::GlobSet;
+use log::info;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const AUTO_APPROVE_MACHINE: &str = "trueflow-auto-approve";
+pub const AUTO_APPROVED_TAG: &str = "auto-approved";
+
+/// Why a block can be approved without a reviewer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AutoApproveRule {
+    /// A short block of one of the `kinds`.
+    Trivial,
+    /// A file matching one of the `paths` globs.
+    Path,
+    /// A lockfile or a file detected as generated.
+    Generated,
+    /// Normalizes like an approved block elsewhere in the tree.
+    Duplicate { fingerprint: String, path: String },
+}
+
+impl AutoApproveRule {
+    /// Summary bucket: the block kind for trivial blocks, else the rule.
+    fn summary_key(&self, block: &Block) -> String {
+        match self {
+            AutoApproveRule::Trivial => block.kind.to_string(),
+            AutoApproveRule::Path => "path".to_string(),
+            AutoApproveRule::Generated => "generated".to_string(),
+            AutoApproveRule::Duplicate { .. } => "duplicate".to_string(),
+        }
+    }
+
+    /// Why the rule applies, e.g. `gap block, 1 line(s)`.
+    pub fn reason(&self, block: &Block) -> String {
+        match self {
+            AutoApproveRule::Trivial => {
+                format!("{} block, {} line(s)", block.kind, block.metrics.line_count)
+            }
+            AutoApproveRule::Path => "path matches [auto_approve] paths".to_string(),
+            AutoApproveRule::Generated => "generated file".to_string(),
+            AutoApproveRule::Duplicate { fingerprint, path } => format!(
+                "same as approved {} in {}",
+                fingerprint.get(..12).unwrap_or(fingerprint),
+                path
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct AutoApproveSummary {
+    pub by_rule: BTreeMap<String, usize>,
+}
+
+impl AutoApproveSummary {
+    pub fn total(&self) -> usize {
+        self.by_rule.values().sum()
+    }
+
+    fn add(&mut self, rule: &AutoApproveRule, block: &Block) {
+        *self.by_rule.entry(rule.summary_key(block)).or_default() += 1;
+    }
+
+    /// Counts per rule, e.g. `gap 2, generated 14`.
+    pub fn breakdown(&self) -> String {
+        self.by_rule
+            .iter()
+            .map(|(rule, count)| format!("{rule} {count}"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+impl fmt::Display for AutoApproveSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Auto-approved {} block(s): {}",
+            self.total(),
+            self.breakdown()
+        )
+    }
+}
+
+/// Machine approvals get their own identity type so they can be told apart
+/// from human and agent verdicts.
+pub fn auto_approve_identity() -> Identity {
+    Identity::Machine {
+        name: AUTO_APPROVE_MACHINE.to_string(),
+        version: Some(env!("CARGO_PKG_VERSION").to_string()),
+    }
+}
+
+/// `[auto_approve]` compiled against the current ledger.
+pub struct AutoApprover {
+    kinds: HashSet<BlockKind>,
+    max_lines: usize,
+    paths: GlobSet,
+    generated: bool,
+    /// Normalized hash of each approved block in the tree, with its
+    /// fingerprint and path. Empty unless `duplicates` is set.
+    approved: HashMap<String, (String, String)>,
+}
+
+impl AutoApprover {
+    pub fn new(config: &AutoApproveConfig) -> Result<Self> {
+        Ok(Self {
+            kinds: config.eligible_kinds(),
+            max_lines: config.max_lines,
+            paths: path_globs(&config.paths).context("Invalid [auto_approve] paths glob")?,
+            generated: config.generated,
+            approved: HashMap::new(),
+        })
+    }
+
+    /// Rules from `config`, with the approved blocks of the tree indexed
+    /// when `duplicates` is set.
+    pub fn load(config: &TrueflowConfig) -> Result<Self> {
+        let mut approver = Self::new(&config.auto_approve)?;
+        if config.auto_approve.duplicates {
+            let history = counted_records(open_store()?.read_history()?, &config.policy);
+            let approved =
+                approved_hashes_from_verdicts(&review_state(&history, &config.policy, None));
+            let paths: HashSet<String> = history
+                .iter()
+                .filter(|record| approved.contains(&record.fingerprint))
+                .filter_map(|record| record.path_hint.clone())
+                .collect();
+            if !paths.is_empty() {
+                let files = scanner::scan_directory_filtered(".", |path| paths.contains(path))?;
+                approver.index_approved(&files, &approved);
+            }
+        }
+        Ok(approver)
+    }
+
+    fn index_approved(&mut self, files: &[FileState], approved: &HashSet<String>) {
+        for file in files {
+            for block in file
+                .blocks
+                .iter()
+                .filter(|block| approved.contains(&block.hash))
+            {
+                let normalized = normalize(&block.content, &file.language);
+                // Blank and comment-only blocks normalize to nothing.
+                if normalized.is_empty() {
+                    continue;
+                }
+                self.approved
+                    .entry(hash_str(&normalized))
+                    .or_insert_with(|| (block.hash.clone(), file.path.clone()));
+            }
+        }
+    }
+
+    /// The first rule `block` of the file at `path` satisfies, if any.
+    pub fn rule_for(
+        &self,
+        path: &str,
+        language: &Language,
+        block: &Block,
+    ) -> Option<AutoApproveRule> {
+        if self.generated && block.tags.iter().any(|tag| tag == GENERATED_TAG) {
+            return Some(AutoApproveRule::Generated);
+        }
+        if self.paths.is_match(path) {
+            return Some(AutoApproveRule::Path);
+        }
+        if self.kinds.contains(&block.kind) && block.metrics.line_count <= self.max_lines {
+            return Some(AutoApproveRule::Trivial);
+        }
+        if self.approved.is_empty() {
+            return None;
+        }
+        let (fingerprint, path) = self
+            .approved
+            .get(&normalized_hash(&block.content, language))?;
+        (*fingerprint != block.hash).then(|| AutoApproveRule::Duplicate {
+            fingerprint: fingerprint.clone(),
+            path: path.clone(),
+        })
+    }
+
+    /// (path, block, rule) for every block a rule approves, in review order.
+    pub fn select<'a>(
+        &self,
+        files: &'a [UnreviewedFile],
+    ) -> Vec<(&'a str, &'a Block, AutoApproveRule)> {
+        files
+            .iter()
+            .flat_map(|file| {
+                file.blocks.iter().filter_map(move |block| {
+                    let rule = self.rule_for(&file.path, &file.language, block)?;
+                    Some((file.path.as_str(), block, rule))
+                })
+            })
+            .collect()
+    }
+}
+
+/// The pass `review` and the TUI run when `[auto_approve] enabled` is set.
+pub fn approve_enabled_rules(
+    files: &[UnreviewedFile],
+    config: &TrueflowConfig,
+) -> Result<AutoApproveSummary> {
+    if !config.auto_approve.enabled {
+        return Ok(AutoApproveSummary::default());
+    }
+    let selected = AutoApprover::load(config)?.select(files);
+    record_approvals(&selected)
+}
+
+/// Records an approval for every selected block. Machine approvals are not
+/// signed; the identity, tag, and note are the audit trail.
+pub fn record_approvals(
+    selected: &[(&str, &Block, AutoApproveRule)],
+) -> Result<AutoApproveSummary> {
+    let mut summary = AutoApproveSummary::default();
+    if selected.is_empty() {
+        return Ok(summary);
+    }
+
+    let store = open_store()?;
+    let identity = auto_approve_identity();
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+    let repo_snapshot = vcs::snapshot_from_workdir();
+    let repo_ref = repo_ref_from_snapshot(&repo_snapshot);
+
+    for (path, block, rule) in selected {
+        let block_state: BlockState =
+            vcs::block_state_for_path(&repo_snapshot, Some(path), &block.hash).into();
+        store.append(Record {
+            path_hint: Some(path.to_string()),
+            line_hint: Some(block.start_line as u32),
+            note: Some(format!("auto-approved: {}", rule.reason(block))),
+            tags: Some(vec![AUTO_APPROVED_TAG.to_string()]),
+            provenance: Some(Provenance {
+                tool: Some(AUTO_APPROVE_MACHINE.to_string()),
+                session: None,
+                prompt: None,
+            }),
+            ..Record::verdict(
+                block.hash.clone(),
+                "review".to_string(),
+                Verdict::Approved,
+                identity.clone(),
+                repo_ref.clone(),
+                block_state,
+                now,
+            )
+        })?;
+        summary.add(rule, block);
+    }
+    info!("{}", summary);
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(kind: BlockKind, content: &str) -> Block {
+        Block::new(content.to_string(), kind, 0, content.lines().count())
+    }
+
+    fn rules(approver: &AutoApprover, files: &[UnreviewedFile]) -> Vec<AutoApproveRule> {
+        approver
+            .select(files)
+            .into_iter()
+            .map(|(_, _, rule)| rule)
+            .collect()
+    }
+
+    #[test]
+    fn selects_small_blocks_of_eligible_kinds() {
+        let config = AutoApproveConfig {
+            enabled: true,
+            max_lines: 2,
+            ..AutoApproveConfig::default()
+        };
+        let files = vec![UnreviewedFile {
+            path: "src/lib.rs".to_string(),
+            language: Language::Rust,
+            blocks: vec![
+                block(BlockKind::Gap, "\n"),
+                block(BlockKind::Comment, "// one\n// two\n"),
+                block(BlockKind::Comment, "// one\n// two\n// three\n"),
+                block(BlockKind::Function, "fn x() {}\n"),
+            ],
+        }];
+
+        let selected: Vec<BlockKind> = AutoApprover::new(&config)
+            .unwrap()
+            .select(&files)
+            .into_iter()
+            .map(|(_, block, _)| block.kind.clone())
+            .collect();
+        assert_eq!(selected, vec![BlockKind::Gap, BlockKind::Comment]);
+    }
+
+    #[test]
+    fn paths_and_generated_files_approve_whole_files() {
+        let config = AutoApproveConfig {
+            kinds: Vec::new(),
+            paths: vec!["generated/**".to_string()],
+            generated: true,
+            ..AutoApproveConfig::default()
+        };
+        let mut lock = block(BlockKind::Code, "[[package]]\nname = \"x\"\n");
+        lock.tags.push(GENERATED_TAG.to_string());
+        let files = vec![
+            UnreviewedFile {
+                path: "generated/api.rs".to_string(),
+                language: Language::Rust,
+                blocks: vec![block(BlockKind::Function, "fn api() {}\n")],
+            },
+            UnreviewedFile {
+                path: "Cargo.lock".to_string(),
+                language: Language::Unknown,
+                blocks: vec![lock],
+            },
+            UnreviewedFile {
+                path: "src/lib.rs".to_string(),
+                language: Language::Rust,
+                blocks: vec![block(BlockKind::Function, "fn lib() {}\n")],
+            },
+        ];
+
+        let approver = AutoApprover::new(&config).unwrap();
+        assert_eq!(
+            rules(&approver, &files),
+            vec![AutoApproveRule::Path, AutoApproveRule::Generated]
+        );
+    }
+
+    #[test]
+    fn duplicates_match_approved_blocks_after_normalizing() {
+        let config = AutoApproveConfig {
+            kinds: Vec::new(),
+            duplicates: true,
+            ..AutoApproveConfig::default()
+        };
+        let approved = block(
+            BlockKind::Function,
+            "fn add(a: u32, b: u32) -> u32 {\n    let sum = a + b;\n    sum\n}\n",
+        );
+        let mut approver = AutoApprover::new(&config).unwrap();
+        approver.index_approved(
+            &[FileState {
+                path: "src/math.rs".to_string(),
+                language: Language::Rust,
+                file_hash: String::new(),
+                generated: false,
+                blocks: vec![approved.clone()],
+            }],
+            &HashSet::from([approved.hash.clone()]),
+        );
+
+        let files = vec![UnreviewedFile {
+            path: "src/util.rs".to_string(),
+            language: Language::Rust,
+            blocks: vec![
+                block(
+                    BlockKind::Function,
+                    "fn add(a: u32, b: u32) -> u32 {\n    // Sum them.\n    let total = a + b;\n    total\n}\n",
+                ),
+                block(
+                    BlockKind::Function,
+                    "fn sub(a: u32, b: u32) -> u32 {\n    a - b\n}\n",
+                ),
+            ],
+        }];
+        assert_eq!(
+            rules(&approver, &files),
+            vec![AutoApproveRule::Duplicate {
+                fingerprint: approved.hash.clone(),
+                path: "src/math.rs".to_string(),
+            }]
+        );
+    }
+}
@@ -0,0 +1,245 @@
+use crate::block::Block;
+use anyhow::{Context, Result};
+use gix::bstr::ByteSlice;
+use gix::diff::blob::intern::InternedInput;
+use gix::diff::blob::{Algorithm, diff};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::path::Path;
+
+/// Who wrote a block's lines: `git blame` of HEAD, with lines changed since
+/// HEAD counted as uncommitted.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+pub struct BlockBlame {
+    /// Authors of the committed lines, most lines first.
+    pub authors: Vec<BlameAuthor>,
+    /// Lines added or changed since the last commit.
+    pub uncommitted_lines: usize,
+    /// The newest commit among the committed lines.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_commit: Option<BlameCommit>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+pub struct BlameAuthor {
+    pub name: String,
+    pub email: String,
+    pub lines: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+pub struct BlameCommit {
+    pub id: String,
+    pub author: String,
+    /// Author time, in seconds since the epoch.
+    pub time: i64,
+    pub summary: String,
+}
+
+impl BlameCommit {
+    pub fn short_id(&self) -> &str {
+        self.id.get(..7).unwrap_or(&self.id)
+    }
+}
+
+/// The blame of one file's working copy, line by line.
+pub struct FileBlame {
+    /// For each working-copy line, the commit that wrote it, or `None` when
+    /// it changed since HEAD.
+    lines: Vec<Option<gix::ObjectId>>,
+    commits: HashMap<gix::ObjectId, Commit>,
+}
+
+struct Commit {
+    name: String,
+    email: String,
+    time: i64,
+    summary: String,
+}
+
+impl FileBlame {
+    /// Blames `path` (repo-relative) at HEAD and maps its lines onto
+    /// `working`, the file as it is now.
+    pub fn for_path(repo: &gix::Repository, path: &str, working: &str) -> Result<Self> {
+        let working_lines = working.lines().count();
+        let Some(head) = repo.head_id().ok().map(|id| id.detach()) else {
+            return Ok(Self::uncommitted(working_lines));
+        };
+        let in_head = repo
+            .head_tree()?
+            .lookup_entry_by_path(Path::new(path))?
+            .is_some();
+        if !in_head {
+            return Ok(Self::uncommitted(working_lines));
+        }
+
+        let outcome = repo
+            .blame_file(path.as_bytes().as_bstr(), head, Default::default())
+            .with_context(|| format!("Failed to blame {path}"))?;
+        let mut head_lines = Vec::new();
+        for entry in &outcome.entries {
+            let start = entry.start_in_blamed_file as usize;
+            let len = entry.len.get() as usize;
+            if head_lines.len() < start + len {
+                head_lines.resize(start + len, None);
+            }
+            for line in &mut head_lines[start..start + len] {
+                *line = Some(entry.commit_id);
+            }
+        }
+
+        let committed = String::from_utf8_lossy(&outcome.blob);
+        let lines = map_lines(&committed, working)
+            .into_iter()
+            .map(|head_line| head_line.and_then(|line| head_lines.get(line).copied().flatten()))
+            .collect::<Vec<_>>();
+
+        let mut commits = HashMap::new();
+        for id in lines.iter().flatten() {
+            if commits.contains_key(id) {
+                continue;
+            }
+            let commit = repo.find_commit(*id)?;
+            let author = commit.author()?;
+            commits.insert(
+                *id,
+                Commit {
+                    name: author.name.trim().to_str_lossy().into_owned(),
+                    email: author.email.trim().to_str_lossy().into_owned(),
+                    time: author.seconds(),
+                    summary: commit
+                        .message()
+                        .map(|message| message.summary().to_str_lossy().to_string())
+                        .unwrap_or_default(),
+                },
+            );
+        }
+        Ok(Self { lines, commits })
+    }
+
+    fn uncommitted(lines: usize) -> Self {
+        Self {
+            lines: vec![None; lines],
+            commits: HashMap::new(),
+        }
+    }
+
+    /// Who wrote `block`'s lines.
+    pub fn block(&self, block: &Block) -> BlockBlame {
+        self.range(block.start_line..block.end_line.max(block.start_line + 1))
+    }
+
+    /// Ids of the commits that wrote `block`'s committed lines.
+    pub fn commit_ids(&self, block: &Block) -> HashSet<String> {
+        self.lines
+            .get(block.start_line..block.end_line.min(self.lines.len()))
+            .unwrap_or_default()
+            .iter()
+            .flatten()
+            .map(|id| id.to_string())
+            .collect()
+    }
+
+    /// How many commits since `since` (seconds since the epoch) last
+    /// changed some of `block`'s lines.
+    pub fn churn(&self, block: &Block, since: i64) -> usize {
+        self.lines
+            .get(block.start_line..block.end_line.min(self.lines.len()))
+            .unwrap_or_default()
+            .iter()
+            .flatten()
+            .filter(|id| self.commits[*id].time >= since)
+            .collect::<HashSet<_>>()
+            .len()
+    }
+
+    fn range(&self, lines: Range<usize>) -> BlockBlame {
+        let mut uncommitted_lines = 0;
+        let mut by_commit: HashMap<gix::ObjectId, usize> = HashMap::new();
+        for line in lines {
+            match self.lines.get(line).copied().flatten() {
+                Some(id) => *by_commit.entry(id).or_default() += 1,
+                None => uncommitted_lines += 1,
+            }
+        }
+
+        let mut by_author: HashMap<&str, BlameAuthor> = HashMap::new();
+        for (id, count) in &by_commit {
+            let commit = &self.commits[id];
+            by_author
+                .entry(&commit.email)
+                .or_insert_with(|| BlameAuthor {
+                    name: commit.name.clone(),
+                    email: commit.email.clone(),
+                    lines: 0,
+                })
+                .lines += count;
+        }
+        let mut authors: Vec<BlameAuthor> = by_author.into_values().collect();
+        authors.sort_by(|a, b| b.lines.cmp(&a.lines).then_with(|| a.name.cmp(&b.name)));
+
+        let last_commit = by_commit
+            .keys()
+            .max_by_key(|id| (self.commits[*id].time, **id))
+            .map(|id| {
+                let commit = &self.commits[id];
+                BlameCommit {
+                    id: id.to_string(),
+                    author: commit.name.clone(),
+                    time: commit.time,
+                    summary: commit.summary.clone(),
+                }
+            });
+        BlockBlame {
+            authors,
+            uncommitted_lines,
+            last_commit,
+        }
+    }
+}
+
+/// For each line of `new`, the line of `old` it is unchanged from.
+fn map_lines(old: &str, new: &str) -> Vec<Option<usize>> {
+    let input = InternedInput::new(old, new);
+    let mut hunks: Vec<(Range<u32>, Range<u32>)> = Vec::new();
+    diff(
+        Algorithm::Histogram,
+        &input,
+        |before: Range<u32>, after: Range<u32>| {
+            hunks.push((before, after));
+        },
+    );
+
+    let mut mapped = Vec::with_capacity(input.after.len());
+    let (mut old_at, mut new_at) = (0, 0);
+    for (before, after) in hunks {
+        while new_at < after.start as usize {
+            mapped.push(Some(old_at));
+            old_at += 1;
+            new_at += 1;
+        }
+        mapped.extend(after.clone().map(|_| None));
+        old_at = before.end as usize;
+        new_at = after.end as usize;
+    }
+    while new_at < input.after.len() {
+        mapped.push(Some(old_at));
+        old_at += 1;
+        new_at += 1;
+    }
+    mapped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unchanged_lines_keep_their_committed_line() {
+        let old = "a\nb\nc\nd\n";
+        let new = "a\nx\nc\nd\ne\n";
+        assert_eq!(map_lines(old, new), [Some(0), None, Some(2), Some(3), None]);
+    }
+}
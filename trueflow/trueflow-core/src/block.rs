@@ -1,10 +1,17 @@
 use crate::analysis::Language;
+use crate::blame::BlockBlame;
+use crate::prereview::PrereviewHint;
+use crate::risk::BlockRisk;
+use crate::vcs::CommitInfo;
 use anyhow::anyhow;
+use regex::Regex;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::str::FromStr;
+use std::sync::OnceLock;
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Default, JsonSchema)]
 pub enum BlockKind {
     #[serde(rename = "TextBlock")]
     #[default]
@@ -79,6 +86,16 @@ pub enum BlockKind {
     Imports,
     #[serde(rename = "FunctionSignature")]
     FunctionSignature,
+    #[serde(rename = "Constants")]
+    Constants,
+    #[serde(rename = "binary")]
+    Binary,
+    #[serde(rename = "statement")]
+    Statement,
+    #[serde(rename = "entry")]
+    Entry,
+    #[serde(rename = "submodule")]
+    Submodule,
 }
 
 impl BlockKind {
@@ -101,7 +118,7 @@ impl BlockKind {
             | BlockKind::Interface
             | BlockKind::Class => 0,
 
-            BlockKind::Const | BlockKind::Static => 20,
+            BlockKind::Const | BlockKind::Static | BlockKind::Constants => 20,
             BlockKind::FunctionSignature => 30,
             BlockKind::Impl => 40,
             BlockKind::Function | BlockKind::Method => 50,
@@ -150,6 +167,11 @@ impl BlockKind {
             BlockKind::Sentence => "Sentence",
             BlockKind::Imports => "Imports",
             BlockKind::FunctionSignature => "FunctionSignature",
+            BlockKind::Constants => "Constants",
+            BlockKind::Binary => "binary",
+            BlockKind::Statement => "statement",
+            BlockKind::Entry => "entry",
+            BlockKind::Submodule => "submodule",
         }
     }
 }
@@ -206,6 +228,11 @@ impl FromStr for BlockKind {
             "sentence" => BlockKind::Sentence,
             "imports" => BlockKind::Imports,
             "functionsignature" | "signature" => BlockKind::FunctionSignature,
+            "constants" => BlockKind::Constants,
+            "binary" => BlockKind::Binary,
+            "statement" => BlockKind::Statement,
+            "entry" => BlockKind::Entry,
+            "submodule" => BlockKind::Submodule,
             _ => {
                 return Err(anyhow!("Unknown block kind: {}", value));
             }
@@ -215,7 +242,8 @@ impl FromStr for BlockKind {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A unit of review: a span of a file, fingerprinted by its content.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Block {
     /// The content-addressable identity of this block
     pub hash: String,
@@ -240,24 +268,147 @@ pub struct Block {
 
     /// 0-indexed end line (exclusive)
     pub end_line: usize,
+
+    /// Size metrics derived from `content`
+    #[serde(flatten)]
+    pub metrics: BlockMetrics,
+
+    /// The approved version this block was edited from, when review found one
+    #[serde(flatten)]
+    pub previous: Option<PreviousApproval>,
+
+    /// Who wrote the block's lines, when the output asked for it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blame: Option<BlockBlame>,
+
+    /// Commits of a `rev:` review that wrote some of the block's lines
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub commits: Vec<CommitInfo>,
+
+    /// The latest `trueflow prereview` annotation; a hint, not a verdict
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prereview: Option<PrereviewHint>,
+
+    /// Risk score from complexity, churn and `[risk.paths]`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub risk: Option<BlockRisk>,
+}
+
+/// An approved block as it was at the revision the approval was recorded
+/// at, so a reviewer can see what changed since.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+pub struct PreviousApproval {
+    pub previous_fingerprint: String,
+    pub previous_revision: String,
+    pub previous_content: String,
+}
+
+impl PreviousApproval {
+    /// The approval's revision, abbreviated the way `git log --oneline` does.
+    pub fn short_revision(&self) -> &str {
+        self.previous_revision
+            .get(..7)
+            .unwrap_or(&self.previous_revision)
+    }
 }
 
 impl Block {
     pub fn new(content: String, kind: BlockKind, start_line: usize, end_line: usize) -> Self {
         Self {
             hash: crate::hashing::hash_str(&content),
+            metrics: BlockMetrics::from_content(&content),
             content,
             kind,
             tags: Vec::new(),
             complexity: 0,
             start_line,
             end_line,
+            previous: None,
+            blame: None,
+            commits: Vec::new(),
+            prereview: None,
+            risk: None,
         }
     }
 
     pub fn span(&self) -> Span {
         Span::new(self.start_line, self.end_line)
     }
+
+    /// The name the block declares (`fn parse` gives `parse`, `impl Display
+    /// for Id` gives `Display for Id`), from its first declaration line.
+    pub fn symbol(&self) -> Option<String> {
+        let line = self
+            .content
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty() && !is_preamble_line(line))?;
+        let captures = declaration_regex().captures(line)?;
+        let rest = captures.get(2)?.as_str();
+        let name = if &captures[1] == "impl" {
+            let rest = rest.trim_start();
+            let rest = match rest.strip_prefix('<') {
+                Some(generics) => generics.split_once('>').map_or("", |(_, rest)| rest),
+                None => rest,
+            };
+            rest.split(['{', ';'])
+                .next()
+                .and_then(|head| head.split(" where ").next())
+                .unwrap_or_default()
+                .trim()
+                .to_string()
+        } else {
+            rest.trim_start()
+                .split(|ch: char| !(ch.is_alphanumeric() || ch == '_' || ch == '-' || ch == '$'))
+                .next()
+                .unwrap_or_default()
+                .to_string()
+        };
+        (!name.is_empty()).then_some(name)
+    }
+}
+
+static DECLARATION: OnceLock<Regex> = OnceLock::new();
+
+/// A declaration keyword after any visibility or modifier words, and the
+/// text following it.
+fn declaration_regex() -> &'static Regex {
+    DECLARATION.get_or_init(|| {
+        Regex::new(
+            r#"^\(?(?:(?:pub(?:\([^)]*\))?|export|default|async|unsafe|const|static|abstract|extern\s+"[^"]*")\s+)*(fn|struct|enum|trait|union|type|mod|impl|class|def|function\*?|interface|defun|defmacro|defvar|defcustom)\b(.*)$"#,
+        )
+        .expect("valid declaration regex")
+    })
+}
+
+/// Attributes, decorators and comments that may precede a declaration.
+fn is_preamble_line(line: &str) -> bool {
+    ["#[", "#!", "@", "//", "/*", "*", ";", "#"]
+        .iter()
+        .any(|prefix| line.starts_with(prefix))
+}
+
+/// Approximate bytes per LLM token, used for budgeting context windows.
+const BYTES_PER_TOKEN: usize = 4;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct BlockMetrics {
+    pub line_count: usize,
+    pub byte_count: usize,
+    /// Approximate token count (bytes / 4, rounded up)
+    pub token_count: usize,
+}
+
+impl BlockMetrics {
+    pub fn from_content(content: &str) -> Self {
+        let byte_count = content.len();
+        Self {
+            line_count: content.lines().count(),
+            byte_count,
+            token_count: byte_count.div_ceil(BYTES_PER_TOKEN),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -280,13 +431,16 @@ impl Span {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct FileState {
     pub path: String,
     #[serde(default)]
     pub language: Language,
     /// The hash of the entire file (e.g. Merkle root of blocks)
     pub file_hash: String,
+    /// Whether the file was detected as generated (lockfile, `@generated`, ...)
+    #[serde(default)]
+    pub generated: bool,
     pub blocks: Vec<Block>,
 }
 
@@ -294,6 +448,31 @@ pub struct FileState {
 mod tests {
     use super::*;
 
+    fn symbol(content: &str) -> Option<String> {
+        Block::new(content.to_string(), BlockKind::Code, 0, 1).symbol()
+    }
+
+    #[test]
+    fn symbol_names_the_declared_item() {
+        assert_eq!(
+            symbol("/// Docs\n#[inline]\npub(crate) async fn parse(x: u8) {}").as_deref(),
+            Some("parse")
+        );
+        assert_eq!(
+            symbol("impl<T: Clone> fmt::Display for Id<T> {\n}").as_deref(),
+            Some("fmt::Display for Id<T>")
+        );
+        assert_eq!(
+            symbol("@cached\ndef load(path):\n").as_deref(),
+            Some("load")
+        );
+        assert_eq!(
+            symbol("(defun trueflow-review (arg)\n)").as_deref(),
+            Some("trueflow-review")
+        );
+        assert_eq!(symbol("let x = 1;"), None);
+    }
+
     #[test]
     fn test_block_kind_serialization_round_trip() {
         // List all variants to ensure full coverage
@@ -334,6 +513,10 @@ mod tests {
             BlockKind::Sentence,
             BlockKind::Imports,
             BlockKind::FunctionSignature,
+            BlockKind::Constants,
+            BlockKind::Binary,
+            BlockKind::Statement,
+            BlockKind::Entry,
         ];
 
         for kind in kinds {
@@ -381,6 +564,16 @@ mod tests {
         assert_eq!(BlockKind::from_str("code").unwrap(), BlockKind::Code);
     }
 
+    #[test]
+    fn test_block_metrics_from_content() {
+        let metrics = BlockMetrics::from_content("fn main() {\n    run();\n}\n");
+        assert_eq!(metrics.line_count, 3);
+        assert_eq!(metrics.byte_count, 25);
+        assert_eq!(metrics.token_count, 7);
+
+        assert_eq!(BlockMetrics::from_content(""), BlockMetrics::default());
+    }
+
     #[test]
     fn test_span_overlap_logic() {
         let base = Span::new(0, 10);
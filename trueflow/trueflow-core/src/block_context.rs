@@ -0,0 +1,147 @@
+use crate::analysis::Language;
+use crate::block::{Block, FileState};
+use crate::scanner::{self, ScanSettings};
+use crate::store::BlockContext;
+use anyhow::Result;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// Scanned files to describe reviewed blocks from, for `Record::context`.
+pub struct ContextIndex {
+    files: HashMap<String, FileState>,
+    base_revision: Option<String>,
+}
+
+impl ContextIndex {
+    /// Scans `paths` (relative to the working directory). Nothing is read
+    /// when `paths` is empty.
+    pub fn scan(paths: &HashSet<String>, base_revision: Option<String>) -> Result<Self> {
+        Self::scan_in(
+            Path::new("."),
+            &ScanSettings::configured()?,
+            paths,
+            base_revision,
+        )
+    }
+
+    /// [`ContextIndex::scan`] with `paths` relative to `root`.
+    pub fn scan_in(
+        root: &Path,
+        settings: &ScanSettings,
+        paths: &HashSet<String>,
+        base_revision: Option<String>,
+    ) -> Result<Self> {
+        let files = if paths.is_empty() {
+            HashMap::new()
+        } else {
+            scanner::scan_directory_with(root, settings, |path| paths.contains(path))?
+                .into_iter()
+                .filter(|file| paths.contains(&file.path))
+                .map(|file| (file.path.clone(), file))
+                .collect()
+        };
+        Ok(Self {
+            files,
+            base_revision,
+        })
+    }
+
+    /// Scans every file under `root`, for verdicts that don't name theirs.
+    pub fn scan_all_in(
+        root: &Path,
+        settings: &ScanSettings,
+        base_revision: Option<String>,
+    ) -> Result<Self> {
+        let files = scanner::scan_directory_with(root, settings, |_| true)?
+            .into_iter()
+            .map(|file| (file.path.clone(), file))
+            .collect();
+        Ok(Self {
+            files,
+            base_revision,
+        })
+    }
+
+    /// The first scanned file, by path, with a block `fingerprint`.
+    pub fn locate(&self, fingerprint: &str) -> Option<&str> {
+        self.files
+            .values()
+            .filter(|file| file.blocks.iter().any(|block| block.hash == fingerprint))
+            .map(|file| file.path.as_str())
+            .min()
+    }
+
+    /// The scanned block `fingerprint` of `path`, with the file's language.
+    pub fn block(&self, path: &str, fingerprint: &str) -> Option<(&Language, &Block)> {
+        let file = self.files.get(path)?;
+        let block = file.blocks.iter().find(|block| block.hash == fingerprint)?;
+        Some((&file.language, block))
+    }
+
+    /// Context for a verdict on `fingerprint` in `path`. A fingerprint not
+    /// among the file's blocks (a sub-block, say) is placed by `line`.
+    pub fn context(
+        &self,
+        path: Option<&str>,
+        fingerprint: &str,
+        line: Option<u32>,
+    ) -> Option<BlockContext> {
+        let mut context = BlockContext {
+            base_revision: self.base_revision.clone(),
+            ..Default::default()
+        };
+        if let Some(file) = path.and_then(|path| self.files.get(path)) {
+            context.language = serde_json::to_value(&file.language)
+                .ok()
+                .and_then(|value| value.as_str().map(str::to_string))
+                .filter(|language| language != "Unknown");
+            context.file_hash = Some(file.file_hash.clone());
+            let block = file.blocks.iter().find(|block| block.hash == fingerprint);
+            context.block_kind = block.map(|block| block.kind.as_str().to_string());
+            let line = block
+                .map(|block| block.start_line)
+                .or(line.map(|line| line as usize));
+            context.symbol = line.and_then(|line| containing_symbol(&file.blocks, line));
+        }
+        (!context.is_empty()).then_some(context)
+    }
+}
+
+/// Name of the narrowest named block covering `line`.
+fn containing_symbol(blocks: &[Block], line: usize) -> Option<String> {
+    blocks
+        .iter()
+        .filter(|block| block.start_line <= line && line < block.end_line.max(block.start_line + 1))
+        .filter_map(|block| {
+            Some((
+                block.end_line.saturating_sub(block.start_line),
+                block.symbol()?,
+            ))
+        })
+        .min_by_key(|(span, _)| *span)
+        .map(|(_, symbol)| symbol)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::BlockKind;
+
+    #[test]
+    fn symbol_comes_from_the_narrowest_named_block() {
+        let blocks = vec![
+            Block::new(
+                "impl Parser {\n    fn parse() {}\n}\n".to_string(),
+                BlockKind::Impl,
+                0,
+                3,
+            ),
+            Block::new("fn parse() {}\n".to_string(), BlockKind::Function, 1, 2),
+            Block::new("// trailing\n".to_string(), BlockKind::Comment, 3, 4),
+        ];
+
+        assert_eq!(containing_symbol(&blocks, 1).as_deref(), Some("parse"));
+        assert_eq!(containing_symbol(&blocks, 2).as_deref(), Some("Parser"));
+        assert_eq!(containing_symbol(&blocks, 3), None);
+    }
+}
@@ -1,5 +1,5 @@
 use crate::analysis::Language;
-use crate::block::{Block, BlockKind};
+use crate::block::{Block, BlockKind, BlockMetrics};
 use crate::complexity;
 use crate::hashing::hash_str;
 use crate::text_split::split_by_paragraph_breaks;
@@ -19,6 +19,11 @@ pub fn split(content: &str, lang: Language) -> Result<Vec<Block>> {
             info!("block_splitter done (blocks={})", blocks.len());
             return Ok(blocks);
         }
+        Language::Sql => {
+            let blocks = split_sql(content);
+            info!("block_splitter done (blocks={})", blocks.len());
+            return Ok(blocks);
+        }
         _ if lang.uses_text_fallback() => {
             let blocks = split_paragraphs(content, lang);
             info!("block_splitter done (blocks={})", blocks.len());
@@ -36,6 +41,9 @@ pub fn split(content: &str, lang: Language) -> Result<Vec<Block>> {
         Language::TypeScript => Some(tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into()),
         Language::Python => Some(tree_sitter_python::LANGUAGE.into()),
         Language::Shell => Some(tree_sitter_bash::LANGUAGE.into()),
+        Language::C => Some(tree_sitter_c::LANGUAGE.into()),
+        Language::Cpp => Some(tree_sitter_cpp::LANGUAGE.into()),
+        Language::Yaml => Some(tree_sitter_yaml::LANGUAGE.into()),
         _ => None,
     };
 
@@ -61,9 +69,13 @@ pub fn split(content: &str, lang: Language) -> Result<Vec<Block>> {
     let mut pending_end: usize = 0;
 
     // Iterate over children of root
-    for child in root.children(&mut cursor) {
+    for child in top_level_nodes(root, &lang, &mut cursor) {
         let start_byte = child.start_byte();
-        let end_byte = child.end_byte();
+        if start_byte < last_end_byte {
+            // Already absorbed into the previous item.
+            continue;
+        }
+        let end_byte = item_end_byte(child, &lang, content);
         let ts_kind = child.kind();
         let is_test = is_test_span(&test_ranges, crate::block::Span::new(start_byte, end_byte));
 
@@ -75,9 +87,18 @@ pub fn split(content: &str, lang: Language) -> Result<Vec<Block>> {
                     || ts_kind == "block_comment"
             }
             Language::Python => ts_kind == "decorator",
+            Language::C | Language::Cpp | Language::Yaml => ts_kind == "comment",
             _ => false,
         };
 
+        // `}  // namespace demo` closes the previous line; leave it in the gap.
+        if is_attribute
+            && matches!(lang, Language::C | Language::Cpp | Language::Yaml)
+            && is_trailing_comment(content, start_byte)
+        {
+            continue;
+        }
+
         if is_attribute {
             if pending_start.is_none() {
                 // First attribute in a potential group. Handle gap prior to it.
@@ -124,14 +145,12 @@ pub fn split(content: &str, lang: Language) -> Result<Vec<Block>> {
         };
 
         let node_content = &content[block_start..end_byte];
-        let mut block = create_block(
-            node_content,
-            map_kind(lang.clone(), ts_kind),
-            content,
-            block_start,
-            end_byte,
-            &lang,
-        );
+        let kind = match lang {
+            Language::C | Language::Cpp => map_c_kind(child),
+            Language::Yaml => map_yaml_kind(ts_kind),
+            _ => map_kind(lang.clone(), ts_kind),
+        };
+        let mut block = create_block(node_content, kind, content, block_start, end_byte, &lang);
         if is_test {
             block.tags.push("test".to_string());
         }
@@ -267,6 +286,127 @@ fn split_paragraphs(content: &str, lang: Language) -> Vec<Block> {
     })
 }
 
+/// One `statement` block per `;`-terminated SQL statement, with its leading
+/// comments and any `-- comment` after the `;` on the same line.
+fn split_sql(content: &str) -> Vec<Block> {
+    let mut ends = sql_statement_ends(content);
+    if ends.last() != Some(&content.len()) {
+        ends.push(content.len());
+    }
+
+    let mut blocks = Vec::new();
+    let mut start = 0;
+    for end in ends {
+        if end <= start {
+            continue;
+        }
+        let end = end_of_trailing_sql_comment(content, end);
+        let chunk = &content[start..end];
+        let statement_start = start + (chunk.len() - chunk.trim_start().len());
+        if statement_start < end {
+            let text = &content[statement_start..end];
+            let kind = if is_sql_comment_only(text) {
+                BlockKind::Comment
+            } else {
+                BlockKind::Statement
+            };
+            blocks.push(create_block(
+                text,
+                kind,
+                content,
+                statement_start,
+                end,
+                &Language::Sql,
+            ));
+        }
+        start = end;
+    }
+    blocks
+}
+
+/// Offsets just past each `;` that ends a statement. Semicolons inside quotes,
+/// comments, and `$tag$` bodies (Postgres functions) don't count.
+fn sql_statement_ends(content: &str) -> Vec<usize> {
+    let bytes = content.as_bytes();
+    let mut ends = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            quote @ (b'\'' | b'"' | b'`') => {
+                i += 1;
+                while i < bytes.len() {
+                    if bytes[i] == quote {
+                        // A doubled quote is an escaped quote.
+                        if bytes.get(i + 1) != Some(&quote) {
+                            break;
+                        }
+                        i += 1;
+                    }
+                    i += 1;
+                }
+                i += 1;
+            }
+            b'-' if bytes.get(i + 1) == Some(&b'-') => {
+                i = content[i..]
+                    .find('\n')
+                    .map_or(bytes.len(), |idx| i + idx + 1);
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i = content[i + 2..]
+                    .find("*/")
+                    .map_or(bytes.len(), |idx| i + 2 + idx + 2);
+            }
+            b'$' => match sql_dollar_tag(&content[i..]) {
+                Some(tag) => {
+                    let body = i + tag.len();
+                    i = content[body..]
+                        .find(tag)
+                        .map_or(bytes.len(), |idx| body + idx + tag.len());
+                }
+                None => i += 1,
+            },
+            b';' => {
+                i += 1;
+                ends.push(i);
+            }
+            _ => i += 1,
+        }
+    }
+    ends
+}
+
+/// `$$` or `$name$` at the start of `text`; `$1` placeholders are not tags.
+fn sql_dollar_tag(text: &str) -> Option<&str> {
+    let rest = text.strip_prefix('$')?;
+    let name_len = rest
+        .find(|ch: char| !(ch.is_ascii_alphanumeric() || ch == '_'))
+        .unwrap_or(rest.len());
+    let name = &rest[..name_len];
+    if name.starts_with(|ch: char| ch.is_ascii_digit()) || !rest[name_len..].starts_with('$') {
+        return None;
+    }
+    Some(&text[..name_len + 2])
+}
+
+fn end_of_trailing_sql_comment(content: &str, end: usize) -> usize {
+    let line_end = content[end..]
+        .find('\n')
+        .map_or(content.len(), |idx| end + idx);
+    if content[end..line_end].trim_start().starts_with("--") {
+        line_end
+    } else {
+        end
+    }
+}
+
+fn is_sql_comment_only(text: &str) -> bool {
+    let text = text.trim();
+    (text.starts_with("/*") && text.ends_with("*/"))
+        || text
+            .lines()
+            .all(|line| line.trim().is_empty() || line.trim_start().starts_with("--"))
+}
+
 #[derive(Debug, Clone)]
 struct MarkdownHeading {
     start: usize,
@@ -358,6 +498,127 @@ fn map_kind(lang: Language, kind: &str) -> BlockKind {
     }
 }
 
+/// C and C++ items keyed on the node, since templates and prototypes are
+/// only told apart by what they wrap.
+fn map_c_kind(node: tree_sitter::Node<'_>) -> BlockKind {
+    match node.kind() {
+        "function_definition" => BlockKind::Function,
+        "struct_specifier" | "union_specifier" => BlockKind::Struct,
+        "class_specifier" => BlockKind::Class,
+        "enum_specifier" => BlockKind::Enum,
+        "type_definition" | "alias_declaration" | "concept_definition" => BlockKind::Type,
+        "preproc_def" | "preproc_function_def" => BlockKind::Macro,
+        "preproc_include" | "using_declaration" | "namespace_alias_definition" => BlockKind::Import,
+        "declaration" if has_descendant_of_kind(node, "function_declarator") => {
+            BlockKind::FunctionSignature
+        }
+        "declaration" => BlockKind::Variable,
+        "template_declaration" => node
+            .named_child(node.named_child_count().saturating_sub(1) as u32)
+            .map(map_c_kind)
+            .unwrap_or(BlockKind::Code),
+        _ => BlockKind::Code,
+    }
+}
+
+fn has_descendant_of_kind(node: tree_sitter::Node<'_>, kind: &str) -> bool {
+    let mut cursor = node.walk();
+    node.children(&mut cursor)
+        .any(|child| child.kind() == kind || has_descendant_of_kind(child, kind))
+}
+
+/// Top-level items to split on. C and C++ items are lifted out of include
+/// guards, `extern "C"` blocks, and namespaces; YAML splits into the top-level
+/// keys or list items of each document. Wrapper lines become gaps.
+fn top_level_nodes<'a>(
+    root: tree_sitter::Node<'a>,
+    lang: &Language,
+    cursor: &mut tree_sitter::TreeCursor<'a>,
+) -> Vec<tree_sitter::Node<'a>> {
+    match lang {
+        Language::C | Language::Cpp => {
+            let mut nodes = Vec::new();
+            collect_c_items(root, &mut nodes);
+            nodes
+        }
+        Language::Yaml => {
+            let mut nodes = Vec::new();
+            collect_yaml_items(root, &mut nodes);
+            nodes
+        }
+        _ => root.children(cursor).collect(),
+    }
+}
+
+fn collect_c_items<'a>(container: tree_sitter::Node<'a>, nodes: &mut Vec<tree_sitter::Node<'a>>) {
+    let mut cursor = container.walk();
+    for (index, child) in container.named_children(&mut cursor).enumerate() {
+        let field = container.field_name_for_named_child(index as u32);
+        if matches!(field, Some("name" | "condition" | "value")) {
+            continue;
+        }
+        match child.kind() {
+            "preproc_ifdef" | "preproc_if" | "preproc_else" | "preproc_elif"
+            | "preproc_elifdef" | "declaration_list" => collect_c_items(child, nodes),
+            "linkage_specification" | "namespace_definition" => {
+                match child.child_by_field_name("body") {
+                    Some(body) if body.kind() == "declaration_list" => collect_c_items(body, nodes),
+                    _ => nodes.push(child),
+                }
+            }
+            _ => nodes.push(child),
+        }
+    }
+}
+
+fn is_trailing_comment(content: &str, start_byte: usize) -> bool {
+    let line_start = content[..start_byte].rfind('\n').map_or(0, |idx| idx + 1);
+    !content[line_start..start_byte].trim().is_empty()
+}
+
+fn collect_yaml_items<'a>(
+    container: tree_sitter::Node<'a>,
+    nodes: &mut Vec<tree_sitter::Node<'a>>,
+) {
+    let mut cursor = container.walk();
+    for child in container.named_children(&mut cursor) {
+        match child.kind() {
+            "document" | "block_node" | "block_mapping" | "block_sequence" => {
+                collect_yaml_items(child, nodes)
+            }
+            _ => nodes.push(child),
+        }
+    }
+}
+
+fn map_yaml_kind(kind: &str) -> BlockKind {
+    match kind {
+        "block_mapping_pair" | "block_sequence_item" => BlockKind::Entry,
+        "comment" => BlockKind::Comment,
+        _ => BlockKind::Code,
+    }
+}
+
+/// End of an item, including the `;` that closes a bare C struct or class and
+/// a comment on the same line.
+fn item_end_byte(node: tree_sitter::Node<'_>, lang: &Language, content: &str) -> usize {
+    let mut last = node;
+    if matches!(lang, Language::C | Language::Cpp)
+        && let Some(next) = last.next_sibling()
+        && next.kind() == ";"
+    {
+        last = next;
+    }
+    if matches!(lang, Language::C | Language::Cpp | Language::Yaml)
+        && let Some(next) = last.next_sibling()
+        && next.kind() == "comment"
+        && !content[last.end_byte()..next.start_byte()].contains('\n')
+    {
+        last = next;
+    }
+    last.end_byte()
+}
+
 fn map_rust_impl_child_kind(kind: &str) -> Option<BlockKind> {
     match kind {
         "function_item" => Some(BlockKind::Method),
@@ -454,12 +715,18 @@ fn create_block(
 
     Block {
         hash,
+        metrics: BlockMetrics::from_content(text),
         content: text.to_string(),
         kind,
         tags: Vec::new(),
         complexity,
         start_line,
         end_line,
+        previous: None,
+        blame: None,
+        commits: Vec::new(),
+        prereview: None,
+        risk: None,
     }
 }
 
@@ -816,6 +1083,95 @@ mod tests {
         assert!(blocks.iter().any(|block| block.kind == BlockKind::Const));
     }
 
+    #[test]
+    fn test_split_c_header_items() {
+        let content = "#ifndef POINT_H\n#define POINT_H\n\n#include <stdio.h>\n\n#define SQ(x) ((x) * (x))\n\n// A point.\nstruct point {\n    int x;\n};\n\ntypedef struct point point_t;\n\nint norm(point_t p);\n\nint norm(point_t p) {\n    return SQ(p.x);\n}\n\n#endif\n";
+        let blocks = split(content, Language::C).unwrap();
+        let kinds: Vec<BlockKind> = blocks
+            .iter()
+            .filter(|block| block.kind != BlockKind::Gap)
+            .map(|block| block.kind.clone())
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                BlockKind::Macro,
+                BlockKind::Import,
+                BlockKind::Macro,
+                BlockKind::Struct,
+                BlockKind::Type,
+                BlockKind::FunctionSignature,
+                BlockKind::Function,
+            ]
+        );
+        let point = blocks
+            .iter()
+            .find(|block| block.kind == BlockKind::Struct)
+            .unwrap();
+        assert!(point.content.starts_with("// A point."));
+        assert!(point.content.ends_with("};"));
+        assert_block_hashes_match(&blocks);
+    }
+
+    #[test]
+    fn test_split_cpp_namespace_items() {
+        let content = "namespace demo {\n\nclass Foo {\n  void run();\n};\n\ntemplate <typename T>\nT id(T x) { return x; }\n\nusing Ints = std::vector<int>;\n\n}\n";
+        let blocks = split(content, Language::Cpp).unwrap();
+        assert!(blocks.iter().any(|block| block.kind == BlockKind::Class));
+        assert!(blocks.iter().any(|block| block.kind == BlockKind::Function));
+        assert!(blocks.iter().any(|block| block.kind == BlockKind::Type));
+        // The namespace wrapper is kept as gaps around the lifted items.
+        assert_eq!(blocks[0].kind, BlockKind::Gap);
+        assert!(blocks[0].content.starts_with("namespace demo {"));
+        assert_eq!(blocks.last().unwrap().content.trim(), "}");
+    }
+
+    #[test]
+    fn test_split_sql_statements() {
+        let content = "-- Users\nCREATE TABLE users (\n    name TEXT DEFAULT ';'\n); -- core\n\nCREATE FUNCTION touch() RETURNS trigger AS $$\nBEGIN\n    NEW.updated = now();\n    RETURN NEW;\nEND;\n$$ LANGUAGE plpgsql;\n\nSELECT * FROM users WHERE id = $1;\n-- trailing\n";
+        let blocks = split(content, Language::Sql).unwrap();
+        let kinds: Vec<BlockKind> = blocks.iter().map(|block| block.kind.clone()).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                BlockKind::Statement,
+                BlockKind::Statement,
+                BlockKind::Statement,
+                BlockKind::Comment,
+            ]
+        );
+        assert!(blocks[0].content.starts_with("-- Users\nCREATE TABLE"));
+        assert!(blocks[0].content.ends_with("); -- core"));
+        assert!(blocks[1].content.ends_with("LANGUAGE plpgsql;"));
+        assert_block_hashes_match(&blocks);
+    }
+
+    #[test]
+    fn test_split_yaml_top_level_keys() {
+        let content = "# Pod\napiVersion: v1\nkind: Pod  # core\nmetadata:\n  name: web\n---\n- name: build\n  run: make\n- name: test\n";
+        let blocks = split(content, Language::Yaml).unwrap();
+        let entries: Vec<&str> = blocks
+            .iter()
+            .filter(|block| block.kind == BlockKind::Entry)
+            .map(|block| block.content.as_str())
+            .collect();
+        assert_eq!(
+            entries,
+            vec![
+                "# Pod\napiVersion: v1",
+                "kind: Pod  # core",
+                "metadata:\n  name: web",
+                "- name: build\n  run: make",
+                "- name: test\n",
+            ]
+        );
+        assert!(
+            blocks
+                .iter()
+                .any(|block| block.kind == BlockKind::Gap && block.content.contains("---"))
+        );
+    }
+
     #[test]
     fn test_markdown_discards_whitespace_only_preamble() {
         let content = "\n\n# Title\nBody";
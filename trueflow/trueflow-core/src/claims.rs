@@ -0,0 +1,160 @@
+use crate::assignment::{normalize_path, path_has_prefix};
+use crate::store::{Claim, Identity, Record, RecordKind, RepoRef};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActiveClaim {
+    pub holder: Identity,
+    pub path: String,
+    pub expires_at: i64,
+}
+
+/// Unexpired claims, one per (holder, path); a newer claim extends an older one.
+#[derive(Debug, Default)]
+pub struct ActiveClaims {
+    claims: Vec<ActiveClaim>,
+}
+
+pub fn active_claims(records: &[Record], now: i64) -> ActiveClaims {
+    let mut latest: HashMap<(Identity, String), i64> = HashMap::new();
+    for record in records
+        .iter()
+        .filter(|record| record.kind == RecordKind::Claim)
+    {
+        let Some(claim) = &record.claim else {
+            continue;
+        };
+        let key = (record.identity.clone(), normalize_path(&claim.path));
+        let expires_at = latest.entry(key).or_insert(claim.expires_at);
+        *expires_at = (*expires_at).max(claim.expires_at);
+    }
+
+    let mut claims: Vec<ActiveClaim> = latest
+        .into_iter()
+        .filter(|(_, expires_at)| *expires_at > now)
+        .map(|((holder, path), expires_at)| ActiveClaim {
+            holder,
+            path,
+            expires_at,
+        })
+        .collect();
+    claims.sort_by(|a, b| (&a.path, a.expires_at).cmp(&(&b.path, b.expires_at)));
+    ActiveClaims { claims }
+}
+
+impl ActiveClaims {
+    /// The claim on `path` (or an enclosing directory) held by someone other than `me`.
+    pub fn held_by_other(&self, path: &str, me: &Identity) -> Option<&ActiveClaim> {
+        let path = normalize_path(path);
+        self.claims
+            .iter()
+            .filter(|claim| &claim.holder != me && path_has_prefix(&path, &claim.path))
+            .max_by_key(|claim| claim.expires_at)
+    }
+
+    /// Whether `me` holds a claim on `path` that outlives `until`.
+    pub fn held_by_me_until(&self, path: &str, me: &Identity, until: i64) -> bool {
+        let path = normalize_path(path);
+        self.claims.iter().any(|claim| {
+            &claim.holder == me && claim.expires_at > until && path_has_prefix(&path, &claim.path)
+        })
+    }
+}
+
+pub fn claim_record(
+    holder: &Identity,
+    repo_ref: RepoRef,
+    path: &str,
+    ttl_secs: i64,
+    now: i64,
+) -> Record {
+    Record {
+        path_hint: Some(normalize_path(path)),
+        claim: Some(Claim {
+            path: normalize_path(path),
+            expires_at: now + ttl_secs,
+        }),
+        ..Record::non_verdict(
+            RecordKind::Claim,
+            String::new(),
+            holder.clone(),
+            repo_ref,
+            now,
+        )
+    }
+}
+
+/// Short human form of the time left on a claim, e.g. "25m".
+pub fn format_remaining(expires_at: i64, now: i64) -> String {
+    let secs = (expires_at - now).max(0);
+    if secs >= 3600 {
+        format!("{}h{}m", secs / 3600, (secs % 3600) / 60)
+    } else if secs >= 60 {
+        format!("{}m", secs / 60)
+    } else {
+        format!("{secs}s")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::VcsSystem;
+
+    fn email(value: &str) -> Identity {
+        Identity::Email {
+            email: value.to_string(),
+        }
+    }
+
+    fn repo_ref() -> RepoRef {
+        RepoRef::Vcs {
+            system: VcsSystem::Git,
+            revision: "deadbeef".to_string(),
+        }
+    }
+
+    #[test]
+    fn expired_claims_are_ignored() {
+        let records = vec![
+            claim_record(&email("bob@example.com"), repo_ref(), "src/net", 60, 1000),
+            claim_record(
+                &email("carol@example.com"),
+                repo_ref(),
+                "src/core.rs",
+                60,
+                0,
+            ),
+        ];
+        let claims = active_claims(&records, 1030);
+        let me = email("alice@example.com");
+
+        let held = claims
+            .held_by_other("src/net/http.rs", &me)
+            .expect("bob holds src/net");
+        assert_eq!(held.holder, email("bob@example.com"));
+        assert!(claims.held_by_other("src/core.rs", &me).is_none());
+        assert!(claims.held_by_other("src/network.rs", &me).is_none());
+    }
+
+    #[test]
+    fn own_claims_are_not_reported_as_conflicts() {
+        let me = email("alice@example.com");
+        let records = vec![
+            claim_record(&me, repo_ref(), "src/lib.rs", 600, 1000),
+            claim_record(&me, repo_ref(), "src/lib.rs", 600, 1200),
+        ];
+        let claims = active_claims(&records, 1300);
+        assert!(claims.held_by_other("src/lib.rs", &me).is_none());
+        assert!(claims.held_by_me_until("src/lib.rs", &me, 1700));
+        assert!(!claims.held_by_me_until("src/lib.rs", &me, 1800));
+    }
+
+    #[test]
+    fn formats_remaining_time() {
+        assert_eq!(format_remaining(100, 70), "30s");
+        assert_eq!(format_remaining(1600, 100), "25m");
+        assert_eq!(format_remaining(5000, 0), "1h23m");
+        assert_eq!(format_remaining(0, 10), "0s");
+    }
+}
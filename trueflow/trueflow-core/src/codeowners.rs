@@ -0,0 +1,173 @@
+use crate::config::OwnersConfig;
+use anyhow::Context;
+use globset::{GlobBuilder, GlobMatcher};
+use log::warn;
+use std::fs;
+use std::path::Path;
+
+const CODEOWNERS_LOCATIONS: &[&str] = &[
+    ".trueflow/CODEOWNERS",
+    ".github/CODEOWNERS",
+    "CODEOWNERS",
+    "docs/CODEOWNERS",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Owner {
+    Email(String),
+    /// `@user` or `@org/team`, without the leading `@`.
+    Handle(String),
+}
+
+#[derive(Debug)]
+struct OwnerRule {
+    matchers: Vec<GlobMatcher>,
+    owners: Vec<Owner>,
+}
+
+/// Parsed CODEOWNERS file. As on GitHub, the last matching rule wins.
+#[derive(Debug, Default)]
+pub struct CodeOwners {
+    rules: Vec<OwnerRule>,
+}
+
+impl CodeOwners {
+    pub fn from_root(root: &Path) -> Self {
+        for location in CODEOWNERS_LOCATIONS {
+            if let Ok(content) = fs::read_to_string(root.join(location)) {
+                return Self::parse(&content);
+            }
+        }
+        Self::default()
+    }
+
+    /// The file `[owners] file` names under `root`, else the usual locations.
+    pub fn configured(root: &Path, config: &OwnersConfig) -> anyhow::Result<Self> {
+        match &config.file {
+            Some(file) => Self::from_file(&root.join(file)),
+            None => Ok(Self::from_root(root)),
+        }
+    }
+
+    /// Reads `path` instead of searching the usual locations.
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Ok(Self::parse(&content))
+    }
+
+    pub fn parse(content: &str) -> Self {
+        let mut rules = Vec::new();
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut parts = line.split_whitespace();
+            let Some(pattern) = parts.next() else {
+                continue;
+            };
+            let owners = parts
+                .take_while(|part| !part.starts_with('#'))
+                .map(parse_owner)
+                .collect();
+            match pattern_matchers(pattern) {
+                Ok(matchers) => rules.push(OwnerRule { matchers, owners }),
+                Err(err) => warn!("Ignoring CODEOWNERS pattern '{}': {}", pattern, err),
+            }
+        }
+        Self { rules }
+    }
+
+    /// `path` is repo-relative with forward slashes.
+    pub fn owners_for(&self, path: &str) -> &[Owner] {
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| rule.matchers.iter().any(|matcher| matcher.is_match(path)))
+            .map(|rule| rule.owners.as_slice())
+            .unwrap_or(&[])
+    }
+}
+
+fn parse_owner(raw: &str) -> Owner {
+    match raw.strip_prefix('@') {
+        Some(handle) => Owner::Handle(handle.to_string()),
+        None => Owner::Email(raw.to_string()),
+    }
+}
+
+/// gitignore-style: unanchored patterns match at any depth, and a pattern
+/// naming a directory covers everything below it.
+fn pattern_matchers(pattern: &str) -> Result<Vec<GlobMatcher>, globset::Error> {
+    let anchored = pattern.starts_with('/') || pattern.trim_end_matches('/').contains('/');
+    let mut base = pattern.trim_start_matches('/').to_string();
+    if base.ends_with('/') {
+        base.push_str("**");
+    }
+    if !anchored {
+        base = format!("**/{base}");
+    }
+
+    let mut matchers = vec![glob_matcher(&base)?];
+    if !base.ends_with("**") {
+        matchers.push(glob_matcher(&format!("{base}/**"))?);
+    }
+    Ok(matchers)
+}
+
+fn glob_matcher(pattern: &str) -> Result<GlobMatcher, globset::Error> {
+    Ok(GlobBuilder::new(pattern)
+        .literal_separator(true)
+        .build()?
+        .compile_matcher())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn emails(owners: &[Owner]) -> Vec<&str> {
+        owners
+            .iter()
+            .map(|owner| match owner {
+                Owner::Email(email) => email.as_str(),
+                Owner::Handle(handle) => handle.as_str(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn last_matching_rule_wins() {
+        let owners = CodeOwners::parse(
+            "# default\n* lead@example.com\n/src/net/ alice@example.com @org/net\n*.md docs@example.com\n",
+        );
+        assert_eq!(
+            emails(owners.owners_for("Cargo.toml")),
+            vec!["lead@example.com"]
+        );
+        assert_eq!(
+            emails(owners.owners_for("src/net/http.rs")),
+            vec!["alice@example.com", "org/net"]
+        );
+        assert_eq!(
+            emails(owners.owners_for("src/net/README.md")),
+            vec!["docs@example.com"]
+        );
+    }
+
+    #[test]
+    fn anchoring_follows_gitignore_rules() {
+        let owners = CodeOwners::parse("docs bob@example.com\n/build/*.rs carol@example.com\n");
+        assert_eq!(
+            emails(owners.owners_for("api/docs/intro.md")),
+            vec!["bob@example.com"]
+        );
+        assert_eq!(
+            emails(owners.owners_for("build/gen.rs")),
+            vec!["carol@example.com"]
+        );
+        assert!(owners.owners_for("build/nested/gen.rs").is_empty());
+        assert!(owners.owners_for("src/build/gen.rs").is_empty());
+    }
+}
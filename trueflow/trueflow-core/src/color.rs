@@ -0,0 +1,110 @@
+use std::io::IsTerminal;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum), clap(rename_all = "lower"))]
+pub enum ColorChoice {
+    /// Color when writing to a terminal and NO_COLOR is unset
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const DIM: &str = "\x1b[2m";
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const YELLOW: &str = "\x1b[33m";
+const MAGENTA: &str = "\x1b[35m";
+const CYAN: &str = "\x1b[36m";
+
+/// Wraps text in ANSI styles, or passes it through when color is off.
+#[derive(Copy, Clone, Debug)]
+pub struct Palette {
+    enabled: bool,
+}
+
+impl Palette {
+    pub fn new(choice: ColorChoice, stream: Stream) -> Self {
+        let enabled = match choice {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                let is_terminal = match stream {
+                    Stream::Stdout => std::io::stdout().is_terminal(),
+                    Stream::Stderr => std::io::stderr().is_terminal(),
+                };
+                is_terminal && !no_color_requested()
+            }
+        };
+        Self { enabled }
+    }
+
+    pub fn heading(&self, text: &str) -> String {
+        self.paint(BOLD, text)
+    }
+
+    pub fn dim(&self, text: &str) -> String {
+        self.paint(DIM, text)
+    }
+
+    /// Colors a review status: approved, rejected, question, comment, or unreviewed.
+    pub fn status(&self, status: &str) -> String {
+        let code = match status.to_ascii_lowercase().as_str() {
+            "approved" => GREEN,
+            "rejected" => RED,
+            "question" => MAGENTA,
+            "comment" => CYAN,
+            _ => YELLOW,
+        };
+        self.paint(code, status)
+    }
+
+    /// Green when everything is reviewed, yellow when partly, red when nothing is.
+    pub fn coverage(&self, reviewed: usize, total: usize, text: &str) -> String {
+        let code = if reviewed >= total {
+            GREEN
+        } else if reviewed > 0 {
+            YELLOW
+        } else {
+            RED
+        };
+        self.paint(code, text)
+    }
+
+    fn paint(&self, code: &str, text: &str) -> String {
+        if self.enabled {
+            format!("{code}{text}{RESET}")
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+/// https://no-color.org: any non-empty value disables color.
+fn no_color_requested() -> bool {
+    std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn never_and_always_ignore_the_terminal() {
+        let plain = Palette::new(ColorChoice::Never, Stream::Stdout);
+        assert_eq!(plain.status("rejected"), "rejected");
+        assert_eq!(plain.heading("src/lib.rs"), "src/lib.rs");
+
+        let colored = Palette::new(ColorChoice::Always, Stream::Stdout);
+        assert_eq!(colored.status("approved"), "\x1b[32mapproved\x1b[0m");
+        assert_eq!(colored.dim("abc"), "\x1b[2mabc\x1b[0m");
+    }
+}
@@ -0,0 +1,236 @@
+use crate::context::TrueflowContext;
+use crate::output::StructuredFormat;
+use crate::store::{Record, Verdict, open_store, without_revoked};
+use crate::time_window::TimeWindow;
+use anyhow::Result;
+use log::info;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct ReviewerActivity {
+    pub identity: String,
+    pub blocks_reviewed: usize,
+    pub approved: usize,
+    pub rejected: usize,
+    pub questions: usize,
+    pub comments: usize,
+    pub notes: usize,
+    pub average_note_length: f64,
+    /// Questions this reviewer opened that are still the latest verdict.
+    pub outstanding_questions: usize,
+}
+
+#[derive(Default)]
+struct Tally {
+    fingerprints: HashSet<String>,
+    approved: usize,
+    rejected: usize,
+    questions: usize,
+    comments: usize,
+    note_chars: usize,
+    notes: usize,
+    asked: HashSet<(String, String)>,
+}
+
+pub fn summarize_activity(records: &[Record], window: TimeWindow) -> Vec<ReviewerActivity> {
+    let mut verdict_records: Vec<&Record> = records
+        .iter()
+        .filter(|record| record.kind.is_verdict())
+        .collect();
+    verdict_records.sort_by_key(|record| record.timestamp);
+
+    // Outstanding-ness is judged against the whole history, not just the window.
+    let mut latest: HashMap<(String, String), Verdict> = HashMap::new();
+    for record in &verdict_records {
+        latest.insert(
+            (record.fingerprint.clone(), record.check.clone()),
+            record.verdict.clone(),
+        );
+    }
+
+    let mut tallies: BTreeMap<String, Tally> = BTreeMap::new();
+    for record in verdict_records
+        .iter()
+        .filter(|record| window.contains(record.timestamp))
+    {
+        let tally = tallies.entry(record.identity.label()).or_default();
+        tally.fingerprints.insert(record.fingerprint.clone());
+        match record.verdict {
+            Verdict::Approved => tally.approved += 1,
+            Verdict::Rejected => tally.rejected += 1,
+            Verdict::Question => {
+                tally.questions += 1;
+                tally
+                    .asked
+                    .insert((record.fingerprint.clone(), record.check.clone()));
+            }
+            Verdict::Comment => tally.comments += 1,
+        }
+        if let Some(note) = record.note.as_deref().map(str::trim)
+            && !note.is_empty()
+        {
+            tally.notes += 1;
+            tally.note_chars += note.chars().count();
+        }
+    }
+
+    let mut activity: Vec<ReviewerActivity> = tallies
+        .into_iter()
+        .map(|(identity, tally)| ReviewerActivity {
+            identity,
+            blocks_reviewed: tally.fingerprints.len(),
+            approved: tally.approved,
+            rejected: tally.rejected,
+            questions: tally.questions,
+            comments: tally.comments,
+            notes: tally.notes,
+            average_note_length: if tally.notes == 0 {
+                0.0
+            } else {
+                tally.note_chars as f64 / tally.notes as f64
+            },
+            outstanding_questions: tally
+                .asked
+                .iter()
+                .filter(|key| latest.get(*key) == Some(&Verdict::Question))
+                .count(),
+        })
+        .collect();
+    activity.sort_by(|a, b| {
+        b.blocks_reviewed
+            .cmp(&a.blocks_reviewed)
+            .then_with(|| a.identity.cmp(&b.identity))
+    });
+    activity
+}
+
+pub fn run(
+    _context: &TrueflowContext,
+    since: Option<&str>,
+    until: Option<&str>,
+    output: Option<StructuredFormat>,
+) -> Result<()> {
+    info!(
+        "activity start (since={:?}, until={:?}, output={:?})",
+        since, until, output
+    );
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+    let window = TimeWindow::parse(since, until, now)?;
+
+    let store = open_store()?;
+    let history = without_revoked(store.read_history()?);
+    let activity = summarize_activity(&history, window);
+
+    if let Some(format) = output {
+        return format.print(&activity);
+    }
+    if activity.is_empty() {
+        println!("No review activity in this window.");
+        return Ok(());
+    }
+
+    let width = activity
+        .iter()
+        .map(|row| row.identity.len())
+        .max()
+        .unwrap_or(0)
+        .max("Reviewer".len());
+    println!(
+        "{:<width$}  {:>6}  {:>8}  {:>8}  {:>9}  {:>6}  {:>8}",
+        "Reviewer", "Blocks", "Approved", "Rejected", "Questions", "Open Q", "Avg note"
+    );
+    for row in &activity {
+        println!(
+            "{:<width$}  {:>6}  {:>8}  {:>8}  {:>9}  {:>6}  {:>8.0}",
+            row.identity,
+            row.blocks_reviewed,
+            row.approved,
+            row.rejected,
+            row.questions,
+            row.outstanding_questions,
+            row.average_note_length
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::{BlockState, Identity, RepoRef, VcsSystem};
+
+    fn record(
+        email: &str,
+        fingerprint: &str,
+        verdict: Verdict,
+        note: Option<&str>,
+        timestamp: i64,
+    ) -> Record {
+        Record {
+            id: format!("{email}-{fingerprint}-{timestamp}"),
+            note: note.map(str::to_string),
+            ..Record::verdict(
+                fingerprint.to_string(),
+                "review".to_string(),
+                verdict,
+                Identity::Email {
+                    email: email.to_string(),
+                },
+                RepoRef::Vcs {
+                    system: VcsSystem::Git,
+                    revision: "deadbeef".to_string(),
+                },
+                BlockState::Committed,
+                timestamp,
+            )
+        }
+    }
+
+    #[test]
+    fn summarizes_per_reviewer_within_window() {
+        let records = vec![
+            record("alice@example.com", "a", Verdict::Approved, None, 10),
+            record(
+                "alice@example.com",
+                "b",
+                Verdict::Rejected,
+                Some("nope"),
+                20,
+            ),
+            record(
+                "alice@example.com",
+                "c",
+                Verdict::Question,
+                Some("why?"),
+                30,
+            ),
+            record("alice@example.com", "d", Verdict::Question, None, 31),
+            // Bob answers one of alice's questions.
+            record("bob@example.com", "d", Verdict::Approved, None, 40),
+            record("bob@example.com", "old", Verdict::Approved, None, 1),
+        ];
+        let window = TimeWindow {
+            since: Some(5),
+            until: None,
+        };
+        let activity = summarize_activity(&records, window);
+        assert_eq!(activity.len(), 2);
+
+        let alice = &activity[0];
+        assert_eq!(alice.identity, "alice@example.com");
+        assert_eq!(alice.blocks_reviewed, 4);
+        assert_eq!((alice.approved, alice.rejected, alice.questions), (1, 1, 2));
+        assert_eq!(alice.notes, 2);
+        assert_eq!(alice.average_note_length, 4.0);
+        assert_eq!(alice.outstanding_questions, 1);
+
+        let bob = &activity[1];
+        assert_eq!(
+            bob.blocks_reviewed, 1,
+            "Records before the window are ignored"
+        );
+        assert_eq!(bob.outstanding_questions, 0);
+    }
+}
@@ -0,0 +1,144 @@
+use crate::assignment::normalize_path;
+use crate::commands::mark::{append_attested, repo_ref_from_snapshot};
+use crate::commands::review::workdir_prefix_from_root;
+use crate::context::TrueflowContext;
+use crate::identity::CurrentIdentity;
+use crate::store::{Assignment, Identity, Record, RecordKind, RepoRef, open_store};
+use crate::tree;
+use crate::vcs;
+use anyhow::{Result, anyhow, bail};
+use log::info;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AssignTarget {
+    Fingerprint(String),
+    /// Repo-relative path prefix (file or directory).
+    Path(String),
+}
+
+impl AssignTarget {
+    /// The target of `assign [TARGET] [--path P] [--fingerprint F]`; clap
+    /// makes sure exactly one is given.
+    pub fn from_args(
+        target: Option<&str>,
+        path: Option<&str>,
+        fingerprint: Option<&str>,
+    ) -> Result<Self> {
+        match (target, path, fingerprint) {
+            (Some(target), _, _) => Ok(parse_assign_target(target)),
+            (None, Some(path), _) if !Path::new(path).exists() => {
+                bail!("No such file or directory: {}", path)
+            }
+            (None, Some(path), _) => Ok(path_target(path)),
+            (None, None, Some(fingerprint)) => Ok(Self::Fingerprint(fingerprint.to_string())),
+            (None, None, None) => bail!("Nothing to assign"),
+        }
+    }
+}
+
+/// Existing paths are assigned by prefix; anything else is a fingerprint.
+pub fn parse_assign_target(raw: &str) -> AssignTarget {
+    if Path::new(raw).exists() {
+        path_target(raw)
+    } else {
+        AssignTarget::Fingerprint(raw.to_string())
+    }
+}
+
+/// A file or directory given relative to the working directory.
+fn path_target(raw: &str) -> AssignTarget {
+    let relative = normalize_path(raw);
+    let path = match workdir_prefix_from_root() {
+        Some(prefix) if relative.is_empty() => prefix,
+        Some(prefix) => format!("{prefix}/{relative}"),
+        None => relative,
+    };
+    AssignTarget::Path(path)
+}
+
+pub fn run(
+    context: &TrueflowContext,
+    target: AssignTarget,
+    assignee: &str,
+    note: Option<String>,
+) -> Result<()> {
+    info!("assign start (target={:?}, to={})", target, assignee);
+    if assignee.trim().is_empty() {
+        return Err(anyhow!("Assignee email must not be empty"));
+    }
+
+    let store = open_store()?;
+    let current = CurrentIdentity::resolve(context)?;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+    let repo_snapshot = vcs::snapshot_from_workdir();
+
+    let (fingerprint, path) = match &target {
+        AssignTarget::Fingerprint(fingerprint) => (fingerprint.clone(), None),
+        AssignTarget::Path(path) => (path_fingerprint(path), Some(path.clone())),
+    };
+
+    let assignment = Assignment {
+        assignee: Identity::Email {
+            email: assignee.trim().to_string(),
+        },
+        path,
+        request: None,
+    };
+    let record = assignment_record(
+        &current.identity,
+        repo_ref_from_snapshot(&repo_snapshot),
+        fingerprint,
+        assignment,
+        note,
+        now,
+    );
+
+    append_attested(store.as_ref(), record, current.signer()?.as_ref())?;
+    info!(
+        "Recorded assignment of {:?} to {} by {}",
+        target, assignee, current.identity
+    );
+    Ok(())
+}
+
+pub(crate) fn assignment_record(
+    assigned_by: &Identity,
+    repo_ref: RepoRef,
+    fingerprint: String,
+    assignment: Assignment,
+    note: Option<String>,
+    timestamp: i64,
+) -> Record {
+    Record {
+        path_hint: assignment.path.clone(),
+        note,
+        assignment: Some(assignment),
+        ..Record::non_verdict(
+            RecordKind::Assignment,
+            fingerprint,
+            assigned_by.clone(),
+            repo_ref,
+            timestamp,
+        )
+    }
+}
+
+/// Subtree hash of the assigned path at assignment time, for reference only.
+fn path_fingerprint(repo_path: &str) -> String {
+    let local_path = match workdir_prefix_from_root() {
+        Some(prefix) => repo_path
+            .strip_prefix(&prefix)
+            .map(|rest| rest.trim_start_matches('/').to_string())
+            .unwrap_or_else(|| repo_path.to_string()),
+        None => repo_path.to_string(),
+    };
+    tree::build_tree_from_path(".")
+        .ok()
+        .and_then(|tree| {
+            tree.find_by_path(&local_path)
+                .map(|id| tree.node(id).hash.clone())
+        })
+        .unwrap_or_default()
+}
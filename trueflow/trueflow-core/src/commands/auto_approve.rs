@@ -0,0 +1,57 @@
+use crate::auto_approve::{AutoApprover, record_approvals};
+use crate::commands::review::{ReviewOptions, collect_unreviewed, parse_review_targets};
+use crate::config::load as load_config;
+use crate::context::TrueflowContext;
+use anyhow::Result;
+use log::info;
+
+pub struct AutoApproveParams {
+    pub all: bool,
+    pub target: Vec<String>,
+    pub dry_run: bool,
+}
+
+/// Runs the `[auto_approve]` rules over the unreviewed blocks in scope,
+/// whether or not `enabled` is set.
+pub fn run(_context: &TrueflowContext, params: AutoApproveParams) -> Result<()> {
+    info!(
+        "auto-approve start (all={}, target={:?}, dry_run={})",
+        params.all, params.target, params.dry_run
+    );
+    let config = load_config()?;
+    let mut filters = config.review.resolve_filters(&[], &[]);
+    // Generated files are hidden from review by default; the rule needs them.
+    filters.include_generated |= config.auto_approve.generated;
+    let options = ReviewOptions {
+        all: params.all,
+        targets: parse_review_targets(&params.target)?,
+        only: Vec::new(),
+        exclude: Vec::new(),
+        assignee: None,
+        reviewer: None,
+        check: None,
+    };
+    let files = collect_unreviewed(&options, &filters)?;
+    let selected = AutoApprover::load(&config)?.select(&files);
+    if selected.is_empty() {
+        println!("No blocks match the [auto_approve] rules.");
+        return Ok(());
+    }
+
+    if params.dry_run {
+        for (path, block, rule) in &selected {
+            println!(
+                "{}:{} {}: {}",
+                path,
+                block.start_line + 1,
+                block.kind,
+                rule.reason(block)
+            );
+        }
+        println!("Would auto-approve {} block(s).", selected.len());
+        return Ok(());
+    }
+    let summary = record_approvals(&selected)?;
+    println!("{}", summary);
+    Ok(())
+}
@@ -0,0 +1,99 @@
+use crate::context::TrueflowContext;
+use crate::store::{Record, open_store};
+use crate::time_window::since_timestamp;
+use anyhow::{Context, Result, bail};
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Bump when the bundle layout changes incompatibly.
+const BUNDLE_VERSION: u32 = 1;
+
+/// Review records packaged for carrying between machines that share no
+/// remote. Records keep their ids and attestations, so importing the same
+/// bundle twice changes nothing.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Bundle {
+    pub version: u32,
+    pub exported_at: i64,
+    /// Oldest first.
+    pub records: Vec<Record>,
+}
+
+/// Writes the records made at or after `since` (a time or a revision) to
+/// `out`, or to stdout.
+pub fn export(_context: &TrueflowContext, since: Option<&str>, out: Option<&Path>) -> Result<()> {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+    let since = since.map(|value| since_timestamp(value, now)).transpose()?;
+    let mut records: Vec<Record> = open_store()?
+        .read_history()?
+        .into_iter()
+        .filter(|record| since.is_none_or(|since| record.timestamp >= since))
+        .collect();
+    records.sort_by(|a, b| (a.timestamp, &a.id).cmp(&(b.timestamp, &b.id)));
+    let count = records.len();
+    let bundle = Bundle {
+        version: BUNDLE_VERSION,
+        exported_at: now,
+        records,
+    };
+    let json = serde_json::to_string_pretty(&bundle)?;
+    info!("export (records={}, since={:?})", count, since);
+    match out {
+        Some(path) => {
+            fs::write(path, format!("{json}\n"))
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            println!("Exported {} record(s) to {}.", count, path.display());
+        }
+        None => println!("{json}"),
+    }
+    Ok(())
+}
+
+/// Appends the bundle's records that the ledger doesn't hold yet, by id.
+pub fn import(_context: &TrueflowContext, path: &Path) -> Result<()> {
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let bundle: Bundle = serde_json::from_str(&content)
+        .with_context(|| format!("{} is not a trueflow bundle", path.display()))?;
+    if bundle.version > BUNDLE_VERSION {
+        bail!(
+            "{} is bundle version {}; this trueflow reads up to {}",
+            path.display(),
+            bundle.version,
+            BUNDLE_VERSION
+        );
+    }
+
+    let store = open_store()?;
+    let (fresh, skipped) = new_records(&store.read_history()?, bundle.records);
+    let imported = fresh.len();
+    if imported > 0 {
+        store.append_all(fresh)?;
+    }
+    info!("import (imported={}, skipped={})", imported, skipped);
+    println!(
+        "Imported {} record(s); {} already present.",
+        imported, skipped
+    );
+    Ok(())
+}
+
+/// Records from `incoming` whose ids `existing` lacks, in order, and how many
+/// were skipped.
+fn new_records(existing: &[Record], incoming: Vec<Record>) -> (Vec<Record>, usize) {
+    let mut seen: HashSet<String> = existing.iter().map(|record| record.id.clone()).collect();
+    let mut fresh = Vec::new();
+    let mut skipped = 0;
+    for record in incoming {
+        if seen.insert(record.id.clone()) {
+            fresh.push(record);
+        } else {
+            skipped += 1;
+        }
+    }
+    (fresh, skipped)
+}
@@ -0,0 +1,292 @@
+use crate::color::Stream;
+use crate::commands::Exit;
+use crate::commands::review::{ReviewTarget, parse_review_targets};
+use crate::context::TrueflowContext;
+use crate::diff_logic::{ReviewRequirement, evaluate_policy};
+use crate::output::{CheckFormat, escape_xml};
+use crate::policy::{Violation, ViolationReason};
+use crate::vcs::DiffBase;
+use anyhow::{Result, bail};
+use log::{info, warn};
+use serde::Serialize;
+
+#[derive(Serialize)]
+struct CheckReport {
+    passed: bool,
+    violations: Vec<Violation>,
+}
+
+pub fn run(
+    context: &TrueflowContext,
+    reviewer: Option<&str>,
+    required_approvals: Option<usize>,
+    target: Option<&str>,
+    format: CheckFormat,
+) -> Result<()> {
+    let report = evaluate_policy(&ReviewRequirement {
+        reviewer: reviewer.map(str::to_string),
+        required_approvals,
+        base: diff_base(target)?,
+    })?;
+    let passed = report.passed();
+
+    match format {
+        CheckFormat::Json | CheckFormat::Yaml | CheckFormat::Toml => {
+            let report = CheckReport {
+                passed,
+                violations: report.violations(),
+            };
+            if let Some(structured) = format.structured() {
+                structured.print(&report)?;
+            }
+            // The report already says it failed; an error would add a
+            // second document to stdout.
+            if !passed {
+                return Err(Exit { code: 1 }.into());
+            }
+        }
+        CheckFormat::Junit => print!("{}", render_junit(&report.violations())),
+        CheckFormat::Github => {
+            for violation in report.violations() {
+                println!("{}", render_github(&violation));
+            }
+        }
+        CheckFormat::Text if passed => {}
+        CheckFormat::Text => {
+            let palette = context.palette(Stream::Stderr);
+            if !report.unreviewed.is_empty() {
+                warn!("Found {} unreviewed change(s):", report.unreviewed.len());
+                eprintln!("Found {} unreviewed change(s):", report.unreviewed.len());
+                for change in &report.unreviewed {
+                    eprintln!(
+                        "  {} {} {}",
+                        palette.status(&change.status),
+                        palette.heading(&format!("{}:{}", change.file, change.line)),
+                        palette.dim(&change.fingerprint)
+                    );
+                }
+            }
+            if !report.rule_violations.is_empty() {
+                warn!(
+                    "Found {} policy violation(s):",
+                    report.rule_violations.len()
+                );
+                eprintln!(
+                    "Found {} policy violation(s):",
+                    report.rule_violations.len()
+                );
+                for violation in &report.rule_violations {
+                    let (status, detail) = describe(&violation.reason);
+                    eprintln!(
+                        "  {} {} {} {}",
+                        palette.status(status),
+                        palette.heading(&format!("{}:{}", violation.file, violation.line)),
+                        detail,
+                        palette.dim(&violation.fingerprint)
+                    );
+                }
+            }
+        }
+    }
+
+    if passed {
+        info!("All clear! No unreviewed changes found.");
+        Ok(())
+    } else if report.unreviewed.is_empty() {
+        bail!("CI Check Failed: Policy violations detected.");
+    } else {
+        bail!("CI Check Failed: Unreviewed code detected.");
+    }
+}
+
+/// The changes `--target` selects: only `rev:` targets and `staged` name a
+/// diff.
+fn diff_base(target: Option<&str>) -> Result<DiffBase> {
+    let Some(target) = target else {
+        return Ok(DiffBase::MainToHead);
+    };
+    match parse_review_targets(&[target.to_string()])?.pop() {
+        Some(ReviewTarget::Revision(revision)) => Ok(DiffBase::Revision(revision)),
+        Some(ReviewTarget::RevisionRange { start, end }) => Ok(DiffBase::Range {
+            start: or_head(start),
+            end: or_head(end),
+        }),
+        Some(ReviewTarget::Staged) => Ok(DiffBase::Staged),
+        _ => bail!(
+            "check only supports rev: and staged targets, got {}",
+            target
+        ),
+    }
+}
+
+/// An omitted side of `rev:A..` or `rev:..B` means HEAD, as in git.
+fn or_head(revision: String) -> String {
+    if revision.is_empty() {
+        "HEAD".to_string()
+    } else {
+        revision
+    }
+}
+
+fn describe(reason: &ViolationReason) -> (&'static str, String) {
+    match reason {
+        ViolationReason::Unreviewed { .. } => ("unreviewed", String::new()),
+        ViolationReason::Rejected { check } => ("rejected", format!("({check})")),
+        ViolationReason::MissingApprovals {
+            check,
+            required,
+            approvals,
+        } => (
+            "unreviewed",
+            format!("needs {required} {check} approval(s), has {approvals}"),
+        ),
+        ViolationReason::MissingOwnerReview { owners } => (
+            "unreviewed",
+            format!("needs owner review ({})", owners.join(", ")),
+        ),
+    }
+}
+
+/// One-line reason for CI output, e.g. `rejected (review)`.
+fn summary(reason: &ViolationReason) -> String {
+    match reason {
+        ViolationReason::Unreviewed { status } => format!("unreviewed ({status})"),
+        ViolationReason::Rejected { check } => format!("rejected ({check})"),
+        _ => describe(reason).1,
+    }
+}
+
+fn reason_tag(reason: &ViolationReason) -> &'static str {
+    match reason {
+        ViolationReason::Unreviewed { .. } => "unreviewed",
+        ViolationReason::Rejected { .. } => "rejected",
+        ViolationReason::MissingApprovals { .. } => "missing_approvals",
+        ViolationReason::MissingOwnerReview { .. } => "missing_owner_review",
+    }
+}
+
+fn render_junit(violations: &[Violation]) -> String {
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    // A passing run still reports one testcase; some dashboards flag empty suites.
+    let tests = violations.len().max(1);
+    xml.push_str(&format!(
+        "<testsuites name=\"trueflow\" tests=\"{tests}\" failures=\"{}\">\n",
+        violations.len()
+    ));
+    xml.push_str(&format!(
+        "  <testsuite name=\"trueflow check\" tests=\"{tests}\" failures=\"{}\">\n",
+        violations.len()
+    ));
+    if violations.is_empty() {
+        xml.push_str("    <testcase classname=\"trueflow\" name=\"all changes reviewed\"/>\n");
+    }
+    for violation in violations {
+        let message = summary(&violation.reason);
+        xml.push_str(&format!(
+            "    <testcase classname=\"{}\" name=\"{}\" file=\"{}\" line=\"{}\">\n",
+            escape_xml(&violation.file),
+            escape_xml(&format!(
+                "{}:{} {}",
+                violation.file, violation.line, violation.fingerprint
+            )),
+            escape_xml(&violation.file),
+            violation.line
+        ));
+        xml.push_str(&format!(
+            "      <failure type=\"{}\" message=\"{}\">{}:{} {}</failure>\n",
+            reason_tag(&violation.reason),
+            escape_xml(&message),
+            escape_xml(&violation.file),
+            violation.line,
+            escape_xml(&violation.fingerprint)
+        ));
+        xml.push_str("    </testcase>\n");
+    }
+    xml.push_str("  </testsuite>\n</testsuites>\n");
+    xml
+}
+
+/// A GitHub Actions `::error` command, which annotates the line on the PR.
+fn render_github(violation: &Violation) -> String {
+    format!(
+        "::error file={},line={},title={}::{} {}",
+        escape_property(&violation.file),
+        violation.line,
+        escape_property(&format!("trueflow: {}", reason_tag(&violation.reason))),
+        escape_data(&summary(&violation.reason)),
+        escape_data(&violation.fingerprint)
+    )
+}
+
+fn escape_data(text: &str) -> String {
+    text.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn escape_property(text: &str) -> String {
+    escape_data(text).replace(':', "%3A").replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn violation(file: &str, reason: ViolationReason) -> Violation {
+        Violation {
+            file: file.to_string(),
+            line: 3,
+            fingerprint: "abc123".to_string(),
+            reason,
+        }
+    }
+
+    #[test]
+    fn targets_pick_the_diff() {
+        assert_eq!(diff_base(None).unwrap(), DiffBase::MainToHead);
+        assert_eq!(
+            diff_base(Some("rev:abc")).unwrap(),
+            DiffBase::Revision("abc".to_string())
+        );
+        assert_eq!(
+            diff_base(Some("rev:origin/main..")).unwrap(),
+            DiffBase::Range {
+                start: "origin/main".to_string(),
+                end: "HEAD".to_string(),
+            }
+        );
+        assert_eq!(diff_base(Some("staged")).unwrap(), DiffBase::Staged);
+        assert!(diff_base(Some("file:src/lib.rs")).is_err());
+    }
+
+    #[test]
+    fn github_annotations_escape_properties() {
+        let line = render_github(&violation(
+            "src/a,b.rs",
+            ViolationReason::Rejected {
+                check: "review".to_string(),
+            },
+        ));
+        assert_eq!(
+            line,
+            "::error file=src/a%2Cb.rs,line=3,title=trueflow%3A rejected::rejected (review) abc123"
+        );
+    }
+
+    #[test]
+    fn junit_has_a_failing_testcase_per_violation() {
+        let xml = render_junit(&[violation(
+            "src/<lib>.rs",
+            ViolationReason::Unreviewed {
+                status: "unreviewed".to_string(),
+            },
+        )]);
+        assert!(xml.contains("tests=\"1\" failures=\"1\""));
+        assert!(xml.contains("classname=\"src/&lt;lib&gt;.rs\""));
+        assert!(xml.contains("<failure type=\"unreviewed\""));
+
+        let passing = render_junit(&[]);
+        assert!(passing.contains("failures=\"0\""));
+        assert!(!passing.contains("<failure"));
+    }
+}
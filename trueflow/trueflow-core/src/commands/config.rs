@@ -0,0 +1,189 @@
+use crate::config::find_config_path;
+use crate::config_layers::{
+    ConfigLayers, ConfigSource, Layer, defaults, lookup, parse_value, repo_config_path,
+    set_in_document, user_config_path,
+};
+use crate::output::StructuredFormat;
+use crate::store::workspace_root;
+use anyhow::{Context, Result, bail};
+use serde::Serialize;
+use std::fs;
+use toml::Value;
+
+/// A key as `--output` prints it.
+#[derive(Serialize)]
+struct EntryOutput {
+    key: String,
+    value: Value,
+    source: String,
+}
+
+/// Prints `key`'s effective value; strings print unquoted.
+pub fn get(key: &str, output: Option<StructuredFormat>) -> Result<()> {
+    let Some((value, source)) = ConfigLayers::load()?.get(key)? else {
+        bail!("{key} is not set and has no default");
+    };
+    if let Some(format) = output {
+        return format.print(&EntryOutput {
+            key: key.to_string(),
+            value,
+            source: source.to_string(),
+        });
+    }
+    match value {
+        Value::String(value) => println!("{value}"),
+        value => println!("{value}"),
+    }
+    Ok(())
+}
+
+/// Writes `key = value` to the repo's `trueflow.toml`, or with `user` to the
+/// per-user config, keeping the rest of the file as it is.
+pub fn set(key: &str, raw: &str, user: bool) -> Result<()> {
+    let (path, source) = if user {
+        let path =
+            user_config_path().context("No user config directory; set TRUEFLOW_CONFIG_DIR")?;
+        (path.clone(), ConfigSource::User(path))
+    } else {
+        let path = repo_config_path(&workspace_root()?);
+        (path.clone(), ConfigSource::Repo(path))
+    };
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(err) => {
+            return Err(err).with_context(|| format!("Failed to read {}", path.display()));
+        }
+    };
+    let value = parse_value(raw);
+    let updated = set_in_document(&content, key, &value)?;
+
+    let layers = ConfigLayers {
+        layers: vec![Layer {
+            source,
+            table: toml::from_str(&updated)?,
+        }],
+    };
+    if layers
+        .unknown_keys()?
+        .iter()
+        .any(|entry| entry.key == key || entry.key.starts_with(&format!("{key}.")))
+    {
+        bail!("Unknown config key {key}");
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, updated).with_context(|| format!("Failed to write {}", path.display()))?;
+    println!("Set {key} = {value} in {}", path.display());
+    Ok(())
+}
+
+/// Every key some layer sets, with its value and source; `all` adds the
+/// built-in defaults of the rest.
+pub fn list(all: bool, output: Option<StructuredFormat>) -> Result<()> {
+    let layers = ConfigLayers::load()?;
+    let mut entries: Vec<(String, Value, ConfigSource)> = layers
+        .entries()
+        .into_iter()
+        .map(|entry| (entry.key, entry.value, entry.source))
+        .collect();
+    if all {
+        let merged = layers.merged();
+        let defaults = ConfigLayers {
+            layers: vec![Layer {
+                source: ConfigSource::Default,
+                table: defaults()?,
+            }],
+        };
+        entries.extend(
+            defaults
+                .entries()
+                .into_iter()
+                .filter(|entry| lookup(&merged, &entry.key).is_none())
+                .map(|entry| (entry.key, entry.value, ConfigSource::Default)),
+        );
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+    }
+    if let Some(format) = output {
+        let entries: Vec<EntryOutput> = entries
+            .into_iter()
+            .map(|(key, value, source)| EntryOutput {
+                key,
+                value,
+                source: source.to_string(),
+            })
+            .collect();
+        return format.print(&entries);
+    }
+    if entries.is_empty() {
+        println!("No config set; every setting is at its default.");
+        return Ok(());
+    }
+    for (key, value, source) in entries {
+        println!("{key} = {value}  # {source}");
+    }
+    Ok(())
+}
+
+/// Shows where config is read from and checks that the merged result is
+/// valid and has no keys trueflow ignores.
+pub fn doctor() -> Result<()> {
+    println!("Config layers, lowest precedence first:");
+    println!("  default   built in");
+    match user_config_path() {
+        Some(path) if path.is_file() => println!("  user      {}", path.display()),
+        Some(path) => println!("  user      {} (not found)", path.display()),
+        None => println!("  user      (no config directory)"),
+    }
+    match find_config_path(&std::env::current_dir()?) {
+        Some(path) => println!("  repo      {}", path.display()),
+        None => println!("  repo      trueflow.toml (not found)"),
+    }
+
+    let layers = match ConfigLayers::load() {
+        Ok(layers) => layers,
+        Err(err) => {
+            println!();
+            println!("Problem: {err:#}");
+            bail!("1 config problem found");
+        }
+    };
+    for layer in &layers.layers {
+        match &layer.source {
+            ConfigSource::Env(var) => println!("  env       {var}"),
+            ConfigSource::Cli(arg) => println!("  --config  {arg}"),
+            _ => {}
+        }
+    }
+
+    let mut problems = Vec::new();
+    match layers.unknown_keys() {
+        Ok(unknown) => problems.extend(
+            unknown
+                .into_iter()
+                .map(|entry| format!("Unknown key {} ({})", entry.key, entry.source)),
+        ),
+        Err(err) => problems.push(format!("{err:#}")),
+    }
+
+    let entries = layers.entries();
+    println!();
+    if entries.is_empty() {
+        println!("Every setting is at its default.");
+    } else {
+        println!("Effective settings:");
+        for entry in entries {
+            println!("  {} = {}  # {}", entry.key, entry.value, entry.source);
+        }
+    }
+    println!();
+    if problems.is_empty() {
+        println!("No problems found.");
+        return Ok(());
+    }
+    for problem in &problems {
+        println!("Problem: {problem}");
+    }
+    bail!("{} config problem(s) found", problems.len())
+}
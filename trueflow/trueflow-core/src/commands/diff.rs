@@ -0,0 +1,69 @@
+use crate::color::Stream;
+use crate::context::TrueflowContext;
+use crate::diff_logic::{ReviewRequirement, get_block_changes, get_unreviewed_changes};
+use crate::output::StructuredFormat;
+use anyhow::Result;
+use std::io::Write;
+
+pub fn run(
+    context: &TrueflowContext,
+    output: Option<StructuredFormat>,
+    blocks: bool,
+) -> Result<()> {
+    if blocks {
+        return run_blocks(context, output);
+    }
+    let unreviewed_changes = get_unreviewed_changes(&ReviewRequirement::default())?;
+
+    if let Some(format) = output {
+        format.print(&unreviewed_changes)?;
+    } else {
+        // Text mode is for humans; keep stdout clean for pipelines.
+        let mut out = context.paged_output(Stream::Stderr);
+        let palette = context.palette(out.stream());
+        for change in unreviewed_changes {
+            writeln!(
+                out,
+                "{} {}",
+                palette.heading(&format!("{}:{}", change.file, change.line)),
+                palette.status(&change.status)
+            )?;
+            writeln!(out, "  {}", palette.dim(&change.fingerprint))?;
+        }
+        out.finish()?;
+    }
+
+    Ok(())
+}
+
+/// `diff --blocks`: changed blocks instead of changed hunks.
+fn run_blocks(context: &TrueflowContext, output: Option<StructuredFormat>) -> Result<()> {
+    let changes = get_block_changes(&ReviewRequirement::default())?;
+
+    if let Some(format) = output {
+        return format.print(&changes);
+    }
+    let mut out = context.paged_output(Stream::Stderr);
+    let palette = context.palette(out.stream());
+    for change in changes {
+        writeln!(
+            out,
+            "{} {} {} {}",
+            palette.heading(&format!("{}:{}", change.file, change.line)),
+            change.change.as_str(),
+            change.kind,
+            palette.status(&change.status)
+        )?;
+        match &change.previous_fingerprint {
+            Some(previous) => writeln!(
+                out,
+                "  {} -> {}",
+                palette.dim(previous),
+                palette.dim(&change.fingerprint)
+            )?,
+            None => writeln!(out, "  {}", palette.dim(&change.fingerprint))?,
+        }
+    }
+    out.finish()?;
+    Ok(())
+}
@@ -0,0 +1,366 @@
+use crate::analysis::Language;
+use crate::block::Block;
+use crate::config::load as load_config;
+use crate::context::TrueflowContext;
+use crate::output::{FeedbackFormat, escape_xml};
+use crate::policy::{should_skip_generated_by_default, should_skip_imports_by_default};
+use crate::resolution::resolved_ids;
+use crate::scanner;
+use crate::store::{Record, Verdict, approved_hashes_from_verdicts, open_store, without_revoked};
+use crate::time_window::since_timestamp;
+use crate::tree;
+use anyhow::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A reviewed block in `feedback --format json`.
+#[derive(Serialize, JsonSchema)]
+pub struct FeedbackEntry {
+    pub file: String,
+    pub block: Block,
+    pub reviews: Vec<Record>,
+    /// The newest verdict, e.g. `rejected`.
+    pub latest_verdict: String,
+}
+
+/// Which reviewed blocks `feedback` exports, and which of their reviews.
+#[derive(Debug, Default)]
+pub struct FeedbackFilter {
+    /// Include approved blocks (for few-shot examples).
+    pub include_approved: bool,
+    /// Include verdicts marked resolved, and answered questions.
+    pub include_resolved: bool,
+    pub only: Vec<String>,
+    pub exclude: Vec<String>,
+    /// Only reviews by this identity (email, or `agent:<name>`).
+    pub author: Option<String>,
+    /// Only verdicts under this check; the others don't count at all.
+    pub check: Option<String>,
+    /// Only reviews since a time (`7d`, `2024-05-01`, unix time) or the
+    /// commit time of a revision.
+    pub since: Option<String>,
+    /// Only blocks whose latest verdict is one of these.
+    pub verdicts: Vec<String>,
+}
+
+pub fn run(
+    _context: &TrueflowContext,
+    format: FeedbackFormat,
+    filter: FeedbackFilter,
+) -> Result<()> {
+    let entries = collect_feedback(&filter)?;
+    match format {
+        FeedbackFormat::Xml => print_xml(&entries),
+        FeedbackFormat::Markdown => {
+            print!("{}", render_markdown(&entries, filter.include_approved))
+        }
+        structured => {
+            if let Some(format) = structured.structured() {
+                format.print(&entries)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Blocks in the working tree with reviews `filter` keeps, by path. Feedback
+/// on blocks that have since changed or been deleted is left out, since
+/// their fingerprints are no longer in the tree.
+pub fn collect_feedback(filter: &FeedbackFilter) -> Result<Vec<FeedbackEntry>> {
+    let config = load_config()?;
+    let filters = config
+        .feedback
+        .resolve_filters(&filter.only, &filter.exclude);
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+    let since = filter
+        .since
+        .as_deref()
+        .map(|since| since_timestamp(since, now))
+        .transpose()?;
+    let verdicts = filter
+        .verdicts
+        .iter()
+        .map(|verdict| verdict.parse::<Verdict>())
+        .collect::<Result<Vec<_>>>()?;
+
+    let files = scanner::scan_directory(".")?;
+    let tree = tree::build_tree_from_files(&files);
+
+    let store = open_store()?;
+    let history = without_revoked(store.read_history()?);
+    let resolved: HashSet<String> = if filter.include_resolved {
+        HashSet::new()
+    } else {
+        resolved_ids(&history)
+            .into_iter()
+            .map(str::to_string)
+            .collect()
+    };
+
+    // Every review of a fingerprint, not just the latest.
+    let mut reviews_by_fp: HashMap<String, Vec<Record>> = HashMap::new();
+    let mut latest_verdict: HashMap<String, Verdict> = HashMap::new();
+
+    for record in history.into_iter().filter(|record| {
+        record.kind.is_verdict()
+            && filter
+                .check
+                .as_deref()
+                .is_none_or(|check| record.check == check)
+    }) {
+        // Last write wins, whoever wrote it: a rejection someone else has
+        // since approved is no longer actionable.
+        latest_verdict.insert(record.fingerprint.clone(), record.verdict.clone());
+        let wanted = filter
+            .author
+            .as_deref()
+            .is_none_or(|author| record.identity.label() == author.trim())
+            && since.is_none_or(|since| record.timestamp >= since)
+            && !resolved.contains(&record.id);
+        if wanted {
+            reviews_by_fp
+                .entry(record.fingerprint.clone())
+                .or_default()
+                .push(record);
+        }
+    }
+
+    let approved_hashes = approved_hashes_from_verdicts(&latest_verdict);
+
+    let mut entries = Vec::new();
+    for file in files {
+        if should_skip_generated_by_default(&file, &filters) {
+            continue;
+        }
+        for block in file.blocks {
+            if !filters.allows_block(&block.kind) {
+                continue;
+            }
+            if should_skip_imports_by_default(&file.path, &block, &filters) {
+                continue;
+            }
+
+            let verdict = latest_verdict
+                .get(&block.hash)
+                .map(|value| value.as_str())
+                .unwrap_or("unreviewed");
+
+            if !verdicts.is_empty() && !verdicts.iter().any(|wanted| wanted.as_str() == verdict) {
+                continue;
+            }
+
+            if !filter.include_approved && verdict == "approved" {
+                continue;
+            }
+
+            if !filter.include_approved
+                && tree
+                    .node_by_path_and_hash(&file.path, &block.hash)
+                    .is_some_and(|node_id| tree.is_node_covered(node_id, &approved_hashes))
+            {
+                continue;
+            }
+
+            // Feedback means critiques: blocks nobody reviewed have none.
+            if let Some(reviews) = reviews_by_fp.get(&block.hash) {
+                entries.push(FeedbackEntry {
+                    file: file.path.clone(),
+                    latest_verdict: verdict.to_string(),
+                    block,
+                    reviews: reviews.clone(),
+                });
+            }
+        }
+    }
+    entries.sort_by(|a, b| a.file.cmp(&b.file));
+    Ok(entries)
+}
+
+fn print_xml(entries: &[FeedbackEntry]) {
+    println!("<trueflow_feedback>");
+    for file in entries.chunk_by(|a, b| a.file == b.file) {
+        println!("  <file path=\"{}\">", escape_xml(&file[0].file));
+        for entry in file {
+            print_block_xml(&entry.block, &entry.reviews);
+        }
+        println!("  </file>");
+    }
+    println!("</trueflow_feedback>");
+}
+
+/// A prompt for a coding agent: the reviewed blocks grouped by file, each
+/// with its code and the reviewers' notes, then what to do about them.
+pub fn render_markdown(entries: &[FeedbackEntry], include_approved: bool) -> String {
+    let mut out = String::from("# Review feedback\n\n");
+    if entries.is_empty() {
+        out.push_str("No open review feedback.\n");
+        return out;
+    }
+    let files = entries.chunk_by(|a, b| a.file == b.file).count();
+    let _ = writeln!(
+        out,
+        "Reviewers left feedback on {} block(s) in {} file(s).",
+        entries.len(),
+        files
+    );
+
+    for file in entries.chunk_by(|a, b| a.file == b.file) {
+        let path = &file[0].file;
+        let language = Path::new(path)
+            .extension()
+            .and_then(|ext| Language::from_extension(&ext.to_string_lossy()));
+        let _ = writeln!(out, "\n## `{}`", path);
+        for entry in file {
+            let block = &entry.block;
+            let _ = writeln!(
+                out,
+                "\n### Lines {}-{} ({}): {}\n",
+                block.start_line + 1,
+                block.end_line,
+                block.kind,
+                entry.latest_verdict
+            );
+            let fence = code_fence(&block.content);
+            let _ = writeln!(
+                out,
+                "{fence}{}\n{}\n{fence}\n",
+                language.as_ref().map_or("", fence_language),
+                block.content.trim_end_matches('\n')
+            );
+            for review in &entry.reviews {
+                let author = if review.identity.is_agent() {
+                    format!("{} (agent)", review.identity)
+                } else {
+                    review.identity.to_string()
+                };
+                let _ = write!(
+                    out,
+                    "- **{}** by {} (`{}`)",
+                    review.verdict, author, review.id
+                );
+                match review.note.as_deref().map(str::trim) {
+                    Some(note) if !note.is_empty() => {
+                        let _ = writeln!(out, ": {}", note.replace('\n', "\n  "));
+                    }
+                    _ => out.push('\n'),
+                }
+                for reference in review.refs.iter().flatten() {
+                    let _ = writeln!(out, "  - See {}", reference);
+                }
+            }
+        }
+    }
+
+    out.push_str(
+        "\n## What to do\n\n\
+         1. Change the code of every rejected block so it addresses the notes above.\n\
+         2. Answer every question, in the code or in your reply.\n",
+    );
+    if include_approved {
+        out.push_str("3. Leave approved blocks as they are; they show what reviewers accept.\n");
+    }
+    out.push_str(
+        "\nThen re-run `trueflow feedback --format markdown`; blocks you changed drop out \
+         once they are reviewed again. To close an item without changing its block, run \
+         `trueflow resolve --id <id> --note \"<why>\"` with the id after its verdict.\n",
+    );
+    out
+}
+
+/// A backtick fence longer than any run of backticks in `content`.
+fn code_fence(content: &str) -> String {
+    let longest = content
+        .split(|ch| ch != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or(0);
+    "`".repeat(longest.max(2) + 1)
+}
+
+fn fence_language(language: &Language) -> &'static str {
+    match language {
+        Language::Rust => "rust",
+        Language::Elisp => "elisp",
+        Language::JavaScript => "javascript",
+        Language::TypeScript => "typescript",
+        Language::Python => "python",
+        Language::Shell => "sh",
+        Language::C => "c",
+        Language::Cpp => "cpp",
+        Language::Sql => "sql",
+        Language::Yaml => "yaml",
+        Language::Markdown => "markdown",
+        Language::Toml => "toml",
+        Language::Nix => "nix",
+        Language::Just => "just",
+        Language::Text | Language::Unknown => "",
+    }
+}
+
+fn print_block_xml(block: &Block, reviews: &[Record]) {
+    println!(
+        "    <block start_line=\"{}\" end_line=\"{}\" kind=\"{}\" hash=\"{}\">",
+        block.start_line,
+        block.end_line,
+        escape_xml(block.kind.as_str()),
+        block.hash
+    );
+
+    println!("      <context><![CDATA[");
+    let safe_content = block.content.replace("]]>", "]]]]><![CDATA[>");
+    println!("{}", safe_content);
+    println!("]]></context>");
+
+    println!("      <reviews>");
+    for r in reviews {
+        let agent_attr = if r.identity.is_agent() {
+            " agent=\"true\""
+        } else {
+            ""
+        };
+        let confidence_attr = r
+            .confidence
+            .map(|confidence| format!(" confidence=\"{confidence:.2}\""))
+            .unwrap_or_default();
+        println!(
+            "        <review verdict=\"{}\" author=\"{}\"{}{} id=\"{}\">",
+            escape_xml(r.verdict.as_str()),
+            escape_xml(&r.identity.to_string()),
+            agent_attr,
+            confidence_attr,
+            escape_xml(&r.id)
+        );
+        if let Some(note) = &r.note {
+            println!("          <comment>{}</comment>", escape_xml(note));
+        }
+        for reference in r.refs.iter().flatten() {
+            println!("          <ref>{}</ref>", escape_xml(reference));
+        }
+        println!("        </review>");
+    }
+    println!("      </reviews>");
+    println!("    </block>");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fence_outgrows_backticks_in_content() {
+        assert_eq!(code_fence("fn main() {}"), "```");
+        assert_eq!(code_fence("let s = \"```\";"), "````");
+        assert_eq!(code_fence("`a` ``b``"), "```");
+    }
+
+    #[test]
+    fn markdown_without_entries_says_so() {
+        let markdown = render_markdown(&[], false);
+        assert!(markdown.contains("No open review feedback."));
+        assert!(!markdown.contains("What to do"));
+    }
+}
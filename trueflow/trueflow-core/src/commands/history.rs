@@ -0,0 +1,95 @@
+use crate::color::Stream;
+use crate::commands::review::format_timestamp;
+use crate::context::TrueflowContext;
+use crate::output::StructuredFormat;
+use crate::store::{RecordQuery, Verdict, open_store};
+use crate::time_window::TimeWindow;
+use anyhow::Result;
+use log::info;
+use std::io::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub struct HistoryParams {
+    pub fingerprint: Option<String>,
+    pub path: Option<String>,
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub verdict: Option<String>,
+    /// `email`, `agent` or `machine`.
+    pub identity_type: Option<String>,
+    pub output: Option<StructuredFormat>,
+}
+
+/// Lists matching verdicts from the ledger, newest first.
+pub fn run(context: &TrueflowContext, params: HistoryParams) -> Result<()> {
+    info!(
+        "history start (fingerprint={:?}, path={:?}, since={:?}, until={:?}, verdict={:?})",
+        params.fingerprint, params.path, params.since, params.until, params.verdict
+    );
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+    let query = RecordQuery {
+        fingerprint: params.fingerprint,
+        path: params.path,
+        window: TimeWindow::parse(params.since.as_deref(), params.until.as_deref(), now)?,
+        verdict: params
+            .verdict
+            .as_deref()
+            .map(str::parse::<Verdict>)
+            .transpose()?,
+    };
+
+    let store = open_store()?;
+    let mut records = store.query(&query)?;
+    if let Some(identity_type) = &params.identity_type {
+        records.retain(|record| record.identity.type_name() == identity_type);
+    }
+    records.reverse();
+
+    if let Some(format) = params.output {
+        return format.print(&records);
+    }
+    if records.is_empty() {
+        println!("No matching review records.");
+        return Ok(());
+    }
+
+    let mut out = context.paged_output(Stream::Stdout);
+    let palette = context.palette(out.stream());
+    for record in &records {
+        let check = if record.check == "review" {
+            String::new()
+        } else {
+            format!(" [{}]", record.check)
+        };
+        writeln!(
+            out,
+            "{}  {}{}  {}",
+            palette.dim(&format_timestamp(record.timestamp)),
+            palette.status(record.verdict.as_str()),
+            check,
+            record.identity
+        )?;
+        let location = match (&record.path_hint, record.line_hint) {
+            (Some(path), Some(line)) => format!("  {path}:{line}"),
+            (Some(path), None) => format!("  {path}"),
+            _ => String::new(),
+        };
+        writeln!(
+            out,
+            "  {}{}",
+            palette.heading(&record.fingerprint),
+            location
+        )?;
+        if let Some(note) = record
+            .note
+            .as_deref()
+            .filter(|note| !note.trim().is_empty())
+        {
+            for line in note.lines() {
+                writeln!(out, "    {line}")?;
+            }
+        }
+    }
+    out.finish()?;
+    Ok(())
+}
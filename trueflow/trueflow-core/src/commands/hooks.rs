@@ -0,0 +1,118 @@
+use crate::vcs;
+use anyhow::{Context, Result, bail};
+use log::info;
+use std::fs;
+use std::path::Path;
+
+/// First comment line of every hook we write, so we never touch others.
+const MARKER: &str = "# Installed by `trueflow hooks install`";
+
+/// Gates the staged changes: HEAD (or the empty tree) against the index.
+const PRE_COMMIT: &str = r#"#!/bin/sh
+# Installed by `trueflow hooks install`; remove with `trueflow hooks uninstall`.
+# Fails the commit when staged changes are unreviewed.
+exec "${TRUEFLOW:-trueflow}" check --target staged
+"#;
+
+/// Gates each pushed ref on the commits the remote does not have yet.
+const PRE_PUSH: &str = r#"#!/bin/sh
+# Installed by `trueflow hooks install`; remove with `trueflow hooks uninstall`.
+# Fails the push when pushed commits contain unreviewed changes.
+remote="$1"
+empty_tree=$(git hash-object -w -t tree /dev/null)
+status=0
+while read -r local_ref local_sha remote_ref remote_sha; do
+    case "$local_sha" in *[!0]*) ;; *) continue ;; esac # deleting a ref
+    if git cat-file -e "$remote_sha^{commit}" 2>/dev/null; then
+        base="$remote_sha"
+    else
+        # New branch, or a remote tip we don't have: start after the newest
+        # commit any branch of this remote already has.
+        first=$(git rev-list --reverse "$local_sha" --not --remotes="$remote" | head -n 1)
+        [ -n "$first" ] || continue
+        if git rev-parse --verify -q "$first^" >/dev/null; then
+            base="$first^"
+        else
+            base="$empty_tree"
+        fi
+    fi
+    "${TRUEFLOW:-trueflow}" check --target "rev:$base..$local_sha" </dev/null || status=1
+done
+exit $status
+"#;
+
+const HOOKS: [(&str, &str); 2] = [("pre-commit", PRE_COMMIT), ("pre-push", PRE_PUSH)];
+
+/// Writes the pre-commit and pre-push hooks. Hooks that trueflow didn't
+/// write are kept unless `force` is set.
+pub fn install(force: bool) -> Result<()> {
+    let dir = vcs::hooks_dir(&vcs::repo_from_workdir()?);
+    fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    for (name, script) in HOOKS {
+        let path = dir.join(name);
+        if !force && path.exists() && !is_ours(&path) {
+            bail!(
+                "{} already exists; rerun with --force to replace it",
+                path.display()
+            );
+        }
+        fs::write(&path, script).with_context(|| format!("Failed to write {}", path.display()))?;
+        make_executable(&path)?;
+        info!("installed hook {}", path.display());
+        println!("Installed {}", path.display());
+    }
+    Ok(())
+}
+
+/// Removes the hooks `install` wrote, leaving any others alone.
+pub fn uninstall() -> Result<()> {
+    let dir = vcs::hooks_dir(&vcs::repo_from_workdir()?);
+    for (name, _) in HOOKS {
+        let path = dir.join(name);
+        if !path.exists() {
+            continue;
+        }
+        if !is_ours(&path) {
+            eprintln!("Skipping {}: not installed by trueflow", path.display());
+            continue;
+        }
+        fs::remove_file(&path).with_context(|| format!("Failed to remove {}", path.display()))?;
+        info!("removed hook {}", path.display());
+        println!("Removed {}", path.display());
+    }
+    Ok(())
+}
+
+fn is_ours(path: &Path) -> bool {
+    fs::read_to_string(path).is_ok_and(|script| {
+        script
+            .lines()
+            .nth(1)
+            .is_some_and(|line| line.starts_with(MARKER))
+    })
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755))?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scripts_carry_the_marker() {
+        for (_, script) in HOOKS {
+            assert!(script.starts_with("#!/bin/sh\n"));
+            assert!(script.lines().nth(1).unwrap().starts_with(MARKER));
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use crate::commands::hooks;
+use crate::config::CONFIG_FILE_NAME;
+use crate::store::{TRUEFLOW_DIR, workspace_root};
+use anyhow::{Context, Result};
+use log::info;
+use std::fs;
+use std::path::Path;
+
+/// Machine-local state under `.trueflow/` that `init` adds to `.gitignore`:
+/// the scan cache, logs, TUI sessions, the S3 and SQLite backends' local
+/// files, and progress snapshots. The ledger itself stays tracked.
+const LOCAL_STATE_IGNORES: &[&str] = &[
+    ".trueflow/cache",
+    ".trueflow/logs/",
+    ".trueflow/last_session.json",
+    ".trueflow/session.json",
+    ".trueflow/s3-cache/",
+    ".trueflow/reviews.db",
+    ".trueflow/metrics.jsonl",
+];
+
+const WORKFLOW_PATH: &str = ".github/workflows/trueflow.yml";
+
+/// Starter `trueflow.toml`: the settings most repositories change first.
+/// `trueflow.example.toml` documents the rest.
+const STARTER_CONFIG: &str = r#"# trueflow configuration. Every key is optional; `trueflow config list --all`
+# shows the effective value of each, and `trueflow config doctor` checks them.
+
+[review]
+# Only include specific block kinds (case-insensitive), e.g. ["function"].
+# Empty means all.
+only = []
+# Exclude block kinds from review, e.g. ["gap", "comment"].
+exclude = []
+# Include generated files (lockfiles, `@generated`, linguist-generated).
+include_generated = false
+
+[vcs]
+# Ref that `diff`, `check` and `review --target base` compare HEAD against.
+# Defaults to main, then master.
+# base_branch = "develop"
+
+[tui]
+# Require confirmation before batch actions.
+confirm_batch = true
+# Palette: "light", "dark", or "auto" to match the terminal background.
+theme = "auto"
+
+[tui.keys]
+# Replace the keys of any action; press `?` in the TUI for the full list.
+# approve = ["y"]
+# reject = ["r"]
+"#;
+
+/// GitHub Actions job failing pull requests with unreviewed changes.
+const CI_WORKFLOW: &str = r#"# Written by `trueflow init --ci`.
+name: trueflow
+
+on:
+  pull_request:
+
+jobs:
+  review-gate:
+    runs-on: ubuntu-latest
+    steps:
+      - uses: actions/checkout@v4
+        with:
+          fetch-depth: 0
+      - run: cargo install --locked --git https://github.com/trueflow-dev/trueflow trueflow
+      - run: trueflow --base "origin/${{ github.base_ref }}" check --format github
+"#;
+
+pub struct InitParams {
+    pub hooks: bool,
+    pub ci: bool,
+    pub force: bool,
+}
+
+/// Sets up the workspace for review: `.trueflow/`, a starter
+/// `trueflow.toml`, local state in `.gitignore`, and optionally git hooks
+/// and a CI workflow. Existing files are kept unless `force` is set.
+pub fn run(params: InitParams) -> Result<()> {
+    let root = workspace_root()?;
+    info!("init start (root={})", root.display());
+
+    let trueflow_dir = root.join(TRUEFLOW_DIR);
+    fs::create_dir_all(&trueflow_dir)
+        .with_context(|| format!("Failed to create {}", trueflow_dir.display()))?;
+    write_file(&root.join(CONFIG_FILE_NAME), STARTER_CONFIG, params.force)?;
+    ignore_local_state(&root)?;
+    if params.hooks {
+        hooks::install(params.force)?;
+    }
+    if params.ci {
+        write_file(&root.join(WORKFLOW_PATH), CI_WORKFLOW, params.force)?;
+    }
+    println!("Initialized trueflow in {}", root.display());
+    Ok(())
+}
+
+fn write_file(path: &Path, content: &str, force: bool) -> Result<()> {
+    if !force && path.exists() {
+        println!("Kept existing {} (--force replaces it)", path.display());
+        return Ok(());
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    fs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))?;
+    println!("Wrote {}", path.display());
+    Ok(())
+}
+
+/// Appends each local state entry to `.gitignore` unless a line already
+/// ignores it.
+fn ignore_local_state(root: &Path) -> Result<()> {
+    let path = root.join(".gitignore");
+    let mut content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(err) => {
+            return Err(err).with_context(|| format!("Failed to read {}", path.display()));
+        }
+    };
+    let missing: Vec<&str> = LOCAL_STATE_IGNORES
+        .iter()
+        .copied()
+        .filter(|entry| !ignores(&content, entry))
+        .collect();
+    if missing.is_empty() {
+        return Ok(());
+    }
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    for entry in &missing {
+        content.push_str(entry);
+        content.push('\n');
+    }
+    fs::write(&path, content).with_context(|| format!("Failed to write {}", path.display()))?;
+    println!("Added {} to {}", missing.join(", "), path.display());
+    Ok(())
+}
+
+fn ignores(gitignore: &str, entry: &str) -> bool {
+    let entry = entry.trim_end_matches('/');
+    gitignore.lines().any(|line| {
+        let line = line.trim().trim_start_matches('/').trim_end_matches('/');
+        line == entry || line == TRUEFLOW_DIR
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::TrueflowConfig;
+
+    #[test]
+    fn starter_config_parses() {
+        let config: TrueflowConfig = toml::from_str(STARTER_CONFIG).unwrap();
+        assert!(config.tui.confirm_batch);
+        assert!(config.vcs.base_branch.is_none());
+    }
+
+    #[test]
+    fn entries_count_as_ignored_with_or_without_slashes() {
+        assert!(ignores("target/\n/.trueflow/cache/\n", ".trueflow/cache"));
+        assert!(ignores(".trueflow/logs\n", ".trueflow/logs/"));
+        assert!(ignores(".trueflow\n", ".trueflow/reviews.db"));
+        assert!(!ignores("target/\n.trueflow/logs\n", ".trueflow/cache"));
+    }
+}
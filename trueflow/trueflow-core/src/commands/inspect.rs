@@ -0,0 +1,72 @@
+use crate::analysis::Language;
+use crate::block::{Block, FileState};
+use crate::context::TrueflowContext;
+use crate::output::StructuredFormat;
+use crate::scanner;
+use crate::sub_splitter;
+use anyhow::{Result, anyhow, bail};
+
+/// A block found by fingerprint, with the file it is in.
+pub struct BlockMatch {
+    pub path: String,
+    pub language: Language,
+    pub block: Block,
+}
+
+pub fn run(context: &TrueflowContext, fingerprint: &str, split: bool) -> Result<()> {
+    let files = scanner::scan_directory(".")?;
+    let format = context.output.unwrap_or(StructuredFormat::Json);
+    let found = find_block(&files, fingerprint)?.ok_or_else(|| anyhow!("Block not found"))?;
+    if split {
+        let sub_blocks = sub_splitter::split(&found.block, found.language)?;
+        format.print(&sub_blocks)?;
+    } else {
+        format.print(&found.block)?;
+    }
+
+    Ok(())
+}
+
+/// The block whose hash starts with `fingerprint`, looking into sub-blocks
+/// when no top-level block matches. Errors when the prefix is ambiguous.
+pub fn find_block(files: &[FileState], fingerprint: &str) -> Result<Option<BlockMatch>> {
+    let mut matches = Vec::new();
+
+    for file in files {
+        for block in &file.blocks {
+            if block.hash.starts_with(fingerprint) {
+                matches.push(BlockMatch {
+                    path: file.path.clone(),
+                    language: file.language.clone(),
+                    block: block.clone(),
+                });
+            }
+        }
+    }
+
+    if matches.is_empty() {
+        for file in files {
+            for block in &file.blocks {
+                if let Ok(sub_blocks) = sub_splitter::split(block, file.language.clone()) {
+                    for sub_block in sub_blocks {
+                        if sub_block.hash.starts_with(fingerprint) {
+                            matches.push(BlockMatch {
+                                path: file.path.clone(),
+                                language: file.language.clone(),
+                                block: sub_block,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    if matches.len() > 1 {
+        bail!(
+            "Multiple blocks matched fingerprint ({} matches). Use a longer prefix.",
+            matches.len()
+        );
+    }
+    Ok(matches.pop())
+}
@@ -0,0 +1,31 @@
+use crate::config::{OidcConfig, default_oidc_scopes, load as load_config};
+use crate::oidc;
+use anyhow::{Result, bail};
+use log::info;
+
+/// Signs in with `[identity.oidc]`, or the issuer and client id given, and
+/// caches the identity for `[identity] provider = "oidc"`.
+pub fn run(issuer: Option<String>, client_id: Option<String>) -> Result<()> {
+    let configured = load_config()?.identity.oidc;
+    let scopes = configured
+        .as_ref()
+        .map(|oidc| oidc.scopes.clone())
+        .unwrap_or_else(default_oidc_scopes);
+    let (issuer, client_id) = match (issuer, client_id, configured) {
+        (Some(issuer), Some(client_id), _) => (issuer, client_id),
+        (issuer, client_id, Some(oidc)) => (
+            issuer.unwrap_or(oidc.issuer),
+            client_id.unwrap_or(oidc.client_id),
+        ),
+        _ => bail!("Set [identity.oidc] issuer and client_id, or pass --issuer and --client-id"),
+    };
+    info!("login start (issuer={issuer}, client_id={client_id})");
+
+    let identity = oidc::login(&OidcConfig {
+        issuer,
+        client_id,
+        scopes,
+    })?;
+    println!("Logged in as {identity}.");
+    Ok(())
+}
@@ -0,0 +1,520 @@
+use crate::block::{Block, BlockKind};
+use crate::block_context::ContextIndex;
+use crate::commands::review::{normalize_path_str, repo_relative_path, workdir_prefix_in};
+use crate::config::{self, TrueflowConfig};
+use crate::context::TrueflowContext;
+use crate::identity::CurrentIdentity;
+use crate::lineage::LineageIndex;
+use crate::policy::{counted_records, review_state};
+use crate::scanner::{self, ScanSettings};
+use crate::signing::Signer;
+use crate::store::{
+    BlockState, Provenance, Record, RecordKind, RepoRef, ReviewStore, Revocation, Verdict,
+    approved_hashes_from_verdicts, find_by_id, open_store, open_store_in, without_revoked,
+    workspace_root_in,
+};
+use crate::tree::{self, TreeNodeKind};
+use crate::vcs;
+use crate::vcs_provider;
+use anyhow::{Context, Result, bail};
+use log::{info, warn};
+use serde::Deserialize;
+use std::io::Read;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone)]
+pub struct MarkParams {
+    pub fingerprint: String,
+    pub verdict: Verdict,
+    pub check: String,
+    pub note: Option<String>,
+    pub path: Option<String>,
+    pub line: Option<u32>,
+    pub provenance: Option<Provenance>,
+    pub confidence: Option<f64>,
+    pub refs: Vec<String>,
+    /// Id (or unique prefix) of the record this one replies to. An empty
+    /// fingerprint, path or line is taken from that record.
+    pub in_reply_to: Option<String>,
+    /// Sign the record even when `[identity] sign` is off.
+    pub sign: bool,
+}
+
+pub fn run(context: &TrueflowContext, params: MarkParams) -> Result<()> {
+    record(context, params).map(|_| ())
+}
+
+/// Appends the verdict and returns the new record's id.
+pub fn record(context: &TrueflowContext, params: MarkParams) -> Result<String> {
+    let mut ids = record_all(context, vec![params])?;
+    Ok(ids.remove(0))
+}
+
+/// Appends one verdict per entry in a single write, resolving the identity
+/// and repo state once, and returns the new records' ids. Nothing is
+/// written when any entry is invalid.
+pub fn record_all(context: &TrueflowContext, batch: Vec<MarkParams>) -> Result<Vec<String>> {
+    record_all_in(context, Path::new("."), &config::load()?, batch)
+}
+
+/// [`record_all`] in `workdir` under `config`, leaving the current
+/// directory alone. Paths in `batch` are relative to `workdir`.
+pub fn record_all_in(
+    context: &TrueflowContext,
+    workdir: &Path,
+    config: &TrueflowConfig,
+    batch: Vec<MarkParams>,
+) -> Result<Vec<String>> {
+    let store = open_store_in(&workspace_root_in(workdir)?, &config.store)?;
+    let mut current = CurrentIdentity::resolve_in(context, workdir, &config.identity)?;
+    current.sign |= batch.iter().any(|params| params.sign);
+    let signer = current.signer_in(workdir)?;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+    let settings = ScanSettings::from_config(config);
+    let repo_snapshot = vcs::snapshot_with(workdir, &settings);
+    let repo_ref = repo_ref_from_snapshot(&repo_snapshot);
+    // Replies resolve their parent, and marked paths their lineage, from
+    // the history.
+    let needs_history = batch
+        .iter()
+        .any(|params| params.path.is_some() || params.in_reply_to.is_some());
+    let history = if needs_history {
+        without_revoked(store.read_history()?)
+    } else {
+        Vec::new()
+    };
+    // Replies take the path of their parent, so resolve them before
+    // scanning.
+    let batch = batch
+        .into_iter()
+        .map(|mut params| {
+            let in_reply_to = match params.in_reply_to.take() {
+                Some(id) => Some(reply_parent(&history, &id, &mut params)?),
+                None => None,
+            };
+            Ok((params, in_reply_to))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let base_revision = vcs::merge_base_and_head_in(workdir, config)
+        .ok()
+        .map(|(base, _)| base);
+    // A verdict without a path is found by its fingerprint.
+    let contexts = if batch.iter().any(|(params, _)| params.path.is_none()) {
+        ContextIndex::scan_all_in(workdir, &settings, base_revision)?
+    } else {
+        let paths = batch
+            .iter()
+            .filter_map(|(params, _)| params.path.as_deref().map(normalize_path_str))
+            .collect();
+        ContextIndex::scan_in(workdir, &settings, &paths, base_revision)?
+    };
+
+    let mut records = Vec::with_capacity(batch.len());
+    let mut marked = Vec::new();
+    for (params, in_reply_to) in batch {
+        info!(
+            "mark start (fingerprint={}, verdict={}, check={}, note_present={}, path={:?}, line={:?})",
+            &params.fingerprint,
+            &params.verdict,
+            &params.check,
+            params.note.is_some(),
+            params.path.as_deref(),
+            params.line
+        );
+        let block_state: BlockState =
+            vcs::block_state_for_path(&repo_snapshot, params.path.as_deref(), &params.fingerprint)
+                .into();
+
+        let MarkParams {
+            fingerprint,
+            verdict,
+            check,
+            note,
+            path,
+            line,
+            provenance,
+            confidence,
+            refs,
+            in_reply_to: _,
+            sign: _,
+        } = params;
+        if let Some(confidence) = confidence
+            && !(0.0..=1.0).contains(&confidence)
+        {
+            bail!("Confidence must be between 0 and 1, got {}", confidence);
+        }
+
+        let normalized_path = path.as_deref().map(normalize_path_str);
+        let context_path = match &normalized_path {
+            Some(path) => Some(path.as_str()),
+            None => contexts.locate(&fingerprint).or_else(|| {
+                warn!(
+                    "Block {} isn't in the workspace; recording it without its context",
+                    fingerprint
+                );
+                None
+            }),
+        };
+        let context = contexts.context(context_path, &fingerprint, line);
+        if let Some(path) = normalized_path {
+            marked.push((path, fingerprint.clone()));
+        }
+        let record = Record {
+            path_hint: path,
+            line_hint: line,
+            note,
+            in_reply_to,
+            provenance: provenance.filter(|provenance| !provenance.is_empty()),
+            confidence,
+            refs: normalize_refs(refs),
+            context,
+            ..Record::verdict(
+                fingerprint.clone(),
+                check.clone(),
+                verdict.clone(),
+                current.identity.clone(),
+                repo_ref.clone(),
+                block_state,
+                now,
+            )
+        };
+
+        records.push(record);
+        info!(
+            "mark prepared (fingerprint={}, check={}, verdict={})",
+            fingerprint,
+            check,
+            verdict.as_str()
+        );
+
+        let signed_msg = if current.sign { " (Signed)" } else { "" };
+        info!(
+            "Recorded verdict '{}' for {} by {}{}",
+            verdict, fingerprint, current.identity, signed_msg
+        );
+    }
+
+    if let Some(signer) = &signer {
+        for record in &mut records {
+            record.attestations = Some(vec![signer.attest(record)?]);
+        }
+    }
+    let ids = records.iter().map(|record| record.id.clone()).collect();
+    records.extend(lineage_records(
+        workdir, config, history, &contexts, &marked, &repo_ref, now,
+    )?);
+    store.append_all(records)?;
+    Ok(ids)
+}
+
+/// Links from the marked blocks to the approved blocks they were edited
+/// from. `review` finds these against HEAD without writing anything;
+/// recording them with a verdict keeps them once the original leaves HEAD.
+fn lineage_records(
+    workdir: &Path,
+    config: &TrueflowConfig,
+    history: Vec<Record>,
+    contexts: &ContextIndex,
+    marked: &[(String, String)],
+    repo_ref: &RepoRef,
+    now: i64,
+) -> Result<Vec<Record>> {
+    if marked.is_empty() {
+        return Ok(Vec::new());
+    }
+    let policy = &config.policy;
+    let history = counted_records(history, policy);
+    let approved = approved_hashes_from_verdicts(&review_state(&history, policy, None));
+    let mut lineage = LineageIndex::from_records(&history);
+    let provider = vcs_provider::detect_with(workdir, &ScanSettings::from_config(config));
+    let workdir_prefix = workdir_prefix_in(workdir);
+    for (path, fingerprint) in marked {
+        let Some((language, block)) = contexts.block(path, fingerprint) else {
+            continue;
+        };
+        lineage.tag_reconfirmations(
+            provider.as_deref(),
+            &repo_relative_path(path, workdir_prefix.as_deref()),
+            language,
+            &mut [block.clone()],
+            &approved,
+        );
+    }
+    Ok(lineage.take_new_records(repo_ref, now))
+}
+
+/// Resolves the record `id` names and fills in what `params` leaves out
+/// from it; returns the full id.
+fn reply_parent(history: &[Record], id: &str, params: &mut MarkParams) -> Result<String> {
+    let parent = find_by_id(history, id, "record", |_| true)?;
+    if params.fingerprint.is_empty() {
+        params.fingerprint = parent.fingerprint.clone();
+    } else if params.fingerprint != parent.fingerprint {
+        bail!(
+            "Record {} is on block {}, not {}",
+            parent.id,
+            parent.fingerprint,
+            params.fingerprint
+        );
+    }
+    if params.path.is_none() {
+        params.path = parent.path_hint.clone();
+        params.line = params.line.or(parent.line_hint);
+    }
+    Ok(parent.id.clone())
+}
+
+/// One `mark --stdin` or `POST /mark` entry; missing fields fall back to
+/// the command line.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MarkEntry {
+    fingerprint: String,
+    verdict: Option<String>,
+    check: Option<String>,
+    note: Option<String>,
+    path: Option<String>,
+    line: Option<u32>,
+    confidence: Option<f64>,
+    #[serde(default)]
+    refs: Vec<String>,
+    in_reply_to: Option<String>,
+}
+
+/// Reads a JSON array or JSON lines of entries from stdin and appends them
+/// all in one locked write.
+pub fn run_stdin(context: &TrueflowContext, template: MarkParams, quiet: bool) -> Result<()> {
+    let mut input = String::new();
+    std::io::stdin().read_to_string(&mut input)?;
+    let batch = parse_entries(&input, &template)?;
+    info!("mark stdin start (entries={})", batch.len());
+    let count = batch.len();
+    if count > 0 {
+        record_all(context, batch)?;
+    }
+    if !quiet {
+        println!("Recorded {} verdict(s).", count);
+    }
+    Ok(())
+}
+
+/// Entries from a JSON array or JSON lines, filled in from `template`.
+pub fn parse_entries(input: &str, template: &MarkParams) -> Result<Vec<MarkParams>> {
+    let entries: Vec<MarkEntry> = if input.trim_start().starts_with('[') {
+        serde_json::from_str(input).context("Invalid JSON array of entries")?
+    } else {
+        input
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| {
+                serde_json::from_str(line)
+                    .with_context(|| format!("Invalid entry on line {}", index + 1))
+            })
+            .collect::<Result<_>>()?
+    };
+    entries
+        .into_iter()
+        .map(|entry| {
+            Ok(MarkParams {
+                verdict: match entry.verdict {
+                    Some(verdict) => verdict.parse()?,
+                    None => template.verdict.clone(),
+                },
+                check: entry.check.unwrap_or_else(|| template.check.clone()),
+                note: entry.note.or_else(|| template.note.clone()),
+                path: entry.path.or_else(|| template.path.clone()),
+                line: entry.line,
+                confidence: entry.confidence.or(template.confidence),
+                refs: if entry.refs.is_empty() {
+                    template.refs.clone()
+                } else {
+                    entry.refs
+                },
+                in_reply_to: entry.in_reply_to.or_else(|| template.in_reply_to.clone()),
+                fingerprint: entry.fingerprint,
+                ..template.clone()
+            })
+        })
+        .collect()
+}
+
+/// Blocks a batch mark covers. Unset filters match everything; kinds and
+/// tags each match when the block has any of them.
+#[derive(Debug, Clone, Default)]
+pub struct BlockSelection {
+    /// File or directory, relative to the working directory.
+    pub path: Option<String>,
+    pub kinds: Vec<BlockKind>,
+    pub tags: Vec<String>,
+}
+
+impl BlockSelection {
+    fn covers_path(&self, file_path: &str) -> bool {
+        let Some(path) = self.path.as_deref().map(selection_path) else {
+            return true;
+        };
+        let file_path = normalize_path_str(file_path);
+        path.is_empty()
+            || file_path == path
+            || file_path
+                .strip_prefix(&path)
+                .is_some_and(|rest| rest.starts_with('/'))
+    }
+
+    fn covers_block(&self, block: &Block) -> bool {
+        (self.kinds.is_empty() || self.kinds.contains(&block.kind))
+            && (self.tags.is_empty() || block.tags.iter().any(|tag| self.tags.contains(tag)))
+    }
+}
+
+fn selection_path(path: &str) -> String {
+    let path = normalize_path_str(path);
+    let path = path.trim_end_matches('/');
+    if path == "." {
+        String::new()
+    } else {
+        path.to_string()
+    }
+}
+
+/// Marks the blocks `selection` picks out of a fresh scan, using `template`
+/// for everything but the fingerprint and location. A file or directory
+/// without kind or tag filters gets a single record on its subtree hash.
+pub fn run_batch(
+    context: &TrueflowContext,
+    selection: &BlockSelection,
+    template: MarkParams,
+    quiet: bool,
+) -> Result<()> {
+    info!("mark batch start (selection={:?})", selection);
+    let files = scanner::scan_directory(".")?;
+    let files: Vec<_> = files
+        .into_iter()
+        .filter(|file| selection.covers_path(&file.path))
+        .collect();
+    if files.is_empty() {
+        bail!(
+            "No files under {}",
+            selection.path.as_deref().unwrap_or(".")
+        );
+    }
+
+    let verdict = template.verdict.clone();
+    let mut batch = Vec::new();
+    let subtree = match (
+        &selection.path,
+        selection.kinds.is_empty() && selection.tags.is_empty(),
+    ) {
+        (Some(path), true) => {
+            let path = selection_path(path);
+            let tree = tree::build_tree_from_files(&files);
+            tree.find_by_path(&path)
+                .map(|id| tree.node(id))
+                .filter(|node| matches!(node.kind, TreeNodeKind::File | TreeNodeKind::Directory))
+                .map(|node| (node.hash.clone(), path))
+        }
+        _ => None,
+    };
+    let target = if let Some((hash, path)) = subtree {
+        let target = path.clone();
+        batch.push(MarkParams {
+            fingerprint: hash,
+            path: Some(path),
+            line: None,
+            ..template
+        });
+        target
+    } else {
+        for file in &files {
+            for block in file
+                .blocks
+                .iter()
+                .filter(|block| selection.covers_block(block))
+            {
+                batch.push(MarkParams {
+                    fingerprint: block.hash.clone(),
+                    path: Some(normalize_path_str(&file.path)),
+                    line: Some(block.start_line as u32),
+                    ..template.clone()
+                });
+            }
+        }
+        format!("{} block(s)", batch.len())
+    };
+
+    if !batch.is_empty() {
+        record_all(context, batch)?;
+    }
+    if !quiet {
+        println!("Recorded '{}' for {}.", verdict, target);
+    }
+    Ok(())
+}
+
+/// Withdraws the current identity's record `id`, which was written for
+/// `fingerprint`. The ledger is append-only, so this adds a revocation.
+pub fn revoke(
+    context: &TrueflowContext,
+    id: &str,
+    fingerprint: &str,
+    path: Option<String>,
+) -> Result<()> {
+    info!("revoke start (id={}, fingerprint={})", id, fingerprint);
+    let store = open_store()?;
+    let current = CurrentIdentity::resolve(context)?;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+    let record = Record {
+        path_hint: path,
+        revocation: Some(Revocation {
+            record: id.to_string(),
+        }),
+        ..Record::non_verdict(
+            RecordKind::Revocation,
+            fingerprint.to_string(),
+            current.identity.clone(),
+            repo_ref_from_snapshot(&vcs::snapshot_from_workdir()),
+            now,
+        )
+    };
+    append_attested(store.as_ref(), record, current.signer()?.as_ref())?;
+    info!("Revoked record {} by {}", id, current.identity);
+    Ok(())
+}
+
+pub fn repo_ref_from_snapshot(repo_snapshot: &vcs::RepoSnapshot) -> RepoRef {
+    let revision = repo_snapshot
+        .repo_ref_revision
+        .clone()
+        .unwrap_or_else(|| "unknown".to_string());
+    RepoRef::Vcs {
+        system: repo_snapshot.system.clone(),
+        revision,
+    }
+}
+
+/// Signs the record when signing is on, then appends it.
+pub(crate) fn append_attested(
+    store: &dyn ReviewStore,
+    mut record: Record,
+    signer: Option<&Signer>,
+) -> Result<()> {
+    if let Some(signer) = signer {
+        record.attestations = Some(vec![signer.attest(&record)?]);
+    }
+    store.append(record)
+}
+
+/// Trims references and drops empty or repeated ones; `None` when nothing is left.
+fn normalize_refs(refs: Vec<String>) -> Option<Vec<String>> {
+    let mut normalized: Vec<String> = Vec::new();
+    for reference in refs {
+        let reference = reference.trim();
+        if !reference.is_empty() && !normalized.iter().any(|existing| existing == reference) {
+            normalized.push(reference.to_string());
+        }
+    }
+    (!normalized.is_empty()).then_some(normalized)
+}
@@ -0,0 +1,209 @@
+use crate::commands::sync::{merge_records, record_key, render_ledger};
+use crate::store::{FileStore, Record};
+use crate::vcs;
+use anyhow::{Context, Result, bail};
+use log::{info, warn};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/// Name the driver is registered under in git config and `.gitattributes`.
+const DRIVER: &str = "trueflow";
+/// Shell command git runs; `%O`, `%A` and `%B` are base, ours and theirs.
+const DRIVER_COMMAND: &str = "\"${TRUEFLOW:-trueflow}\" merge-driver %O %A %B";
+
+/// Merges three versions of a ledger and writes the result over `ours`, as
+/// git expects from a merge driver. Records are merged by id like `sync`,
+/// but an id edited on both sides keeps the newer copy (ties broken by
+/// content), so both sides of a merge arrive at the same file.
+pub fn run(base: &Path, ours: &Path, theirs: &Path) -> Result<()> {
+    let base_records = read_ledger_file(base)?;
+    let ours_records = read_ledger_file(ours)?;
+    let theirs_records = read_ledger_file(theirs)?;
+    let records = merge_ledgers(&base_records, ours_records, theirs_records);
+    fs::write(ours, render_ledger(&records)?)
+        .with_context(|| format!("Failed to write {}", ours.display()))?;
+    info!("merged {} record(s) into {}", records.len(), ours.display());
+    Ok(())
+}
+
+pub fn merge_ledgers(base: &[Record], ours: Vec<Record>, theirs: Vec<Record>) -> Vec<Record> {
+    let mut candidates: HashMap<String, Vec<Record>> = HashMap::new();
+    for record in ours.iter().chain(theirs.iter()) {
+        candidates
+            .entry(record.id.clone())
+            .or_default()
+            .push(record.clone());
+    }
+    let merge = merge_records(base, ours, theirs);
+    let mut records = merge.records;
+    for record in &mut records {
+        if !merge.conflicts.contains(&record.id) {
+            continue;
+        }
+        if let Some(newest) = candidates
+            .remove(&record.id)
+            .and_then(|copies| copies.into_iter().max_by_key(conflict_rank))
+        {
+            *record = newest;
+        }
+    }
+    records.sort_by(|a, b| (a.timestamp, &a.id).cmp(&(b.timestamp, &b.id)));
+    records
+}
+
+fn conflict_rank(record: &Record) -> (i64, String) {
+    (record.timestamp, record_key(record))
+}
+
+/// Records in a ledger file; a missing file is an empty ledger. Lines that
+/// don't parse (say, markers left by an earlier textual merge) fail the
+/// merge, so git leaves the conflict for a person instead of losing them.
+fn read_ledger_file(path: &Path) -> Result<Vec<Record>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let mut records = Vec::new();
+    let mut unreadable = 0;
+    for (index, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<Record>(line) {
+            Ok(record) => records.push(record),
+            Err(err) => {
+                warn!("{}:{}: {}", path.display(), index + 1, err);
+                unreadable += 1;
+            }
+        }
+    }
+    if unreadable > 0 {
+        bail!(
+            "{} has {} unreadable line(s); resolve the conflict by hand",
+            path.display(),
+            unreadable
+        );
+    }
+    Ok(records)
+}
+
+/// Registers the driver in the repository's git config and maps the ledger
+/// to it in `.gitattributes`. Returns whether anything changed.
+pub fn install() -> Result<bool> {
+    let repo = vcs::repo_from_workdir()?;
+    let Some(workdir) = repo.workdir().map(Path::to_path_buf) else {
+        bail!("The merge driver needs a git checkout with a working tree");
+    };
+    let workdir = workdir.canonicalize().unwrap_or(workdir);
+    let ledger = FileStore::new()?.db_path();
+    let ledger = ledger.canonicalize().unwrap_or(ledger);
+    let pattern = ledger
+        .strip_prefix(&workdir)
+        .map(|path| path.to_string_lossy().replace('\\', "/"))
+        .unwrap_or_else(|_| ".trueflow/reviews.jsonl".to_string());
+
+    let mut changed = false;
+    let config = repo.config_snapshot();
+    let wanted = [
+        ("name", "trueflow review ledger merge"),
+        ("driver", DRIVER_COMMAND),
+    ];
+    for (key, value) in wanted {
+        let key = format!("merge.{DRIVER}.{key}");
+        if config
+            .string(key.as_str())
+            .is_some_and(|current| *current == value)
+        {
+            continue;
+        }
+        let output = Command::new("git")
+            .current_dir(&workdir)
+            .args(["config", "--local", &key, value])
+            .output()
+            .context("Failed to run git config")?;
+        if !output.status.success() {
+            bail!(
+                "git config {key} failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        changed = true;
+    }
+
+    let attributes = workdir.join(".gitattributes");
+    let existing = fs::read_to_string(&attributes).unwrap_or_default();
+    let line = format!("{pattern} merge={DRIVER}");
+    if !existing.lines().any(|current| current.trim() == line) {
+        let mut content = existing;
+        if !content.is_empty() && !content.ends_with('\n') {
+            content.push('\n');
+        }
+        content.push_str(&line);
+        content.push('\n');
+        fs::write(&attributes, content)
+            .with_context(|| format!("Failed to write {}", attributes.display()))?;
+        changed = true;
+    }
+
+    if changed {
+        println!(
+            "Installed the {DRIVER} merge driver for {pattern}; commit .gitattributes to share it."
+        );
+    }
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::{BlockState, Identity, RepoRef, VcsSystem, Verdict};
+
+    fn record(id: &str, timestamp: i64, verdict: Verdict) -> Record {
+        Record {
+            id: id.to_string(),
+            ..Record::verdict(
+                "abc".to_string(),
+                "review".to_string(),
+                verdict,
+                Identity::Email {
+                    email: "a@example.com".to_string(),
+                },
+                RepoRef::Vcs {
+                    system: VcsSystem::Git,
+                    revision: "deadbeef".to_string(),
+                },
+                BlockState::Unknown,
+                timestamp,
+            )
+        }
+    }
+
+    #[test]
+    fn conflicting_edits_keep_the_newer_copy_on_both_sides() {
+        let base = vec![record("1", 10, Verdict::Approved)];
+        let ours = vec![record("1", 20, Verdict::Rejected)];
+        let theirs = vec![record("1", 30, Verdict::Comment)];
+
+        let merged = merge_ledgers(&base, ours.clone(), theirs.clone());
+        let swapped = merge_ledgers(&base, theirs, ours);
+
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].timestamp, 30);
+        assert_eq!(record_key(&merged[0]), record_key(&swapped[0]));
+    }
+
+    #[test]
+    fn records_from_both_sides_are_kept_in_order() {
+        let base = vec![record("1", 10, Verdict::Approved)];
+        let ours = vec![base[0].clone(), record("3", 30, Verdict::Approved)];
+        let theirs = vec![base[0].clone(), record("2", 20, Verdict::Approved)];
+
+        let merged = merge_ledgers(&base, ours, theirs);
+
+        let ids: Vec<&str> = merged.iter().map(|record| record.id.as_str()).collect();
+        assert_eq!(ids, ["1", "2", "3"]);
+    }
+}
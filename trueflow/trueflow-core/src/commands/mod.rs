@@ -0,0 +1,46 @@
+pub mod activity;
+pub mod assign;
+pub mod auto_approve;
+pub mod bundle;
+pub mod check;
+pub mod config;
+pub mod diff;
+pub mod feedback;
+pub mod history;
+pub mod hooks;
+pub mod init;
+pub mod inspect;
+pub mod login;
+pub mod mark;
+pub mod merge_driver;
+pub mod prereview;
+pub mod publish;
+pub mod question;
+pub mod report;
+pub mod request;
+pub mod resolve;
+pub mod review;
+pub mod scan;
+pub mod schema;
+pub mod snapshot;
+pub mod stats;
+pub mod store;
+pub mod sync;
+pub mod thread;
+pub mod verify;
+
+/// Ends a command that succeeded but must report a non-zero status, such
+/// as `check` failing its policy. The CLI flushes stdout and exits with
+/// `code`; library callers get it back as an ordinary error.
+#[derive(Debug)]
+pub struct Exit {
+    pub code: i32,
+}
+
+impl std::fmt::Display for Exit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "exit status {}", self.code)
+    }
+}
+
+impl std::error::Error for Exit {}
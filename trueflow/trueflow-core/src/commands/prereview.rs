@@ -0,0 +1,115 @@
+use crate::commands::mark::repo_ref_from_snapshot;
+use crate::commands::review::{ReviewOptions, collect_unreviewed, parse_review_targets};
+use crate::config::load as load_config;
+use crate::context::TrueflowContext;
+use crate::prereview::{
+    Annotator, PrereviewRequest, latest_hints, prereview_identity, prereview_record,
+};
+use crate::progress;
+use crate::store::{BlockState, open_store, without_revoked};
+use crate::vcs;
+use anyhow::{Result, bail};
+use log::{info, warn};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub struct PrereviewParams {
+    pub all: bool,
+    pub target: Vec<String>,
+    /// Annotate blocks that already have an annotation too.
+    pub force: bool,
+    pub dry_run: bool,
+}
+
+/// Sends the unreviewed blocks in scope to the `[prereview]` annotator and
+/// records its answers as `comment` records under the `prereview` check.
+pub fn run(_context: &TrueflowContext, params: PrereviewParams) -> Result<()> {
+    info!(
+        "prereview start (all={}, target={:?}, force={}, dry_run={})",
+        params.all, params.target, params.force, params.dry_run
+    );
+    let config = load_config()?;
+    let annotator = Annotator::from_config(&config.prereview)?;
+    let filters = config.review.resolve_filters(&[], &[]);
+    let options = ReviewOptions {
+        all: params.all,
+        targets: parse_review_targets(&params.target)?,
+        only: Vec::new(),
+        exclude: Vec::new(),
+        assignee: None,
+        reviewer: None,
+        check: None,
+    };
+    let files = collect_unreviewed(&options, &filters)?;
+    let store = open_store()?;
+    let annotated = if params.force {
+        Default::default()
+    } else {
+        latest_hints(&without_revoked(store.read_history()?))
+    };
+    let selected: Vec<_> = files
+        .iter()
+        .flat_map(|file| file.blocks.iter().map(move |block| (file, block)))
+        .filter(|(_, block)| !block.content.trim().is_empty())
+        .filter(|(_, block)| !annotated.contains_key(&block.hash))
+        .collect();
+    if selected.is_empty() {
+        println!("No blocks to pre-review.");
+        return Ok(());
+    }
+
+    if params.dry_run {
+        for (file, block) in &selected {
+            println!("{}:{} {}", file.path, block.start_line + 1, block.kind);
+        }
+        println!("Would pre-review {} block(s).", selected.len());
+        return Ok(());
+    }
+
+    let identity = prereview_identity(&config.prereview);
+    let repo_snapshot = vcs::snapshot_from_workdir();
+    let repo_ref = repo_ref_from_snapshot(&repo_snapshot);
+    let bar = progress::bar(selected.len(), "Pre-reviewing");
+    let (mut recorded, mut failed) = (0, 0);
+    for (file, block) in selected {
+        bar.set_message(file.path.clone());
+        let request = PrereviewRequest {
+            path: &file.path,
+            language: &file.language,
+            block,
+        };
+        match annotator.annotate(&request) {
+            Ok(response) => {
+                let block_state: BlockState =
+                    vcs::block_state_for_path(&repo_snapshot, Some(&file.path), &block.hash).into();
+                let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+                store.append(prereview_record(
+                    &identity,
+                    repo_ref.clone(),
+                    block_state,
+                    &file.path,
+                    block,
+                    response,
+                    now,
+                ))?;
+                recorded += 1;
+            }
+            Err(err) => {
+                warn!(
+                    "Pre-review of {}:{} failed: {:#}",
+                    file.path,
+                    block.start_line + 1,
+                    err
+                );
+                failed += 1;
+            }
+        }
+        bar.inc(1);
+    }
+    bar.finish_and_clear();
+
+    println!("Pre-reviewed {} block(s).", recorded);
+    if failed > 0 {
+        bail!("Pre-review failed for {} block(s)", failed);
+    }
+    Ok(())
+}
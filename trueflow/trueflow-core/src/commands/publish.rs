@@ -0,0 +1,67 @@
+use crate::config::load as load_config;
+use crate::context::TrueflowContext;
+use crate::diff_logic::{Change, ReviewRequirement, get_unreviewed_changes};
+use crate::publish::{Comment, PullRequest, publisher};
+use crate::store::Verdict;
+use crate::vcs;
+use anyhow::Result;
+use log::info;
+
+pub fn run(_context: &TrueflowContext, pr: u64, dry_run: bool) -> Result<()> {
+    let config = load_config()?.publish;
+    let changes = get_unreviewed_changes(&ReviewRequirement::default())?;
+    let comments = review_comments(&changes);
+    let target = format!("{} #{pr}", config.provider.as_str());
+
+    if comments.is_empty() {
+        println!("No review notes to publish.");
+        return Ok(());
+    }
+
+    if dry_run {
+        println!("Would publish {} comment(s) to {target}:", comments.len());
+        for comment in &comments {
+            println!("  {}:{} {}", comment.file, comment.line, comment.body);
+        }
+        return Ok(());
+    }
+
+    let publisher = publisher(&config)?;
+    let (base_sha, head_sha) = vcs::merge_base_and_head()?;
+    info!("Publishing {} comment(s) to {target}...", comments.len());
+    publisher.publish(
+        &PullRequest {
+            number: pr,
+            base_sha,
+            head_sha,
+        },
+        &comments,
+    )?;
+    println!("Published {} comment(s) to {target}.", comments.len());
+    Ok(())
+}
+
+/// Notes on changes that still block the gate, one comment per note.
+fn review_comments(changes: &[Change]) -> Vec<Comment> {
+    changes
+        .iter()
+        .flat_map(|change| {
+            change
+                .reviews
+                .iter()
+                .filter(|record| record.verdict != Verdict::Approved)
+                .filter_map(move |record| {
+                    let note = record.note.as_deref()?.trim();
+                    (!note.is_empty()).then(|| Comment {
+                        file: change.file.clone(),
+                        line: change.line,
+                        body: format!(
+                            "**{}** ({}): {note}",
+                            record.verdict.as_str(),
+                            record.identity.label()
+                        ),
+                    })
+                })
+        })
+        .collect()
+}
@@ -0,0 +1,112 @@
+use crate::color::Stream;
+use crate::commands::mark::{append_attested, repo_ref_from_snapshot};
+use crate::commands::review::format_timestamp;
+use crate::context::TrueflowContext;
+use crate::identity::CurrentIdentity;
+use crate::output::StructuredFormat;
+use crate::question::{answer_record, open_questions};
+use crate::store::{Record, RecordQuery, Verdict, find_by_id, open_store, without_revoked};
+use crate::vcs;
+use anyhow::Result;
+use log::info;
+use std::io::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub struct QuestionsParams {
+    pub path: Option<String>,
+    pub author: Option<String>,
+    pub output: Option<StructuredFormat>,
+}
+
+/// Lists open questions, oldest first.
+pub fn list(context: &TrueflowContext, params: QuestionsParams) -> Result<()> {
+    info!(
+        "questions start (path={:?}, author={:?})",
+        params.path, params.author
+    );
+    let store = open_store()?;
+    let history = without_revoked(store.read_history()?);
+    let path_filter = RecordQuery {
+        path: params.path.clone(),
+        ..RecordQuery::default()
+    };
+    let questions: Vec<&Record> = open_questions(&history)
+        .into_iter()
+        .filter(|record| path_filter.matches(record))
+        .filter(|record| {
+            params
+                .author
+                .as_deref()
+                .is_none_or(|author| record.identity.label() == author.trim())
+        })
+        .collect();
+
+    if let Some(format) = params.output {
+        return format.print(&questions);
+    }
+    if questions.is_empty() {
+        println!("No open questions.");
+        return Ok(());
+    }
+
+    let mut out = context.paged_output(Stream::Stdout);
+    let palette = context.palette(out.stream());
+    for record in &questions {
+        writeln!(
+            out,
+            "{}  {}  {}",
+            palette.heading(&record.id),
+            palette.dim(&format_timestamp(record.timestamp)),
+            record.identity
+        )?;
+        let location = match (&record.path_hint, record.line_hint) {
+            (Some(path), Some(line)) => format!("{path}:{line}  "),
+            (Some(path), None) => format!("{path}  "),
+            _ => String::new(),
+        };
+        writeln!(out, "  {}{}", location, palette.dim(&record.fingerprint))?;
+        for line in record.note.as_deref().unwrap_or("").lines() {
+            writeln!(out, "    {line}")?;
+        }
+    }
+    out.finish()?;
+    Ok(())
+}
+
+/// Records a reply to the question `id` (or a unique prefix of it), which
+/// closes it. Without a note the question is just marked resolved.
+pub fn answer(context: &TrueflowContext, id: &str, note: Option<String>) -> Result<()> {
+    info!("answer start (id={})", id);
+    let note = note.filter(|note| !note.trim().is_empty());
+    let store = open_store()?;
+    let history = without_revoked(store.read_history()?);
+    let question = find_question(&history, id)?;
+    if !open_questions(&history)
+        .iter()
+        .any(|open| open.id == question.id)
+    {
+        eprintln!("Question {} was already closed.", question.id);
+    }
+
+    let current = CurrentIdentity::resolve(context)?;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+    let record = answer_record(
+        question,
+        &current.identity,
+        repo_ref_from_snapshot(&vcs::snapshot_from_workdir()),
+        note,
+        now,
+    );
+    append_attested(store.as_ref(), record, current.signer()?.as_ref())?;
+    info!(
+        "Closed question {} asked by {}",
+        question.id, question.identity
+    );
+    Ok(())
+}
+
+fn find_question<'a>(history: &'a [Record], id: &str) -> Result<&'a Record> {
+    find_by_id(history, id, "question", |record| {
+        record.kind.is_verdict() && record.verdict == Verdict::Question
+    })
+}
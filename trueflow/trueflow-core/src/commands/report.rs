@@ -0,0 +1,360 @@
+use crate::commands::activity::{ReviewerActivity, summarize_activity};
+use crate::commands::review::{ReviewSummary, format_timestamp};
+use crate::commands::stats::{
+    CoverageStats, FileCoverage, file_coverage, percent, review_everything, summarize_coverage,
+};
+use crate::highlight::{HighlightKind, highlight_lines};
+use crate::metrics::{Burndown, burndown as summarize_burndown, read_snapshots};
+use crate::output::{StructuredFormat, escape_xml};
+use crate::store::{Record, Verdict, open_store, without_revoked};
+use crate::time_window::TimeWindow;
+use anyhow::{Context, Result};
+use log::info;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const BAR_WIDTH: usize = 40;
+
+/// Unreviewed blocks over the snapshots `trueflow snapshot` recorded.
+pub fn burndown(
+    since: Option<&str>,
+    until: Option<&str>,
+    output: Option<StructuredFormat>,
+) -> Result<()> {
+    info!(
+        "report burndown start (since={:?}, until={:?}, output={:?})",
+        since, until, output
+    );
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+    let window = TimeWindow::parse(since, until, now)?;
+    let report = summarize_burndown(read_snapshots()?, window);
+
+    if let Some(format) = output {
+        return format.print(&report);
+    }
+    if report.points.is_empty() {
+        println!("No snapshots in this window; record them with `trueflow snapshot`.");
+        return Ok(());
+    }
+    print!("{}", render_burndown(&report, BAR_WIDTH));
+    Ok(())
+}
+
+/// One bar per snapshot, scaled to the largest, then the change per directory.
+fn render_burndown(report: &Burndown, bar_width: usize) -> String {
+    let mut out = String::new();
+    let max = report
+        .points
+        .iter()
+        .map(|point| point.unreviewed)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    for point in &report.points {
+        let bar = (point.unreviewed * bar_width).div_ceil(max);
+        out.push_str(&format!(
+            "{}  {:<bar_width$}  {}\n",
+            format_timestamp(point.timestamp),
+            "#".repeat(bar),
+            point.unreviewed
+        ));
+    }
+
+    let (first, last) = (&report.points[0], &report.points[report.points.len() - 1]);
+    out.push_str(&format!(
+        "\nUnreviewed: {} -> {} ({:+}) over {} snapshot(s)\n",
+        first.unreviewed,
+        last.unreviewed,
+        report.change,
+        report.points.len()
+    ));
+
+    let rows: Vec<_> = report
+        .directories
+        .iter()
+        .filter(|row| row.start > 0 || row.end > 0)
+        .collect();
+    if rows.is_empty() {
+        return out;
+    }
+    let width = rows
+        .iter()
+        .map(|row| row.name.len())
+        .max()
+        .unwrap_or(0)
+        .max("Directory".len());
+    out.push_str(&format!(
+        "\n{:<width$}  {:>6}  {:>6}  {:>6}\n",
+        "Directory", "Start", "End", "Change"
+    ));
+    for row in rows {
+        out.push_str(&format!(
+            "{:<width$}  {:>6}  {:>6}  {:>+6}\n",
+            row.name, row.start, row.end, row.change
+        ));
+    }
+    out
+}
+
+/// Writes `out/index.html`: coverage, unreviewed blocks and the notes left
+/// on them, and who reviewed. One file with inline styles, for readers who
+/// don't have trueflow.
+pub fn html(out: &Path) -> Result<()> {
+    info!("report html start (out={})", out.display());
+    let summary = review_everything()?;
+    let files = file_coverage(&summary);
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+    let history = without_revoked(open_store()?.read_history()?);
+    let report = HtmlReport {
+        generated_at: now,
+        stats: summarize_coverage(&files, 1, now),
+        files: &files,
+        summary: &summary,
+        reviewers: summarize_activity(&history, TimeWindow::default()),
+        notes: notes_by_fingerprint(&history),
+    };
+
+    fs::create_dir_all(out).with_context(|| format!("Failed to create {}", out.display()))?;
+    let path = out.join("index.html");
+    fs::write(&path, report.render())
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    println!("Wrote review report to {}", path.display());
+    Ok(())
+}
+
+/// Verdicts that carry a note, oldest first, keyed by fingerprint.
+fn notes_by_fingerprint(history: &[Record]) -> HashMap<String, Vec<&Record>> {
+    let mut notes: HashMap<String, Vec<&Record>> = HashMap::new();
+    for record in history.iter().filter(|record| {
+        record.kind.is_verdict()
+            && record
+                .note
+                .as_deref()
+                .is_some_and(|note| !note.trim().is_empty())
+    }) {
+        notes
+            .entry(record.fingerprint.clone())
+            .or_default()
+            .push(record);
+    }
+    for records in notes.values_mut() {
+        records.sort_by_key(|record| record.timestamp);
+    }
+    notes
+}
+
+const STYLE: &str = "\
+body { font-family: system-ui, sans-serif; margin: 2rem auto; max-width: 72rem; padding: 0 1rem; color: #1f2328; }
+h1, h2, h3 { font-weight: 600; }
+table { border-collapse: collapse; margin-bottom: 1.5rem; }
+th, td { padding: 0.25rem 0.75rem; border-bottom: 1px solid #d0d7de; text-align: right; }
+th:first-child, td:first-child { text-align: left; }
+.muted { color: #656d76; }
+.block { border: 1px solid #d0d7de; border-radius: 6px; margin: 1rem 0; }
+.block header { background: #f6f8fa; padding: 0.4rem 0.75rem; border-bottom: 1px solid #d0d7de; }
+.status { font-weight: 600; }
+.status.rejected { color: #cf222e; }
+.status.question { color: #9a6700; }
+.notes { margin: 0; padding: 0.5rem 0.75rem 0.5rem 2rem; border-bottom: 1px solid #d0d7de; }
+pre { margin: 0; padding: 0.5rem 0.75rem; overflow-x: auto; font-size: 0.85rem; }
+.ln { color: #8c959f; user-select: none; display: inline-block; width: 3.5em; }
+.hl-keyword { color: #cf222e; }
+.hl-string { color: #0a3069; }
+.hl-number, .hl-constant { color: #0550ae; }
+.hl-comment { color: #6e7781; font-style: italic; }
+.hl-function { color: #8250df; }
+.hl-type { color: #953800; }
+";
+
+struct HtmlReport<'a> {
+    generated_at: i64,
+    stats: CoverageStats,
+    files: &'a [FileCoverage],
+    summary: &'a ReviewSummary,
+    reviewers: Vec<ReviewerActivity>,
+    notes: HashMap<String, Vec<&'a Record>>,
+}
+
+impl HtmlReport<'_> {
+    fn render(&self) -> String {
+        let mut html = String::new();
+        let total = &self.stats.total;
+        let _ = write!(
+            html,
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>Review report</title>\n<style>\n{STYLE}</style>\n</head>\n<body>\n\
+             <h1>Review report</h1>\n<p class=\"muted\">Generated {}</p>\n\
+             <p>{} of {} blocks approved ({:.1}%), {} rejected, {} open question(s).</p>\n",
+            format_timestamp(self.generated_at),
+            total.approved,
+            total.blocks,
+            total.reviewed_percent,
+            total.rejected,
+            total.questions
+        );
+        self.render_files(&mut html);
+        self.render_reviewers(&mut html);
+        self.render_unreviewed(&mut html);
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+
+    fn render_files(&self, html: &mut String) {
+        html.push_str(
+            "<h2>Coverage</h2>\n<table>\n<tr><th>File</th><th>Language</th><th>Blocks</th>\
+             <th>Approved</th><th>Rejected</th><th>Questions</th><th>Reviewed</th></tr>\n",
+        );
+        for file in self.files {
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td>\
+                 <td>{:.1}%</td></tr>",
+                escape_xml(&file.path),
+                escape_xml(&file.language),
+                file.blocks,
+                file.approved(),
+                file.rejected,
+                file.questions,
+                percent(file.approved(), file.blocks)
+            );
+        }
+        html.push_str("</table>\n");
+    }
+
+    fn render_reviewers(&self, html: &mut String) {
+        html.push_str("<h2>Reviewers</h2>\n");
+        if self.reviewers.is_empty() {
+            html.push_str("<p class=\"muted\">No verdicts recorded yet.</p>\n");
+            return;
+        }
+        html.push_str(
+            "<table>\n<tr><th>Reviewer</th><th>Blocks</th><th>Approved</th><th>Rejected</th>\
+             <th>Questions</th><th>Comments</th></tr>\n",
+        );
+        for row in &self.reviewers {
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                escape_xml(&row.identity),
+                row.blocks_reviewed,
+                row.approved,
+                row.rejected,
+                row.questions,
+                row.comments
+            );
+        }
+        html.push_str("</table>\n");
+    }
+
+    fn render_unreviewed(&self, html: &mut String) {
+        html.push_str("<h2>Unreviewed blocks</h2>\n");
+        if self.summary.files.is_empty() {
+            html.push_str("<p class=\"muted\">Every block is approved.</p>\n");
+            return;
+        }
+        for file in &self.summary.files {
+            let _ = writeln!(html, "<h3>{}</h3>", escape_xml(&file.path));
+            for block in &file.blocks {
+                let (status, class) = match self.summary.review_state.get(&block.hash) {
+                    Some(Verdict::Rejected) => ("Rejected", "rejected"),
+                    Some(Verdict::Question) => ("Question", "question"),
+                    _ => ("Unreviewed", "open"),
+                };
+                let _ = writeln!(
+                    html,
+                    "<section class=\"block\" id=\"{hash}\">\n<header><span class=\"status {class}\">\
+                     {status}</span> {kind}, lines {start}-{end} \
+                     <span class=\"muted\">{short}</span></header>",
+                    hash = escape_xml(&block.hash),
+                    kind = block.kind,
+                    start = block.start_line + 1,
+                    end = block.end_line,
+                    short = escape_xml(&block.hash[..block.hash.len().min(12)]),
+                );
+                if let Some(notes) = self.notes.get(&block.hash) {
+                    html.push_str("<ul class=\"notes\">\n");
+                    for record in notes {
+                        let _ = writeln!(
+                            html,
+                            "<li><strong>{}</strong> {} <span class=\"muted\">{}</span>: {}</li>",
+                            escape_xml(&record.identity.label()),
+                            record.verdict,
+                            format_timestamp(record.timestamp),
+                            escape_xml(record.note.as_deref().unwrap_or_default())
+                        );
+                    }
+                    html.push_str("</ul>\n");
+                }
+                let lines: Vec<&str> = block.content.lines().collect();
+                html.push_str("<pre><code>");
+                for (offset, tokens) in highlight_lines(&lines, Some(&file.language))
+                    .iter()
+                    .enumerate()
+                {
+                    let _ = write!(
+                        html,
+                        "<span class=\"ln\">{}</span>",
+                        block.start_line + offset + 1
+                    );
+                    for token in tokens {
+                        let text = escape_xml(&token.text);
+                        match token.kind {
+                            Some(kind) => {
+                                let _ = write!(
+                                    html,
+                                    "<span class=\"hl-{}\">{text}</span>",
+                                    highlight_class(kind)
+                                );
+                            }
+                            None => html.push_str(&text),
+                        }
+                    }
+                    html.push('\n');
+                }
+                html.push_str("</code></pre>\n</section>\n");
+            }
+        }
+    }
+}
+
+fn highlight_class(kind: HighlightKind) -> &'static str {
+    match kind {
+        HighlightKind::Keyword => "keyword",
+        HighlightKind::String => "string",
+        HighlightKind::Number => "number",
+        HighlightKind::Comment => "comment",
+        HighlightKind::Function => "function",
+        HighlightKind::Type => "type",
+        HighlightKind::Constant => "constant",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::MetricsSnapshot;
+
+    #[test]
+    fn bars_scale_to_the_largest_snapshot() {
+        let point = |timestamp, unreviewed: usize| MetricsSnapshot {
+            timestamp,
+            total_blocks: 10,
+            unreviewed,
+            directories: [("src".to_string(), unreviewed)].into(),
+        };
+        let report = summarize_burndown(
+            vec![point(0, 10), point(86_400, 5), point(172_800, 0)],
+            TimeWindow::default(),
+        );
+        let text = render_burndown(&report, 10);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "1970-01-01 00:00 UTC  ##########  10");
+        assert_eq!(lines[1], "1970-01-02 00:00 UTC  #####       5");
+        assert_eq!(lines[2], "1970-01-03 00:00 UTC              0");
+        assert!(text.contains("Unreviewed: 10 -> 0 (-10) over 3 snapshot(s)"));
+        assert!(text.contains("src            10       0     -10"), "{text}");
+    }
+}
@@ -0,0 +1,307 @@
+use crate::codeowners::{CodeOwners, Owner};
+use crate::commands::assign::assignment_record;
+use crate::commands::mark::{append_attested, repo_ref_from_snapshot};
+use crate::commands::review::{
+    ReviewOptions, ReviewTarget, collect_unreviewed, parse_review_targets, repo_relative_path,
+    workdir_prefix_from_root,
+};
+use crate::config::load as load_config;
+use crate::context::TrueflowContext;
+use crate::identity::CurrentIdentity;
+use crate::output::StructuredFormat;
+use crate::store::{Assignment, Identity, Record, RecordKind, open_store, workspace_root};
+use crate::vcs;
+use anyhow::{Result, bail};
+use log::{info, warn};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::time::{SystemTime, UNIX_EPOCH};
+use uuid::Uuid;
+
+pub struct RequestParams {
+    pub all: bool,
+    pub target: Vec<String>,
+    pub reviewers: Vec<String>,
+    pub note: Option<String>,
+}
+
+/// Assigns every unreviewed file in scope to its reviewers under one request id.
+pub fn run(context: &TrueflowContext, params: RequestParams) -> Result<()> {
+    info!(
+        "request start (all={}, target={:?}, reviewers={:?})",
+        params.all, params.target, params.reviewers
+    );
+    let config = load_config()?;
+    let filters = config.review.resolve_filters(&[], &[]);
+    let options = ReviewOptions {
+        all: params.all,
+        targets: parse_review_targets(&params.target)?,
+        only: Vec::new(),
+        exclude: Vec::new(),
+        assignee: None,
+        reviewer: None,
+        check: None,
+    };
+    let files = collect_unreviewed(&options, &filters)?;
+    if files.is_empty() {
+        println!("Nothing to request: no unreviewed blocks in scope.");
+        return Ok(());
+    }
+
+    let explicit: Vec<Identity> = params
+        .reviewers
+        .iter()
+        .map(|email| email.trim())
+        .filter(|email| !email.is_empty())
+        .map(|email| Identity::Email {
+            email: email.to_string(),
+        })
+        .collect();
+    let owners = if explicit.is_empty() {
+        let root = workspace_root().unwrap_or_else(|_| ".".into());
+        Some(CodeOwners::configured(&root, &config.owners)?)
+    } else {
+        None
+    };
+
+    let workdir_prefix = workdir_prefix_from_root();
+    let mut plan: Vec<(String, Vec<Identity>)> = Vec::new();
+    let mut unmapped = BTreeSet::new();
+    for file in &files {
+        let repo_path = repo_relative_path(&file.path, workdir_prefix.as_deref());
+        let reviewers = match &owners {
+            Some(owners) => owner_identities(
+                owners.owners_for(&repo_path),
+                &config.owners.handles,
+                &mut unmapped,
+            ),
+            None => explicit.clone(),
+        };
+        if reviewers.is_empty() {
+            warn!("No reviewers for {}", repo_path);
+            continue;
+        }
+        plan.push((repo_path, reviewers));
+    }
+    if !unmapped.is_empty() {
+        let handles: Vec<String> = unmapped.iter().map(|handle| format!("@{handle}")).collect();
+        bail!(
+            "No [owners.handles] entry for CODEOWNERS handle(s) {}; map them to emails or pass --reviewer",
+            handles.join(", ")
+        );
+    }
+    if plan.is_empty() {
+        bail!("No reviewers found: pass --reviewer or add owners to CODEOWNERS");
+    }
+
+    let store = open_store()?;
+    let current = CurrentIdentity::resolve(context)?;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+    let repo_ref = repo_ref_from_snapshot(&vcs::snapshot_from_workdir());
+    let request_id = Uuid::new_v4().to_string();
+    let signer = current.signer()?;
+
+    let mut requested = BTreeSet::new();
+    for (path, reviewers) in &plan {
+        for reviewer in reviewers {
+            let assignment = Assignment {
+                assignee: reviewer.clone(),
+                path: Some(path.clone()),
+                request: Some(request_id.clone()),
+            };
+            let record = assignment_record(
+                &current.identity,
+                repo_ref.clone(),
+                String::new(),
+                assignment,
+                params.note.clone(),
+                now,
+            );
+            append_attested(store.as_ref(), record, signer.as_ref())?;
+            requested.insert(reviewer.label());
+        }
+    }
+
+    println!(
+        "Requested review of {} file(s) from {} (request {})",
+        plan.len(),
+        requested.into_iter().collect::<Vec<_>>().join(", "),
+        short_id(&request_id)
+    );
+    if plan.len() < files.len() {
+        println!(
+            "{} file(s) had no reviewer and were skipped.",
+            files.len() - plan.len()
+        );
+    }
+    Ok(())
+}
+
+/// Reviewers for `owners`, with handles resolved through `[owners.handles]`.
+/// Handles without an entry are added to `unmapped`.
+fn owner_identities(
+    owners: &[Owner],
+    handles: &HashMap<String, Vec<String>>,
+    unmapped: &mut BTreeSet<String>,
+) -> Vec<Identity> {
+    let mut identities = Vec::new();
+    for owner in owners {
+        let emails = match owner {
+            Owner::Email(email) => std::slice::from_ref(email),
+            Owner::Handle(handle) => match handles.get(handle) {
+                Some(emails) => emails.as_slice(),
+                None => {
+                    unmapped.insert(handle.clone());
+                    continue;
+                }
+            },
+        };
+        for email in emails {
+            let identity = Identity::Email {
+                email: email.clone(),
+            };
+            if !identities.contains(&identity) {
+                identities.push(identity);
+            }
+        }
+    }
+    identities
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReviewRequest {
+    pub id: String,
+    pub requested_by: String,
+    pub timestamp: i64,
+    pub note: Option<String>,
+    pub reviewers: Vec<String>,
+    pub paths: Vec<String>,
+    pub remaining_blocks: usize,
+}
+
+impl ReviewRequest {
+    pub fn is_open(&self) -> bool {
+        self.remaining_blocks > 0
+    }
+}
+
+/// Groups request assignment records by request id, oldest first.
+pub fn group_requests(records: &[Record]) -> Vec<ReviewRequest> {
+    let mut grouped: BTreeMap<String, ReviewRequest> = BTreeMap::new();
+    for record in records
+        .iter()
+        .filter(|record| record.kind == RecordKind::Assignment)
+    {
+        let Some(assignment) = &record.assignment else {
+            continue;
+        };
+        let Some(request_id) = &assignment.request else {
+            continue;
+        };
+        let request = grouped
+            .entry(request_id.clone())
+            .or_insert_with(|| ReviewRequest {
+                id: request_id.clone(),
+                requested_by: record.identity.label(),
+                timestamp: record.timestamp,
+                note: record.note.clone(),
+                reviewers: Vec::new(),
+                paths: Vec::new(),
+                remaining_blocks: 0,
+            });
+        let reviewer = assignment.assignee.label();
+        if !request.reviewers.contains(&reviewer) {
+            request.reviewers.push(reviewer);
+        }
+        if let Some(path) = &assignment.path
+            && !request.paths.contains(path)
+        {
+            request.paths.push(path.clone());
+        }
+    }
+    let mut requests: Vec<ReviewRequest> = grouped.into_values().collect();
+    requests.sort_by_key(|request| request.timestamp);
+    requests
+}
+
+pub fn list(
+    context: &TrueflowContext,
+    output: Option<StructuredFormat>,
+    mine: bool,
+    all: bool,
+) -> Result<()> {
+    info!(
+        "requests start (output={:?}, mine={}, all={})",
+        output, mine, all
+    );
+    let store = open_store()?;
+    let history = store.read_history()?;
+    let mut requests = group_requests(&history);
+
+    if mine {
+        let me = CurrentIdentity::resolve(context)?.identity.label();
+        requests.retain(|request| request.reviewers.contains(&me));
+    }
+
+    if !requests.is_empty() {
+        let config = load_config()?;
+        let filters = config.review.resolve_filters(&[], &[]);
+        let options = ReviewOptions {
+            all: true,
+            targets: vec![ReviewTarget::All],
+            only: Vec::new(),
+            exclude: Vec::new(),
+            assignee: None,
+            reviewer: None,
+            check: None,
+        };
+        let workdir_prefix = workdir_prefix_from_root();
+        let unreviewed: Vec<(String, usize)> = collect_unreviewed(&options, &filters)?
+            .into_iter()
+            .map(|file| {
+                (
+                    repo_relative_path(&file.path, workdir_prefix.as_deref()),
+                    file.blocks.len(),
+                )
+            })
+            .collect();
+        for request in &mut requests {
+            request.remaining_blocks = unreviewed
+                .iter()
+                .filter(|(path, _)| request.paths.contains(path))
+                .map(|(_, blocks)| blocks)
+                .sum();
+        }
+    }
+
+    if !all {
+        requests.retain(ReviewRequest::is_open);
+    }
+
+    if let Some(format) = output {
+        format.print(&requests)?;
+    } else if requests.is_empty() {
+        println!("No open review requests.");
+    } else {
+        for request in &requests {
+            let status = if request.is_open() { "open" } else { "done" };
+            println!(
+                "[{}] {} by {} -> {} ({} file(s), {} block(s) remaining)",
+                status,
+                short_id(&request.id),
+                request.requested_by,
+                request.reviewers.join(", "),
+                request.paths.len(),
+                request.remaining_blocks
+            );
+            if let Some(note) = &request.note {
+                println!("    > {}", note);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn short_id(id: &str) -> &str {
+    id.get(..8).unwrap_or(id)
+}
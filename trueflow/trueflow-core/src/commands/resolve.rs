@@ -0,0 +1,42 @@
+use crate::commands::mark::{append_attested, repo_ref_from_snapshot};
+use crate::commands::question;
+use crate::context::TrueflowContext;
+use crate::identity::CurrentIdentity;
+use crate::resolution::{resolution_record, resolved_ids};
+use crate::store::{Verdict, find_by_id, open_store, without_revoked};
+use crate::vcs;
+use anyhow::Result;
+use log::info;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Marks the verdict `id` (or a unique prefix of it) as dealt with, which
+/// hides it from `feedback`. Questions are closed as `answer` closes them.
+pub fn run(context: &TrueflowContext, id: &str, note: Option<String>) -> Result<()> {
+    info!("resolve start (id={})", id);
+    let note = note.filter(|note| !note.trim().is_empty());
+    let store = open_store()?;
+    let history = without_revoked(store.read_history()?);
+    let verdict = find_by_id(&history, id, "verdict", |record| record.kind.is_verdict())?;
+    if verdict.verdict == Verdict::Question {
+        return question::answer(context, &verdict.id, note);
+    }
+    if resolved_ids(&history).contains(verdict.id.as_str()) {
+        eprintln!("Verdict {} was already resolved.", verdict.id);
+    }
+
+    let current = CurrentIdentity::resolve(context)?;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+    let record = resolution_record(
+        verdict,
+        &current.identity,
+        repo_ref_from_snapshot(&vcs::snapshot_from_workdir()),
+        note,
+        now,
+    );
+    append_attested(store.as_ref(), record, current.signer()?.as_ref())?;
+    info!(
+        "Resolved {} verdict {} by {}",
+        verdict.verdict, verdict.id, verdict.identity
+    );
+    Ok(())
+}
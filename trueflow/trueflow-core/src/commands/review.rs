@@ -0,0 +1,1198 @@
+use crate::analysis::Language;
+use crate::anchor::ApprovalAnchors;
+use crate::assignment::latest_assignments;
+use crate::auto_approve::approve_enabled_rules;
+use crate::blame::FileBlame;
+use crate::block::{Block, BlockKind, FileState};
+use crate::color::Stream;
+use crate::commands::Exit;
+use crate::config::{BlockFilters, PolicyConfig, TrueflowConfig, load as load_config};
+use crate::context::TrueflowContext;
+use crate::generated::GENERATED_TAG;
+use crate::identity::CurrentIdentity;
+use crate::lineage::{LineageIndex, RECONFIRM_TAG};
+use crate::output::{OutputFormat, md_cell, render_structured};
+use crate::owner_review::{OWNER_REVIEW_TAG, OwnerReview};
+use crate::path_tags::PathTags;
+use crate::policy::{
+    check_state, counted_records, review_state, should_skip_generated_by_default,
+    should_skip_impl_by_default, should_skip_imports_by_default,
+};
+use crate::prereview::latest_hints;
+use crate::question::{QUESTION_TAG, open_questions};
+use crate::risk::RiskScorer;
+use crate::scanner::{self, ScanSettings};
+use crate::session::SessionSnapshot;
+use crate::store::{
+    Identity, Verdict, approved_hashes_from_verdicts, open_store, open_store_in, without_revoked,
+    workspace_root_in,
+};
+use crate::sub_splitter;
+use crate::tree;
+use crate::vcs::{self, CommitInfo};
+use crate::vcs_provider;
+use anyhow::{Context, Result, anyhow};
+use globset::{GlobBuilder, GlobMatcher};
+use log::{info, warn};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::Write;
+use std::path::Path;
+
+#[derive(Serialize, JsonSchema)]
+pub struct UnreviewedFile {
+    pub path: String,
+    pub language: Language,
+    pub blocks: Vec<Block>,
+}
+
+/// Which blocks to review. The default is what `trueflow review` shows
+/// without flags: uncommitted changes.
+#[derive(Default)]
+pub struct ReviewOptions {
+    pub all: bool,
+    pub targets: Vec<ReviewTarget>,
+    pub only: Vec<String>,
+    pub exclude: Vec<String>,
+    /// Only keep blocks assigned to this reviewer.
+    pub assignee: Option<Identity>,
+    /// Judge blocks by this identity's own verdicts (its label) instead of
+    /// the shared review state.
+    pub reviewer: Option<String>,
+    /// Review under this check instead of `review`: blocks of the paths its
+    /// `[tags]` entry marks (every block without one), judged by the
+    /// verdicts recorded under it.
+    pub check: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReviewTarget {
+    DirtyWorktree,
+    /// What the next commit would contain: the index against HEAD.
+    Staged,
+    MainDiff,
+    All,
+    File(String),
+    /// Every file under a directory, relative to the working directory.
+    Directory(String),
+    /// Files matching a glob such as `src/**/*.rs`.
+    Glob(String),
+    Revision(String),
+    RevisionRange {
+        start: String,
+        end: String,
+    },
+}
+
+impl ReviewTarget {
+    /// The `staged`/`base`/`file:`/`dir:`/`glob:`/`rev:` spelling accepted by `--target`.
+    pub fn label(&self) -> String {
+        match self {
+            ReviewTarget::DirtyWorktree => "dirty worktree".to_string(),
+            ReviewTarget::Staged => "staged".to_string(),
+            ReviewTarget::MainDiff => format!("diff vs {}", vcs::base_label()),
+            ReviewTarget::All => "entire review".to_string(),
+            ReviewTarget::File(path) => format!("file:{path}"),
+            ReviewTarget::Directory(path) => format!("dir:{path}"),
+            ReviewTarget::Glob(pattern) => format!("glob:{pattern}"),
+            ReviewTarget::Revision(revision) => format!("rev:{revision}"),
+            ReviewTarget::RevisionRange { start, end } => format!("rev:{start}..{end}"),
+        }
+    }
+
+    /// Targets that only mean something inside a version-controlled checkout.
+    pub fn needs_repository(&self) -> bool {
+        matches!(
+            self,
+            ReviewTarget::DirtyWorktree
+                | ReviewTarget::Staged
+                | ReviewTarget::MainDiff
+                | ReviewTarget::Revision(_)
+                | ReviewTarget::RevisionRange { .. }
+        )
+    }
+}
+
+/// Files selected by review targets: exact paths plus `dir:` and `glob:` patterns.
+#[derive(Debug, Default)]
+struct TargetPaths {
+    paths: HashSet<String>,
+    dirs: Vec<String>,
+    globs: Vec<GlobMatcher>,
+    /// Staged blobs of `staged` targets, reviewed instead of the worktree.
+    staged: HashMap<String, gix::ObjectId>,
+    /// The commits `rev:` targets cover, oldest first.
+    commits: Vec<CommitInfo>,
+}
+
+impl TargetPaths {
+    fn contains(&self, path: &str) -> bool {
+        self.paths.contains(path)
+            || self.dirs.iter().any(|dir| {
+                dir.is_empty()
+                    || path
+                        .strip_prefix(dir.as_str())
+                        .is_some_and(|rest| rest.starts_with('/'))
+            })
+            || self.globs.iter().any(|glob| glob.is_match(path))
+    }
+}
+
+fn target_glob(pattern: &str) -> Result<GlobMatcher> {
+    Ok(GlobBuilder::new(&normalize_path_str(pattern))
+        .literal_separator(true)
+        .build()
+        .with_context(|| format!("Invalid glob target: {pattern}"))?
+        .compile_matcher())
+}
+
+/// Reviewable blocks in one scanned file, reviewed or not.
+pub struct FileBlockCount {
+    pub path: String,
+    pub language: Language,
+    pub blocks: usize,
+}
+
+pub struct ReviewSummary {
+    pub files: Vec<UnreviewedFile>,
+    pub total_blocks: usize,
+    pub block_counts: Vec<FileBlockCount>,
+    pub review_state: HashMap<String, Verdict>,
+    pub tree: tree::Tree,
+    pub unreviewed_block_nodes: HashSet<tree::TreeNodeId>,
+    /// The commits `rev:` targets cover, oldest first.
+    pub commits: Vec<CommitInfo>,
+}
+
+pub fn collect_review_summary(
+    options: &ReviewOptions,
+    filters: &BlockFilters,
+) -> Result<ReviewSummary> {
+    collect_review_summary_in(Path::new("."), &load_config()?, options, filters)
+}
+
+/// [`collect_review_summary`] for `workdir` under `config`, leaving the
+/// current directory alone. Paths in the summary are relative to `workdir`.
+pub fn collect_review_summary_in(
+    workdir: &Path,
+    config: &TrueflowConfig,
+    options: &ReviewOptions,
+    filters: &BlockFilters,
+) -> Result<ReviewSummary> {
+    info!(
+        "review collect (all={}, only={:?}, exclude={:?})",
+        options.all, options.only, options.exclude
+    );
+    let target_paths = resolve_review_targets(workdir, config, options)?;
+    let workdir_prefix = workdir_prefix_in(workdir);
+    let root = workspace_root_in(workdir)?;
+
+    // 1. Load Approved Hashes
+    let store = open_store_in(&root, &config.store)?;
+    let policy = &config.policy;
+    let history = counted_records(store.read_history()?, policy);
+    info!("loaded {} review records", history.len());
+
+    let fingerprint_status = match options.check.as_deref() {
+        Some(check) => check_state(&history, policy, options.reviewer.as_deref(), check),
+        None => review_state(&history, policy, options.reviewer.as_deref()),
+    };
+    // Owners sign off under `review`; a check lane leaves them out.
+    let owner_review = match options.check {
+        Some(_) => None,
+        None => OwnerReview::load_in(&root, config, &history)?,
+    };
+    let path_tags = PathTags::new(&config.tags)?;
+    let lane_tag = options
+        .check
+        .as_deref()
+        .filter(|check| path_tags.defines(check));
+    let approved_hashes = approved_hashes_from_verdicts(&fingerprint_status);
+    let assignments = latest_assignments(&history);
+    // With a reviewer, only the questions they asked mark their queue.
+    let questioned: HashSet<&str> = open_questions(&history)
+        .into_iter()
+        .filter(|record| {
+            options
+                .reviewer
+                .as_deref()
+                .is_none_or(|reviewer| record.identity.label() == reviewer)
+        })
+        .map(|record| record.fingerprint.as_str())
+        .collect();
+    let mut lineage = LineageIndex::from_records(&history);
+    let mut anchors = ApprovalAnchors::from_records(&history, &approved_hashes);
+    let settings = ScanSettings::from_config(config);
+    let repo = vcs::repo_in(workdir).ok();
+    let provider = vcs_provider::detect_with(workdir, &settings);
+
+    // 2. Scan Directory (Merkle Tree), parsing only targeted files
+    let mut files = match &target_paths {
+        Some(targets) => scanner::scan_directory_with(workdir, &settings, |path| {
+            targets.contains(path)
+                || targets.contains(&repo_relative_path(path, workdir_prefix.as_deref()))
+        })?,
+        None => scanner::scan_directory_with(workdir, &settings, |_| true)?,
+    };
+    if let (Some(targets), Some(repo)) = (&target_paths, &repo) {
+        use_staged_blocks(
+            repo,
+            &mut files,
+            &targets.staged,
+            workdir_prefix.as_deref(),
+            &settings,
+        )?;
+    }
+    info!("scanned {} files", files.len());
+    let tree = tree::build_tree_from_files(&files);
+
+    // 3. Subtraction (Tree Traversal)
+    let mut unreviewed_files = Vec::new();
+    let mut total_blocks = 0;
+    let mut block_counts = Vec::new();
+    let mut unreviewed_block_nodes = HashSet::new();
+
+    for file in files {
+        let file_path = normalize_path_str(&file.path);
+        let repo_path = repo_relative_path(&file_path, workdir_prefix.as_deref());
+        if let Some(targets) = &target_paths
+            && !targets.contains(&file_path)
+            && !targets.contains(&repo_path)
+        {
+            continue;
+        }
+        if let Some(tag) = lane_tag
+            && !path_tags.has_tag(&repo_path, tag)
+        {
+            continue;
+        }
+
+        if should_skip_generated_by_default(&file, filters) {
+            continue;
+        }
+
+        let language = file.language.clone();
+        let mut reviewable_blocks = Vec::new();
+        for block in file.blocks {
+            if !filters.allows_block(&block.kind) {
+                continue;
+            }
+            if should_skip_imports_by_default(&file.path, &block, filters) {
+                continue;
+            }
+            if should_skip_impl_by_default(&block, filters) {
+                continue;
+            }
+            reviewable_blocks.push(block);
+        }
+        total_blocks += reviewable_blocks.len();
+        block_counts.push(FileBlockCount {
+            path: file.path.clone(),
+            language: language.clone(),
+            blocks: reviewable_blocks.len(),
+        });
+
+        // Optimization: If the FILE hash is approved, everything inside is approved.
+        let file_approved = fingerprint_status.get(&file.file_hash) == Some(&Verdict::Approved);
+        let file_owners = owner_review
+            .as_ref()
+            .and_then(|owner_review| owner_review.for_path(&repo_path));
+        if file_approved && file_owners.is_none() {
+            continue;
+        }
+
+        let current_hashes: HashSet<String> = reviewable_blocks
+            .iter()
+            .map(|block| block.hash.clone())
+            .collect();
+        let mut unreviewed_blocks = Vec::new();
+        for mut block in reviewable_blocks {
+            let node_id = tree.find_block_node(&file.path, &block);
+            let reviewed = file_approved
+                || is_block_reviewed(
+                    &tree,
+                    node_id,
+                    &block,
+                    |block| {
+                        sub_splitter::split_with(block, language.clone(), settings.normalization)
+                    },
+                    &fingerprint_status,
+                    &approved_hashes,
+                    filters,
+                );
+            if reviewed {
+                // Approved, but an owner still has to sign off.
+                match &file_owners {
+                    Some(owners) if !owners.covers(&tree, node_id, &block.hash) => {
+                        block.tags.push(OWNER_REVIEW_TAG.to_string());
+                    }
+                    _ => continue,
+                }
+            }
+
+            if let Some(assignee) = &options.assignee
+                && !assignments.is_assigned_to(&repo_path, &block.hash, assignee)
+            {
+                continue;
+            }
+
+            if questioned.contains(block.hash.as_str()) {
+                block.tags.push(QUESTION_TAG.to_string());
+            }
+            block
+                .tags
+                .extend(path_tags.tags_for(&repo_path).map(str::to_string));
+            if let Some(node_id) = node_id {
+                unreviewed_block_nodes.insert(node_id);
+            }
+            unreviewed_blocks.push(block);
+        }
+
+        if !unreviewed_blocks.is_empty() {
+            lineage.tag_reconfirmations(
+                provider.as_deref(),
+                &repo_path,
+                &language,
+                &mut unreviewed_blocks,
+                &approved_hashes,
+            );
+            if let Some(provider) = provider.as_deref()
+                && !anchors.is_empty()
+            {
+                anchors.annotate(
+                    provider,
+                    &[file_path.as_str(), repo_path.as_str()],
+                    &repo_path,
+                    &mut unreviewed_blocks,
+                    &current_hashes,
+                    &lineage,
+                );
+            }
+            unreviewed_files.push(UnreviewedFile {
+                path: file.path,
+                language,
+                blocks: unreviewed_blocks,
+            });
+        }
+    }
+
+    // 1. Sort blocks within files
+    for file in &mut unreviewed_files {
+        file.blocks
+            .sort_by_key(|block| (kind_rank(block), block.start_line));
+    }
+
+    // 2. Sort files (Files with higher priority blocks come first)
+    unreviewed_files.sort_by(|a, b| {
+        let rank_fn = |file: &UnreviewedFile| file.blocks.first().map(kind_rank).unwrap_or(100);
+        (rank_fn(a), &a.path).cmp(&(rank_fn(b), &b.path))
+    });
+
+    Ok(ReviewSummary {
+        files: unreviewed_files,
+        total_blocks,
+        block_counts,
+        review_state: fingerprint_status,
+        tree,
+        unreviewed_block_nodes,
+        commits: target_paths
+            .map(|targets| targets.commits)
+            .unwrap_or_default(),
+    })
+}
+
+/// Swaps the worktree blocks of staged files for the blocks of their staged
+/// blobs, so unstaged edits don't show up in a staged review.
+fn use_staged_blocks(
+    repo: &gix::Repository,
+    files: &mut [FileState],
+    staged: &HashMap<String, gix::ObjectId>,
+    workdir_prefix: Option<&str>,
+    settings: &ScanSettings,
+) -> Result<()> {
+    if staged.is_empty() {
+        return Ok(());
+    }
+    for file in files {
+        let repo_path = repo_relative_path(&file.path, workdir_prefix);
+        let Some(id) = staged.get(&repo_path) else {
+            continue;
+        };
+        let is_submodule = file
+            .blocks
+            .first()
+            .is_some_and(|block| block.kind == BlockKind::Submodule);
+        let mut blocks = if is_submodule {
+            vec![scanner::submodule_block(&id.to_string())]
+        } else {
+            vcs::blob_blocks(repo, &repo_path, *id, settings)?
+        };
+        if file.generated {
+            for block in &mut blocks {
+                block.tags.push(GENERATED_TAG.to_string());
+            }
+        }
+        file.file_hash = scanner::file_hash(&blocks);
+        file.blocks = blocks;
+    }
+    Ok(())
+}
+
+/// Whether the block counts as approved: itself, through something containing
+/// it, or through all of the sub-blocks `split` cuts it into.
+fn is_block_reviewed(
+    tree: &tree::Tree,
+    node_id: Option<tree::TreeNodeId>,
+    block: &Block,
+    split: impl FnOnce(&Block) -> Result<Vec<Block>>,
+    fingerprint_status: &HashMap<String, Verdict>,
+    approved_hashes: &HashSet<String>,
+    filters: &BlockFilters,
+) -> bool {
+    if node_id.is_some_and(|node_id| tree.is_node_covered(node_id, approved_hashes)) {
+        return true;
+    }
+    match fingerprint_status.get(&block.hash) {
+        Some(verdict) => *verdict == Verdict::Approved,
+        // Not explicitly reviewed. Check implicit approval via sub-blocks.
+        None => split(block).is_ok_and(|sub_blocks| {
+            !sub_blocks.is_empty()
+                && sub_blocks.iter().all(|sb| {
+                    !filters.allows_subblock(&sb.kind)
+                        || fingerprint_status.get(&sb.hash) == Some(&Verdict::Approved)
+                })
+        }),
+    }
+}
+
+pub fn collect_unreviewed(
+    options: &ReviewOptions,
+    filters: &BlockFilters,
+) -> Result<Vec<UnreviewedFile>> {
+    Ok(collect_review_summary(options, filters)?.files)
+}
+
+/// The current identity when `[policy] per_reviewer` gives everyone their own queue.
+pub fn default_reviewer(
+    context: &TrueflowContext,
+    policy: &PolicyConfig,
+) -> Result<Option<String>> {
+    if !policy.per_reviewer {
+        return Ok(None);
+    }
+    Ok(Some(CurrentIdentity::resolve(context)?.identity.label()))
+}
+
+/// [`default_reviewer`] for the workspace at `root` under `config`.
+pub fn default_reviewer_in(
+    context: &TrueflowContext,
+    root: &Path,
+    config: &TrueflowConfig,
+) -> Result<Option<String>> {
+    if !config.policy.per_reviewer {
+        return Ok(None);
+    }
+    let current = CurrentIdentity::resolve_in(context, root, &config.identity)?;
+    Ok(Some(current.identity.label()))
+}
+
+fn resolve_review_targets(
+    workdir: &Path,
+    config: &TrueflowConfig,
+    options: &ReviewOptions,
+) -> Result<Option<TargetPaths>> {
+    let targets = normalize_targets(options);
+    if targets
+        .iter()
+        .any(|target| matches!(target, ReviewTarget::All))
+    {
+        return Ok(None);
+    }
+
+    let provider = vcs_provider::detect_in(workdir);
+    let mut paths = HashSet::new();
+    let mut selected = TargetPaths::default();
+    for target in targets {
+        if provider.is_none() && target.needs_repository() {
+            warn!(
+                "Not inside a repository; skipping the {} target (try --all)",
+                target.label()
+            );
+            continue;
+        }
+        match target {
+            ReviewTarget::DirtyWorktree => {
+                if let Some(Ok(dirty)) = provider.as_ref().map(|provider| provider.dirty_files()) {
+                    paths.extend(dirty);
+                }
+            }
+            ReviewTarget::Staged => {
+                let repo = vcs::repo_in(workdir)?;
+                for (path, id) in vcs::staged_changes(&repo)? {
+                    if let Some(id) = id {
+                        selected.staged.insert(normalize_path_str(&path), id);
+                    }
+                    paths.insert(path);
+                }
+            }
+            ReviewTarget::MainDiff => {
+                let repo = vcs::repo_in(workdir)?;
+                paths.extend(vcs::files_changed_from_base(&repo, vcs::base_from(config))?);
+            }
+            ReviewTarget::File(path) => {
+                paths.insert(path);
+            }
+            ReviewTarget::Directory(path) => {
+                let dir = normalize_path_str(&path);
+                let dir = dir.trim_end_matches('/');
+                selected
+                    .dirs
+                    .push(if dir == "." { "" } else { dir }.to_string());
+            }
+            ReviewTarget::Glob(pattern) => {
+                selected.globs.push(target_glob(&pattern)?);
+            }
+            ReviewTarget::Revision(revision) => {
+                if let Some(provider) = &provider {
+                    paths.extend(provider.files_changed_in_revision(&revision)?);
+                    selected
+                        .commits
+                        .push(provider.commit_in_revision(&revision)?);
+                }
+            }
+            ReviewTarget::RevisionRange { start, end } => {
+                if let Some(provider) = &provider {
+                    paths.extend(provider.files_changed_in_range(&start, &end)?);
+                    selected
+                        .commits
+                        .extend(provider.commits_in_range(&start, &end)?);
+                }
+            }
+            ReviewTarget::All => {}
+        }
+    }
+
+    selected.paths = paths
+        .into_iter()
+        .map(|path| normalize_path_str(&path))
+        .collect();
+    Ok(Some(selected))
+}
+
+fn normalize_targets(options: &ReviewOptions) -> Vec<ReviewTarget> {
+    // Assignments may point at committed code, so --mine alone looks everywhere.
+    if options.all || (options.targets.is_empty() && options.assignee.is_some()) {
+        return vec![ReviewTarget::All];
+    }
+    if options.targets.is_empty() {
+        return vec![ReviewTarget::DirtyWorktree];
+    }
+    options.targets.clone()
+}
+
+pub fn workdir_prefix_from_root() -> Option<String> {
+    workdir_prefix_in(&std::env::current_dir().ok()?)
+}
+
+/// `workdir`'s offset from the root of the workspace around it.
+pub fn workdir_prefix_in(workdir: &Path) -> Option<String> {
+    let repo_root = workspace_root_in(workdir).ok()?;
+    let repo_root = repo_root.canonicalize().unwrap_or(repo_root);
+    let workdir = workdir
+        .canonicalize()
+        .unwrap_or_else(|_| workdir.to_path_buf());
+    let relative = workdir.strip_prefix(&repo_root).ok()?;
+    let relative_str = normalize_path_str(relative.to_string_lossy().as_ref());
+    if relative_str.is_empty() || relative_str == "." {
+        None
+    } else {
+        Some(relative_str)
+    }
+}
+
+/// Joins a workdir-relative path onto the workdir's offset from the repo root.
+pub fn repo_relative_path(path: &str, workdir_prefix: Option<&str>) -> String {
+    let path = normalize_path_str(path);
+    match workdir_prefix {
+        Some(prefix) => format!("{prefix}/{path}"),
+        None => path,
+    }
+}
+
+pub fn normalize_path_str(path: &str) -> String {
+    path.trim_start_matches("./").replace('\\', "/")
+}
+
+pub fn parse_review_targets(values: &[String]) -> Result<Vec<ReviewTarget>> {
+    let mut targets = Vec::new();
+    for raw in values {
+        if raw == "staged" {
+            targets.push(ReviewTarget::Staged);
+            continue;
+        }
+        if raw == "base" {
+            targets.push(ReviewTarget::MainDiff);
+            continue;
+        }
+        if let Some(rest) = raw.strip_prefix("file:") {
+            targets.push(ReviewTarget::File(rest.to_string()));
+            continue;
+        }
+        if let Some(rest) = raw.strip_prefix("dir:") {
+            targets.push(ReviewTarget::Directory(rest.to_string()));
+            continue;
+        }
+        if let Some(rest) = raw.strip_prefix("glob:") {
+            target_glob(rest)?;
+            targets.push(ReviewTarget::Glob(rest.to_string()));
+            continue;
+        }
+        if let Some(rest) = raw.strip_prefix("rev:") {
+            if let Some((start, end)) = rest.split_once("..") {
+                targets.push(ReviewTarget::RevisionRange {
+                    start: start.to_string(),
+                    end: end.to_string(),
+                });
+            } else {
+                targets.push(ReviewTarget::Revision(rest.to_string()));
+            }
+            continue;
+        }
+        return Err(anyhow!("Unknown review target: {}", raw));
+    }
+    Ok(targets)
+}
+
+pub struct ReviewParams {
+    pub format: OutputFormat,
+    pub all: bool,
+    pub target: Vec<String>,
+    pub only: Vec<String>,
+    pub exclude: Vec<String>,
+    pub mine: bool,
+    pub summary: bool,
+    pub page: Page,
+    pub since_last: bool,
+    pub count: bool,
+    pub reviewer: Option<String>,
+    pub check: Option<String>,
+}
+
+/// A window over the review queue, counted in blocks in review order.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Page {
+    pub offset: usize,
+    pub limit: Option<usize>,
+    pub max_files: Option<usize>,
+}
+
+impl Page {
+    fn is_unbounded(&self) -> bool {
+        self.offset == 0 && self.limit.is_none() && self.max_files.is_none()
+    }
+
+    /// Skips `offset` blocks, keeps at most `limit` blocks, and stops after
+    /// `max_files` files. Files left without blocks are dropped.
+    pub fn apply(&self, files: Vec<UnreviewedFile>) -> Vec<UnreviewedFile> {
+        let mut skip = self.offset;
+        let mut remaining = self.limit.unwrap_or(usize::MAX);
+        let max_files = self.max_files.unwrap_or(usize::MAX);
+        let mut paged = Vec::new();
+        for mut file in files {
+            if remaining == 0 || paged.len() >= max_files {
+                break;
+            }
+            if skip >= file.blocks.len() {
+                skip -= file.blocks.len();
+                continue;
+            }
+            let keep = remaining.min(file.blocks.len() - skip);
+            file.blocks = file.blocks.into_iter().skip(skip).take(keep).collect();
+            skip = 0;
+            remaining -= keep;
+            paged.push(file);
+        }
+        paged
+    }
+}
+
+pub fn run(context: &TrueflowContext, params: ReviewParams) -> Result<()> {
+    let ReviewParams {
+        format,
+        all,
+        target,
+        only,
+        exclude,
+        mine,
+        summary,
+        page,
+        since_last,
+        count,
+        reviewer,
+        check,
+    } = params;
+    info!(
+        "review start (format={:?}, all={}, target={:?}, only={:?}, exclude={:?}, mine={}, summary={})",
+        format, all, target, only, exclude, mine, summary
+    );
+    let config = load_config()?;
+    let filters = config.review.resolve_filters(&only, &exclude);
+    let options = ReviewOptions {
+        all,
+        targets: parse_review_targets(&target)?,
+        only,
+        exclude,
+        assignee: if mine {
+            Some(CurrentIdentity::resolve(context)?.identity)
+        } else {
+            None
+        },
+        reviewer: match reviewer {
+            Some(reviewer) => Some(reviewer),
+            None => default_reviewer(context, &config.policy)?,
+        },
+        check: check.filter(|check| check != "review"),
+    };
+    if normalize_targets(&options)
+        .iter()
+        .any(ReviewTarget::needs_repository)
+        && vcs_provider::detect().is_none()
+    {
+        eprintln!("Not inside a repository, so only file targets apply; try --all.");
+    }
+    let ReviewSummary {
+        files: mut unreviewed_files,
+        commits,
+        ..
+    } = collect_review_summary(&options, &filters)?;
+    // Auto-approvals are recorded under `review` only.
+    if options.check.is_none() {
+        let auto_approved = approve_enabled_rules(&unreviewed_files, &config)?;
+        if auto_approved.total() > 0 {
+            eprintln!("{}", auto_approved);
+            unreviewed_files = collect_unreviewed(&options, &filters)?;
+        }
+    }
+
+    if since_last {
+        match SessionSnapshot::load()? {
+            Some(snapshot) => {
+                unreviewed_files = snapshot.new_since(unreviewed_files);
+                eprintln!(
+                    "Showing blocks that became unreviewed since the session ended {}.",
+                    format_timestamp(snapshot.ended_at)
+                );
+            }
+            None => eprintln!("No finished review session recorded yet; showing everything."),
+        }
+    }
+
+    let total_blocks: usize = unreviewed_files.iter().map(|file| file.blocks.len()).sum();
+    info!(
+        "unreviewed summary (files={}, blocks={})",
+        unreviewed_files.len(),
+        total_blocks
+    );
+    if count {
+        println!("{}", total_blocks);
+        if total_blocks > 0 {
+            // Like `grep -q`: scripts can branch on the exit code alone.
+            return Err(Exit { code: 1 }.into());
+        }
+        return Ok(());
+    }
+    if !summary && !page.is_unbounded() {
+        unreviewed_files = page.apply(unreviewed_files);
+        let shown: usize = unreviewed_files.iter().map(|file| file.blocks.len()).sum();
+        if shown < total_blocks {
+            eprintln!(
+                "Showing {} of {} unreviewed block(s) from offset {}.",
+                shown, total_blocks, page.offset
+            );
+        }
+    }
+    if !summary && format.structured().is_some() {
+        let scorer = RiskScorer::new(&config.risk)?;
+        annotate_blocks(&mut unreviewed_files, &commits, Some(&scorer));
+        annotate_prereview(&mut unreviewed_files)?;
+    }
+    if summary {
+        print_summary(context, &summarize_files(&unreviewed_files), format)?;
+    } else if let Some(rendered) = render_structured(&unreviewed_files, format)? {
+        println!("{}", rendered);
+    } else if format == OutputFormat::Md {
+        print!("{}", files_markdown(&unreviewed_files));
+    } else if unreviewed_files.is_empty() {
+        println!("All clear! No unreviewed blocks found.");
+    } else {
+        let mut out = context.paged_output(Stream::Stdout);
+        let palette = context.palette(out.stream());
+        for file in unreviewed_files {
+            writeln!(out, "File: {}", palette.heading(&file.path))?;
+            for block in file.blocks {
+                let status = if block.tags.iter().any(|tag| tag == QUESTION_TAG) {
+                    "Question"
+                } else if block.tags.iter().any(|tag| tag == RECONFIRM_TAG) {
+                    "Reconfirm"
+                } else if block.tags.iter().any(|tag| tag == OWNER_REVIEW_TAG) {
+                    "Owner review"
+                } else {
+                    "Unreviewed"
+                };
+                writeln!(
+                    out,
+                    "  [{}] L{}-L{} (Hash: {}) Kind: {}",
+                    palette.status(status),
+                    block.start_line,
+                    block.end_line,
+                    palette.dim(&block.hash),
+                    block.kind
+                )?;
+                if let Some(first_line) = block.content.lines().next() {
+                    writeln!(out, "    > {}", palette.dim(first_line.trim()))?;
+                }
+                if let Some(previous) = &block.previous {
+                    writeln!(
+                        out,
+                        "    {}",
+                        palette.dim(&format!(
+                            "Changed since approval at {}",
+                            previous.short_revision()
+                        ))
+                    )?;
+                }
+            }
+        }
+        out.finish()?;
+    }
+
+    Ok(())
+}
+
+/// Sets `blame` on the blocks of `files`, `commits` to those of `commits`
+/// that wrote some of the block's lines, and `risk` when there is a
+/// `scorer`. Outside git, where there is no blame, a block gets the commits
+/// that changed its file and no churn.
+pub fn annotate_blocks(
+    files: &mut [UnreviewedFile],
+    commits: &[CommitInfo],
+    scorer: Option<&RiskScorer>,
+) {
+    let repo = vcs::repo_from_workdir().ok();
+    let provider = vcs_provider::detect();
+    let workdir_prefix = workdir_prefix_from_root();
+    // Files each of `commits` changed, read only when there is no blame.
+    let mut changed: Option<Vec<HashSet<String>>> = None;
+    for file in files {
+        let repo_path =
+            repo_relative_path(&normalize_path_str(&file.path), workdir_prefix.as_deref());
+        let blame = repo.as_ref().and_then(|repo| {
+            let working = std::fs::read_to_string(&file.path).ok()?;
+            FileBlame::for_path(repo, &repo_path, &working)
+                .inspect_err(|err| warn!("Skipping blame for {}: {}", file.path, err))
+                .ok()
+        });
+        if let Some(scorer) = scorer {
+            for block in &mut file.blocks {
+                block.risk = Some(scorer.score(&repo_path, block, blame.as_ref()));
+            }
+        }
+        if let Some(blame) = blame {
+            for block in &mut file.blocks {
+                let ids = blame.commit_ids(block);
+                block.commits = commits
+                    .iter()
+                    .filter(|commit| ids.contains(&commit.id))
+                    .cloned()
+                    .collect();
+                block.blame = Some(blame.block(block));
+            }
+            continue;
+        }
+        let Some(provider) = provider.as_ref().filter(|_| !commits.is_empty()) else {
+            continue;
+        };
+        let changed = changed.get_or_insert_with(|| {
+            commits
+                .iter()
+                .map(|commit| {
+                    provider
+                        .files_changed_in_revision(&commit.id)
+                        .unwrap_or_default()
+                })
+                .collect()
+        });
+        let touching: Vec<CommitInfo> = commits
+            .iter()
+            .zip(changed.iter())
+            .filter(|(_, paths)| paths.contains(&repo_path))
+            .map(|(commit, _)| commit.clone())
+            .collect();
+        for block in &mut file.blocks {
+            block.commits = touching.clone();
+        }
+    }
+}
+
+/// Sets `prereview` on the blocks of `files` that `trueflow prereview`
+/// annotated.
+pub fn annotate_prereview(files: &mut [UnreviewedFile]) -> Result<()> {
+    let hints = latest_hints(&without_revoked(open_store()?.read_history()?));
+    if hints.is_empty() {
+        return Ok(());
+    }
+    for block in files.iter_mut().flat_map(|file| &mut file.blocks) {
+        block.prereview = hints.get(&block.hash).cloned();
+    }
+    Ok(())
+}
+
+/// Markdown listing: one table per file, suitable for a PR description.
+fn files_markdown(files: &[UnreviewedFile]) -> String {
+    let total_blocks: usize = files.iter().map(|file| file.blocks.len()).sum();
+    let mut md = String::from("## Unreviewed blocks\n\n");
+    if files.is_empty() {
+        md.push_str("All clear! No unreviewed blocks found.\n");
+        return md;
+    }
+    md.push_str(&format!(
+        "{} block(s) in {} file(s).\n",
+        total_blocks,
+        files.len()
+    ));
+    for file in files {
+        md.push_str(&format!("\n### `{}`\n\n", file.path));
+        md.push_str("| Lines | Kind | Hash | Preview |\n|---|---|---|---|\n");
+        for block in &file.blocks {
+            let preview = block.content.lines().next().unwrap_or("").trim();
+            md.push_str(&format!(
+                "| L{}-L{} | {} | `{}` | `{}` |\n",
+                block.start_line,
+                block.end_line,
+                block.kind,
+                short_hash(&block.hash),
+                md_cell(&preview.replace('`', "'"))
+            ));
+        }
+    }
+    md
+}
+
+fn short_hash(hash: &str) -> &str {
+    hash.get(..12).unwrap_or(hash)
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct FileSummary {
+    pub path: String,
+    pub blocks: usize,
+    pub kinds: BTreeMap<String, usize>,
+}
+
+#[derive(Debug, Serialize)]
+struct ReviewSummaryReport<'a> {
+    files: &'a [FileSummary],
+    total_files: usize,
+    total_blocks: usize,
+}
+
+/// One entry per file with its unreviewed block count by kind.
+pub fn summarize_files(files: &[UnreviewedFile]) -> Vec<FileSummary> {
+    files
+        .iter()
+        .map(|file| {
+            let mut kinds = BTreeMap::new();
+            for block in &file.blocks {
+                *kinds.entry(block.kind.to_string()).or_default() += 1;
+            }
+            FileSummary {
+                path: file.path.clone(),
+                blocks: file.blocks.len(),
+                kinds,
+            }
+        })
+        .collect()
+}
+
+fn kinds_label(summary: &FileSummary) -> String {
+    summary
+        .kinds
+        .iter()
+        .map(|(kind, count)| format!("{kind} {count}"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn print_summary(
+    context: &TrueflowContext,
+    summaries: &[FileSummary],
+    format: OutputFormat,
+) -> Result<()> {
+    let total_blocks: usize = summaries.iter().map(|summary| summary.blocks).sum();
+    let report = ReviewSummaryReport {
+        files: summaries,
+        total_files: summaries.len(),
+        total_blocks,
+    };
+    if let Some(rendered) = render_structured(&report, format)? {
+        println!("{}", rendered);
+        return Ok(());
+    }
+    if format == OutputFormat::Md {
+        println!("| File | Blocks | Kinds |\n|---|---:|---|");
+        for summary in summaries {
+            println!(
+                "| `{}` | {} | {} |",
+                summary.path,
+                summary.blocks,
+                md_cell(&kinds_label(summary))
+            );
+        }
+        println!(
+            "| **Total** | {} | {} file(s) |",
+            total_blocks,
+            summaries.len()
+        );
+        return Ok(());
+    }
+    if summaries.is_empty() {
+        println!("All clear! No unreviewed blocks found.");
+        return Ok(());
+    }
+
+    let mut out = context.paged_output(Stream::Stdout);
+    let palette = context.palette(out.stream());
+    let width = summaries
+        .iter()
+        .map(|summary| summary.path.len())
+        .max()
+        .unwrap_or(0);
+    for summary in summaries {
+        writeln!(
+            out,
+            "{}{}  {:>4}  {}",
+            palette.heading(&summary.path),
+            " ".repeat(width - summary.path.len()),
+            summary.blocks,
+            palette.dim(&kinds_label(summary))
+        )?;
+    }
+    writeln!(
+        out,
+        "Total: {} unreviewed block(s) in {} file(s)",
+        total_blocks,
+        summaries.len()
+    )?;
+    out.finish()?;
+    Ok(())
+}
+
+pub fn format_timestamp(timestamp: i64) -> String {
+    chrono::DateTime::from_timestamp(timestamp, 0)
+        .map(|time| time.format("%Y-%m-%d %H:%M UTC").to_string())
+        .unwrap_or_else(|| timestamp.to_string())
+}
+
+fn kind_rank(block: &Block) -> u8 {
+    if block.tags.iter().any(|tag| tag == "test") {
+        return 10;
+    }
+    block.kind.default_review_priority()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::{BlockKind, BlockMetrics};
+
+    fn make_block(kind: BlockKind, tags: &[&str]) -> Block {
+        Block {
+            hash: "hash".to_string(),
+            content: "content".to_string(),
+            kind,
+            tags: tags.iter().map(|tag| (*tag).to_string()).collect(),
+            complexity: 0,
+            start_line: 0,
+            end_line: 1,
+            metrics: BlockMetrics::from_content("content"),
+            previous: None,
+            blame: None,
+            commits: Vec::new(),
+            prereview: None,
+            risk: None,
+        }
+    }
+
+    fn make_file(path: &str, blocks: usize) -> UnreviewedFile {
+        UnreviewedFile {
+            path: path.to_string(),
+            language: Language::Rust,
+            blocks: (0..blocks)
+                .map(|_| make_block(BlockKind::Function, &[]))
+                .collect(),
+        }
+    }
+
+    fn page_shape(files: &[UnreviewedFile]) -> Vec<(&str, usize)> {
+        files
+            .iter()
+            .map(|file| (file.path.as_str(), file.blocks.len()))
+            .collect()
+    }
+
+    #[test]
+    fn test_page_windows_blocks_across_files() {
+        let files = || vec![make_file("a", 3), make_file("b", 2), make_file("c", 4)];
+        let page = Page {
+            offset: 2,
+            limit: Some(4),
+            max_files: None,
+        };
+        assert_eq!(
+            page_shape(&page.apply(files())),
+            vec![("a", 1), ("b", 2), ("c", 1)]
+        );
+
+        let page = Page {
+            offset: 3,
+            limit: None,
+            max_files: Some(1),
+        };
+        assert_eq!(page_shape(&page.apply(files())), vec![("b", 2)]);
+        assert_eq!(page_shape(&Page::default().apply(files())).len(), 3);
+    }
+
+    #[test]
+    fn test_review_priority_ordering() {
+        let test_block = make_block(BlockKind::Function, &["test"]);
+        let signature_block = make_block(BlockKind::FunctionSignature, &[]);
+        let function_block = make_block(BlockKind::Function, &[]);
+
+        let ordered = [
+            make_block(BlockKind::Struct, &[]),
+            test_block,
+            signature_block,
+            function_block,
+        ];
+
+        for window in ordered.windows(2) {
+            let first = kind_rank(&window[0]);
+            let second = kind_rank(&window[1]);
+            assert!(
+                first < second,
+                "expected {:?} (rank {}) before {:?} (rank {})",
+                window[0].kind,
+                first,
+                window[1].kind,
+                second
+            );
+        }
+
+        let data_rank = kind_rank(&make_block(BlockKind::Struct, &[]));
+        assert_eq!(data_rank, kind_rank(&make_block(BlockKind::Enum, &[])));
+        assert_eq!(data_rank, kind_rank(&make_block(BlockKind::Type, &[])));
+        assert_eq!(data_rank, kind_rank(&make_block(BlockKind::Interface, &[])));
+        assert_eq!(data_rank, kind_rank(&make_block(BlockKind::Class, &[])));
+    }
+}
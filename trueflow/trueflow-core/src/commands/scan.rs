@@ -0,0 +1,164 @@
+use crate::color::{Palette, Stream};
+use crate::config::load as load_config;
+use crate::context::TrueflowContext;
+use crate::output::StructuredFormat;
+use crate::policy::{counted_records, review_state};
+use crate::risk::{self, RiskScorer};
+use crate::scanner;
+use crate::store::{approved_hashes_from_verdicts, open_store};
+use crate::tree::{self, Tree, TreeNodeId, TreeNodeKind};
+use anyhow::Result;
+use std::collections::HashSet;
+use std::io::Write;
+
+const HASH_PREFIX_LEN: usize = 8;
+
+pub fn run(
+    context: &TrueflowContext,
+    output: Option<StructuredFormat>,
+    tree_output: bool,
+    churn: bool,
+) -> Result<()> {
+    let mut files = scanner::scan_directory(".")?;
+    if tree_output {
+        let tree = tree::build_tree_from_files(&files);
+        if let Some(format) = output {
+            format.print(&tree.view_json())?;
+        } else {
+            print_tree(context, &tree)?;
+        }
+        return Ok(());
+    }
+
+    if let Some(format) = output {
+        // Churn blames every file, which costs far more than the scan.
+        let mut config = load_config()?.risk;
+        if !churn {
+            config.churn_days = 0;
+        }
+        risk::annotate_files(&mut files, &RiskScorer::new(&config)?);
+        format.print(&files)?;
+    } else {
+        for file in files {
+            println!("File: {} (Hash: {})", file.path, file.file_hash);
+            for block in file.blocks {
+                println!(
+                    "  Block [L{}-L{}]: {}",
+                    block.start_line, block.end_line, block.hash
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+fn print_tree(context: &TrueflowContext, tree: &Tree) -> Result<()> {
+    let store = open_store()?;
+    let policy = load_config()?.policy;
+    let history = counted_records(store.read_history()?, &policy);
+    let approved = approved_hashes_from_verdicts(&review_state(&history, &policy, None));
+
+    let mut out = context.paged_output(Stream::Stdout);
+    let renderer = TreeRenderer {
+        tree,
+        approved: &approved,
+        palette: context.palette(out.stream()),
+    };
+    for line in renderer.render() {
+        writeln!(out, "{}", line)?;
+    }
+    out.finish()?;
+    Ok(())
+}
+
+/// Box-drawing view of the Merkle tree with hash prefixes and, per node,
+/// how many of the blocks below it are covered by an approval.
+struct TreeRenderer<'a> {
+    tree: &'a Tree,
+    approved: &'a HashSet<String>,
+    palette: Palette,
+}
+
+impl TreeRenderer<'_> {
+    fn render(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        let root = self.tree.root();
+        lines.push(self.label(root));
+        self.render_children(root, "", &mut lines);
+        lines
+    }
+
+    fn render_children(&self, id: TreeNodeId, indent: &str, lines: &mut Vec<String>) {
+        let children = &self.tree.node(id).children;
+        for (index, child) in children.iter().enumerate() {
+            let last = index + 1 == children.len();
+            let (branch, continuation) = if last {
+                ("└── ", "    ")
+            } else {
+                ("├── ", "│   ")
+            };
+            lines.push(format!("{indent}{branch}{}", self.label(*child)));
+            self.render_children(*child, &format!("{indent}{continuation}"), lines);
+        }
+    }
+
+    fn label(&self, id: TreeNodeId) -> String {
+        let node = self.tree.node(id);
+        let hash = self
+            .palette
+            .dim(node.hash.get(..HASH_PREFIX_LEN).unwrap_or(&node.hash));
+        let (reviewed, total) = self.coverage(id);
+        match node.kind {
+            TreeNodeKind::Block => {
+                let (kind, lines) = node
+                    .block
+                    .as_ref()
+                    .map(|block| {
+                        (
+                            block.kind.to_string(),
+                            format!("L{}-L{}", block.start_line, block.end_line),
+                        )
+                    })
+                    .unwrap_or_default();
+                let mark = if reviewed == total { "✓" } else { "·" };
+                format!(
+                    "{} {} {} {}",
+                    self.palette.coverage(reviewed, total, mark),
+                    kind,
+                    lines,
+                    hash
+                )
+            }
+            TreeNodeKind::Root | TreeNodeKind::Directory | TreeNodeKind::File => {
+                let name = match node.kind {
+                    TreeNodeKind::Root => ".".to_string(),
+                    TreeNodeKind::Directory => format!("{}/", node.name),
+                    _ => node.name.clone(),
+                };
+                format!(
+                    "{} {} {}",
+                    self.palette.heading(&name),
+                    hash,
+                    self.palette.coverage(
+                        reviewed,
+                        total,
+                        &format!("({reviewed}/{total} reviewed)")
+                    )
+                )
+            }
+        }
+    }
+
+    /// (covered, total) leaf blocks under `id`.
+    fn coverage(&self, id: TreeNodeId) -> (usize, usize) {
+        let node = self.tree.node(id);
+        if node.kind == TreeNodeKind::Block && node.children.is_empty() {
+            let covered = self.tree.is_node_covered(id, self.approved);
+            return (usize::from(covered), 1);
+        }
+        node.children
+            .iter()
+            .map(|child| self.coverage(*child))
+            .fold((0, 0), |(covered, total), (c, t)| (covered + c, total + t))
+    }
+}
@@ -0,0 +1,15 @@
+use crate::schemas::SchemaName;
+use anyhow::Result;
+
+/// Prints the JSON schema of `name`, or lists the schemas without one.
+pub fn run(name: Option<SchemaName>) -> Result<()> {
+    match name {
+        Some(name) => println!("{}", serde_json::to_string_pretty(&name.schema())?),
+        None => {
+            for name in SchemaName::ALL {
+                println!("{} (v{})", name.name(), name.version());
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,31 @@
+use crate::commands::stats::collect_stats;
+use crate::context::TrueflowContext;
+use crate::metrics::{MetricsSnapshot, append_snapshot};
+use crate::output::StructuredFormat;
+use anyhow::Result;
+use log::info;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Appends the current review debt to `.trueflow/metrics.jsonl`.
+pub fn run(
+    context: &TrueflowContext,
+    depth: usize,
+    output: Option<StructuredFormat>,
+) -> Result<()> {
+    info!("snapshot start (depth={}, output={:?})", depth, output);
+    let stats = collect_stats(context, depth)?;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+    let snapshot = MetricsSnapshot::from_stats(&stats, now);
+    let path = append_snapshot(&snapshot)?;
+
+    if let Some(format) = output {
+        return format.print(&snapshot);
+    }
+    println!(
+        "Recorded {} unreviewed of {} block(s) in {}",
+        snapshot.unreviewed,
+        snapshot.total_blocks,
+        path.display()
+    );
+    Ok(())
+}
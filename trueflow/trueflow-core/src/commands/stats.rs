@@ -0,0 +1,446 @@
+use crate::commands::review::{
+    ReviewOptions, ReviewSummary, ReviewTarget, collect_review_summary, normalize_path_str,
+    repo_relative_path, workdir_prefix_from_root,
+};
+use crate::config::load as load_config;
+use crate::context::TrueflowContext;
+use crate::output::StructuredFormat;
+use crate::store::Verdict;
+use crate::vcs;
+use anyhow::{Context, Result};
+use log::{info, warn};
+use serde::Serialize;
+use serde_json::json;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Review coverage of one file: its reviewable blocks and how many of them
+/// are still rejected, questioned, or otherwise open.
+#[derive(Debug, Clone, Default)]
+pub struct FileCoverage {
+    pub path: String,
+    pub language: String,
+    pub blocks: usize,
+    pub rejected: usize,
+    pub questions: usize,
+    pub open: usize,
+    /// When the file last changed, if any of its blocks are not approved.
+    pub changed_at: Option<i64>,
+}
+
+impl FileCoverage {
+    pub fn approved(&self) -> usize {
+        self.blocks
+            .saturating_sub(self.rejected + self.questions + self.open)
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct CoverageRow {
+    pub name: String,
+    pub blocks: usize,
+    pub approved: usize,
+    pub rejected: usize,
+    pub questions: usize,
+    pub reviewed_percent: f64,
+    /// Unix time the oldest unapproved block last changed.
+    pub oldest_unreviewed_at: Option<i64>,
+    pub oldest_unreviewed_age_seconds: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CoverageStats {
+    pub total: CoverageRow,
+    pub directories: Vec<CoverageRow>,
+    pub languages: Vec<CoverageRow>,
+}
+
+#[derive(Default)]
+struct Tally {
+    blocks: usize,
+    unapproved: usize,
+    rejected: usize,
+    questions: usize,
+    oldest: Option<i64>,
+}
+
+impl Tally {
+    fn add(&mut self, file: &FileCoverage) {
+        let unapproved = file.rejected + file.questions + file.open;
+        self.blocks += file.blocks;
+        self.unapproved += unapproved;
+        self.rejected += file.rejected;
+        self.questions += file.questions;
+        if unapproved > 0
+            && let Some(changed_at) = file.changed_at
+        {
+            self.oldest = Some(
+                self.oldest
+                    .map_or(changed_at, |oldest| oldest.min(changed_at)),
+            );
+        }
+    }
+
+    fn row(self, name: String, now: i64) -> CoverageRow {
+        let approved = self.blocks.saturating_sub(self.unapproved);
+        CoverageRow {
+            name,
+            blocks: self.blocks,
+            approved,
+            rejected: self.rejected,
+            questions: self.questions,
+            reviewed_percent: percent(approved, self.blocks),
+            oldest_unreviewed_at: self.oldest,
+            oldest_unreviewed_age_seconds: self.oldest.map(|oldest| (now - oldest).max(0)),
+        }
+    }
+}
+
+/// Share of `approved` in `blocks`, to one decimal; an empty scope is fully reviewed.
+pub fn percent(approved: usize, blocks: usize) -> f64 {
+    if blocks == 0 {
+        return 100.0;
+    }
+    (approved as f64 * 1000.0 / blocks as f64).floor() / 10.0
+}
+
+/// The first `depth` directories of `path`, or `.` for files at the top.
+pub fn directory_key(path: &str, depth: usize) -> String {
+    let parts: Vec<&str> = path.split('/').collect();
+    let dirs = &parts[..parts.len().saturating_sub(1)];
+    if dirs.is_empty() || depth == 0 {
+        return ".".to_string();
+    }
+    dirs[..dirs.len().min(depth)].join("/")
+}
+
+pub fn summarize_coverage(files: &[FileCoverage], depth: usize, now: i64) -> CoverageStats {
+    let mut total = Tally::default();
+    let mut directories: BTreeMap<String, Tally> = BTreeMap::new();
+    let mut languages: BTreeMap<String, Tally> = BTreeMap::new();
+    for file in files {
+        total.add(file);
+        directories
+            .entry(directory_key(&file.path, depth))
+            .or_default()
+            .add(file);
+        languages
+            .entry(file.language.clone())
+            .or_default()
+            .add(file);
+    }
+    let rows = |tallies: BTreeMap<String, Tally>| {
+        tallies
+            .into_iter()
+            .map(|(name, tally)| tally.row(name, now))
+            .collect()
+    };
+    CoverageStats {
+        total: total.row("total".to_string(), now),
+        directories: rows(directories),
+        languages: rows(languages),
+    }
+}
+
+pub fn run(
+    context: &TrueflowContext,
+    output: Option<StructuredFormat>,
+    depth: usize,
+    badge: Option<&Path>,
+) -> Result<()> {
+    info!(
+        "stats start (output={:?}, depth={}, badge={:?})",
+        output, depth, badge
+    );
+    let stats = collect_stats(context, depth)?;
+
+    if let Some(path) = badge {
+        write_badge(path, stats.total.reviewed_percent)?;
+        eprintln!("Wrote coverage badge to {}", path.display());
+    }
+
+    if let Some(format) = output {
+        return format.print(&stats);
+    }
+    print_rows("Directory", &stats.directories);
+    println!();
+    print_rows("Language", &stats.languages);
+    println!();
+    let total = &stats.total;
+    println!(
+        "Total: {}/{} blocks approved ({:.1}%), {} rejected, {} open question(s)",
+        total.approved, total.blocks, total.reviewed_percent, total.rejected, total.questions
+    );
+    if let Some(age) = total.oldest_unreviewed_age_seconds {
+        println!("Oldest unreviewed block: changed {} ago", format_age(age));
+    }
+    Ok(())
+}
+
+/// Coverage of everything in the working tree, grouped `depth` directory
+/// levels deep.
+pub fn collect_stats(_context: &TrueflowContext, depth: usize) -> Result<CoverageStats> {
+    let files = file_coverage(&review_everything()?);
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+    Ok(summarize_coverage(&files, depth, now))
+}
+
+/// Every block in the working tree, filtered by `[review]`.
+pub fn review_everything() -> Result<ReviewSummary> {
+    let config = load_config()?;
+    let filters = config.review.resolve_filters(&[], &[]);
+    let options = ReviewOptions {
+        all: true,
+        targets: vec![ReviewTarget::All],
+        ..ReviewOptions::default()
+    };
+    collect_review_summary(&options, &filters)
+}
+
+/// Coverage of each file in `summary`, sorted by path.
+pub fn file_coverage(summary: &ReviewSummary) -> Vec<FileCoverage> {
+    let mut files: HashMap<String, FileCoverage> = summary
+        .block_counts
+        .iter()
+        .map(|count| {
+            let path = normalize_path_str(&count.path);
+            let coverage = FileCoverage {
+                path: path.clone(),
+                language: format!("{:?}", count.language),
+                blocks: count.blocks,
+                ..Default::default()
+            };
+            (path, coverage)
+        })
+        .collect();
+    for unreviewed in &summary.files {
+        let Some(file) = files.get_mut(&normalize_path_str(&unreviewed.path)) else {
+            continue;
+        };
+        for block in &unreviewed.blocks {
+            match summary.review_state.get(&block.hash) {
+                Some(Verdict::Rejected) => file.rejected += 1,
+                Some(Verdict::Question) => file.questions += 1,
+                _ => file.open += 1,
+            }
+        }
+    }
+    stamp_change_times(&mut files);
+
+    let mut files: Vec<FileCoverage> = files.into_values().collect();
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    files
+}
+
+/// Sets `changed_at` on files with unapproved blocks: the last commit that
+/// touched them, or their mtime when they are dirty or not in git.
+fn stamp_change_times(files: &mut HashMap<String, FileCoverage>) {
+    let pending: Vec<&mut FileCoverage> = files
+        .values_mut()
+        .filter(|file| file.rejected + file.questions + file.open > 0)
+        .collect();
+    if pending.is_empty() {
+        return;
+    }
+    let workdir_prefix = workdir_prefix_from_root();
+    let repo_path = |path: &str| repo_relative_path(path, workdir_prefix.as_deref());
+    let (commit_times, dirty) = match vcs::repo_from_workdir() {
+        Ok(repo) => {
+            let paths: HashSet<String> = pending.iter().map(|file| repo_path(&file.path)).collect();
+            let times = vcs::last_changed_times(&repo, &paths).unwrap_or_else(|err| {
+                warn!("Failed to read commit times: {err}");
+                HashMap::new()
+            });
+            (times, vcs::dirty_files(&repo).unwrap_or_default())
+        }
+        Err(_) => (HashMap::new(), HashSet::new()),
+    };
+    for file in pending {
+        let repo_path = repo_path(&file.path);
+        file.changed_at = commit_times
+            .get(&repo_path)
+            .filter(|_| !dirty.contains(&repo_path))
+            .copied()
+            .or_else(|| modified_at(&file.path));
+    }
+}
+
+fn modified_at(path: &str) -> Option<i64> {
+    let modified = fs::metadata(path).ok()?.modified().ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_secs() as i64)
+}
+
+fn print_rows(heading: &str, rows: &[CoverageRow]) {
+    let width = rows
+        .iter()
+        .map(|row| row.name.len())
+        .max()
+        .unwrap_or(0)
+        .max(heading.len());
+    println!(
+        "{:<width$}  {:>6}  {:>8}  {:>8}  {:>9}  {:>8}  {:>6}",
+        heading, "Blocks", "Approved", "Rejected", "Questions", "Reviewed", "Oldest"
+    );
+    for row in rows {
+        println!(
+            "{:<width$}  {:>6}  {:>8}  {:>8}  {:>9}  {:>7.1}%  {:>6}",
+            row.name,
+            row.blocks,
+            row.approved,
+            row.rejected,
+            row.questions,
+            row.reviewed_percent,
+            row.oldest_unreviewed_age_seconds
+                .map(format_age)
+                .unwrap_or_else(|| "-".to_string())
+        );
+    }
+}
+
+/// Rough age: minutes under an hour, hours under a day, then days.
+fn format_age(seconds: i64) -> String {
+    match seconds {
+        ..3600 => format!("{}m", seconds / 60),
+        3600..86400 => format!("{}h", seconds / 3600),
+        _ => format!("{}d", seconds / 86400),
+    }
+}
+
+/// Shields.io colour for a coverage percentage.
+fn badge_color(percent: f64) -> &'static str {
+    match percent {
+        p if p >= 90.0 => "#4c1",
+        p if p >= 75.0 => "#97ca00",
+        p if p >= 50.0 => "#dfb317",
+        p if p >= 25.0 => "#fe7d37",
+        _ => "#e05d44",
+    }
+}
+
+/// Writes a `reviewed | NN%` badge: Shields endpoint JSON when `path` ends
+/// in `.json`, a flat SVG otherwise.
+fn write_badge(path: &Path, percent: f64) -> Result<()> {
+    let message = format!("{percent:.0}%");
+    let color = badge_color(percent);
+    let contents = if path.extension().is_some_and(|ext| ext == "json") {
+        serde_json::to_string_pretty(&json!({
+            "schemaVersion": 1,
+            "label": "reviewed",
+            "message": message,
+            "color": color.trim_start_matches('#'),
+        }))?
+    } else {
+        badge_svg("reviewed", &message, color)
+    };
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, contents).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+fn badge_svg(label: &str, message: &str, color: &str) -> String {
+    // Verdana 11px averages about 7px a character; pad 5px on each side.
+    let text_width = |text: &str| text.chars().count() * 7 + 10;
+    let label_width = text_width(label);
+    let message_width = text_width(message);
+    let width = label_width + message_width;
+    let label_x = label_width * 5;
+    let message_x = (label_width * 2 + message_width) * 5;
+    format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="20" role="img" aria-label="{label}: {message}">
+  <title>{label}: {message}</title>
+  <linearGradient id="s" x2="0" y2="100%"><stop offset="0" stop-color="#bbb" stop-opacity=".1"/><stop offset="1" stop-opacity=".1"/></linearGradient>
+  <clipPath id="r"><rect width="{width}" height="20" rx="3" fill="#fff"/></clipPath>
+  <g clip-path="url(#r)">
+    <rect width="{label_width}" height="20" fill="#555"/>
+    <rect x="{label_width}" width="{message_width}" height="20" fill="{color}"/>
+    <rect width="{width}" height="20" fill="url(#s)"/>
+  </g>
+  <g fill="#fff" text-anchor="middle" font-family="Verdana,Geneva,DejaVu Sans,sans-serif" font-size="110">
+    <text x="{label_x}" y="140" transform="scale(.1)">{label}</text>
+    <text x="{message_x}" y="140" transform="scale(.1)">{message}</text>
+  </g>
+</svg>
+"##
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(path: &str, language: &str, blocks: usize, open: usize) -> FileCoverage {
+        FileCoverage {
+            path: path.to_string(),
+            language: language.to_string(),
+            blocks,
+            open,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn groups_directories_by_depth() {
+        assert_eq!(directory_key("main.rs", 1), ".");
+        assert_eq!(directory_key("src/commands/stats.rs", 1), "src");
+        assert_eq!(directory_key("src/commands/stats.rs", 2), "src/commands");
+        assert_eq!(directory_key("src/lib.rs", 3), "src");
+    }
+
+    #[test]
+    fn summarizes_per_directory_and_language() {
+        let files = vec![
+            FileCoverage {
+                rejected: 1,
+                questions: 1,
+                changed_at: Some(100),
+                ..file("src/a.rs", "Rust", 4, 0)
+            },
+            FileCoverage {
+                changed_at: Some(50),
+                ..file("src/b.py", "Python", 2, 2)
+            },
+            FileCoverage {
+                changed_at: Some(10),
+                ..file("docs/c.rs", "Rust", 3, 0)
+            },
+        ];
+        let stats = summarize_coverage(&files, 1, 1000);
+
+        assert_eq!(stats.total.blocks, 9);
+        assert_eq!(stats.total.approved, 5);
+        assert_eq!(stats.total.reviewed_percent, 55.5);
+        // docs/c.rs is fully approved, so its age does not count.
+        assert_eq!(stats.total.oldest_unreviewed_at, Some(50));
+        assert_eq!(stats.total.oldest_unreviewed_age_seconds, Some(950));
+
+        let names: Vec<&str> = stats
+            .directories
+            .iter()
+            .map(|row| row.name.as_str())
+            .collect();
+        assert_eq!(names, ["docs", "src"]);
+        assert_eq!(stats.directories[0].reviewed_percent, 100.0);
+        assert_eq!(stats.directories[0].oldest_unreviewed_at, None);
+
+        let rust = &stats.languages[1];
+        assert_eq!(rust.name, "Rust");
+        assert_eq!(
+            (rust.blocks, rust.approved, rust.rejected, rust.questions),
+            (7, 5, 1, 1)
+        );
+    }
+
+    #[test]
+    fn badge_colors_follow_coverage() {
+        assert_eq!(badge_color(100.0), "#4c1");
+        assert_eq!(badge_color(60.0), "#dfb317");
+        assert_eq!(badge_color(0.0), "#e05d44");
+        assert!(badge_svg("reviewed", "60%", "#dfb317").contains(">60%</text>"));
+    }
+}
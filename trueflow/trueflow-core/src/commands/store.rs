@@ -0,0 +1,111 @@
+use crate::block_context::ContextIndex;
+use crate::config;
+use crate::store::{
+    BlockContext, CURRENT_VERSION, FileStore, LedgerLine, Record, StoreBackend, open_store,
+    parse_ledger_lines,
+};
+use anyhow::{Result, bail};
+use log::info;
+use std::collections::HashSet;
+
+/// Rewrites records older than `CURRENT_VERSION` at the current version. A
+/// verdict whose block is still in the tree gets its block context; the file
+/// hash is left out since the file may have changed since. Signed records
+/// keep their version, since any change would break the signature, and every
+/// other line is written back byte for byte. A ledger with unreadable lines
+/// or records newer than this version is left alone.
+pub fn migrate(dry_run: bool) -> Result<()> {
+    let store = open_store()?;
+    // The JSONL ledger stays locked for the whole migration, so a concurrent
+    // append waits instead of being overwritten.
+    let mut ledger = match config::load()?.store.backend {
+        StoreBackend::Jsonl => Some(FileStore::new()?.lock()?),
+        _ => None,
+    };
+    let mut lines = match &mut ledger {
+        Some(ledger) => parse_ledger_lines(&ledger.read()?, "the ledger")?,
+        None => store
+            .read_history()?
+            .into_iter()
+            .map(LedgerLine::from)
+            .collect(),
+    };
+    let newer = lines
+        .iter()
+        .filter(|line| line.record.version > CURRENT_VERSION)
+        .count();
+    if newer > 0 {
+        bail!(
+            "{newer} record(s) are newer than version {CURRENT_VERSION}; upgrade trueflow before migrating"
+        );
+    }
+    let paths: HashSet<String> = lines
+        .iter()
+        .map(|line| &line.record)
+        .filter(|record| needs_migration(record))
+        .filter_map(|record| record.path_hint.clone())
+        .collect();
+    let contexts = ContextIndex::scan(&paths, None)?;
+
+    let mut migrated = 0;
+    let mut signed = 0;
+    for line in &mut lines {
+        let record = &mut line.record;
+        if !needs_migration(record) {
+            continue;
+        }
+        if record
+            .attestations
+            .as_ref()
+            .is_some_and(|attestations| !attestations.is_empty())
+        {
+            signed += 1;
+            continue;
+        }
+        record.version = CURRENT_VERSION;
+        if record.context.is_none() && record.kind.is_verdict() {
+            record.context = contexts
+                .context(
+                    record.path_hint.as_deref(),
+                    &record.fingerprint,
+                    record.line_hint,
+                )
+                .filter(|context| context.block_kind.is_some())
+                .map(|context| BlockContext {
+                    file_hash: None,
+                    base_revision: None,
+                    ..context
+                });
+        }
+        line.raw = serde_json::to_string(record)?;
+        migrated += 1;
+    }
+
+    let verb = if dry_run { "Would migrate" } else { "Migrated" };
+    println!("{verb} {migrated} record(s) to version {CURRENT_VERSION}.");
+    if signed > 0 {
+        println!("{signed} signed record(s) keep their version so their signatures stay valid.");
+    }
+    if migrated > 0 && !dry_run {
+        match &mut ledger {
+            Some(ledger) => {
+                let mut content = String::new();
+                for line in &lines {
+                    content.push_str(&line.raw);
+                    content.push('\n');
+                }
+                ledger.replace(&content)?;
+            }
+            None => {
+                let records: Vec<Record> = lines.into_iter().map(|line| line.record).collect();
+                store.rewrite(&records)?;
+            }
+        }
+        info!("migrated {migrated} record(s) to version {CURRENT_VERSION}");
+    }
+    Ok(())
+}
+
+fn needs_migration(record: &Record) -> bool {
+    record.version < CURRENT_VERSION
+}
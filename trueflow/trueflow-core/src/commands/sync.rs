@@ -0,0 +1,398 @@
+use crate::commands::merge_driver;
+use crate::config;
+use crate::context::TrueflowContext;
+use crate::store::{FileStore, LedgerLine, Record, StoreBackend, open_store, parse_ledger_lines};
+use anyhow::{Context, Result};
+use log::{info, warn};
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Branch the ledger is shared on. It holds a single `reviews.jsonl`.
+const SYNC_BRANCH: &str = "trueflow-db";
+const LEDGER_FILE: &str = "reviews.jsonl";
+/// Fetch-merge-push rounds before giving up on a remote that keeps moving.
+const MAX_ATTEMPTS: usize = 3;
+
+/// Result of merging the local ledger with the remote one.
+#[derive(Debug, Default)]
+pub struct SyncMerge {
+    pub records: Vec<Record>,
+    /// Remote records the local ledger did not have.
+    pub pulled: usize,
+    /// Local records the remote ledger did not have.
+    pub pushed: usize,
+    /// Ids whose content differs on both sides; the remote copy is kept.
+    pub conflicts: Vec<String>,
+}
+
+pub fn run(_context: &TrueflowContext, remote: &str, dry_run: bool) -> Result<()> {
+    let store = open_store()?;
+    let backend = config::load()?.store.backend;
+    if !dry_run && let Err(err) = merge_driver::install() {
+        warn!("Failed to install the reviews.jsonl merge driver: {err:#}");
+    }
+    let local_ref = format!("refs/heads/{SYNC_BRANCH}");
+    let tracking_ref = format!("refs/remotes/{remote}/{SYNC_BRANCH}");
+
+    for attempt in 1..=MAX_ATTEMPTS {
+        info!("Fetching {SYNC_BRANCH} from {remote} (attempt {attempt})...");
+        fetch(remote, &tracking_ref);
+
+        let base = read_ledger_at(&local_ref)?.unwrap_or_default();
+        let theirs = read_ledger_at(&tracking_ref)?.unwrap_or_default();
+        let remote_head = rev_parse(&tracking_ref);
+        // The JSONL ledger stays locked from the read to the rewrite so a
+        // concurrent `mark` waits instead of being overwritten.
+        let mut ledger = match backend {
+            StoreBackend::Jsonl => Some(FileStore::new()?.lock()?),
+            _ => None,
+        };
+        let ours = match &mut ledger {
+            Some(ledger) => parse_ledger_lines(&ledger.read()?, "the local ledger")?,
+            None => store
+                .read_history()?
+                .into_iter()
+                .map(LedgerLine::from)
+                .collect(),
+        };
+        let ours_ids: HashSet<String> = ours.iter().map(|line| line.record.id.clone()).collect();
+        let merge = merge_records(&records_of(&base), records_of(&ours), records_of(&theirs));
+        for id in &merge.conflicts {
+            warn!("Record {id} differs locally and on {remote}; keeping the remote copy");
+        }
+
+        if dry_run {
+            println!(
+                "Would pull {} and push {} record(s) via {remote}/{SYNC_BRANCH}.",
+                merge.pulled, merge.pushed
+            );
+            if !merge.conflicts.is_empty() {
+                println!(
+                    "{} record(s) conflict; the remote copies would be kept.",
+                    merge.conflicts.len()
+                );
+            }
+            return Ok(());
+        }
+
+        let content = render_merged(&merge.records, [&ours, &theirs])?;
+        if let Some(mut ledger) = ledger {
+            ledger.replace(&content)?;
+        } else {
+            // Shared and indexed stores are never replaced; others may have
+            // appended since the read.
+            let pulled: Vec<Record> = merge
+                .records
+                .iter()
+                .filter(|record| !ours_ids.contains(&record.id))
+                .cloned()
+                .collect();
+            if !pulled.is_empty() {
+                store.append_all(pulled)?;
+            }
+        }
+
+        let commit = if merge.pushed == 0 && remote_head.is_some() {
+            remote_head.clone().context("remote head")?
+        } else {
+            let blob = git_hash_object(&content)?;
+            let tree = git_mktree(&blob)?;
+            let parent = remote_head.clone().or_else(|| rev_parse(&local_ref));
+            git_commit_tree(&tree, parent.as_deref(), "Sync reviews")?
+        };
+
+        if Some(&commit) != remote_head.as_ref() {
+            info!("Pushing to {remote}...");
+            let pushed = Command::new("git")
+                .args(["push", remote, &format!("{commit}:{local_ref}")])
+                .status()
+                .context("Failed to execute git push")?
+                .success();
+            if !pushed {
+                // Usually someone else pushed first; merge their records and retry.
+                warn!("Push to {remote} rejected; merging again");
+                continue;
+            }
+        }
+
+        update_ref(&local_ref, &commit)?;
+        println!(
+            "Pulled {} and pushed {} record(s) via {remote}/{SYNC_BRANCH}.",
+            merge.pulled, merge.pushed
+        );
+        info!("Sync complete.");
+        return Ok(());
+    }
+
+    anyhow::bail!(
+        "Failed to push {SYNC_BRANCH} to {remote} after {MAX_ATTEMPTS} attempts; try syncing again"
+    )
+}
+
+/// Three-way merge of ledgers keyed by record id. The ledger is append-only:
+/// records are never dropped, so a record missing on one side is taken from
+/// the other. When both sides hold different content for an id, the side that
+/// still matches `base` lost the edit; if neither does, the remote wins since
+/// others may already have it.
+pub fn merge_records(base: &[Record], ours: Vec<Record>, theirs: Vec<Record>) -> SyncMerge {
+    let base: HashMap<&str, String> = base
+        .iter()
+        .map(|record| (record.id.as_str(), record_key(record)))
+        .collect();
+    let mut merged: HashMap<String, Record> = HashMap::new();
+    let mut theirs_ids = HashSet::new();
+    for record in theirs {
+        theirs_ids.insert(record.id.clone());
+        merged.entry(record.id.clone()).or_insert(record);
+    }
+
+    let mut outcome = SyncMerge::default();
+    let mut ours_ids = HashSet::new();
+    for record in ours {
+        if !ours_ids.insert(record.id.clone()) {
+            continue;
+        }
+        let Some(remote) = merged.get(&record.id) else {
+            outcome.pushed += 1;
+            merged.insert(record.id.clone(), record);
+            continue;
+        };
+        let local_key = record_key(&record);
+        let remote_key = record_key(remote);
+        if local_key == remote_key {
+            continue;
+        }
+        match base.get(record.id.as_str()) {
+            Some(base_key) if *base_key == remote_key => {
+                outcome.pushed += 1;
+                merged.insert(record.id.clone(), record);
+            }
+            Some(base_key) if *base_key == local_key => outcome.pulled += 1,
+            _ => outcome.conflicts.push(record.id.clone()),
+        }
+    }
+    outcome.pulled += theirs_ids.difference(&ours_ids).count();
+
+    let mut records: Vec<Record> = merged.into_values().collect();
+    records.sort_by(|a, b| (a.timestamp, &a.id).cmp(&(b.timestamp, &b.id)));
+    outcome.conflicts.sort();
+    outcome.records = records;
+    outcome
+}
+
+pub(crate) fn record_key(record: &Record) -> String {
+    serde_json::to_string(record).unwrap_or_default()
+}
+
+fn records_of(lines: &[LedgerLine]) -> Vec<Record> {
+    lines.iter().map(|line| line.record.clone()).collect()
+}
+
+/// Renders merged `records`, each as the line it was read from in `sources`
+/// when one holds it, so fields this version does not know are kept.
+fn render_merged(records: &[Record], sources: [&[LedgerLine]; 2]) -> Result<String> {
+    let raw: HashMap<(&str, String), &str> = sources
+        .into_iter()
+        .flatten()
+        .map(|line| {
+            (
+                (line.record.id.as_str(), record_key(&line.record)),
+                line.raw.as_str(),
+            )
+        })
+        .collect();
+    let mut content = String::new();
+    for record in records {
+        match raw.get(&(record.id.as_str(), record_key(record))) {
+            Some(line) => content.push_str(line),
+            None => content.push_str(&serde_json::to_string(record)?),
+        }
+        content.push('\n');
+    }
+    Ok(content)
+}
+
+pub(crate) fn render_ledger(records: &[Record]) -> Result<String> {
+    let mut content = String::new();
+    for record in records {
+        content.push_str(&serde_json::to_string(record)?);
+        content.push('\n');
+    }
+    Ok(content)
+}
+
+fn fetch(remote: &str, tracking_ref: &str) {
+    let refspec = format!("+refs/heads/{SYNC_BRANCH}:{tracking_ref}");
+    match Command::new("git")
+        .args(["fetch", "--quiet", remote, &refspec])
+        .output()
+    {
+        // A remote without the branch yet is fine; the first push creates it.
+        Ok(output) if !output.status.success() => info!(
+            "Fetch of {SYNC_BRANCH} from {remote} failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ),
+        Ok(_) => {}
+        Err(err) => warn!("Failed to run git fetch: {err}"),
+    }
+}
+
+/// Lines of the ledger committed at `reference`, or `None` when the ref
+/// does not exist. A line that does not parse fails the sync rather than
+/// being dropped from the merged ledger.
+fn read_ledger_at(reference: &str) -> Result<Option<Vec<LedgerLine>>> {
+    let output = Command::new("git")
+        .args(["show", &format!("{reference}:{LEDGER_FILE}")])
+        .output()?;
+    if !output.status.success() {
+        return Ok(None);
+    }
+    let content = String::from_utf8(output.stdout)?;
+    parse_ledger_lines(&content, &format!("{reference}:{LEDGER_FILE}")).map(Some)
+}
+
+fn rev_parse(reference: &str) -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--verify", "--quiet", reference])
+        .output()
+        .ok()?;
+    if output.status.success() {
+        Some(String::from_utf8(output.stdout).ok()?.trim().to_string())
+    } else {
+        None
+    }
+}
+
+fn update_ref(reference: &str, commit: &str) -> Result<()> {
+    let output = Command::new("git")
+        .args(["update-ref", reference, commit])
+        .output()
+        .context("Failed to update local trueflow-db ref")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "git update-ref failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    Ok(())
+}
+
+fn git_hash_object(content: &str) -> Result<String> {
+    let mut child = Command::new("git")
+        .args(["hash-object", "-w", "--stdin"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()?;
+
+    {
+        let stdin = child.stdin.as_mut().context("Failed to open stdin")?;
+        stdin.write_all(content.as_bytes())?;
+    }
+
+    let output = child.wait_with_output()?;
+    Ok(String::from_utf8(output.stdout)?.trim().to_string())
+}
+
+fn git_mktree(blob_hash: &str) -> Result<String> {
+    let entry = format!("100644 blob {}\t{}", blob_hash, LEDGER_FILE);
+    let mut child = Command::new("git")
+        .arg("mktree")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()?;
+
+    {
+        let stdin = child.stdin.as_mut().context("Failed to open stdin")?;
+        stdin.write_all(entry.as_bytes())?;
+    }
+
+    let output = child.wait_with_output()?;
+    Ok(String::from_utf8(output.stdout)?.trim().to_string())
+}
+
+fn git_commit_tree(tree_hash: &str, parent: Option<&str>, message: &str) -> Result<String> {
+    let mut cmd = Command::new("git");
+    cmd.arg("commit-tree").arg(tree_hash);
+
+    if let Some(p) = parent {
+        cmd.arg("-p").arg(p);
+    }
+
+    cmd.arg("-m").arg(message);
+
+    let output = cmd.output()?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "git commit-tree failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    Ok(String::from_utf8(output.stdout)?.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::{BlockState, Identity, RepoRef, VcsSystem, Verdict};
+
+    fn record(id: &str, note: &str) -> Record {
+        Record {
+            id: id.to_string(),
+            kind: Default::default(),
+            version: 1,
+            note: Some(note.to_string()),
+            ..Record::verdict(
+                format!("fp-{id}"),
+                "review".to_string(),
+                Verdict::Approved,
+                Identity::Email {
+                    email: "a@example.com".to_string(),
+                },
+                RepoRef::Vcs {
+                    system: VcsSystem::Git,
+                    revision: "deadbeef".to_string(),
+                },
+                BlockState::Committed,
+                1,
+            )
+        }
+    }
+
+    fn ids(merge: &SyncMerge) -> Vec<&str> {
+        merge.records.iter().map(|r| r.id.as_str()).collect()
+    }
+
+    #[test]
+    fn unions_records_from_both_sides() {
+        let base = vec![record("a", "")];
+        let merge = merge_records(
+            &base,
+            vec![record("a", ""), record("b", "")],
+            vec![record("a", ""), record("c", "")],
+        );
+        assert_eq!(ids(&merge), vec!["a", "b", "c"]);
+        assert_eq!((merge.pulled, merge.pushed), (1, 1));
+
+        // Dropping a record locally does not delete it remotely.
+        let merge = merge_records(&base, Vec::new(), vec![record("a", "")]);
+        assert_eq!(ids(&merge), vec!["a"]);
+    }
+
+    #[test]
+    fn resolves_edits_against_the_base() {
+        let base = vec![record("a", "old")];
+        let merge = merge_records(&base, vec![record("a", "new")], vec![record("a", "old")]);
+        assert_eq!(merge.records[0].note.as_deref(), Some("new"));
+        assert!(merge.conflicts.is_empty());
+
+        let merge = merge_records(
+            &base,
+            vec![record("a", "mine")],
+            vec![record("a", "theirs")],
+        );
+        assert_eq!(merge.records[0].note.as_deref(), Some("theirs"));
+        assert_eq!(merge.conflicts, vec!["a".to_string()]);
+    }
+}
@@ -0,0 +1,75 @@
+use crate::color::Stream;
+use crate::commands::review::format_timestamp;
+use crate::context::TrueflowContext;
+use crate::output::StructuredFormat;
+use crate::store::{Record, open_store, without_revoked};
+use crate::thread::{label, threads};
+use anyhow::{Result, bail};
+use log::info;
+use std::collections::BTreeSet;
+use std::io::Write;
+
+/// Prints the discussion on a block: every verdict, answer and resolution,
+/// with replies indented under what they reply to.
+pub fn run(
+    context: &TrueflowContext,
+    fingerprint: &str,
+    output: Option<StructuredFormat>,
+) -> Result<()> {
+    info!("thread start (fingerprint={})", fingerprint);
+    let history = without_revoked(open_store()?.read_history()?);
+    let fingerprint = resolve_fingerprint(&history, fingerprint)?;
+    let threads = threads(&history, &fingerprint);
+
+    if let Some(format) = output {
+        return format.print(&threads);
+    }
+    if threads.is_empty() {
+        println!("No discussion on {}.", fingerprint);
+        return Ok(());
+    }
+
+    let mut out = context.paged_output(Stream::Stdout);
+    let palette = context.palette(out.stream());
+    writeln!(out, "{}", palette.heading(&fingerprint))?;
+    for (depth, record) in threads.iter().flat_map(|thread| thread.flatten()) {
+        let indent = "  ".repeat(depth);
+        let marker = if depth == 0 { "" } else { "└ " };
+        let id: String = record.id.chars().take(8).collect();
+        writeln!(
+            out,
+            "{indent}{marker}{}  {}  {}  {}",
+            palette.dim(&id),
+            palette.status(label(record)),
+            record.identity,
+            palette.dim(&format_timestamp(record.timestamp))
+        )?;
+        let body_indent = format!("{indent}{}", " ".repeat(marker.chars().count() + 2));
+        for line in record.note.as_deref().unwrap_or("").lines() {
+            writeln!(out, "{body_indent}{line}")?;
+        }
+    }
+    out.finish()?;
+    Ok(())
+}
+
+/// The one fingerprint in the ledger starting with `prefix`.
+fn resolve_fingerprint(history: &[Record], prefix: &str) -> Result<String> {
+    let prefix = prefix.trim();
+    if prefix.is_empty() {
+        bail!("A fingerprint must not be empty");
+    }
+    let matches: BTreeSet<&str> = history
+        .iter()
+        .map(|record| record.fingerprint.as_str())
+        .filter(|fingerprint| fingerprint.starts_with(prefix))
+        .collect();
+    match matches.len() {
+        0 => Ok(prefix.to_string()),
+        1 => Ok(matches.into_iter().next().unwrap_or(prefix).to_string()),
+        _ => bail!(
+            "Fingerprint {} matches more than one block; use more characters",
+            prefix
+        ),
+    }
+}
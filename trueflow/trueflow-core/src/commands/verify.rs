@@ -0,0 +1,280 @@
+use crate::commands::Exit;
+use crate::config;
+use crate::record_schema;
+use crate::scanner;
+use crate::signing::{Verification, Verifier};
+use crate::store::{
+    CURRENT_VERSION, FileStore, Record, ReviewStore, StoreBackend, open_store, without_revoked,
+};
+use crate::tree;
+use anyhow::{Context, Result};
+use log::info;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+
+/// Exit status when `--strict` finds reviews of code that is gone but the
+/// ledger itself is sound. Integrity failures exit with 1.
+const STALE_EXIT_CODE: i32 = 2;
+
+/// One ledger entry as stored, before it is trusted to be a `Record`.
+struct Entry {
+    /// Where the entry came from, for messages: a line number or an id.
+    location: String,
+    json: std::result::Result<Value, String>,
+}
+
+#[derive(Default)]
+struct Summary {
+    records: usize,
+    attested: usize,
+    unattested: usize,
+    unverified: usize,
+    invalid: usize,
+    schema_errors: usize,
+    duplicate_ids: usize,
+    stale: usize,
+}
+
+/// Checks the ledger: every entry against the record schema and version,
+/// attestation signatures, duplicate ids, and verdicts whose fingerprint no
+/// longer appears in the tree.
+pub fn run(all: bool, id: Option<String>, strict: bool) -> Result<()> {
+    let store = open_store()?;
+    let entries = ledger_entries(store.as_ref())?;
+    let entries = filter_entries(entries, all, id.as_deref())?;
+
+    let schema = record_schema::record_schema();
+    let verifier = Verifier::for_workspace(&config::load()?)?;
+    let mut summary = Summary::default();
+    let mut records = Vec::new();
+    let mut id_counts: HashMap<String, usize> = HashMap::new();
+
+    for entry in entries {
+        summary.records += 1;
+        let json = match entry.json {
+            Ok(json) => json,
+            Err(err) => {
+                summary.schema_errors += 1;
+                eprintln!("SCHEMA ERROR {}: not JSON: {err}", entry.location);
+                continue;
+            }
+        };
+        if let Some(id) = json.get("id").and_then(Value::as_str) {
+            *id_counts.entry(id.to_string()).or_default() += 1;
+        }
+
+        let mut json = json;
+        if let Some(fields) = json.as_object_mut() {
+            // Version 0 records predate the field.
+            fields.entry("version").or_insert(Value::from(0));
+        }
+        let mut problems = record_schema::validate(&schema, &json);
+        if let Some(version) = json.get("version").and_then(Value::as_u64)
+            && version > u64::from(CURRENT_VERSION)
+        {
+            problems.push(format!(
+                "/version: {version} is newer than this trueflow reads ({CURRENT_VERSION})"
+            ));
+        }
+        if !problems.is_empty() {
+            summary.schema_errors += 1;
+            for problem in &problems {
+                eprintln!("SCHEMA ERROR {}: {problem}", entry.location);
+            }
+        }
+
+        match serde_json::from_value::<Record>(json) {
+            Ok(record) => {
+                verify_attestations(&verifier, &record, &mut summary)?;
+                records.push(record);
+            }
+            Err(err) if problems.is_empty() => {
+                summary.schema_errors += 1;
+                eprintln!("SCHEMA ERROR {}: {err}", entry.location);
+            }
+            Err(_) => {}
+        }
+    }
+
+    let mut duplicates: Vec<(&String, &usize)> =
+        id_counts.iter().filter(|(_, count)| **count > 1).collect();
+    duplicates.sort();
+    for (id, count) in &duplicates {
+        eprintln!("DUPLICATE ID id={id} records={count}");
+    }
+    summary.duplicate_ids = duplicates.len();
+
+    let stale = stale_verdicts(records)?;
+    summary.stale = stale.len();
+    if strict {
+        for record in &stale {
+            eprintln!(
+                "STALE FINGERPRINT id={} fingerprint={}{}",
+                record.id,
+                record.fingerprint,
+                record
+                    .path_hint
+                    .as_deref()
+                    .map(|path| format!(" path={path}"))
+                    .unwrap_or_default()
+            );
+        }
+    }
+
+    println!("Records: {}", summary.records);
+    println!("Attested: {}", summary.attested);
+    println!("Unattested: {}", summary.unattested);
+    println!("Unverified: {}", summary.unverified);
+    println!("Invalid: {}", summary.invalid);
+    println!("Schema errors: {}", summary.schema_errors);
+    println!("Duplicate ids: {}", summary.duplicate_ids);
+    println!("Stale fingerprints: {}", summary.stale);
+
+    let mut failures = Vec::new();
+    if summary.invalid > 0 {
+        failures.push("Signature verification failed".to_string());
+    }
+    if summary.schema_errors > 0 {
+        failures.push(format!(
+            "{} record(s) don't match the schema",
+            summary.schema_errors
+        ));
+    }
+    if summary.duplicate_ids > 0 {
+        failures.push(format!(
+            "{} id(s) appear more than once",
+            summary.duplicate_ids
+        ));
+    }
+    if !failures.is_empty() {
+        anyhow::bail!(failures.join("; "));
+    }
+    if strict && summary.stale > 0 {
+        eprintln!(
+            "{} verdict(s) review code that is no longer in the tree.",
+            summary.stale
+        );
+        return Err(Exit {
+            code: STALE_EXIT_CODE,
+        }
+        .into());
+    }
+
+    Ok(())
+}
+
+fn verify_attestations(verifier: &Verifier, record: &Record, summary: &mut Summary) -> Result<()> {
+    let Some(attestations) = record.attestations.as_ref().filter(|list| !list.is_empty()) else {
+        summary.unattested += 1;
+        return Ok(());
+    };
+
+    let payload = record.signing_payload()?;
+    let mut record_invalid_count = 0;
+    let mut unpinned = false;
+
+    for (index, attestation) in attestations.iter().enumerate() {
+        match verifier.verify(record, &payload, attestation) {
+            Ok(Verification::Valid) => {}
+            Ok(Verification::Unpinned(hint)) => {
+                unpinned = true;
+                eprintln!(
+                    "SIGNATURE UNVERIFIED id={} attestation={}: no pinned signer, {}",
+                    record.id, index, hint
+                );
+            }
+            Ok(Verification::Invalid) => {
+                record_invalid_count += 1;
+                eprintln!(
+                    "SIGNATURE VERIFICATION FAILED id={} attestation={}",
+                    record.id, index
+                );
+            }
+            Err(e) => {
+                record_invalid_count += 1;
+                info!("attestation verification error: {e}");
+                eprintln!(
+                    "SIGNATURE VERIFICATION ERROR id={} attestation={}: {}",
+                    record.id, index, e
+                );
+            }
+        }
+    }
+
+    if record_invalid_count > 0 {
+        summary.invalid += record_invalid_count;
+    } else if unpinned {
+        summary.unverified += 1;
+    } else {
+        summary.attested += 1;
+    }
+    Ok(())
+}
+
+/// The ledger as stored. The JSONL backend is read line by line so lines
+/// `read_history` would skip are checked too; other backends only hand
+/// back records, which are checked as they serialize.
+fn ledger_entries(store: &dyn ReviewStore) -> Result<Vec<Entry>> {
+    if config::load()?.store.backend == StoreBackend::Jsonl {
+        let path = FileStore::new()?.db_path();
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        return Ok(content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| Entry {
+                location: format!("line={}", index + 1),
+                json: serde_json::from_str(line).map_err(|err| err.to_string()),
+            })
+            .collect());
+    }
+    Ok(store
+        .read_history()?
+        .into_iter()
+        .map(|record| Entry {
+            location: format!("id={}", record.id),
+            json: serde_json::to_value(&record).map_err(|err| err.to_string()),
+        })
+        .collect())
+}
+
+fn filter_entries(entries: Vec<Entry>, all: bool, id: Option<&str>) -> Result<Vec<Entry>> {
+    if all && id.is_some() {
+        anyhow::bail!("Use --all or --id, not both");
+    }
+
+    if !all && id.is_none() {
+        anyhow::bail!("Provide --all or --id");
+    }
+
+    if let Some(target) = id {
+        return Ok(entries
+            .into_iter()
+            .filter(|entry| {
+                entry
+                    .json
+                    .as_ref()
+                    .is_ok_and(|json| json.get("id").and_then(Value::as_str) == Some(target))
+            })
+            .collect());
+    }
+
+    Ok(entries)
+}
+
+/// Unrevoked verdicts whose fingerprint matches no file or block in the
+/// working tree.
+fn stale_verdicts(records: Vec<Record>) -> Result<Vec<Record>> {
+    let files = scanner::scan_directory(".")?;
+    let tree = tree::build_tree_from_files(&files);
+    let live: HashSet<&str> = tree.nodes().iter().map(|node| node.hash.as_str()).collect();
+    Ok(without_revoked(records)
+        .into_iter()
+        .filter(|record| record.kind.is_verdict() && !live.contains(record.fingerprint.as_str()))
+        .collect())
+}
@@ -13,6 +13,8 @@ pub fn calculate(content: &str, lang: Language) -> u32 {
         Language::TypeScript => Some(tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into()),
         Language::Python => Some(tree_sitter_python::LANGUAGE.into()),
         Language::Shell => Some(tree_sitter_bash::LANGUAGE.into()),
+        Language::C => Some(tree_sitter_c::LANGUAGE.into()),
+        Language::Cpp => Some(tree_sitter_cpp::LANGUAGE.into()),
         _ => None,
     };
 
@@ -65,6 +67,17 @@ fn calculate_node(node: Node, nesting: u32, lang: &Language) -> u32 {
             kind,
             "if_statement" | "for_statement" | "while_statement" | "case_statement"
         ),
+        Language::C | Language::Cpp => matches!(
+            kind,
+            "if_statement"
+                | "for_statement"
+                | "for_range_loop"
+                | "while_statement"
+                | "do_statement"
+                | "switch_statement"
+                | "catch_clause"
+                | "conditional_expression"
+        ),
         _ => false,
     };
 
@@ -72,7 +85,7 @@ fn calculate_node(node: Node, nesting: u32, lang: &Language) -> u32 {
         Language::Rust => matches!(kind, "&&" | "||"),
         Language::JavaScript | Language::TypeScript => matches!(kind, "&&" | "||" | "??"),
         Language::Python => matches!(kind, "and" | "or"), // Python uses 'boolean_operator' usually, need to check grammar
-        Language::Shell => matches!(kind, "&&" | "||"),
+        Language::Shell | Language::C | Language::Cpp => matches!(kind, "&&" | "||"),
         _ => false,
     };
 
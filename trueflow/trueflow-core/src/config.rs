@@ -0,0 +1,700 @@
+use anyhow::{Context, Result};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use crate::block::BlockKind;
+use crate::config_layers::ConfigLayers;
+use crate::key_action::Action;
+use crate::publish::Provider;
+use crate::signing::SigningFormat;
+use crate::store::{StoreBackend, Verdict};
+
+pub const CONFIG_FILE_NAME: &str = "trueflow.toml";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TrueflowConfig {
+    #[serde(default)]
+    pub review: BlockFilterConfig,
+    #[serde(default)]
+    pub feedback: BlockFilterConfig,
+    #[serde(default)]
+    pub tui: TuiConfig,
+    #[serde(default)]
+    pub policy: PolicyConfig,
+    #[serde(default)]
+    pub auto_approve: AutoApproveConfig,
+    #[serde(default)]
+    pub prereview: PrereviewConfig,
+    #[serde(default)]
+    pub risk: RiskConfig,
+    #[serde(default)]
+    pub logging: LoggingConfig,
+    #[serde(default)]
+    pub optimizer: OptimizerConfig,
+    #[serde(default)]
+    pub hashing: HashingConfig,
+    #[serde(default)]
+    pub identity: IdentityConfig,
+    #[serde(default)]
+    pub sigstore: SigstoreConfig,
+    #[serde(default)]
+    pub signers: SignersConfig,
+    #[serde(default)]
+    pub owners: OwnersConfig,
+    #[serde(default)]
+    pub publish: PublishConfig,
+    #[serde(default)]
+    pub store: StoreConfig,
+    #[serde(default)]
+    pub scan: ScanConfig,
+    #[serde(default)]
+    pub vcs: VcsConfig,
+    #[serde(default)]
+    pub team: TeamConfig,
+    /// `[tags]`: path globs per tag, e.g. `security = ["src/auth/**"]`.
+    /// Blocks of matching files carry the tag.
+    #[serde(default)]
+    pub tags: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VcsConfig {
+    /// Ref that "diff vs main" scopes branch off from; main or master when unset.
+    #[serde(default)]
+    pub base_branch: Option<String>,
+}
+
+/// `[team]`: keeping reviewers who work at the same time out of each other's way.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TeamConfig {
+    /// The TUI claims the file it shows, flags files someone else claimed
+    /// and leaves them for last.
+    #[serde(default = "default_true")]
+    pub claims: bool,
+    /// Minutes a claim lasts unless the TUI refreshes it.
+    #[serde(default = "default_claim_ttl_minutes")]
+    pub claim_ttl_minutes: u32,
+}
+
+impl Default for TeamConfig {
+    fn default() -> Self {
+        Self {
+            claims: true,
+            claim_ttl_minutes: default_claim_ttl_minutes(),
+        }
+    }
+}
+
+fn default_claim_ttl_minutes() -> u32 {
+    30
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScanConfig {
+    /// Files parsed in parallel; 0 uses one thread per CPU.
+    #[serde(default)]
+    pub jobs: usize,
+    /// Globs to scan exclusively, even if an ignore file excludes them.
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// Globs never to scan.
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// Scan the files inside submodules instead of one pointer block each.
+    #[serde(default)]
+    pub recurse_submodules: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StoreConfig {
+    #[serde(default)]
+    pub backend: StoreBackend,
+    /// Review server for the `http` backend, or bucket URL for `s3`.
+    #[serde(default)]
+    pub url: Option<String>,
+    /// Variable holding the server's bearer token (default `TRUEFLOW_STORE_TOKEN`).
+    #[serde(default)]
+    pub token_env: Option<String>,
+    /// Key prefix of the `s3` backend's record objects.
+    #[serde(default)]
+    pub prefix: Option<String>,
+    /// Region requests to the `s3` backend are signed for (default `us-east-1`).
+    #[serde(default)]
+    pub region: Option<String>,
+}
+
+/// Where `publish` posts review notes.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PublishConfig {
+    #[serde(default)]
+    pub provider: Provider,
+    /// API base URL; defaults to the public GitHub or GitLab API. Required for Gitea.
+    #[serde(default)]
+    pub url: Option<String>,
+    /// Repository (`owner/name`) or GitLab project path.
+    #[serde(default)]
+    pub repo: Option<String>,
+    /// Environment variable holding the API token; defaults to
+    /// `GITHUB_TOKEN`, `GITLAB_TOKEN`, or `GITEA_TOKEN`.
+    #[serde(default)]
+    pub token_env: Option<String>,
+}
+
+/// Where path owners come from, for `[policy] require_owner_review`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OwnersConfig {
+    /// Owners file relative to the repository root; defaults to the first of
+    /// `.trueflow/CODEOWNERS`, `.github/CODEOWNERS`, `CODEOWNERS`, `docs/CODEOWNERS`.
+    #[serde(default)]
+    pub file: Option<String>,
+    /// Emails behind CODEOWNERS handles, keyed without the `@`:
+    /// `"org/net" = ["alice@example.com"]`.
+    #[serde(default)]
+    pub handles: HashMap<String, Vec<String>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IdentityConfig {
+    /// Sign every record with git's `user.signingkey`, as `mark --sign` does.
+    #[serde(default)]
+    pub sign: bool,
+    /// Signature backend; defaults to git's `gpg.format`.
+    #[serde(default)]
+    pub format: Option<SigningFormat>,
+    /// Who records are attributed to when no `--agent` is given.
+    #[serde(default)]
+    pub provider: IdentityProvider,
+    /// Name recorded with `provider = "machine"`, e.g. `nightly-ci`.
+    #[serde(default)]
+    pub machine: Option<String>,
+    /// `[identity.oidc]`: the provider `trueflow login` signs in with.
+    #[serde(default)]
+    pub oidc: Option<OidcConfig>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IdentityProvider {
+    /// git's `user.email`.
+    #[default]
+    Git,
+    /// The identity `trueflow login` cached for `[identity.oidc] issuer`.
+    Oidc,
+    /// `[identity] machine`, for service accounts.
+    Machine,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OidcConfig {
+    /// Issuer URL, e.g. `https://login.example.com`; must support the
+    /// device authorization flow.
+    pub issuer: String,
+    /// Client id registered with the issuer for trueflow.
+    pub client_id: String,
+    #[serde(default = "default_oidc_scopes")]
+    pub scopes: Vec<String>,
+}
+
+pub fn default_oidc_scopes() -> Vec<String> {
+    vec!["openid".to_string(), "email".to_string()]
+}
+
+/// Who Sigstore attestations must come from for `verify` to accept them.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SigstoreConfig {
+    /// Certificate subject, e.g. a CI workflow URL or an email.
+    #[serde(default)]
+    pub certificate_identity: Option<String>,
+    /// OIDC issuer, e.g. `https://token.actions.githubusercontent.com`.
+    #[serde(default)]
+    pub certificate_oidc_issuer: Option<String>,
+}
+
+/// Whose GPG and SSH attestations `verify` accepts. The key embedded in a
+/// record only proves the record wasn't altered, not who signed it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SignersConfig {
+    /// Fingerprints of trusted OpenPGP keys (primary key or signing subkey).
+    #[serde(default)]
+    pub pgp_fingerprints: Vec<String>,
+    /// An `ssh-keygen` allowed signers file, whose principals are emails.
+    /// Defaults to git's `gpg.ssh.allowedSignersFile`.
+    #[serde(default)]
+    pub allowed_signers: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HashingConfig {
+    #[serde(default)]
+    pub normalization: Normalization,
+}
+
+/// What block content is canonicalized to before it is fingerprinted.
+/// Switching modes changes every block fingerprint.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Normalization {
+    /// Exact bytes.
+    Strict,
+    /// Trailing whitespace trimmed and line endings normalized.
+    #[default]
+    Whitespace,
+    /// Comments and all whitespace dropped, so reformatting or rewording a
+    /// comment keeps approvals.
+    Semantic,
+}
+
+/// How adjacent blocks are merged before review. Changing these changes
+/// block fingerprints, so existing approvals of affected blocks stop matching.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OptimizerConfig {
+    /// Merge consecutive imports into one `Imports` block.
+    #[serde(default = "default_true")]
+    pub merge_imports: bool,
+    /// Merge consecutive module declarations into one `Modules` block.
+    #[serde(default = "default_true")]
+    pub merge_modules: bool,
+    #[serde(default = "default_max_module_lines")]
+    pub max_module_lines: usize,
+    /// Merge runs of `const`/`static` items into one `Constants` block.
+    #[serde(default)]
+    pub merge_constants: bool,
+    #[serde(default = "default_max_constant_lines")]
+    pub max_constant_lines: usize,
+    /// Merge small neighbouring code paragraphs.
+    #[serde(default = "default_true")]
+    pub merge_code_paragraphs: bool,
+    #[serde(default = "default_max_code_paragraph_lines")]
+    pub max_code_paragraph_lines: usize,
+    /// Coalesce neighbouring blocks of any kind that are at most
+    /// `tiny_block_max_lines` lines and `tiny_block_max_bytes` bytes.
+    #[serde(default)]
+    pub merge_tiny_blocks: bool,
+    #[serde(default = "default_tiny_block_max_lines")]
+    pub tiny_block_max_lines: usize,
+    #[serde(default = "default_tiny_block_max_bytes")]
+    pub tiny_block_max_bytes: usize,
+    /// Largest span, in lines, a run of tiny blocks is merged into.
+    #[serde(default = "default_max_tiny_run_lines")]
+    pub max_tiny_run_lines: usize,
+}
+
+impl Default for OptimizerConfig {
+    fn default() -> Self {
+        Self {
+            merge_imports: true,
+            merge_modules: true,
+            max_module_lines: default_max_module_lines(),
+            merge_constants: false,
+            max_constant_lines: default_max_constant_lines(),
+            merge_code_paragraphs: true,
+            max_code_paragraph_lines: default_max_code_paragraph_lines(),
+            merge_tiny_blocks: false,
+            tiny_block_max_lines: default_tiny_block_max_lines(),
+            tiny_block_max_bytes: default_tiny_block_max_bytes(),
+            max_tiny_run_lines: default_max_tiny_run_lines(),
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_max_module_lines() -> usize {
+    48
+}
+
+fn default_max_constant_lines() -> usize {
+    24
+}
+
+fn default_max_code_paragraph_lines() -> usize {
+    8
+}
+
+fn default_tiny_block_max_lines() -> usize {
+    2
+}
+
+fn default_tiny_block_max_bytes() -> usize {
+    160
+}
+
+fn default_max_tiny_run_lines() -> usize {
+    24
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LoggingConfig {
+    /// Also write JSON logs to `.trueflow/logs/trueflow.jsonl`.
+    #[serde(default)]
+    pub structured: bool,
+    /// Rotate the structured log once it reaches this size.
+    #[serde(default = "default_log_max_bytes")]
+    pub max_bytes: u64,
+    /// Rotated files to keep (`trueflow.jsonl.1` is the newest).
+    #[serde(default = "default_log_max_files")]
+    pub max_files: usize,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            structured: false,
+            max_bytes: default_log_max_bytes(),
+            max_files: default_log_max_files(),
+        }
+    }
+}
+
+fn default_log_max_bytes() -> u64 {
+    5 * 1024 * 1024
+}
+
+fn default_log_max_files() -> usize {
+    3
+}
+
+/// Rules for approving low-risk blocks without a reviewer, recorded under a
+/// `machine` identity. `enabled` runs them during `review`; `trueflow
+/// auto-approve` runs them on demand.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AutoApproveConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_auto_approve_kinds")]
+    pub kinds: Vec<String>,
+    /// Blocks of `kinds` longer than this are left for a human.
+    #[serde(default = "default_auto_approve_max_lines")]
+    pub max_lines: usize,
+    /// Globs of files whose blocks are all approved, e.g. `generated/**`.
+    #[serde(default)]
+    pub paths: Vec<String>,
+    /// Approve lockfiles and files detected as generated.
+    #[serde(default)]
+    pub generated: bool,
+    /// Approve blocks that normalize like an approved block elsewhere in
+    /// the tree (differing only in formatting, comments, or local names).
+    #[serde(default)]
+    pub duplicates: bool,
+}
+
+impl Default for AutoApproveConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            kinds: default_auto_approve_kinds(),
+            max_lines: default_auto_approve_max_lines(),
+            paths: Vec::new(),
+            generated: false,
+            duplicates: false,
+        }
+    }
+}
+
+impl AutoApproveConfig {
+    pub fn eligible_kinds(&self) -> HashSet<BlockKind> {
+        parse_block_kinds(&self.kinds)
+    }
+}
+
+fn default_auto_approve_kinds() -> Vec<String> {
+    ["gap", "import", "imports", "comment"]
+        .iter()
+        .map(|kind| kind.to_string())
+        .collect()
+}
+
+fn default_auto_approve_max_lines() -> usize {
+    10
+}
+
+/// `[risk]`: what raises a block's risk score besides its complexity.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiskConfig {
+    /// Days back a commit counts as recent churn.
+    #[serde(default = "default_churn_days")]
+    pub churn_days: u32,
+    /// Points (up to 100) added to blocks of files matching each glob, e.g.
+    /// `"src/auth/**" = 30`; the highest matching weight counts.
+    #[serde(default)]
+    pub paths: BTreeMap<String, u8>,
+}
+
+impl Default for RiskConfig {
+    fn default() -> Self {
+        Self {
+            churn_days: default_churn_days(),
+            paths: BTreeMap::new(),
+        }
+    }
+}
+
+fn default_churn_days() -> u32 {
+    90
+}
+
+/// `[prereview]`: the annotator `trueflow prereview` asks about each
+/// unreviewed block. It gets one JSON request per block and answers with a
+/// JSON summary, risk and suggested verdict.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PrereviewConfig {
+    /// Shell command reading the request on stdin and writing the answer to
+    /// stdout, e.g. a script calling a local model.
+    #[serde(default)]
+    pub command: Option<String>,
+    /// Endpoint the request is POSTed to instead.
+    #[serde(default)]
+    pub url: Option<String>,
+    /// Variable holding a bearer token for `url`.
+    #[serde(default)]
+    pub token_env: Option<String>,
+    /// Machine identity the annotations are recorded under; defaults to
+    /// `trueflow-prereview`.
+    #[serde(default)]
+    pub machine: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PolicyConfig {
+    /// Only human verdicts count; agent and machine verdicts are advisory.
+    #[serde(default)]
+    pub require_human: bool,
+    /// Agent verdicts below this confidence need human confirmation.
+    #[serde(default)]
+    pub min_agent_confidence: Option<f64>,
+    /// Distinct human identities that must approve a block before it counts
+    /// as reviewed. One keeps the shared last-write-wins state.
+    #[serde(default = "default_required_approvals")]
+    pub required_approvals: usize,
+    /// `review` and the TUI show each reviewer their own queue: blocks they
+    /// have not approved themselves, whatever others recorded.
+    #[serde(default)]
+    pub per_reviewer: bool,
+    /// Verdicts that let a change through `check` without an approval, e.g.
+    /// `["comment"]`. Rejections always block.
+    #[serde(default)]
+    pub non_blocking_verdicts: Vec<Verdict>,
+    /// Extra approvals `check` requires for matching paths.
+    #[serde(default)]
+    pub rules: Vec<PolicyRule>,
+    /// Blocks with CODEOWNERS owners also need an approval from one of them.
+    #[serde(default)]
+    pub require_owner_review: bool,
+}
+
+impl Default for PolicyConfig {
+    fn default() -> Self {
+        Self {
+            require_human: false,
+            min_agent_confidence: None,
+            required_approvals: default_required_approvals(),
+            per_reviewer: false,
+            non_blocking_verdicts: Vec::new(),
+            rules: Vec::new(),
+            require_owner_review: false,
+        }
+    }
+}
+
+fn default_required_approvals() -> usize {
+    1
+}
+
+/// A `[[policy.rules]]` entry: changes under `paths` need `approvals`
+/// distinct approvers recording verdicts under `check`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyRule {
+    /// Globs relative to the repository root; every path when both this
+    /// and `tags` are empty.
+    #[serde(default)]
+    pub paths: Vec<String>,
+    /// `[tags]` entries whose paths the rule covers too.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default = "default_rule_check")]
+    pub check: String,
+    #[serde(default = "default_required_approvals")]
+    pub approvals: usize,
+}
+
+fn default_rule_check() -> String {
+    "review".to_string()
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TuiConfig {
+    #[serde(default = "default_confirm_batch")]
+    pub confirm_batch: bool,
+    /// `[tui.keys]`: replacement key lists per action, e.g. `approve = ["y"]`.
+    #[serde(default)]
+    pub keys: HashMap<Action, Vec<String>>,
+    #[serde(default)]
+    pub theme: ThemeMode,
+    /// `[tui.colors]`: palette overrides by name, e.g. `keyword = "#458588"`.
+    #[serde(default)]
+    pub colors: HashMap<String, String>,
+}
+
+impl Default for TuiConfig {
+    fn default() -> Self {
+        Self {
+            confirm_batch: true,
+            keys: HashMap::new(),
+            theme: ThemeMode::default(),
+            colors: HashMap::new(),
+        }
+    }
+}
+
+/// Which palette the TUI starts from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemeMode {
+    Light,
+    Dark,
+    /// Dark or light to match the terminal's background.
+    #[default]
+    Auto,
+}
+
+fn default_confirm_batch() -> bool {
+    true
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct BlockFilterConfig {
+    #[serde(default)]
+    pub only: Vec<String>,
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// Include files detected as generated (lockfiles, `@generated`, ...)
+    #[serde(default)]
+    pub include_generated: bool,
+    /// Order the TUI walks blocks in (`[review]` only).
+    #[serde(default)]
+    pub order: ReviewOrderMode,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReviewOrderMode {
+    /// File by file, signatures and small blocks first.
+    #[default]
+    File,
+    /// Riskiest blocks first, by `[risk]` score.
+    Risk,
+}
+
+impl BlockFilterConfig {
+    pub fn resolve_filters(&self, cli_only: &[String], cli_exclude: &[String]) -> BlockFilters {
+        let only_values = if cli_only.is_empty() {
+            &self.only
+        } else {
+            cli_only
+        };
+        let exclude_values = if cli_exclude.is_empty() {
+            &self.exclude
+        } else {
+            cli_exclude
+        };
+        let mut filters = BlockFilters::from_lists(only_values, exclude_values);
+        filters.include_generated = self.include_generated;
+        filters
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct BlockFilters {
+    only: Option<HashSet<BlockKind>>,
+    exclude: HashSet<BlockKind>,
+    pub include_generated: bool,
+}
+
+impl BlockFilters {
+    pub fn from_lists(only: &[String], exclude: &[String]) -> Self {
+        let only_set = parse_block_kinds(only);
+        let exclude_set = parse_block_kinds(exclude);
+        let only = if only_set.is_empty() {
+            None
+        } else {
+            Some(only_set)
+        };
+        Self {
+            only,
+            exclude: exclude_set,
+            include_generated: false,
+        }
+    }
+
+    pub fn allows_block(&self, kind: &BlockKind) -> bool {
+        if self.exclude.contains(kind) {
+            return false;
+        }
+        match &self.only {
+            Some(only) => only.contains(kind),
+            None => true,
+        }
+    }
+
+    pub fn allows_subblock(&self, kind: &BlockKind) -> bool {
+        !self.exclude.contains(kind)
+    }
+
+    pub fn only_contains(&self, kind: &BlockKind) -> bool {
+        self.only.as_ref().is_some_and(|only| only.contains(kind))
+    }
+}
+
+/// Per-user trueflow files: `TRUEFLOW_CONFIG_DIR`, else `trueflow` in the
+/// platform config directory (`~/.config/trueflow` on Linux).
+pub fn user_config_dir() -> Result<PathBuf> {
+    if let Some(dir) = std::env::var_os("TRUEFLOW_CONFIG_DIR").filter(|dir| !dir.is_empty()) {
+        return Ok(PathBuf::from(dir));
+    }
+    dirs::config_dir()
+        .map(|dir| dir.join("trueflow"))
+        .context("No user config directory; set TRUEFLOW_CONFIG_DIR")
+}
+
+/// The effective config: built-in defaults under the user file, the repo's
+/// `trueflow.toml`, `TRUEFLOW__*` variables and `--config` arguments.
+pub fn load() -> Result<TrueflowConfig> {
+    ConfigLayers::load()?.config()
+}
+
+/// The config that applies in `dir` rather than the working directory.
+pub fn load_in(dir: &Path) -> Result<TrueflowConfig> {
+    ConfigLayers::load_in(dir)?.config()
+}
+
+/// The nearest `trueflow.toml` at or above `start_dir`.
+pub fn find_config_path(start_dir: &Path) -> Option<PathBuf> {
+    let mut current = Some(start_dir);
+    while let Some(dir) = current {
+        let candidate = dir.join(CONFIG_FILE_NAME);
+        if candidate.is_file() {
+            return Some(candidate);
+        }
+        current = dir.parent();
+    }
+    None
+}
+
+fn parse_block_kinds(values: &[String]) -> HashSet<BlockKind> {
+    let mut kinds = HashSet::new();
+    for value in values {
+        match value.parse::<BlockKind>() {
+            Ok(kind) => {
+                kinds.insert(kind);
+            }
+            Err(err) => {
+                warn!("Ignoring unknown block kind '{}': {}", value, err);
+            }
+        }
+    }
+    kinds
+}
@@ -0,0 +1,460 @@
+use crate::config::{CONFIG_FILE_NAME, TrueflowConfig, find_config_path, user_config_dir};
+use anyhow::{Context, Result, anyhow, bail};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use toml::{Table, Value};
+
+/// The per-user config file, in [`user_config_dir`].
+pub const USER_CONFIG_FILE_NAME: &str = "config.toml";
+/// `TRUEFLOW__POLICY__REQUIRE_HUMAN=true` sets `policy.require_human`.
+const ENV_PREFIX: &str = "TRUEFLOW__";
+
+/// `--config KEY=VALUE` arguments, set once at startup.
+static CLI_OVERRIDES: OnceLock<Vec<String>> = OnceLock::new();
+
+pub fn configure_overrides(overrides: Vec<String>) {
+    if !overrides.is_empty() {
+        let _ = CLI_OVERRIDES.set(overrides);
+    }
+}
+
+/// Where a config value came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigSource {
+    Default,
+    User(PathBuf),
+    Repo(PathBuf),
+    Env(String),
+    Cli(String),
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigSource::Default => f.write_str("default"),
+            ConfigSource::User(path) => write!(f, "user {}", path.display()),
+            ConfigSource::Repo(path) => write!(f, "repo {}", path.display()),
+            ConfigSource::Env(var) => write!(f, "env {var}"),
+            ConfigSource::Cli(arg) => write!(f, "--config {arg}"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Layer {
+    pub source: ConfigSource,
+    pub table: Table,
+}
+
+/// Config layers, lowest precedence first: the user file, the repo's
+/// `trueflow.toml`, `TRUEFLOW__*` variables, then `--config` arguments.
+/// Tables merge key by key; any other value, arrays included, replaces the
+/// one below it.
+#[derive(Debug, Default)]
+pub struct ConfigLayers {
+    pub layers: Vec<Layer>,
+}
+
+/// A key set by some layer, with its effective value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigEntry {
+    pub key: String,
+    pub value: Value,
+    pub source: ConfigSource,
+}
+
+impl ConfigLayers {
+    /// The layers that apply in the working directory.
+    pub fn load() -> Result<Self> {
+        Self::load_in(&std::env::current_dir()?)
+    }
+
+    /// The layers that apply in `dir`.
+    pub fn load_in(dir: &Path) -> Result<Self> {
+        let mut layers = Vec::new();
+        if let Some(path) = user_config_path().filter(|path| path.is_file()) {
+            layers.push(Layer {
+                table: read_table(&path)?,
+                source: ConfigSource::User(path),
+            });
+        }
+        if let Some(path) = find_config_path(dir) {
+            layers.push(Layer {
+                table: read_table(&path)?,
+                source: ConfigSource::Repo(path),
+            });
+        }
+        for (var, raw) in env_overrides() {
+            let key = var[ENV_PREFIX.len()..]
+                .split("__")
+                .map(str::to_lowercase)
+                .collect::<Vec<_>>()
+                .join(".");
+            layers.push(Layer {
+                table: dotted_table(&key, parse_value(&raw))?,
+                source: ConfigSource::Env(var),
+            });
+        }
+        for arg in CLI_OVERRIDES.get().into_iter().flatten() {
+            let (key, raw) = arg
+                .split_once('=')
+                .with_context(|| format!("--config {arg}: expected KEY=VALUE"))?;
+            layers.push(Layer {
+                table: dotted_table(key.trim(), parse_value(raw.trim()))?,
+                source: ConfigSource::Cli(arg.clone()),
+            });
+        }
+        Ok(Self { layers })
+    }
+
+    /// Every layer merged, defaults left out.
+    pub fn merged(&self) -> Table {
+        let mut merged = Table::new();
+        for layer in &self.layers {
+            merge(&mut merged, &layer.table);
+        }
+        merged
+    }
+
+    /// The effective config. An invalid value is blamed on the first
+    /// layer that fails on its own.
+    pub fn config(&self) -> Result<TrueflowConfig> {
+        Value::Table(self.merged()).try_into().map_err(|err| {
+            let culprit = self.layers.iter().find(|layer| {
+                Value::Table(layer.table.clone())
+                    .try_into::<TrueflowConfig>()
+                    .is_err()
+            });
+            match culprit {
+                Some(layer) => anyhow!("Invalid config from {}: {}", layer.source, err),
+                None => anyhow!("Invalid config: {}", err),
+            }
+        })
+    }
+
+    /// Keys set by a layer, each with its effective value and the last
+    /// layer that set it.
+    pub fn entries(&self) -> Vec<ConfigEntry> {
+        let merged = self.merged();
+        let mut entries = Vec::new();
+        for (key, value) in leaves(&merged) {
+            let source = self
+                .layers
+                .iter()
+                .rev()
+                .find(|layer| lookup(&layer.table, &key).is_some())
+                .map(|layer| layer.source.clone())
+                .unwrap_or(ConfigSource::Default);
+            entries.push(ConfigEntry {
+                key,
+                value: value.clone(),
+                source,
+            });
+        }
+        entries
+    }
+
+    /// `key`'s effective value and where it came from; built-in defaults
+    /// count for keys no layer sets.
+    pub fn get(&self, key: &str) -> Result<Option<(Value, ConfigSource)>> {
+        let merged = self.merged();
+        if let Some(value) = lookup(&merged, key) {
+            let source = self
+                .layers
+                .iter()
+                .rev()
+                .find(|layer| lookup(&layer.table, key).is_some())
+                .map(|layer| layer.source.clone())
+                .unwrap_or(ConfigSource::Default);
+            return Ok(Some((value.clone(), source)));
+        }
+        Ok(lookup(&defaults()?, key).map(|value| (value.clone(), ConfigSource::Default)))
+    }
+
+    /// Set keys the config does not read, such as misspelt ones: they do
+    /// not survive a round trip through [`TrueflowConfig`].
+    pub fn unknown_keys(&self) -> Result<Vec<ConfigEntry>> {
+        let effective = Table::try_from(self.config()?)?;
+        Ok(self
+            .entries()
+            .into_iter()
+            .filter(|entry| lookup(&effective, &entry.key).is_none())
+            .collect())
+    }
+}
+
+/// Built-in defaults as TOML, for showing keys no layer sets.
+pub fn defaults() -> Result<Table> {
+    Ok(Table::try_from(TrueflowConfig::default())?)
+}
+
+pub fn user_config_path() -> Option<PathBuf> {
+    user_config_dir()
+        .ok()
+        .map(|dir| dir.join(USER_CONFIG_FILE_NAME))
+}
+
+/// The repo's `trueflow.toml`, or where it would go at `root`.
+pub fn repo_config_path(root: &Path) -> PathBuf {
+    find_config_path(root).unwrap_or_else(|| root.join(CONFIG_FILE_NAME))
+}
+
+fn env_overrides() -> Vec<(String, String)> {
+    let mut vars: Vec<(String, String)> = std::env::vars()
+        .filter(|(name, _)| name.starts_with(ENV_PREFIX) && name.len() > ENV_PREFIX.len())
+        .collect();
+    vars.sort();
+    vars
+}
+
+fn read_table(path: &Path) -> Result<Table> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config: {}", path.display()))?;
+    toml::from_str(&content).with_context(|| format!("Failed to parse config: {}", path.display()))
+}
+
+/// A TOML value (`true`, `3`, `["a"]`, `"quoted"`), else the raw text as a
+/// string.
+pub fn parse_value(raw: &str) -> Value {
+    toml::from_str::<Table>(&format!("value = {raw}"))
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| Value::String(raw.to_string()))
+}
+
+/// `{a = {b = value}}` for `a.b`.
+fn dotted_table(key: &str, value: Value) -> Result<Table> {
+    let parts: Vec<&str> = key.split('.').collect();
+    if parts.iter().any(|part| part.trim().is_empty()) {
+        bail!("Invalid config key '{key}'");
+    }
+    let mut value = value;
+    for part in parts.iter().skip(1).rev() {
+        value = Value::Table(Table::from_iter([(part.to_string(), value)]));
+    }
+    Ok(Table::from_iter([(parts[0].to_string(), value)]))
+}
+
+fn merge(base: &mut Table, overlay: &Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(key), value) {
+            (Some(Value::Table(base)), Value::Table(overlay)) => merge(base, overlay),
+            _ => {
+                base.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+pub fn lookup<'a>(table: &'a Table, key: &str) -> Option<&'a Value> {
+    let mut parts = key.split('.');
+    let mut value = table.get(parts.next()?)?;
+    for part in parts {
+        value = value.as_table()?.get(part)?;
+    }
+    Some(value)
+}
+
+/// Dotted keys of every non-table value, in key order.
+fn leaves(table: &Table) -> Vec<(String, &Value)> {
+    let mut out = Vec::new();
+    for (key, value) in table {
+        match value {
+            Value::Table(inner) => out.extend(
+                leaves(inner)
+                    .into_iter()
+                    .map(|(inner_key, value)| (format!("{key}.{inner_key}"), value)),
+            ),
+            _ => out.push((key.clone(), value)),
+        }
+    }
+    out
+}
+
+/// `content` with `key` set to `value`, keeping comments and layout: the
+/// key's line is replaced, or the line is added at the end of its table.
+pub fn set_in_document(content: &str, key: &str, value: &Value) -> Result<String> {
+    let (section, name) = match key.rsplit_once('.') {
+        Some((section, name)) => (Some(section), name),
+        None => (None, key),
+    };
+    let line = format!("{name} = {value}");
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    let start = match section {
+        None => Some(0),
+        Some(section) => lines
+            .iter()
+            .position(|line| header_name(line) == Some(section))
+            .map(|idx| idx + 1),
+    };
+    match (start, section) {
+        (Some(start), _) => {
+            let end = lines[start..]
+                .iter()
+                .position(|line| line.trim_start().starts_with('['))
+                .map_or(lines.len(), |idx| start + idx);
+            match (start..end).find(|&idx| key_name(&lines[idx]) == Some(name)) {
+                Some(idx) => lines[idx] = line,
+                None => {
+                    let at = (start..end)
+                        .rev()
+                        .find(|&idx| !lines[idx].trim().is_empty())
+                        .map_or(start, |idx| idx + 1);
+                    lines.insert(at, line);
+                }
+            }
+        }
+        (None, Some(section)) => {
+            if lines.last().is_some_and(|line| !line.trim().is_empty()) {
+                lines.push(String::new());
+            }
+            lines.push(format!("[{section}]"));
+            lines.push(line);
+        }
+        (None, None) => unreachable!("top-level keys always have a start"),
+    }
+
+    let mut updated = lines.join("\n");
+    updated.push('\n');
+    let table: Table = toml::from_str(&updated)
+        .map_err(|err| anyhow!("Could not set {key} in place ({err}); edit the file by hand"))?;
+    if lookup(&table, key) != Some(value) {
+        bail!("Could not set {key} in place; edit the file by hand");
+    }
+    Ok(updated)
+}
+
+/// `policy` for a `[policy]` header line; arrays of tables are not headers
+/// a key can be added under.
+fn header_name(line: &str) -> Option<&str> {
+    let line = line.trim();
+    if line.starts_with("[[") {
+        return None;
+    }
+    let rest = line.strip_prefix('[')?;
+    Some(rest[..rest.find(']')?].trim())
+}
+
+fn key_name(line: &str) -> Option<&str> {
+    let line = line.trim_start();
+    if line.starts_with('#') {
+        return None;
+    }
+    line.split_once('=').map(|(key, _)| key.trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layer(source: ConfigSource, toml: &str) -> Layer {
+        Layer {
+            source,
+            table: toml::from_str(toml).unwrap(),
+        }
+    }
+
+    #[test]
+    fn later_layers_win_key_by_key() {
+        let user = ConfigSource::User(PathBuf::from("user.toml"));
+        let repo = ConfigSource::Repo(PathBuf::from("trueflow.toml"));
+        let cli = ConfigSource::Cli("policy.required_approvals=3".to_string());
+        let layers = ConfigLayers {
+            layers: vec![
+                layer(
+                    user.clone(),
+                    "[policy]\nrequire_human = true\nrequired_approvals = 2\n[tui]\ntheme = \"dark\"\n",
+                ),
+                layer(repo.clone(), "[policy]\nrequired_approvals = 2\n"),
+                Layer {
+                    source: cli.clone(),
+                    table: dotted_table("policy.required_approvals", parse_value("3")).unwrap(),
+                },
+            ],
+        };
+
+        let config = layers.config().unwrap();
+        assert!(config.policy.require_human);
+        assert_eq!(config.policy.required_approvals, 3);
+        let sources: Vec<(String, ConfigSource)> = layers
+            .entries()
+            .into_iter()
+            .map(|entry| (entry.key, entry.source))
+            .collect();
+        assert_eq!(
+            sources,
+            vec![
+                ("policy.require_human".to_string(), user),
+                ("policy.required_approvals".to_string(), cli),
+                (
+                    "tui.theme".to_string(),
+                    ConfigSource::User(PathBuf::from("user.toml"))
+                ),
+            ]
+        );
+        assert_eq!(
+            layers.get("scan.jobs").unwrap(),
+            Some((Value::Integer(0), ConfigSource::Default))
+        );
+    }
+
+    #[test]
+    fn invalid_values_name_their_layer_and_typos_are_unknown() {
+        let repo = ConfigSource::Repo(PathBuf::from("trueflow.toml"));
+        let bad = ConfigLayers {
+            layers: vec![layer(
+                repo.clone(),
+                "[policy]\nrequired_approvals = \"two\"\n",
+            )],
+        };
+        let err = bad.config().unwrap_err().to_string();
+        assert!(err.contains("repo trueflow.toml"), "{err}");
+
+        let typo = ConfigLayers {
+            layers: vec![layer(repo, "[policy]\nrequire_humans = true\n")],
+        };
+        let unknown: Vec<String> = typo
+            .unknown_keys()
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.key)
+            .collect();
+        assert_eq!(unknown, vec!["policy.require_humans".to_string()]);
+    }
+
+    #[test]
+    fn raw_values_fall_back_to_strings() {
+        assert_eq!(parse_value("true"), Value::Boolean(true));
+        assert_eq!(
+            parse_value("nightly-ci"),
+            Value::String("nightly-ci".to_string())
+        );
+        assert_eq!(
+            parse_value("[\"gap\"]"),
+            Value::Array(vec![Value::String("gap".to_string())])
+        );
+    }
+
+    #[test]
+    fn set_keeps_comments_and_places_keys_in_their_table() {
+        let content = "# Review policy\n[policy]\nrequire_human = false # for now\n\n[tui]\ntheme = \"dark\"\n";
+        let updated =
+            set_in_document(content, "policy.require_human", &Value::Boolean(true)).unwrap();
+        assert_eq!(
+            updated,
+            "# Review policy\n[policy]\nrequire_human = true\n\n[tui]\ntheme = \"dark\"\n"
+        );
+
+        let updated =
+            set_in_document(&updated, "policy.required_approvals", &Value::Integer(2)).unwrap();
+        assert!(updated.contains("require_human = true\nrequired_approvals = 2\n\n[tui]"));
+
+        let updated = set_in_document(&updated, "scan.jobs", &Value::Integer(4)).unwrap();
+        assert!(
+            updated.ends_with("theme = \"dark\"\n\n[scan]\njobs = 4\n"),
+            "{updated}"
+        );
+    }
+}
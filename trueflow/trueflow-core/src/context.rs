@@ -0,0 +1,48 @@
+use anyhow::Result;
+use std::path::PathBuf;
+
+use crate::color::{ColorChoice, Palette, Stream};
+use crate::output::{CheckFormat, OutputFormat, StructuredFormat};
+use crate::pager::PagedOutput;
+use crate::store::{FileStore, Identity};
+
+/// The global options every command sees.
+pub struct TrueflowContext {
+    pub color: ColorChoice,
+    pub no_pager: bool,
+    /// `--output`, over each command's own format flags.
+    pub output: Option<StructuredFormat>,
+    /// `--agent`: records are written as this agent instead of `[identity]`.
+    pub agent: Option<Identity>,
+}
+
+impl TrueflowContext {
+    pub fn palette(&self, stream: Stream) -> Palette {
+        Palette::new(self.color, stream)
+    }
+
+    /// Text output for `fallback`, paged when stdout is a terminal.
+    pub fn paged_output(&self, fallback: Stream) -> PagedOutput {
+        PagedOutput::start(self.no_pager, fallback)
+    }
+
+    /// Format for commands with a `--json` flag: `--output`, else JSON when
+    /// `json` is set, else text (`None`).
+    pub fn structured_output(&self, json: bool) -> Option<StructuredFormat> {
+        self.output.or(json.then_some(StructuredFormat::Json))
+    }
+
+    pub fn output_format(&self, format: OutputFormat, json: bool) -> OutputFormat {
+        self.output
+            .map_or(OutputFormat::resolve(format, json), OutputFormat::from)
+    }
+
+    pub fn check_format(&self, format: CheckFormat, json: bool) -> CheckFormat {
+        self.output
+            .map_or(CheckFormat::resolve(format, json), CheckFormat::from)
+    }
+
+    pub fn trueflow_dir(&self) -> Result<PathBuf> {
+        Ok(FileStore::new()?.trueflow_dir())
+    }
+}
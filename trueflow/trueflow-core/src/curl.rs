@@ -0,0 +1,172 @@
+use anyhow::{Context, Result, bail};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+
+const DEFAULT_CURL: &str = "curl";
+
+/// `TRUEFLOW_CURL`, else `curl` on `PATH`.
+fn program() -> String {
+    std::env::var("TRUEFLOW_CURL")
+        .ok()
+        .filter(|program| !program.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_CURL.to_string())
+}
+
+/// Sends a request with curl and returns the response body. The request goes
+/// in a config on stdin so tokens never show up in the process list.
+pub fn request(method: &str, url: &str, headers: &[String], body: Option<&str>) -> Result<Vec<u8>> {
+    request_with(method, url, headers, body, &[])
+}
+
+/// [`request`] with extra curl config `options`, such as `aws-sigv4`.
+pub fn request_with(
+    method: &str,
+    url: &str,
+    headers: &[String],
+    body: Option<&str>,
+    options: &[(&str, String)],
+) -> Result<Vec<u8>> {
+    send(method, url, headers, body, options, true)
+}
+
+/// [`request`] that returns the body of error responses too, for APIs such
+/// as OAuth that report errors in a JSON body.
+pub fn request_any_status(
+    method: &str,
+    url: &str,
+    headers: &[String],
+    body: Option<&str>,
+) -> Result<Vec<u8>> {
+    send(method, url, headers, body, &[], false)
+}
+
+fn send(
+    method: &str,
+    url: &str,
+    headers: &[String],
+    body: Option<&str>,
+    options: &[(&str, String)],
+    fail_on_status: bool,
+) -> Result<Vec<u8>> {
+    let mut config = format!("url = {}\nrequest = {}\n", quote(url), quote(method));
+    for (name, value) in options {
+        config.push_str(&format!("{name} = {}\n", quote(value)));
+    }
+    for header in headers {
+        config.push_str(&format!("header = {}\n", quote(header)));
+    }
+    if let Some(body) = body {
+        config.push_str(&format!("data-binary = {}\n", quote(body)));
+    }
+
+    let mut args = vec!["--silent", "--show-error"];
+    if fail_on_status {
+        args.push("--fail-with-body");
+    }
+    let output = run(&args, &config)?;
+    if !output.status.success() {
+        bail!(
+            "{method} {url} failed: {} {}",
+            String::from_utf8_lossy(&output.stderr).trim(),
+            String::from_utf8_lossy(&output.stdout).trim()
+        );
+    }
+    Ok(output.stdout)
+}
+
+/// Downloads each `(url, path)` pair in one curl run, several transfers at a
+/// time, and returns each one's HTTP status, or 0 when it got none. Error
+/// responses are written out too, so callers must check the status.
+pub fn download_all(
+    downloads: &[(String, PathBuf)],
+    headers: &[String],
+    options: &[(&str, String)],
+) -> Result<Vec<u16>> {
+    let mut config = format!(
+        "write-out = {}\n",
+        quote("%{http_code} %{filename_effective}\n")
+    );
+    for (name, value) in options {
+        config.push_str(&format!("{name} = {}\n", quote(value)));
+    }
+    for header in headers {
+        config.push_str(&format!("header = {}\n", quote(header)));
+    }
+    for (url, path) in downloads {
+        config.push_str(&format!(
+            "url = {}\noutput = {}\n",
+            quote(url),
+            quote(&path.to_string_lossy())
+        ));
+    }
+
+    let output = run(&["--silent", "--show-error", "--parallel"], &config)?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let statuses: HashMap<&str, u16> = stdout
+        .lines()
+        .filter_map(|line| {
+            let (status, path) = line.split_once(' ')?;
+            Some((path, status.parse().ok()?))
+        })
+        .collect();
+    if statuses.is_empty() && !output.status.success() {
+        bail!(
+            "Downloading {} objects failed: {}",
+            downloads.len(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(downloads
+        .iter()
+        .map(|(_, path)| {
+            statuses
+                .get(path.to_string_lossy().as_ref())
+                .copied()
+                .unwrap_or(0)
+        })
+        .collect())
+}
+
+/// Runs curl with `args` and the rest of its options as a config on stdin.
+fn run(args: &[&str], config: &str) -> Result<Output> {
+    let mut child = Command::new(program())
+        .args(args)
+        .args(["--config", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to run curl (set TRUEFLOW_CURL to its path)")?;
+    child
+        .stdin
+        .take()
+        .context("Failed to open stdin")?
+        .write_all(config.as_bytes())?;
+    Ok(child.wait_with_output()?)
+}
+
+/// A double-quoted curl config value.
+fn quote(value: &str) -> String {
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+        .replace('\t', "\\t");
+    format!("\"{escaped}\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quotes_json_for_curl_config() {
+        assert_eq!(
+            quote(r#"{"body":"a \"b\"\n"}"#),
+            r#""{\"body\":\"a \\\"b\\\"\\n\"}""#
+        );
+    }
+}
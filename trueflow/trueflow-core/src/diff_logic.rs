@@ -0,0 +1,443 @@
+use crate::block::{Block, BlockKind};
+use crate::config::load as load_config;
+use crate::hashing::compute_fingerprint;
+use crate::owner_review::OwnerReview;
+use crate::policy::{
+    Violation, ViolationReason, counted_records, is_non_blocking, review_state, rule_states,
+};
+use crate::store::{Record, Verdict, approved_hashes_from_verdicts, open_store};
+use crate::tree;
+use crate::vcs;
+use anyhow::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::HashMap;
+
+#[derive(Serialize, JsonSchema)]
+pub struct Change {
+    pub fingerprint: String,
+    pub file: String,
+    pub line: u32,
+    pub diff_content: String, // The +/- diff
+    pub new_content: String,  // The clean new content (for editing/preview)
+    pub context: String,
+    pub status: String,
+    pub reviews: Vec<Record>,
+}
+
+/// Which verdicts decide whether a change is reviewed.
+#[derive(Debug, Default)]
+pub struct ReviewRequirement {
+    /// Only this identity's verdicts count.
+    pub reviewer: Option<String>,
+    /// Overrides `[policy] required_approvals`.
+    pub required_approvals: Option<usize>,
+    /// The changes to gate; main against HEAD by default.
+    pub base: vcs::DiffBase,
+}
+
+/// Everything `check` gates on: changes without a review approval, plus
+/// violations of `[[policy.rules]]`.
+pub struct PolicyReport {
+    pub unreviewed: Vec<Change>,
+    pub rule_violations: Vec<Violation>,
+}
+
+impl PolicyReport {
+    pub fn passed(&self) -> bool {
+        self.unreviewed.is_empty() && self.rule_violations.is_empty()
+    }
+
+    /// All violations, unreviewed changes first.
+    pub fn violations(&self) -> Vec<Violation> {
+        self.unreviewed
+            .iter()
+            .map(|change| Violation {
+                file: change.file.clone(),
+                line: change.line,
+                fingerprint: change.fingerprint.clone(),
+                reason: if change.status == Verdict::Rejected.as_str() {
+                    ViolationReason::Rejected {
+                        check: "review".to_string(),
+                    }
+                } else {
+                    ViolationReason::Unreviewed {
+                        status: change.status.clone(),
+                    }
+                },
+            })
+            .chain(self.rule_violations.iter().cloned())
+            .collect()
+    }
+}
+
+pub fn get_unreviewed_changes(requirement: &ReviewRequirement) -> Result<Vec<Change>> {
+    Ok(evaluate_policy(requirement)?.unreviewed)
+}
+
+pub fn evaluate_policy(requirement: &ReviewRequirement) -> Result<PolicyReport> {
+    // 1. Load DB
+    let store = open_store()?;
+    let mut config = load_config()?;
+    if let Some(required) = requirement.required_approvals {
+        config.policy.required_approvals = required;
+    }
+    let policy = &config.policy;
+    let history = counted_records(store.read_history()?, policy);
+
+    // Build lookup map: (fingerprint, check) -> verdict
+    // We also store the full history for the fingerprint to enable queries
+    let review_state = review_state(&history, policy, requirement.reviewer.as_deref());
+    let rules = rule_states(policy, &config.tags, &history)?;
+    let owner_review = OwnerReview::load(&config, &history)?;
+    let mut reviews_by_fp: HashMap<String, Vec<Record>> = HashMap::new();
+
+    for record in history
+        .into_iter()
+        .filter(|record| record.kind.is_verdict())
+    {
+        reviews_by_fp
+            .entry(record.fingerprint.clone())
+            .or_default()
+            .push(record);
+    }
+
+    let approved_hashes = approved_hashes_from_verdicts(&review_state);
+    let tree = tree::build_tree_from_path(".")?;
+
+    // 2. Compute Diff
+    let diff_hunks = vcs::diff_hunks(&requirement.base)?;
+
+    let mut unreviewed_changes = Vec::new();
+    let mut rule_violations = Vec::new();
+
+    for hunk in diff_hunks {
+        let (diff_content, new_content, context, hash_body) = parse_hunk_lines(&hunk.lines);
+
+        let fp = compute_fingerprint(&hash_body, &context);
+        let fp_str = fp.as_string();
+
+        // Check status
+        let verdict = review_state.get(&fp_str);
+        let status = verdict.map(|v| v.as_str()).unwrap_or("unreviewed");
+
+        // Get all reviews for this hunk
+        let reviews = reviews_by_fp.get(&fp_str).cloned().unwrap_or_default();
+
+        let node = tree.find_by_path(&hunk.file_path);
+        for rule in rules.iter().filter(|rule| rule.applies_to(&hunk.file_path)) {
+            let covered =
+                node.is_some_and(|node_id| tree.is_node_covered(node_id, rule.approved_hashes()));
+            if let Some(reason) = rule.violation(&fp_str, covered) {
+                rule_violations.push(Violation {
+                    file: hunk.file_path.clone(),
+                    line: hunk.new_start,
+                    fingerprint: fp_str.clone(),
+                    reason,
+                });
+            }
+        }
+
+        let reviewed = verdict == Some(&Verdict::Approved)
+            || node.is_some_and(|node_id| tree.is_node_covered(node_id, &approved_hashes));
+        if reviewed {
+            if let Some(owners) = owner_review
+                .as_ref()
+                .and_then(|owner_review| owner_review.for_path(&hunk.file_path))
+                && !owners.covers(&tree, node, &fp_str)
+            {
+                rule_violations.push(Violation {
+                    file: hunk.file_path.clone(),
+                    line: hunk.new_start,
+                    fingerprint: fp_str,
+                    reason: ViolationReason::MissingOwnerReview {
+                        owners: owners.owners,
+                    },
+                });
+            }
+            continue;
+        }
+
+        let blocking = verdict.is_none_or(|verdict| !is_non_blocking(policy, verdict));
+        if blocking {
+            unreviewed_changes.push(Change {
+                fingerprint: fp_str,
+                file: hunk.file_path.clone(),
+                line: hunk.new_start,
+                diff_content,
+                new_content,
+                context,
+                status: status.to_string(),
+                reviews,
+            });
+        }
+    }
+
+    Ok(PolicyReport {
+        unreviewed: unreviewed_changes,
+        rule_violations,
+    })
+}
+
+/// How a block differs between the two sides of a diff.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockChangeKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+impl BlockChangeKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BlockChangeKind::Added => "added",
+            BlockChangeKind::Removed => "removed",
+            BlockChangeKind::Modified => "modified",
+        }
+    }
+}
+
+/// A changed block, identified by the same fingerprint `review` uses.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct BlockChange {
+    pub file: String,
+    pub change: BlockChangeKind,
+    pub kind: BlockKind,
+    /// 1-based start line; on the old side for removals.
+    pub line: u32,
+    /// The new fingerprint, or the old one for removals.
+    pub fingerprint: String,
+    /// The fingerprint a modified block had before.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_fingerprint: Option<String>,
+    pub status: String,
+}
+
+/// The diff's changed hunks as added, removed, and modified blocks of the
+/// old and new versions of each file.
+pub fn get_block_changes(requirement: &ReviewRequirement) -> Result<Vec<BlockChange>> {
+    let store = open_store()?;
+    let mut config = load_config()?;
+    if let Some(required) = requirement.required_approvals {
+        config.policy.required_approvals = required;
+    }
+    let history = counted_records(store.read_history()?, &config.policy);
+    let review_state = review_state(&history, &config.policy, requirement.reviewer.as_deref());
+
+    let mut changes = Vec::new();
+    for file in vcs::changed_file_blocks(&requirement.base)? {
+        for (old, new) in pair_changed_blocks(&file.old, &file.new) {
+            let (change, block) = match (old, new) {
+                (Some(_), Some(new)) => (BlockChangeKind::Modified, new),
+                (None, Some(new)) => (BlockChangeKind::Added, new),
+                (Some(old), None) => (BlockChangeKind::Removed, old),
+                (None, None) => continue,
+            };
+            let status = review_state
+                .get(&block.hash)
+                .map(|verdict| verdict.as_str())
+                .unwrap_or("unreviewed");
+            changes.push(BlockChange {
+                file: file.path.clone(),
+                change,
+                kind: block.kind.clone(),
+                line: block.start_line as u32 + 1,
+                fingerprint: block.hash.clone(),
+                previous_fingerprint: new.and(old).map(|old| old.hash.clone()),
+                status: status.to_string(),
+            });
+        }
+    }
+    Ok(changes)
+}
+
+/// Blocks that differ between `old` and `new`. Blocks present on both sides
+/// anchor the match; between anchors, blocks of the same kind pair up in
+/// order as modifications and the rest are additions or removals. Gaps are
+/// left out.
+fn pair_changed_blocks<'a>(
+    old: &'a [Block],
+    new: &'a [Block],
+) -> Vec<(Option<&'a Block>, Option<&'a Block>)> {
+    let old: Vec<&Block> = old
+        .iter()
+        .filter(|block| block.kind != BlockKind::Gap)
+        .collect();
+    let new: Vec<&Block> = new
+        .iter()
+        .filter(|block| block.kind != BlockKind::Gap)
+        .collect();
+    let (n, m) = (old.len(), new.len());
+
+    // Longest common subsequence of fingerprints.
+    let mut lengths = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lengths[i][j] = if old[i].hash == new[j].hash {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut pairs = Vec::new();
+    let (mut removed, mut added) = (Vec::new(), Vec::new());
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if old[i].hash == new[j].hash {
+            pair_gap(&mut removed, &mut added, &mut pairs);
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            removed.push(old[i]);
+            i += 1;
+        } else {
+            added.push(new[j]);
+            j += 1;
+        }
+    }
+    removed.extend(&old[i..]);
+    added.extend(&new[j..]);
+    pair_gap(&mut removed, &mut added, &mut pairs);
+    pairs
+}
+
+fn pair_gap<'a>(
+    removed: &mut Vec<&'a Block>,
+    added: &mut Vec<&'a Block>,
+    pairs: &mut Vec<(Option<&'a Block>, Option<&'a Block>)>,
+) {
+    for new in added.drain(..) {
+        match removed.iter().position(|old| old.kind == new.kind) {
+            Some(index) => pairs.push((Some(removed.remove(index)), Some(new))),
+            None => pairs.push((None, Some(new))),
+        }
+    }
+    pairs.extend(removed.drain(..).map(|old| (Some(old), None)));
+}
+
+fn parse_hunk_lines(lines: &[String]) -> (String, String, String, String) {
+    let mut diff_content = String::new();
+    let mut new_content = String::new();
+    let mut context = String::new();
+    let mut hash_body = String::new();
+
+    for line in lines {
+        if let Some(stripped) = line.strip_prefix(' ') {
+            context.push_str(line);
+            new_content.push_str(stripped);
+        } else if let Some(stripped) = line.strip_prefix('+') {
+            diff_content.push_str(line);
+            hash_body.push_str(line);
+            new_content.push_str(stripped);
+        } else if line.starts_with('-') {
+            diff_content.push_str(line);
+            hash_body.push_str(line);
+        }
+    }
+
+    (diff_content, new_content, context, hash_body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_simple_hunk() {
+        let lines = vec![
+            " context 1\n".to_string(),
+            "-old\n".to_string(),
+            "+new\n".to_string(),
+            " context 2\n".to_string(),
+        ];
+
+        let (diff, new, ctx, hash) = parse_hunk_lines(&lines);
+
+        assert_eq!(diff, "-old\n+new\n");
+        assert_eq!(new, "context 1\nnew\ncontext 2\n");
+        assert_eq!(ctx, " context 1\n context 2\n");
+        assert_eq!(hash, "-old\n+new\n");
+    }
+
+    #[test]
+    fn test_hunk_only_additions() {
+        let lines = vec!["+add1\n".to_string(), "+add2\n".to_string()];
+
+        let (diff, new, ctx, hash) = parse_hunk_lines(&lines);
+
+        assert_eq!(diff, "+add1\n+add2\n");
+        assert_eq!(new, "add1\nadd2\n");
+        assert_eq!(ctx, "");
+        assert_eq!(hash, "+add1\n+add2\n");
+    }
+
+    #[test]
+    fn test_hunk_mixed_context() {
+        // Ensures context is just concatenated, ignoring position relative to edits
+        let lines = vec![
+            " pre\n".to_string(),
+            "-del\n".to_string(),
+            " mid\n".to_string(),
+            "+add\n".to_string(),
+        ];
+
+        let (_, _, ctx, _) = parse_hunk_lines(&lines);
+        assert_eq!(ctx, " pre\n mid\n");
+    }
+
+    fn block(content: &str, kind: BlockKind, line: usize) -> Block {
+        Block::new(content.to_string(), kind, line, line + 1)
+    }
+
+    fn summarize(
+        pairs: &[(Option<&Block>, Option<&Block>)],
+    ) -> Vec<(Option<String>, Option<String>)> {
+        pairs
+            .iter()
+            .map(|(old, new)| {
+                (
+                    old.map(|block| block.content.clone()),
+                    new.map(|block| block.content.clone()),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn blocks_pair_up_between_unchanged_anchors() {
+        let old = vec![
+            block("use a;", BlockKind::Import, 0),
+            block("fn one() {}", BlockKind::Function, 1),
+            block("\n", BlockKind::Gap, 2),
+            block("fn two() {}", BlockKind::Function, 3),
+            block("struct Gone;", BlockKind::Struct, 4),
+        ];
+        let new = vec![
+            block("use a;", BlockKind::Import, 0),
+            block("fn one() { edited(); }", BlockKind::Function, 1),
+            block("fn two() {}", BlockKind::Function, 2),
+            block("fn three() {}", BlockKind::Function, 3),
+        ];
+        let some = |text: &str| Some(text.to_string());
+        assert_eq!(
+            summarize(&pair_changed_blocks(&old, &new)),
+            [
+                (some("fn one() {}"), some("fn one() { edited(); }")),
+                (None, some("fn three() {}")),
+                (some("struct Gone;"), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn new_files_are_all_additions() {
+        let new = vec![block("fn one() {}", BlockKind::Function, 0)];
+        assert_eq!(
+            summarize(&pair_changed_blocks(&[], &new)),
+            [(None, Some("fn one() {}".to_string()))]
+        );
+    }
+}
@@ -0,0 +1,120 @@
+//! The review engine behind the CLI, for tools that embed it instead of
+//! running `trueflow` and parsing its JSON.
+
+use crate::block::FileState;
+use crate::commands::mark::repo_ref_from_snapshot;
+use crate::commands::review::{ReviewOptions, ReviewSummary, collect_review_summary_in};
+use crate::config::{self, TrueflowConfig};
+use crate::identity::CurrentIdentity;
+use crate::record_schema::{record_schema, validate};
+use crate::scanner::{self, ScanSettings};
+use crate::store::{BlockState, Record, Verdict, open_store_in, workspace_root_in};
+use crate::vcs;
+use anyhow::{Result, bail};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Scans, review queues and the review ledger of one workspace.
+///
+/// The engine reads its root and config rather than the current directory,
+/// and never changes the directory or exits the process. What the CLI sets
+/// for the whole process at startup (`--base`, `--recurse-submodules`,
+/// `-c`) applies to every engine in it. Paths it takes and returns are
+/// relative to the root.
+pub struct Engine {
+    root: PathBuf,
+    config: TrueflowConfig,
+}
+
+impl Engine {
+    /// The workspace around the current directory.
+    pub fn discover() -> Result<Self> {
+        Self::open(std::env::current_dir()?)
+    }
+
+    /// The workspace around `dir`, with the config layered from its root.
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self> {
+        let root = workspace_root_in(dir.as_ref())?;
+        let config = config::load_in(&root)?;
+        Ok(Self { root, config })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The layered config (`trueflow.toml`, user config, environment).
+    pub fn config(&self) -> &TrueflowConfig {
+        &self.config
+    }
+
+    /// Every file of the workspace, split into blocks.
+    pub fn scan(&self) -> Result<Vec<FileState>> {
+        scanner::scan_directory_with(&self.root, &self.scan_settings(), |_| true)
+    }
+
+    /// The unreviewed blocks `options` selects, filtered by `[review]` as
+    /// `trueflow review` does.
+    pub fn review(&self, options: &ReviewOptions) -> Result<ReviewSummary> {
+        let filters = self
+            .config
+            .review
+            .resolve_filters(&options.only, &options.exclude);
+        collect_review_summary_in(&self.root, &self.config, options, &filters)
+    }
+
+    /// Every record in the ledger, oldest first.
+    pub fn history(&self) -> Result<Vec<Record>> {
+        open_store_in(&self.root, &self.config.store)?.read_history()
+    }
+
+    /// A verdict on `fingerprint` by the configured identity, ready for
+    /// [`Engine::mark`]. `path` is the file the block is in, relative to the
+    /// workspace root.
+    pub fn verdict(
+        &self,
+        fingerprint: &str,
+        verdict: Verdict,
+        path: Option<&str>,
+    ) -> Result<Record> {
+        let snapshot = vcs::snapshot_with(&self.root, &self.scan_settings());
+        Ok(Record {
+            path_hint: path.map(str::to_string),
+            ..Record::verdict(
+                fingerprint.to_string(),
+                "review".to_string(),
+                verdict,
+                self.identity()?.identity,
+                repo_ref_from_snapshot(&snapshot),
+                BlockState::from(vcs::block_state_for_path(&snapshot, path, fingerprint)),
+                SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64,
+            )
+        })
+    }
+
+    /// Appends `record` to the ledger, signed when `[identity] sign` is on.
+    /// Records that break the record schema are refused.
+    pub fn mark(&self, mut record: Record) -> Result<()> {
+        let problems = validate(&record_schema(), &serde_json::to_value(&record)?);
+        if !problems.is_empty() {
+            bail!("Invalid record: {}", problems.join("; "));
+        }
+        if let Some(confidence) = record.confidence
+            && !(0.0..=1.0).contains(&confidence)
+        {
+            bail!("Confidence must be between 0 and 1, got {}", confidence);
+        }
+        if let Some(signer) = self.identity()?.signer_in(&self.root)? {
+            record.attestations = Some(vec![signer.attest(&record)?]);
+        }
+        open_store_in(&self.root, &self.config.store)?.append(record)
+    }
+
+    fn scan_settings(&self) -> ScanSettings {
+        ScanSettings::from_config(&self.config)
+    }
+
+    fn identity(&self) -> Result<CurrentIdentity> {
+        CurrentIdentity::configured_in(&self.root, &self.config.identity)
+    }
+}
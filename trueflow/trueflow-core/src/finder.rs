@@ -0,0 +1,105 @@
+use anyhow::{Context, Result, bail};
+use std::path::Path;
+
+use crate::analysis::Language;
+use crate::block::{Block, BlockKind};
+use crate::{block_splitter, hashing, optimizer};
+
+#[allow(dead_code)] // Used by the library and tests, not the binary.
+pub fn fuzzy_find_block(path: &Path, fuzzy_ident: &str) -> Result<Block> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read file: {}", path.display()))?;
+    let file_type = crate::analysis::analyze_file(path);
+
+    let language = match file_type {
+        crate::analysis::FileType::Code(code_file) => code_file.language,
+        _ => Language::Unknown,
+    };
+
+    let mut blocks = match block_splitter::split(&content, language.clone()) {
+        Ok(blocks) if !blocks.is_empty() => optimizer::optimize(blocks),
+        Ok(_) => Vec::new(),
+        Err(err) => bail!("Failed to split file {}: {}", path.display(), err),
+    };
+    hashing::apply_normalization(&mut blocks, &language);
+
+    let mut matches = blocks
+        .iter()
+        .filter(|block| {
+            matches!(block.kind, BlockKind::Function | BlockKind::Method)
+                && block.content.contains(fuzzy_ident)
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+
+    if matches.is_empty() {
+        matches = blocks
+            .iter()
+            .filter(|block| block.content.contains(fuzzy_ident))
+            .cloned()
+            .collect();
+    }
+
+    if matches.is_empty() {
+        bail!("No block matched '{}' in {}", fuzzy_ident, path.display());
+    }
+    if matches.len() > 1 {
+        bail!(
+            "Multiple blocks matched '{}' in {}",
+            fuzzy_ident,
+            path.display()
+        );
+    }
+
+    Ok(matches.remove(0))
+}
+
+/// Scores `candidate` against a fuzzy `query`: its characters must appear in
+/// order (ignoring case). Runs of consecutive characters and matches at word
+/// starts score higher; `None` when the query does not match.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    let query: Vec<char> = query.chars().flat_map(char::to_lowercase).collect();
+    if query.is_empty() {
+        return Some(0);
+    }
+    let mut score = 0;
+    let mut matched = 0;
+    let mut previous: Option<char> = None;
+    let mut run = false;
+    for ch in candidate.chars() {
+        let Some(&wanted) = query.get(matched) else {
+            break;
+        };
+        if ch.to_lowercase().eq(std::iter::once(wanted)) {
+            score += 1;
+            if run {
+                score += 5;
+            }
+            if previous.is_none_or(|previous| !previous.is_alphanumeric()) {
+                score += 3;
+            }
+            matched += 1;
+            run = true;
+        } else {
+            run = false;
+        }
+        previous = Some(ch);
+    }
+    // Shorter candidates win ties.
+    (matched == query.len()).then(|| score * 100 - candidate.chars().count() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuzzy_scores_prefer_contiguous_matches() {
+        assert!(fuzzy_score("phh", "src/vcs.rs fn parse_hunk_header").is_some());
+        assert!(fuzzy_score("xyz", "src/vcs.rs").is_none());
+        let exact = fuzzy_score("parse_hunk", "src/vcs.rs fn parse_hunk_header");
+        let scattered = fuzzy_score("parse_hunk", "src/parser/hunks.rs fn apply_sun_key");
+        assert!(exact > scattered, "{exact:?} vs {scattered:?}");
+        assert!(fuzzy_score("VCS", "src/vcs.rs").is_some());
+    }
+}
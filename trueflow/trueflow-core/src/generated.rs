@@ -0,0 +1,178 @@
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use log::warn;
+use std::fs;
+use std::path::Path;
+
+pub const GENERATED_TAG: &str = "generated";
+
+/// Only the head of a file is inspected for generator markers.
+const MARKER_SCAN_LINES: usize = 10;
+
+const GENERATED_MARKERS: &[&str] = &["@generated", "DO NOT EDIT", "Code generated by"];
+
+const LOCKFILE_NAMES: &[&str] = &[
+    "Cargo.lock",
+    "package-lock.json",
+    "npm-shrinkwrap.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "bun.lockb",
+    "poetry.lock",
+    "Pipfile.lock",
+    "uv.lock",
+    "Gemfile.lock",
+    "composer.lock",
+    "go.sum",
+    "flake.lock",
+    "mix.lock",
+    "pubspec.lock",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GeneratedReason {
+    Lockfile,
+    Marker,
+    LinguistGenerated,
+}
+
+/// Detects generated files from lockfile names, header markers, and
+/// `linguist-generated` entries in the root `.gitattributes`.
+#[derive(Debug, Clone)]
+pub struct GeneratedDetector {
+    linguist_generated: GlobSet,
+    linguist_not_generated: GlobSet,
+}
+
+impl Default for GeneratedDetector {
+    fn default() -> Self {
+        Self {
+            linguist_generated: GlobSet::empty(),
+            linguist_not_generated: GlobSet::empty(),
+        }
+    }
+}
+
+impl GeneratedDetector {
+    pub fn from_root(root: &Path) -> Self {
+        match fs::read_to_string(root.join(".gitattributes")) {
+            Ok(content) => Self::from_gitattributes(&content),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn from_gitattributes(content: &str) -> Self {
+        let mut generated = GlobSetBuilder::new();
+        let mut not_generated = GlobSetBuilder::new();
+
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut parts = line.split_whitespace();
+            let Some(pattern) = parts.next() else {
+                continue;
+            };
+            for attribute in parts {
+                let target = match attribute {
+                    "linguist-generated" | "linguist-generated=true" => &mut generated,
+                    "-linguist-generated" | "linguist-generated=false" => &mut not_generated,
+                    _ => continue,
+                };
+                match attribute_glob(pattern) {
+                    Ok(glob) => {
+                        target.add(glob);
+                    }
+                    Err(err) => warn!("Ignoring .gitattributes pattern '{}': {}", pattern, err),
+                }
+            }
+        }
+
+        Self {
+            linguist_generated: generated.build().unwrap_or_else(|_| GlobSet::empty()),
+            linguist_not_generated: not_generated.build().unwrap_or_else(|_| GlobSet::empty()),
+        }
+    }
+
+    /// `path` is repo-relative with forward slashes.
+    pub fn detect(&self, path: &str, content: &str) -> Option<GeneratedReason> {
+        if self.linguist_not_generated.is_match(path) {
+            return None;
+        }
+        if self.linguist_generated.is_match(path) {
+            return Some(GeneratedReason::LinguistGenerated);
+        }
+
+        let file_name = path.rsplit('/').next().unwrap_or(path);
+        if LOCKFILE_NAMES.contains(&file_name) {
+            return Some(GeneratedReason::Lockfile);
+        }
+
+        let has_marker = content
+            .lines()
+            .take(MARKER_SCAN_LINES)
+            .any(|line| GENERATED_MARKERS.iter().any(|marker| line.contains(marker)));
+        has_marker.then_some(GeneratedReason::Marker)
+    }
+}
+
+/// gitattributes patterns without a slash match at any depth.
+fn attribute_glob(pattern: &str) -> Result<Glob, globset::Error> {
+    let pattern = pattern.trim_start_matches('/');
+    if pattern.contains('/') {
+        Glob::new(pattern)
+    } else {
+        Glob::new(&format!("**/{pattern}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_lockfiles_by_name() {
+        let detector = GeneratedDetector::default();
+        assert_eq!(
+            detector.detect("Cargo.lock", "# This file is maintained by cargo"),
+            Some(GeneratedReason::Lockfile)
+        );
+        assert_eq!(
+            detector.detect("web/package-lock.json", "{}"),
+            Some(GeneratedReason::Lockfile)
+        );
+        assert_eq!(detector.detect("src/lock.rs", "fn lock() {}"), None);
+    }
+
+    #[test]
+    fn detects_markers_in_file_header_only() {
+        let detector = GeneratedDetector::default();
+        assert_eq!(
+            detector.detect("src/pb.rs", "// @generated by protoc\nfn x() {}"),
+            Some(GeneratedReason::Marker)
+        );
+        assert_eq!(
+            detector.detect("gen.go", "// Code generated by stringer. DO NOT EDIT.\n"),
+            Some(GeneratedReason::Marker)
+        );
+
+        let late_marker = format!("{}// @generated\n", "fn x() {}\n".repeat(20));
+        assert_eq!(detector.detect("src/lib.rs", &late_marker), None);
+    }
+
+    #[test]
+    fn honors_linguist_generated_attributes() {
+        let detector = GeneratedDetector::from_gitattributes(
+            "# comment\n*.pb.go linguist-generated=true\n/proto/** linguist-generated\nCargo.lock -linguist-generated\n",
+        );
+        assert_eq!(
+            detector.detect("api/v1/service.pb.go", "package v1"),
+            Some(GeneratedReason::LinguistGenerated)
+        );
+        assert_eq!(
+            detector.detect("proto/types.rs", "pub struct T;"),
+            Some(GeneratedReason::LinguistGenerated)
+        );
+        assert_eq!(detector.detect("Cargo.lock", "[[package]]"), None);
+    }
+}
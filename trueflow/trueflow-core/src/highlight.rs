@@ -0,0 +1,297 @@
+use crate::analysis::Language;
+use log::warn;
+use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+use tree_sitter::{Parser, Query, QueryCursor, StreamingIterator};
+
+/// Highlighted texts kept before the cache starts over.
+const CACHE_LIMIT: usize = 64;
+
+/// What a highlight capture marks; each maps to a `[tui.theme]` color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HighlightKind {
+    Keyword,
+    String,
+    Number,
+    Comment,
+    Function,
+    Type,
+    Constant,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HighlightToken {
+    pub text: String,
+    /// `None` for plain code.
+    pub kind: Option<HighlightKind>,
+}
+
+/// Lines of tokens for `lines`, highlighted together so strings and
+/// comments spanning lines are marked on all of them. Languages without a
+/// grammar come back as plain text.
+pub fn highlight_lines<S: AsRef<str>>(
+    lines: &[S],
+    language: Option<&Language>,
+) -> Rc<Vec<Vec<HighlightToken>>> {
+    let text = lines
+        .iter()
+        .map(AsRef::as_ref)
+        .collect::<Vec<_>>()
+        .join("\n");
+    let mut hasher = DefaultHasher::new();
+    language.hash(&mut hasher);
+    text.hash(&mut hasher);
+    let key = hasher.finish();
+
+    if let Some(cached) = TOKENS.with(|cache| cache.borrow().get(&key).cloned()) {
+        return cached;
+    }
+    let kinds = language
+        .and_then(|language| QUERIES.with(|queries| highlight_kinds(queries, language, &text)));
+    let tokens = Rc::new(split_tokens(&text, kinds.as_deref()));
+    TOKENS.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        if cache.len() >= CACHE_LIMIT {
+            cache.clear();
+        }
+        cache.insert(key, tokens.clone());
+    });
+    tokens
+}
+
+thread_local! {
+    static QUERIES: RefCell<HashMap<Language, Option<Rc<Grammar>>>> = RefCell::new(HashMap::new());
+    static TOKENS: RefCell<HashMap<u64, Rc<Vec<Vec<HighlightToken>>>>> =
+        RefCell::new(HashMap::new());
+}
+
+struct Grammar {
+    language: tree_sitter::Language,
+    query: Query,
+    /// The kind of each of `query`'s capture names.
+    kinds: Vec<Option<HighlightKind>>,
+}
+
+/// The grammar and the highlights query it ships for `language`. TypeScript
+/// and C++ extend the JavaScript and C queries, which follow their own.
+fn grammar_source(language: &Language) -> Option<(tree_sitter::Language, String)> {
+    Some(match language {
+        Language::Rust => (
+            tree_sitter_rust::LANGUAGE.into(),
+            tree_sitter_rust::HIGHLIGHTS_QUERY.to_string(),
+        ),
+        Language::JavaScript => (
+            tree_sitter_javascript::LANGUAGE.into(),
+            tree_sitter_javascript::HIGHLIGHT_QUERY.to_string(),
+        ),
+        Language::TypeScript => (
+            tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+            format!(
+                "{}\n{}",
+                tree_sitter_typescript::HIGHLIGHTS_QUERY,
+                tree_sitter_javascript::HIGHLIGHT_QUERY
+            ),
+        ),
+        Language::Python => (
+            tree_sitter_python::LANGUAGE.into(),
+            tree_sitter_python::HIGHLIGHTS_QUERY.to_string(),
+        ),
+        Language::Shell => (
+            tree_sitter_bash::LANGUAGE.into(),
+            tree_sitter_bash::HIGHLIGHT_QUERY.to_string(),
+        ),
+        Language::C => (
+            tree_sitter_c::LANGUAGE.into(),
+            tree_sitter_c::HIGHLIGHT_QUERY.to_string(),
+        ),
+        Language::Cpp => (
+            tree_sitter_cpp::LANGUAGE.into(),
+            format!(
+                "{}\n{}",
+                tree_sitter_cpp::HIGHLIGHT_QUERY,
+                tree_sitter_c::HIGHLIGHT_QUERY
+            ),
+        ),
+        Language::Yaml => (
+            tree_sitter_yaml::LANGUAGE.into(),
+            tree_sitter_yaml::HIGHLIGHTS_QUERY.to_string(),
+        ),
+        Language::Markdown => (
+            tree_sitter_md::LANGUAGE.into(),
+            tree_sitter_md::HIGHLIGHT_QUERY_BLOCK.to_string(),
+        ),
+        _ => return None,
+    })
+}
+
+fn load_grammar(language: &Language) -> Option<Grammar> {
+    let (ts_language, source) = grammar_source(language)?;
+    let query = match Query::new(&ts_language, &source) {
+        Ok(query) => query,
+        Err(err) => {
+            warn!("Highlighting disabled for {language:?}: {err}");
+            return None;
+        }
+    };
+    let kinds = query
+        .capture_names()
+        .iter()
+        .map(|name| kind_for_capture(name))
+        .collect();
+    Some(Grammar {
+        language: ts_language,
+        query,
+        kinds,
+    })
+}
+
+/// Maps a capture name such as `keyword.control` or `string.special` to
+/// the kind it is colored as, going by its first component.
+fn kind_for_capture(name: &str) -> Option<HighlightKind> {
+    let mut parts = name.split('.');
+    Some(match (parts.next()?, parts.next()) {
+        ("keyword" | "conditional" | "repeat" | "include" | "exception", _) => {
+            HighlightKind::Keyword
+        }
+        ("text", Some("title")) | ("markup", Some("heading")) => HighlightKind::Keyword,
+        ("string" | "character" | "escape", _) | ("text", Some("literal")) => HighlightKind::String,
+        ("number" | "float" | "boolean", _) => HighlightKind::Number,
+        ("constant", Some("numeric")) | ("constant", Some("builtin")) => HighlightKind::Number,
+        ("comment", _) => HighlightKind::Comment,
+        ("function" | "method" | "constructor", _) => HighlightKind::Function,
+        ("type" | "tag", _) => HighlightKind::Type,
+        ("constant" | "label" | "attribute", _) | ("text", Some("uri" | "reference")) => {
+            HighlightKind::Constant
+        }
+        _ => return None,
+    })
+}
+
+/// The kind of every byte of `text`, or `None` without a usable grammar.
+/// Inner nodes win over the nodes enclosing them; for captures of the
+/// same node the first pattern with a kind wins, so catch-alls such as
+/// `(identifier) @variable` don't hide later, more specific patterns.
+fn highlight_kinds(
+    queries: &RefCell<HashMap<Language, Option<Rc<Grammar>>>>,
+    language: &Language,
+    text: &str,
+) -> Option<Vec<Option<HighlightKind>>> {
+    let grammar = queries
+        .borrow_mut()
+        .entry(language.clone())
+        .or_insert_with(|| load_grammar(language).map(Rc::new))
+        .clone()?;
+    let mut parser = Parser::new();
+    parser.set_language(&grammar.language).ok()?;
+    let tree = parser.parse(text, None)?;
+
+    let mut kinds = vec![None; text.len()];
+    let mut seen = HashSet::new();
+    let mut cursor = QueryCursor::new();
+    let mut captures = cursor.captures(&grammar.query, tree.root_node(), text.as_bytes());
+    while let Some((found, index)) = captures.next() {
+        let capture = found.captures[*index];
+        let Some(kind) = grammar.kinds[capture.index as usize] else {
+            continue;
+        };
+        if seen.insert(capture.node.id()) {
+            let range = capture.node.byte_range();
+            kinds[range.start.min(text.len())..range.end.min(text.len())].fill(Some(kind));
+        }
+    }
+    Some(kinds)
+}
+
+/// Splits `text` into lines of tokens, starting a token wherever the kind
+/// changes.
+fn split_tokens(text: &str, kinds: Option<&[Option<HighlightKind>]>) -> Vec<Vec<HighlightToken>> {
+    let mut offset = 0;
+    text.split('\n')
+        .map(|line| {
+            let start = offset;
+            offset += line.len() + 1;
+            let Some(kinds) = kinds else {
+                return vec![HighlightToken {
+                    text: line.to_string(),
+                    kind: None,
+                }];
+            };
+            let mut tokens: Vec<HighlightToken> = Vec::new();
+            for (index, ch) in line.char_indices() {
+                let kind = kinds[start + index];
+                match tokens.last_mut() {
+                    Some(token) if token.kind == kind => token.text.push(ch),
+                    _ => tokens.push(HighlightToken {
+                        text: ch.to_string(),
+                        kind,
+                    }),
+                }
+            }
+            tokens
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds_of(lines: &[&str], language: Language) -> Vec<(String, Option<HighlightKind>)> {
+        highlight_lines(lines, Some(&language))
+            .iter()
+            .flatten()
+            .filter(|token| !token.text.trim().is_empty())
+            .map(|token| (token.text.trim().to_string(), token.kind))
+            .collect()
+    }
+
+    #[test]
+    fn every_grammar_query_compiles() {
+        for language in [
+            Language::Rust,
+            Language::JavaScript,
+            Language::TypeScript,
+            Language::Python,
+            Language::Shell,
+            Language::C,
+            Language::Cpp,
+            Language::Yaml,
+            Language::Markdown,
+        ] {
+            assert!(load_grammar(&language).is_some(), "{language:?}");
+        }
+    }
+
+    #[test]
+    fn highlights_python_keywords_strings_and_comments() {
+        let tokens = kinds_of(
+            &["def greet(name):", "    # say hi", "    return \"hi\""],
+            Language::Python,
+        );
+        assert!(tokens.contains(&("def".to_string(), Some(HighlightKind::Keyword))));
+        assert!(tokens.contains(&("greet".to_string(), Some(HighlightKind::Function))));
+        assert!(tokens.contains(&("# say hi".to_string(), Some(HighlightKind::Comment))));
+        assert!(tokens.contains(&("\"hi\"".to_string(), Some(HighlightKind::String))));
+    }
+
+    #[test]
+    fn multi_line_comments_mark_every_line() {
+        let lines = highlight_lines(&["/* one", "two */", "int x = 1;"], Some(&Language::C));
+        assert_eq!(lines[1][0].kind, Some(HighlightKind::Comment));
+        assert!(
+            lines[2]
+                .iter()
+                .any(|token| token.text == "1" && token.kind == Some(HighlightKind::Number))
+        );
+    }
+
+    #[test]
+    fn languages_without_grammars_stay_plain() {
+        let lines = highlight_lines(&["[section]", "key = 1"], Some(&Language::Toml));
+        assert_eq!(lines.len(), 2);
+        assert!(lines.iter().flatten().all(|token| token.kind.is_none()));
+    }
+}
@@ -0,0 +1,113 @@
+use crate::config::StoreConfig;
+use crate::curl;
+use crate::store::{Record, ReviewStore};
+use anyhow::{Context, Result, bail};
+
+/// Variable the bearer token is read from unless `[store] token_env` names another.
+const DEFAULT_TOKEN_ENV: &str = "TRUEFLOW_STORE_TOKEN";
+
+/// The ledger kept by a team server. `GET {url}/records` returns every record
+/// oldest first and `POST {url}/records` appends a JSON array in one write.
+/// `trueflow serve` speaks this too.
+pub struct HttpStore {
+    url: String,
+    token: String,
+}
+
+impl HttpStore {
+    /// Reads the server URL from `[store] url` and the token from the
+    /// environment.
+    pub fn open(config: &StoreConfig) -> Result<Self> {
+        Self::with_token(config, server_token(config))
+    }
+
+    fn with_token(config: &StoreConfig, token: Option<String>) -> Result<Self> {
+        let url = config
+            .url
+            .as_deref()
+            .context("Set [store] url to the review server, e.g. \"https://reviews.example.com\"")?
+            .trim_end_matches('/')
+            .to_string();
+        let token_env = config.token_env.as_deref().unwrap_or(DEFAULT_TOKEN_ENV);
+        let Some(token) = token else {
+            bail!("Set {token_env} to the review server's token");
+        };
+        Ok(Self { url, token })
+    }
+
+    fn send(&self, method: &str, records: Option<&[Record]>) -> Result<Vec<u8>> {
+        let mut headers = vec![
+            "Accept: application/json".to_string(),
+            format!("Authorization: Bearer {}", self.token),
+        ];
+        let body = records.map(serde_json::to_string).transpose()?;
+        if body.is_some() {
+            headers.push("Content-Type: application/json".to_string());
+        }
+        curl::request(
+            method,
+            &format!("{}/records", self.url),
+            &headers,
+            body.as_deref(),
+        )
+    }
+}
+
+impl ReviewStore for HttpStore {
+    fn read_history(&self) -> Result<Vec<Record>> {
+        let body = self.send("GET", None)?;
+        serde_json::from_slice(&body)
+            .with_context(|| format!("{}/records did not return review records", self.url))
+    }
+
+    fn append(&self, record: Record) -> Result<()> {
+        self.append_all(vec![record])
+    }
+
+    fn append_all(&self, records: Vec<Record>) -> Result<()> {
+        if records.is_empty() {
+            return Ok(());
+        }
+        self.send("POST", Some(&records))?;
+        Ok(())
+    }
+
+    /// Others append to the server concurrently, so its ledger is never
+    /// replaced from here.
+    fn rewrite(&self, _records: &[Record]) -> Result<()> {
+        bail!("The review server's ledger can only be appended to; rewrite it on the server")
+    }
+}
+
+/// The bearer token from `[store] token_env`, or `TRUEFLOW_STORE_TOKEN`, if
+/// set. Clients must send it and `trueflow serve` requires it.
+pub fn server_token(config: &StoreConfig) -> Option<String> {
+    let token_env = config.token_env.as_deref().unwrap_or(DEFAULT_TOKEN_ENV);
+    std::env::var(token_env)
+        .ok()
+        .filter(|token| !token.trim().is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn needs_a_url_and_a_named_token() {
+        let err = HttpStore::open(&StoreConfig::default()).err();
+        assert!(err.is_some_and(|err| err.to_string().contains("[store] url")));
+
+        let config = StoreConfig {
+            url: Some("http://localhost:8080/".to_string()),
+            token_env: Some("TRUEFLOW_TEST_UNSET_TOKEN".to_string()),
+            ..StoreConfig::default()
+        };
+        let err = HttpStore::open(&config).err();
+        assert!(err.is_some_and(|err| err.to_string().contains("TRUEFLOW_TEST_UNSET_TOKEN")));
+
+        let store =
+            HttpStore::with_token(&config, Some("s3cret".to_string())).expect("store with a token");
+        assert_eq!(store.url, "http://localhost:8080");
+        assert!(store.rewrite(&[]).is_err());
+    }
+}
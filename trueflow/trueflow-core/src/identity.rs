@@ -0,0 +1,117 @@
+use crate::config::{self, IdentityConfig, IdentityProvider};
+use crate::context::TrueflowContext;
+use crate::oidc;
+use crate::signing::{Signer, SigningFormat};
+use crate::store::Identity;
+use crate::vcs;
+use anyhow::{Context, Result};
+use log::warn;
+use std::path::Path;
+
+const UNKNOWN_EMAIL: &str = "unknown@localhost";
+
+/// The identity records are written as, and whether they are signed.
+#[derive(Debug, Clone)]
+pub struct CurrentIdentity {
+    pub identity: Identity,
+    /// Sign records with git's signing key: `[identity] sign = true` or `mark --sign`.
+    pub sign: bool,
+    /// `[identity] format`; git's `gpg.format` when unset.
+    pub signing_format: Option<SigningFormat>,
+}
+
+impl CurrentIdentity {
+    /// `--agent` (or `TRUEFLOW_AGENT`) selects an agent identity; otherwise
+    /// `[identity] provider` decides. The default uses git config for
+    /// identity if available, but falls back gracefully.
+    pub fn resolve(context: &TrueflowContext) -> Result<Self> {
+        Self::resolve_in(context, Path::new("."), &identity_settings())
+    }
+
+    /// [`CurrentIdentity::resolve`] with `settings` in the workspace at `root`.
+    pub fn resolve_in(
+        context: &TrueflowContext,
+        root: &Path,
+        settings: &IdentityConfig,
+    ) -> Result<Self> {
+        let identity = match &context.agent {
+            Some(identity) => identity.clone(),
+            None => configured_identity(root, settings)?,
+        };
+        Ok(Self {
+            identity,
+            sign: settings.sign,
+            signing_format: settings.format,
+        })
+    }
+
+    /// The identity `[identity]` configures, for callers without `--agent`.
+    pub fn configured() -> Result<Self> {
+        Self::configured_in(Path::new("."), &identity_settings())
+    }
+
+    /// The identity `settings` configure in the workspace at `root`.
+    pub fn configured_in(root: &Path, settings: &IdentityConfig) -> Result<Self> {
+        Ok(Self {
+            identity: configured_identity(root, settings)?,
+            sign: settings.sign,
+            signing_format: settings.format,
+        })
+    }
+
+    /// The key to sign with, or `None` when signing is off.
+    pub fn signer(&self) -> Result<Option<Signer>> {
+        self.signer_in(Path::new("."))
+    }
+
+    /// [`CurrentIdentity::signer`] with git config from the workspace at `root`.
+    pub fn signer_in(&self, root: &Path) -> Result<Option<Signer>> {
+        if !self.sign {
+            return Ok(None);
+        }
+        let config = vcs::git_config_in(root).context("Signing needs a git repository")?;
+        Signer::from_git_config(&config, self.signing_format).map(Some)
+    }
+}
+
+fn identity_settings() -> IdentityConfig {
+    match config::load() {
+        Ok(config) => config.identity,
+        Err(err) => {
+            warn!("Ignoring [identity] settings: {:#}", err);
+            Default::default()
+        }
+    }
+}
+
+fn configured_identity(root: &Path, settings: &IdentityConfig) -> Result<Identity> {
+    match settings.provider {
+        IdentityProvider::Git => {
+            let email = match vcs::git_config_in(root) {
+                Ok(config) => config.email,
+                Err(_) => UNKNOWN_EMAIL.to_string(),
+            };
+            Ok(Identity::Email { email })
+        }
+        IdentityProvider::Oidc => {
+            let oidc = settings
+                .oidc
+                .as_ref()
+                .context("[identity] provider = \"oidc\" needs [identity.oidc] settings")?;
+            oidc::cached_identity(&oidc.issuer)?
+                .with_context(|| format!("Not logged in to {}; run `trueflow login`", oidc.issuer))
+        }
+        IdentityProvider::Machine => {
+            let name = settings
+                .machine
+                .as_deref()
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .context("[identity] provider = \"machine\" needs a `machine` name")?;
+            Ok(Identity::Machine {
+                name: name.to_string(),
+                version: None,
+            })
+        }
+    }
+}
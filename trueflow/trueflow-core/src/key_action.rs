@@ -0,0 +1,182 @@
+use serde::{Deserialize, Serialize};
+
+/// Something a TUI key does; the names are the keys of `[tui.keys]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Approve,
+    Reject,
+    Comment,
+    /// Ask a question about the block, with a note.
+    Question,
+    Undo,
+    Diff,
+    /// Fold the block's sub-blocks down to their first lines.
+    Fold,
+    /// Split the block into sub-blocks that take verdicts of their own.
+    Split,
+    /// Start (or drop) a selection of consecutive blocks in review order.
+    Select,
+    /// Move the block to the end of the review order without a verdict.
+    Defer,
+    /// Open the block in `$EDITOR`.
+    Edit,
+    Search,
+    /// Next search match; falls through to `next` when nothing was searched.
+    SearchNext,
+    SearchPrev,
+    ClearSearch,
+    Ascend,
+    Descend,
+    Next,
+    Prev,
+    /// Start reviewing from the root screen.
+    Start,
+    PageDown,
+    PageUp,
+    HalfPageDown,
+    HalfPageUp,
+    Top,
+    Bottom,
+    Root,
+    Help,
+    Quit,
+}
+
+impl Action {
+    /// Every action, in the order a key bound to several of them tries them.
+    pub const ALL: [Action; 29] = [
+        Action::Approve,
+        Action::Reject,
+        Action::Comment,
+        Action::Question,
+        Action::Undo,
+        Action::Diff,
+        Action::Fold,
+        Action::Split,
+        Action::Select,
+        Action::Defer,
+        Action::Edit,
+        Action::Search,
+        Action::SearchNext,
+        Action::SearchPrev,
+        Action::ClearSearch,
+        Action::Ascend,
+        Action::Descend,
+        Action::Next,
+        Action::Prev,
+        Action::Start,
+        Action::PageDown,
+        Action::PageUp,
+        Action::HalfPageDown,
+        Action::HalfPageUp,
+        Action::Top,
+        Action::Bottom,
+        Action::Root,
+        Action::Help,
+        Action::Quit,
+    ];
+
+    /// The name `[tui.keys]` uses.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Approve => "approve",
+            Action::Reject => "reject",
+            Action::Comment => "comment",
+            Action::Question => "question",
+            Action::Undo => "undo",
+            Action::Diff => "diff",
+            Action::Fold => "fold",
+            Action::Split => "split",
+            Action::Select => "select",
+            Action::Defer => "defer",
+            Action::Edit => "edit",
+            Action::Search => "search",
+            Action::SearchNext => "search_next",
+            Action::SearchPrev => "search_prev",
+            Action::ClearSearch => "clear_search",
+            Action::Ascend => "ascend",
+            Action::Descend => "descend",
+            Action::Next => "next",
+            Action::Prev => "prev",
+            Action::Start => "start",
+            Action::PageDown => "page_down",
+            Action::PageUp => "page_up",
+            Action::HalfPageDown => "half_page_down",
+            Action::HalfPageUp => "half_page_up",
+            Action::Top => "top",
+            Action::Bottom => "bottom",
+            Action::Root => "root",
+            Action::Help => "help",
+            Action::Quit => "quit",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Action::Approve => "approve the block",
+            Action::Reject => "reject the block",
+            Action::Comment => "comment on the block",
+            Action::Question => "ask a question about the block",
+            Action::Undo => "undo the last verdict",
+            Action::Diff => "toggle the side-by-side diff",
+            Action::Fold => "fold or unfold the block's sub-blocks",
+            Action::Split => "split the block into sub-blocks to review one by one",
+            Action::Select => "select consecutive blocks (next/prev extend it) to judge at once",
+            Action::Defer => "skip the block for now; it comes back at the end",
+            Action::Edit => "open the block in $EDITOR; the review reloads when it exits",
+            Action::Search => "search files and blocks",
+            Action::SearchNext => "next search match",
+            Action::SearchPrev => "previous search match",
+            Action::ClearSearch => "clear the search",
+            Action::Ascend => "go to the parent",
+            Action::Descend => "go to the first child",
+            Action::Next => "next sibling",
+            Action::Prev => "previous sibling",
+            Action::Start => "start reviewing (from the root)",
+            Action::PageDown => "scroll down a page",
+            Action::PageUp => "scroll up a page",
+            Action::HalfPageDown => "scroll down half a page",
+            Action::HalfPageUp => "scroll up half a page",
+            Action::Top => "scroll to the top",
+            Action::Bottom => "scroll to the bottom",
+            Action::Root => "jump to the root",
+            Action::Help => "show this help",
+            Action::Quit => "quit (progress is saved)",
+        }
+    }
+
+    pub fn default_keys(&self) -> &'static [&'static str] {
+        match self {
+            Action::Approve => &["a"],
+            Action::Reject => &["x"],
+            Action::Comment => &["c"],
+            Action::Question => &["Q"],
+            Action::Undo => &["u"],
+            Action::Diff => &["d"],
+            Action::Fold => &["z"],
+            Action::Split => &["s"],
+            Action::Select => &["v"],
+            Action::Defer => &["S"],
+            Action::Edit => &["e"],
+            Action::Search => &["/"],
+            Action::SearchNext => &["n"],
+            Action::SearchPrev => &["N"],
+            Action::ClearSearch => &["esc"],
+            Action::Ascend => &["i", "up"],
+            Action::Descend => &["k", "down"],
+            Action::Next => &["l", "right", "n"],
+            Action::Prev => &["j", "left", "b"],
+            Action::Start => &["enter", "space"],
+            Action::PageDown => &["pagedown", "space"],
+            Action::PageUp => &["pageup"],
+            Action::HalfPageDown => &["ctrl-d"],
+            Action::HalfPageUp => &["ctrl-u"],
+            Action::Top => &["home"],
+            Action::Bottom => &["end"],
+            Action::Root => &["g"],
+            Action::Help => &["?"],
+            Action::Quit => &["q"],
+        }
+    }
+}
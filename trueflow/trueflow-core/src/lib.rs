@@ -0,0 +1,92 @@
+//! The trueflow review engine: semantic blocks, review queues and the
+//! review ledger. The `trueflow` CLI is a thin client of this crate; other
+//! tools can drive the same engine through [`Engine`]:
+//!
+//! ```no_run
+//! use trueflow_core::{Engine, ReviewOptions, Verdict};
+//!
+//! # fn main() -> anyhow::Result<()> {
+//! let engine = Engine::open("path/to/repo")?;
+//! let summary = engine.review(&ReviewOptions {
+//!     all: true,
+//!     ..ReviewOptions::default()
+//! })?;
+//! for file in &summary.files {
+//!     for block in &file.blocks {
+//!         if block.content.len() < 80 {
+//!             let record = engine.verdict(&block.hash, Verdict::Approved, Some(&file.path))?;
+//!             engine.mark(record)?;
+//!         }
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The other modules are the CLI's building blocks; they are public but
+//! change with the CLI, and most work in the current directory. The `clap`
+//! feature derives `clap::ValueEnum` on the enums the CLI takes as arguments.
+
+pub mod analysis;
+pub mod anchor;
+pub mod assignment;
+pub mod auto_approve;
+pub mod blame;
+pub mod block;
+pub mod block_context;
+pub mod block_splitter;
+pub mod claims;
+pub mod codeowners;
+pub mod color;
+pub mod commands;
+pub mod complexity;
+pub mod config;
+pub mod config_layers;
+pub mod context;
+pub mod curl;
+pub mod diff_logic;
+pub mod engine;
+pub mod finder;
+pub mod generated;
+pub mod hashing;
+pub mod highlight;
+pub mod http_store;
+pub mod identity;
+pub mod key_action;
+pub mod lineage;
+pub mod logging;
+pub mod metrics;
+pub mod oidc;
+pub mod optimizer;
+pub mod output;
+pub mod owner_review;
+pub mod pager;
+pub mod path_tags;
+pub mod policy;
+pub mod prereview;
+pub mod progress;
+pub mod publish;
+pub mod question;
+pub mod record_schema;
+pub mod resolution;
+pub mod risk;
+pub mod s3_store;
+pub mod scanner;
+pub mod schemas;
+pub mod session;
+pub mod side_by_side;
+pub mod signing;
+pub mod sqlite_store;
+pub mod store;
+pub mod sub_splitter;
+pub mod text_split;
+pub mod thread;
+pub mod time_window;
+pub mod tree;
+pub mod vcs;
+pub mod vcs_provider;
+
+pub use block::{Block, BlockKind, FileState};
+pub use commands::review::{ReviewOptions, ReviewSummary, ReviewTarget, UnreviewedFile};
+pub use engine::Engine;
+pub use store::{Identity, Record, Verdict};
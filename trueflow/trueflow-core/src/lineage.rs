@@ -0,0 +1,263 @@
+use crate::analysis::Language;
+use crate::block::Block;
+use crate::hashing::{hash_str, tokenize};
+use crate::store::{Identity, LineageLink, Record, RecordKind, RepoRef};
+use crate::vcs_provider::VcsProvider;
+use std::collections::{HashMap, HashSet};
+
+pub const LINEAGE_AGENT: &str = "trueflow-lineage";
+/// Tag on unreviewed blocks edited from an approved block.
+pub const RECONFIRM_TAG: &str = "reconfirm";
+/// Longest chain of edits followed back to an approval.
+const MAX_LINEAGE_DEPTH: usize = 32;
+
+/// Keywords that introduce a local binding. The name that follows is
+/// renumbered, so renaming a local does not change the normalized form.
+const BINDERS: &[&str] = &["let", "var", "const", "auto", "for", "local"];
+/// Modifiers allowed between a binder and the bound name.
+const BINDING_MODIFIERS: &[&str] = &["mut", "ref"];
+
+pub fn lineage_identity() -> Identity {
+    Identity::Agent {
+        name: LINEAGE_AGENT.to_string(),
+        model: None,
+        version: Some(env!("CARGO_PKG_VERSION").to_string()),
+    }
+}
+
+/// Content with comments and whitespace dropped and, for code, locals
+/// (names bound by `let`, `var`, `for`, ...) numbered by first use. Blocks
+/// that differ only by formatting, comments, or a local rename normalize to
+/// the same text.
+pub fn normalize(content: &str, language: &Language) -> String {
+    let tokens = tokenize(content, language);
+    let locals: HashSet<&str> = if renames_locals(language) {
+        bound_names(&tokens)
+    } else {
+        HashSet::new()
+    };
+    let mut numbers: HashMap<&str, usize> = HashMap::new();
+    tokens
+        .iter()
+        .map(|token| {
+            if locals.contains(token) {
+                let next = numbers.len();
+                format!("${}", numbers.entry(token).or_insert(next))
+            } else {
+                token.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn bound_names<'a>(tokens: &[&'a str]) -> HashSet<&'a str> {
+    let mut names = HashSet::new();
+    for (idx, token) in tokens.iter().enumerate() {
+        if !BINDERS.contains(token) {
+            continue;
+        }
+        let mut next = idx + 1;
+        while tokens
+            .get(next)
+            .is_some_and(|token| BINDING_MODIFIERS.contains(token))
+        {
+            next += 1;
+        }
+        if let Some(name) = tokens.get(next)
+            && name.starts_with(|ch: char| ch.is_alphabetic() || ch == '_')
+        {
+            names.insert(*name);
+        }
+    }
+    names
+}
+
+pub fn normalized_hash(content: &str, language: &Language) -> String {
+    hash_str(&normalize(content, language))
+}
+
+fn renames_locals(language: &Language) -> bool {
+    matches!(
+        language,
+        Language::Rust
+            | Language::JavaScript
+            | Language::TypeScript
+            | Language::Python
+            | Language::Shell
+            | Language::C
+            | Language::Cpp
+            | Language::Elisp
+    )
+}
+
+/// Lineage links from the ledger: each fingerprint mapped to the one it was
+/// edited from, plus links found during this run. Found links stay in
+/// memory until [`LineageIndex::take_new_records`]; only `mark` records them.
+#[derive(Debug, Default)]
+pub struct LineageIndex {
+    parents: HashMap<String, String>,
+    new_links: Vec<NewLink>,
+}
+
+#[derive(Debug)]
+struct NewLink {
+    to: String,
+    from: String,
+    normalized: String,
+    path: String,
+}
+
+impl LineageIndex {
+    pub fn from_records(records: &[Record]) -> Self {
+        let mut parents = HashMap::new();
+        for record in records
+            .iter()
+            .filter(|record| record.kind == RecordKind::Lineage)
+        {
+            if let Some(link) = &record.lineage
+                && link.from != record.fingerprint
+            {
+                parents.insert(record.fingerprint.clone(), link.from.clone());
+            }
+        }
+        Self {
+            parents,
+            new_links: Vec::new(),
+        }
+    }
+
+    /// The closest approved block `fingerprint` was edited from.
+    pub fn approved_ancestor(&self, fingerprint: &str, approved: &HashSet<String>) -> Option<&str> {
+        let mut current = self.parents.get(fingerprint)?;
+        for _ in 0..MAX_LINEAGE_DEPTH {
+            if approved.contains(current) {
+                return Some(current);
+            }
+            current = self.parents.get(current)?;
+        }
+        None
+    }
+
+    /// Tags `blocks` (unreviewed blocks of `path`) whose content normalizes
+    /// like an approved block, either through a recorded link or by matching
+    /// the approved blocks of the committed version of the file.
+    pub fn tag_reconfirmations(
+        &mut self,
+        provider: Option<&dyn VcsProvider>,
+        path: &str,
+        language: &Language,
+        blocks: &mut [Block],
+        approved: &HashSet<String>,
+    ) {
+        let mut head_candidates: Option<HashMap<String, String>> = None;
+        for block in blocks.iter_mut() {
+            if self.approved_ancestor(&block.hash, approved).is_some() {
+                block.tags.push(RECONFIRM_TAG.to_string());
+                continue;
+            }
+            let Some(provider) = provider else {
+                continue;
+            };
+            let candidates = head_candidates
+                .get_or_insert_with(|| approved_head_blocks(provider, path, language, approved));
+            if candidates.is_empty() {
+                continue;
+            }
+            let normalized = normalized_hash(&block.content, language);
+            if let Some(from) = candidates.get(&normalized)
+                && *from != block.hash
+            {
+                self.parents.insert(block.hash.clone(), from.clone());
+                self.new_links.push(NewLink {
+                    to: block.hash.clone(),
+                    from: from.clone(),
+                    normalized,
+                    path: path.to_string(),
+                });
+                block.tags.push(RECONFIRM_TAG.to_string());
+            }
+        }
+    }
+
+    /// Ledger records for links found since the index was loaded.
+    pub fn take_new_records(&mut self, repo_ref: &RepoRef, now: i64) -> Vec<Record> {
+        self.new_links
+            .drain(..)
+            .map(|link| Record {
+                path_hint: Some(link.path),
+                lineage: Some(LineageLink {
+                    from: link.from,
+                    normalized: link.normalized,
+                }),
+                ..Record::non_verdict(
+                    RecordKind::Lineage,
+                    link.to,
+                    lineage_identity(),
+                    repo_ref.clone(),
+                    now,
+                )
+            })
+            .collect()
+    }
+}
+
+/// Normalized hash -> fingerprint for the approved blocks of `path` at HEAD.
+fn approved_head_blocks(
+    provider: &dyn VcsProvider,
+    path: &str,
+    language: &Language,
+    approved: &HashSet<String>,
+) -> HashMap<String, String> {
+    let Ok(blocks) = provider.head_blocks_for_path(path) else {
+        return HashMap::new();
+    };
+    blocks
+        .into_iter()
+        .filter(|block| approved.contains(&block.hash))
+        .map(|block| (normalized_hash(&block.content, language), block.hash))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formatting_comments_and_renames_normalize_alike() {
+        let original =
+            "fn total(items: &[u32]) -> u32 {\n    let s = items.iter().sum();\n    s\n}\n";
+        let edited = "fn total(items: &[u32]) -> u32 {\n    // Add them up.\n    let acc = items.iter().sum();   acc\n}\n";
+        assert_eq!(
+            normalize(original, &Language::Rust),
+            normalize(edited, &Language::Rust)
+        );
+
+        let changed =
+            "fn total(items: &[u32]) -> u32 {\n    let s = items.iter().max();\n    s\n}\n";
+        assert_ne!(
+            normalize(original, &Language::Rust),
+            normalize(changed, &Language::Rust)
+        );
+    }
+
+    #[test]
+    fn string_literals_are_not_comments() {
+        let a = "x = \"# not a comment\"\n";
+        let b = "x = \"# changed\"\n";
+        assert_ne!(
+            normalize(a, &Language::Python),
+            normalize(b, &Language::Python)
+        );
+    }
+
+    #[test]
+    fn follows_links_back_to_an_approval() {
+        let mut index = LineageIndex::default();
+        index.parents.insert("c".to_string(), "b".to_string());
+        index.parents.insert("b".to_string(), "a".to_string());
+        let approved = HashSet::from(["a".to_string()]);
+        assert_eq!(index.approved_ancestor("c", &approved), Some("a"));
+        assert_eq!(index.approved_ancestor("a", &approved), None);
+    }
+}
@@ -0,0 +1,253 @@
+use anyhow::Result;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::store::FileStore;
+
+const STRUCTURED_LOG_FILE: &str = "trueflow.jsonl";
+
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum), clap(rename_all = "lower"))]
+pub enum LoggingMode {
+    File,
+    Stderr,
+}
+
+/// A JSON-lines log written alongside the terminal log, rotated by size.
+#[derive(Debug, Clone)]
+pub struct StructuredLog {
+    pub path: PathBuf,
+    pub max_bytes: u64,
+    pub max_files: usize,
+}
+
+impl StructuredLog {
+    /// `.trueflow/logs/trueflow.jsonl` in the current store.
+    pub fn default_path() -> Result<PathBuf> {
+        Ok(log_dir()?.join(STRUCTURED_LOG_FILE))
+    }
+}
+
+pub fn init_logging(
+    mode: LoggingMode,
+    debug: bool,
+    structured: Option<StructuredLog>,
+) -> Result<()> {
+    let level = if debug {
+        log::LevelFilter::Debug
+    } else {
+        log::LevelFilter::Warn
+    };
+    let mut terminal = fern::Dispatch::new()
+        .level(level)
+        .format(|out, message, record| {
+            let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f");
+            let thread_id = format!("{:?}", std::thread::current().id());
+            let module = record.module_path().unwrap_or(record.target());
+            let line = record
+                .line()
+                .map(|line| line.to_string())
+                .unwrap_or_else(|| "?".to_string());
+            out.finish(format_args!(
+                "[{}] [{}] [{}] [{}:{}] [{}]",
+                record.level(),
+                timestamp,
+                thread_id,
+                module,
+                line,
+                message
+            ))
+        });
+
+    let mut log_warnings = Vec::new();
+    match mode {
+        LoggingMode::Stderr => {
+            terminal = terminal.chain(std::io::stderr());
+        }
+        LoggingMode::File => match create_log_file() {
+            Ok(log_file) => {
+                terminal = terminal.chain(log_file);
+            }
+            Err(err) => {
+                terminal = terminal.chain(std::io::stderr());
+                log_warnings.push(format!("Failed to open log file: {}", err));
+            }
+        },
+    }
+
+    let mut dispatch = fern::Dispatch::new().chain(terminal);
+    if let Some(structured) = structured {
+        match RotatingFile::open(&structured) {
+            Ok(file) => {
+                let structured_level = if debug {
+                    log::LevelFilter::Debug
+                } else {
+                    log::LevelFilter::Info
+                };
+                let writer: Box<dyn Write + Send> = Box::new(file);
+                dispatch = dispatch.chain(
+                    fern::Dispatch::new()
+                        .level(structured_level)
+                        .format(|out, message, record| {
+                            let entry = serde_json::json!({
+                                "timestamp": chrono::Local::now().to_rfc3339(),
+                                "level": record.level().as_str(),
+                                "target": record.target(),
+                                "module": record.module_path(),
+                                "line": record.line(),
+                                "thread": format!("{:?}", std::thread::current().id()),
+                                "message": message.to_string(),
+                            });
+                            out.finish(format_args!("{}", entry))
+                        })
+                        .chain(writer),
+                );
+            }
+            Err(err) => log_warnings.push(format!(
+                "Failed to open structured log {}: {}",
+                structured.path.display(),
+                err
+            )),
+        }
+    }
+
+    dispatch.apply()?;
+    for warning in log_warnings {
+        log::warn!("{}", warning);
+    }
+    Ok(())
+}
+
+fn log_dir() -> Result<PathBuf> {
+    Ok(FileStore::new()?.trueflow_dir().join("logs"))
+}
+
+fn create_log_file() -> Result<std::fs::File> {
+    let log_dir = log_dir()?;
+    fs::create_dir_all(&log_dir)?;
+
+    let date = chrono::Local::now().format("%Y-%m-%d").to_string();
+    let log_path: PathBuf = log_dir.join(format!("{}.log", date));
+
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(log_path)?;
+
+    Ok(file)
+}
+
+/// Appends to `path`, moving it to `path.1` (and older files up to
+/// `path.<max_files>`) once it would grow past `max_bytes`. Rotation only
+/// happens between lines so every file stays valid JSON lines.
+struct RotatingFile {
+    path: PathBuf,
+    max_bytes: u64,
+    max_files: usize,
+    file: File,
+    size: u64,
+    at_line_start: bool,
+}
+
+impl RotatingFile {
+    fn open(config: &StructuredLog) -> io::Result<Self> {
+        if let Some(parent) = config.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = append_to(&config.path)?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            path: config.path.clone(),
+            max_bytes: config.max_bytes,
+            max_files: config.max_files,
+            file,
+            size,
+            at_line_start: true,
+        })
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        if self.max_files == 0 {
+            fs::remove_file(&self.path)?;
+        } else {
+            for index in (1..self.max_files).rev() {
+                let from = rotated_path(&self.path, index);
+                if from.exists() {
+                    fs::rename(&from, rotated_path(&self.path, index + 1))?;
+                }
+            }
+            fs::rename(&self.path, rotated_path(&self.path, 1))?;
+        }
+        self.file = append_to(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.at_line_start && self.size > 0 && self.size + buf.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+        if written > 0 {
+            self.at_line_start = buf[written - 1] == b'\n';
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+fn append_to(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(format!(".{index}"));
+    PathBuf::from(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotates_between_lines_and_keeps_max_files() -> Result<()> {
+        let dir = std::env::temp_dir()
+            .join("trueflow_logging")
+            .join(uuid::Uuid::new_v4().to_string());
+        let config = StructuredLog {
+            path: dir.join("trueflow.jsonl"),
+            max_bytes: 5,
+            max_files: 2,
+        };
+        let mut file = RotatingFile::open(&config)?;
+        for line in ["first\n", "second\n", "third\n", "fourth\n"] {
+            // Split writes mimic fern writing the payload and newline separately.
+            let (head, tail) = line.split_at(3);
+            file.write_all(head.as_bytes())?;
+            file.write_all(tail.as_bytes())?;
+        }
+        file.flush()?;
+
+        assert_eq!(fs::read_to_string(&config.path)?, "fourth\n");
+        assert_eq!(
+            fs::read_to_string(rotated_path(&config.path, 1))?,
+            "third\n"
+        );
+        assert_eq!(
+            fs::read_to_string(rotated_path(&config.path, 2))?,
+            "second\n"
+        );
+        assert!(!rotated_path(&config.path, 3).exists());
+        fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+}
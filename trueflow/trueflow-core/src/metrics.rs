@@ -0,0 +1,198 @@
+use crate::commands::stats::CoverageStats;
+use crate::store::FileStore;
+use crate::time_window::TimeWindow;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+const METRICS_FILE: &str = "metrics.jsonl";
+
+/// Review debt at one point in time, as `trueflow snapshot` records it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricsSnapshot {
+    pub timestamp: i64,
+    pub total_blocks: usize,
+    pub unreviewed: usize,
+    /// Unreviewed blocks per directory, grouped as `trueflow stats --depth`.
+    pub directories: BTreeMap<String, usize>,
+}
+
+impl MetricsSnapshot {
+    pub fn from_stats(stats: &CoverageStats, timestamp: i64) -> Self {
+        let unreviewed = |blocks: usize, approved: usize| blocks.saturating_sub(approved);
+        Self {
+            timestamp,
+            total_blocks: stats.total.blocks,
+            unreviewed: unreviewed(stats.total.blocks, stats.total.approved),
+            directories: stats
+                .directories
+                .iter()
+                .map(|row| (row.name.clone(), unreviewed(row.blocks, row.approved)))
+                .collect(),
+        }
+    }
+}
+
+/// `.trueflow/metrics.jsonl`, one snapshot per line. Unlike the ledger it
+/// holds no verdicts, so it can be committed or kept local.
+pub fn metrics_path() -> Result<PathBuf> {
+    Ok(FileStore::new()?.trueflow_dir().join(METRICS_FILE))
+}
+
+pub fn append_snapshot(snapshot: &MetricsSnapshot) -> Result<PathBuf> {
+    let path = metrics_path()?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut line = serde_json::to_string(snapshot)?;
+    line.push('\n');
+    file.write_all(line.as_bytes())?;
+    Ok(path)
+}
+
+/// Every recorded snapshot, oldest first.
+pub fn read_snapshots() -> Result<Vec<MetricsSnapshot>> {
+    let path = metrics_path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let contents =
+        fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let mut snapshots = contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str(line)
+                .with_context(|| format!("{}:{}: invalid snapshot", path.display(), index + 1))
+        })
+        .collect::<Result<Vec<MetricsSnapshot>>>()?;
+    snapshots.sort_by_key(|snapshot| snapshot.timestamp);
+    Ok(snapshots)
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DirectoryBurndown {
+    pub name: String,
+    pub start: usize,
+    pub end: usize,
+    pub change: i64,
+}
+
+/// How review debt moved across the snapshots in a window.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Burndown {
+    pub since: Option<i64>,
+    pub until: Option<i64>,
+    pub points: Vec<MetricsSnapshot>,
+    /// Unreviewed blocks at the end minus at the start; negative is progress.
+    pub change: i64,
+    /// Directories in the first or last snapshot; one missing from a
+    /// snapshot counts as 0 there.
+    pub directories: Vec<DirectoryBurndown>,
+}
+
+pub fn burndown(snapshots: Vec<MetricsSnapshot>, window: TimeWindow) -> Burndown {
+    let points: Vec<MetricsSnapshot> = snapshots
+        .into_iter()
+        .filter(|snapshot| window.contains(snapshot.timestamp))
+        .collect();
+    let (change, directories) = match (points.first(), points.last()) {
+        (Some(first), Some(last)) => {
+            let names: BTreeSet<&String> = first
+                .directories
+                .keys()
+                .chain(last.directories.keys())
+                .collect();
+            let count = |snapshot: &MetricsSnapshot, name: &str| {
+                snapshot.directories.get(name).copied().unwrap_or(0)
+            };
+            let directories = names
+                .into_iter()
+                .map(|name| {
+                    let (start, end) = (count(first, name), count(last, name));
+                    DirectoryBurndown {
+                        name: name.clone(),
+                        start,
+                        end,
+                        change: end as i64 - start as i64,
+                    }
+                })
+                .collect();
+            (
+                last.unreviewed as i64 - first.unreviewed as i64,
+                directories,
+            )
+        }
+        _ => (0, Vec::new()),
+    };
+    Burndown {
+        since: window.since,
+        until: window.until,
+        points,
+        change,
+        directories,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(
+        timestamp: i64,
+        unreviewed: usize,
+        directories: &[(&str, usize)],
+    ) -> MetricsSnapshot {
+        MetricsSnapshot {
+            timestamp,
+            total_blocks: 100,
+            unreviewed,
+            directories: directories
+                .iter()
+                .map(|(name, count)| (name.to_string(), *count))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn burndown_compares_first_and_last_snapshot_in_window() {
+        let snapshots = vec![
+            snapshot(10, 90, &[("src", 80), ("docs", 10)]),
+            snapshot(20, 60, &[("src", 60), ("docs", 0)]),
+            snapshot(30, 40, &[("src", 35), ("tests", 5)]),
+        ];
+        let window = TimeWindow {
+            since: Some(20),
+            until: None,
+        };
+        let report = burndown(snapshots, window);
+
+        assert_eq!(report.points.len(), 2);
+        assert_eq!(report.change, -20);
+        let rows: Vec<(&str, usize, usize, i64)> = report
+            .directories
+            .iter()
+            .map(|row| (row.name.as_str(), row.start, row.end, row.change))
+            .collect();
+        assert_eq!(
+            rows,
+            [("docs", 0, 0, 0), ("src", 60, 35, -25), ("tests", 0, 5, 5)]
+        );
+    }
+
+    #[test]
+    fn empty_window_has_no_change() {
+        let report = burndown(
+            vec![snapshot(10, 5, &[])],
+            TimeWindow::parse(None, Some("5"), 0).unwrap(),
+        );
+        assert!(report.points.is_empty());
+        assert_eq!(report.change, 0);
+    }
+}
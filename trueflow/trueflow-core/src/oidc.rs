@@ -0,0 +1,337 @@
+use crate::config::{OidcConfig, user_config_dir};
+use crate::curl;
+use crate::store::Identity;
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const CACHE_FILE_NAME: &str = "oidc.json";
+const DEVICE_CODE_GRANT: &str = "urn:ietf:params:oauth:grant-type:device_code";
+/// Poll interval when the issuer does not suggest one (RFC 8628).
+const DEFAULT_POLL_SECS: u64 = 5;
+
+#[derive(Debug, Deserialize)]
+struct Discovery {
+    issuer: String,
+    token_endpoint: String,
+    device_authorization_endpoint: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeviceAuthorization {
+    device_code: String,
+    user_code: String,
+    // Google still sends `verification_url`.
+    #[serde(alias = "verification_url")]
+    verification_uri: String,
+    verification_uri_complete: Option<String>,
+    expires_in: u64,
+    interval: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    id_token: Option<String>,
+    error: Option<String>,
+    error_description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Claims {
+    iss: String,
+    sub: String,
+    aud: Audience,
+    exp: i64,
+    email: Option<String>,
+    email_verified: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Audience {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl Audience {
+    fn contains(&self, client_id: &str) -> bool {
+        match self {
+            Audience::One(audience) => audience == client_id,
+            Audience::Many(audiences) => audiences.iter().any(|audience| audience == client_id),
+        }
+    }
+}
+
+/// Identities from `trueflow login`, by issuer. Tokens are not kept.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct LoginCache {
+    logins: BTreeMap<String, CachedLogin>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedLogin {
+    identity: Identity,
+    logged_in_at: i64,
+}
+
+/// Signs in with the OAuth device flow: prints a code to enter at the
+/// issuer, waits for the sign-in, and caches the identity from the ID token.
+pub fn login(config: &OidcConfig) -> Result<Identity> {
+    let discovery: Discovery = get_json(&format!(
+        "{}/.well-known/openid-configuration",
+        config.issuer.trim_end_matches('/')
+    ))?;
+    // OIDC Discovery: the document must name the issuer it was fetched for.
+    if discovery.issuer.trim_end_matches('/') != config.issuer.trim_end_matches('/') {
+        bail!(
+            "{} announces issuer {}; refusing to sign in",
+            config.issuer,
+            discovery.issuer
+        );
+    }
+    let device_endpoint = discovery
+        .device_authorization_endpoint
+        .as_deref()
+        .with_context(|| {
+            format!(
+                "{} does not support the device authorization flow",
+                config.issuer
+            )
+        })?;
+    let device: DeviceAuthorization = post_form(
+        device_endpoint,
+        &[
+            ("client_id", config.client_id.as_str()),
+            ("scope", &config.scopes.join(" ")),
+        ],
+    )?;
+
+    match &device.verification_uri_complete {
+        Some(uri) => println!("Open {uri} to sign in (code {}).", device.user_code),
+        None => println!(
+            "Open {} and enter the code {}.",
+            device.verification_uri, device.user_code
+        ),
+    }
+    let id_token = poll_for_id_token(&discovery, &device, &config.client_id)?;
+    let claims = decode_claims(&id_token)?;
+    let identity = identity_from_claims(claims, &config.issuer, &config.client_id, now()?)?;
+    save_login(&config.issuer, &identity)?;
+    Ok(identity)
+}
+
+/// The identity `trueflow login` cached for `issuer`.
+pub fn cached_identity(issuer: &str) -> Result<Option<Identity>> {
+    Ok(load_cache()?
+        .logins
+        .remove(issuer.trim_end_matches('/'))
+        .map(|login| login.identity))
+}
+
+fn poll_for_id_token(
+    discovery: &Discovery,
+    device: &DeviceAuthorization,
+    client_id: &str,
+) -> Result<String> {
+    let mut interval = device.interval.unwrap_or(DEFAULT_POLL_SECS);
+    let deadline = now()? + device.expires_in as i64;
+    while now()? < deadline {
+        thread::sleep(Duration::from_secs(interval));
+        let response: TokenResponse = post_form(
+            &discovery.token_endpoint,
+            &[
+                ("grant_type", DEVICE_CODE_GRANT),
+                ("device_code", device.device_code.as_str()),
+                ("client_id", client_id),
+            ],
+        )?;
+        if let Some(id_token) = response.id_token {
+            return Ok(id_token);
+        }
+        match response.error.as_deref() {
+            Some("authorization_pending") => {}
+            Some("slow_down") => interval += DEFAULT_POLL_SECS,
+            Some(error) => bail!(
+                "Sign-in failed: {}",
+                response.error_description.as_deref().unwrap_or(error)
+            ),
+            None => bail!("The token response has no id_token; is `openid` among the scopes?"),
+        }
+    }
+    bail!("The sign-in code expired; run `trueflow login` again")
+}
+
+/// The ID token's claims. Its signature is not checked: the token comes
+/// straight from the token endpoint over TLS, which OIDC accepts in place
+/// of the signature.
+fn decode_claims(id_token: &str) -> Result<Claims> {
+    let payload = id_token
+        .split('.')
+        .nth(1)
+        .context("The ID token is not a JWT")?;
+    let bytes = base64url_decode(payload).context("The ID token payload is not base64url")?;
+    serde_json::from_slice(&bytes).context("Failed to parse the ID token claims")
+}
+
+fn identity_from_claims(
+    claims: Claims,
+    issuer: &str,
+    client_id: &str,
+    now: i64,
+) -> Result<Identity> {
+    if claims.iss.trim_end_matches('/') != issuer.trim_end_matches('/') {
+        bail!("The ID token is from {}, not {}", claims.iss, issuer);
+    }
+    if !claims.aud.contains(client_id) {
+        bail!("The ID token was not issued to client {client_id}");
+    }
+    if claims.exp <= now {
+        bail!("The ID token has expired");
+    }
+    // An email the issuer has not verified says nothing about who signed in.
+    let email = claims.email.filter(|_| claims.email_verified == Some(true));
+    Ok(Identity::Oidc {
+        issuer: claims.iss.trim_end_matches('/').to_string(),
+        subject: claims.sub,
+        email,
+    })
+}
+
+fn get_json<T: for<'de> Deserialize<'de>>(url: &str) -> Result<T> {
+    let body = curl::request("GET", url, &[], None)?;
+    serde_json::from_slice(&body).with_context(|| format!("Unexpected response from {url}"))
+}
+
+/// POSTs a form; error responses are returned too, since OAuth puts its
+/// errors in the body.
+fn post_form<T: for<'de> Deserialize<'de>>(url: &str, fields: &[(&str, &str)]) -> Result<T> {
+    let form = url::form_urlencoded::Serializer::new(String::new())
+        .extend_pairs(fields)
+        .finish();
+    let body = curl::request_any_status(
+        "POST",
+        url,
+        &[
+            "Content-Type: application/x-www-form-urlencoded".to_string(),
+            "Accept: application/json".to_string(),
+        ],
+        Some(&form),
+    )?;
+    serde_json::from_slice(&body).with_context(|| format!("Unexpected response from {url}"))
+}
+
+fn cache_path() -> Result<PathBuf> {
+    Ok(user_config_dir()?.join(CACHE_FILE_NAME))
+}
+
+fn load_cache() -> Result<LoginCache> {
+    let path = cache_path()?;
+    match fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", path.display())),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(LoginCache::default()),
+        Err(err) => Err(err).with_context(|| format!("Failed to read {}", path.display())),
+    }
+}
+
+fn save_login(issuer: &str, identity: &Identity) -> Result<()> {
+    let mut cache = load_cache()?;
+    cache.logins.insert(
+        issuer.trim_end_matches('/').to_string(),
+        CachedLogin {
+            identity: identity.clone(),
+            logged_in_at: now()?,
+        },
+    );
+    let path = cache_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, serde_json::to_string_pretty(&cache)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+fn now() -> Result<i64> {
+    Ok(SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64)
+}
+
+fn base64url_decode(input: &str) -> Result<Vec<u8>> {
+    let mut output = Vec::with_capacity(input.len() * 3 / 4);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for byte in input.trim_end_matches('=').bytes() {
+        let value = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'-' | b'+' => 62,
+            b'_' | b'/' => 63,
+            _ => bail!("invalid base64 character {:?}", byte as char),
+        };
+        buffer = (buffer << 6) | u32::from(value);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            output.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn claims(json: &str) -> Claims {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn decodes_unpadded_base64url() {
+        assert_eq!(base64url_decode("eyJhIjoxfQ").unwrap(), br#"{"a":1}"#);
+        assert_eq!(base64url_decode("-_8").unwrap(), [0xfb, 0xff]);
+    }
+
+    #[test]
+    fn identity_needs_matching_issuer_audience_and_expiry() {
+        let token = r#"{"iss":"https://sso.example.com/","sub":"42","aud":["other","trueflow"],"exp":200,"email":"a@example.com","email_verified":true}"#;
+        assert_eq!(
+            identity_from_claims(claims(token), "https://sso.example.com", "trueflow", 100)
+                .unwrap(),
+            Identity::Oidc {
+                issuer: "https://sso.example.com".to_string(),
+                subject: "42".to_string(),
+                email: Some("a@example.com".to_string()),
+            }
+        );
+        assert!(
+            identity_from_claims(claims(token), "https://evil.example.com", "trueflow", 100)
+                .is_err()
+        );
+        assert!(
+            identity_from_claims(claims(token), "https://sso.example.com", "cli", 100).is_err()
+        );
+        assert!(
+            identity_from_claims(claims(token), "https://sso.example.com", "trueflow", 300)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn unverified_email_is_dropped() {
+        for token in [
+            r#"{"iss":"https://sso.example.com","sub":"42","aud":"trueflow","exp":200,"email":"a@example.com","email_verified":false}"#,
+            r#"{"iss":"https://sso.example.com","sub":"42","aud":"trueflow","exp":200,"email":"a@example.com"}"#,
+        ] {
+            let identity =
+                identity_from_claims(claims(token), "https://sso.example.com", "trueflow", 100)
+                    .unwrap();
+            assert_eq!(identity.label(), "oidc:42");
+        }
+    }
+}
@@ -0,0 +1,445 @@
+use crate::block::{Block, BlockKind};
+use crate::config::{self, OptimizerConfig};
+use log::warn;
+use std::mem;
+use std::sync::OnceLock;
+
+/// Merge rules from `[optimizer]` in `trueflow.toml`, loaded once per process
+/// so every caller splits files into the same blocks.
+pub fn configured_rules() -> &'static OptimizerConfig {
+    static RULES: OnceLock<OptimizerConfig> = OnceLock::new();
+    RULES.get_or_init(|| match config::load() {
+        Ok(config) => config.optimizer,
+        Err(err) => {
+            warn!("Using default optimizer rules: {:#}", err);
+            OptimizerConfig::default()
+        }
+    })
+}
+
+pub fn optimize(blocks: Vec<Block>) -> Vec<Block> {
+    optimize_with(blocks, configured_rules())
+}
+
+pub fn optimize_with(blocks: Vec<Block>, rules: &OptimizerConfig) -> Vec<Block> {
+    let mut blocks = blocks;
+    if rules.merge_imports {
+        blocks = optimize_imports(blocks);
+    }
+    if rules.merge_modules {
+        blocks = optimize_modules(blocks, rules.max_module_lines);
+    }
+    if rules.merge_constants {
+        blocks = optimize_constants(blocks, rules.max_constant_lines);
+    }
+    if rules.merge_code_paragraphs {
+        blocks = optimize_code_paragraphs(blocks, rules.max_code_paragraph_lines);
+    }
+    if rules.merge_tiny_blocks {
+        blocks = optimize_tiny_blocks(blocks, rules);
+    }
+    blocks
+}
+
+fn optimize_imports(blocks: Vec<Block>) -> Vec<Block> {
+    optimize_sequence(
+        blocks,
+        |block, buffer| {
+            if block.kind == BlockKind::Import
+                || (block.kind == BlockKind::Gap && !buffer.is_empty())
+            {
+                Decision::Buffer
+            } else {
+                Decision::FlushAndEmit
+            }
+        },
+        |buffer| flush_blocks(buffer, &[BlockKind::Import], BlockKind::Imports, Some("\n")),
+    )
+}
+
+fn optimize_code_paragraphs(blocks: Vec<Block>, max_lines: usize) -> Vec<Block> {
+    optimize_sequence(
+        blocks,
+        |block, buffer| {
+            if !matches!(block.kind, BlockKind::CodeParagraph | BlockKind::Gap) {
+                return Decision::FlushAndEmit;
+            }
+
+            if block.kind == BlockKind::Gap {
+                return Decision::Buffer;
+            }
+
+            // It is CodeParagraph. Check if adding it would exceed the limit.
+            let start_line = buffer
+                .iter()
+                .find(|b| b.kind == BlockKind::CodeParagraph)
+                .map(|b| b.start_line)
+                .unwrap_or(block.start_line);
+            let end_line = block.end_line;
+            let size = end_line.saturating_sub(start_line);
+
+            if size > max_lines {
+                Decision::FlushAndBuffer
+            } else {
+                Decision::Buffer
+            }
+        },
+        |buffer| {
+            flush_blocks(
+                buffer,
+                &[BlockKind::CodeParagraph],
+                BlockKind::CodeParagraph,
+                None,
+            )
+        },
+    )
+}
+
+fn optimize_modules(blocks: Vec<Block>, max_lines: usize) -> Vec<Block> {
+    optimize_runs(blocks, &[BlockKind::Module], BlockKind::Modules, max_lines)
+}
+
+/// Merges runs of `const`/`static` items into a single `Constants` block.
+fn optimize_constants(blocks: Vec<Block>, max_lines: usize) -> Vec<Block> {
+    optimize_runs(
+        blocks,
+        &[BlockKind::Const, BlockKind::Static],
+        BlockKind::Constants,
+        max_lines,
+    )
+}
+
+/// Merges runs of `targets`, allowing gaps and comments in between, as long
+/// as the merged span stays within `max_lines`.
+fn optimize_runs(
+    blocks: Vec<Block>,
+    targets: &[BlockKind],
+    merged_kind: BlockKind,
+    max_lines: usize,
+) -> Vec<Block> {
+    optimize_sequence(
+        blocks,
+        |block, buffer| {
+            let is_target = targets.contains(&block.kind);
+            if !is_target && !matches!(block.kind, BlockKind::Gap | BlockKind::Comment) {
+                return Decision::FlushAndEmit;
+            }
+
+            if !is_target {
+                return Decision::Buffer;
+            }
+
+            let start_line = buffer
+                .iter()
+                .find(|b| targets.contains(&b.kind))
+                .map(|b| b.start_line)
+                .unwrap_or(block.start_line);
+            let end_line = block.end_line;
+            let size = end_line.saturating_sub(start_line);
+
+            if size > max_lines {
+                Decision::FlushAndBuffer
+            } else {
+                Decision::Buffer
+            }
+        },
+        |buffer| flush_blocks(buffer, targets, merged_kind.clone(), None),
+    )
+}
+
+fn is_tiny(block: &Block, rules: &OptimizerConfig) -> bool {
+    block.kind != BlockKind::Gap
+        && block.metrics.line_count <= rules.tiny_block_max_lines
+        && block.metrics.byte_count <= rules.tiny_block_max_bytes
+}
+
+/// Coalesces runs of tiny neighbouring blocks of any kind (gaps in between
+/// are absorbed) into one reviewable unit of at most `max_tiny_run_lines`.
+fn optimize_tiny_blocks(blocks: Vec<Block>, rules: &OptimizerConfig) -> Vec<Block> {
+    optimize_sequence(
+        blocks,
+        |block, buffer| {
+            if block.kind == BlockKind::Gap {
+                return if buffer.is_empty() {
+                    Decision::FlushAndEmit
+                } else {
+                    Decision::Buffer
+                };
+            }
+            if !is_tiny(block, rules) {
+                return Decision::FlushAndEmit;
+            }
+
+            let start_line = buffer
+                .first()
+                .map(|b| b.start_line)
+                .unwrap_or(block.start_line);
+            let size = block.end_line.saturating_sub(start_line);
+
+            if size > rules.max_tiny_run_lines {
+                Decision::FlushAndBuffer
+            } else {
+                Decision::Buffer
+            }
+        },
+        |buffer| {
+            let mut kinds: Vec<BlockKind> = Vec::new();
+            for block in &buffer {
+                if block.kind != BlockKind::Gap && !kinds.contains(&block.kind) {
+                    kinds.push(block.kind.clone());
+                }
+            }
+            // A run of one kind keeps it; mixed runs become plain code.
+            let merged_kind = match kinds.as_slice() {
+                [kind] => kind.clone(),
+                _ => BlockKind::Code,
+            };
+            flush_blocks(buffer, &kinds, merged_kind, None)
+        },
+    )
+}
+
+enum Decision {
+    Buffer,
+    FlushAndBuffer,
+    FlushAndEmit,
+}
+
+fn optimize_sequence<F>(
+    blocks: Vec<Block>,
+    mut decider: F,
+    flusher: impl Fn(Vec<Block>) -> Vec<Block>,
+) -> Vec<Block>
+where
+    F: FnMut(&Block, &Vec<Block>) -> Decision,
+{
+    let mut optimized = Vec::with_capacity(blocks.len());
+    let mut buffer = Vec::new();
+
+    for block in blocks {
+        match decider(&block, &buffer) {
+            Decision::Buffer => buffer.push(block),
+            Decision::FlushAndBuffer => {
+                if !buffer.is_empty() {
+                    optimized.extend(flusher(mem::take(&mut buffer)));
+                }
+                buffer.push(block);
+            }
+            Decision::FlushAndEmit => {
+                if !buffer.is_empty() {
+                    optimized.extend(flusher(mem::take(&mut buffer)));
+                }
+                optimized.push(block);
+            }
+        }
+    }
+
+    if !buffer.is_empty() {
+        optimized.extend(flusher(buffer));
+    }
+
+    optimized
+}
+
+fn flush_blocks(
+    buffer: Vec<Block>,
+    targets: &[BlockKind],
+    merged_kind: BlockKind,
+    separator: Option<&str>,
+) -> Vec<Block> {
+    let is_target = |block: &Block| targets.contains(&block.kind);
+    let target_count = buffer.iter().filter(|b| is_target(b)).count();
+    if target_count < 2 {
+        return buffer;
+    }
+
+    let first_idx = buffer.iter().position(is_target).unwrap();
+    let last_idx = buffer.iter().rposition(is_target).unwrap();
+
+    let mut result = Vec::with_capacity(buffer.len() - (last_idx - first_idx));
+
+    // Emit leading gaps
+    result.extend(buffer.iter().take(first_idx).cloned());
+
+    // Merge range
+    let range = &buffer[first_idx..=last_idx];
+    let start_line = range[0].start_line;
+    let end_line = range.last().unwrap().end_line;
+
+    let mut content = String::new();
+    let mut prev_was_target = false;
+
+    for block in range {
+        if let Some(sep) = separator
+            && prev_was_target
+            && is_target(block)
+        {
+            content.push_str(sep);
+        }
+        content.push_str(&block.content);
+        prev_was_target = is_target(block);
+    }
+
+    let merged_block = Block::new(content, merged_kind, start_line, end_line);
+    result.push(merged_block);
+
+    // Emit trailing gaps
+    result.extend(buffer.iter().skip(last_idx + 1).cloned());
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::Block;
+
+    fn make_block(kind: BlockKind, content: &str, start: usize, end: usize) -> Block {
+        Block::new(content.to_string(), kind, start, end)
+    }
+
+    #[test]
+    fn test_merge_small_paragraphs() {
+        let blocks = vec![
+            make_block(BlockKind::CodeParagraph, "P1\n", 0, 2), // 2 lines
+            make_block(BlockKind::Gap, "\n", 2, 3),             // 1 line
+            make_block(BlockKind::CodeParagraph, "P2\n", 3, 5), // 2 lines
+        ];
+        // Total span: 5 - 0 = 5 lines. Should merge.
+
+        let optimized = optimize_with(blocks, &OptimizerConfig::default());
+        assert_eq!(optimized.len(), 1);
+        assert_eq!(optimized[0].kind, BlockKind::CodeParagraph);
+        assert_eq!(optimized[0].content, "P1\n\nP2\n");
+    }
+
+    #[test]
+    fn test_dont_merge_large_paragraphs() {
+        let blocks = vec![
+            make_block(BlockKind::CodeParagraph, "P1\nP1\nP1\nP1\n", 0, 4), // 4 lines
+            make_block(BlockKind::Gap, "\n", 4, 5),                         // 1 line
+            make_block(BlockKind::CodeParagraph, "P2\nP2\nP2\nP2\n", 5, 9), // 4 lines
+        ];
+        // Total span: 9 - 0 = 9 lines. Should NOT merge.
+
+        let optimized = optimize_with(blocks, &OptimizerConfig::default());
+        assert_eq!(optimized.len(), 3);
+        assert_eq!(optimized[0].kind, BlockKind::CodeParagraph);
+        assert_eq!(optimized[1].kind, BlockKind::Gap);
+        assert_eq!(optimized[2].kind, BlockKind::CodeParagraph);
+    }
+
+    #[test]
+    fn test_merge_sequence_greedy() {
+        let blocks = vec![
+            make_block(BlockKind::CodeParagraph, "P1\n", 0, 1), // 1 line
+            make_block(BlockKind::Gap, "\n", 1, 2),             // 1 line
+            make_block(BlockKind::CodeParagraph, "P2\n", 2, 3), // 1 line
+            // Span 0..3 = 3 lines. Merge P1+Gap+P2.
+            make_block(BlockKind::Gap, "\n\n\n\n\n\n", 3, 9), // 6 lines
+            make_block(BlockKind::CodeParagraph, "P3\n", 9, 10), // 1 line
+        ];
+        // Adding P3: Span 0..10 = 10 lines. Too big.
+        // Should flush P1+Gap+P2. Then emit Gap(6). Then buffer P3.
+
+        let optimized = optimize_with(blocks, &OptimizerConfig::default());
+        // P1+Gap+P2 merged = 1 block.
+        // Gap(6) = 1 block.
+        // P3 = 1 block.
+        // Total 3 blocks.
+        assert_eq!(optimized.len(), 3);
+        assert_eq!(optimized[0].kind, BlockKind::CodeParagraph);
+        assert_eq!(optimized[0].content, "P1\n\nP2\n");
+        assert_eq!(optimized[1].kind, BlockKind::Gap);
+        assert_eq!(optimized[2].kind, BlockKind::CodeParagraph);
+        assert_eq!(optimized[2].content, "P3\n");
+    }
+
+    #[test]
+    fn test_constants_merge_only_when_enabled() {
+        let blocks = || {
+            vec![
+                make_block(BlockKind::Const, "const A: u8 = 1;\n", 0, 1),
+                make_block(BlockKind::Comment, "// B\n", 1, 2),
+                make_block(BlockKind::Static, "static B: u8 = 2;\n", 2, 3),
+            ]
+        };
+
+        let default_rules = OptimizerConfig::default();
+        assert_eq!(optimize_with(blocks(), &default_rules).len(), 3);
+
+        let rules = OptimizerConfig {
+            merge_constants: true,
+            ..OptimizerConfig::default()
+        };
+        let optimized = optimize_with(blocks(), &rules);
+        assert_eq!(optimized.len(), 1);
+        assert_eq!(optimized[0].kind, BlockKind::Constants);
+        assert_eq!(
+            optimized[0].content,
+            "const A: u8 = 1;\n// B\nstatic B: u8 = 2;\n"
+        );
+    }
+
+    #[test]
+    fn test_rules_control_paragraph_merging() {
+        let blocks = || {
+            vec![
+                make_block(BlockKind::CodeParagraph, "P1\nP1\nP1\nP1\n", 0, 4),
+                make_block(BlockKind::Gap, "\n", 4, 5),
+                make_block(BlockKind::CodeParagraph, "P2\nP2\nP2\nP2\n", 5, 9),
+            ]
+        };
+
+        let larger = OptimizerConfig {
+            max_code_paragraph_lines: 12,
+            ..OptimizerConfig::default()
+        };
+        assert_eq!(optimize_with(blocks(), &larger).len(), 1);
+
+        let disabled = OptimizerConfig {
+            merge_code_paragraphs: false,
+            max_code_paragraph_lines: 12,
+            ..OptimizerConfig::default()
+        };
+        assert_eq!(optimize_with(blocks(), &disabled).len(), 3);
+    }
+
+    #[test]
+    fn test_tiny_blocks_coalesce_when_enabled() {
+        let blocks = || {
+            vec![
+                make_block(BlockKind::Struct, "struct A;\n", 0, 1),
+                make_block(BlockKind::Gap, "\n", 1, 2),
+                make_block(BlockKind::Struct, "struct B;\n", 2, 3),
+                make_block(BlockKind::Type, "type C = B;\n", 3, 4),
+                make_block(BlockKind::Gap, "\n", 4, 5),
+                make_block(
+                    BlockKind::Function,
+                    "fn big() {\n    one();\n    two();\n}\n",
+                    5,
+                    9,
+                ),
+            ]
+        };
+
+        let default_rules = OptimizerConfig::default();
+        assert_eq!(optimize_with(blocks(), &default_rules).len(), 6);
+
+        let rules = OptimizerConfig {
+            merge_tiny_blocks: true,
+            ..OptimizerConfig::default()
+        };
+        let optimized = optimize_with(blocks(), &rules);
+        let kinds: Vec<BlockKind> = optimized.iter().map(|b| b.kind.clone()).collect();
+        assert_eq!(
+            kinds,
+            vec![BlockKind::Code, BlockKind::Gap, BlockKind::Function]
+        );
+        assert_eq!(
+            optimized[0].content,
+            "struct A;\n\nstruct B;\ntype C = B;\n"
+        );
+        assert_eq!((optimized[0].start_line, optimized[0].end_line), (0, 4));
+    }
+}
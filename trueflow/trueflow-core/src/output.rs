@@ -0,0 +1,229 @@
+use anyhow::Result;
+use serde::Serialize;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum), clap(rename_all = "lower"))]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+    Yaml,
+    Toml,
+    /// Markdown, for pasting into PR descriptions
+    Md,
+}
+
+impl OutputFormat {
+    /// `--json` predates `--format` and still wins when given.
+    pub fn resolve(format: OutputFormat, json: bool) -> Self {
+        if json { OutputFormat::Json } else { format }
+    }
+
+    pub fn structured(self) -> Option<StructuredFormat> {
+        match self {
+            OutputFormat::Json => Some(StructuredFormat::Json),
+            OutputFormat::Yaml => Some(StructuredFormat::Yaml),
+            OutputFormat::Toml => Some(StructuredFormat::Toml),
+            OutputFormat::Text | OutputFormat::Md => None,
+        }
+    }
+}
+
+impl From<StructuredFormat> for OutputFormat {
+    fn from(format: StructuredFormat) -> Self {
+        match format {
+            StructuredFormat::Json => OutputFormat::Json,
+            StructuredFormat::Yaml => OutputFormat::Yaml,
+            StructuredFormat::Toml => OutputFormat::Toml,
+        }
+    }
+}
+
+/// The machine-readable formats of the global `--output` flag.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum), clap(rename_all = "lower"))]
+pub enum StructuredFormat {
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl StructuredFormat {
+    pub fn render<T: Serialize>(self, value: &T) -> Result<String> {
+        Ok(match self {
+            StructuredFormat::Json => serde_json::to_string_pretty(value)?,
+            StructuredFormat::Yaml => serde_yaml_ng::to_string(value)?,
+            StructuredFormat::Toml => render_toml(value)?,
+        })
+    }
+
+    /// Prints `value` to stdout.
+    pub fn print<T: Serialize>(self, value: &T) -> Result<()> {
+        println!("{}", self.render(value)?.trim_end());
+        Ok(())
+    }
+}
+
+/// TOML has no null and needs a table at the top: nulls are dropped, and
+/// anything but a table is put under `items`.
+fn render_toml<T: Serialize>(value: &T) -> Result<String> {
+    let value = match drop_nulls(serde_json::to_value(value)?) {
+        object @ serde_json::Value::Object(_) => object,
+        other => serde_json::json!({ "items": other }),
+    };
+    Ok(toml::to_string_pretty(&value)?)
+}
+
+fn drop_nulls(value: serde_json::Value) -> serde_json::Value {
+    use serde_json::Value;
+    match value {
+        Value::Object(fields) => Value::Object(
+            fields
+                .into_iter()
+                .filter(|(_, value)| !value.is_null())
+                .map(|(key, value)| (key, drop_nulls(value)))
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(
+            items
+                .into_iter()
+                .filter(|item| !item.is_null())
+                .map(drop_nulls)
+                .collect(),
+        ),
+        other => other,
+    }
+}
+
+/// A failed command under `--output`: the error and its causes.
+#[derive(Serialize)]
+struct ErrorReport {
+    error: ErrorDetail,
+}
+
+#[derive(Serialize)]
+struct ErrorDetail {
+    message: String,
+    causes: Vec<String>,
+}
+
+/// Prints `err` to stdout as a structured `error` object.
+pub fn print_error(err: &anyhow::Error, format: StructuredFormat) -> Result<()> {
+    format.print(&ErrorReport {
+        error: ErrorDetail {
+            message: err.to_string(),
+            causes: err.chain().skip(1).map(ToString::to_string).collect(),
+        },
+    })
+}
+
+/// Output formats for `feedback`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum), clap(rename_all = "lower"))]
+pub enum FeedbackFormat {
+    #[default]
+    Xml,
+    Json,
+    Yaml,
+    Toml,
+    /// A prompt for coding agents, grouped by file
+    #[cfg_attr(feature = "clap", value(alias = "md"))]
+    Markdown,
+}
+
+impl FeedbackFormat {
+    pub fn structured(self) -> Option<StructuredFormat> {
+        match self {
+            FeedbackFormat::Json => Some(StructuredFormat::Json),
+            FeedbackFormat::Yaml => Some(StructuredFormat::Yaml),
+            FeedbackFormat::Toml => Some(StructuredFormat::Toml),
+            FeedbackFormat::Xml | FeedbackFormat::Markdown => None,
+        }
+    }
+}
+
+impl From<StructuredFormat> for FeedbackFormat {
+    fn from(format: StructuredFormat) -> Self {
+        match format {
+            StructuredFormat::Json => FeedbackFormat::Json,
+            StructuredFormat::Yaml => FeedbackFormat::Yaml,
+            StructuredFormat::Toml => FeedbackFormat::Toml,
+        }
+    }
+}
+
+/// Output formats for `check`, which adds CI report formats to text and JSON.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum), clap(rename_all = "lower"))]
+pub enum CheckFormat {
+    #[default]
+    Text,
+    Json,
+    Yaml,
+    Toml,
+    /// JUnit XML, one testcase per violation
+    Junit,
+    /// GitHub Actions `::error` workflow commands
+    Github,
+}
+
+impl CheckFormat {
+    pub fn resolve(format: CheckFormat, json: bool) -> Self {
+        if json { CheckFormat::Json } else { format }
+    }
+
+    pub fn structured(self) -> Option<StructuredFormat> {
+        match self {
+            CheckFormat::Json => Some(StructuredFormat::Json),
+            CheckFormat::Yaml => Some(StructuredFormat::Yaml),
+            CheckFormat::Toml => Some(StructuredFormat::Toml),
+            CheckFormat::Text | CheckFormat::Junit | CheckFormat::Github => None,
+        }
+    }
+}
+
+impl From<StructuredFormat> for CheckFormat {
+    fn from(format: StructuredFormat) -> Self {
+        match format {
+            StructuredFormat::Json => CheckFormat::Json,
+            StructuredFormat::Yaml => CheckFormat::Yaml,
+            StructuredFormat::Toml => CheckFormat::Toml,
+        }
+    }
+}
+
+/// Serializes `value` for the machine-readable formats; `None` for text and Markdown.
+pub fn render_structured<T: Serialize>(value: &T, format: OutputFormat) -> Result<Option<String>> {
+    format
+        .structured()
+        .map(|format| format.render(value))
+        .transpose()
+}
+
+/// Escapes text for a single Markdown table cell.
+pub fn md_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+/// Escapes text for XML content and attribute values.
+pub fn escape_xml(s: &str) -> String {
+    s.replace("&", "&amp;")
+        .replace("<", "&lt;")
+        .replace(">", "&gt;")
+        .replace("\"", "&quot;")
+        .replace("'", "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn toml_drops_nulls_and_wraps_non_tables() {
+        let rendered = StructuredFormat::Toml
+            .render(&json!([{ "path": "a.rs", "note": null }]))
+            .unwrap();
+        assert_eq!(rendered, "[[items]]\npath = \"a.rs\"\n");
+    }
+}
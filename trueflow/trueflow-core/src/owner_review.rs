@@ -0,0 +1,195 @@
+use crate::codeowners::{CodeOwners, Owner};
+use crate::config::TrueflowConfig;
+use crate::store::{Record, Verdict, workspace_root};
+use crate::tree::{Tree, TreeNodeId};
+use anyhow::Result;
+use log::warn;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Tag on approved blocks that still need an approval from an owner.
+pub const OWNER_REVIEW_TAG: &str = "needs-owner-review";
+
+/// `[policy] require_owner_review`: owned blocks need an approval from one of
+/// their CODEOWNERS owners, not just from anyone.
+pub struct OwnerReview {
+    owners: CodeOwners,
+    handles: HashMap<String, Vec<String>>,
+    /// Identities currently approving each fingerprint under `review`.
+    approvers: HashMap<String, HashSet<String>>,
+}
+
+/// Owners of one file and the fingerprints they approve.
+pub struct FileOwners {
+    pub owners: Vec<String>,
+    approved: HashSet<String>,
+}
+
+impl OwnerReview {
+    /// `None` unless `[policy] require_owner_review` is set.
+    pub fn load(config: &TrueflowConfig, records: &[Record]) -> Result<Option<Self>> {
+        let root = workspace_root().unwrap_or_else(|_| PathBuf::from("."));
+        Self::load_in(&root, config, records)
+    }
+
+    /// [`OwnerReview::load`] for the workspace at `root`.
+    pub fn load_in(
+        root: &Path,
+        config: &TrueflowConfig,
+        records: &[Record],
+    ) -> Result<Option<Self>> {
+        if !config.policy.require_owner_review {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            owners: CodeOwners::configured(root, &config.owners)?,
+            handles: config.owners.handles.clone(),
+            approvers: current_approvers(records),
+        }))
+    }
+
+    /// `None` when nobody owns `path` (repo-relative).
+    pub fn for_path(&self, path: &str) -> Option<FileOwners> {
+        let owners = self.owner_labels(path);
+        if owners.is_empty() {
+            return None;
+        }
+        let approved = self
+            .approvers
+            .iter()
+            .filter(|(_, approvers)| owners.iter().any(|owner| approvers.contains(owner)))
+            .map(|(fingerprint, _)| fingerprint.clone())
+            .collect();
+        Some(FileOwners { owners, approved })
+    }
+
+    /// Identity labels owning `path`. Handles without an `[owners.handles]`
+    /// entry stay as `@handle`, which no identity matches.
+    fn owner_labels(&self, path: &str) -> Vec<String> {
+        let mut labels: Vec<String> = Vec::new();
+        for owner in self.owners.owners_for(path) {
+            let resolved = match owner {
+                Owner::Email(email) => vec![email.clone()],
+                Owner::Handle(handle) => match self.handles.get(handle) {
+                    Some(emails) => emails.clone(),
+                    None => {
+                        warn!("No [owners.handles] entry for @{}", handle);
+                        vec![format!("@{handle}")]
+                    }
+                },
+            };
+            for label in resolved {
+                if !labels.contains(&label) {
+                    labels.push(label);
+                }
+            }
+        }
+        labels
+    }
+}
+
+impl FileOwners {
+    /// Whether an owner approved the block itself or something containing it.
+    pub fn covers(&self, tree: &Tree, node_id: Option<TreeNodeId>, fingerprint: &str) -> bool {
+        self.approved.contains(fingerprint)
+            || node_id.is_some_and(|node_id| tree.is_node_covered(node_id, &self.approved))
+    }
+}
+
+fn current_approvers(records: &[Record]) -> HashMap<String, HashSet<String>> {
+    let mut sorted: Vec<&Record> = records
+        .iter()
+        .filter(|record| record.kind.is_verdict() && record.check == "review")
+        .collect();
+    sorted.sort_by_key(|record| record.timestamp);
+    let mut latest: HashMap<&str, HashMap<String, &Verdict>> = HashMap::new();
+    for record in sorted {
+        latest
+            .entry(record.fingerprint.as_str())
+            .or_default()
+            .insert(record.identity.label(), &record.verdict);
+    }
+    latest
+        .into_iter()
+        .map(|(fingerprint, by_identity)| {
+            let approvers = by_identity
+                .into_iter()
+                .filter(|(_, verdict)| **verdict == Verdict::Approved)
+                .map(|(identity, _)| identity)
+                .collect();
+            (fingerprint.to_string(), approvers)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::{BlockState, Identity, RepoRef, VcsSystem};
+
+    fn approval(fingerprint: &str, email: &str, verdict: Verdict, timestamp: i64) -> Record {
+        Record {
+            id: format!("{fingerprint}-{email}-{timestamp}"),
+            version: 1,
+            ..Record::verdict(
+                fingerprint.to_string(),
+                "review".to_string(),
+                verdict,
+                Identity::Email {
+                    email: email.to_string(),
+                },
+                RepoRef::Vcs {
+                    system: VcsSystem::Git,
+                    revision: "deadbeef".to_string(),
+                },
+                BlockState::Committed,
+                timestamp,
+            )
+        }
+    }
+
+    fn owner_review(records: &[Record]) -> OwnerReview {
+        OwnerReview {
+            owners: CodeOwners::parse("/src/net/ @org/net\n/src/db/ @dba\n"),
+            handles: HashMap::from([(
+                "org/net".to_string(),
+                vec!["alice@example.com".to_string()],
+            )]),
+            approvers: current_approvers(records),
+        }
+    }
+
+    #[test]
+    fn handles_resolve_through_the_config() {
+        let review = owner_review(&[]);
+        assert_eq!(
+            review
+                .for_path("src/net/http.rs")
+                .map(|owners| owners.owners),
+            Some(vec!["alice@example.com".to_string()])
+        );
+        assert_eq!(
+            review
+                .for_path("src/db/pool.rs")
+                .map(|owners| owners.owners),
+            Some(vec!["@dba".to_string()])
+        );
+        assert!(review.for_path("README.md").is_none());
+    }
+
+    #[test]
+    fn only_current_owner_approvals_count() {
+        let records = vec![
+            approval("a", "bob@example.com", Verdict::Approved, 1),
+            approval("b", "alice@example.com", Verdict::Approved, 1),
+            approval("c", "alice@example.com", Verdict::Approved, 1),
+            approval("c", "alice@example.com", Verdict::Rejected, 2),
+        ];
+        let owners = owner_review(&records)
+            .for_path("src/net/http.rs")
+            .expect("owned");
+        assert!(!owners.approved.contains("a"));
+        assert!(owners.approved.contains("b"));
+        assert!(!owners.approved.contains("c"));
+    }
+}
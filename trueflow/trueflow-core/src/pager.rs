@@ -0,0 +1,103 @@
+use crate::color::Stream;
+use log::warn;
+use std::io::{self, IsTerminal, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+
+const DEFAULT_PAGER: &str = "less -R";
+
+/// Text output that goes through `$PAGER` when stdout is a terminal, like git.
+pub enum PagedOutput {
+    Direct(Stream),
+    Pager { child: Child, stdin: ChildStdin },
+}
+
+impl PagedOutput {
+    /// Starts the pager unless `no_pager` is set or stdout is not a terminal;
+    /// otherwise writes go straight to `fallback`.
+    pub fn start(no_pager: bool, fallback: Stream) -> Self {
+        if no_pager || !io::stdout().is_terminal() {
+            return Self::Direct(fallback);
+        }
+        let Some(command) = pager_command() else {
+            return Self::Direct(fallback);
+        };
+        let mut parts = command.split_whitespace();
+        let Some(program) = parts.next() else {
+            return Self::Direct(fallback);
+        };
+        let mut cmd = Command::new(program);
+        cmd.args(parts).stdin(Stdio::piped());
+        if std::env::var_os("LESS").is_none() {
+            // Quit if one screen, keep colors, don't clear on exit.
+            cmd.env("LESS", "FRX");
+        }
+        match cmd.spawn() {
+            Ok(mut child) => match child.stdin.take() {
+                Some(stdin) => Self::Pager { child, stdin },
+                None => Self::Direct(fallback),
+            },
+            Err(err) => {
+                warn!("Failed to start pager '{}': {}", command, err);
+                Self::Direct(fallback)
+            }
+        }
+    }
+
+    /// Stream that colors should be decided for.
+    pub fn stream(&self) -> Stream {
+        match self {
+            Self::Direct(stream) => *stream,
+            Self::Pager { .. } => Stream::Stdout,
+        }
+    }
+
+    /// Closes the pager's input and waits for the user to quit it.
+    pub fn finish(self) -> io::Result<()> {
+        match self {
+            Self::Direct(Stream::Stdout) => io::stdout().flush(),
+            Self::Direct(Stream::Stderr) => io::stderr().flush(),
+            Self::Pager { mut child, stdin } => {
+                drop(stdin);
+                child.wait().map(|_| ())
+            }
+        }
+    }
+}
+
+impl Write for PagedOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Direct(Stream::Stdout) => io::stdout().write(buf),
+            Self::Direct(Stream::Stderr) => io::stderr().write(buf),
+            // Quitting the pager early closes the pipe; drop the rest.
+            Self::Pager { stdin, .. } => match stdin.write(buf) {
+                Err(err) if err.kind() == io::ErrorKind::BrokenPipe => Ok(buf.len()),
+                result => result,
+            },
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Direct(Stream::Stdout) => io::stdout().flush(),
+            Self::Direct(Stream::Stderr) => io::stderr().flush(),
+            Self::Pager { stdin, .. } => match stdin.flush() {
+                Err(err) if err.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+                result => result,
+            },
+        }
+    }
+}
+
+/// `TRUEFLOW_PAGER`, then `PAGER`, then `less -R`. Empty or `cat` disables paging.
+fn pager_command() -> Option<String> {
+    let command = std::env::var("TRUEFLOW_PAGER")
+        .or_else(|_| std::env::var("PAGER"))
+        .unwrap_or_else(|_| DEFAULT_PAGER.to_string());
+    let command = command.trim();
+    if command.is_empty() || command == "cat" {
+        None
+    } else {
+        Some(command.to_string())
+    }
+}
@@ -0,0 +1,72 @@
+use anyhow::{Context, Result};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use std::collections::BTreeMap;
+
+/// `[tags]` compiled: each tag with the globs of the paths it marks.
+#[derive(Debug, Default)]
+pub struct PathTags {
+    tags: Vec<(String, GlobSet)>,
+}
+
+impl PathTags {
+    pub fn new(config: &BTreeMap<String, Vec<String>>) -> Result<Self> {
+        let tags = config
+            .iter()
+            .map(|(tag, patterns)| {
+                let globs =
+                    path_globs(patterns).with_context(|| format!("Invalid [tags] {tag} glob"))?;
+                Ok((tag.clone(), globs))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { tags })
+    }
+
+    /// Tags whose globs match `path`, relative to the repository root.
+    pub fn tags_for<'a>(&'a self, path: &'a str) -> impl Iterator<Item = &'a str> {
+        self.tags
+            .iter()
+            .filter(move |(_, globs)| globs.is_match(path))
+            .map(|(tag, _)| tag.as_str())
+    }
+
+    pub fn has_tag(&self, path: &str, tag: &str) -> bool {
+        self.tags_for(path).any(|found| found == tag)
+    }
+
+    pub fn defines(&self, tag: &str) -> bool {
+        self.tags.iter().any(|(name, _)| name == tag)
+    }
+}
+
+/// Globs relative to the repository root; a leading `/` is ignored.
+pub fn path_globs<'a>(patterns: impl IntoIterator<Item = &'a String>) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(Glob::new(pattern.trim_start_matches('/'))?);
+    }
+    Ok(builder.build()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paths_carry_every_matching_tag() {
+        let config = BTreeMap::from([
+            (
+                "security".to_string(),
+                vec!["src/auth/**".to_string(), "**/crypto*".to_string()],
+            ),
+            ("docs".to_string(), vec!["/docs/**".to_string()]),
+        ]);
+        let tags = PathTags::new(&config).unwrap();
+
+        assert!(tags.has_tag("src/auth/login.rs", "security"));
+        assert!(tags.has_tag("lib/crypto_box.rs", "security"));
+        assert!(tags.has_tag("docs/intro.md", "docs"));
+        assert_eq!(tags.tags_for("src/main.rs").count(), 0);
+        assert!(tags.defines("docs"));
+        assert!(!tags.defines("perf"));
+    }
+}
@@ -0,0 +1,445 @@
+use crate::block::{Block, BlockKind, FileState};
+use crate::config::{BlockFilters, PolicyConfig, PolicyRule};
+use crate::path_tags::path_globs;
+use crate::store::{
+    Record, Verdict, approved_hashes_from_verdicts, latest_verdicts, quorum_verdicts,
+    without_revoked,
+};
+use anyhow::{Result, bail};
+use globset::GlobSet;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+pub fn should_skip_imports_by_default(path: &str, block: &Block, filters: &BlockFilters) -> bool {
+    if block.kind.is_import_like() && !is_lib_rs(path) && !filters.only_contains(&block.kind) {
+        return true;
+    }
+    false
+}
+
+pub fn should_skip_impl_by_default(block: &Block, filters: &BlockFilters) -> bool {
+    matches!(block.kind, BlockKind::Impl | BlockKind::Interface)
+        && !filters.only_contains(&block.kind)
+}
+
+pub fn should_skip_generated_by_default(file: &FileState, filters: &BlockFilters) -> bool {
+    file.generated && !filters.include_generated
+}
+
+/// Keeps only records that count toward review state. Revoked records are
+/// dropped, and so are agent and machine verdicts when `require_human` is
+/// set, and agent verdicts whose confidence is below `min_agent_confidence`
+/// (a missing confidence counts as below).
+pub fn counted_records(records: Vec<Record>, policy: &PolicyConfig) -> Vec<Record> {
+    without_revoked(records)
+        .into_iter()
+        .filter(|record| {
+            let automated = record.identity.is_agent() || record.identity.is_machine();
+            if !record.kind.is_verdict() || !automated {
+                return true;
+            }
+            if policy.require_human {
+                return false;
+            }
+            if record.identity.is_machine() {
+                return true;
+            }
+            match policy.min_agent_confidence {
+                Some(min) => record
+                    .confidence
+                    .is_some_and(|confidence| confidence >= min),
+                None => true,
+            }
+        })
+        .collect()
+}
+
+/// Review verdict per fingerprint. With a `reviewer`, only that identity's
+/// own verdicts count; otherwise `required_approvals` above one needs that
+/// many distinct human approvers, and the default is last-write-wins.
+pub fn review_state(
+    records: &[Record],
+    policy: &PolicyConfig,
+    reviewer: Option<&str>,
+) -> HashMap<String, Verdict> {
+    check_state(records, policy, reviewer, "review")
+}
+
+/// [`review_state`] from the verdicts recorded under `check`.
+pub fn check_state(
+    records: &[Record],
+    policy: &PolicyConfig,
+    reviewer: Option<&str>,
+    check: &str,
+) -> HashMap<String, Verdict> {
+    if let Some(reviewer) = reviewer {
+        let own: Vec<Record> = records
+            .iter()
+            .filter(|record| record.identity.label() == reviewer)
+            .cloned()
+            .collect();
+        return latest_verdicts(&own, check);
+    }
+    if policy.required_approvals > 1 {
+        return quorum_verdicts(records, check, policy.required_approvals);
+    }
+    latest_verdicts(records, check)
+}
+
+/// Whether `verdict` lets a change through `check` without an approval.
+pub fn is_non_blocking(policy: &PolicyConfig, verdict: &Verdict) -> bool {
+    *verdict != Verdict::Rejected && policy.non_blocking_verdicts.contains(verdict)
+}
+
+/// Why a change fails `check`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum ViolationReason {
+    /// Not approved under the `review` check; `status` is its current verdict.
+    Unreviewed { status: String },
+    /// Currently rejected under `check`.
+    Rejected { check: String },
+    /// A path rule wants more distinct approvers under `check`.
+    MissingApprovals {
+        check: String,
+        required: usize,
+        approvals: usize,
+    },
+    /// Approved, but not by any of the path's CODEOWNERS `owners`.
+    MissingOwnerReview { owners: Vec<String> },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Violation {
+    pub file: String,
+    pub line: u32,
+    pub fingerprint: String,
+    #[serde(flatten)]
+    pub reason: ViolationReason,
+}
+
+/// A `[[policy.rules]]` entry with its globs compiled and its verdicts folded.
+pub struct RuleState {
+    pub check: String,
+    pub required: usize,
+    paths: Option<GlobSet>,
+    /// Fingerprints with enough approvals under the rule's check.
+    approved: HashSet<String>,
+    /// Current approvers and whether anyone currently rejects, per fingerprint.
+    tallies: HashMap<String, (usize, bool)>,
+}
+
+impl RuleState {
+    pub fn applies_to(&self, path: &str) -> bool {
+        self.paths.as_ref().is_none_or(|paths| paths.is_match(path))
+    }
+
+    pub fn approved_hashes(&self) -> &HashSet<String> {
+        &self.approved
+    }
+
+    /// The rule's verdict on a change whose fingerprint is `fingerprint`.
+    /// `covered` says whether approved blocks around the change already
+    /// satisfy the rule.
+    pub fn violation(&self, fingerprint: &str, covered: bool) -> Option<ViolationReason> {
+        let (approvals, rejected) = self.tallies.get(fingerprint).copied().unwrap_or_default();
+        if rejected {
+            return Some(ViolationReason::Rejected {
+                check: self.check.clone(),
+            });
+        }
+        if covered || approvals >= self.required {
+            return None;
+        }
+        Some(ViolationReason::MissingApprovals {
+            check: self.check.clone(),
+            required: self.required,
+            approvals,
+        })
+    }
+}
+
+/// Compiles `[[policy.rules]]` against the counted ledger records. `tags`
+/// is the `[tags]` table the rules' `tags` refer to.
+pub fn rule_states(
+    policy: &PolicyConfig,
+    tags: &BTreeMap<String, Vec<String>>,
+    records: &[Record],
+) -> Result<Vec<RuleState>> {
+    if policy.non_blocking_verdicts.contains(&Verdict::Rejected) {
+        bail!("[policy] non_blocking_verdicts cannot include rejected");
+    }
+    policy
+        .rules
+        .iter()
+        .map(|rule| rule_state(rule, tags, records))
+        .collect()
+}
+
+fn rule_state(
+    rule: &PolicyRule,
+    tags: &BTreeMap<String, Vec<String>>,
+    records: &[Record],
+) -> Result<RuleState> {
+    let mut patterns = rule.paths.clone();
+    for tag in &rule.tags {
+        let Some(tag_paths) = tags.get(tag) else {
+            bail!("[[policy.rules]] names tag {tag}, which [tags] does not define");
+        };
+        patterns.extend(tag_paths.iter().cloned());
+    }
+    let paths = if patterns.is_empty() && rule.tags.is_empty() {
+        None
+    } else {
+        Some(path_globs(&patterns)?)
+    };
+    let required = rule.approvals.max(1);
+    let verdicts = quorum_verdicts(records, &rule.check, required);
+    Ok(RuleState {
+        check: rule.check.clone(),
+        required,
+        paths,
+        approved: approved_hashes_from_verdicts(&verdicts),
+        tallies: approval_tallies(records, &rule.check, required),
+    })
+}
+
+fn approval_tallies(
+    records: &[Record],
+    check: &str,
+    required: usize,
+) -> HashMap<String, (usize, bool)> {
+    let mut sorted: Vec<&Record> = records
+        .iter()
+        .filter(|record| record.kind.is_verdict() && record.check == check)
+        .collect();
+    sorted.sort_by_key(|record| record.timestamp);
+    let mut latest: HashMap<&str, HashMap<String, &Record>> = HashMap::new();
+    for record in sorted {
+        latest
+            .entry(record.fingerprint.as_str())
+            .or_default()
+            .insert(record.identity.label(), record);
+    }
+    latest
+        .into_iter()
+        .map(|(fingerprint, by_identity)| {
+            let approvals = by_identity
+                .values()
+                .filter(|record| {
+                    record.verdict == Verdict::Approved
+                        && record.identity.counts_toward_quorum(required)
+                })
+                .count();
+            let rejected = by_identity
+                .values()
+                .any(|record| record.verdict == Verdict::Rejected);
+            (fingerprint.to_string(), (approvals, rejected))
+        })
+        .collect()
+}
+
+fn is_lib_rs(path: &str) -> bool {
+    path.ends_with("/lib.rs") || path == "lib.rs"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::{BlockState, Identity, RecordKind, RepoRef, VcsSystem};
+
+    fn verdict(fingerprint: &str, check: &str, email: &str, verdict: Verdict) -> Record {
+        Record {
+            id: format!("{fingerprint}-{check}-{email}"),
+            version: 1,
+            ..Record::verdict(
+                fingerprint.to_string(),
+                check.to_string(),
+                verdict,
+                Identity::Email {
+                    email: email.to_string(),
+                },
+                RepoRef::Vcs {
+                    system: VcsSystem::Git,
+                    revision: "deadbeef".to_string(),
+                },
+                BlockState::Committed,
+                1,
+            )
+        }
+    }
+
+    fn agent_verdict(fingerprint: &str, check: &str, name: &str, verdict: Verdict) -> Record {
+        Record {
+            id: format!("{fingerprint}-{check}-agent-{name}"),
+            identity: Identity::Agent {
+                name: name.to_string(),
+                model: None,
+                version: None,
+            },
+            ..self::verdict(fingerprint, check, name, verdict)
+        }
+    }
+
+    fn revocation(of: &Record, email: &str) -> Record {
+        Record {
+            id: format!("revoke-{}-{email}", of.id),
+            kind: RecordKind::Revocation,
+            check: RecordKind::Revocation.as_str().to_string(),
+            verdict: Verdict::Comment,
+            timestamp: 2,
+            revocation: Some(crate::store::Revocation {
+                record: of.id.clone(),
+            }),
+            ..verdict(&of.fingerprint, "revocation", email, Verdict::Comment)
+        }
+    }
+
+    #[test]
+    fn revoked_verdicts_stop_counting() {
+        let mut question = verdict("fp", "review", "a@example.com", Verdict::Question);
+        question.id = "question".to_string();
+        let approval = verdict("fp", "review", "a@example.com", Verdict::Approved);
+        let policy = PolicyConfig::default();
+
+        // Only the author can take a verdict back.
+        let records = vec![
+            question.clone(),
+            approval.clone(),
+            revocation(&approval, "b@example.com"),
+        ];
+        let state = review_state(&counted_records(records, &policy), &policy, None);
+        assert_eq!(state.get("fp"), Some(&Verdict::Approved));
+
+        let records = vec![
+            question,
+            approval.clone(),
+            revocation(&approval, "a@example.com"),
+        ];
+        let state = review_state(&counted_records(records, &policy), &policy, None);
+        assert_eq!(state.get("fp"), Some(&Verdict::Question));
+    }
+
+    fn security_rule() -> PolicyConfig {
+        PolicyConfig {
+            rules: vec![PolicyRule {
+                paths: vec!["src/crypto/**".to_string()],
+                tags: Vec::new(),
+                check: "security".to_string(),
+                approvals: 2,
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn rules_count_distinct_approvers_under_their_check() {
+        let records = vec![
+            verdict("fp", "security", "a@example.com", Verdict::Approved),
+            verdict("fp", "review", "b@example.com", Verdict::Approved),
+        ];
+        let rules = rule_states(&security_rule(), &BTreeMap::new(), &records).unwrap();
+        let rule = &rules[0];
+        assert!(rule.applies_to("src/crypto/aes.rs"));
+        assert!(!rule.applies_to("src/lib.rs"));
+        assert_eq!(
+            rule.violation("fp", false),
+            Some(ViolationReason::MissingApprovals {
+                check: "security".to_string(),
+                required: 2,
+                approvals: 1,
+            })
+        );
+
+        let mut records = records;
+        records.push(verdict(
+            "fp",
+            "security",
+            "c@example.com",
+            Verdict::Approved,
+        ));
+        let rules = rule_states(&security_rule(), &BTreeMap::new(), &records).unwrap();
+        assert_eq!(rules[0].violation("fp", false), None);
+        assert!(rules[0].approved_hashes().contains("fp"));
+    }
+
+    #[test]
+    fn agent_approvals_do_not_make_a_quorum() {
+        // One person plus agents they can name freely is still one approver.
+        let policy = PolicyConfig {
+            required_approvals: 2,
+            ..Default::default()
+        };
+        let mut records = vec![
+            verdict("fp", "review", "a@example.com", Verdict::Approved),
+            agent_verdict("fp", "review", "a-bot", Verdict::Approved),
+        ];
+        let state = review_state(&counted_records(records.clone(), &policy), &policy, None);
+        assert_eq!(state.get("fp"), None);
+
+        records.push(verdict("fp", "review", "b@example.com", Verdict::Approved));
+        let state = review_state(&counted_records(records, &policy), &policy, None);
+        assert_eq!(state.get("fp"), Some(&Verdict::Approved));
+
+        let records = vec![
+            verdict("fp", "security", "a@example.com", Verdict::Approved),
+            agent_verdict("fp", "security", "a-bot", Verdict::Approved),
+        ];
+        let rules = rule_states(&security_rule(), &BTreeMap::new(), &records).unwrap();
+        assert_eq!(
+            rules[0].violation("fp", false),
+            Some(ViolationReason::MissingApprovals {
+                check: "security".to_string(),
+                required: 2,
+                approvals: 1,
+            })
+        );
+        assert!(!rules[0].approved_hashes().contains("fp"));
+    }
+
+    #[test]
+    fn rules_cover_the_paths_of_their_tags() {
+        let policy = PolicyConfig {
+            rules: vec![PolicyRule {
+                paths: Vec::new(),
+                tags: vec!["security".to_string()],
+                check: "security".to_string(),
+                approvals: 1,
+            }],
+            ..Default::default()
+        };
+        let tags = BTreeMap::from([("security".to_string(), vec!["src/auth/**".to_string()])]);
+        let rules = rule_states(&policy, &tags, &[]).unwrap();
+        assert!(rules[0].applies_to("src/auth/login.rs"));
+        assert!(!rules[0].applies_to("src/lib.rs"));
+
+        assert!(rule_states(&policy, &BTreeMap::new(), &[]).is_err());
+    }
+
+    #[test]
+    fn a_rejection_fails_the_rule_despite_approvals() {
+        let records = vec![
+            verdict("fp", "security", "a@example.com", Verdict::Approved),
+            verdict("fp", "security", "b@example.com", Verdict::Approved),
+            verdict("fp", "security", "c@example.com", Verdict::Rejected),
+        ];
+        let rules = rule_states(&security_rule(), &BTreeMap::new(), &records).unwrap();
+        assert_eq!(
+            rules[0].violation("fp", true),
+            Some(ViolationReason::Rejected {
+                check: "security".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn rejected_verdicts_cannot_be_non_blocking() {
+        let policy = PolicyConfig {
+            non_blocking_verdicts: vec![Verdict::Comment, Verdict::Rejected],
+            ..Default::default()
+        };
+        assert!(rule_states(&policy, &BTreeMap::new(), &[]).is_err());
+        assert!(!is_non_blocking(&policy, &Verdict::Rejected));
+        assert!(is_non_blocking(&policy, &Verdict::Comment));
+    }
+}
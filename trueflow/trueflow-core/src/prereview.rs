@@ -0,0 +1,288 @@
+use crate::analysis::Language;
+use crate::block::Block;
+use crate::config::PrereviewConfig;
+use crate::curl;
+use crate::store::{BlockState, Identity, Prereview, Record, RepoRef, Verdict};
+use anyhow::{Context, Result, bail};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Check the annotations are recorded under. Review state only folds the
+/// `review` check, so they never approve or block anything.
+pub const PREREVIEW_CHECK: &str = "prereview";
+pub const DEFAULT_PREREVIEW_MACHINE: &str = "trueflow-prereview";
+
+/// What the annotator is sent for each block.
+#[derive(Debug, Serialize)]
+pub struct PrereviewRequest<'a> {
+    pub path: &'a str,
+    pub language: &'a Language,
+    /// The block as `review --json` shows it.
+    pub block: &'a Block,
+}
+
+/// What the annotator answers; every field may be left out.
+#[derive(Debug, Default, Deserialize)]
+pub struct PrereviewResponse {
+    #[serde(default)]
+    pub summary: Option<String>,
+    #[serde(default)]
+    pub risk: Option<u8>,
+    #[serde(default)]
+    pub suggested_verdict: Option<Verdict>,
+}
+
+/// The latest annotation on a block, as `review --json` and the TUI show it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+pub struct PrereviewHint {
+    /// Who annotated the block, e.g. `machine:trueflow-prereview`.
+    pub by: String,
+    pub timestamp: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    /// From 0 to 100.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub risk: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggested_verdict: Option<Verdict>,
+}
+
+/// Where `[prereview]` sends blocks.
+pub enum Annotator {
+    /// A shell command, request on stdin and answer on stdout.
+    Command(String),
+    /// An endpoint the request is POSTed to.
+    Http { url: String, token: Option<String> },
+}
+
+impl Annotator {
+    pub fn from_config(config: &PrereviewConfig) -> Result<Self> {
+        let non_empty = |value: &Option<String>| {
+            value
+                .as_deref()
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(str::to_string)
+        };
+        match (non_empty(&config.command), non_empty(&config.url)) {
+            (Some(_), Some(_)) => bail!("Set either [prereview] command or url, not both"),
+            (Some(command), None) => Ok(Annotator::Command(command)),
+            (None, Some(url)) => {
+                let token = match config.token_env.as_deref() {
+                    Some(token_env) => Some(
+                        std::env::var(token_env)
+                            .ok()
+                            .filter(|token| !token.trim().is_empty())
+                            .with_context(|| format!("Set {token_env} to the annotator's token"))?,
+                    ),
+                    None => None,
+                };
+                Ok(Annotator::Http { url, token })
+            }
+            (None, None) => bail!("Set [prereview] command or url to run pre-review"),
+        }
+    }
+
+    /// Sends `request` and parses the answer.
+    pub fn annotate(&self, request: &PrereviewRequest) -> Result<PrereviewResponse> {
+        let body = serde_json::to_string(request)?;
+        let output = match self {
+            Annotator::Command(command) => run_command(command, &body)?,
+            Annotator::Http { url, token } => {
+                let mut headers = vec![
+                    "Content-Type: application/json".to_string(),
+                    "Accept: application/json".to_string(),
+                ];
+                if let Some(token) = token {
+                    headers.push(format!("Authorization: Bearer {token}"));
+                }
+                curl::request("POST", url, &headers, Some(&body))?
+            }
+        };
+        let response: PrereviewResponse = serde_json::from_slice(&output).with_context(|| {
+            format!(
+                "The annotator's answer is not valid JSON: {}",
+                String::from_utf8_lossy(&output).trim()
+            )
+        })?;
+        if let Some(risk) = response.risk
+            && risk > 100
+        {
+            bail!("Risk must be between 0 and 100, got {}", risk);
+        }
+        Ok(response)
+    }
+}
+
+fn run_command(command: &str, input: &str) -> Result<Vec<u8>> {
+    let mut child = Command::new("sh")
+        .args(["-c", command])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to run `{command}`"))?;
+    child
+        .stdin
+        .take()
+        .context("Failed to open stdin")?
+        .write_all(input.as_bytes())?;
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        bail!(
+            "`{command}` failed ({}): {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(output.stdout)
+}
+
+/// The identity annotations are recorded under: `[prereview] machine`.
+pub fn prereview_identity(config: &PrereviewConfig) -> Identity {
+    Identity::Machine {
+        name: config
+            .machine
+            .as_deref()
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .unwrap_or(DEFAULT_PREREVIEW_MACHINE)
+            .to_string(),
+        version: None,
+    }
+}
+
+/// A `comment` under the `prereview` check holding `response` for `block`.
+pub fn prereview_record(
+    identity: &Identity,
+    repo_ref: RepoRef,
+    block_state: BlockState,
+    path: &str,
+    block: &Block,
+    response: PrereviewResponse,
+    now: i64,
+) -> Record {
+    Record {
+        path_hint: Some(path.to_string()),
+        line_hint: Some(block.start_line as u32),
+        note: response
+            .summary
+            .map(|summary| summary.trim().to_string())
+            .filter(|summary| !summary.is_empty()),
+        prereview: Some(Prereview {
+            risk: response.risk,
+            suggested_verdict: response.suggested_verdict,
+        }),
+        ..Record::verdict(
+            block.hash.clone(),
+            PREREVIEW_CHECK.to_string(),
+            Verdict::Comment,
+            identity.clone(),
+            repo_ref,
+            block_state,
+            now,
+        )
+    }
+}
+
+pub fn is_prereview(record: &Record) -> bool {
+    record.kind.is_verdict() && record.check == PREREVIEW_CHECK
+}
+
+/// The latest annotation per fingerprint.
+pub fn latest_hints(records: &[Record]) -> HashMap<String, PrereviewHint> {
+    let mut hints: HashMap<String, PrereviewHint> = HashMap::new();
+    for record in records.iter().filter(|record| is_prereview(record)) {
+        if hints
+            .get(&record.fingerprint)
+            .is_some_and(|hint| hint.timestamp > record.timestamp)
+        {
+            continue;
+        }
+        let prereview = record.prereview.clone().unwrap_or_default();
+        hints.insert(
+            record.fingerprint.clone(),
+            PrereviewHint {
+                by: record.identity.label(),
+                timestamp: record.timestamp,
+                summary: record.note.clone(),
+                risk: prereview.risk,
+                suggested_verdict: prereview.suggested_verdict,
+            },
+        );
+    }
+    hints
+}
+
+/// "Pre-review (machine:bot): risk 70, suggests rejected. Unchecked unwrap"
+pub fn hint_row(hint: &PrereviewHint) -> String {
+    let mut parts = Vec::new();
+    if let Some(risk) = hint.risk {
+        parts.push(format!("risk {risk}"));
+    }
+    if let Some(verdict) = &hint.suggested_verdict {
+        parts.push(format!("suggests {verdict}"));
+    }
+    let mut row = format!("Pre-review ({})", hint.by);
+    if !parts.is_empty() {
+        row.push_str(&format!(": {}", parts.join(", ")));
+    }
+    if let Some(summary) = &hint.summary {
+        row.push_str(if parts.is_empty() { ": " } else { ". " });
+        row.push_str(summary.lines().next().unwrap_or_default());
+    }
+    row
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::BlockKind;
+    use crate::store::VcsSystem;
+
+    fn record(at: i64, response: PrereviewResponse) -> Record {
+        let block = Block::new("fn a() {}".to_string(), BlockKind::Function, 0, 1);
+        prereview_record(
+            &prereview_identity(&PrereviewConfig::default()),
+            RepoRef::Vcs {
+                system: VcsSystem::Git,
+                revision: "deadbeef".to_string(),
+            },
+            BlockState::Committed,
+            "src/a.rs",
+            &block,
+            response,
+            at,
+        )
+    }
+
+    #[test]
+    fn the_latest_annotation_wins() {
+        let old = record(
+            1,
+            PrereviewResponse {
+                summary: Some("Looks fine".to_string()),
+                risk: Some(10),
+                suggested_verdict: Some(Verdict::Approved),
+            },
+        );
+        let new = record(
+            2,
+            PrereviewResponse {
+                summary: Some("Unchecked unwrap\nMore detail".to_string()),
+                risk: Some(70),
+                suggested_verdict: Some(Verdict::Rejected),
+            },
+        );
+        let hints = latest_hints(&[new.clone(), old]);
+        let hint = &hints[&new.fingerprint];
+        assert_eq!(hint.risk, Some(70));
+        assert_eq!(
+            hint_row(hint),
+            "Pre-review (machine:trueflow-prereview): risk 70, suggests rejected. Unchecked unwrap"
+        );
+    }
+}
@@ -0,0 +1,210 @@
+use crate::config::PublishConfig;
+use crate::curl;
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+const GITHUB_API: &str = "https://api.github.com";
+const GITLAB_API: &str = "https://gitlab.com/api/v4";
+
+/// Code host `publish` posts to: `[publish] provider`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Provider {
+    #[default]
+    GitHub,
+    GitLab,
+    Gitea,
+}
+
+impl Provider {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Provider::GitHub => "github",
+            Provider::GitLab => "gitlab",
+            Provider::Gitea => "gitea",
+        }
+    }
+
+    fn default_token_env(&self) -> &'static str {
+        match self {
+            Provider::GitHub => "GITHUB_TOKEN",
+            Provider::GitLab => "GITLAB_TOKEN",
+            Provider::Gitea => "GITEA_TOKEN",
+        }
+    }
+}
+
+/// A review note anchored to a line of the new side of the diff.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comment {
+    pub file: String,
+    pub line: u32,
+    pub body: String,
+}
+
+/// The pull (or merge) request comments go to, and the commits its diff spans.
+#[derive(Debug, Clone)]
+pub struct PullRequest {
+    pub number: u64,
+    pub base_sha: String,
+    pub head_sha: String,
+}
+
+pub trait Publisher {
+    fn publish(&self, pr: &PullRequest, comments: &[Comment]) -> Result<()>;
+}
+
+/// The publisher `[publish]` selects, with its token read from the environment.
+pub fn publisher(config: &PublishConfig) -> Result<Box<dyn Publisher>> {
+    let provider = config.provider;
+    let repo = config
+        .repo
+        .clone()
+        .context("Set [publish] repo to the repository path, e.g. \"org/app\"")?;
+    let token_env = config
+        .token_env
+        .as_deref()
+        .unwrap_or(provider.default_token_env());
+    let token = std::env::var(token_env)
+        .ok()
+        .filter(|token| !token.trim().is_empty())
+        .with_context(|| format!("Set {token_env} to a {} API token", provider.as_str()))?;
+    let api = match (provider, config.url.as_deref()) {
+        (_, Some(url)) => url.trim_end_matches('/').to_string(),
+        (Provider::GitHub, None) => GITHUB_API.to_string(),
+        (Provider::GitLab, None) => GITLAB_API.to_string(),
+        (Provider::Gitea, None) => {
+            bail!("Set [publish] url to the Gitea API, e.g. https://gitea.example.com/api/v1")
+        }
+    };
+    Ok(match provider {
+        Provider::GitHub => Box::new(GitHub { api, repo, token }),
+        Provider::GitLab => Box::new(GitLab { api, repo, token }),
+        Provider::Gitea => Box::new(Gitea { api, repo, token }),
+    })
+}
+
+/// Review comments on a GitHub pull request.
+struct GitHub {
+    api: String,
+    repo: String,
+    token: String,
+}
+
+impl Publisher for GitHub {
+    fn publish(&self, pr: &PullRequest, comments: &[Comment]) -> Result<()> {
+        let url = format!(
+            "{}/repos/{}/pulls/{}/comments",
+            self.api, self.repo, pr.number
+        );
+        let headers = [
+            format!("Authorization: Bearer {}", self.token),
+            "Accept: application/vnd.github+json".to_string(),
+        ];
+        for comment in comments {
+            let body = json!({
+                "body": comment.body,
+                "commit_id": pr.head_sha,
+                "path": comment.file,
+                "line": comment.line,
+                "side": "RIGHT",
+            });
+            post_json(&url, &headers, &body)?;
+        }
+        Ok(())
+    }
+}
+
+/// Diff discussions on a GitLab merge request.
+struct GitLab {
+    api: String,
+    repo: String,
+    token: String,
+}
+
+impl Publisher for GitLab {
+    fn publish(&self, pr: &PullRequest, comments: &[Comment]) -> Result<()> {
+        let project: String = url::form_urlencoded::byte_serialize(self.repo.as_bytes()).collect();
+        let url = format!(
+            "{}/projects/{project}/merge_requests/{}/discussions",
+            self.api, pr.number
+        );
+        let headers = [format!("PRIVATE-TOKEN: {}", self.token)];
+        for comment in comments {
+            let body = json!({
+                "body": comment.body,
+                "position": {
+                    "position_type": "text",
+                    "base_sha": pr.base_sha,
+                    "start_sha": pr.base_sha,
+                    "head_sha": pr.head_sha,
+                    "old_path": comment.file,
+                    "new_path": comment.file,
+                    "new_line": comment.line,
+                },
+            });
+            post_json(&url, &headers, &body)?;
+        }
+        Ok(())
+    }
+}
+
+/// One review holding every comment on a Gitea pull request.
+struct Gitea {
+    api: String,
+    repo: String,
+    token: String,
+}
+
+impl Publisher for Gitea {
+    fn publish(&self, pr: &PullRequest, comments: &[Comment]) -> Result<()> {
+        let url = format!(
+            "{}/repos/{}/pulls/{}/reviews",
+            self.api, self.repo, pr.number
+        );
+        let headers = [format!("Authorization: token {}", self.token)];
+        let comments: Vec<Value> = comments
+            .iter()
+            .map(|comment| {
+                json!({
+                    "path": comment.file,
+                    "new_position": comment.line,
+                    "body": comment.body,
+                })
+            })
+            .collect();
+        let body = json!({
+            "event": "COMMENT",
+            "commit_id": pr.head_sha,
+            "body": "",
+            "comments": comments,
+        });
+        post_json(&url, &headers, &body)
+    }
+}
+
+/// POSTs `body` as JSON.
+fn post_json(url: &str, headers: &[String], body: &Value) -> Result<()> {
+    let mut headers = headers.to_vec();
+    headers.push("Content-Type: application/json".to_string());
+    curl::request("POST", url, &headers, Some(&serde_json::to_string(body)?))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gitea_needs_an_api_url() {
+        let config = PublishConfig {
+            provider: Provider::Gitea,
+            repo: Some("org/app".to_string()),
+            token_env: Some("PATH".to_string()),
+            ..Default::default()
+        };
+        let err = publisher(&config).err().map(|err| err.to_string());
+        assert!(err.is_some_and(|err| err.contains("[publish] url")));
+    }
+}
@@ -0,0 +1,136 @@
+use crate::store::{Answer, Identity, Record, RecordKind, RepoRef, Verdict};
+use std::collections::{HashMap, HashSet};
+
+/// Tag on review blocks with a question still waiting for an answer.
+pub const QUESTION_TAG: &str = "open-question";
+
+/// Question verdicts nobody has answered or resolved yet, oldest first. A
+/// newer verdict from the asker on the same block and check also closes
+/// their question.
+pub fn open_questions(records: &[Record]) -> Vec<&Record> {
+    let answered: HashSet<&str> = records
+        .iter()
+        .filter(|record| record.kind == RecordKind::Answer)
+        .filter_map(|record| record.answer.as_ref())
+        .map(|answer| answer.question.as_str())
+        .collect();
+
+    let mut verdicts: Vec<&Record> = records
+        .iter()
+        .filter(|record| record.kind.is_verdict())
+        .collect();
+    verdicts.sort_by_key(|record| record.timestamp);
+    let mut latest: HashMap<(&str, &str, String), &str> = HashMap::new();
+    for record in &verdicts {
+        latest.insert(
+            (&record.fingerprint, &record.check, record.identity.label()),
+            &record.id,
+        );
+    }
+
+    verdicts
+        .into_iter()
+        .filter(|record| record.verdict == Verdict::Question)
+        .filter(|record| !answered.contains(record.id.as_str()))
+        .filter(|record| {
+            latest.get(&(
+                record.fingerprint.as_str(),
+                record.check.as_str(),
+                record.identity.label(),
+            )) == Some(&record.id.as_str())
+        })
+        .collect()
+}
+
+/// A reply to (or, without a note, a plain resolution of) `question`.
+pub fn answer_record(
+    question: &Record,
+    answered_by: &Identity,
+    repo_ref: RepoRef,
+    note: Option<String>,
+    timestamp: i64,
+) -> Record {
+    Record {
+        path_hint: question.path_hint.clone(),
+        line_hint: question.line_hint,
+        note,
+        answer: Some(Answer {
+            question: question.id.clone(),
+        }),
+        ..Record::non_verdict(
+            RecordKind::Answer,
+            question.fingerprint.clone(),
+            answered_by.clone(),
+            repo_ref,
+            timestamp,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::{BlockState, VcsSystem};
+
+    fn repo_ref() -> RepoRef {
+        RepoRef::Vcs {
+            system: VcsSystem::Git,
+            revision: "deadbeef".to_string(),
+        }
+    }
+
+    fn verdict(id: &str, fingerprint: &str, email: &str, verdict: Verdict, at: i64) -> Record {
+        Record {
+            id: id.to_string(),
+            path_hint: Some("src/lib.rs".to_string()),
+            line_hint: Some(1),
+            note: Some("why?".to_string()),
+            ..Record::verdict(
+                fingerprint.to_string(),
+                "review".to_string(),
+                verdict,
+                Identity::Email {
+                    email: email.to_string(),
+                },
+                repo_ref(),
+                BlockState::Committed,
+                at,
+            )
+        }
+    }
+
+    fn ids(records: &[Record]) -> Vec<String> {
+        open_questions(records)
+            .into_iter()
+            .map(|record| record.id.clone())
+            .collect()
+    }
+
+    #[test]
+    fn answers_close_questions() {
+        let first = verdict("q1", "a", "alice@example.com", Verdict::Question, 1);
+        let second = verdict("q2", "b", "alice@example.com", Verdict::Question, 2);
+        let bob = Identity::Email {
+            email: "bob@example.com".to_string(),
+        };
+        let reply = answer_record(&first, &bob, repo_ref(), Some("because".to_string()), 3);
+        assert_eq!(reply.fingerprint, "a");
+        assert_eq!(reply.path_hint.as_deref(), Some("src/lib.rs"));
+
+        let records = vec![first.clone(), second.clone()];
+        assert_eq!(ids(&records), vec!["q1", "q2"]);
+        assert_eq!(ids(&[first, second, reply]), vec!["q2"]);
+    }
+
+    #[test]
+    fn a_newer_verdict_from_the_asker_closes_the_question() {
+        let records = vec![
+            verdict("q1", "a", "alice@example.com", Verdict::Question, 1),
+            verdict("q2", "b", "alice@example.com", Verdict::Question, 1),
+            verdict("v1", "a", "alice@example.com", Verdict::Approved, 2),
+            // Someone else's verdict leaves alice's question open.
+            verdict("v2", "b", "bob@example.com", Verdict::Approved, 2),
+        ];
+        assert_eq!(ids(&records), vec!["q2"]);
+    }
+}
@@ -0,0 +1,234 @@
+//! The JSON schema of review records, generated from `store::Record`, and a
+//! validator for the part of JSON Schema that schema uses.
+
+use crate::store::Record;
+use regex::Regex;
+use schemars::generate::SchemaSettings;
+use serde_json::Value;
+
+/// The draft 2020-12 schema of a serialized record.
+pub fn record_schema() -> Value {
+    let schema = SchemaSettings::draft2020_12()
+        .for_serialize()
+        .into_generator()
+        .into_root_schema_for::<Record>();
+    serde_json::to_value(schema).unwrap_or(Value::Bool(true))
+}
+
+/// Every way `value` breaks `schema`, as `path: problem` lines. Supports
+/// `$ref` into `$defs`, `type`, `const`, `enum`, `anyOf`, `oneOf`, object
+/// and array keywords, `minLength`, `pattern`, `minimum`, `maximum` and the
+/// `email` format; other keywords are ignored.
+pub fn validate(schema: &Value, value: &Value) -> Vec<String> {
+    let mut errors = Vec::new();
+    check(schema, schema, value, "", &mut errors);
+    errors
+}
+
+fn check(root: &Value, schema: &Value, value: &Value, path: &str, errors: &mut Vec<String>) {
+    let at = if path.is_empty() { "record" } else { path };
+    let schema = match schema {
+        Value::Bool(true) => return,
+        Value::Bool(false) => {
+            errors.push(format!("{at}: not allowed"));
+            return;
+        }
+        Value::Object(schema) => schema,
+        _ => return,
+    };
+
+    if let Some(target) = schema.get("$ref").and_then(Value::as_str) {
+        match target
+            .strip_prefix('#')
+            .and_then(|pointer| root.pointer(pointer))
+        {
+            Some(resolved) => check(root, resolved, value, path, errors),
+            None => errors.push(format!("{at}: unresolvable reference {target}")),
+        }
+    }
+
+    if let Some(types) = schema.get("type") {
+        let allowed: Vec<&str> = match types {
+            Value::String(name) => vec![name.as_str()],
+            Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !allowed.is_empty() && !allowed.iter().any(|name| has_type(value, name)) {
+            errors.push(format!("{at}: expected {}", allowed.join(" or ")));
+            return;
+        }
+    }
+
+    if let Some(expected) = schema.get("const")
+        && expected != value
+    {
+        errors.push(format!("{at}: expected {expected}"));
+    }
+    if let Some(options) = schema.get("enum").and_then(Value::as_array)
+        && !options.contains(value)
+    {
+        errors.push(format!("{at}: {value} is not one of the allowed values"));
+    }
+    for keyword in ["anyOf", "oneOf"] {
+        let Some(branches) = schema.get(keyword).and_then(Value::as_array) else {
+            continue;
+        };
+        let outcomes: Vec<Vec<String>> = branches
+            .iter()
+            .map(|branch| validate_at(root, branch, value, path))
+            .collect();
+        let matching = outcomes.iter().filter(|found| found.is_empty()).count();
+        if matching == 0 {
+            // The closest shape's own errors say more than "no match".
+            if let Some(closest) = outcomes.into_iter().min_by_key(Vec::len) {
+                errors.extend(closest);
+            }
+        } else if matching > 1 && keyword == "oneOf" {
+            errors.push(format!("{at}: matches more than one shape"));
+        }
+    }
+
+    match value {
+        Value::Object(fields) => {
+            let properties = schema.get("properties").and_then(Value::as_object);
+            for name in schema
+                .get("required")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+            {
+                if !fields.contains_key(name) {
+                    errors.push(format!("{at}: missing {name}"));
+                }
+            }
+            for (name, field) in fields {
+                let field_path = format!("{path}/{name}");
+                match properties.and_then(|properties| properties.get(name)) {
+                    Some(field_schema) => check(root, field_schema, field, &field_path, errors),
+                    None => match schema.get("additionalProperties") {
+                        Some(Value::Bool(false)) => {
+                            errors.push(format!("{at}: unknown field {name}"));
+                        }
+                        Some(extra) => check(root, extra, field, &field_path, errors),
+                        None => {}
+                    },
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(item_schema) = schema.get("items") {
+                for (index, item) in items.iter().enumerate() {
+                    check(root, item_schema, item, &format!("{path}/{index}"), errors);
+                }
+            }
+        }
+        Value::String(text) => {
+            if let Some(min) = schema.get("minLength").and_then(Value::as_u64)
+                && (text.chars().count() as u64) < min
+            {
+                errors.push(format!("{at}: shorter than {min} character(s)"));
+            }
+            if let Some(pattern) = schema.get("pattern").and_then(Value::as_str)
+                && let Ok(regex) = Regex::new(pattern)
+                && !regex.is_match(text)
+            {
+                errors.push(format!("{at}: does not match {pattern}"));
+            }
+            if schema.get("format").and_then(Value::as_str) == Some("email") && !is_email(text) {
+                errors.push(format!("{at}: not an email address"));
+            }
+        }
+        Value::Number(number) => {
+            let number = number.as_f64().unwrap_or_default();
+            if let Some(min) = schema.get("minimum").and_then(Value::as_f64)
+                && number < min
+            {
+                errors.push(format!("{at}: below the minimum {min}"));
+            }
+            if let Some(max) = schema.get("maximum").and_then(Value::as_f64)
+                && number > max
+            {
+                errors.push(format!("{at}: above the maximum {max}"));
+            }
+        }
+        _ => {}
+    }
+}
+
+fn validate_at(root: &Value, schema: &Value, value: &Value, path: &str) -> Vec<String> {
+    let mut errors = Vec::new();
+    check(root, schema, value, path, &mut errors);
+    errors
+}
+
+fn has_type(value: &Value, name: &str) -> bool {
+    match name {
+        "null" => value.is_null(),
+        "boolean" => value.is_boolean(),
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64(),
+        _ => true,
+    }
+}
+
+fn is_email(text: &str) -> bool {
+    text.split_once('@')
+        .is_some_and(|(local, domain)| !local.is_empty() && !domain.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn record() -> Value {
+        json!({
+            "id": "r1",
+            "version": 1,
+            "fingerprint": "abc",
+            "check": "review",
+            "verdict": "approved",
+            "identity": { "type": "email", "email": "a@example.com" },
+            "repo_ref": { "type": "vcs", "system": "git", "revision": "deadbeef" },
+            "block_state": "committed",
+            "timestamp": 10,
+            "path_hint": null,
+            "line_hint": null,
+            "note": null,
+            "tags": null
+        })
+    }
+
+    #[test]
+    fn a_written_record_is_valid() {
+        assert_eq!(validate(&record_schema(), &record()), Vec::<String>::new());
+    }
+
+    #[test]
+    fn problems_name_the_field() {
+        let mut value = record();
+        value["verdict"] = json!("maybe");
+        value["identity"]["email"] = json!("nobody");
+        value["surprise"] = json!(true);
+        value
+            .as_object_mut()
+            .map(|fields| fields.remove("timestamp"));
+
+        let errors = validate(&record_schema(), &value);
+
+        assert!(
+            errors.iter().any(|error| error.starts_with("/verdict:")),
+            "{errors:?}"
+        );
+        assert!(
+            errors.iter().any(|error| error.contains("not an email")),
+            "{errors:?}"
+        );
+        assert!(errors.contains(&"record: unknown field surprise".to_string()));
+        assert!(errors.contains(&"record: missing timestamp".to_string()));
+    }
+}
@@ -0,0 +1,102 @@
+use crate::store::{Identity, Record, RecordKind, RepoRef, Resolution};
+use std::collections::HashSet;
+
+/// Ids of verdicts whose feedback has been dealt with: resolved, or for
+/// questions, answered.
+pub fn resolved_ids(records: &[Record]) -> HashSet<&str> {
+    records
+        .iter()
+        .filter_map(|record| match record.kind {
+            RecordKind::Resolution => record.resolution.as_ref().map(|r| r.record.as_str()),
+            RecordKind::Answer => record.answer.as_ref().map(|a| a.question.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Marks `verdict` as dealt with, e.g. "fixed in abc123".
+pub fn resolution_record(
+    verdict: &Record,
+    resolved_by: &Identity,
+    repo_ref: RepoRef,
+    note: Option<String>,
+    timestamp: i64,
+) -> Record {
+    Record {
+        path_hint: verdict.path_hint.clone(),
+        line_hint: verdict.line_hint,
+        note,
+        resolution: Some(Resolution {
+            record: verdict.id.clone(),
+        }),
+        ..Record::non_verdict(
+            RecordKind::Resolution,
+            verdict.fingerprint.clone(),
+            resolved_by.clone(),
+            repo_ref,
+            timestamp,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::question::answer_record;
+    use crate::store::{BlockState, VcsSystem, Verdict};
+
+    fn repo_ref() -> RepoRef {
+        RepoRef::Vcs {
+            system: VcsSystem::Git,
+            revision: "deadbeef".to_string(),
+        }
+    }
+
+    fn bob() -> Identity {
+        Identity::Email {
+            email: "bob@example.com".to_string(),
+        }
+    }
+
+    fn verdict(id: &str, verdict: Verdict) -> Record {
+        Record {
+            id: id.to_string(),
+            path_hint: Some("src/lib.rs".to_string()),
+            line_hint: Some(3),
+            note: Some("Use a constant".to_string()),
+            ..Record::verdict(
+                "a".to_string(),
+                "review".to_string(),
+                verdict,
+                Identity::Email {
+                    email: "alice@example.com".to_string(),
+                },
+                repo_ref(),
+                BlockState::Committed,
+                1,
+            )
+        }
+    }
+
+    #[test]
+    fn resolutions_and_answers_mark_verdicts_resolved() {
+        let rejected = verdict("r1", Verdict::Rejected);
+        let question = verdict("q1", Verdict::Question);
+        let open = verdict("r2", Verdict::Rejected);
+        let fixed = resolution_record(
+            &rejected,
+            &bob(),
+            repo_ref(),
+            Some("fixed in abc123".to_string()),
+            2,
+        );
+        assert_eq!(fixed.fingerprint, "a");
+        assert_eq!(fixed.line_hint, Some(3));
+        assert_eq!(fixed.check, "resolution");
+        let reply = answer_record(&question, &bob(), repo_ref(), None, 2);
+
+        let records = vec![rejected, question, open, fixed, reply];
+        let resolved = resolved_ids(&records);
+        assert_eq!(resolved, HashSet::from(["r1", "q1"]));
+    }
+}
@@ -0,0 +1,143 @@
+use crate::blame::FileBlame;
+use crate::block::{Block, FileState};
+use crate::commands::review::{normalize_path_str, repo_relative_path, workdir_prefix_from_root};
+use crate::config::RiskConfig;
+use crate::path_tags::path_globs;
+use crate::vcs;
+use anyhow::{Context, Result};
+use globset::GlobSet;
+use log::warn;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Points per unit of block complexity, and the most complexity can add.
+const COMPLEXITY_POINTS: u32 = 4;
+const MAX_COMPLEXITY_POINTS: u32 = 40;
+/// Points per recent commit on the block's lines, and the most churn can add.
+const CHURN_POINTS: u32 = 10;
+const MAX_CHURN_POINTS: u32 = 30;
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// How risky a block is to get wrong, from 0 to 100, and the points each
+/// part adds.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+pub struct BlockRisk {
+    /// The sum of the parts, capped at 100.
+    #[schemars(range(max = 100))]
+    pub score: u8,
+    /// From the block's control-flow complexity.
+    pub complexity: u8,
+    /// From commits in the `[risk] churn_days` window that last changed its
+    /// lines.
+    pub churn: u8,
+    /// The highest `[risk.paths]` weight matching its file.
+    pub path: u8,
+}
+
+/// `[risk]` compiled.
+pub struct RiskScorer {
+    paths: Vec<(GlobSet, u8)>,
+    /// Commits at or after this time count as churn; `None` when
+    /// `churn_days` is 0.
+    since: Option<i64>,
+}
+
+impl RiskScorer {
+    pub fn new(config: &RiskConfig) -> Result<Self> {
+        let paths = config
+            .paths
+            .iter()
+            .map(|(pattern, weight)| {
+                let globs = path_globs([pattern])
+                    .with_context(|| format!("Invalid [risk.paths] glob {pattern}"))?;
+                Ok((globs, (*weight).min(100)))
+            })
+            .collect::<Result<_>>()?;
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+        Ok(Self {
+            paths,
+            since: (config.churn_days > 0)
+                .then(|| now - i64::from(config.churn_days) * SECONDS_PER_DAY),
+        })
+    }
+
+    /// Whether scores need blame for churn.
+    pub fn counts_churn(&self) -> bool {
+        self.since.is_some()
+    }
+
+    /// Scores `block` of the file at `repo_path`; without `blame` there is no
+    /// churn.
+    pub fn score(&self, repo_path: &str, block: &Block, blame: Option<&FileBlame>) -> BlockRisk {
+        let complexity = (block.complexity * COMPLEXITY_POINTS).min(MAX_COMPLEXITY_POINTS);
+        let churn = blame
+            .zip(self.since)
+            .map(|(blame, since)| blame.churn(block, since) as u32 * CHURN_POINTS)
+            .unwrap_or_default()
+            .min(MAX_CHURN_POINTS);
+        let path = self
+            .paths
+            .iter()
+            .filter(|(globs, _)| globs.is_match(repo_path))
+            .map(|(_, weight)| *weight)
+            .max()
+            .unwrap_or_default();
+        BlockRisk {
+            score: (complexity + churn + u32::from(path)).min(100) as u8,
+            complexity: complexity as u8,
+            churn: churn as u8,
+            path,
+        }
+    }
+}
+
+/// Sets `risk` on every block of `files`, as `scan` lists them. Files are
+/// only blamed when `scorer` counts churn.
+pub fn annotate_files(files: &mut [FileState], scorer: &RiskScorer) {
+    let repo = vcs::repo_from_workdir()
+        .ok()
+        .filter(|_| scorer.counts_churn());
+    let workdir_prefix = workdir_prefix_from_root();
+    for file in files {
+        let repo_path =
+            repo_relative_path(&normalize_path_str(&file.path), workdir_prefix.as_deref());
+        let blame = repo.as_ref().and_then(|repo| {
+            let working = std::fs::read_to_string(&file.path).ok()?;
+            FileBlame::for_path(repo, &repo_path, &working)
+                .inspect_err(|err| warn!("Skipping churn for {}: {}", file.path, err))
+                .ok()
+        });
+        for block in &mut file.blocks {
+            block.risk = Some(scorer.score(&repo_path, block, blame.as_ref()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::BlockKind;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn parts_add_up_and_cap_at_one_hundred() {
+        let scorer = RiskScorer::new(&RiskConfig {
+            paths: BTreeMap::from([
+                ("src/auth/**".to_string(), 30),
+                ("src/auth/crypto.rs".to_string(), 70),
+            ]),
+            ..RiskConfig::default()
+        })
+        .unwrap();
+        let mut block = Block::new("fn a() {}".to_string(), BlockKind::Function, 0, 1);
+        block.complexity = 3;
+
+        let risk = scorer.score("src/lib.rs", &block, None);
+        assert_eq!((risk.score, risk.complexity, risk.path), (12, 12, 0));
+
+        block.complexity = 20;
+        let risk = scorer.score("src/auth/crypto.rs", &block, None);
+        assert_eq!((risk.score, risk.complexity, risk.path), (100, 40, 70));
+    }
+}
@@ -0,0 +1,383 @@
+use crate::config::StoreConfig;
+use crate::curl;
+use crate::hashing::hash_str;
+use crate::store::{FileStore, Record, ReviewStore};
+use anyhow::{Context, Result, bail};
+use log::{info, warn};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+
+const DEFAULT_REGION: &str = "us-east-1";
+
+/// The ledger as one object per record in an S3-compatible bucket. Writers
+/// only ever add their own objects, so concurrent appends cannot conflict.
+/// Reads list the prefix and download the objects the local cache lacks in
+/// one batch.
+pub struct S3Store {
+    bucket_url: String,
+    /// Key prefix of the record objects, ending in `/` unless empty.
+    prefix: String,
+    region: String,
+    credentials: Option<Credentials>,
+    cache_dir: PathBuf,
+}
+
+/// `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY`, and `AWS_SESSION_TOKEN`.
+struct Credentials {
+    access_key: String,
+    secret_key: String,
+    session_token: Option<String>,
+}
+
+/// A listed object; its ETag changes whenever its content does.
+#[derive(Debug, PartialEq, Eq)]
+struct Listed {
+    key: String,
+    etag: String,
+}
+
+impl S3Store {
+    /// Reads the bucket from `[store] url`, `prefix`, and `region`, and
+    /// credentials from the standard AWS variables; without them, requests
+    /// are unsigned.
+    pub fn open(config: &StoreConfig, files: &FileStore) -> Result<Self> {
+        let bucket_url = config
+            .url
+            .as_deref()
+            .context(
+                "Set [store] url to the bucket, e.g. \"https://s3.eu-west-1.amazonaws.com/reviews\"",
+            )?
+            .trim_end_matches('/')
+            .to_string();
+        let prefix = config.prefix.as_deref().unwrap_or("").trim_matches('/');
+        let prefix = if prefix.is_empty() {
+            String::new()
+        } else {
+            format!("{prefix}/")
+        };
+        let env = |name| {
+            std::env::var(name)
+                .ok()
+                .filter(|value: &String| !value.is_empty())
+        };
+        let credentials = match (env("AWS_ACCESS_KEY_ID"), env("AWS_SECRET_ACCESS_KEY")) {
+            (Some(access_key), Some(secret_key)) => Some(Credentials {
+                access_key,
+                secret_key,
+                session_token: env("AWS_SESSION_TOKEN"),
+            }),
+            (None, None) => None,
+            _ => bail!("Set both AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY, or neither"),
+        };
+        Ok(Self {
+            bucket_url,
+            prefix,
+            region: config
+                .region
+                .clone()
+                .unwrap_or_else(|| DEFAULT_REGION.to_string()),
+            credentials,
+            cache_dir: files.s3_cache_dir(),
+        })
+    }
+
+    /// Headers and curl options that sign requests, when there are credentials.
+    fn auth(&self) -> (Vec<String>, Vec<(&'static str, String)>) {
+        let mut headers = Vec::new();
+        let mut options = Vec::new();
+        if let Some(credentials) = &self.credentials {
+            options.push(("aws-sigv4", format!("aws:amz:{}:s3", self.region)));
+            options.push((
+                "user",
+                format!("{}:{}", credentials.access_key, credentials.secret_key),
+            ));
+            if let Some(token) = &credentials.session_token {
+                headers.push(format!("x-amz-security-token: {token}"));
+            }
+        }
+        (headers, options)
+    }
+
+    fn send(&self, method: &str, url: &str, body: Option<&str>) -> Result<Vec<u8>> {
+        let (mut headers, options) = self.auth();
+        if body.is_some() {
+            headers.push("Content-Type: application/json".to_string());
+        }
+        curl::request_with(method, url, &headers, body, &options)
+    }
+
+    fn object_url(&self, key: &str) -> String {
+        format!("{}/{}", self.bucket_url, uri_encode(key, true))
+    }
+
+    fn list_url(&self, token: Option<&str>) -> String {
+        let mut url = format!(
+            "{}?list-type=2&prefix={}",
+            self.bucket_url,
+            uri_encode(&self.prefix, false)
+        );
+        if let Some(token) = token {
+            url.push_str(&format!("&continuation-token={}", uri_encode(token, false)));
+        }
+        url
+    }
+
+    /// Keys sort by timestamp, so listing order is ledger order.
+    fn key(&self, record: &Record) -> String {
+        format!(
+            "{}{:012}-{}.json",
+            self.prefix,
+            record.timestamp.max(0),
+            object_name(&record.id)
+        )
+    }
+
+    fn list(&self) -> Result<Vec<Listed>> {
+        let mut listed = Vec::new();
+        let mut token: Option<String> = None;
+        loop {
+            let body = self.send("GET", &self.list_url(token.as_deref()), None)?;
+            let page = parse_listing(&String::from_utf8_lossy(&body))?;
+            listed.extend(page.objects);
+            match page.next {
+                Some(next) => token = Some(next),
+                None => break,
+            }
+        }
+        listed.retain(|object| object.key.ends_with(".json"));
+        listed.sort_by(|a, b| a.key.cmp(&b.key));
+        Ok(listed)
+    }
+
+    fn put(&self, record: &Record) -> Result<()> {
+        let body = serde_json::to_string(record)?;
+        self.send("PUT", &self.object_url(&self.key(record)), Some(&body))?;
+        Ok(())
+    }
+
+    /// Where `object` is cached; the name changes with its content.
+    fn cached_path(&self, object: &Listed) -> PathBuf {
+        self.cache_dir.join(format!(
+            "{}.json",
+            hash_str(&format!("{}\n{}", object.key, object.etag))
+        ))
+    }
+
+    /// Downloads the objects missing from the cache in one curl run. Failed
+    /// downloads are reported and left uncached, to be retried next read.
+    fn download_missing<'a>(&self, objects: impl IntoIterator<Item = &'a Listed>) -> Result<()> {
+        fs::create_dir_all(&self.cache_dir)?;
+        let missing: Vec<(&Listed, PathBuf)> = objects
+            .into_iter()
+            .map(|object| (object, self.cached_path(object)))
+            .filter(|(_, cached)| !cached.exists())
+            .collect();
+        if missing.is_empty() {
+            return Ok(());
+        }
+        // Into `.part` files, so an interrupted run leaves nothing to trust.
+        let downloads: Vec<(String, PathBuf)> = missing
+            .iter()
+            .map(|(object, cached)| (self.object_url(&object.key), cached.with_extension("part")))
+            .collect();
+        let (headers, options) = self.auth();
+        let statuses = curl::download_all(&downloads, &headers, &options)?;
+        for (((object, cached), (_, part)), status) in missing.iter().zip(&downloads).zip(statuses)
+        {
+            if status != 200 || fs::rename(part, cached).is_err() {
+                warn!("Failed to download {} (HTTP {})", object.key, status);
+                let _ = fs::remove_file(part);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ReviewStore for S3Store {
+    /// Objects that fail to download or aren't records are reported and
+    /// skipped, like malformed lines in the JSONL ledger.
+    fn read_history(&self) -> Result<Vec<Record>> {
+        let listed = self.list()?;
+        self.download_missing(&listed)?;
+        let mut current = HashSet::new();
+        let mut records = Vec::with_capacity(listed.len());
+        for object in &listed {
+            let cached = self.cached_path(object);
+            let Ok(body) = fs::read(&cached) else {
+                continue;
+            };
+            match serde_json::from_slice(&body) {
+                Ok(record) => records.push(record),
+                Err(err) => warn!("Skipping {}: not a review record: {}", object.key, err),
+            }
+            current.insert(cached);
+        }
+        // Objects overwritten or removed from the bucket leave cache entries
+        // behind.
+        for entry in fs::read_dir(&self.cache_dir)?.flatten() {
+            if !current.contains(&entry.path()) {
+                let _ = fs::remove_file(entry.path());
+            }
+        }
+        info!(
+            "s3 read (records={}, skipped={})",
+            records.len(),
+            listed.len() - records.len()
+        );
+        Ok(records)
+    }
+
+    fn append(&self, record: Record) -> Result<()> {
+        self.put(&record)
+    }
+
+    /// Each record is its own object, so a reader listing mid-write may see
+    /// part of the batch.
+    fn append_all(&self, records: Vec<Record>) -> Result<()> {
+        for record in &records {
+            self.put(record)?;
+        }
+        Ok(())
+    }
+
+    /// Only ever adds: records missing from the bucket are uploaded and
+    /// changed ones overwritten in place, but objects absent from `records`
+    /// stay, since other writers may have added them since the read.
+    fn rewrite(&self, records: &[Record]) -> Result<()> {
+        let listed: HashMap<String, Listed> = self
+            .list()?
+            .into_iter()
+            .map(|object| (object.key.clone(), object))
+            .collect();
+        let existing: Vec<&Listed> = records
+            .iter()
+            .filter_map(|record| listed.get(&self.key(record)))
+            .collect();
+        self.download_missing(existing)?;
+        for record in records {
+            let body = serde_json::to_string(record)?;
+            if let Some(object) = listed.get(&self.key(record))
+                && fs::read(self.cached_path(object)).is_ok_and(|cached| cached == body.as_bytes())
+            {
+                continue;
+            }
+            self.send("PUT", &self.object_url(&self.key(record)), Some(&body))?;
+        }
+        Ok(())
+    }
+}
+
+/// One page of a ListObjectsV2 response.
+struct Page {
+    objects: Vec<Listed>,
+    next: Option<String>,
+}
+
+fn parse_listing(xml: &str) -> Result<Page> {
+    let document = roxmltree::Document::parse(xml).context("Malformed bucket listing")?;
+    let child_text = |node: roxmltree::Node, name: &str| {
+        node.children()
+            .find(|child| child.has_tag_name(name))
+            .map(|child| child.text().unwrap_or_default().to_string())
+    };
+    let root = document.root_element();
+    Ok(Page {
+        objects: root
+            .children()
+            .filter(|node| node.has_tag_name("Contents"))
+            .filter_map(|contents| {
+                Some(Listed {
+                    key: child_text(contents, "Key")?,
+                    etag: child_text(contents, "ETag").unwrap_or_default(),
+                })
+            })
+            .collect(),
+        next: child_text(root, "NextContinuationToken").filter(|token| !token.is_empty()),
+    })
+}
+
+/// SigV4's URI encoding: every byte but the unreserved characters is
+/// percent-encoded, `/` included unless `keep_slash`.
+fn uri_encode(value: &str, keep_slash: bool) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            b'/' if keep_slash => encoded.push('/'),
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+/// Record ids as they are when safe in a key, else hashed.
+fn object_name(id: &str) -> String {
+    let safe = !id.is_empty()
+        && id
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | '.'));
+    if safe { id.to_string() } else { hash_str(id) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn listings_yield_keys_etags_and_the_next_token() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<ListBucketResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+  <IsTruncated>true</IsTruncated>
+  <Contents><Key>team/a&amp;b.json</Key><ETag>&quot;abc&quot;</ETag></Contents>
+  <Contents><Key>team/c.json</Key><ETag>"def"</ETag></Contents>
+  <NextContinuationToken>next/1</NextContinuationToken>
+</ListBucketResult>"#;
+        let page = parse_listing(xml).unwrap();
+        assert_eq!(
+            page.objects,
+            [
+                Listed {
+                    key: "team/a&b.json".to_string(),
+                    etag: "\"abc\"".to_string(),
+                },
+                Listed {
+                    key: "team/c.json".to_string(),
+                    etag: "\"def\"".to_string(),
+                },
+            ]
+        );
+        assert_eq!(page.next.as_deref(), Some("next/1"));
+        assert!(parse_listing("<ListBucketResult/>").unwrap().next.is_none());
+        assert!(parse_listing("<ListBucketResult><Contents>").is_err());
+    }
+
+    #[test]
+    fn keys_and_prefixes_are_uri_encoded() {
+        let store = S3Store {
+            bucket_url: "https://s3.example.com/reviews".to_string(),
+            prefix: "team a+b?#/".to_string(),
+            region: DEFAULT_REGION.to_string(),
+            credentials: None,
+            cache_dir: PathBuf::new(),
+        };
+        assert_eq!(
+            store.object_url("team a+b?#/000000000001-x~y.json"),
+            "https://s3.example.com/reviews/team%20a%2Bb%3F%23/000000000001-x~y.json"
+        );
+        assert_eq!(
+            store.list_url(Some("1/a=b")),
+            "https://s3.example.com/reviews?list-type=2&prefix=team%20a%2Bb%3F%23%2F\
+             &continuation-token=1%2Fa%3Db"
+        );
+    }
+
+    #[test]
+    fn unsafe_ids_are_hashed() {
+        assert_eq!(object_name("4f1c-9a_b.2"), "4f1c-9a_b.2");
+        assert_eq!(object_name("a/b"), hash_str("a/b"));
+        assert_eq!(object_name(""), hash_str(""));
+    }
+}
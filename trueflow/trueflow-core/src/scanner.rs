@@ -0,0 +1,880 @@
+use crate::analysis::{self, FileType, Language};
+use crate::block::{Block, BlockKind, BlockMetrics, FileState};
+use crate::block_splitter;
+use crate::config::{self, Normalization, OptimizerConfig, ScanConfig, TrueflowConfig};
+use crate::generated::{GENERATED_TAG, GeneratedDetector};
+use crate::hashing::{self, hash_str};
+use crate::optimizer;
+use crate::progress;
+use crate::text_split::split_by_paragraph_breaks;
+use crate::vcs;
+use anyhow::Result;
+use dirs::home_dir;
+use ignore::WalkBuilder;
+use ignore::overrides::OverrideBuilder;
+use log::warn;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Sizes the thread pool files are parsed on: `jobs`, else `[scan] jobs`,
+/// else one thread per CPU.
+pub fn configure_jobs(jobs: Option<usize>) {
+    let jobs = jobs.unwrap_or_else(|| {
+        config::load()
+            .map(|config| config.scan.jobs)
+            .unwrap_or_default()
+    });
+    if let Err(err) = rayon::ThreadPoolBuilder::new()
+        .num_threads(jobs)
+        .build_global()
+    {
+        warn!("Failed to size the scan thread pool: {}", err);
+    }
+}
+
+/// `--recurse-submodules`, set once at startup.
+static RECURSE_SUBMODULES: OnceLock<bool> = OnceLock::new();
+
+/// Makes scans descend into submodules, over `[scan] recurse_submodules`.
+pub fn configure_submodules(recurse: bool) {
+    if recurse {
+        let _ = RECURSE_SUBMODULES.set(true);
+    }
+}
+
+fn recurse_submodules(settings: &ScanConfig) -> bool {
+    settings.recurse_submodules || RECURSE_SUBMODULES.get().copied().unwrap_or(false)
+}
+
+/// The config a scan reads: which files (`[scan]`), how they are split
+/// (`[optimizer]`), and how blocks are fingerprinted (`[hashing]`).
+#[derive(Debug, Clone, Default)]
+pub struct ScanSettings {
+    pub scan: ScanConfig,
+    pub optimizer: OptimizerConfig,
+    pub normalization: Normalization,
+}
+
+impl ScanSettings {
+    pub fn from_config(config: &TrueflowConfig) -> Self {
+        Self {
+            scan: config.scan.clone(),
+            optimizer: config.optimizer.clone(),
+            normalization: config.hashing.normalization,
+        }
+    }
+
+    /// The settings the CLI scans with: the working directory's config, with
+    /// the optimizer rules and normalization fixed for the whole process.
+    pub fn configured() -> Result<Self> {
+        Ok(Self {
+            scan: config::load()?.scan,
+            ..Self::for_process()
+        })
+    }
+
+    /// The optimizer rules and normalization fixed for the whole process,
+    /// for splitting committed versions of files.
+    pub fn for_process() -> Self {
+        Self {
+            scan: ScanConfig::default(),
+            optimizer: optimizer::configured_rules().clone(),
+            normalization: hashing::configured_normalization(),
+        }
+    }
+}
+
+pub fn scan_directory<P: AsRef<Path>>(root: P) -> Result<Vec<FileState>> {
+    scan_directory_filtered(root, |_| true)
+}
+
+/// Scans only the files whose root-relative path passes `filter`. Other files
+/// are not read; they are returned only if the cache still holds them
+/// unchanged, so directory hashes stay intact when the cache is warm.
+pub fn scan_directory_filtered<P, F>(root: P, filter: F) -> Result<Vec<FileState>>
+where
+    P: AsRef<Path>,
+    F: Fn(&str) -> bool + Sync,
+{
+    scan_directory_with(root, &ScanSettings::configured()?, filter)
+}
+
+/// [`scan_directory_filtered`] with explicit settings. Paths are relative to
+/// `root`.
+pub fn scan_directory_with<P, F>(
+    root: P,
+    settings: &ScanSettings,
+    filter: F,
+) -> Result<Vec<FileState>>
+where
+    P: AsRef<Path>,
+    F: Fn(&str) -> bool + Sync,
+{
+    let root = root.as_ref();
+    let mut cache = load_cache(root, settings)?.unwrap_or_default();
+    let mut files = Vec::new();
+    let detector = GeneratedDetector::from_root(root);
+
+    // Walk first so the progress bar knows the total.
+    let mut paths = Vec::new();
+    for entry in walker(root, &settings.scan)?.build() {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(err) => {
+                warn!("Skipping unreadable entry: {}", err);
+                continue;
+            }
+        };
+        if entry.file_type().is_some_and(|kind| kind.is_file()) {
+            paths.push(entry.into_path());
+        }
+    }
+
+    let bar = progress::bar(paths.len(), "Scanning");
+    // Parse in parallel; collecting keeps the walk order.
+    let scanned: Vec<(String, Scanned)> = paths
+        .par_iter()
+        .map(|path| {
+            let key = slash_path(path.strip_prefix(root).unwrap_or(path));
+            bar.set_message(key.clone());
+            let scanned = if filter(&key) {
+                scan_file(path, &key, root, &cache, &detector, settings)
+            } else {
+                Scanned::OutOfScope(cache.unchanged(&key, path))
+            };
+            bar.inc(1);
+            (key, scanned)
+        })
+        .collect();
+    bar.finish_and_clear();
+
+    let mut dirty = false;
+    let mut cached_files = Vec::with_capacity(scanned.len());
+    for (key, scanned) in scanned {
+        let entry = cache.files.remove(&key);
+        let cached = match scanned {
+            Scanned::Cached(cached) => {
+                dirty |= cached.refreshed;
+                cached
+            }
+            Scanned::Parsed(cached) => {
+                dirty = true;
+                cached
+            }
+            Scanned::OutOfScope(unchanged) => {
+                if unchanged {
+                    files.extend(entry.as_ref().map(|cached| cached.file_state.clone()));
+                }
+                // Keep the entry for the scan that does cover it.
+                cached_files.extend(entry);
+                continue;
+            }
+            Scanned::Skipped => {
+                dirty |= entry.is_some();
+                continue;
+            }
+        };
+        files.push(cached.file_state.clone());
+        cached_files.push(cached);
+    }
+
+    // Entries left over belong to deleted files.
+    if dirty || !cache.files.is_empty() {
+        write_cache(root, cached_files, settings)?;
+    }
+    if !recurse_submodules(&settings.scan) {
+        files.extend(submodule_files(root));
+    }
+    Ok(files)
+}
+
+/// One pointer block per submodule under `root`; the walk skips their files.
+fn submodule_files(root: &Path) -> Vec<FileState> {
+    let Ok(repo) = gix::discover(root) else {
+        return Vec::new();
+    };
+    let (Some(workdir), Ok(absolute_root)) = (
+        repo.workdir().and_then(|dir| dir.canonicalize().ok()),
+        root.canonicalize(),
+    ) else {
+        return Vec::new();
+    };
+    let pointers = match vcs::submodule_pointers(&repo) {
+        Ok(pointers) => pointers,
+        Err(err) => {
+            warn!("Skipping submodules: {}", err);
+            return Vec::new();
+        }
+    };
+    pointers
+        .into_iter()
+        .filter_map(|(path, commit)| {
+            let relative = workdir.join(path);
+            let relative = relative.strip_prefix(&absolute_root).ok()?;
+            let blocks = vec![submodule_block(&commit)];
+            Some(FileState {
+                path: slash_path(relative),
+                language: Language::Unknown,
+                file_hash: file_hash(&blocks),
+                generated: false,
+                blocks,
+            })
+        })
+        .collect()
+}
+
+enum Scanned {
+    Cached(CachedFile),
+    Parsed(CachedFile),
+    /// Not parsed; whether the cached entry still matches the file.
+    OutOfScope(bool),
+    Skipped,
+}
+
+fn scan_file(
+    path: &Path,
+    key: &str,
+    root: &Path,
+    cache: &ScanCache,
+    detector: &GeneratedDetector,
+    settings: &ScanSettings,
+) -> Scanned {
+    let Ok(stamp) = FileStamp::read(path) else {
+        warn!("Skipping unreadable file {:?}", path);
+        return Scanned::Skipped;
+    };
+    let stamp = match cache.files.get(key) {
+        Some(cached) => match cache.revalidate(cached, stamp, path) {
+            Ok(cached) => return Scanned::Cached(cached),
+            Err(stamp) => stamp,
+        },
+        None => stamp,
+    };
+    match process_file(path, root, detector, settings) {
+        Ok(file_state) => Scanned::Parsed(CachedFile::new(stamp, file_state, path)),
+        Err(e) => {
+            warn!("Skipping file {:?}: {}", path, e);
+            Scanned::Skipped
+        }
+    }
+}
+
+/// Walks `root` the way scans do: skipping hidden files, build output, and
+/// whatever `.gitignore`, `.git/info/exclude`, and `.trueflowignore` exclude,
+/// with `[scan] include` / `exclude` taking precedence.
+fn walker(root: &Path, settings: &ScanConfig) -> Result<WalkBuilder> {
+    let recurse = recurse_submodules(settings);
+    let mut overrides = OverrideBuilder::new(root);
+    for glob in &settings.include {
+        overrides.add(glob)?;
+        // Whitelist the directories leading to the glob too, or an ignored
+        // parent would never be descended into.
+        for dir in literal_parents(glob) {
+            overrides.add(&format!("/{dir}/"))?;
+        }
+    }
+    for glob in &settings.exclude {
+        overrides.add(&format!("!{glob}"))?;
+    }
+
+    let mut builder = WalkBuilder::new(root);
+    builder
+        .hidden(true)
+        .git_ignore(true)
+        .git_exclude(true)
+        .git_global(false)
+        .require_git(false)
+        .add_custom_ignore_filename(IGNORE_FILE)
+        .overrides(overrides.build()?)
+        .filter_entry(move |entry| {
+            entry.depth() == 0
+                || !(is_ignored_name(&entry.file_name().to_string_lossy())
+                    || (!recurse && is_nested_checkout(entry)))
+        });
+    Ok(builder)
+}
+
+/// `a`, `a/b` for `a/b/**/*.rs`: the glob's leading directories without
+/// wildcards.
+fn literal_parents(glob: &str) -> Vec<String> {
+    let components: Vec<&str> = glob.trim_start_matches('/').split('/').collect();
+    let mut parents = Vec::new();
+    for end in 1..components.len() {
+        let dir = &components[..end];
+        if dir
+            .last()
+            .is_some_and(|name| name.is_empty() || name.contains(['*', '?', '[', '{']))
+        {
+            break;
+        }
+        parents.push(dir.join("/"));
+    }
+    parents
+}
+
+/// Directories directly under `root` that a scan would descend into.
+pub fn top_level_dirs(root: &Path) -> Vec<String> {
+    let Ok(mut builder) = config::load_in(root).and_then(|config| walker(root, &config.scan))
+    else {
+        return Vec::new();
+    };
+    let mut dirs: Vec<String> = builder
+        .max_depth(Some(1))
+        .build()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.depth() == 1 && entry.file_type().is_some_and(|kind| kind.is_dir()))
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .collect();
+    dirs.sort();
+    dirs
+}
+
+/// A submodule or other repository checked out inside the scanned one.
+fn is_nested_checkout(entry: &ignore::DirEntry) -> bool {
+    entry.file_type().is_some_and(|kind| kind.is_dir()) && entry.path().join(".git").exists()
+}
+
+/// Names never scanned, whatever the ignore files say.
+fn is_ignored_name(name: &str) -> bool {
+    name.starts_with('.') || // .git, .trueflow, .env
+    name == "target" ||      // rust build
+    name == "node_modules" // js dependencies
+}
+
+/// Per-directory ignore file, in `.gitignore` syntax.
+const IGNORE_FILE: &str = ".trueflowignore";
+
+/// Bump when the cached `FileState` shape changes so stale caches are discarded.
+const CACHE_VERSION: u32 = 8;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    #[serde(default)]
+    version: u32,
+    files: Vec<CachedFile>,
+    root_hash: String,
+    /// When the cache was written, in seconds. Files modified in that same
+    /// second may have changed without their mtime moving.
+    #[serde(default)]
+    written_at: u64,
+    /// Merge rules the cached blocks were produced with.
+    #[serde(default)]
+    optimizer: Option<OptimizerConfig>,
+    /// Hashing mode the cached fingerprints were computed with.
+    #[serde(default)]
+    normalization: Option<Normalization>,
+    /// `.gitattributes`, which decides which files count as generated.
+    #[serde(default)]
+    attributes_hash: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedFile {
+    path: String,
+    modified_at: u64,
+    size: u64,
+    /// SHA-256 of the raw bytes, checked when the mtime can't be trusted.
+    content_hash: String,
+    file_state: FileState,
+    /// Whether the stamp changed since the cache was read.
+    #[serde(skip)]
+    refreshed: bool,
+}
+
+impl CachedFile {
+    fn new(stamp: FileStamp, file_state: FileState, path: &Path) -> Self {
+        let content_hash = stamp.content_hash(path).unwrap_or_default();
+        Self {
+            path: file_state.path.clone(),
+            modified_at: stamp.modified_at,
+            size: stamp.size,
+            content_hash,
+            file_state,
+            refreshed: false,
+        }
+    }
+}
+
+/// The size and mtime a cached entry is first checked against.
+#[derive(Debug, Clone, Copy)]
+struct FileStamp {
+    modified_at: u64,
+    size: u64,
+}
+
+impl FileStamp {
+    fn read(path: &Path) -> Result<Self> {
+        let metadata = fs::metadata(path)?;
+        Ok(Self {
+            modified_at: system_time_to_epoch(metadata.modified()?),
+            size: metadata.len(),
+        })
+    }
+
+    fn content_hash(&self, path: &Path) -> Result<String> {
+        Ok(format!("{:x}", Sha256::digest(fs::read(path)?)))
+    }
+}
+
+/// Valid cached files by path; empty when the cache is missing or was built
+/// under different settings.
+#[derive(Debug, Default)]
+struct ScanCache {
+    files: HashMap<String, CachedFile>,
+    written_at: u64,
+}
+
+impl ScanCache {
+    /// Whether the entry for `key` still matches the file by size and mtime
+    /// alone, without reading it.
+    fn unchanged(&self, key: &str, path: &Path) -> bool {
+        let (Some(cached), Ok(stamp)) = (self.files.get(key), FileStamp::read(path)) else {
+            return false;
+        };
+        stamp.size == cached.size
+            && stamp.modified_at == cached.modified_at
+            && stamp.modified_at < self.written_at
+    }
+
+    /// `cached` if the file on disk still matches it, else the fresh stamp.
+    /// A matching size and mtime is trusted unless the mtime falls in the
+    /// second the cache was written; otherwise the content hash decides, so
+    /// a touched or re-checked-out file is not re-parsed.
+    fn revalidate(
+        &self,
+        cached: &CachedFile,
+        stamp: FileStamp,
+        path: &Path,
+    ) -> std::result::Result<CachedFile, FileStamp> {
+        if stamp.size != cached.size {
+            return Err(stamp);
+        }
+        if stamp.modified_at == cached.modified_at && stamp.modified_at < self.written_at {
+            return Ok(cached.clone());
+        }
+        match stamp.content_hash(path) {
+            // Refreshed either way: rewriting the cache moves `written_at`
+            // past the mtime, so the next run can trust the stamp again.
+            Ok(hash) if hash == cached.content_hash => Ok(CachedFile {
+                modified_at: stamp.modified_at,
+                refreshed: true,
+                ..cached.clone()
+            }),
+            _ => Err(stamp),
+        }
+    }
+}
+
+fn load_cache(root: &Path, settings: &ScanSettings) -> Result<Option<ScanCache>> {
+    let cache_path = cache_path(root)?;
+    let contents = match fs::read_to_string(&cache_path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+
+    let Ok(entry) = serde_json::from_str::<CacheEntry>(&contents) else {
+        return Ok(None);
+    };
+    if entry.version != CACHE_VERSION
+        || entry.root_hash != cache_root_hash(root)
+        || entry.optimizer.as_ref() != Some(&settings.optimizer)
+        || entry.normalization != Some(settings.normalization)
+        || entry.attributes_hash != attributes_hash(root)
+    {
+        return Ok(None);
+    }
+
+    Ok(Some(ScanCache {
+        files: entry
+            .files
+            .into_iter()
+            .map(|file| (file.path.clone(), file))
+            .collect(),
+        written_at: entry.written_at,
+    }))
+}
+
+fn write_cache(root: &Path, files: Vec<CachedFile>, settings: &ScanSettings) -> Result<()> {
+    let cache_path = cache_path(root)?;
+    if let Some(parent) = cache_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let entry = CacheEntry {
+        version: CACHE_VERSION,
+        files,
+        root_hash: cache_root_hash(root),
+        written_at: system_time_to_epoch(SystemTime::now()),
+        optimizer: Some(settings.optimizer.clone()),
+        normalization: Some(settings.normalization),
+        attributes_hash: attributes_hash(root),
+    };
+
+    let contents = serde_json::to_string(&entry)?;
+    fs::write(cache_path, contents)?;
+    Ok(())
+}
+
+fn attributes_hash(root: &Path) -> Option<String> {
+    fs::read_to_string(root.join(".gitattributes"))
+        .ok()
+        .map(|content| hash_str(&content))
+}
+
+fn cache_path(root: &Path) -> Result<PathBuf> {
+    let identity = cache_identity(root);
+    let repo_name = identity
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "repo".to_string());
+    let cache_root = home_dir().unwrap_or_else(|| root.to_path_buf());
+    let root_hash = hash_str(identity.to_string_lossy().as_ref());
+    Ok(cache_root
+        .join(".trueflow")
+        .join("cache")
+        .join(format!("scan-{}-{}.json", repo_name, root_hash)))
+}
+
+fn cache_identity(root: &Path) -> PathBuf {
+    root.canonicalize().unwrap_or_else(|_| root.to_path_buf())
+}
+
+fn cache_root_hash(root: &Path) -> String {
+    let identity = cache_identity(root);
+    hash_str(identity.to_string_lossy().as_ref())
+}
+
+fn system_time_to_epoch(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Renders a scanned path with `/` separators and no leading `./`, the form
+/// every path in trueflow (tree, ledger hints, targets) uses on all platforms.
+pub fn slash_path(path: &Path) -> String {
+    let mut rendered = String::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::Prefix(prefix) => rendered.push_str(&prefix.as_os_str().to_string_lossy()),
+            Component::RootDir => rendered.push('/'),
+            other => {
+                if !rendered.is_empty() && !rendered.ends_with('/') {
+                    rendered.push('/');
+                }
+                rendered.push_str(&other.as_os_str().to_string_lossy());
+            }
+        }
+    }
+    rendered
+}
+
+// TODO: Investigate whether salsa can help incremental review caching.
+
+fn process_file(
+    path: &Path,
+    root: &Path,
+    detector: &GeneratedDetector,
+    settings: &ScanSettings,
+) -> Result<FileState> {
+    let file_type = analysis::analyze_file(path);
+    let relative_path = slash_path(path.strip_prefix(root).unwrap_or(path));
+
+    if matches!(file_type, FileType::Binary) {
+        let blocks = vec![binary_block(&fs::read(path)?)];
+        return Ok(FileState {
+            path: relative_path,
+            language: Language::Unknown,
+            file_hash: file_hash(&blocks),
+            generated: false,
+            blocks,
+        });
+    }
+
+    let content = fs::read_to_string(path)?;
+
+    // Choose chunker based on analysis
+    let (language, mut blocks) = match file_type {
+        FileType::Code(code_file) => {
+            // Check if we have a splitter for this language
+            let language = code_file.language.clone();
+            let blocks = block_splitter::split(&content, language.clone());
+
+            match blocks {
+                Ok(b) if !b.is_empty() => {
+                    (language, optimizer::optimize_with(b, &settings.optimizer))
+                }
+                Ok(_) => (
+                    language,
+                    fallback_split_blocks(&content, FallbackMode::Code),
+                ), // Fallback if splitter returns empty (not implemented or empty file)
+                Err(e) => {
+                    warn!(
+                        "Failed to parse file {:?}: {}, falling back to paragraphs",
+                        path, e
+                    );
+                    (
+                        language,
+                        fallback_split_blocks(&content, FallbackMode::Code),
+                    )
+                }
+            }
+        }
+        FileType::Text => (
+            Language::Text,
+            fallback_split_blocks(&content, FallbackMode::Text),
+        ),
+        _ => (
+            Language::Unknown,
+            fallback_split_blocks(&content, FallbackMode::Text),
+        ), // Fallback for non-code files
+    };
+
+    hashing::apply_normalization_with(&mut blocks, &language, settings.normalization);
+
+    let generated = detector.detect(&relative_path, &content).is_some();
+    if generated {
+        for block in &mut blocks {
+            block.tags.push(GENERATED_TAG.to_string());
+        }
+    }
+
+    let file_hash = file_hash(&blocks);
+
+    Ok(FileState {
+        path: relative_path,
+        language,
+        file_hash,
+        generated,
+        blocks,
+    })
+}
+
+/// Merkle root of a file's block hashes.
+pub(crate) fn file_hash(blocks: &[Block]) -> String {
+    let mut hasher = Sha256::new();
+    for block in blocks {
+        hasher.update(&block.hash);
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// Binary files are reviewed as a single block describing the content, so
+/// any change to the bytes changes the block's fingerprint.
+pub(crate) fn binary_block(bytes: &[u8]) -> Block {
+    let content = format!(
+        "binary: {}\nsize: {} bytes\nsha256: {:x}\n",
+        analysis::detect_binary_type(bytes),
+        bytes.len(),
+        Sha256::digest(bytes)
+    );
+    Block::new(content, BlockKind::Binary, 0, 0)
+}
+
+/// A submodule is reviewed as the commit it points at: the block's
+/// fingerprint is the commit id, so moving the pointer needs a new review.
+pub(crate) fn submodule_block(commit: &str) -> Block {
+    let mut block = Block::new(
+        format!("Subproject commit {commit}\n"),
+        BlockKind::Submodule,
+        0,
+        0,
+    );
+    block.hash = commit.to_string();
+    block
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum FallbackMode {
+    Code,
+    Text,
+}
+
+pub(crate) fn fallback_split_blocks(content: &str, mode: FallbackMode) -> Vec<Block> {
+    let fallback = split_by_paragraph_breaks(content, |chunk, start, end, is_gap| {
+        let kind = classify_fallback_chunk(chunk, mode, is_gap);
+        create_fallback_block(content, chunk, kind, start, end)
+    });
+
+    if fallback.is_empty() {
+        return Vec::new();
+    }
+
+    fallback
+}
+
+fn classify_fallback_chunk(chunk: &str, mode: FallbackMode, is_gap: bool) -> BlockKind {
+    if is_gap {
+        return BlockKind::Gap;
+    }
+
+    if chunk.trim().is_empty() {
+        return BlockKind::Gap;
+    }
+
+    match mode {
+        FallbackMode::Code => classify_code_paragraph(chunk),
+        FallbackMode::Text => BlockKind::Paragraph,
+    }
+}
+
+fn classify_code_paragraph(chunk: &str) -> BlockKind {
+    let trimmed = chunk.trim();
+    if trimmed.is_empty() {
+        return BlockKind::Gap;
+    }
+
+    let is_comment = trimmed.lines().all(|line| {
+        let line = line.trim_start();
+        line.starts_with("//")
+            || line.starts_with('#')
+            || line.starts_with("/*")
+            || line.starts_with('*')
+    });
+
+    if is_comment {
+        BlockKind::Comment
+    } else {
+        BlockKind::CodeParagraph
+    }
+}
+
+fn create_fallback_block(
+    full_source: &str,
+    chunk: &str,
+    kind: BlockKind,
+    start: usize,
+    end: usize,
+) -> Block {
+    let (start_line, end_line) = byte_range_to_lines(full_source, start, end);
+    Block {
+        hash: hash_str(chunk),
+        metrics: BlockMetrics::from_content(chunk),
+        content: chunk.to_string(),
+        kind,
+        tags: Vec::new(),
+        complexity: 0,
+        start_line,
+        end_line,
+        previous: None,
+        blame: None,
+        commits: Vec::new(),
+        prereview: None,
+        risk: None,
+    }
+}
+
+fn byte_range_to_lines(source: &str, start: usize, end: usize) -> (usize, usize) {
+    let pre = &source[..start];
+    let start_line = pre.lines().count();
+    let start_line = if start > 0 && pre.ends_with('\n') {
+        start_line
+    } else {
+        start_line.saturating_sub(1)
+    };
+
+    let mid = &source[start..end];
+    let new_lines = mid.chars().filter(|&c| c == '\n').count();
+    let end_line = start_line + new_lines + if mid.ends_with('\n') { 0 } else { 1 };
+
+    (start_line, end_line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_merged_blocks(blocks: Vec<Block>, expected: &str) {
+        let merged = blocks
+            .into_iter()
+            .map(|block| block.content)
+            .collect::<String>();
+        assert_eq!(merged, expected);
+    }
+
+    #[test]
+    fn fallback_split_text_paragraphs() {
+        let content = "Para 1.\n\nPara 2.";
+        let blocks = fallback_split_blocks(content, FallbackMode::Text);
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(blocks[0].kind, BlockKind::Paragraph);
+        assert_eq!(blocks[1].kind, BlockKind::Gap);
+        assert_eq!(blocks[2].kind, BlockKind::Paragraph);
+        assert_merged_blocks(blocks, content);
+    }
+
+    #[test]
+    fn fallback_split_code_paragraphs() {
+        let content = "fn main() {}\n\n// comment";
+        let blocks = fallback_split_blocks(content, FallbackMode::Code);
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(blocks[0].kind, BlockKind::CodeParagraph);
+        assert_eq!(blocks[1].kind, BlockKind::Gap);
+        assert_eq!(blocks[2].kind, BlockKind::Comment);
+        assert_merged_blocks(blocks, content);
+    }
+
+    #[test]
+    fn cache_entries_fall_back_to_content_hashes() {
+        let dir = std::env::temp_dir().join(format!("trueflow-scan-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("lib.rs");
+        fs::write(&path, "fn a() {}\n").unwrap();
+        let stamp = FileStamp::read(&path).unwrap();
+        let file_state = FileState {
+            path: "lib.rs".to_string(),
+            language: Language::Rust,
+            file_hash: String::new(),
+            generated: false,
+            blocks: Vec::new(),
+        };
+        let cached = CachedFile::new(stamp, file_state, &path);
+        let cache = ScanCache {
+            files: HashMap::new(),
+            written_at: stamp.modified_at + 1,
+        };
+
+        // Touched: the mtime moved, the content did not.
+        let touched = FileStamp {
+            modified_at: stamp.modified_at + 5,
+            ..stamp
+        };
+        let revalidated = cache.revalidate(&cached, touched, &path).unwrap();
+        assert!(revalidated.refreshed);
+
+        // Edited within the same second, keeping the size.
+        fs::write(&path, "fn b() {}\n").unwrap();
+        let racy = ScanCache {
+            written_at: stamp.modified_at,
+            ..cache
+        };
+        assert!(racy.revalidate(&cached, stamp, &path).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn include_globs_whitelist_their_parents() {
+        assert_eq!(literal_parents("vendor/**"), ["vendor"]);
+        assert_eq!(
+            literal_parents("/third_party/zlib/*.c"),
+            ["third_party", "third_party/zlib"]
+        );
+        assert!(literal_parents("**/*.rs").is_empty());
+        assert!(literal_parents("README.md").is_empty());
+    }
+
+    #[test]
+    fn slash_path_drops_curdir_and_uses_forward_slashes() {
+        let path: PathBuf = [".", "src", "net", "mod.rs"].iter().collect();
+        assert_eq!(slash_path(&path), "src/net/mod.rs");
+        assert_eq!(slash_path(Path::new("README.md")), "README.md");
+    }
+}
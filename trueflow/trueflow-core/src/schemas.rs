@@ -0,0 +1,97 @@
+//! Versioned JSON schemas of the JSON trueflow writes, for `trueflow schema`.
+
+use crate::block::FileState;
+use crate::commands::feedback::FeedbackEntry;
+use crate::commands::review::UnreviewedFile;
+use crate::diff_logic::{BlockChange, Change};
+use crate::record_schema::record_schema;
+use crate::store::CURRENT_VERSION;
+use schemars::JsonSchema;
+use schemars::generate::SchemaSettings;
+use serde_json::Value;
+
+/// Version of the command output schemas. Bump it when a field is renamed,
+/// removed or changes type; new optional fields keep it.
+pub const OUTPUT_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "clap",
+    derive(clap::ValueEnum),
+    clap(rename_all = "kebab-case")
+)]
+pub enum SchemaName {
+    /// A review record in the ledger
+    Record,
+    /// `review --json`
+    Review,
+    /// `scan --json`
+    Scan,
+    /// `diff --json`
+    Diff,
+    /// `diff --blocks --json`
+    DiffBlocks,
+    /// `feedback --format json`
+    Feedback,
+}
+
+impl SchemaName {
+    pub const ALL: [SchemaName; 6] = [
+        SchemaName::Record,
+        SchemaName::Review,
+        SchemaName::Scan,
+        SchemaName::Diff,
+        SchemaName::DiffBlocks,
+        SchemaName::Feedback,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SchemaName::Record => "record",
+            SchemaName::Review => "review",
+            SchemaName::Scan => "scan",
+            SchemaName::Diff => "diff",
+            SchemaName::DiffBlocks => "diff-blocks",
+            SchemaName::Feedback => "feedback",
+        }
+    }
+
+    /// Records carry their own `version`; command output shares one.
+    pub fn version(self) -> u32 {
+        match self {
+            SchemaName::Record => CURRENT_VERSION,
+            _ => OUTPUT_SCHEMA_VERSION,
+        }
+    }
+
+    /// The draft 2020-12 schema, with an `$id` naming it and its version.
+    pub fn schema(self) -> Value {
+        let mut schema = match self {
+            SchemaName::Record => record_schema(),
+            SchemaName::Review => schema_for::<Vec<UnreviewedFile>>(),
+            SchemaName::Scan => schema_for::<Vec<FileState>>(),
+            SchemaName::Diff => schema_for::<Vec<Change>>(),
+            SchemaName::DiffBlocks => schema_for::<Vec<BlockChange>>(),
+            SchemaName::Feedback => schema_for::<Vec<FeedbackEntry>>(),
+        };
+        if let Some(fields) = schema.as_object_mut() {
+            fields.insert(
+                "$id".to_string(),
+                Value::String(format!(
+                    "urn:trueflow:schema:{}:v{}",
+                    self.name(),
+                    self.version()
+                )),
+            );
+        }
+        schema
+    }
+}
+
+fn schema_for<T: JsonSchema>() -> Value {
+    let schema = SchemaSettings::draft2020_12()
+        .for_serialize()
+        .into_generator()
+        .into_root_schema_for::<T>();
+    serde_json::to_value(schema).unwrap_or(Value::Bool(true))
+}
@@ -0,0 +1,114 @@
+use crate::commands::review::UnreviewedFile;
+use crate::store::FileStore;
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::PathBuf;
+
+const SESSION_FILE: &str = "last_session.json";
+
+/// What was still unreviewed when the last review session ended. Local to
+/// this checkout; it is not part of the synced ledger.
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SessionSnapshot {
+    pub ended_at: i64,
+    pub unreviewed: BTreeSet<String>,
+}
+
+impl SessionSnapshot {
+    pub fn from_files(files: &[UnreviewedFile], ended_at: i64) -> Self {
+        Self {
+            ended_at,
+            unreviewed: files
+                .iter()
+                .flat_map(|file| file.blocks.iter().map(|block| block.hash.clone()))
+                .collect(),
+        }
+    }
+
+    pub fn load() -> Result<Option<Self>> {
+        load_json(SESSION_FILE)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        save_json(SESSION_FILE, self)
+    }
+
+    /// Keeps only blocks that were not already waiting when the session ended.
+    pub fn new_since(&self, files: Vec<UnreviewedFile>) -> Vec<UnreviewedFile> {
+        files
+            .into_iter()
+            .filter_map(|mut file| {
+                file.blocks
+                    .retain(|block| !self.unreviewed.contains(&block.hash));
+                (!file.blocks.is_empty()).then_some(file)
+            })
+            .collect()
+    }
+}
+
+fn session_path(name: &str) -> Result<PathBuf> {
+    Ok(FileStore::new()?.trueflow_dir().join(name))
+}
+
+/// Reads `.trueflow/<name>`, or `None` when it does not exist.
+pub fn load_json<T: DeserializeOwned>(name: &str) -> Result<Option<T>> {
+    let path = session_path(name)?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content =
+        fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let value = serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+    Ok(Some(value))
+}
+
+pub fn save_json<T: Serialize>(name: &str, value: &T) -> Result<()> {
+    let path = session_path(name)?;
+    fs::write(&path, serde_json::to_string(value)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+pub fn remove_json(name: &str) -> Result<()> {
+    let path = session_path(name)?;
+    match fs::remove_file(&path) {
+        Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+            Err(err).with_context(|| format!("Failed to remove {}", path.display()))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::Language;
+    use crate::block::{Block, BlockKind};
+
+    fn file(path: &str, contents: &[&str]) -> UnreviewedFile {
+        UnreviewedFile {
+            path: path.to_string(),
+            language: Language::Rust,
+            blocks: contents
+                .iter()
+                .map(|content| Block::new(content.to_string(), BlockKind::Function, 0, 1))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn new_since_drops_blocks_that_were_already_pending() {
+        let snapshot = SessionSnapshot::from_files(&[file("a.rs", &["fn old() {}"])], 10);
+        let now = vec![
+            file("a.rs", &["fn old() {}", "fn new() {}"]),
+            file("b.rs", &["fn old() {}"]),
+        ];
+        let fresh = snapshot.new_since(now);
+        assert_eq!(fresh.len(), 1);
+        assert_eq!(fresh[0].path, "a.rs");
+        assert_eq!(fresh[0].blocks[0].content, "fn new() {}");
+    }
+}
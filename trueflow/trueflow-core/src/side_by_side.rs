@@ -0,0 +1,115 @@
+use gix::diff::blob::intern::InternedInput;
+use gix::diff::blob::{Algorithm, diff};
+use std::ops::Range;
+
+/// One row of a side-by-side diff. A side is `None` where lines were only
+/// added or only removed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffRow {
+    pub old: Option<String>,
+    pub new: Option<String>,
+    /// Zero-based line of `new` in the new text.
+    pub new_line: Option<usize>,
+    pub changed: bool,
+}
+
+/// Pairs the lines of `old` and `new`: unchanged lines side by side, and
+/// each changed hunk's removed and added lines row by row.
+pub fn align(old: &str, new: &str) -> Vec<DiffRow> {
+    let input = InternedInput::new(old, new);
+    let mut hunks: Vec<(Range<u32>, Range<u32>)> = Vec::new();
+    diff(
+        Algorithm::Histogram,
+        &input,
+        |before: Range<u32>, after: Range<u32>| {
+            hunks.push((before, after));
+        },
+    );
+
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let line = |lines: &[&str], index: usize| lines.get(index).map(|line| line.to_string());
+
+    let mut rows = Vec::new();
+    let (mut old_at, mut new_at) = (0, 0);
+    let hunks = hunks
+        .into_iter()
+        .map(|(before, after)| {
+            (
+                before.start as usize..before.end as usize,
+                after.start as usize..after.end as usize,
+            )
+        })
+        .chain([(
+            old_lines.len()..old_lines.len(),
+            new_lines.len()..new_lines.len(),
+        )]);
+    for (before, after) in hunks {
+        while old_at < before.start && new_at < after.start {
+            rows.push(DiffRow {
+                old: line(&old_lines, old_at),
+                new: line(&new_lines, new_at),
+                new_line: Some(new_at),
+                changed: false,
+            });
+            old_at += 1;
+            new_at += 1;
+        }
+        for offset in 0..before.len().max(after.len()) {
+            let old = (offset < before.len()).then(|| before.start + offset);
+            let new = (offset < after.len()).then(|| after.start + offset);
+            rows.push(DiffRow {
+                old: old.and_then(|index| line(&old_lines, index)),
+                new: new.and_then(|index| line(&new_lines, index)),
+                new_line: new,
+                changed: true,
+            });
+        }
+        old_at = before.end;
+        new_at = after.end;
+    }
+    rows
+}
+
+/// The rows from the first to the last new line in `lines`, including
+/// removals between them.
+pub fn rows_for_lines(rows: &[DiffRow], lines: Range<usize>) -> &[DiffRow] {
+    let in_range = |row: &DiffRow| row.new_line.is_some_and(|line| lines.contains(&line));
+    match (
+        rows.iter().position(in_range),
+        rows.iter().rposition(in_range),
+    ) {
+        (Some(first), Some(last)) => &rows[first..=last],
+        _ => &[],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aligns_changed_added_and_removed_lines() {
+        let old = "fn a() {\n    one();\n    two();\n}\n";
+        let new = "fn a() {\n    uno();\n}\nfn b() {}\n";
+        let rows = align(old, new);
+        let sides: Vec<(Option<&str>, Option<&str>, bool)> = rows
+            .iter()
+            .map(|row| (row.old.as_deref(), row.new.as_deref(), row.changed))
+            .collect();
+        assert_eq!(
+            sides,
+            [
+                (Some("fn a() {"), Some("fn a() {"), false),
+                (Some("    one();"), Some("    uno();"), true),
+                (Some("    two();"), None, true),
+                (Some("}"), Some("}"), false),
+                (None, Some("fn b() {}"), true),
+            ]
+        );
+
+        let block = rows_for_lines(&rows, 0..3);
+        assert_eq!(block.len(), 4);
+        assert!(rows_for_lines(&rows, 9..10).is_empty());
+    }
+}
@@ -0,0 +1,580 @@
+use crate::config::{SignersConfig, SigstoreConfig, TrueflowConfig};
+use crate::store::{Attestation, AttestationKind, Canonicalization, Identity, Record};
+use crate::vcs::{self, GitConfig};
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Namespace SSH signatures are made in, so they can't be replayed as git
+/// commit or tag signatures.
+const SSH_NAMESPACE: &str = "trueflow";
+/// Principal the embedded public key is trusted as while checking that a
+/// record is intact.
+const SSH_PRINCIPAL: &str = "trueflow";
+const DEFAULT_COSIGN: &str = "cosign";
+
+/// Signature backend: `[identity] format`, else git's `gpg.format`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SigningFormat {
+    #[default]
+    OpenPgp,
+    Ssh,
+    /// Keyless: a short-lived Fulcio certificate, logged to Rekor.
+    Sigstore,
+}
+
+impl SigningFormat {
+    fn from_git(value: Option<&str>) -> Result<Self> {
+        match value.map(str::trim) {
+            None | Some("") | Some("openpgp") => Ok(Self::OpenPgp),
+            Some("ssh") => Ok(Self::Ssh),
+            Some(other) => bail!("Unsupported gpg.format '{}' (use openpgp or ssh)", other),
+        }
+    }
+}
+
+/// The key records are signed with: `user.signingkey` in the format given by
+/// `gpg.format`, like `git commit -S`. Sigstore needs no key.
+#[derive(Debug, Clone, Default)]
+pub struct Signer {
+    pub format: SigningFormat,
+    pub key: Option<String>,
+}
+
+impl Signer {
+    pub fn from_git_config(config: &GitConfig, format: Option<SigningFormat>) -> Result<Self> {
+        let format = match format {
+            Some(format) => format,
+            None => SigningFormat::from_git(config.signing_format.as_deref())?,
+        };
+        Ok(Self {
+            format,
+            key: config.signing_key.clone(),
+        })
+    }
+
+    /// Signs the record's JCS payload.
+    pub fn attest(&self, record: &Record) -> Result<Attestation> {
+        let payload = record.signing_payload()?;
+        let (kind, signature, public_key) = match self.format {
+            SigningFormat::OpenPgp => (
+                AttestationKind::Pgp,
+                gpg_sign(&payload, self.key.as_deref())?,
+                gpg_export(self.key.as_deref())?,
+            ),
+            SigningFormat::Ssh => {
+                let key = self
+                    .key
+                    .as_deref()
+                    .context("SSH signing needs user.signingkey")?;
+                let scratch = Scratch::new("trueflow-ssh-sign")?;
+                let (key_file, public_key) = ssh_key(key, &scratch)?;
+                (
+                    AttestationKind::Ssh,
+                    ssh_sign(&payload, &key_file)?,
+                    public_key,
+                )
+            }
+            SigningFormat::Sigstore => {
+                let (bundle, certificate) = cosign_sign(&payload)?;
+                (AttestationKind::Sigstore, bundle, certificate)
+            }
+        };
+        Ok(Attestation {
+            kind,
+            canonicalization: Canonicalization::JcsV1,
+            signature,
+            public_key,
+        })
+    }
+}
+
+/// What checking one attestation found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verification {
+    Valid,
+    Invalid,
+    /// An intact signature with no trusted signer to check it against: any
+    /// key (or any Fulcio certificate) would pass, so it proves nothing.
+    Unpinned(String),
+}
+
+/// Checks GPG and SSH attestations against the public key embedded in each,
+/// then the signer against `[signers]`; Sigstore bundles are checked against
+/// the Fulcio root and the Rekor log instead.
+pub struct Verifier {
+    scratch: Scratch,
+    sigstore: SigstoreConfig,
+    signers: SignersConfig,
+}
+
+impl Verifier {
+    pub fn new(sigstore: SigstoreConfig, signers: SignersConfig) -> Result<Self> {
+        Ok(Self {
+            scratch: Scratch::new("trueflow-verify")?,
+            sigstore,
+            signers,
+        })
+    }
+
+    /// `[sigstore]` and `[signers]` from `config`, falling back to git's
+    /// `gpg.ssh.allowedSignersFile` in the working directory.
+    pub fn for_workspace(config: &TrueflowConfig) -> Result<Self> {
+        let mut signers = config.signers.clone();
+        if signers.allowed_signers.is_none() {
+            signers.allowed_signers = vcs::git_config_from_workdir()
+                .ok()
+                .and_then(|git| git.allowed_signers);
+        }
+        Self::new(config.sigstore.clone(), signers)
+    }
+
+    /// Checks `attestation` of `record`, whose signing payload is `payload`.
+    pub fn verify(
+        &self,
+        record: &Record,
+        payload: &str,
+        attestation: &Attestation,
+    ) -> Result<Verification> {
+        if attestation.canonicalization != Canonicalization::JcsV1 {
+            return Ok(Verification::Invalid);
+        }
+        match attestation.kind {
+            AttestationKind::Pgp => {
+                self.verify_pgp(payload, &attestation.signature, &attestation.public_key)
+            }
+            AttestationKind::Ssh => self.verify_ssh(
+                &record.identity,
+                payload,
+                &attestation.signature,
+                &attestation.public_key,
+            ),
+            AttestationKind::Sigstore => {
+                self.verify_sigstore(&record.identity, payload, &attestation.signature)
+            }
+        }
+    }
+
+    /// The certificate must name `[sigstore] certificate_identity`, or else
+    /// the record's own email, and come from `certificate_oidc_issuer`, or
+    /// else the record's OIDC issuer.
+    fn verify_sigstore(
+        &self,
+        identity: &Identity,
+        payload: &str,
+        bundle: &str,
+    ) -> Result<Verification> {
+        let (email, record_issuer) = match identity {
+            Identity::Email { email } => (Some(email), None),
+            Identity::Oidc { issuer, email, .. } => (email.as_ref(), Some(issuer)),
+            _ => (None, None),
+        };
+        let Some(certificate_identity) = self.sigstore.certificate_identity.as_ref().or(email)
+        else {
+            return Ok(Verification::Unpinned(
+                "set [sigstore] certificate_identity".to_string(),
+            ));
+        };
+        let Some(issuer) = self
+            .sigstore
+            .certificate_oidc_issuer
+            .as_ref()
+            .or(record_issuer)
+        else {
+            return Ok(Verification::Unpinned(
+                "set [sigstore] certificate_oidc_issuer".to_string(),
+            ));
+        };
+
+        let bundle_path = self.scratch.path.join("bundle.json");
+        let payload_path = self.scratch.path.join("payload.json");
+        fs::write(&bundle_path, bundle)?;
+        fs::write(&payload_path, payload)?;
+        let status = Command::new(cosign_program())
+            .args(["verify-blob", "--bundle"])
+            .arg(&bundle_path)
+            .arg("--certificate-identity")
+            .arg(certificate_identity)
+            .arg("--certificate-oidc-issuer")
+            .arg(issuer)
+            .arg(&payload_path)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .context("Failed to run cosign")?;
+        Ok(if status.success() {
+            Verification::Valid
+        } else {
+            Verification::Invalid
+        })
+    }
+
+    /// The signature must check out against the embedded key, and that key
+    /// (or the subkey that signed) must be in `[signers] pgp_fingerprints`.
+    fn verify_pgp(&self, payload: &str, signature: &str, public_key: &str) -> Result<Verification> {
+        // A fresh keyring per record, so one record's key can't vouch for another.
+        let home = self.scratch.path.join(uuid::Uuid::new_v4().to_string());
+        fs::create_dir_all(&home)?;
+        let key_path = home.join("pubkey.asc");
+        let sig_path = home.join("signature.asc");
+        let payload_path = home.join("payload.txt");
+        fs::write(&key_path, public_key)?;
+        fs::write(&sig_path, signature)?;
+        fs::write(&payload_path, payload)?;
+
+        let import = Command::new("gpg")
+            .args(["--batch", "--no-tty", "--homedir"])
+            .arg(&home)
+            .arg("--import")
+            .arg(&key_path)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .context("Failed to import gpg public key")?;
+        if !import.success() {
+            // If import fails, we can't verify.
+            return Ok(Verification::Invalid);
+        }
+
+        let verify = Command::new("gpg")
+            .args(["--batch", "--no-tty", "--status-fd", "1", "--homedir"])
+            .arg(&home)
+            .arg("--verify")
+            .arg(&sig_path)
+            .arg(&payload_path)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .output()
+            .context("Failed to verify gpg signature")?;
+        if !verify.status.success() {
+            return Ok(Verification::Invalid);
+        }
+        let signed_by = valid_signature_fingerprints(&String::from_utf8_lossy(&verify.stdout));
+        if signed_by.is_empty() {
+            return Ok(Verification::Invalid);
+        }
+        if self.signers.pgp_fingerprints.is_empty() {
+            return Ok(Verification::Unpinned(
+                "set [signers] pgp_fingerprints".to_string(),
+            ));
+        }
+        let trusted = self
+            .signers
+            .pgp_fingerprints
+            .iter()
+            .map(|fingerprint| normalize_fingerprint(fingerprint))
+            .any(|fingerprint| signed_by.contains(&fingerprint));
+        Ok(if trusted {
+            Verification::Valid
+        } else {
+            Verification::Invalid
+        })
+    }
+
+    /// The signature must check out against the embedded key, and then
+    /// against the allowed signers file for the record's identity: its email,
+    /// or `agent:<name>` / `machine:<name>`.
+    fn verify_ssh(
+        &self,
+        identity: &Identity,
+        payload: &str,
+        signature: &str,
+        public_key: &str,
+    ) -> Result<Verification> {
+        let embedded_path = self.scratch.path.join("embedded_signer");
+        fs::write(
+            &embedded_path,
+            format!("{SSH_PRINCIPAL} {}\n", public_key.trim()),
+        )?;
+        if !self.ssh_verifies(&embedded_path, SSH_PRINCIPAL, payload, signature)? {
+            return Ok(Verification::Invalid);
+        }
+        let Some(allowed_signers) = self.signers.allowed_signers.as_deref() else {
+            return Ok(Verification::Unpinned(
+                "set gpg.ssh.allowedSignersFile or [signers] allowed_signers".to_string(),
+            ));
+        };
+        let allowed_path = expand_home(allowed_signers);
+        if !allowed_path.exists() {
+            bail!("Allowed signers file {} not found", allowed_path.display());
+        }
+        Ok(
+            if self.ssh_verifies(&allowed_path, &identity.label(), payload, signature)? {
+                Verification::Valid
+            } else {
+                Verification::Invalid
+            },
+        )
+    }
+
+    fn ssh_verifies(
+        &self,
+        allowed_path: &Path,
+        principal: &str,
+        payload: &str,
+        signature: &str,
+    ) -> Result<bool> {
+        let sig_path = self.scratch.path.join("signature.sig");
+        fs::write(&sig_path, format!("{}\n", signature.trim()))?;
+
+        let mut child = Command::new("ssh-keygen")
+            .args(["-Y", "verify", "-f"])
+            .arg(allowed_path)
+            .args(["-I", principal, "-n", SSH_NAMESPACE, "-s"])
+            .arg(&sig_path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .context("Failed to spawn ssh-keygen")?;
+        write_stdin(&mut child, payload)?;
+        Ok(child.wait()?.success())
+    }
+}
+
+/// Fingerprints of the signing key and its primary key from `gpg --status-fd`
+/// `VALIDSIG` lines.
+fn valid_signature_fingerprints(status: &str) -> Vec<String> {
+    status
+        .lines()
+        .filter_map(|line| line.strip_prefix("[GNUPG:] VALIDSIG "))
+        .flat_map(|fields| {
+            let fields: Vec<&str> = fields.split_whitespace().collect();
+            [fields.first().copied(), fields.last().copied()]
+        })
+        .flatten()
+        .map(normalize_fingerprint)
+        .collect()
+}
+
+/// Upper-case hex without spaces or a `0x` prefix, as gpg prints it.
+fn normalize_fingerprint(fingerprint: &str) -> String {
+    let fingerprint = fingerprint.trim();
+    fingerprint
+        .strip_prefix("0x")
+        .unwrap_or(fingerprint)
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_uppercase()
+}
+
+/// Temporary directory removed on drop.
+struct Scratch {
+    path: PathBuf,
+}
+
+impl Scratch {
+    fn new(prefix: &str) -> Result<Self> {
+        let path = std::env::temp_dir()
+            .join(prefix)
+            .join(uuid::Uuid::new_v4().to_string());
+        fs::create_dir_all(&path)?;
+        Ok(Self { path })
+    }
+}
+
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+fn write_stdin(child: &mut std::process::Child, data: &str) -> Result<()> {
+    let mut stdin = child.stdin.take().context("Failed to open stdin")?;
+    stdin.write_all(data.as_bytes())?;
+    Ok(())
+}
+
+fn gpg_sign(data: &str, key_id: Option<&str>) -> Result<String> {
+    let mut cmd = Command::new("gpg");
+    cmd.arg("--detach-sign").arg("--armor");
+    if let Some(kid) = key_id {
+        cmd.arg("--local-user").arg(kid);
+    }
+
+    let mut child = cmd
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .context("Failed to spawn gpg")?;
+    write_stdin(&mut child, data)?;
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        bail!("GPG signing failed");
+    }
+    Ok(String::from_utf8(output.stdout)?.trim().to_string())
+}
+
+fn gpg_export(key_id: Option<&str>) -> Result<String> {
+    let mut cmd = Command::new("gpg");
+    cmd.arg("--armor").arg("--export");
+    if let Some(kid) = key_id {
+        cmd.arg(kid);
+    }
+
+    let output = cmd.output().context("Failed to run gpg export")?;
+    if !output.status.success() {
+        bail!("GPG export failed");
+    }
+    Ok(String::from_utf8(output.stdout)?.trim().to_string())
+}
+
+/// Key file to pass to `ssh-keygen -Y sign`, and the public key to embed.
+/// Like git, `user.signingkey` is either a literal public key (optionally
+/// prefixed with `key::`, private half in ssh-agent) or a path to a key file.
+fn ssh_key(signing_key: &str, scratch: &Scratch) -> Result<(PathBuf, String)> {
+    let literal = signing_key.strip_prefix("key::").or_else(|| {
+        ["ssh-", "ecdsa-", "sk-"]
+            .iter()
+            .any(|prefix| signing_key.starts_with(prefix))
+            .then_some(signing_key)
+    });
+    if let Some(public_key) = literal {
+        let public_key = bare_public_key(public_key)?;
+        let key_file = scratch.path.join("signing_key.pub");
+        fs::write(&key_file, format!("{public_key}\n"))?;
+        return Ok((key_file, public_key));
+    }
+
+    let key_file = expand_home(signing_key);
+    let public_key = if key_file.extension().is_some_and(|ext| ext == "pub") {
+        fs::read_to_string(&key_file)
+            .with_context(|| format!("Failed to read {}", key_file.display()))?
+    } else if let Ok(public_key) = fs::read_to_string(format!("{}.pub", key_file.display())) {
+        public_key
+    } else {
+        let output = Command::new("ssh-keygen")
+            .arg("-y")
+            .arg("-f")
+            .arg(&key_file)
+            .output()
+            .context("Failed to run ssh-keygen")?;
+        if !output.status.success() {
+            bail!(
+                "Failed to read SSH public key from {}: {}",
+                key_file.display(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        String::from_utf8(output.stdout)?
+    };
+    Ok((key_file, bare_public_key(&public_key)?))
+}
+
+/// `<type> <base64>`, without the trailing comment.
+fn bare_public_key(key: &str) -> Result<String> {
+    let mut fields = key.split_whitespace();
+    match (fields.next(), fields.next()) {
+        (Some(kind), Some(blob)) => Ok(format!("{kind} {blob}")),
+        _ => bail!("Malformed SSH public key '{}'", key.trim()),
+    }
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), std::env::var_os("HOME")) {
+        (Some(rest), Some(home)) => Path::new(&home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// `TRUEFLOW_COSIGN`, else `cosign` on `PATH`.
+fn cosign_program() -> String {
+    std::env::var("TRUEFLOW_COSIGN")
+        .ok()
+        .filter(|program| !program.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_COSIGN.to_string())
+}
+
+/// Keyless signature of `data`: the Sigstore bundle (signature, certificate,
+/// and Rekor entry) and the signing certificate. cosign picks up an ambient
+/// OIDC token in CI and opens a browser otherwise.
+fn cosign_sign(data: &str) -> Result<(String, String)> {
+    let scratch = Scratch::new("trueflow-sigstore-sign")?;
+    let payload_path = scratch.path.join("payload.json");
+    let bundle_path = scratch.path.join("bundle.json");
+    let certificate_path = scratch.path.join("certificate.pem");
+    fs::write(&payload_path, data)?;
+
+    let output = Command::new(cosign_program())
+        .args(["sign-blob", "--yes", "--bundle"])
+        .arg(&bundle_path)
+        .arg("--output-certificate")
+        .arg(&certificate_path)
+        .arg(&payload_path)
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .output()
+        .context("Failed to run cosign (set TRUEFLOW_COSIGN to its path)")?;
+    if !output.status.success() {
+        bail!(
+            "Sigstore signing failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    let bundle = fs::read_to_string(&bundle_path).context("cosign wrote no bundle")?;
+    // Newer cosign keeps the certificate only inside the bundle.
+    let certificate = fs::read_to_string(&certificate_path).unwrap_or_default();
+    Ok((bundle.trim().to_string(), certificate.trim().to_string()))
+}
+
+fn ssh_sign(data: &str, key_file: &Path) -> Result<String> {
+    let mut child = Command::new("ssh-keygen")
+        .args(["-Y", "sign", "-n", SSH_NAMESPACE, "-f"])
+        .arg(key_file)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to spawn ssh-keygen")?;
+    write_stdin(&mut child, data)?;
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        bail!(
+            "SSH signing failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8(output.stdout)?.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_git_signing_formats() {
+        assert_eq!(
+            SigningFormat::from_git(None).unwrap(),
+            SigningFormat::OpenPgp
+        );
+        assert_eq!(
+            SigningFormat::from_git(Some("ssh")).unwrap(),
+            SigningFormat::Ssh
+        );
+        assert!(SigningFormat::from_git(Some("x509")).is_err());
+    }
+
+    #[test]
+    fn strips_public_key_comments() {
+        assert_eq!(
+            bare_public_key("ssh-ed25519 AAAAC3Nz me@example.com\n").unwrap(),
+            "ssh-ed25519 AAAAC3Nz"
+        );
+        assert!(bare_public_key("ssh-ed25519").is_err());
+    }
+
+    #[test]
+    fn reads_signer_fingerprints_from_gpg_status() {
+        let status = "[GNUPG:] NEWSIG\n[GNUPG:] VALIDSIG 1111AAAA 2026-01-01 1767225600 0 4 0 22 10 00 2222BBBB\n";
+        assert_eq!(
+            valid_signature_fingerprints(status),
+            vec!["1111AAAA".to_string(), "2222BBBB".to_string()]
+        );
+        assert_eq!(normalize_fingerprint("0x2222 bbbb"), "2222BBBB");
+    }
+}
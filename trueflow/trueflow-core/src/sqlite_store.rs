@@ -0,0 +1,167 @@
+use crate::store::{FileStore, Record, RecordKind, RecordQuery, ReviewStore};
+use anyhow::{Context, Result};
+use log::{info, warn};
+use rusqlite::types::Value;
+use rusqlite::{Connection, TransactionBehavior, params, params_from_iter};
+use std::time::Duration;
+
+/// Bumped with each schema change; stored in SQLite's `user_version`.
+const SCHEMA_VERSION: i64 = 1;
+const BUSY_TIMEOUT: Duration = Duration::from_secs(10);
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS records (
+    seq INTEGER PRIMARY KEY AUTOINCREMENT,
+    id TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    fingerprint TEXT NOT NULL,
+    check_name TEXT NOT NULL,
+    verdict TEXT NOT NULL,
+    identity TEXT NOT NULL,
+    timestamp INTEGER NOT NULL,
+    body TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS records_fingerprint ON records (fingerprint);
+CREATE INDEX IF NOT EXISTS records_check ON records (check_name);
+CREATE INDEX IF NOT EXISTS records_timestamp ON records (timestamp);
+CREATE INDEX IF NOT EXISTS records_identity ON records (identity);
+";
+
+/// The ledger in `.trueflow/reviews.db`. Each row keeps the record's JSON
+/// alongside the columns queries filter on; `seq` preserves append order.
+pub struct SqliteStore {
+    conn: Connection,
+}
+
+impl SqliteStore {
+    /// Opens the database beside `files`' ledger, creating it on first use
+    /// and importing the records already in `reviews.jsonl`.
+    pub fn open(files: &FileStore) -> Result<Self> {
+        let path = files.sqlite_path();
+        let mut conn = Connection::open(&path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        migrate(&mut conn, files)?;
+        Ok(Self { conn })
+    }
+}
+
+fn migrate(conn: &mut Connection, files: &FileStore) -> Result<()> {
+    // Immediate, so two first runs don't both import the JSONL ledger.
+    let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+    let version: i64 = tx.pragma_query_value(None, "user_version", |row| row.get(0))?;
+    if version >= SCHEMA_VERSION {
+        return Ok(());
+    }
+    tx.execute_batch(SCHEMA)?;
+    if version == 0 {
+        let records = files.read_history()?;
+        for record in &records {
+            insert(&tx, record)?;
+        }
+        if !records.is_empty() {
+            info!(
+                "Imported {} record(s) from {} into {}",
+                records.len(),
+                files.db_path().display(),
+                files.sqlite_path().display()
+            );
+        }
+    }
+    tx.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+    tx.commit()?;
+    Ok(())
+}
+
+fn insert(conn: &Connection, record: &Record) -> Result<()> {
+    conn.execute(
+        "INSERT INTO records (id, kind, fingerprint, check_name, verdict, identity, timestamp, body)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            record.id,
+            record.kind.as_str(),
+            record.fingerprint,
+            record.check,
+            record.verdict.as_str(),
+            record.identity.label(),
+            record.timestamp,
+            serde_json::to_string(record)?,
+        ],
+    )?;
+    Ok(())
+}
+
+fn read_records(conn: &Connection, sql: &str, values: Vec<Value>) -> Result<Vec<Record>> {
+    let mut statement = conn.prepare(sql)?;
+    let rows = statement.query_map(params_from_iter(values), |row| row.get::<_, String>(0))?;
+    let mut records = Vec::new();
+    for body in rows {
+        match serde_json::from_str::<Record>(&body?) {
+            Ok(record) => records.push(record),
+            Err(err) => warn!("Skipping malformed record: {}", err),
+        }
+    }
+    Ok(records)
+}
+
+impl ReviewStore for SqliteStore {
+    fn read_history(&self) -> Result<Vec<Record>> {
+        read_records(
+            &self.conn,
+            "SELECT body FROM records ORDER BY seq",
+            Vec::new(),
+        )
+    }
+
+    fn append(&self, record: Record) -> Result<()> {
+        insert(&self.conn, &record)
+    }
+
+    fn append_all(&self, records: Vec<Record>) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        for record in &records {
+            insert(&tx, record)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn rewrite(&self, records: &[Record]) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute("DELETE FROM records", [])?;
+        for record in records {
+            insert(&tx, record)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn query(&self, query: &RecordQuery) -> Result<Vec<Record>> {
+        // Narrow with the indexes, then apply the exact filter.
+        let mut sql = String::from("SELECT body FROM records WHERE kind = ?");
+        let mut values = vec![Value::Text(RecordKind::Verdict.as_str().to_string())];
+        if let Some(prefix) = &query.fingerprint {
+            sql.push_str(" AND fingerprint >= ? AND fingerprint < ?");
+            values.push(Value::Text(prefix.clone()));
+            values.push(Value::Text(format!("{prefix}\u{10FFFF}")));
+        }
+        if let Some(since) = query.window.since {
+            sql.push_str(" AND timestamp >= ?");
+            values.push(Value::Integer(since));
+        }
+        if let Some(until) = query.window.until {
+            sql.push_str(" AND timestamp < ?");
+            values.push(Value::Integer(until));
+        }
+        if let Some(verdict) = &query.verdict {
+            sql.push_str(" AND verdict = ?");
+            values.push(Value::Text(verdict.as_str().to_string()));
+        }
+        sql.push_str(" ORDER BY timestamp, seq");
+        Ok(read_records(&self.conn, &sql, values)?
+            .into_iter()
+            .filter(|record| query.matches(record))
+            .collect())
+    }
+}
@@ -0,0 +1,1005 @@
+use anyhow::{Result, bail};
+use fs2::FileExt;
+use log::warn;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::config::{self, StoreConfig};
+use crate::http_store::HttpStore;
+use crate::s3_store::S3Store;
+use crate::sqlite_store::SqliteStore;
+use crate::time_window::TimeWindow;
+use crate::vcs_provider;
+use uuid::Uuid;
+
+pub const TRUEFLOW_DIR: &str = ".trueflow";
+const DB_FILE: &str = "reviews.jsonl";
+const SQLITE_FILE: &str = "reviews.db";
+const S3_CACHE_DIR: &str = "s3-cache";
+/// Version 2 added `context`; versions 0 and 1 still read as-is.
+pub const CURRENT_VERSION: u32 = 2;
+
+fn default_version() -> u32 {
+    0 // Legacy records
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash, JsonSchema)]
+#[serde(tag = "type")]
+#[schemars(deny_unknown_fields)]
+pub enum Identity {
+    #[serde(rename = "email")]
+    Email {
+        #[schemars(email)]
+        email: String,
+    },
+    /// An automated reviewer. Never counts as a human approval.
+    #[serde(rename = "agent")]
+    Agent {
+        #[schemars(length(min = 1))]
+        name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        model: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        version: Option<String>,
+    },
+    /// A person as an OpenID Connect provider knows them, from `trueflow login`.
+    #[serde(rename = "oidc")]
+    Oidc {
+        /// Issuer URL.
+        #[schemars(length(min = 1))]
+        issuer: String,
+        /// The issuer's stable id for the person.
+        #[schemars(length(min = 1))]
+        subject: String,
+        /// Verified email claim, when the issuer shares one.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        email: Option<String>,
+    },
+    /// A trueflow rule, such as `auto-approve`, or a service account.
+    #[serde(rename = "machine")]
+    Machine {
+        #[schemars(length(min = 1))]
+        name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        version: Option<String>,
+    },
+    // Future: DID, etc.
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+#[schemars(deny_unknown_fields)]
+pub enum Verdict {
+    Approved,
+    Rejected,
+    Question,
+    Comment,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+#[schemars(deny_unknown_fields)]
+pub enum VcsSystem {
+    Git,
+    Mercurial,
+    Jujutsu,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, JsonSchema)]
+#[serde(tag = "type", rename_all = "lowercase")]
+#[schemars(deny_unknown_fields)]
+pub enum RepoRef {
+    Vcs {
+        system: VcsSystem,
+        /// A commit id, or `unknown` before the first commit.
+        #[schemars(regex(pattern = "^([0-9a-f]{7,40}|unknown)$"))]
+        revision: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+#[schemars(deny_unknown_fields)]
+pub enum BlockState {
+    Committed,
+    Uncommitted,
+    Unknown,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[schemars(deny_unknown_fields)]
+pub enum AttestationKind {
+    Pgp,
+    Ssh,
+    // Sigstore bundle in `signature`, Fulcio certificate in `public_key`.
+    Sigstore,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[schemars(deny_unknown_fields)]
+pub enum Canonicalization {
+    JcsV1,
+}
+
+/// What a ledger record means. Records without a kind are verdicts.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+#[schemars(deny_unknown_fields)]
+pub enum RecordKind {
+    #[default]
+    Verdict,
+    Assignment,
+    Claim,
+    Lineage,
+    Revocation,
+    Answer,
+    Resolution,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct Assignment {
+    pub assignee: Identity,
+    /// Repo-relative path prefix. When absent the assignment covers only
+    /// the record fingerprint.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Review request this assignment belongs to. Assignments from the same
+    /// request add reviewers instead of replacing each other.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request: Option<String>,
+}
+
+/// A reviewer working on a path, visible to others until it expires.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct Claim {
+    /// Repo-relative path prefix.
+    pub path: String,
+    #[schemars(range(min = 0))]
+    pub expires_at: i64,
+}
+
+/// Records that a block was edited from another one with the same
+/// normalized content, so approvals of `from` can be re-confirmed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct LineageLink {
+    /// Fingerprint of the earlier block.
+    pub from: String,
+    /// Hash of the content both blocks normalize to.
+    pub normalized: String,
+}
+
+/// Withdraws an earlier record, e.g. a verdict given by accident. Only the
+/// identity that wrote a record can revoke it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct Revocation {
+    /// Id of the revoked record.
+    pub record: String,
+}
+
+/// Replies to a question verdict and closes it; the reply is the note.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct Answer {
+    /// Id of the question record.
+    pub question: String,
+}
+
+/// What a `trueflow prereview` annotator said about a block. The summary is
+/// the note; the record is a `comment` under the `prereview` check, so it
+/// never changes review state.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct Prereview {
+    /// How risky the annotator thinks the block is, from 0 to 100.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(range(max = 100))]
+    pub risk: Option<u8>,
+    /// The verdict it would give; a hint, never recorded as one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggested_verdict: Option<Verdict>,
+}
+
+/// Marks the feedback of a verdict as dealt with; `feedback` hides it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct Resolution {
+    /// Id of the resolved verdict record.
+    pub record: String,
+}
+
+/// Where a verdict came from, for tracing it back to a tool run or session.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct Provenance {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session: Option<String>,
+    /// Reference to the prompt that produced the verdict (path, URL, or id).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+}
+
+impl Provenance {
+    pub fn is_empty(&self) -> bool {
+        self.tool.is_none() && self.session.is_none() && self.prompt.is_none()
+    }
+}
+
+/// What the reviewed block looked like when the verdict was given, so tools
+/// can show the verdict without re-scanning the repository at that commit.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct BlockContext {
+    /// Block kind as `scan` reports it, e.g. `function`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_kind: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Innermost named item containing the block, e.g. `parse`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+    /// Hash of the whole file at review time.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_hash: Option<String>,
+    /// Revision the reviewed changes were compared against.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_revision: Option<String>,
+}
+
+impl BlockContext {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct Attestation {
+    pub kind: AttestationKind,
+    pub canonicalization: Canonicalization,
+    pub signature: String,
+    pub public_key: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct Record {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "RecordKind::is_verdict")]
+    pub kind: RecordKind,
+    // Schema version
+    #[serde(default = "default_version")]
+    #[schemars(range(min = 0))]
+    pub version: u32,
+    pub fingerprint: String,
+    #[schemars(length(min = 1))]
+    pub check: String,
+    pub verdict: Verdict,
+
+    pub identity: Identity,
+
+    pub repo_ref: RepoRef,
+    pub block_state: BlockState,
+
+    #[schemars(range(min = 0))]
+    pub timestamp: i64,
+    pub path_hint: Option<String>,
+    pub line_hint: Option<u32>,
+    pub note: Option<String>,
+    #[schemars(inner(length(min = 1)))]
+    pub tags: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assignment: Option<Assignment>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub claim: Option<Claim>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lineage: Option<LineageLink>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revocation: Option<Revocation>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub answer: Option<Answer>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolution: Option<Resolution>,
+    /// Id of the record this one replies to, threading a discussion.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub in_reply_to: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
+    /// Reviewer's confidence in the verdict, from 0 to 1.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(range(min = 0.0, max = 1.0))]
+    pub confidence: Option<f64>,
+    /// External references (issue URLs or keys like `JIRA-123`) the verdict points at.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(inner(length(min = 1)))]
+    pub refs: Option<Vec<String>>,
+    /// The block as it was at review time; written since version 2.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<BlockContext>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prereview: Option<Prereview>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attestations: Option<Vec<Attestation>>,
+}
+
+impl Record {
+    /// A new `verdict` on `fingerprint` under `check`, with no hints, notes or
+    /// payloads set.
+    pub fn verdict(
+        fingerprint: String,
+        check: String,
+        verdict: Verdict,
+        identity: Identity,
+        repo_ref: RepoRef,
+        block_state: BlockState,
+        timestamp: i64,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            kind: RecordKind::Verdict,
+            version: CURRENT_VERSION,
+            fingerprint,
+            check,
+            verdict,
+            identity,
+            repo_ref,
+            block_state,
+            timestamp,
+            path_hint: None,
+            line_hint: None,
+            note: None,
+            tags: None,
+            assignment: None,
+            claim: None,
+            lineage: None,
+            revocation: None,
+            answer: None,
+            resolution: None,
+            in_reply_to: None,
+            provenance: None,
+            confidence: None,
+            refs: None,
+            context: None,
+            prereview: None,
+            attestations: None,
+        }
+    }
+
+    /// A new record of a `kind` that carries no verdict; the caller sets its
+    /// payload. Its check is the kind's name, since older readers only fold
+    /// check=review records into verdict state.
+    pub fn non_verdict(
+        kind: RecordKind,
+        fingerprint: String,
+        identity: Identity,
+        repo_ref: RepoRef,
+        timestamp: i64,
+    ) -> Self {
+        Self {
+            kind,
+            ..Self::verdict(
+                fingerprint,
+                kind.as_str().to_string(),
+                Verdict::Comment,
+                identity,
+                repo_ref,
+                BlockState::Unknown,
+                timestamp,
+            )
+        }
+    }
+
+    pub fn signing_payload(&self) -> Result<String> {
+        let mut payload = self.clone();
+        payload.attestations = None;
+        Ok(serde_jcs::to_string(&payload)?)
+    }
+}
+
+impl Identity {
+    /// Stable display form: the email (an OIDC identity's verified email, or
+    /// `oidc:<subject>` without one), `agent:<name>` for agents, or
+    /// `machine:<name>` for rules.
+    pub fn label(&self) -> String {
+        match self {
+            Identity::Email { email } => email.clone(),
+            Identity::Oidc { email, subject, .. } => {
+                email.clone().unwrap_or_else(|| format!("oidc:{subject}"))
+            }
+            Identity::Agent { name, .. } => format!("agent:{name}"),
+            Identity::Machine { name, .. } => format!("machine:{name}"),
+        }
+    }
+
+    /// The serialized `type`: `email`, `oidc`, `agent` or `machine`.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Identity::Email { .. } => "email",
+            Identity::Oidc { .. } => "oidc",
+            Identity::Agent { .. } => "agent",
+            Identity::Machine { .. } => "machine",
+        }
+    }
+
+    pub fn is_agent(&self) -> bool {
+        matches!(self, Identity::Agent { .. })
+    }
+
+    pub fn is_machine(&self) -> bool {
+        matches!(self, Identity::Machine { .. })
+    }
+
+    /// A person, as opposed to an agent or machine identity.
+    pub fn is_human(&self) -> bool {
+        !self.is_agent() && !self.is_machine()
+    }
+
+    /// Whether this identity's approval counts toward `required` approvals.
+    /// Agent names are free to choose, so a quorum above one counts people
+    /// only; a single required approval accepts any identity.
+    pub fn counts_toward_quorum(&self, required: usize) -> bool {
+        required <= 1 || self.is_human()
+    }
+}
+
+impl fmt::Display for Identity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.label())?;
+        let (model, version) = match self {
+            Identity::Email { .. } => return Ok(()),
+            Identity::Oidc { issuer, .. } => return write!(f, " ({issuer})"),
+            Identity::Agent { model, version, .. } => (model, version),
+            Identity::Machine { version, .. } => (&None, version),
+        };
+        match (model, version) {
+            (Some(model), Some(version)) => write!(f, " ({model} {version})"),
+            (Some(model), None) => write!(f, " ({model})"),
+            (None, Some(version)) => write!(f, " ({version})"),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+impl RecordKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RecordKind::Verdict => "verdict",
+            RecordKind::Assignment => "assignment",
+            RecordKind::Claim => "claim",
+            RecordKind::Lineage => "lineage",
+            RecordKind::Revocation => "revocation",
+            RecordKind::Answer => "answer",
+            RecordKind::Resolution => "resolution",
+        }
+    }
+
+    pub fn is_verdict(&self) -> bool {
+        matches!(self, RecordKind::Verdict)
+    }
+}
+
+impl Verdict {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Verdict::Approved => "approved",
+            Verdict::Rejected => "rejected",
+            Verdict::Question => "question",
+            Verdict::Comment => "comment",
+        }
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Verdict {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "approved" => Ok(Verdict::Approved),
+            "rejected" => Ok(Verdict::Rejected),
+            "question" => Ok(Verdict::Question),
+            "comment" => Ok(Verdict::Comment),
+            _ => Err(anyhow::anyhow!("Unknown verdict: {}", value)),
+        }
+    }
+}
+
+impl VcsSystem {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VcsSystem::Git => "git",
+            VcsSystem::Mercurial => "mercurial",
+            VcsSystem::Jujutsu => "jujutsu",
+        }
+    }
+}
+
+impl fmt::Display for VcsSystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl BlockState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BlockState::Committed => "committed",
+            BlockState::Uncommitted => "uncommitted",
+            BlockState::Unknown => "unknown",
+        }
+    }
+}
+
+impl From<crate::vcs::BlockStateResult> for BlockState {
+    fn from(result: crate::vcs::BlockStateResult) -> Self {
+        match result {
+            crate::vcs::BlockStateResult::Committed => BlockState::Committed,
+            crate::vcs::BlockStateResult::Uncommitted => BlockState::Uncommitted,
+            crate::vcs::BlockStateResult::Unknown => BlockState::Unknown,
+        }
+    }
+}
+
+impl fmt::Display for BlockState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+pub trait ReviewStore {
+    fn read_history(&self) -> Result<Vec<Record>>;
+    fn append(&self, record: Record) -> Result<()>;
+    /// Appends `records` in order as one write, so readers see all or none.
+    fn append_all(&self, records: Vec<Record>) -> Result<()>;
+    /// Replaces the whole ledger with `records`, in order.
+    fn rewrite(&self, records: &[Record]) -> Result<()>;
+
+    /// Verdict records matching `query`, oldest first.
+    fn query(&self, query: &RecordQuery) -> Result<Vec<Record>> {
+        let mut records: Vec<Record> = self
+            .read_history()?
+            .into_iter()
+            .filter(|record| query.matches(record))
+            .collect();
+        records.sort_by_key(|record| record.timestamp);
+        Ok(records)
+    }
+}
+
+/// Filters over verdict records; unset fields match everything.
+#[derive(Debug, Clone, Default)]
+pub struct RecordQuery {
+    /// A full fingerprint or a prefix of one.
+    pub fingerprint: Option<String>,
+    /// A file path hint, or a directory containing it.
+    pub path: Option<String>,
+    pub window: TimeWindow,
+    pub verdict: Option<Verdict>,
+}
+
+impl RecordQuery {
+    pub fn matches(&self, record: &Record) -> bool {
+        record.kind.is_verdict()
+            && self
+                .fingerprint
+                .as_deref()
+                .is_none_or(|prefix| record.fingerprint.starts_with(prefix))
+            && self.path.as_deref().is_none_or(|path| {
+                record
+                    .path_hint
+                    .as_deref()
+                    .is_some_and(|hint| path_matches(hint, path))
+            })
+            && self.window.contains(record.timestamp)
+            && self
+                .verdict
+                .as_ref()
+                .is_none_or(|verdict| &record.verdict == verdict)
+    }
+}
+
+fn path_matches(hint: &str, path: &str) -> bool {
+    let hint = hint.trim_start_matches("./");
+    let path = path.trim_start_matches("./").trim_end_matches('/');
+    path.is_empty()
+        || hint == path
+        || hint
+            .strip_prefix(path)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
+
+/// Drops records revoked by their own author. Revocation records stay, so
+/// the ledger still shows what was withdrawn.
+pub fn without_revoked(records: Vec<Record>) -> Vec<Record> {
+    let revoked: HashSet<(String, String)> = records
+        .iter()
+        .filter_map(|record| {
+            let revocation = record.revocation.as_ref()?;
+            Some((revocation.record.clone(), record.identity.label()))
+        })
+        .collect();
+    if revoked.is_empty() {
+        return records;
+    }
+    records
+        .into_iter()
+        .filter(|record| !revoked.contains(&(record.id.clone(), record.identity.label())))
+        .collect()
+}
+
+/// The record among those `wanted` keeps whose id is `id`, or starts with
+/// it if that is unique. `noun` names the records in errors.
+pub fn find_by_id<'a>(
+    records: &'a [Record],
+    id: &str,
+    noun: &str,
+    wanted: impl Fn(&Record) -> bool,
+) -> Result<&'a Record> {
+    let id = id.trim();
+    if id.is_empty() {
+        bail!("A {} id must not be empty", noun);
+    }
+    let matches: Vec<&Record> = records
+        .iter()
+        .filter(|record| wanted(record) && record.id.starts_with(id))
+        .collect();
+    if let Some(exact) = matches.iter().find(|record| record.id == id) {
+        return Ok(exact);
+    }
+    match matches.as_slice() {
+        [] => bail!("No {} with id {}", noun, id),
+        [record] => Ok(record),
+        _ => bail!(
+            "Id {} matches more than one {}; use more characters",
+            id,
+            noun
+        ),
+    }
+}
+
+/// The last verdict recorded under `check` per fingerprint.
+pub fn latest_verdicts(records: &[Record], check: &str) -> HashMap<String, Verdict> {
+    let mut sorted = records.to_vec();
+    sorted.sort_by_key(|record| record.timestamp);
+    let mut verdicts = HashMap::new();
+    for record in sorted {
+        if record.kind.is_verdict() && record.check == check {
+            verdicts.insert(record.fingerprint, record.verdict);
+        }
+    }
+    verdicts
+}
+
+/// Combines each identity's latest verdict under `check` per fingerprint. A
+/// block is approved once `required` distinct identities currently approve it
+/// and none currently rejects it; short of that it carries the most recent
+/// non-approval verdict, or no verdict while approvals are still pending.
+/// Above one approval only human identities count toward `required`.
+pub fn quorum_verdicts(
+    records: &[Record],
+    check: &str,
+    required: usize,
+) -> HashMap<String, Verdict> {
+    let mut sorted: Vec<&Record> = records
+        .iter()
+        .filter(|record| record.kind.is_verdict() && record.check == check)
+        .collect();
+    sorted.sort_by_key(|record| record.timestamp);
+
+    let mut by_identity: HashMap<&str, HashMap<String, (usize, &Verdict, bool)>> = HashMap::new();
+    for (order, record) in sorted.iter().enumerate() {
+        by_identity
+            .entry(record.fingerprint.as_str())
+            .or_default()
+            .insert(
+                record.identity.label(),
+                (
+                    order,
+                    &record.verdict,
+                    record.identity.counts_toward_quorum(required),
+                ),
+            );
+    }
+
+    let mut verdicts = HashMap::new();
+    for (fingerprint, latest) in by_identity {
+        let approvals = latest
+            .values()
+            .filter(|(_, verdict, counts)| *counts && **verdict == Verdict::Approved)
+            .count();
+        let rejected = latest
+            .values()
+            .any(|(_, verdict, _)| **verdict == Verdict::Rejected);
+        let verdict = if rejected {
+            Some(Verdict::Rejected)
+        } else if approvals >= required.max(1) {
+            Some(Verdict::Approved)
+        } else {
+            latest
+                .values()
+                .filter(|(_, verdict, _)| **verdict != Verdict::Approved)
+                .max_by_key(|(order, _, _)| *order)
+                .map(|(_, verdict, _)| (*verdict).clone())
+        };
+        if let Some(verdict) = verdict {
+            verdicts.insert(fingerprint.to_string(), verdict);
+        }
+    }
+    verdicts
+}
+
+pub fn approved_hashes_from_verdicts(verdicts: &HashMap<String, Verdict>) -> HashSet<String> {
+    verdicts
+        .iter()
+        .filter_map(|(hash, verdict)| {
+            if verdict == &Verdict::Approved {
+                Some(hash.clone())
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Which store holds the ledger: `[store] backend`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StoreBackend {
+    /// `.trueflow/reviews.jsonl`, one record per line.
+    #[default]
+    Jsonl,
+    /// `.trueflow/reviews.db`, indexed; imports the JSONL ledger on first use.
+    Sqlite,
+    /// A review server at `[store] url`, shared by the whole team.
+    Http,
+    /// One object per record in the S3-compatible bucket at `[store] url`.
+    S3,
+}
+
+/// The review store `[store] backend` selects.
+pub fn open_store() -> Result<Box<dyn ReviewStore>> {
+    open_store_in(&workspace_root()?, &config::load()?.store)
+}
+
+/// The store `config` selects for the workspace at `root`.
+pub fn open_store_in(root: &Path, config: &StoreConfig) -> Result<Box<dyn ReviewStore>> {
+    let files = FileStore::at(root.to_path_buf())?;
+    Ok(match config.backend {
+        StoreBackend::Jsonl => Box::new(files),
+        StoreBackend::Sqlite => Box::new(SqliteStore::open(&files)?),
+        StoreBackend::Http => Box::new(HttpStore::open(config)?),
+        StoreBackend::S3 => Box::new(S3Store::open(config, &files)?),
+    })
+}
+
+/// The directory reviews are anchored at: the repository root, else the
+/// nearest directory with a `.trueflow`, else the working directory.
+pub fn workspace_root() -> Result<PathBuf> {
+    workspace_root_in(&std::env::current_dir()?)
+}
+
+/// [`workspace_root`] for `start` rather than the working directory.
+pub fn workspace_root_in(start: &Path) -> Result<PathBuf> {
+    if let Some(root) = vcs_provider::repo_root_in(start) {
+        return Ok(root);
+    }
+    let start_dir = std::path::absolute(start)?;
+    Ok(start_dir
+        .ancestors()
+        .find(|dir| dir.join(TRUEFLOW_DIR).exists())
+        .map(Path::to_path_buf)
+        .unwrap_or(start_dir))
+}
+
+pub struct FileStore {
+    root_path: PathBuf,
+}
+
+/// A ledger line and the record it holds. Rewrites put the line back as it
+/// was, so fields written by a newer trueflow survive.
+#[derive(Debug, Clone)]
+pub struct LedgerLine {
+    pub record: Record,
+    pub raw: String,
+}
+
+impl From<Record> for LedgerLine {
+    /// A record that was not read from a line, rendered as this version
+    /// writes it.
+    fn from(record: Record) -> Self {
+        let raw = serde_json::to_string(&record).unwrap_or_default();
+        Self { record, raw }
+    }
+}
+
+/// Every record in the ledger text `content` read from `source`. Unlike
+/// [`ReviewStore::read_history`], a line that does not parse is an error:
+/// callers go on to rewrite the ledger and would otherwise drop it.
+pub fn parse_ledger_lines(content: &str, source: &str) -> Result<Vec<LedgerLine>> {
+    let mut lines = Vec::new();
+    let mut unreadable = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<Record>(line) {
+            Ok(record) => lines.push(LedgerLine {
+                record,
+                raw: line.to_string(),
+            }),
+            Err(err) => unreadable.push(format!("{source}:{}: {err}", index + 1)),
+        }
+    }
+    if !unreadable.is_empty() {
+        bail!(
+            "{source} has {} unreadable line(s); fix or remove them first:\n{}",
+            unreadable.len(),
+            unreadable.join("\n")
+        );
+    }
+    Ok(lines)
+}
+
+/// The JSONL ledger under its exclusive lock, for a read-modify-write that
+/// must not lose a concurrent append. The lock is released on drop.
+pub struct LockedLedger {
+    file: fs::File,
+}
+
+impl LockedLedger {
+    pub fn read(&mut self) -> Result<String> {
+        let mut content = String::new();
+        self.file.rewind()?;
+        self.file.read_to_string(&mut content)?;
+        Ok(content)
+    }
+
+    pub fn replace(&mut self, content: &str) -> Result<()> {
+        self.file.set_len(0)?;
+        self.file.rewind()?;
+        self.file.write_all(content.as_bytes())?;
+        Ok(())
+    }
+}
+
+fn ensure_trueflow_dir(root: &Path) -> Result<()> {
+    let trueflow_dir = root.join(TRUEFLOW_DIR);
+    if !trueflow_dir.exists() {
+        fs::create_dir(&trueflow_dir)?;
+    }
+    Ok(())
+}
+
+impl FileStore {
+    pub fn new() -> Result<Self> {
+        Self::at(workspace_root()?)
+    }
+
+    /// The store of the workspace at `root_path`.
+    pub fn at(root_path: PathBuf) -> Result<Self> {
+        ensure_trueflow_dir(&root_path)?;
+        Ok(Self { root_path })
+    }
+
+    /// The workspace's `.trueflow` directory, home of the ledger and of the
+    /// local state kept next to it.
+    pub fn trueflow_dir(&self) -> PathBuf {
+        self.root_path.join(TRUEFLOW_DIR)
+    }
+
+    pub fn db_path(&self) -> PathBuf {
+        self.trueflow_dir().join(DB_FILE)
+    }
+
+    pub fn sqlite_path(&self) -> PathBuf {
+        self.trueflow_dir().join(SQLITE_FILE)
+    }
+
+    /// Where the `s3` backend keeps the record objects it has downloaded.
+    pub fn s3_cache_dir(&self) -> PathBuf {
+        self.trueflow_dir().join(S3_CACHE_DIR)
+    }
+
+    /// Takes the ledger's exclusive lock, creating the file if needed.
+    /// Appends, including this process's own, wait until it is dropped.
+    pub fn lock(&self) -> Result<LockedLedger> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(self.db_path())?;
+        file.lock_exclusive()?;
+        Ok(LockedLedger { file })
+    }
+}
+
+impl ReviewStore for FileStore {
+    fn read_history(&self) -> Result<Vec<Record>> {
+        let db_path = self.db_path();
+
+        if !db_path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let file = fs::File::open(db_path)?;
+        file.lock_shared()?; // Shared lock for reading
+
+        let reader = BufReader::new(file);
+        let mut records = Vec::new();
+
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<Record>(&line) {
+                Ok(record) => records.push(record),
+                Err(err) => warn!("Skipping malformed record: {}", err),
+            }
+        }
+
+        // Lock releases when file is dropped
+        Ok(records)
+    }
+
+    fn append(&self, record: Record) -> Result<()> {
+        let db_path = self.db_path();
+
+        let mut file = OpenOptions::new().create(true).append(true).open(db_path)?;
+        file.lock_exclusive()?; // Exclusive lock for appending
+
+        let mut line = serde_json::to_string(&record)?;
+        line.push('\n');
+
+        file.write_all(line.as_bytes())?;
+
+        // Lock releases when file is dropped
+        Ok(())
+    }
+
+    fn append_all(&self, records: Vec<Record>) -> Result<()> {
+        let mut content = String::new();
+        for record in &records {
+            content.push_str(&serde_json::to_string(record)?);
+            content.push('\n');
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.db_path())?;
+        file.lock_exclusive()?;
+        file.write_all(content.as_bytes())?;
+        Ok(())
+    }
+
+    fn rewrite(&self, records: &[Record]) -> Result<()> {
+        let mut content = String::new();
+        for record in records {
+            content.push_str(&serde_json::to_string(record)?);
+            content.push('\n');
+        }
+        // Truncate only once the lock is held so concurrent readers never see
+        // a half-written ledger.
+        self.lock()?.replace(&content)
+    }
+}
@@ -1,6 +1,7 @@
 use crate::analysis::Language;
-use crate::block::{Block, BlockKind};
-use crate::hashing::hash_str;
+use crate::block::{Block, BlockKind, BlockMetrics};
+use crate::config::Normalization;
+use crate::hashing::{self, hash_str};
 use crate::text_split::{paragraph_break_regex, split_by_paragraph_breaks};
 use anyhow::{Context, Result};
 use log::info;
@@ -8,6 +9,11 @@ use tree_sitter::Parser;
 use tree_sitter_md;
 
 pub fn split(block: &Block, lang: Language) -> Result<Vec<Block>> {
+    split_with(block, lang, hashing::configured_normalization())
+}
+
+/// [`split`], fingerprinting the sub-blocks under `mode`.
+pub fn split_with(block: &Block, lang: Language, mode: Normalization) -> Result<Vec<Block>> {
     info!(
         "sub_splitter start (lang={:?}, kind={}, bytes={}, hash={})",
         lang,
@@ -16,7 +22,8 @@ pub fn split(block: &Block, lang: Language) -> Result<Vec<Block>> {
         block.hash
     );
 
-    let blocks = match lang {
+    let language = lang.clone();
+    let mut blocks = match lang {
         Language::Markdown => split_markdown(block)?,
         Language::Text => split_sentences(block)?,
         Language::Toml | Language::Nix | Language::Just => split_code(block)?,
@@ -37,9 +44,15 @@ pub fn split(block: &Block, lang: Language) -> Result<Vec<Block>> {
         {
             split_js_function(block, lang)?
         }
+        Language::C | Language::Cpp
+            if matches!(block.kind, BlockKind::Function | BlockKind::Method) =>
+        {
+            split_c_function(block, lang)?
+        }
         _ => split_code(block)?, // Default for Rust, Python, etc.
     };
 
+    hashing::apply_normalization_with(&mut blocks, &language, mode);
     info!("sub_splitter done (blocks={})", blocks.len());
     Ok(blocks)
 }
@@ -336,6 +349,24 @@ fn split_js_function(block: &Block, lang: Language) -> Result<Vec<Block>> {
     )
 }
 
+fn split_c_function(block: &Block, lang: Language) -> Result<Vec<Block>> {
+    let language = match lang {
+        Language::Cpp => tree_sitter_cpp::LANGUAGE.into(),
+        _ => tree_sitter_c::LANGUAGE.into(),
+    };
+    split_function_with_parser(
+        block,
+        FunctionSplitConfig {
+            language,
+            function_kind: "function_definition",
+            body_kind: "compound_statement",
+            signature_end: signature_end_offset,
+            comment_kinds: &["comment"],
+            trim_closing_brace: true,
+        },
+    )
+}
+
 fn split_function_with_parser(
     block: &Block,
     config: FunctionSplitConfig<'_>,
@@ -624,6 +655,7 @@ fn create_sub_block_with_kind(
 
     Block {
         hash: hash_str(content),
+        metrics: BlockMetrics::from_content(content),
         content: content.to_string(),
         kind,
         tags: parent.tags.clone(),
@@ -640,6 +672,11 @@ fn create_sub_block_with_kind(
         // Let's set 0 for MVP to fix compilation.
         start_line,
         end_line,
+        previous: None,
+        blame: None,
+        commits: Vec::new(),
+        prereview: None,
+        risk: None,
     }
 }
 
@@ -658,6 +695,12 @@ mod tests {
             complexity: 0,
             start_line: 0,
             end_line: content.lines().count(),
+            metrics: BlockMetrics::from_content(content),
+            previous: None,
+            blame: None,
+            commits: Vec::new(),
+            prereview: None,
+            risk: None,
         }
     }
 
@@ -751,6 +794,16 @@ mod tests {
         assert!(!chunks.iter().any(|b| b.kind == BlockKind::Impl));
     }
 
+    #[test]
+    fn test_split_c_function_body() {
+        let content = "int main(void) {\n    int x = 1;\n\n    // done\n    return x;\n}";
+        let block = make_block(content, BlockKind::Function);
+        let chunks = split(&block, Language::C).unwrap();
+        assert_eq!(chunks[0].kind, BlockKind::FunctionSignature);
+        assert!(chunks.iter().any(|b| b.kind == BlockKind::Comment));
+        assert_eq!(merge_blocks(chunks), content);
+    }
+
     #[test]
     fn test_split_nix_paragraphs_preserve_content() {
         let content = "{ foo = \"bar\"; }\n\n{ baz = \"qux\"; }";
@@ -0,0 +1,199 @@
+use crate::store::{Record, RecordKind};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+/// A record in a block's discussion and the replies to it.
+#[derive(Debug, Clone, Serialize)]
+pub struct ThreadNode {
+    #[serde(flatten)]
+    pub record: Record,
+    pub replies: Vec<ThreadNode>,
+}
+
+impl ThreadNode {
+    /// This record and every reply below it, depth first, with how deep
+    /// each one is nested.
+    pub fn flatten(&self) -> Vec<(usize, &Record)> {
+        let mut entries = Vec::new();
+        self.walk(0, &mut entries);
+        entries
+    }
+
+    fn walk<'a>(&'a self, depth: usize, entries: &mut Vec<(usize, &'a Record)>) {
+        entries.push((depth, &self.record));
+        for reply in &self.replies {
+            reply.walk(depth + 1, entries);
+        }
+    }
+}
+
+/// What `record` continues: the record it replies to, the question it
+/// answers, or the verdict it resolves.
+pub fn parent_id(record: &Record) -> Option<&str> {
+    record
+        .in_reply_to
+        .as_deref()
+        .or_else(|| {
+            record
+                .answer
+                .as_ref()
+                .map(|answer| answer.question.as_str())
+        })
+        .or_else(|| {
+            record
+                .resolution
+                .as_ref()
+                .map(|resolution| resolution.record.as_str())
+        })
+}
+
+fn in_discussion(record: &Record) -> bool {
+    matches!(
+        record.kind,
+        RecordKind::Verdict | RecordKind::Answer | RecordKind::Resolution
+    )
+}
+
+/// The verdicts, answers and resolutions on `fingerprint` as threads, oldest
+/// first. A reply whose parent is not among them starts a thread of its own.
+pub fn threads(records: &[Record], fingerprint: &str) -> Vec<ThreadNode> {
+    let mut discussion: Vec<&Record> = records
+        .iter()
+        .filter(|record| record.fingerprint == fingerprint && in_discussion(record))
+        .collect();
+    discussion.sort_by_key(|record| record.timestamp);
+    let ids: HashSet<&str> = discussion.iter().map(|record| record.id.as_str()).collect();
+
+    let mut roots = Vec::new();
+    let mut children: HashMap<&str, Vec<&Record>> = HashMap::new();
+    for record in discussion {
+        match parent_id(record).filter(|parent| ids.contains(parent) && *parent != record.id) {
+            Some(parent) => children.entry(parent).or_default().push(record),
+            None => roots.push(record),
+        }
+    }
+    roots
+        .into_iter()
+        .map(|record| build(record, &children, &mut HashSet::new()))
+        .collect()
+}
+
+/// Threads for every block with a discussion, by fingerprint.
+pub fn by_block(records: &[Record]) -> HashMap<String, Vec<ThreadNode>> {
+    let mut grouped: HashMap<&str, Vec<Record>> = HashMap::new();
+    for record in records.iter().filter(|record| in_discussion(record)) {
+        grouped
+            .entry(&record.fingerprint)
+            .or_default()
+            .push(record.clone());
+    }
+    grouped
+        .into_iter()
+        .map(|(fingerprint, records)| (fingerprint.to_string(), threads(&records, fingerprint)))
+        .collect()
+}
+
+/// The verdict, or `answer` / `resolution` for the records that close one.
+pub fn label(record: &Record) -> &str {
+    match record.kind {
+        RecordKind::Verdict => record.verdict.as_str(),
+        ref kind => kind.as_str(),
+    }
+}
+
+fn build<'a>(
+    record: &'a Record,
+    children: &HashMap<&str, Vec<&'a Record>>,
+    seen: &mut HashSet<&'a str>,
+) -> ThreadNode {
+    seen.insert(&record.id);
+    let mut replies = Vec::new();
+    for reply in children.get(record.id.as_str()).into_iter().flatten() {
+        // Ids are unique in a sane ledger; a merged one could still loop.
+        if !seen.contains(reply.id.as_str()) {
+            replies.push(build(reply, children, seen));
+        }
+    }
+    ThreadNode {
+        record: record.clone(),
+        replies,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::question::answer_record;
+    use crate::store::{BlockState, Identity, RepoRef, VcsSystem, Verdict};
+
+    fn record(id: &str, fingerprint: &str, reply_to: Option<&str>, at: i64) -> Record {
+        Record {
+            id: id.to_string(),
+            note: Some(id.to_string()),
+            in_reply_to: reply_to.map(str::to_string),
+            ..Record::verdict(
+                fingerprint.to_string(),
+                "review".to_string(),
+                Verdict::Comment,
+                Identity::Email {
+                    email: "alice@example.com".to_string(),
+                },
+                RepoRef::Vcs {
+                    system: VcsSystem::Git,
+                    revision: "deadbeef".to_string(),
+                },
+                BlockState::Committed,
+                at,
+            )
+        }
+    }
+
+    fn shape(threads: &[ThreadNode]) -> Vec<(usize, String)> {
+        threads
+            .iter()
+            .flat_map(ThreadNode::flatten)
+            .map(|(depth, record)| (depth, record.id.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn replies_nest_under_their_parents() {
+        let question = Record {
+            verdict: Verdict::Question,
+            ..record("q", "a", None, 3)
+        };
+        let answer = answer_record(
+            &question,
+            &question.identity,
+            question.repo_ref.clone(),
+            None,
+            4,
+        );
+        let records = vec![
+            record("root", "a", None, 1),
+            record("reply", "a", Some("root"), 2),
+            record("nested", "a", Some("reply"), 5),
+            record("orphan", "a", Some("gone"), 6),
+            record("other", "b", None, 1),
+            question,
+            answer.clone(),
+        ];
+        assert_eq!(
+            shape(&threads(&records, "a")),
+            [
+                (0, "root".to_string()),
+                (1, "reply".to_string()),
+                (2, "nested".to_string()),
+                (0, "q".to_string()),
+                (1, answer.id),
+                (0, "orphan".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn self_replies_do_not_loop() {
+        let records = vec![record("x", "a", Some("x"), 1)];
+        assert_eq!(shape(&threads(&records, "a")), [(0, "x".to_string())]);
+    }
+}
@@ -0,0 +1,102 @@
+use crate::vcs;
+use anyhow::{Context, Result, anyhow};
+use chrono::{NaiveDate, TimeZone, Utc};
+
+/// Half-open `[since, until)` range of unix timestamps; open ends are unbounded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TimeWindow {
+    pub since: Option<i64>,
+    pub until: Option<i64>,
+}
+
+impl TimeWindow {
+    pub fn parse(since: Option<&str>, until: Option<&str>, now: i64) -> Result<Self> {
+        Ok(Self {
+            since: since
+                .map(|value| parse_time_bound(value, now))
+                .transpose()?,
+            until: until
+                .map(|value| parse_time_bound(value, now))
+                .transpose()?,
+        })
+    }
+
+    pub fn contains(&self, timestamp: i64) -> bool {
+        self.since.is_none_or(|since| timestamp >= since)
+            && self.until.is_none_or(|until| timestamp < until)
+    }
+}
+
+/// Accepts a relative age (`30m`, `12h`, `7d`, `2w`), a date (`2024-05-01`,
+/// midnight UTC), or a unix timestamp.
+pub fn parse_time_bound(value: &str, now: i64) -> Result<i64> {
+    let value = value.trim();
+    if let Ok(timestamp) = value.parse::<i64>() {
+        return Ok(timestamp);
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        let midnight = date
+            .and_hms_opt(0, 0, 0)
+            .ok_or_else(|| anyhow!("Invalid date: {}", value))?;
+        return Ok(Utc.from_utc_datetime(&midnight).timestamp());
+    }
+
+    let split = value
+        .find(|ch: char| !ch.is_ascii_digit())
+        .ok_or_else(|| anyhow!("Invalid time: {}", value))?;
+    let (amount, unit) = value.split_at(split);
+    let amount: i64 = amount
+        .parse()
+        .map_err(|_| anyhow!("Invalid time: {}", value))?;
+    let unit_secs = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => {
+            return Err(anyhow!(
+                "Unknown time unit in '{}' (use s, m, h, d, w)",
+                value
+            ));
+        }
+    };
+    Ok(now - amount * unit_secs)
+}
+
+/// `since` as a time bound, else as the commit time of a revision.
+pub fn since_timestamp(since: &str, now: i64) -> Result<i64> {
+    parse_time_bound(since, now).or_else(|_| {
+        vcs::commit_time(since)
+            .with_context(|| format!("--since {since} is neither a time nor a revision"))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_relative_dates_and_timestamps() {
+        let now = 1_000_000;
+        assert_eq!(parse_time_bound("7d", now).unwrap(), now - 7 * 86_400);
+        assert_eq!(parse_time_bound("90m", now).unwrap(), now - 5_400);
+        assert_eq!(parse_time_bound("1700000000", now).unwrap(), 1_700_000_000);
+        assert_eq!(parse_time_bound("2024-05-01", now).unwrap(), 1_714_521_600);
+        assert!(parse_time_bound("7y", now).is_err());
+        assert!(parse_time_bound("soon", now).is_err());
+    }
+
+    #[test]
+    fn window_is_half_open() {
+        let window = TimeWindow {
+            since: Some(10),
+            until: Some(20),
+        };
+        assert!(window.contains(10));
+        assert!(window.contains(19));
+        assert!(!window.contains(20));
+        assert!(!window.contains(9));
+        assert!(TimeWindow::default().contains(0));
+    }
+}
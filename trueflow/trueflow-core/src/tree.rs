@@ -170,6 +170,33 @@ impl Tree {
         self.file_paths.iter().map(|path| path.as_str())
     }
 
+    /// Attaches `blocks` (sub-blocks of the block at `parent`) as its
+    /// children, after any it already has.
+    pub fn add_sub_blocks(&mut self, parent: TreeNodeId, blocks: Vec<Block>) -> Vec<TreeNodeId> {
+        let (path, language) = {
+            let node = self.node(parent);
+            (node.path.clone(), node.language.clone())
+        };
+        let mut ids = Vec::new();
+        for block in blocks {
+            let id = TreeNodeId(self.nodes.len());
+            self.nodes.push(TreeNode {
+                id,
+                parent: Some(parent),
+                kind: TreeNodeKind::Block,
+                name: block_label(&block),
+                path: path.clone(),
+                hash: block.hash.clone(),
+                children: Vec::new(),
+                block: Some(block),
+                language: language.clone(),
+            });
+            ids.push(id);
+        }
+        self.nodes[parent.0].children.extend(ids.iter().copied());
+        ids
+    }
+
     pub fn is_node_covered(&self, id: TreeNodeId, approved_hashes: &HashSet<String>) -> bool {
         self.ancestors(id)
             .iter()
@@ -495,4 +522,35 @@ mod tests {
 
         assert_eq!(hash_first, hash_second);
     }
+
+    #[test]
+    fn sub_blocks_become_children_of_their_block() {
+        let content = "fn a() {\n    let x = 1;\n\n    x\n}\n";
+        let file = FileState {
+            path: "a.rs".to_string(),
+            language: Language::Rust,
+            blocks: vec![Block::new(content.to_string(), BlockKind::Function, 0, 5)],
+            file_hash: "hash-a".to_string(),
+            generated: false,
+        };
+        let mut tree = build_tree_from_files(&[file]);
+        let file_id = tree.find_by_path("a.rs").expect("file");
+        let block_id = tree.node(file_id).children[0];
+
+        let parts = vec![
+            Block::new("let x = 1;".to_string(), BlockKind::CodeParagraph, 1, 2),
+            Block::new("x".to_string(), BlockKind::CodeParagraph, 3, 4),
+        ];
+        let ids = tree.add_sub_blocks(block_id, parts);
+
+        assert_eq!(tree.node(block_id).children, ids);
+        let second = tree.node(ids[1]);
+        assert_eq!(second.parent, Some(block_id));
+        assert_eq!(second.path, "a.rs");
+        assert_eq!(second.name, "CodeParagraph:L4-L4");
+        assert_eq!(
+            tree.node_by_path_and_hash("a.rs", &second.hash),
+            Some(ids[1])
+        );
+    }
 }
@@ -0,0 +1,894 @@
+use crate::analysis::{self, Language};
+use crate::block::Block;
+use crate::block_splitter;
+use crate::config;
+use crate::hashing;
+use crate::scanner::{self, ScanSettings};
+use crate::store::VcsSystem;
+use crate::vcs_provider;
+use anyhow::{Context, Result};
+use gix::bstr::ByteSlice;
+use gix::object::tree::{EntryKind, EntryMode};
+use gix::status::UntrackedFiles;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// `--base`, set once at startup.
+static BASE_OVERRIDE: OnceLock<String> = OnceLock::new();
+
+/// Makes `base` the ref diffs against main compare to, over `[vcs] base_branch`.
+pub fn configure_base(base: Option<String>) {
+    if let Some(base) = base {
+        let _ = BASE_OVERRIDE.set(base);
+    }
+}
+
+/// `--base`, else `[vcs] base_branch`; `None` means main, then master.
+pub fn configured_base() -> Option<String> {
+    BASE_OVERRIDE.get().cloned().or_else(|| {
+        config::load()
+            .ok()
+            .and_then(|config| config.vcs.base_branch)
+    })
+}
+
+/// [`configured_base`] with `config` in place of the current directory's.
+pub fn base_from(config: &config::TrueflowConfig) -> Option<String> {
+    BASE_OVERRIDE
+        .get()
+        .cloned()
+        .or_else(|| config.vcs.base_branch.clone())
+}
+
+/// The base ref's name for labels.
+pub fn base_label() -> String {
+    configured_base().unwrap_or_else(|| "main".to_string())
+}
+
+#[derive(Clone)]
+pub struct RepoSnapshot {
+    pub system: VcsSystem,
+    pub repo_ref_revision: Option<String>,
+    repo: Option<gix::Repository>,
+    /// How committed blocks are split; `None` for the process's settings.
+    settings: Option<ScanSettings>,
+}
+
+#[derive(Debug, Clone)]
+pub struct DiffHunk {
+    pub file_path: String,
+    pub new_start: u32,
+    pub lines: Vec<String>,
+}
+
+pub struct GitConfig {
+    pub email: String,
+    pub signing_key: Option<String>,
+    /// `gpg.format`: `openpgp` (default) or `ssh`.
+    pub signing_format: Option<String>,
+    /// `gpg.ssh.allowedSignersFile`: who SSH signatures are trusted from.
+    pub allowed_signers: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct CommitInfo {
+    pub id: String,
+    /// The first line of the message.
+    pub summary: String,
+    /// The whole message, trimmed. Mercurial and Jujutsu give only the
+    /// first line.
+    pub message: String,
+}
+
+impl CommitInfo {
+    /// Abbreviated the way `git log --oneline` does.
+    pub fn short_id(&self) -> &str {
+        self.id.get(..7).unwrap_or(&self.id)
+    }
+}
+
+pub fn repo_from_workdir() -> Result<gix::Repository> {
+    repo_in(Path::new("."))
+}
+
+/// The git repository containing `start`.
+pub fn repo_in(start: &Path) -> Result<gix::Repository> {
+    Ok(gix::discover(start)?)
+}
+
+pub fn snapshot_from_workdir() -> RepoSnapshot {
+    snapshot_in(Path::new("."))
+}
+
+pub fn snapshot_in(start: &Path) -> RepoSnapshot {
+    snapshot_from(start, None)
+}
+
+/// [`snapshot_in`], splitting committed blocks with `settings`.
+pub fn snapshot_with(start: &Path, settings: &ScanSettings) -> RepoSnapshot {
+    snapshot_from(start, Some(settings.clone()))
+}
+
+fn snapshot_from(start: &Path, settings: Option<ScanSettings>) -> RepoSnapshot {
+    let repo = repo_in(start).ok();
+    let provider = vcs_provider::detect_in(start);
+    RepoSnapshot {
+        system: provider
+            .as_ref()
+            .map_or(VcsSystem::Git, |provider| provider.system()),
+        repo_ref_revision: provider.and_then(|provider| provider.head_revision()),
+        repo,
+        settings,
+    }
+}
+
+pub fn git_config_from_workdir() -> Result<GitConfig> {
+    git_config_in(Path::new("."))
+}
+
+/// `user.email` and the signing settings of the repository containing `start`.
+pub fn git_config_in(start: &Path) -> Result<GitConfig> {
+    let repo = repo_in(start)?;
+    let config = repo.config_snapshot();
+    let email = config
+        .string("user.email")
+        .map(|value| value.to_string())
+        .unwrap_or_else(|| "unknown@localhost".to_string());
+    let signing_key = config
+        .string("user.signingkey")
+        .map(|value| value.to_string());
+    let signing_format = config.string("gpg.format").map(|value| value.to_string());
+    let allowed_signers = config
+        .string("gpg.ssh.allowedSignersFile")
+        .map(|value| value.to_string());
+    Ok(GitConfig {
+        email,
+        signing_key,
+        signing_format,
+        allowed_signers,
+    })
+}
+
+/// Where git looks for hooks: `core.hooksPath` (relative to the worktree)
+/// or `.git/hooks`.
+pub fn hooks_dir(repo: &gix::Repository) -> PathBuf {
+    let configured = repo
+        .config_snapshot()
+        .string("core.hooksPath")
+        .map(|value| PathBuf::from(value.to_str_lossy().into_owned()));
+    match (configured, repo.workdir()) {
+        (Some(path), _) if path.is_absolute() => path,
+        (Some(path), Some(workdir)) => workdir.join(path),
+        _ => repo.git_dir().join("hooks"),
+    }
+}
+
+pub fn dirty_files(repo: &gix::Repository) -> Result<HashSet<String>> {
+    let mut dirty = HashSet::new();
+    let iter = repo
+        .status(gix::progress::Discard)?
+        .untracked_files(UntrackedFiles::Files)
+        .into_index_worktree_iter(Vec::new())?;
+    for entry in iter {
+        let item = entry?;
+        let summary = item.summary();
+        if summary.is_none() {
+            continue;
+        }
+        dirty.insert(item.rela_path().to_str_lossy().to_string());
+    }
+    Ok(dirty)
+}
+
+pub fn block_state_for_path(
+    repo_snapshot: &RepoSnapshot,
+    path_hint: Option<&str>,
+    fingerprint: &str,
+) -> BlockStateResult {
+    let Some(repo) = &repo_snapshot.repo else {
+        return BlockStateResult::Unknown;
+    };
+    let Some(path) = path_hint else {
+        return BlockStateResult::Unknown;
+    };
+    let normalized = path.trim_start_matches("./");
+
+    let settings = repo_snapshot
+        .settings
+        .clone()
+        .unwrap_or_else(ScanSettings::for_process);
+    if let Ok(blocks) = head_blocks_for_path(repo, normalized, &settings)
+        && blocks.iter().any(|block| block.hash == fingerprint)
+    {
+        return BlockStateResult::Committed;
+    }
+
+    if let Ok(dirty) = dirty_files(repo)
+        && (dirty.contains(normalized) || dirty.contains(path))
+    {
+        return BlockStateResult::Uncommitted;
+    }
+
+    BlockStateResult::Unknown
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockStateResult {
+    Committed,
+    Uncommitted,
+    Unknown,
+}
+
+pub fn head_blocks_for_path(
+    repo: &gix::Repository,
+    path: &str,
+    settings: &ScanSettings,
+) -> Result<Vec<Block>> {
+    let head_tree = repo.head_tree()?;
+    let entry = head_tree
+        .lookup_entry_by_path(Path::new(path))?
+        .context("path not found in head tree")?;
+    if entry.mode().kind() == EntryKind::Tree {
+        return Ok(Vec::new());
+    }
+    blob_blocks(repo, path, entry.object_id(), settings)
+}
+
+/// Blocks of `path` as committed at `revision`; empty when it did not exist.
+pub fn blocks_at_revision(
+    repo: &gix::Repository,
+    revision: &str,
+    path: &str,
+    settings: &ScanSettings,
+) -> Result<Vec<Block>> {
+    let tree = resolve_tree(repo, revision)?;
+    let Some(entry) = tree.lookup_entry_by_path(Path::new(path))? else {
+        return Ok(Vec::new());
+    };
+    if entry.mode().kind() == EntryKind::Tree {
+        return Ok(Vec::new());
+    }
+    blob_blocks(repo, path, entry.object_id(), settings)
+}
+
+/// Paths whose index entry differs from HEAD, with the staged blob (`None`
+/// when the path is staged for deletion).
+pub fn staged_changes(repo: &gix::Repository) -> Result<HashMap<String, Option<gix::ObjectId>>> {
+    let mut staged = HashMap::new();
+    let iter = repo
+        .status(gix::progress::Discard)?
+        .untracked_files(UntrackedFiles::None)
+        .into_iter(Vec::new())?;
+    for item in iter {
+        let gix::status::Item::TreeIndex(change) = item? else {
+            continue;
+        };
+        let id = match &change {
+            gix::diff::index::ChangeRef::Deletion { .. } => None,
+            _ => Some(change.id().to_owned()),
+        };
+        staged.insert(change.location().to_str_lossy().into_owned(), id);
+    }
+    Ok(staged)
+}
+
+/// Submodules registered in the index, by repo-relative path, with the commit
+/// checked out there (or the recorded one when it isn't checked out).
+pub fn submodule_pointers(repo: &gix::Repository) -> Result<Vec<(String, String)>> {
+    let Some(workdir) = repo.workdir() else {
+        return Ok(Vec::new());
+    };
+    let index = repo.index_or_empty()?;
+    let mut pointers = Vec::new();
+    for entry in index.entries() {
+        if entry.mode != gix::index::entry::Mode::COMMIT {
+            continue;
+        }
+        let path = entry.path(&index).to_str_lossy().into_owned();
+        let checked_out = gix::open(workdir.join(&path))
+            .ok()
+            .and_then(|submodule| submodule.head_id().ok().map(|id| id.detach()));
+        pointers.push((path, checked_out.unwrap_or(entry.id).to_string()));
+    }
+    Ok(pointers)
+}
+
+/// Blocks of the blob `id`, split as the file at `path` would be.
+pub fn blob_blocks(
+    repo: &gix::Repository,
+    path: &str,
+    id: gix::ObjectId,
+    settings: &ScanSettings,
+) -> Result<Vec<Block>> {
+    let blob = repo.find_object(id)?.try_into_blob()?;
+    content_blocks(path, &blob.data, settings)
+}
+
+/// Blocks of a committed version of `path`, split like a scan would.
+pub fn content_blocks(path: &str, data: &[u8], settings: &ScanSettings) -> Result<Vec<Block>> {
+    if analysis::is_binary(data) {
+        return Ok(vec![scanner::binary_block(data)]);
+    }
+    let content = std::str::from_utf8(data).context("utf8")?;
+    let extension = Path::new(path).extension().and_then(|ext| ext.to_str());
+    let language = extension
+        .and_then(Language::from_extension)
+        .unwrap_or(Language::Unknown);
+    Ok(split_blocks(content, language, settings))
+}
+
+/// `path` (repo-relative) as of the merge base with the base branch, or HEAD
+/// when there is none. `None` when it did not exist there.
+pub fn base_file_contents(repo: &gix::Repository, path: &str) -> Result<Option<String>> {
+    let base_commit = match base_and_head_commits(repo) {
+        Ok((base_commit, _)) => base_commit,
+        Err(_) => repo.head_commit()?,
+    };
+    let Some(entry) = base_commit.tree()?.lookup_entry_by_path(Path::new(path))? else {
+        return Ok(None);
+    };
+    if entry.mode().kind() == EntryKind::Tree {
+        return Ok(None);
+    }
+    let blob = entry.object()?.try_into_blob()?;
+    Ok(Some(String::from_utf8_lossy(&blob.data).into_owned()))
+}
+
+/// The two sides of the diff `check` gates on.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum DiffBase {
+    /// The merge base with the base branch (main by default) against HEAD.
+    #[default]
+    MainToHead,
+    /// A commit against its first parent.
+    Revision(String),
+    /// Any two revisions; either side may name a tree, such as the staged
+    /// tree `git write-tree` prints.
+    Range { start: String, end: String },
+    /// HEAD (or the empty tree before the first commit) against the index.
+    Staged,
+}
+
+pub fn diff_hunks(base: &DiffBase) -> Result<Vec<DiffHunk>> {
+    let repo = repo_from_workdir()?;
+    let (start_tree, end_tree) = diff_base_trees(&repo, base)?;
+    diff_trees(&repo, &start_tree, &end_tree)
+}
+
+/// A file changed between the two sides of a diff, split into blocks on
+/// each side. A side is empty when the file didn't exist there.
+#[derive(Debug, Clone)]
+pub struct ChangedFileBlocks {
+    pub path: String,
+    pub old: Vec<Block>,
+    pub new: Vec<Block>,
+}
+
+pub fn changed_file_blocks(base: &DiffBase) -> Result<Vec<ChangedFileBlocks>> {
+    let repo = repo_from_workdir()?;
+    let (start_tree, end_tree) = diff_base_trees(&repo, base)?;
+    let changes = repo.diff_tree_to_tree(Some(&start_tree), Some(&end_tree), None)?;
+    let settings = ScanSettings::for_process();
+    let mut files = Vec::new();
+    for change in changes {
+        let change_ref = change.to_ref();
+        let location = change_ref.location();
+        if location.is_empty() || !is_blob_change(&change_ref) {
+            continue;
+        }
+        let path = location.to_str_lossy().into_owned();
+        let (_, id) = change_ref.entry_mode_and_id();
+        let (_, source_id) = change_ref.source_entry_mode_and_id();
+        let (old, new) = match change_ref {
+            gix::diff::tree_with_rewrites::ChangeRef::Addition { .. } => (
+                Vec::new(),
+                blob_blocks(&repo, &path, id.to_owned(), &settings)?,
+            ),
+            gix::diff::tree_with_rewrites::ChangeRef::Deletion { .. } => (
+                blob_blocks(&repo, &path, id.to_owned(), &settings)?,
+                Vec::new(),
+            ),
+            _ => (
+                blob_blocks(&repo, &path, source_id.to_owned(), &settings)?,
+                blob_blocks(&repo, &path, id.to_owned(), &settings)?,
+            ),
+        };
+        files.push(ChangedFileBlocks { path, old, new });
+    }
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(files)
+}
+
+fn diff_base_trees<'repo>(
+    repo: &'repo gix::Repository,
+    base: &DiffBase,
+) -> Result<(gix::Tree<'repo>, gix::Tree<'repo>)> {
+    match base {
+        DiffBase::MainToHead => base_and_head_trees(repo),
+        DiffBase::Revision(revision) => {
+            let commit = resolve_commit(repo, revision)?;
+            let parent_tree = match commit.parent_ids().next() {
+                Some(parent_id) => repo.find_commit(parent_id)?.tree()?,
+                None => repo.empty_tree(),
+            };
+            Ok((parent_tree, commit.tree()?))
+        }
+        DiffBase::Range { start, end } => {
+            Ok((resolve_tree(repo, start)?, resolve_tree(repo, end)?))
+        }
+        DiffBase::Staged => {
+            let head_tree = match repo.head_commit() {
+                Ok(commit) => commit.tree()?,
+                Err(_) => repo.empty_tree(),
+            };
+            Ok((head_tree, staged_tree(repo)?))
+        }
+    }
+}
+
+/// The index as a tree, written to the object database as `git write-tree`
+/// does.
+fn staged_tree(repo: &gix::Repository) -> Result<gix::Tree<'_>> {
+    let index = repo.index_or_empty()?;
+    let mut editor = repo.edit_tree(gix::ObjectId::empty_tree(repo.object_hash()))?;
+    for entry in index.entries() {
+        let path = entry.path(&index);
+        if entry.stage_raw() != 0 {
+            anyhow::bail!("{} has unresolved conflicts", path.to_str_lossy());
+        }
+        if entry
+            .flags
+            .contains(gix::index::entry::Flags::INTENT_TO_ADD)
+        {
+            continue;
+        }
+        let mode = entry
+            .mode
+            .to_tree_entry_mode()
+            .with_context(|| format!("Unsupported mode for {}", path.to_str_lossy()))?;
+        editor.upsert(path, mode.kind(), entry.id)?;
+    }
+    Ok(editor.write()?.object()?.into_tree())
+}
+
+fn resolve_commit<'repo>(
+    repo: &'repo gix::Repository,
+    revision: &str,
+) -> Result<gix::Commit<'repo>> {
+    repo.rev_parse_single(revision)
+        .with_context(|| format!("Unknown revision: {revision}"))?
+        .object()?
+        .peel_to_commit()
+        .with_context(|| format!("{revision} does not resolve to a commit"))
+}
+
+/// When `revision` was committed, as a unix timestamp.
+pub fn commit_time(revision: &str) -> Result<i64> {
+    let repo = repo_from_workdir()?;
+    Ok(resolve_commit(&repo, revision)?.time()?.seconds)
+}
+
+fn resolve_tree<'repo>(repo: &'repo gix::Repository, revision: &str) -> Result<gix::Tree<'repo>> {
+    repo.rev_parse_single(revision)
+        .with_context(|| format!("Unknown revision: {revision}"))?
+        .object()?
+        .peel_to_tree()
+        .with_context(|| format!("{revision} does not resolve to a tree"))
+}
+
+pub fn files_changed_main_to_head() -> Result<HashSet<String>> {
+    let repo = repo_from_workdir()?;
+    files_changed_main_to_head_in_repo(&repo)
+}
+
+pub fn files_changed_main_to_head_in_repo(repo: &gix::Repository) -> Result<HashSet<String>> {
+    files_changed_from_base(repo, configured_base())
+}
+
+/// Files changed between `base` (main, then master, when `None`) and HEAD.
+pub fn files_changed_from_base(
+    repo: &gix::Repository,
+    base: Option<String>,
+) -> Result<HashSet<String>> {
+    let (base_commit, head_commit) = base_and_head_commits_from(repo, base)?;
+    collect_changed_paths(repo, Some(&base_commit.tree()?), Some(&head_commit.tree()?))
+}
+
+pub fn recent_commits_in_repo(repo: &gix::Repository, limit: usize) -> Result<Vec<CommitInfo>> {
+    if limit == 0 {
+        return Ok(Vec::new());
+    }
+
+    let head_commit = match repo.head_commit() {
+        Ok(commit) => commit,
+        Err(_) => return Ok(Vec::new()),
+    };
+
+    let mut commits = Vec::new();
+    let mut current = head_commit;
+
+    loop {
+        commits.push(commit_info(&current));
+
+        if commits.len() >= limit {
+            break;
+        }
+
+        let Some(parent_id) = current.parent_ids().next() else {
+            break;
+        };
+        current = repo.find_commit(parent_id)?;
+    }
+
+    Ok(commits)
+}
+
+/// The commit `revision` names.
+pub fn commit_in_revision(repo: &gix::Repository, revision: &str) -> Result<CommitInfo> {
+    Ok(commit_info(&resolve_commit(repo, revision)?))
+}
+
+/// The commits `git log start..end` lists, oldest first.
+pub fn commits_in_range(repo: &gix::Repository, start: &str, end: &str) -> Result<Vec<CommitInfo>> {
+    let start = resolve_commit(repo, start)?.id;
+    let end = resolve_commit(repo, end)?.id;
+    let mut commits = Vec::new();
+    for info in repo.rev_walk([end]).with_hidden([start]).all()? {
+        commits.push(commit_info(&info?.object()?));
+    }
+    commits.reverse();
+    Ok(commits)
+}
+
+fn commit_info(commit: &gix::Commit<'_>) -> CommitInfo {
+    let summary = commit
+        .message()
+        .map(|message| message.summary().to_str_lossy().to_string())
+        .unwrap_or_else(|_| "(no message)".to_string());
+    CommitInfo {
+        id: commit.id().detach().to_string(),
+        summary,
+        message: commit
+            .message_raw_sloppy()
+            .to_str_lossy()
+            .trim()
+            .to_string(),
+    }
+}
+
+/// Commits `last_changed_times` walks back from HEAD before giving up.
+const LAST_CHANGED_MAX_COMMITS: usize = 10_000;
+
+/// When each of `paths` (repo-relative) was last changed, as the commit time
+/// of the newest first-parent commit touching it. Paths with no such commit
+/// are left out.
+pub fn last_changed_times(
+    repo: &gix::Repository,
+    paths: &HashSet<String>,
+) -> Result<HashMap<String, i64>> {
+    let mut times = HashMap::new();
+    let Ok(mut current) = repo.head_commit() else {
+        return Ok(times);
+    };
+    for _ in 0..LAST_CHANGED_MAX_COMMITS {
+        if times.len() == paths.len() {
+            break;
+        }
+        let parent = current
+            .parent_ids()
+            .next()
+            .map(|id| repo.find_commit(id))
+            .transpose()?;
+        let parent_tree = match &parent {
+            Some(parent) => parent.tree()?,
+            None => repo.empty_tree(),
+        };
+        let seconds = current.time()?.seconds;
+        for path in collect_changed_paths(repo, Some(&parent_tree), Some(&current.tree()?))? {
+            if paths.contains(&path) {
+                times.entry(path).or_insert(seconds);
+            }
+        }
+        match parent {
+            Some(parent) => current = parent,
+            None => break,
+        }
+    }
+    Ok(times)
+}
+
+pub fn files_changed_in_revision(
+    repo: &gix::Repository,
+    revision: &str,
+) -> Result<HashSet<String>> {
+    let object = repo.rev_parse_single(revision)?;
+    let commit = object
+        .object()?
+        .peel_to_commit()
+        .context("revision must resolve to a commit")?;
+    let commit_tree = commit.tree()?;
+    let parent_tree = if let Some(parent_id) = commit.parent_ids().next() {
+        repo.find_commit(parent_id)?.tree()?
+    } else {
+        repo.empty_tree()
+    };
+    collect_changed_paths(repo, Some(&parent_tree), Some(&commit_tree))
+}
+
+pub fn files_changed_in_range(
+    repo: &gix::Repository,
+    start: &str,
+    end: &str,
+) -> Result<HashSet<String>> {
+    let start_obj = repo.rev_parse_single(start)?;
+    let end_obj = repo.rev_parse_single(end)?;
+    let start_commit = start_obj
+        .object()?
+        .peel_to_commit()
+        .context("start revision must resolve to a commit")?;
+    let end_commit = end_obj
+        .object()?
+        .peel_to_commit()
+        .context("end revision must resolve to a commit")?;
+    let start_tree = start_commit.tree()?;
+    let end_tree = end_commit.tree()?;
+    collect_changed_paths(repo, Some(&start_tree), Some(&end_tree))
+}
+
+fn diff_trees(
+    repo: &gix::Repository,
+    base_tree: &gix::Tree<'_>,
+    head_tree: &gix::Tree<'_>,
+) -> Result<Vec<DiffHunk>> {
+    let mut hunks = Vec::new();
+    let mut diff_cache = repo.diff_resource_cache_for_tree_diff()?;
+    let changes = repo.diff_tree_to_tree(Some(base_tree), Some(head_tree), None)?;
+
+    for change in changes {
+        let change_ref = change.to_ref();
+        let location = change_ref.location();
+        if location.is_empty() {
+            continue;
+        }
+        if !is_blob_change(&change_ref) {
+            continue;
+        }
+
+        diff_cache.set_resource_by_change(change_ref, &repo.objects)?;
+        let prep = diff_cache.prepare_diff()?;
+        match prep.operation {
+            gix::diff::blob::platform::prepare_diff::Operation::SourceOrDestinationIsBinary => {
+                let path = location.to_str_lossy();
+                hunks.push(binary_hunk(repo, &change_ref, path.as_ref())?);
+            }
+            gix::diff::blob::platform::prepare_diff::Operation::ExternalCommand { .. } => {
+                diff_cache.clear_resource_cache_keep_allocation();
+                continue;
+            }
+            gix::diff::blob::platform::prepare_diff::Operation::InternalDiff { algorithm } => {
+                let input = prep.interned_input();
+                let sink = gix::diff::blob::UnifiedDiff::new(
+                    &input,
+                    gix::diff::blob::unified_diff::ConsumeBinaryHunk::new(String::new(), "\n"),
+                    gix::diff::blob::unified_diff::ContextSize::symmetrical(3),
+                );
+                let unified = gix::diff::blob::diff(algorithm, &input, sink)?;
+                let path = location.to_str_lossy();
+                collect_hunks(&mut hunks, path.as_ref(), &unified)?;
+            }
+        }
+
+        diff_cache.clear_resource_cache_keep_allocation();
+    }
+
+    Ok(hunks)
+}
+
+/// Binary changes become one hunk over the file's metadata block, so they are
+/// reviewed like text: approving the block (or the hunk) covers the change.
+fn binary_hunk(
+    repo: &gix::Repository,
+    change: &gix::diff::tree_with_rewrites::ChangeRef<'_>,
+    path: &str,
+) -> Result<DiffHunk> {
+    let (_, id) = change.entry_mode_and_id();
+    let object = repo.find_object(id)?;
+    let block = scanner::binary_block(&object.data);
+    let sign = if matches!(
+        change,
+        gix::diff::tree_with_rewrites::ChangeRef::Deletion { .. }
+    ) {
+        '-'
+    } else {
+        '+'
+    };
+    Ok(DiffHunk {
+        file_path: path.to_string(),
+        new_start: 1,
+        lines: block
+            .content
+            .lines()
+            .map(|line| format!("{sign}{line}\n"))
+            .collect(),
+    })
+}
+
+/// Ids of the merge base with the base branch and of HEAD: the two sides
+/// of the diff `DiffBase::MainToHead` names.
+pub fn merge_base_and_head() -> Result<(String, String)> {
+    let repo = repo_from_workdir()?;
+    let (base_commit, head_commit) = base_and_head_commits(&repo)?;
+    Ok((base_commit.id.to_string(), head_commit.id.to_string()))
+}
+
+/// [`merge_base_and_head`] for the repository containing `start`, against
+/// the base branch `config` names.
+pub fn merge_base_and_head_in(
+    start: &Path,
+    config: &config::TrueflowConfig,
+) -> Result<(String, String)> {
+    let repo = repo_in(start)?;
+    let (base_commit, head_commit) = base_and_head_commits_from(&repo, base_from(config))?;
+    Ok((base_commit.id.to_string(), head_commit.id.to_string()))
+}
+
+fn base_and_head_trees<'repo>(
+    repo: &'repo gix::Repository,
+) -> Result<(gix::Tree<'repo>, gix::Tree<'repo>)> {
+    let (base_commit, head_commit) = base_and_head_commits(repo)?;
+    Ok((base_commit.tree()?, head_commit.tree()?))
+}
+
+fn base_and_head_commits(repo: &gix::Repository) -> Result<(gix::Commit<'_>, gix::Commit<'_>)> {
+    base_and_head_commits_from(repo, configured_base())
+}
+
+fn base_and_head_commits_from(
+    repo: &gix::Repository,
+    base: Option<String>,
+) -> Result<(gix::Commit<'_>, gix::Commit<'_>)> {
+    let head_commit = repo.head_commit()?;
+
+    let main_commit = match base {
+        Some(base) => resolve_commit(repo, &base)?,
+        None => repo
+            .find_reference("main")
+            .or_else(|_| repo.find_reference("master"))
+            .context("Could not find main or master branch; set --base or [vcs] base_branch")?
+            .peel_to_commit()?,
+    };
+    let main_id = main_commit.id().detach();
+
+    let base_commit = match repo.merge_base(head_commit.id().detach(), main_id) {
+        Ok(base_id) => repo.find_commit(base_id.detach())?,
+        Err(_) => main_commit,
+    };
+
+    Ok((base_commit, head_commit))
+}
+
+fn collect_changed_paths(
+    repo: &gix::Repository,
+    base_tree: Option<&gix::Tree<'_>>,
+    head_tree: Option<&gix::Tree<'_>>,
+) -> Result<HashSet<String>> {
+    let changes = repo.diff_tree_to_tree(base_tree, head_tree, None)?;
+    let mut paths = HashSet::new();
+    for change in changes {
+        let change_ref = change.to_ref();
+        let location = change_ref.location();
+        if location.is_empty() {
+            continue;
+        }
+        if !is_blob_change(&change_ref) {
+            continue;
+        }
+        paths.insert(location.to_str_lossy().to_string());
+    }
+    Ok(paths)
+}
+
+fn is_blob_change(change: &gix::diff::tree_with_rewrites::ChangeRef<'_>) -> bool {
+    let is_blob = |mode: EntryMode| {
+        matches!(
+            mode.kind(),
+            EntryKind::Blob | EntryKind::BlobExecutable | EntryKind::Link
+        )
+    };
+    let (mode, _) = change.entry_mode_and_id();
+    let (source_mode, _) = change.source_entry_mode_and_id();
+    is_blob(mode) || is_blob(source_mode)
+}
+
+fn collect_hunks(hunks: &mut Vec<DiffHunk>, path: &str, unified: &str) -> Result<()> {
+    let mut current: Option<DiffHunk> = None;
+    for line in unified.lines() {
+        if let Some(header) = parse_hunk_header(line) {
+            if let Some(hunk) = current.take() {
+                hunks.push(hunk);
+            }
+            current = Some(DiffHunk {
+                file_path: path.to_string(),
+                new_start: header.after_start,
+                lines: Vec::new(),
+            });
+            continue;
+        }
+        if let Some(hunk) = &mut current
+            && (line.starts_with('+') || line.starts_with('-') || line.starts_with(' '))
+        {
+            hunk.lines.push(format!("{}\n", line));
+        }
+    }
+    if let Some(hunk) = current {
+        hunks.push(hunk);
+    }
+    Ok(())
+}
+
+fn parse_hunk_header(line: &str) -> Option<HunkHeader> {
+    let line = line.strip_prefix("@@ -")?;
+    let (before, rest) = line.split_once(' ')?;
+    let rest = rest.strip_prefix('+')?;
+    let (after, _) = rest.split_once(" @@")?;
+    Some(HunkHeader {
+        before_start: parse_hunk_start(before)?,
+        after_start: parse_hunk_start(after)?,
+    })
+}
+
+fn parse_hunk_start(range: &str) -> Option<u32> {
+    let (start, _) = range.split_once(',').unwrap_or((range, ""));
+    start.parse().ok()
+}
+
+struct HunkHeader {
+    #[allow(dead_code)]
+    before_start: u32,
+    after_start: u32,
+}
+
+fn split_blocks(content: &str, language: Language, settings: &ScanSettings) -> Vec<Block> {
+    if language != Language::Unknown
+        && let Ok(blocks) = block_splitter::split(content, language.clone())
+        && !blocks.is_empty()
+    {
+        let mut blocks = crate::optimizer::optimize_with(blocks, &settings.optimizer);
+        hashing::apply_normalization_with(&mut blocks, &language, settings.normalization);
+        return blocks;
+    }
+
+    let mut blocks = scanner::fallback_split_blocks(content, scanner::FallbackMode::Text);
+    hashing::apply_normalization_with(&mut blocks, &Language::Unknown, settings.normalization);
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_hunk_header_extracts_positions() {
+        let header = "@@ -10,2 +12,4 @@";
+        let parsed = parse_hunk_header(header).expect("header");
+        assert_eq!(parsed.before_start, 10);
+        assert_eq!(parsed.after_start, 12);
+    }
+
+    #[test]
+    fn collect_hunks_groups_lines_by_header() {
+        let diff = "@@ -1,1 +1,2 @@\n-foo\n+foo\n+bar\n@@ -5,1 +6,1 @@\n-baz\n+qux\n";
+        let mut hunks = Vec::new();
+        collect_hunks(&mut hunks, "src/main.rs", diff).expect("collect");
+
+        assert_eq!(hunks.len(), 2);
+        assert_eq!(hunks[0].file_path, "src/main.rs");
+        assert_eq!(hunks[0].new_start, 1);
+        assert_eq!(hunks[0].lines, vec!["-foo\n", "+foo\n", "+bar\n"]);
+        assert_eq!(hunks[1].new_start, 6);
+        assert_eq!(hunks[1].lines, vec!["-baz\n", "+qux\n"]);
+    }
+}
@@ -0,0 +1,462 @@
+use crate::block::Block;
+use crate::scanner::ScanSettings;
+use crate::store::VcsSystem;
+use crate::vcs::{self, CommitInfo};
+use anyhow::{Context, Result, bail};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// The version-control operations review scoping needs, so Mercurial and
+/// Jujutsu checkouts work like git ones. Paths are relative to `root`.
+pub trait VcsProvider {
+    fn system(&self) -> VcsSystem;
+    fn root(&self) -> &Path;
+    /// Files changed in the working copy, untracked ones included.
+    fn dirty_files(&self) -> Result<HashSet<String>>;
+    fn files_changed_in_revision(&self, revision: &str) -> Result<HashSet<String>>;
+    fn files_changed_in_range(&self, start: &str, end: &str) -> Result<HashSet<String>>;
+    /// Blocks of `path` as last committed.
+    fn head_blocks_for_path(&self, path: &str) -> Result<Vec<Block>>;
+    /// Blocks of `path` as committed at `revision`.
+    fn blocks_at_revision(&self, revision: &str, path: &str) -> Result<Vec<Block>>;
+    /// The newest `limit` commits leading to the working copy, newest first.
+    fn recent_commits(&self, limit: usize) -> Result<Vec<CommitInfo>>;
+    /// The commit `revision` names.
+    fn commit_in_revision(&self, revision: &str) -> Result<CommitInfo>;
+    /// Commits reachable from `end` but not from `start`, oldest first.
+    fn commits_in_range(&self, start: &str, end: &str) -> Result<Vec<CommitInfo>>;
+    /// Id of the commit the working copy is based on.
+    fn head_revision(&self) -> Option<String>;
+}
+
+/// The repository containing the working directory. A `.jj` directory wins
+/// over `.git`, since colocated Jujutsu repositories have both.
+pub fn detect() -> Option<Box<dyn VcsProvider>> {
+    detect_in(Path::new("."))
+}
+
+/// The repository containing `start`.
+pub fn detect_in(start: &Path) -> Option<Box<dyn VcsProvider>> {
+    detect_from(start, None)
+}
+
+/// [`detect_in`], splitting committed blocks with `settings`.
+pub fn detect_with(start: &Path, settings: &ScanSettings) -> Option<Box<dyn VcsProvider>> {
+    detect_from(start, Some(settings.clone()))
+}
+
+fn detect_from(start: &Path, settings: Option<ScanSettings>) -> Option<Box<dyn VcsProvider>> {
+    let start = std::path::absolute(start).ok()?;
+    for dir in start.ancestors() {
+        if dir.join(".jj").is_dir() {
+            return Some(Box::new(Jujutsu::new(dir, settings)));
+        }
+        if dir.join(".hg").is_dir() {
+            return Some(Box::new(Mercurial::new(dir, settings)));
+        }
+        if dir.join(".git").exists() {
+            return Git::discover(&start, settings)
+                .map(|git| Box::new(git) as Box<dyn VcsProvider>);
+        }
+    }
+    None
+}
+
+/// `settings`, else the process's.
+fn split_settings(settings: &Option<ScanSettings>) -> ScanSettings {
+    settings.clone().unwrap_or_else(ScanSettings::for_process)
+}
+
+/// Root of the repository containing the working directory, of any system.
+pub fn repo_root() -> Option<PathBuf> {
+    detect().map(|provider| provider.root().to_path_buf())
+}
+
+/// Root of the repository containing `start`, of any system.
+pub fn repo_root_in(start: &Path) -> Option<PathBuf> {
+    detect_in(start).map(|provider| provider.root().to_path_buf())
+}
+
+pub struct Git {
+    repo: gix::Repository,
+    root: PathBuf,
+    settings: Option<ScanSettings>,
+}
+
+impl Git {
+    fn discover(start: &Path, settings: Option<ScanSettings>) -> Option<Self> {
+        let repo = vcs::repo_in(start).ok()?;
+        let root = repo.workdir()?.to_path_buf();
+        Some(Self {
+            repo,
+            root,
+            settings,
+        })
+    }
+}
+
+impl VcsProvider for Git {
+    fn system(&self) -> VcsSystem {
+        VcsSystem::Git
+    }
+
+    fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn dirty_files(&self) -> Result<HashSet<String>> {
+        vcs::dirty_files(&self.repo)
+    }
+
+    fn files_changed_in_revision(&self, revision: &str) -> Result<HashSet<String>> {
+        vcs::files_changed_in_revision(&self.repo, revision)
+    }
+
+    fn files_changed_in_range(&self, start: &str, end: &str) -> Result<HashSet<String>> {
+        vcs::files_changed_in_range(&self.repo, start, end)
+    }
+
+    fn head_blocks_for_path(&self, path: &str) -> Result<Vec<Block>> {
+        vcs::head_blocks_for_path(&self.repo, path, &split_settings(&self.settings))
+    }
+
+    fn blocks_at_revision(&self, revision: &str, path: &str) -> Result<Vec<Block>> {
+        vcs::blocks_at_revision(&self.repo, revision, path, &split_settings(&self.settings))
+    }
+
+    fn recent_commits(&self, limit: usize) -> Result<Vec<CommitInfo>> {
+        vcs::recent_commits_in_repo(&self.repo, limit)
+    }
+
+    fn commit_in_revision(&self, revision: &str) -> Result<CommitInfo> {
+        vcs::commit_in_revision(&self.repo, revision)
+    }
+
+    fn commits_in_range(&self, start: &str, end: &str) -> Result<Vec<CommitInfo>> {
+        vcs::commits_in_range(&self.repo, start, end)
+    }
+
+    fn head_revision(&self) -> Option<String> {
+        self.repo.head_id().ok().map(|id| id.detach().to_string())
+    }
+}
+
+/// Mercurial, through the `hg` command line (`TRUEFLOW_HG` overrides it).
+pub struct Mercurial {
+    root: PathBuf,
+    settings: Option<ScanSettings>,
+}
+
+impl Mercurial {
+    fn new(root: &Path, settings: Option<ScanSettings>) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            settings,
+        }
+    }
+
+    fn run(&self, args: &[&str]) -> Result<Vec<u8>> {
+        run_tool(
+            &tool("TRUEFLOW_HG", "hg"),
+            &self.root,
+            args,
+            &[("HGPLAIN", "1")],
+        )
+    }
+
+    fn status(&self, args: &[&str]) -> Result<HashSet<String>> {
+        let mut full = vec!["status", "--no-status"];
+        full.extend(args);
+        Ok(parse_paths(&self.run(&full)?))
+    }
+}
+
+/// `<node>\t<summary>` lines, as `parse_commits` reads them.
+const HG_LOG_TEMPLATE: &str = "{node}\\t{desc|firstline}\\n";
+
+impl VcsProvider for Mercurial {
+    fn system(&self) -> VcsSystem {
+        VcsSystem::Mercurial
+    }
+
+    fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn dirty_files(&self) -> Result<HashSet<String>> {
+        self.status(&["--modified", "--added", "--unknown"])
+    }
+
+    fn files_changed_in_revision(&self, revision: &str) -> Result<HashSet<String>> {
+        self.status(&["--change", revision])
+    }
+
+    fn files_changed_in_range(&self, start: &str, end: &str) -> Result<HashSet<String>> {
+        self.status(&["--rev", start, "--rev", end])
+    }
+
+    fn head_blocks_for_path(&self, path: &str) -> Result<Vec<Block>> {
+        self.blocks_at_revision(".", path)
+    }
+
+    fn blocks_at_revision(&self, revision: &str, path: &str) -> Result<Vec<Block>> {
+        vcs::content_blocks(
+            path,
+            &self.run(&["cat", "--rev", revision, path])?,
+            &split_settings(&self.settings),
+        )
+    }
+
+    fn recent_commits(&self, limit: usize) -> Result<Vec<CommitInfo>> {
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+        let limit = limit.to_string();
+        let output = self.run(&[
+            "log",
+            "--rev",
+            "reverse(::.)",
+            "--limit",
+            &limit,
+            "--template",
+            HG_LOG_TEMPLATE,
+        ])?;
+        Ok(parse_commits(&output))
+    }
+
+    fn commit_in_revision(&self, revision: &str) -> Result<CommitInfo> {
+        let output = self.run(&["log", "--rev", revision, "--template", HG_LOG_TEMPLATE])?;
+        parse_commits(&output)
+            .into_iter()
+            .next()
+            .with_context(|| format!("Unknown revision: {revision}"))
+    }
+
+    fn commits_in_range(&self, start: &str, end: &str) -> Result<Vec<CommitInfo>> {
+        let revset = format!("only({end}, {start})");
+        // `only()` is a set, which `hg log` lists in revision order.
+        let output = self.run(&["log", "--rev", &revset, "--template", HG_LOG_TEMPLATE])?;
+        Ok(parse_commits(&output))
+    }
+
+    fn head_revision(&self) -> Option<String> {
+        let output = self
+            .run(&["log", "--rev", ".", "--template", "{node}"])
+            .ok()?;
+        let node = String::from_utf8_lossy(&output).trim().to_string();
+        // An empty repository's working copy sits on the null revision.
+        (!node.is_empty() && node.chars().any(|ch| ch != '0')).then_some(node)
+    }
+}
+
+/// Jujutsu, through the `jj` command line (`TRUEFLOW_JJ` overrides it). The
+/// working copy is itself a commit (`@`), so "committed" means its parent.
+pub struct Jujutsu {
+    root: PathBuf,
+    settings: Option<ScanSettings>,
+}
+
+impl Jujutsu {
+    fn new(root: &Path, settings: Option<ScanSettings>) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            settings,
+        }
+    }
+
+    fn run(&self, args: &[&str]) -> Result<Vec<u8>> {
+        let mut full = vec!["--no-pager", "--color", "never"];
+        full.extend(args);
+        run_tool(&tool("TRUEFLOW_JJ", "jj"), &self.root, &full, &[])
+    }
+
+    fn changed(&self, args: &[&str]) -> Result<HashSet<String>> {
+        let mut full = vec!["diff", "--name-only"];
+        full.extend(args);
+        Ok(parse_paths(&self.run(&full)?))
+    }
+}
+
+/// `<commit id>\t<summary>` lines, as `parse_commits` reads them.
+const JJ_LOG_TEMPLATE: &str = r#"commit_id ++ "\t" ++ description.first_line() ++ "\n""#;
+
+impl VcsProvider for Jujutsu {
+    fn system(&self) -> VcsSystem {
+        VcsSystem::Jujutsu
+    }
+
+    fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn dirty_files(&self) -> Result<HashSet<String>> {
+        self.changed(&["--revisions", "@"])
+    }
+
+    fn files_changed_in_revision(&self, revision: &str) -> Result<HashSet<String>> {
+        self.changed(&["--revisions", revision])
+    }
+
+    fn files_changed_in_range(&self, start: &str, end: &str) -> Result<HashSet<String>> {
+        self.changed(&["--from", start, "--to", end])
+    }
+
+    fn head_blocks_for_path(&self, path: &str) -> Result<Vec<Block>> {
+        self.blocks_at_revision("@-", path)
+    }
+
+    fn blocks_at_revision(&self, revision: &str, path: &str) -> Result<Vec<Block>> {
+        vcs::content_blocks(
+            path,
+            &self.run(&["file", "show", "--revision", revision, path])?,
+            &split_settings(&self.settings),
+        )
+    }
+
+    fn recent_commits(&self, limit: usize) -> Result<Vec<CommitInfo>> {
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+        let limit = limit.to_string();
+        let output = self.run(&[
+            "log",
+            "--no-graph",
+            "--revisions",
+            "::@- ~ root()",
+            "--limit",
+            &limit,
+            "--template",
+            JJ_LOG_TEMPLATE,
+        ])?;
+        Ok(parse_commits(&output))
+    }
+
+    fn commit_in_revision(&self, revision: &str) -> Result<CommitInfo> {
+        let output = self.run(&[
+            "log",
+            "--no-graph",
+            "--revisions",
+            revision,
+            "--template",
+            JJ_LOG_TEMPLATE,
+        ])?;
+        parse_commits(&output)
+            .into_iter()
+            .next()
+            .with_context(|| format!("Unknown revision: {revision}"))
+    }
+
+    fn commits_in_range(&self, start: &str, end: &str) -> Result<Vec<CommitInfo>> {
+        let revset = format!("{start}..{end}");
+        let output = self.run(&[
+            "log",
+            "--no-graph",
+            "--reversed",
+            "--revisions",
+            &revset,
+            "--template",
+            JJ_LOG_TEMPLATE,
+        ])?;
+        Ok(parse_commits(&output))
+    }
+
+    fn head_revision(&self) -> Option<String> {
+        let output = self
+            .run(&[
+                "log",
+                "--no-graph",
+                "--revisions",
+                "@-",
+                "--template",
+                "commit_id",
+            ])
+            .ok()?;
+        let id = String::from_utf8_lossy(&output).trim().to_string();
+        (!id.is_empty() && id.chars().any(|ch| ch != '0')).then_some(id)
+    }
+}
+
+fn tool(env: &str, default: &str) -> String {
+    std::env::var(env)
+        .ok()
+        .filter(|program| !program.trim().is_empty())
+        .unwrap_or_else(|| default.to_string())
+}
+
+fn run_tool(program: &str, root: &Path, args: &[&str], envs: &[(&str, &str)]) -> Result<Vec<u8>> {
+    let output = Command::new(program)
+        .args(args)
+        .current_dir(root)
+        .envs(envs.iter().copied())
+        .output()
+        .with_context(|| format!("Failed to run {program}"))?;
+    if !output.status.success() {
+        bail!(
+            "{} {} failed: {}",
+            program,
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(output.stdout)
+}
+
+/// One repo-relative path per line, with `/` separators.
+fn parse_paths(output: &[u8]) -> HashSet<String> {
+    String::from_utf8_lossy(output)
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| line.replace('\\', "/"))
+        .collect()
+}
+
+/// `<id>\t<summary>` lines.
+fn parse_commits(output: &[u8]) -> Vec<CommitInfo> {
+    String::from_utf8_lossy(output)
+        .lines()
+        .filter_map(|line| {
+            let (id, summary) = line.split_once('\t').unwrap_or((line, ""));
+            let id = id.trim();
+            (!id.is_empty()).then(|| CommitInfo {
+                id: id.to_string(),
+                summary: summary.trim().to_string(),
+                message: summary.trim().to_string(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commit_lines_split_on_tabs() {
+        let commits = parse_commits(b"abc123\tFix the parser\n\ndef456\t\n");
+        assert_eq!(
+            commits,
+            [
+                CommitInfo {
+                    id: "abc123".to_string(),
+                    summary: "Fix the parser".to_string(),
+                    message: "Fix the parser".to_string(),
+                },
+                CommitInfo {
+                    id: "def456".to_string(),
+                    summary: String::new(),
+                    message: String::new(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn paths_use_forward_slashes() {
+        let paths = parse_paths(b"src\\lib.rs\nREADME.md\n\n");
+        assert!(paths.contains("src/lib.rs"));
+        assert!(paths.contains("README.md"));
+        assert_eq!(paths.len(), 2);
+    }
+}